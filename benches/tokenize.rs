@@ -0,0 +1,108 @@
+//! Encoding, decoding and special-token lookup speed of the test tokenizer.
+//!
+//! Run with `cargo bench`; compare runs with criterion's `--save-baseline` and
+//! `--baseline` flags to catch regressions.
+
+use std::hint::black_box;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use tekken::parallel::ParallelismConfig;
+use tekken::special_tokens::{SpecialTokenPolicy, SpecialTokens};
+use tekken::tekkenizer::Tekkenizer;
+
+fn tokenizer() -> Tekkenizer {
+    Tekkenizer::from_file(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/assets/tekken.json"
+    ))
+    .expect("Failed to load tokenizer from file")
+}
+
+/// Prose, code and non-Latin text, so every pretokenizer branch is exercised.
+fn corpus() -> Vec<String> {
+    let samples = [
+        "The quick brown fox jumps over the lazy dog. It wasn't the first time, and it won't be the last!\n",
+        "fn main() {\n    let values: Vec<u32> = (0..1024).map(|x| x * 2).collect();\n    println!(\"{values:?}\");\n}\n",
+        "Le tokenizer découpe le texte en morceaux avant la fusion BPE. 東京は日本の首都です。 Привет, мир! 🚀\n",
+    ];
+    samples.iter().map(|sample| sample.repeat(40)).collect()
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let tokenizer = tokenizer();
+    let corpus = corpus();
+    let mut group = c.benchmark_group("encode");
+    for (index, text) in corpus.iter().enumerate() {
+        group.throughput(Throughput::Bytes(text.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(index), text, |b, text| {
+            b.iter(|| tokenizer.encode(black_box(text), false, false).unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn bench_encode_batch(c: &mut Criterion) {
+    let tokenizer = tokenizer();
+    let batch: Vec<String> = corpus().into_iter().cycle().take(64).collect();
+    let bytes: usize = batch.iter().map(String::len).sum();
+    let mut group = c.benchmark_group("encode_batch");
+    group.throughput(Throughput::Bytes(bytes as u64));
+    for (name, parallelism) in [
+        ("sequential", ParallelismConfig::sequential()),
+        ("default", ParallelismConfig::default()),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                tokenizer
+                    .encode_batch(black_box(&batch), false, false, &parallelism)
+                    .unwrap()
+                    .results
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let tokenizer = tokenizer();
+    let tokens = tokenizer.encode(&corpus().concat(), false, false).unwrap();
+    let mut group = c.benchmark_group("decode");
+    group.throughput(Throughput::Elements(tokens.len() as u64));
+    group.bench_function("ignore_special", |b| {
+        b.iter(|| {
+            tokenizer
+                .decode(black_box(&tokens), SpecialTokenPolicy::Ignore)
+                .unwrap()
+        });
+    });
+    group.finish();
+}
+
+fn bench_special_tokens(c: &mut Criterion) {
+    let tokenizer = tokenizer();
+    let mut group = c.benchmark_group("special_tokens");
+    for token_str in ["[INST]", "<SPECIAL_500>"] {
+        group.bench_with_input(
+            BenchmarkId::new("get_control_token", token_str),
+            token_str,
+            |b, token_str| b.iter(|| tokenizer.get_control_token(black_box(token_str)).unwrap()),
+        );
+    }
+    group.bench_function("id_of", |b| {
+        b.iter(|| {
+            tokenizer
+                .id_of(black_box(SpecialTokens::BeginInst))
+                .unwrap()
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_encode,
+    bench_encode_batch,
+    bench_decode,
+    bench_special_tokens
+);
+criterion_main!(benches);
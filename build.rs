@@ -0,0 +1,87 @@
+//! Generates the C header for the `ffi` feature and the gRPC service for `grpc`.
+
+fn main() {
+    #[cfg(feature = "ffi")]
+    generate_header();
+    #[cfg(feature = "grpc")]
+    generate_grpc_service();
+}
+
+/// Generates `tekken.h` from the declarations in `src/ffi.rs` into `OUT_DIR`.
+///
+/// The build never writes into the source directory, which is read-only for
+/// registry dependencies and `cargo package`; `include/tekken.h` is the
+/// committed copy, and `tests/test_ffi.rs` checks that it is up to date. A
+/// failure is reported as a warning, since the Rust library does not need the
+/// header.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    match write_header() {
+        Ok(header) => println!(
+            "cargo:rustc-env=TEKKEN_GENERATED_HEADER={}",
+            header.display()
+        ),
+        Err(error) => println!("cargo:warning=Unable to generate tekken.h: {error}"),
+    }
+}
+
+#[cfg(feature = "ffi")]
+fn write_header() -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
+    let crate_dir = std::path::PathBuf::from(std::env::var("CARGO_MANIFEST_DIR")?);
+    let header = std::path::PathBuf::from(std::env::var("OUT_DIR")?).join("tekken.h");
+
+    let config = cbindgen::Config::from_file(crate_dir.join("cbindgen.toml"))?;
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir.join("src/ffi.rs"))
+        .generate()?
+        .write_to_file(&header);
+    Ok(header)
+}
+
+/// Generates the `Tokenizer` service of `proto/tekken.proto`.
+///
+/// Messages are declared by hand in `src/grpc.rs`, so the build does not need `protoc`.
+#[cfg(feature = "grpc")]
+fn generate_grpc_service() {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    println!("cargo:rerun-if-changed=build.rs");
+    let method = |name: &str, route: &str, input: &str, output: &str| {
+        Method::builder()
+            .name(name)
+            .route_name(route)
+            .input_type(format!("crate::grpc::proto::{input}"))
+            .output_type(format!("crate::grpc::proto::{output}"))
+            .codec_path("tonic_prost::ProstCodec")
+    };
+    let service = Service::builder()
+        .name("Tokenizer")
+        .package("tekken.v1")
+        .method(method("encode", "Encode", "EncodeRequest", "EncodeResponse").build())
+        .method(method("decode", "Decode", "DecodeRequest", "DecodeResponse").build())
+        .method(
+            method(
+                "encode_audio",
+                "EncodeAudio",
+                "EncodeAudioRequest",
+                "EncodeResponse",
+            )
+            .build(),
+        )
+        .method(
+            method(
+                "decode_stream",
+                "DecodeStream",
+                "DecodeStreamRequest",
+                "DecodeResponse",
+            )
+            .client_streaming()
+            .server_streaming()
+            .build(),
+        )
+        .build();
+    Builder::new().build_transport(false).compile(&[service]);
+}
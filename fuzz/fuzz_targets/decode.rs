@@ -0,0 +1,49 @@
+//! Decoding arbitrary token IDs must never panic, whatever the policies.
+
+#![no_main]
+
+use std::sync::LazyLock;
+
+use libfuzzer_sys::fuzz_target;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::{InvalidTokenPolicy, Tekkenizer};
+
+static TOKENIZER: LazyLock<Tekkenizer> = LazyLock::new(|| {
+    Tekkenizer::from_file(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/assets/tekken.json"
+    ))
+    .expect("Failed to load tokenizer from file")
+});
+
+fuzz_target!(|data: &[u8]| {
+    let tokenizer = &*TOKENIZER;
+    let tokens: Vec<u32> = data
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+
+    for special_token_policy in [
+        SpecialTokenPolicy::Ignore,
+        SpecialTokenPolicy::Keep,
+        SpecialTokenPolicy::Raise,
+    ] {
+        let strict = tokenizer.decode(&tokens, special_token_policy);
+        let _ = tokenizer.decode_bytes(&tokens, special_token_policy);
+        for invalid_token_policy in [
+            InvalidTokenPolicy::Error,
+            InvalidTokenPolicy::Skip,
+            InvalidTokenPolicy::Replace('\u{FFFD}'),
+        ] {
+            let lenient = tokenizer.decode_with_invalid_policy(
+                &tokens,
+                special_token_policy,
+                invalid_token_policy,
+            );
+            // Invalid tokens only change the result when they are present
+            if let Ok(strict) = &strict {
+                assert_eq!(lenient.as_ref().ok(), Some(strict));
+            }
+        }
+    }
+});
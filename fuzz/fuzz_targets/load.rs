@@ -0,0 +1,27 @@
+//! Parsing arbitrary bytes as a tokenizer file must fail cleanly, and anything
+//! that loads must be usable.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+fuzz_target!(|data: &[u8]| {
+    for loaded in [
+        Tekkenizer::from_bytes(data),
+        Tekkenizer::from_bytes_with_report(data).map(|(tokenizer, _)| tokenizer),
+    ] {
+        let Ok(tokenizer) = loaded else {
+            continue;
+        };
+        if let Ok(tokens) = tokenizer.encode("Hello, world! 123 🚀\n", true, true) {
+            let _ = tokenizer.decode(&tokens, SpecialTokenPolicy::Keep);
+        }
+        let vocab_size = u32::try_from(tokenizer.vocab_size()).unwrap_or(u32::MAX);
+        let _ = tokenizer.decode(
+            &[0, 1, 2, vocab_size.saturating_sub(1), vocab_size],
+            SpecialTokenPolicy::Keep,
+        );
+    }
+});
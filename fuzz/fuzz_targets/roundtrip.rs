@@ -0,0 +1,29 @@
+//! Encoding arbitrary UTF-8 and decoding the tokens must give back the text.
+
+#![no_main]
+
+use std::sync::LazyLock;
+
+use libfuzzer_sys::fuzz_target;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: LazyLock<Tekkenizer> = LazyLock::new(|| {
+    Tekkenizer::from_file(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../tests/assets/tekken.json"
+    ))
+    .expect("Failed to load tokenizer from file")
+});
+
+fuzz_target!(|text: &str| {
+    let tokenizer = &*TOKENIZER;
+    let tokens = tokenizer.encode(text, true, true).expect("encoding failed");
+    assert_eq!(tokens.first(), tokenizer.bos_id().ok().as_ref());
+    assert_eq!(tokens.last(), tokenizer.eos_id().ok().as_ref());
+
+    let decoded = tokenizer
+        .decode(&tokens, SpecialTokenPolicy::Ignore)
+        .expect("decoding failed");
+    assert_eq!(decoded, text);
+});
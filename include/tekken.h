@@ -0,0 +1,222 @@
+/* Tekken tokenizer C API. Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#ifndef TEKKEN_H
+#define TEKKEN_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Status of an FFI call.
+typedef enum TekkenErrorCode {
+  // The call succeeded.
+  TEKKEN_ERROR_CODE_OK = 0,
+  // A required pointer argument was null.
+  TEKKEN_ERROR_CODE_NULL_POINTER = 1,
+  // Input text or a path was not valid UTF-8.
+  TEKKEN_ERROR_CODE_INVALID_UTF8 = 2,
+  // Reading a file failed.
+  TEKKEN_ERROR_CODE_IO = 3,
+  // The tokenizer file could not be parsed.
+  TEKKEN_ERROR_CODE_PARSE = 4,
+  // The tokenizer configuration is invalid.
+  TEKKEN_ERROR_CODE_INVALID_CONFIG = 5,
+  // A required token is missing from the vocabulary.
+  TEKKEN_ERROR_CODE_TOKEN_NOT_FOUND = 6,
+  // A token ID is outside the vocabulary.
+  TEKKEN_ERROR_CODE_TOKEN_OUT_OF_RANGE = 7,
+  // The special token policy was violated.
+  TEKKEN_ERROR_CODE_SPECIAL_TOKEN_POLICY = 8,
+  // Decoded text contains a NUL byte and cannot be returned as a C string.
+  TEKKEN_ERROR_CODE_INTERIOR_NUL = 9,
+  // A Rust panic was caught at the ABI boundary.
+  TEKKEN_ERROR_CODE_PANIC = 10,
+  // Any other error.
+  TEKKEN_ERROR_CODE_OTHER = 11,
+  // An argument was outside its allowed values, e.g. an unknown policy.
+  TEKKEN_ERROR_CODE_INVALID_ARGUMENT = 12,
+} TekkenErrorCode;
+
+// How special tokens are handled when decoding.
+//
+// Functions take the policy as a `uint32_t` holding one of these values, so an
+// unknown value is reported as [`TekkenErrorCode::InvalidArgument`] rather than
+// being undefined behavior.
+typedef enum TekkenSpecialTokenPolicy {
+  // Omit special tokens from the output.
+  TEKKEN_SPECIAL_TOKEN_POLICY_IGNORE = 0,
+  // Keep special tokens as their string form.
+  TEKKEN_SPECIAL_TOKEN_POLICY_KEEP = 1,
+  // Fail on special tokens.
+  TEKKEN_SPECIAL_TOKEN_POLICY_RAISE = 2,
+} TekkenSpecialTokenPolicy;
+
+// An opaque streaming decoder handle.
+//
+// The decoder borrows its tokenizer, which must outlive it.
+typedef struct TekkenDecoder TekkenDecoder;
+
+// An opaque tokenizer handle.
+typedef struct TekkenTokenizer TekkenTokenizer;
+
+// Error details filled in by fallible functions.
+//
+// Zero-initialize before use. `message` is null on success and otherwise a
+// NUL-terminated string that must be released with [`tekken_error_free`].
+// The struct may be reused across calls: each call releases the message left
+// by the previous one before storing its own.
+typedef struct TekkenError {
+  // The error code.
+  enum TekkenErrorCode code;
+  // Human-readable description, or null.
+  char *message;
+} TekkenError;
+
+// Token IDs produced by [`tekken_encode`].
+//
+// Release with [`tekken_encoding_free`].
+typedef struct TekkenEncoding {
+  // Pointer to `len` token IDs, or null when empty.
+  uint32_t *ids;
+  // Number of token IDs.
+  size_t len;
+} TekkenEncoding;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Loads a tokenizer from a `tekken.json` file.
+//
+// Returns null on failure.
+//
+// # Safety
+//
+// `path` must be a NUL-terminated string; `error` must be null or valid.
+struct TekkenTokenizer *tekken_tokenizer_from_file(const char *path, struct TekkenError *error);
+
+// Loads a tokenizer from the bytes of a tokenizer file.
+//
+// Returns null on failure.
+//
+// # Safety
+//
+// `data` must point to `len` readable bytes; `error` must be null or valid.
+struct TekkenTokenizer *tekken_tokenizer_from_bytes(const uint8_t *data,
+                                                    size_t len,
+                                                    struct TekkenError *error);
+
+// Releases a tokenizer. Null is ignored.
+//
+// # Safety
+//
+// `tokenizer` must come from `tekken_tokenizer_from_*`, must not be used
+// afterwards, and must not be borrowed by a live decoder.
+void tekken_tokenizer_free(struct TekkenTokenizer *tokenizer);
+
+// Returns the vocabulary size including special tokens, or 0 for null.
+//
+// # Safety
+//
+// `tokenizer` must be null or a live tokenizer handle.
+size_t tekken_tokenizer_vocab_size(const struct TekkenTokenizer *tokenizer);
+
+// Encodes UTF-8 text into token IDs.
+//
+// On success `out` holds the IDs and must be released with [`tekken_encoding_free`].
+//
+// # Safety
+//
+// `tokenizer` must be a live handle, `text` must point to `text_len` readable
+// bytes, `out` must be writable and `error` must be null or valid.
+enum TekkenErrorCode tekken_encode(const struct TekkenTokenizer *tokenizer,
+                                   const uint8_t *text,
+                                   size_t text_len,
+                                   bool add_beginning_of_sequence,
+                                   bool add_end_of_sequence,
+                                   struct TekkenEncoding *out,
+                                   struct TekkenError *error);
+
+// Releases the IDs of an encoding and resets it. Null is ignored.
+//
+// # Safety
+//
+// `encoding` must be null or filled by [`tekken_encode`] and not yet released.
+void tekken_encoding_free(struct TekkenEncoding *encoding);
+
+// Decodes token IDs into a NUL-terminated UTF-8 string.
+//
+// `policy` is a [`TekkenSpecialTokenPolicy`] value. On success `*out_text`
+// must be released with [`tekken_string_free`].
+//
+// # Safety
+//
+// `tokenizer` must be a live handle, `ids` must point to `len` IDs, `out_text`
+// must be writable and `error` must be null or valid.
+enum TekkenErrorCode tekken_decode(const struct TekkenTokenizer *tokenizer,
+                                   const uint32_t *ids,
+                                   size_t len,
+                                   uint32_t policy,
+                                   char **out_text,
+                                   struct TekkenError *error);
+
+// Releases a string returned by this library. Null is ignored.
+//
+// # Safety
+//
+// `text` must be null or a string returned by this library and not yet released.
+void tekken_string_free(char *text);
+
+// Releases the message of an error and resets its code. Null is ignored.
+//
+// # Safety
+//
+// `error` must be null or valid.
+void tekken_error_free(struct TekkenError *error);
+
+// Creates a streaming decoder for tokens generated one at a time.
+//
+// `policy` is a [`TekkenSpecialTokenPolicy`] value. Returns null if
+// `tokenizer` is null or `policy` is unknown.
+//
+// # Safety
+//
+// `tokenizer` must be null or a live handle that outlives the decoder.
+struct TekkenDecoder *tekken_decoder_new(const struct TekkenTokenizer *tokenizer, uint32_t policy);
+
+// Feeds the next token to a decoder.
+//
+// On success `*out_text` holds the text completed by this token, possibly the
+// empty string, and must be released with [`tekken_string_free`].
+//
+// # Safety
+//
+// `decoder` must be a live handle not used concurrently, `out_text` must be
+// writable and `error` must be null or valid.
+enum TekkenErrorCode tekken_decoder_push(struct TekkenDecoder *decoder,
+                                         uint32_t token_id,
+                                         char **out_text,
+                                         struct TekkenError *error);
+
+// Checks that the stream did not end inside a character and resets the decoder.
+//
+// # Safety
+//
+// `decoder` must be a live handle and `error` must be null or valid.
+enum TekkenErrorCode tekken_decoder_finish(struct TekkenDecoder *decoder,
+                                           struct TekkenError *error);
+
+// Releases a decoder. Null is ignored.
+//
+// # Safety
+//
+// `decoder` must be null or come from [`tekken_decoder_new`] and not be used afterwards.
+void tekken_decoder_free(struct TekkenDecoder *decoder);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TEKKEN_H */
@@ -0,0 +1,238 @@
+//! Token-to-character alignment for per-token visualizations.
+//!
+//! Tekken tokens are byte sequences, so a single character may be split across
+//! several byte-fallback tokens. The alignment map attributes every token to the
+//! characters it overlaps, which keeps UI heatmaps (e.g. per-token logprobs) well
+//! defined even for emoji and CJK text.
+//!
+//! [`TokenSpan`] packs an alignment with the token's kind into a
+//! JSON-serializable record, so tokenizer visualizers can render a text or a
+//! token sequence from a single call.
+
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{Result, TokenizerError};
+use crate::special_tokens::SpecialTokenPolicy;
+use crate::tekkenizer::Tekkenizer;
+
+/// Alignment of a single token to the text it was encoded from.
+///
+/// # Fields
+///
+/// * `token_id` - The token ID
+/// * `char_range` - Range of character indices (not byte offsets) covered by the token
+/// * `byte_range` - Range of byte offsets covered by the token
+/// * `piece` - Display text for the token
+///
+/// Tokens that split a multi-byte character all share that character's
+/// `char_range` and `piece`. Special tokens have an empty range at their
+/// position and their token string as `piece`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAlignment {
+    /// The token ID.
+    pub token_id: u32,
+    /// Range of character indices covered by the token.
+    pub char_range: Range<usize>,
+    /// Range of byte offsets covered by the token.
+    pub byte_range: Range<usize>,
+    /// Display text for the token.
+    pub piece: String,
+}
+
+/// A token annotated for visualization, serializable to JSON.
+///
+/// # Fields
+///
+/// * `token_id` - The token ID
+/// * `piece` - Display text for the token, as in [`TokenAlignment::piece`]
+/// * `byte_range` - Range of byte offsets covered by the token, serialized as
+///   `{"start": .., "end": ..}`
+/// * `is_special` - Whether the token is a special token
+/// * `is_byte` - Whether the token is a single-byte fallback token
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let spans = tokenizer.annotate_text("Hello 🚀", true, false)?;
+/// println!("{}", serde_json::to_string(&spans)?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenSpan {
+    /// The token ID.
+    pub token_id: u32,
+    /// Display text for the token.
+    pub piece: String,
+    /// Range of byte offsets covered by the token.
+    pub byte_range: Range<usize>,
+    /// Whether the token is a special token.
+    pub is_special: bool,
+    /// Whether the token is a single-byte fallback token.
+    pub is_byte: bool,
+}
+
+impl Tekkenizer {
+    /// Encodes text and annotates each token for visualization.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to encode
+    /// * `add_beginning_of_sequence` - Whether to prepend the BOS token
+    /// * `add_end_of_sequence` - Whether to append the EOS token
+    ///
+    /// # Returns
+    ///
+    /// One `TokenSpan` per token, with byte ranges into `text`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails.
+    pub fn annotate_text(
+        &self,
+        text: &str,
+        add_beginning_of_sequence: bool,
+        add_end_of_sequence: bool,
+    ) -> Result<Vec<TokenSpan>> {
+        let tokens = self.encode(text, add_beginning_of_sequence, add_end_of_sequence)?;
+        self.token_spans(text, &tokens)
+    }
+
+    /// Annotates each token of a sequence for visualization.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs, e.g. generated by a model
+    ///
+    /// # Returns
+    ///
+    /// One `TokenSpan` per token, with byte ranges into the decoded text
+    /// without special tokens; special tokens have empty ranges.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token ID is out of range or the regular tokens do
+    /// not decode to valid UTF-8.
+    pub fn annotate_tokens(&self, tokens: &[u32]) -> Result<Vec<TokenSpan>> {
+        let text = self.decode(tokens, SpecialTokenPolicy::Ignore)?;
+        self.token_spans(&text, tokens)
+    }
+
+    fn token_spans(&self, text: &str, tokens: &[u32]) -> Result<Vec<TokenSpan>> {
+        Ok(self
+            .token_alignments(text, tokens)?
+            .into_iter()
+            .map(|alignment| TokenSpan {
+                token_id: alignment.token_id,
+                piece: alignment.piece,
+                byte_range: alignment.byte_range,
+                is_special: self.is_special_token(alignment.token_id),
+                is_byte: self.is_byte(alignment.token_id),
+            })
+            .collect())
+    }
+
+    /// Aligns each token of an encoding to the characters of its source text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text that was encoded
+    /// * `tokens` - The token IDs produced for `text` (special tokens are allowed)
+    ///
+    /// # Returns
+    ///
+    /// One `TokenAlignment` per input token, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token ID is out of range or the tokens do not
+    /// decode to exactly `text`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let text = "Hello 🚀";
+    /// let tokens = tokenizer.encode(text, true, false)?;
+    /// for alignment in tokenizer.token_alignments(text, &tokens)? {
+    ///     println!("{} -> {:?} {:?}", alignment.token_id, alignment.char_range, alignment.piece);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn token_alignments(&self, text: &str, tokens: &[u32]) -> Result<Vec<TokenAlignment>> {
+        let bytes = text.as_bytes();
+
+        // Character index of the character containing each byte, plus one past the end
+        let mut char_of_byte = Vec::with_capacity(bytes.len() + 1);
+        for (char_index, ch) in text.chars().enumerate() {
+            char_of_byte.extend(std::iter::repeat_n(char_index, ch.len_utf8()));
+        }
+        char_of_byte.push(text.chars().count());
+
+        let mut alignments = Vec::with_capacity(tokens.len());
+        let mut offset = 0;
+
+        for &token_id in tokens {
+            if self.is_special_token(token_id) {
+                let position = char_of_byte[offset];
+                alignments.push(TokenAlignment {
+                    token_id,
+                    char_range: position..position,
+                    byte_range: offset..offset,
+                    piece: self.special_token(token_id)?.token_str.clone(),
+                });
+                continue;
+            }
+
+            let token_bytes = self.regular_token_bytes(token_id)?;
+            let end = offset + token_bytes.len();
+            if bytes.get(offset..end) != Some(token_bytes) {
+                return Err(TokenizerError::Tokenizers(format!(
+                    "Token ID {token_id} at byte offset {offset} does not match the given text"
+                )));
+            }
+
+            let char_range = if token_bytes.is_empty() {
+                char_of_byte[offset]..char_of_byte[offset]
+            } else {
+                char_of_byte[offset]..char_of_byte[end - 1] + 1
+            };
+            alignments.push(TokenAlignment {
+                token_id,
+                char_range,
+                byte_range: offset..end,
+                piece: String::new(),
+            });
+            offset = end;
+        }
+
+        if offset != bytes.len() {
+            return Err(TokenizerError::Tokenizers(format!(
+                "Tokens cover {offset} of {} bytes of the given text",
+                bytes.len()
+            )));
+        }
+
+        // Widen display pieces to whole characters so split characters render once per token
+        let char_starts: Vec<usize> = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(bytes.len()))
+            .collect();
+        for alignment in &mut alignments {
+            if !alignment.byte_range.is_empty() {
+                let start = char_starts[alignment.char_range.start];
+                let end = char_starts[alignment.char_range.end];
+                alignment.piece = text[start..end].to_string();
+            }
+        }
+
+        Ok(alignments)
+    }
+}
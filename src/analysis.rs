@@ -0,0 +1,400 @@
+//! Corpus statistics under a tokenizer's vocabulary.
+//!
+//! [`TokenStats`] tokenizes a corpus and records how often each token occurs and
+//! how densely the text is encoded. Comparing the statistics of two vocabularies
+//! on the same corpus shows whether a custom vocabulary is worth training: a
+//! better-fitting vocabulary needs fewer tokens per word and compresses more
+//! bytes into each token. [`ByteFallbackReport`] measures how often characters
+//! are missing from the vocabulary altogether, broken down by [`Script`].
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::errors::Result;
+use crate::tekkenizer::Tekkenizer;
+
+/// Token statistics of a corpus.
+///
+/// Texts are encoded without BOS/EOS, so the counts cover the text only. Words
+/// are whitespace-separated runs of characters.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::analysis::TokenStats;
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let corpus = ["The quick brown fox.", "Jumps over the lazy dog."];
+/// let stats = TokenStats::from_texts(&tokenizer, corpus)?;
+///
+/// println!("{:.2} tokens per word", stats.tokens_per_word());
+/// println!("{:.2} bytes per token", stats.compression_ratio());
+/// for (token_id, count) in stats.most_frequent(10) {
+///     println!("{token_id}: {count}");
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenStats {
+    /// Number of texts analyzed.
+    pub num_texts: usize,
+    /// Total number of tokens.
+    pub num_tokens: usize,
+    /// Total number of characters.
+    pub num_chars: usize,
+    /// Total number of whitespace-separated words.
+    pub num_words: usize,
+    /// Total number of UTF-8 bytes.
+    pub num_bytes: usize,
+    /// Occurrences of each token, indexed by token ID.
+    pub token_counts: Vec<usize>,
+}
+
+impl TokenStats {
+    /// Creates empty statistics for a tokenizer's vocabulary.
+    #[must_use]
+    pub fn new(tokenizer: &Tekkenizer) -> Self {
+        Self {
+            num_texts: 0,
+            num_tokens: 0,
+            num_chars: 0,
+            num_words: 0,
+            num_bytes: 0,
+            token_counts: vec![0; tokenizer.vocab_size()],
+        }
+    }
+
+    /// Computes the statistics of a corpus.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer whose vocabulary is analyzed
+    /// * `texts` - The texts of the corpus
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a text cannot be encoded.
+    pub fn from_texts<I, S>(tokenizer: &Tekkenizer, texts: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut stats = Self::new(tokenizer);
+        for text in texts {
+            stats.add_text(tokenizer, text.as_ref())?;
+        }
+        Ok(stats)
+    }
+
+    /// Adds one text to the statistics.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text cannot be encoded.
+    pub fn add_text(&mut self, tokenizer: &Tekkenizer, text: &str) -> Result<()> {
+        let tokens = tokenizer.encode(text, false, false)?;
+        for &token in &tokens {
+            let index = token as usize;
+            if index >= self.token_counts.len() {
+                self.token_counts.resize(index + 1, 0);
+            }
+            self.token_counts[index] += 1;
+        }
+        self.num_texts += 1;
+        self.num_tokens += tokens.len();
+        self.num_chars += text.chars().count();
+        self.num_words += text.split_whitespace().count();
+        self.num_bytes += text.len();
+        Ok(())
+    }
+
+    /// Returns the average number of tokens per character.
+    #[must_use]
+    pub fn tokens_per_char(&self) -> f64 {
+        ratio(self.num_tokens, self.num_chars)
+    }
+
+    /// Returns the average number of tokens per word.
+    #[must_use]
+    pub fn tokens_per_word(&self) -> f64 {
+        ratio(self.num_tokens, self.num_words)
+    }
+
+    /// Returns the compression ratio, the average number of UTF-8 bytes per token.
+    ///
+    /// Higher is better: each token carries more text.
+    #[must_use]
+    pub fn compression_ratio(&self) -> f64 {
+        ratio(self.num_bytes, self.num_tokens)
+    }
+
+    /// Returns the number of distinct tokens that occur in the corpus.
+    #[must_use]
+    pub fn num_distinct_tokens(&self) -> usize {
+        self.token_counts.iter().filter(|&&count| count > 0).count()
+    }
+
+    /// Returns the fraction of the vocabulary that occurs in the corpus.
+    #[must_use]
+    pub fn vocab_coverage(&self) -> f64 {
+        ratio(self.num_distinct_tokens(), self.token_counts.len())
+    }
+
+    /// Returns the `n` most frequent tokens with their counts, most frequent
+    /// first; ties are ordered by token ID.
+    #[must_use]
+    pub fn most_frequent(&self, n: usize) -> Vec<(u32, usize)> {
+        let mut frequent: Vec<(u32, usize)> = self
+            .token_counts
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .filter_map(|(id, &count)| Some((u32::try_from(id).ok()?, count)))
+            .collect();
+        frequent.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        frequent.truncate(n);
+        frequent
+    }
+
+    /// Returns a histogram of token frequencies as `(occurrences, num_tokens)`
+    /// pairs in increasing order of occurrences: `num_tokens` vocabulary tokens
+    /// occur exactly `occurrences` times.
+    ///
+    /// The pair for 0 occurrences counts the tokens that never occur.
+    #[must_use]
+    pub fn frequency_histogram(&self) -> Vec<(usize, usize)> {
+        let mut histogram = BTreeMap::new();
+        for &count in &self.token_counts {
+            *histogram.entry(count).or_insert(0) += 1;
+        }
+        histogram.into_iter().collect()
+    }
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+/// Coarse Unicode script of a character, used to break down coverage reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Script {
+    /// Digits, punctuation, whitespace and symbols shared by all scripts.
+    Common,
+    /// Latin letters, including accented and extended forms.
+    Latin,
+    /// Greek and Coptic.
+    Greek,
+    /// Cyrillic.
+    Cyrillic,
+    /// Armenian.
+    Armenian,
+    /// Hebrew.
+    Hebrew,
+    /// Arabic.
+    Arabic,
+    /// Devanagari.
+    Devanagari,
+    /// Bengali.
+    Bengali,
+    /// Tamil.
+    Tamil,
+    /// Thai.
+    Thai,
+    /// Georgian.
+    Georgian,
+    /// Korean Hangul.
+    Hangul,
+    /// Japanese Hiragana and Katakana.
+    Kana,
+    /// CJK ideographs.
+    Han,
+    /// Emoji and pictographs.
+    Emoji,
+    /// Any other script.
+    Other,
+}
+
+impl Script {
+    /// Returns the script of a character.
+    #[must_use]
+    pub fn of(ch: char) -> Self {
+        if ch.is_ascii_alphabetic() {
+            return Self::Latin;
+        }
+        if ch.is_ascii() || ch.is_whitespace() || ch.is_numeric() {
+            return Self::Common;
+        }
+        match u32::from(ch) {
+            0x00C0..=0x024F | 0x1E00..=0x1EFF => Self::Latin,
+            0x0370..=0x03FF | 0x1F00..=0x1FFF => Self::Greek,
+            0x0400..=0x052F => Self::Cyrillic,
+            0x0530..=0x058F => Self::Armenian,
+            0x0590..=0x05FF => Self::Hebrew,
+            0x0600..=0x06FF | 0x0750..=0x077F | 0x08A0..=0x08FF => Self::Arabic,
+            0x0900..=0x097F => Self::Devanagari,
+            0x0980..=0x09FF => Self::Bengali,
+            0x0B80..=0x0BFF => Self::Tamil,
+            0x0E00..=0x0E7F => Self::Thai,
+            0x10A0..=0x10FF => Self::Georgian,
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Self::Hangul,
+            0x3040..=0x30FF | 0x31F0..=0x31FF => Self::Kana,
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x3FFFF => Self::Han,
+            0x1F000..=0x1FAFF | 0x2600..=0x27BF => Self::Emoji,
+            0x0080..=0x00BF | 0x2000..=0x2BFF | 0x3000..=0x303F | 0xFE00..=0xFE0F => Self::Common,
+            _ => Self::Other,
+        }
+    }
+
+    /// Returns the name of the script.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Common => "Common",
+            Self::Latin => "Latin",
+            Self::Greek => "Greek",
+            Self::Cyrillic => "Cyrillic",
+            Self::Armenian => "Armenian",
+            Self::Hebrew => "Hebrew",
+            Self::Arabic => "Arabic",
+            Self::Devanagari => "Devanagari",
+            Self::Bengali => "Bengali",
+            Self::Tamil => "Tamil",
+            Self::Thai => "Thai",
+            Self::Georgian => "Georgian",
+            Self::Hangul => "Hangul",
+            Self::Kana => "Kana",
+            Self::Han => "Han",
+            Self::Emoji => "Emoji",
+            Self::Other => "Other",
+        }
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Token counts of a [`ByteFallbackReport`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FallbackCounts {
+    /// Number of tokens emitted.
+    pub num_tokens: usize,
+    /// Number of those tokens that are byte fallbacks.
+    pub num_fallback_tokens: usize,
+}
+
+impl FallbackCounts {
+    /// Returns the fraction of tokens that are byte fallbacks.
+    #[must_use]
+    pub fn fallback_rate(&self) -> f64 {
+        ratio(self.num_fallback_tokens, self.num_tokens)
+    }
+}
+
+/// How often encoding falls back to single-byte tokens.
+///
+/// A byte fallback is a single-byte token holding part of a multi-byte UTF-8
+/// character: the vocabulary has no token for the whole character, so it is
+/// spelled out byte by byte. A high fallback rate means the vocabulary covers
+/// the text poorly. Single-byte tokens for ASCII characters are complete
+/// characters and are not counted as fallbacks.
+///
+/// Tokens are also counted per [`Script`], attributed to the first character
+/// they overlap that is not [`Script::Common`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::analysis::ByteFallbackReport;
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let report = ByteFallbackReport::from_texts(&tokenizer, ["Hello", "ᏣᎳᎩ ᎦᏬᏂᎯᏍᏗ"])?;
+/// println!("{:.1}% byte fallbacks", 100.0 * report.total.fallback_rate());
+/// for (script, counts) in &report.by_script {
+///     println!("{script}: {:.1}%", 100.0 * counts.fallback_rate());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ByteFallbackReport {
+    /// Counts over all texts.
+    pub total: FallbackCounts,
+    /// Counts per script.
+    pub by_script: BTreeMap<Script, FallbackCounts>,
+}
+
+impl ByteFallbackReport {
+    /// Computes the byte-fallback report of a corpus.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a text cannot be encoded.
+    pub fn from_texts<I, S>(tokenizer: &Tekkenizer, texts: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut report = Self::default();
+        for text in texts {
+            report.add_text(tokenizer, text.as_ref())?;
+        }
+        Ok(report)
+    }
+
+    /// Adds one text to the report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text cannot be encoded.
+    pub fn add_text(&mut self, tokenizer: &Tekkenizer, text: &str) -> Result<()> {
+        let tokens = tokenizer.encode(text, false, false)?;
+        let mut offset = 0;
+        for token in tokens {
+            let token_bytes = tokenizer.regular_token_bytes(token)?;
+            let end = offset + token_bytes.len();
+            let is_fallback = token_bytes.len() == 1 && !token_bytes[0].is_ascii();
+            let script = token_script(text, offset, end);
+
+            for counts in [&mut self.total, self.by_script.entry(script).or_default()] {
+                counts.num_tokens += 1;
+                counts.num_fallback_tokens += usize::from(is_fallback);
+            }
+            offset = end;
+        }
+        Ok(())
+    }
+}
+
+/// Script of the first non-common character overlapping `text[start..end]`.
+fn token_script(text: &str, mut start: usize, end: usize) -> Script {
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    text[start..]
+        .char_indices()
+        .take_while(|&(index, _)| start + index < end)
+        .map(|(_, ch)| Script::of(ch))
+        .find(|&script| script != Script::Common)
+        .unwrap_or(Script::Common)
+}
+
+impl Tekkenizer {
+    /// Reports how often encoding a single document falls back to single-byte
+    /// tokens; see [`ByteFallbackReport`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text cannot be encoded.
+    pub fn byte_fallback_report(&self, text: &str) -> Result<ByteFallbackReport> {
+        ByteFallbackReport::from_texts(self, [text])
+    }
+}
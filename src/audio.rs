@@ -1,7 +1,16 @@
+//! Audio configuration and processing.
+//!
+//! The configuration types ([`AudioConfig`], [`AudioSpectrogramConfig`]) are always
+//! available so tokenizer files with an audio section load in every build. Loading,
+//! resampling and encoding audio require the `audio` feature (on by default).
+
 use crate::errors::{Result, TokenizerError};
+#[cfg(feature = "audio")]
 use base64::Engine;
+#[cfg(feature = "audio")]
 use ndarray::Array1;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "audio")]
 use std::path::Path;
 
 /// Configuration for generating audio spectrograms.
@@ -16,8 +25,10 @@ use std::path::Path;
 /// * `window_size` - Window size for Fourier transform (typically 400)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioSpectrogramConfig {
+    #[serde(alias = "n_mels")]
     pub num_mel_bins: usize,
     pub hop_length: usize,
+    #[serde(alias = "n_fft")]
     pub window_size: usize,
 }
 
@@ -82,12 +93,17 @@ impl AudioSpectrogramConfig {
 /// * `frame_rate` - Number of frames per second for the tokenizer model
 /// * `audio_encoding_config` - Spectrogram generation parameters
 /// * `chunk_length_s` - Optional chunk length in seconds for padding
+/// * `extra` - Keys not known to this crate, kept so they survive a round trip
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioConfig {
+    #[serde(alias = "sample_rate")]
     pub sampling_rate: usize,
     pub frame_rate: f64,
+    #[serde(alias = "encoding_config")]
     pub audio_encoding_config: AudioSpectrogramConfig,
     pub chunk_length_s: Option<f64>,
+    #[serde(flatten, default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl AudioConfig {
@@ -113,31 +129,70 @@ impl AudioConfig {
         encoding_config: AudioSpectrogramConfig,
         chunk_length_s: Option<f64>,
     ) -> Result<Self> {
-        if sampling_rate == 0 {
+        let config = Self {
+            sampling_rate,
+            frame_rate,
+            audio_encoding_config: encoding_config,
+            chunk_length_s,
+            extra: serde_json::Map::new(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that audio can be encoded with this configuration.
+    ///
+    /// Configurations read from tokenizer files are checked when the tokenizer
+    /// is loaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the sampling rate or hop length is zero, the frame
+    /// rate is not positive, the chunk length is not positive or covers no
+    /// samples, or a token would cover less than one spectrogram hop
+    /// (`sampling_rate / frame_rate < hop_length`).
+    pub fn validate(&self) -> Result<()> {
+        if self.sampling_rate == 0 {
             return Err(TokenizerError::InvalidConfig(
                 "sampling_rate must be > 0".to_string(),
             ));
         }
-        if frame_rate <= 0.0 {
+        if self.frame_rate <= 0.0 {
             return Err(TokenizerError::InvalidConfig(
                 "frame_rate must be > 0".to_string(),
             ));
         }
+        if self.audio_encoding_config.hop_length == 0 {
+            return Err(TokenizerError::InvalidConfig(
+                "hop_length must be > 0".to_string(),
+            ));
+        }
 
-        if let Some(chunk_length) = chunk_length_s {
-            if chunk_length <= 0.0 {
-                return Err(TokenizerError::InvalidConfig(
-                    "chunk_length_s must be > 0".to_string(),
-                ));
-            }
+        if let Some(chunk_length) = self.chunk_length_s
+            && chunk_length <= 0.0
+        {
+            return Err(TokenizerError::InvalidConfig(
+                "chunk_length_s must be > 0".to_string(),
+            ));
         }
 
-        Ok(Self {
-            sampling_rate,
-            frame_rate,
-            audio_encoding_config: encoding_config,
-            chunk_length_s,
-        })
+        if self
+            .chunk_frames()
+            .is_ok_and(|chunk_frames| chunk_frames == 0)
+        {
+            return Err(TokenizerError::InvalidConfig(
+                "chunk_length_s must cover at least one sample".to_string(),
+            ));
+        }
+        if self.audio_length_per_tok() == 0 {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "A token at frame_rate {} covers {} samples, less than one hop of {}",
+                self.frame_rate,
+                self.samples_per_token(),
+                self.audio_encoding_config.hop_length
+            )));
+        }
+        Ok(())
     }
 
     /// Calculates the number of audio frames per chunk.
@@ -171,14 +226,18 @@ impl AudioConfig {
         }
     }
 
-    /// Calculates the length of audio (in samples) represented by each token.
+    /// Calculates the number of spectrogram hops represented by each token.
     ///
-    /// This determines the downsampling factor from audio samples to tokens
-    /// based on the frame rate and spectrogram hop length.
+    /// This is the downsampling factor from spectrogram frames to tokens,
+    /// `sampling_rate / frame_rate / hop_length` rounded down, computed exactly as
+    /// the Python `audio_length_per_tok`. When `sampling_rate / frame_rate` is not
+    /// a multiple of the hop length the remainder is silently dropped, so tokens
+    /// are emitted faster than `frame_rate`; use [`AudioConfig::samples_per_token`]
+    /// and [`AudioConfig::tokens_per_second`] for the exact figures.
     ///
     /// # Returns
     ///
-    /// Number of audio samples per token.
+    /// Number of spectrogram hops per token.
     #[must_use]
     #[allow(
         clippy::cast_possible_truncation,
@@ -186,38 +245,200 @@ impl AudioConfig {
         clippy::cast_precision_loss
     )]
     pub fn audio_length_per_tok(&self) -> usize {
-        #[allow(clippy::cast_precision_loss)]
-        let mut downsample_factor = self.sampling_rate as f64 / self.frame_rate;
-        #[allow(clippy::cast_precision_loss)]
-        {
-            downsample_factor /= self.audio_encoding_config.hop_length as f64;
-        }
-        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-        {
-            downsample_factor as usize
+        // Same steps as Python: `sampling_rate // frame_rate / hop_length`, truncated
+        let downsample_factor = self.samples_per_token().floor();
+        (downsample_factor / self.audio_encoding_config.hop_length as f64) as usize
+    }
+
+    /// Returns the number of audio samples per token implied by the frame rate,
+    /// `sampling_rate / frame_rate`, without rounding.
+    ///
+    /// Encoded tokens cover `audio_length_per_tok() * hop_length` samples each,
+    /// which is this value rounded down to whole hops.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tekken::audio::{AudioConfig, AudioSpectrogramConfig};
+    ///
+    /// let config = AudioConfig::new(16000, 12.5, AudioSpectrogramConfig::new(128, 160, 400)?, None)?;
+    /// assert_eq!(config.samples_per_token(), 1280.0);
+    /// assert_eq!(config.audio_length_per_tok(), 8);
+    /// # Ok::<(), tekken::TokenizerError>(())
+    /// ```
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn samples_per_token(&self) -> f64 {
+        self.sampling_rate as f64 / self.frame_rate
+    }
+
+    /// Returns the number of tokens encoded per second of audio.
+    ///
+    /// This equals `frame_rate` unless [`AudioConfig::audio_length_per_tok`]
+    /// rounds down, in which case tokens come slightly faster. The result is
+    /// finite for configurations that pass [`AudioConfig::validate`].
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn tokens_per_second(&self) -> f64 {
+        let samples_per_token = self.audio_length_per_tok() * self.audio_encoding_config.hop_length;
+        self.sampling_rate as f64 / samples_per_token as f64
+    }
+
+    /// Returns the length in samples that audio of `num_samples` samples is
+    /// padded to before encoding.
+    ///
+    /// With `chunk_length_s` set, audio is padded to a whole number of chunks;
+    /// otherwise it is padded to at least one spectrogram window.
+    #[must_use]
+    pub fn padded_length(&self, num_samples: usize) -> usize {
+        match self.chunk_frames() {
+            // Valid configurations have chunks of at least one sample
+            Ok(chunk_frames) => {
+                let chunk_frames = chunk_frames.max(1);
+                num_samples.div_ceil(chunk_frames) * chunk_frames
+            }
+            Err(_) => num_samples.max(self.audio_encoding_config.window_size),
         }
     }
+
+    /// Returns the number of `[AUDIO]` tokens for audio of `num_samples` samples
+    /// at [`AudioConfig::sampling_rate`], after padding.
+    ///
+    /// This mirrors the Python encoder: the padded signal is measured in hops,
+    /// one less if it does not end on a hop boundary, and divided into tokens of
+    /// [`AudioConfig::audio_length_per_tok`] hops, rounding up. The
+    /// `[BEGIN_AUDIO]` token is not included.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tekken::audio::{AudioConfig, AudioSpectrogramConfig};
+    ///
+    /// let config = AudioConfig::new(16000, 12.5, AudioSpectrogramConfig::new(128, 160, 400)?, None)?;
+    /// // One second of audio: 100 hops of 8 per token
+    /// assert_eq!(config.expected_tokens_for_samples(16000), 13);
+    /// # Ok::<(), tekken::TokenizerError>(())
+    /// ```
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn expected_tokens_for_samples(&self, num_samples: usize) -> usize {
+        let num_samples = self.padded_length(num_samples);
+        let hop_length = self.audio_encoding_config.hop_length;
+        let num_hops = if num_samples.is_multiple_of(hop_length) {
+            num_samples / hop_length
+        } else {
+            (num_samples as f64 / hop_length as f64 - 1.0).ceil() as usize
+        };
+        num_hops.div_ceil(self.audio_length_per_tok().max(1))
+    }
+}
+
+/// Floating-point type of audio samples: `f32` (the default) or `f64`.
+///
+/// Processing in `f64` keeps full precision through resampling and spectrogram
+/// computation for scientific pipelines. The trait is sealed.
+#[cfg(feature = "audio")]
+pub trait AudioSample:
+    rubato::Sample + rustfft::FftNum + rustfft::num_traits::Float + Default + sealed::Sealed
+{
+    /// Size of one sample in bytes.
+    const BYTES: usize;
+
+    /// Converts an `f64`, rounding to the nearest representable value.
+    fn from_f64_lossy(value: f64) -> Self;
+
+    /// Appends the little-endian bytes of the sample.
+    fn write_le_bytes(self, out: &mut Vec<u8>);
+
+    /// Reads a sample from exactly [`AudioSample::BYTES`] little-endian bytes.
+    fn read_le_bytes(bytes: &[u8]) -> Self;
+}
+
+#[cfg(feature = "audio")]
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for f32 {}
+    impl Sealed for f64 {}
+}
+
+#[cfg(feature = "audio")]
+impl AudioSample for f32 {
+    const BYTES: usize = 4;
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn from_f64_lossy(value: f64) -> Self {
+        value as f32
+    }
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_le_bytes(bytes: &[u8]) -> Self {
+        let mut array = [0; 4];
+        array.copy_from_slice(bytes);
+        Self::from_le_bytes(array)
+    }
+}
+
+#[cfg(feature = "audio")]
+impl AudioSample for f64 {
+    const BYTES: usize = 8;
+
+    fn from_f64_lossy(value: f64) -> Self {
+        value
+    }
+
+    fn write_le_bytes(self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn read_le_bytes(bytes: &[u8]) -> Self {
+        let mut array = [0; 8];
+        array.copy_from_slice(bytes);
+        Self::from_le_bytes(array)
+    }
 }
 
 /// Represents audio data with metadata.
 ///
 /// This struct holds audio waveform data along with its sampling rate and format.
 /// It provides methods for loading, processing, and converting audio data.
+/// Samples are `f32` unless another [`AudioSample`] type is chosen, e.g.
+/// `Audio<f64>` for high-precision processing.
 ///
 /// # Fields
 ///
-/// * `audio_array` - Audio waveform as a 1D array of f32 samples
+/// * `audio_array` - Audio waveform as a 1D array of samples
 /// * `sampling_rate` - Sampling rate in Hz
 /// * `format` - Audio format string (e.g., "wav")
-#[derive(Debug, Clone)]
-pub struct Audio {
-    pub audio_array: Array1<f32>,
+///
+/// # Serialization
+///
+/// The samples are serialized as their little-endian bytes: base64 encoded in
+/// human-readable formats such as JSON, raw bytes in binary formats.
+#[cfg(feature = "audio")]
+// The only `unsafe` is inside `ndarray::s!`; any deserialized samples are valid audio
+#[allow(clippy::unsafe_derive_deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Audio<S: AudioSample = f32> {
+    #[serde(with = "sample_bytes")]
+    pub audio_array: Array1<S>,
     pub sampling_rate: usize,
     pub format: String,
 }
 
+#[cfg(feature = "audio")]
 impl Audio {
-    /// Creates a new Audio instance.
+    /// Creates a new Audio instance with `f32` samples.
+    ///
+    /// Use [`Audio::from_array`] for other [`AudioSample`] types.
     ///
     /// # Arguments
     ///
@@ -311,9 +532,12 @@ impl Audio {
 
     /// Loads audio data from a base64-encoded string.
     ///
+    /// Data URLs such as `data:audio/wav;base64,...` are accepted as well, see
+    /// [`Audio::from_data_url`].
+    ///
     /// # Arguments
     ///
-    /// * `data` - Base64-encoded audio data
+    /// * `data` - Base64-encoded audio data, or a data URL
     ///
     /// # Returns
     ///
@@ -323,10 +547,75 @@ impl Audio {
     ///
     /// Returns an error if decoding or parsing fails.
     pub fn from_base64(data: &str) -> Result<Self> {
+        if data.starts_with("data:") {
+            return Self::from_data_url(data);
+        }
         let audio_bytes = base64::engine::general_purpose::STANDARD.decode(data)?;
         Self::from_bytes(&audio_bytes)
     }
 
+    /// Loads audio data from a base64 data URL, as sent by multimodal chat APIs.
+    ///
+    /// The MIME type picks the container decoder. WAV (`audio/wav`,
+    /// `audio/x-wav`, `audio/wave`, `audio/vnd.wave`) is supported; MIME
+    /// parameters such as `;codecs=1` are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - A data URL of the form `data:<mime>[;<params>];base64,<data>`
+    ///
+    /// # Returns
+    ///
+    /// A new Audio instance with the decoded data.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TokenizerError::UnsupportedFormat` for MIME types other than WAV,
+    /// and an error if the URL is malformed, not base64-encoded, or the audio
+    /// cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::audio::Audio;
+    ///
+    /// # let url = "data:audio/wav;base64,UklGRg==";
+    /// let audio = Audio::from_data_url(url)?;
+    /// println!("{:.2}s of audio", audio.duration());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_data_url(url: &str) -> Result<Self> {
+        let (header, data) = url
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(','))
+            .ok_or_else(|| {
+                TokenizerError::Audio(
+                    "Malformed data URL: expected `data:<mime>;base64,<data>`".to_string(),
+                )
+            })?;
+
+        let mut parts = header.split(';').map(str::trim);
+        let mime = parts.next().unwrap_or_default().to_ascii_lowercase();
+        if !parts.any(|part| part.eq_ignore_ascii_case("base64")) {
+            return Err(TokenizerError::Audio(
+                "Data URL is not base64-encoded".to_string(),
+            ));
+        }
+
+        match mime.as_str() {
+            "audio/wav" | "audio/x-wav" | "audio/wave" | "audio/vnd.wave" => {
+                let audio_bytes = base64::engine::general_purpose::STANDARD.decode(data.trim())?;
+                Self::from_bytes(&audio_bytes)
+            }
+            "" => Err(TokenizerError::UnsupportedFormat(
+                "Data URL without a MIME type; expected audio/wav".to_string(),
+            )),
+            _ => Err(TokenizerError::UnsupportedFormat(format!(
+                "Audio MIME type {mime} is not supported; expected audio/wav"
+            ))),
+        }
+    }
+
     /// Loads audio data from raw bytes.
     ///
     /// # Arguments
@@ -384,6 +673,63 @@ impl Audio {
 
         Ok(Self::new(audio_array, sampling_rate, "wav".to_string()))
     }
+}
+
+#[cfg(feature = "audio")]
+impl<S: AudioSample> Audio<S> {
+    /// Creates audio from samples of any [`AudioSample`] type.
+    ///
+    /// [`Audio::new`] is the `f32` shorthand.
+    ///
+    /// # Arguments
+    ///
+    /// * `audio_array` - Audio waveform data as a 1D array
+    /// * `sampling_rate` - Sampling rate in Hz
+    /// * `format` - Audio format string
+    #[must_use]
+    pub fn from_array(audio_array: Array1<S>, sampling_rate: usize, format: String) -> Self {
+        Self {
+            audio_array,
+            sampling_rate,
+            format,
+        }
+    }
+
+    /// Creates audio from 16-bit PCM samples, scaled to `[-1, 1)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples` - Mono 16-bit PCM samples
+    /// * `sampling_rate` - Sampling rate in Hz
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tekken::audio::Audio;
+    ///
+    /// let audio = Audio::<f64>::from_i16(&[0, 16384, -32768], 16000);
+    /// assert_eq!(audio.audio_array.to_vec(), vec![0.0, 0.5, -1.0]);
+    /// ```
+    #[must_use]
+    pub fn from_i16(samples: &[i16], sampling_rate: usize) -> Self {
+        let scale = S::from_f64_lossy(1.0 / 32768.0);
+        let audio_array = samples
+            .iter()
+            .map(|&sample| S::from_f64_lossy(f64::from(sample)) * scale)
+            .collect();
+        Self::from_array(audio_array, sampling_rate, "pcm".to_string())
+    }
+
+    /// Converts the samples to another [`AudioSample`] type.
+    #[must_use]
+    pub fn cast<T: AudioSample>(&self) -> Audio<T> {
+        Audio::from_array(
+            self.audio_array
+                .mapv(|sample| T::from_f64_lossy(sample.to_f64().unwrap_or_default())),
+            self.sampling_rate,
+            self.format.clone(),
+        )
+    }
 
     /// Calculates the duration of the audio in seconds.
     ///
@@ -407,20 +753,65 @@ impl Audio {
     ///
     /// # Errors
     ///
-    /// Currently returns an error as resampling is not yet implemented.
-    ///
-    /// # Note
-    ///
-    /// This is a placeholder implementation that needs proper resampling logic.
+    /// Returns an error if either sampling rate is zero.
     pub fn resample(&mut self, target_rate: usize) -> Result<()> {
         if self.sampling_rate == target_rate {
             return Ok(());
         }
 
-        // For now, return an error for resampling - this would need proper implementation
-        Err(TokenizerError::Audio(
-            "Resampling not yet implemented".to_string(),
-        ))
+        self.audio_array =
+            resample_samples(&self.audio_array.to_vec(), self.sampling_rate, target_rate)?.into();
+        self.sampling_rate = target_rate;
+        Ok(())
+    }
+
+    /// Changes the playback speed of the audio, keeping its sampling rate.
+    ///
+    /// This is the resampling-based speed perturbation used to augment speech
+    /// training data: the audio is resampled as if it had been recorded at
+    /// `sampling_rate * factor`, so a factor of `1.1` makes it 10% shorter and
+    /// raises its pitch accordingly. See [`SPEED_FACTORS`] for the usual factors.
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - Speed factor; above `1.0` is faster, below is slower
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `factor` is not a positive finite number, or too small
+    /// for the audio's sampling rate.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::audio::{Audio, SPEED_FACTORS};
+    /// let audio = Audio::from_file("audio.wav")?;
+    /// for factor in SPEED_FACTORS {
+    ///     let mut perturbed = audio.clone();
+    ///     perturbed.perturb_speed(factor)?;
+    ///     println!("{factor}: {:.2}s", perturbed.duration());
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn perturb_speed(&mut self, factor: f64) -> Result<()> {
+        if !factor.is_finite() || factor <= 0.0 {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Speed factor must be a positive number, got {factor}"
+            )));
+        }
+        let source_rate = (self.sampling_rate as f64 * factor).round() as usize;
+        if source_rate == self.sampling_rate {
+            return Ok(());
+        }
+
+        self.audio_array =
+            resample_samples(&self.audio_array.to_vec(), source_rate, self.sampling_rate)?.into();
+        Ok(())
     }
 
     /// Pads the audio to meet minimum length requirements.
@@ -435,24 +826,18 @@ impl Audio {
     ///
     /// # Errors
     ///
-    /// Returns an error if configuration is invalid.
+    /// Returns an error if `chunk_length_s` is set but covers no samples.
     pub fn pad(&mut self, config: &AudioConfig) -> Result<()> {
         let current_length = self.audio_array.len();
-
-        let target_length = if let Some(_chunk_length_s) = config.chunk_length_s {
-            let chunk_frames = config.chunk_frames()?;
-
-            current_length.div_ceil(chunk_frames) * chunk_frames
-        } else if current_length < config.audio_encoding_config.window_size {
-            config.audio_encoding_config.window_size
-        } else {
-            return Ok(());
-        };
+        if config.chunk_length_s.is_some() && config.chunk_frames()? == 0 {
+            return Err(TokenizerError::InvalidConfig(
+                "chunk_length_s must cover at least one sample".to_string(),
+            ));
+        }
+        let target_length = config.padded_length(current_length);
 
         if target_length > current_length {
-            let padding_length = target_length - current_length;
-            let _ = padding_length; // Padding length calculated but not used in debug
-            let mut padded = Array1::zeros(target_length);
+            let mut padded = Array1::from_elem(target_length, S::zero());
             padded
                 .slice_mut(ndarray::s![..current_length])
                 .assign(&self.audio_array);
@@ -463,6 +848,56 @@ impl Audio {
     }
 }
 
+/// Speed factors commonly used for speed perturbation, see [`Audio::perturb_speed`].
+#[cfg(feature = "audio")]
+pub const SPEED_FACTORS: [f64; 3] = [0.9, 1.0, 1.1];
+
+/// Resamples mono samples from `from_rate` to `to_rate` Hz.
+///
+/// The output has `len * to_rate / from_rate` samples (rounded) and is aligned
+/// with the input, i.e. the resampler's delay is removed.
+#[cfg(feature = "audio")]
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn resample_samples<S: AudioSample>(
+    samples: &[S],
+    from_rate: usize,
+    to_rate: usize,
+) -> Result<Vec<S>> {
+    use rubato::Resampler;
+
+    let resample_error =
+        |e: &dyn std::fmt::Display| TokenizerError::Audio(format!("Resampling failed: {e}"));
+    let mut resampler = rubato::FftFixedInOut::<S>::new(from_rate, to_rate, 1024, 1)
+        .map_err(|e| resample_error(&e))?;
+    let delay = resampler.output_delay();
+    let expected = (samples.len() as f64 * to_rate as f64 / from_rate as f64).round() as usize;
+
+    let mut output = Vec::with_capacity(expected + delay + resampler.output_frames_max());
+    let mut input = samples;
+    while output.len() < expected + delay {
+        let (chunk, rest) = input.split_at(resampler.input_frames_next().min(input.len()));
+        input = rest;
+        // Once the input runs out, zeros flush the samples still held back by the delay
+        let frames = if chunk.len() == resampler.input_frames_next() {
+            resampler.process(&[chunk], None)
+        } else if chunk.is_empty() {
+            resampler.process_partial(None::<&[&[S]]>, None)
+        } else {
+            resampler.process_partial(Some(&[chunk]), None)
+        }
+        .map_err(|e| resample_error(&e))?;
+        output.extend_from_slice(&frames[0]);
+    }
+
+    output.drain(..delay);
+    output.truncate(expected);
+    Ok(output)
+}
+
 /// Result of audio tokenization containing tokens and processed audio.
 ///
 /// This struct encapsulates the output of audio encoding, containing both
@@ -472,10 +907,16 @@ impl Audio {
 ///
 /// * `tokens` - Token sequence (u32) representing the audio (includes `begin_audio` and audio tokens)
 /// * `audio` - Processed audio data after resampling and padding
-#[derive(Debug, Clone)]
-pub struct AudioEncoding {
+/// * `token_time_ranges` - Start and end time in seconds of each `[AUDIO]` token,
+///   in order; the leading `begin_audio` token has no entry
+#[cfg(feature = "audio")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct AudioEncoding<S: AudioSample = f32> {
     pub tokens: Vec<u32>,
-    pub audio: Audio,
+    pub audio: Audio<S>,
+    #[serde(default)]
+    pub token_time_ranges: Vec<(f64, f64)>,
 }
 
 /// Encoder for converting audio data into token sequences.
@@ -547,47 +988,285 @@ impl AudioEncoder {
     /// println!("Audio encoded to {} tokens", encoding.tokens.len());
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    #[allow(
-        clippy::cast_possible_truncation,
-        clippy::cast_sign_loss,
-        clippy::cast_precision_loss
-    )]
-    pub fn encode(&self, mut audio: Audio) -> Result<AudioEncoding> {
+    #[cfg(feature = "audio")]
+    pub fn encode<S: AudioSample>(&self, mut audio: Audio<S>) -> Result<AudioEncoding<S>> {
         // Resample to target sampling rate
         audio.resample(self.config.sampling_rate)?;
 
         // Pad audio if needed
         audio.pad(&self.config)?;
 
-        let signal_length = audio.audio_array.len();
+        let num_audio_tokens = self
+            .config
+            .expected_tokens_for_samples(audio.audio_array.len());
 
-        // Calculate signal length after downsampling for spectrogram
-        let signal_length = if signal_length % self.config.audio_encoding_config.hop_length != 0 {
-            #[allow(
-                clippy::cast_possible_truncation,
-                clippy::cast_sign_loss,
-                clippy::cast_precision_loss
-            )]
-            {
-                (signal_length as f64 / self.config.audio_encoding_config.hop_length as f64 - 1.0)
-                    .ceil() as usize
-            }
+        let mut tokens = vec![self.begin_audio_token_id];
+        tokens.extend(vec![self.audio_token_id; num_audio_tokens]);
+
+        let token_time_ranges = self.token_time_ranges(0, num_audio_tokens, audio.duration());
+
+        Ok(AudioEncoding {
+            tokens,
+            audio,
+            token_time_ranges,
+        })
+    }
+}
+
+#[cfg(feature = "audio")]
+impl AudioEncoder {
+    /// Encodes audio after changing its speed by `factor`.
+    ///
+    /// The audio is resampled to the target sampling rate, speed-perturbed with
+    /// [`Audio::perturb_speed`] and then encoded, so the returned encoding holds
+    /// the perturbed audio and the token count for its new duration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `factor` is invalid or audio processing fails.
+    pub fn encode_with_speed<S: AudioSample>(
+        &self,
+        mut audio: Audio<S>,
+        factor: f64,
+    ) -> Result<AudioEncoding<S>> {
+        audio.resample(self.config.sampling_rate)?;
+        audio.perturb_speed(factor)?;
+        self.encode(audio)
+    }
+
+    /// Number of samples at the target sampling rate covered by one `[AUDIO]` token.
+    fn samples_per_encoded_token(&self) -> usize {
+        self.config.audio_length_per_tok() * self.config.audio_encoding_config.hop_length
+    }
+
+    /// Time ranges of `count` tokens starting at token `first`, none ending after `end`.
+    ///
+    /// Each token covers `audio_length_per_tok` spectrogram hops.
+    #[allow(clippy::cast_precision_loss)]
+    fn token_time_ranges(&self, first: usize, count: usize, end: f64) -> Vec<(f64, f64)> {
+        let samples_per_token = self.samples_per_encoded_token() as f64;
+        let sampling_rate = self.config.sampling_rate as f64;
+        (first..first + count)
+            .map(|i| {
+                let token_end = ((i + 1) as f64 * samples_per_token / sampling_rate).min(end);
+                (
+                    (i as f64 * samples_per_token / sampling_rate).min(token_end),
+                    token_end,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Incremental audio encoder for audio that arrives in pieces, such as a live
+/// recording.
+///
+/// Samples at the encoder's target sampling rate are buffered with
+/// [`StreamingAudioEncoder::push`]; [`StreamingAudioEncoder::pop`] turns every
+/// complete token's worth of samples into `[AUDIO]` tokens. The first batch
+/// starts with the `begin_audio` token, and time ranges count from the first
+/// pushed sample, so concatenating all batches gives one continuous encoding.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use tekken::tekkenizer::Tekkenizer;
+/// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// # let chunks: Vec<Vec<f32>> = Vec::new();
+/// let mut stream = tokenizer.streaming_audio_encoder()?;
+/// for chunk in chunks {
+///     stream.push(&chunk);
+///     while let Some(batch) = stream.pop(1) {
+///         println!("{} new tokens", batch.tokens.len());
+///     }
+/// }
+/// if let Some(batch) = stream.finish() {
+///     println!("{} final tokens", batch.tokens.len());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "audio")]
+#[derive(Debug, Clone)]
+pub struct StreamingAudioEncoder {
+    encoder: AudioEncoder,
+    pending: Vec<f32>,
+    num_tokens: usize,
+}
+
+#[cfg(feature = "audio")]
+impl StreamingAudioEncoder {
+    /// Creates a streaming encoder that emits tokens as configured by `encoder`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the encoder's configuration is invalid (see
+    /// [`AudioConfig::validate`]).
+    pub fn new(encoder: AudioEncoder) -> Result<Self> {
+        encoder.config.validate()?;
+        Ok(Self {
+            encoder,
+            pending: Vec::new(),
+            num_tokens: 0,
+        })
+    }
+
+    /// Returns the audio configuration samples must match.
+    #[must_use]
+    pub fn config(&self) -> &AudioConfig {
+        &self.encoder.config
+    }
+
+    /// Buffers mono samples at [`AudioConfig::sampling_rate`].
+    pub fn push(&mut self, samples: &[f32]) {
+        self.pending.extend_from_slice(samples);
+    }
+
+    /// Number of `[AUDIO]` tokens emitted so far.
+    #[must_use]
+    pub fn num_tokens(&self) -> usize {
+        self.num_tokens
+    }
+
+    /// Encodes all complete tokens in the buffer, if there are at least `min_tokens`.
+    ///
+    /// Samples of a trailing partial token stay buffered for the next call.
+    ///
+    /// # Returns
+    ///
+    /// The new tokens with the audio they cover, or `None` if fewer than
+    /// `min_tokens` (and at least one) complete tokens are buffered.
+    pub fn pop(&mut self, min_tokens: usize) -> Option<AudioEncoding> {
+        let count = self.pending.len() / self.encoder.samples_per_encoded_token();
+        if count == 0 || count < min_tokens {
+            return None;
+        }
+        let samples: Vec<f32> = self
+            .pending
+            .drain(..count * self.encoder.samples_per_encoded_token())
+            .collect();
+        let num_samples = samples.len();
+        Some(self.emit(samples, count, num_samples))
+    }
+
+    /// Encodes the remaining samples, padding the last token with silence.
+    ///
+    /// # Returns
+    ///
+    /// The final tokens, or `None` if no samples are buffered.
+    #[must_use]
+    pub fn finish(mut self) -> Option<AudioEncoding> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let samples_per_token = self.encoder.samples_per_encoded_token();
+        let count = self.pending.len().div_ceil(samples_per_token);
+        let mut samples = std::mem::take(&mut self.pending);
+        let num_samples = samples.len();
+        samples.resize(count * samples_per_token, 0.0);
+        Some(self.emit(samples, count, num_samples))
+    }
+
+    /// Emits `count` tokens covering `samples`, of which the first `num_samples`
+    /// are recorded audio and the rest is padding.
+    fn emit(&mut self, samples: Vec<f32>, count: usize, num_samples: usize) -> AudioEncoding {
+        let mut tokens = Vec::with_capacity(count + 1);
+        if self.num_tokens == 0 {
+            tokens.push(self.encoder.begin_audio_token_id);
+        }
+        tokens.extend(std::iter::repeat_n(self.encoder.audio_token_id, count));
+
+        let samples_before = self.num_tokens * self.encoder.samples_per_encoded_token();
+        #[allow(clippy::cast_precision_loss)]
+        let end = (samples_before + num_samples) as f64 / self.encoder.config.sampling_rate as f64;
+        let token_time_ranges = self.encoder.token_time_ranges(self.num_tokens, count, end);
+        self.num_tokens += count;
+
+        AudioEncoding {
+            tokens,
+            audio: Audio::new(
+                Array1::from(samples),
+                self.encoder.config.sampling_rate,
+                "pcm".to_string(),
+            ),
+            token_time_ranges,
+        }
+    }
+}
+
+/// Compact serde representation of audio samples as little-endian bytes.
+#[cfg(feature = "audio")]
+mod sample_bytes {
+    use std::fmt;
+    use std::marker::PhantomData;
+
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use ndarray::Array1;
+    use serde::de::{self, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    use super::AudioSample;
+
+    pub(super) fn serialize<T: AudioSample, S: Serializer>(
+        samples: &Array1<T>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut bytes = Vec::with_capacity(samples.len() * T::BYTES);
+        for &sample in samples {
+            sample.write_le_bytes(&mut bytes);
+        }
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&STANDARD.encode(bytes))
         } else {
-            signal_length / self.config.audio_encoding_config.hop_length
-        };
+            serializer.serialize_bytes(&bytes)
+        }
+    }
+
+    pub(super) fn deserialize<'de, T: AudioSample, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Array1<T>, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(SampleVisitor(PhantomData))
+        } else {
+            deserializer.deserialize_byte_buf(SampleVisitor(PhantomData))
+        }
+    }
 
-        #[allow(
-            clippy::cast_possible_truncation,
-            clippy::cast_sign_loss,
-            clippy::cast_precision_loss
-        )]
-        let num_audio_tokens =
-            (signal_length as f64 / self.config.audio_length_per_tok() as f64).ceil() as usize;
+    struct SampleVisitor<T>(PhantomData<T>);
 
-        let mut tokens = vec![self.begin_audio_token_id];
-        tokens.extend(vec![self.audio_token_id; num_audio_tokens]);
+    impl<'de, T: AudioSample> Visitor<'de> for SampleVisitor<T> {
+        type Value = Array1<T>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "little-endian {}-byte samples as bytes or a base64 string",
+                T::BYTES
+            )
+        }
+
+        fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+            let bytes = STANDARD.decode(value).map_err(E::custom)?;
+            self.visit_bytes(&bytes)
+        }
+
+        fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+            if !bytes.len().is_multiple_of(T::BYTES) {
+                return Err(E::invalid_length(
+                    bytes.len(),
+                    &format!("a multiple of {} bytes", T::BYTES).as_str(),
+                ));
+            }
+            Ok(bytes.chunks_exact(T::BYTES).map(T::read_le_bytes).collect())
+        }
 
-        Ok(AudioEncoding { tokens, audio })
+        fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(byte) = seq.next_element::<u8>()? {
+                bytes.push(byte);
+            }
+            self.visit_bytes(&bytes)
+        }
     }
 }
 
@@ -680,6 +1359,7 @@ pub fn mel_to_hertz(mel: f64) -> f64 {
 /// println!("Filter bank shape: {:?}", filter_bank.dim());
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
+#[cfg(feature = "audio")]
 #[allow(clippy::cast_precision_loss)]
 pub fn mel_filter_bank(
     num_frequency_bins: usize,
@@ -746,3 +1426,110 @@ pub fn mel_filter_bank(
 
     Ok(filter_bank)
 }
+
+/// Computes the normalized log-mel spectrogram fed to the audio encoder.
+///
+/// Follows the Whisper feature extractor used by Mistral audio models: a centered
+/// STFT with a periodic Hann window of `window_size` samples and reflect padding,
+/// the power spectrum projected onto a Slaney mel filter bank up to the Nyquist
+/// frequency, then `log10` clamped to 8 decades below the maximum and rescaled by
+/// `(x + 4) / 4`. As in Whisper, the last STFT frame is dropped.
+///
+/// # Arguments
+///
+/// * `audio` - The waveform, already resampled to the model's sampling rate
+/// * `config` - Mel bin count, hop length and window size
+///
+/// # Returns
+///
+/// An array of shape `(num_mel_bins, num_frames)`.
+///
+/// # Errors
+///
+/// Returns an error if the audio is not longer than half a window, or the
+/// configuration produces an invalid filter bank.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::audio::{Audio, AudioSpectrogramConfig, log_mel_spectrogram};
+///
+/// let audio = Audio::from_file("audio.wav")?;
+/// let config = AudioSpectrogramConfig::new(128, 160, 400)?;
+/// let features = log_mel_spectrogram(&audio, &config)?;
+/// assert_eq!(features.nrows(), 128);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "audio")]
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+pub fn log_mel_spectrogram<S: AudioSample>(
+    audio: &Audio<S>,
+    config: &AudioSpectrogramConfig,
+) -> Result<ndarray::Array2<S>> {
+    let n_fft = config.window_size;
+    let hop = config.hop_length;
+    let pad = n_fft / 2;
+    let samples = audio.audio_array.as_slice().map_or_else(
+        || std::borrow::Cow::Owned(audio.audio_array.to_vec()),
+        std::borrow::Cow::Borrowed,
+    );
+    if samples.len() <= pad {
+        return Err(TokenizerError::Audio(format!(
+            "Audio of {} samples is too short for a window of {n_fft}",
+            samples.len()
+        )));
+    }
+
+    // Reflect padding, as in `torch.stft(center=True)`
+    let mut padded = Vec::with_capacity(samples.len() + 2 * pad);
+    padded.extend((1..=pad).rev().map(|i| samples[i]));
+    padded.extend_from_slice(&samples);
+    padded.extend((0..pad).map(|i| samples[samples.len() - 2 - i]));
+
+    let num_frames = 1 + (padded.len() - n_fft) / hop;
+    let num_bins = n_fft / 2 + 1;
+    let window: Vec<S> = (0..n_fft)
+        .map(|i| {
+            S::from_f64_lossy(
+                0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n_fft as f64).cos(),
+            )
+        })
+        .collect();
+    let filters = mel_filter_bank(
+        num_bins,
+        config.num_mel_bins,
+        0.0,
+        audio.sampling_rate as f64 / 2.0,
+        audio.sampling_rate,
+    )?
+    .mapv(S::from_f64_lossy);
+
+    let fft = rustfft::FftPlanner::<S>::new().plan_fft_forward(n_fft);
+    let mut buffer = vec![rustfft::num_complex::Complex::<S>::default(); n_fft];
+    // The last frame is dropped, matching Whisper's `stft[..., :-1]`
+    let mut power = ndarray::Array2::<S>::zeros((num_bins, num_frames - 1));
+    for frame in 0..num_frames - 1 {
+        let start = frame * hop;
+        for (slot, (&sample, &weight)) in buffer
+            .iter_mut()
+            .zip(padded[start..start + n_fft].iter().zip(&window))
+        {
+            *slot = rustfft::num_complex::Complex::new(sample * weight, S::zero());
+        }
+        fft.process(&mut buffer);
+        for (bin, value) in buffer[..num_bins].iter().enumerate() {
+            power[[bin, frame]] = value.norm_sqr();
+        }
+    }
+
+    let mut log_spec = filters.t().dot(&power);
+    let (floor, range, offset) = (
+        S::from_f64_lossy(1e-10),
+        S::from_f64_lossy(8.0),
+        S::from_f64_lossy(4.0),
+    );
+    log_spec.mapv_inplace(|value| value.max(floor).log10());
+    let max = log_spec.fold(S::neg_infinity(), |max, &value| max.max(value));
+    log_spec.mapv_inplace(|value| (value.max(max - range) + offset) / offset);
+    Ok(log_spec)
+}
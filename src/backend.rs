@@ -0,0 +1,241 @@
+//! Pluggable BPE encoding engines.
+//!
+//! By default a [`Tekkenizer`] encodes text with its built-in byte-level BPE
+//! engine. Any type implementing [`BpeBackend`] can be installed instead with
+//! [`Tekkenizer::with_backend`], for example to cross-check results or to match
+//! the regex semantics of another runtime:
+//!
+//! - [`TiktokenBackend`] (feature `tiktoken`): tiktoken-rs with `fancy-regex`
+//! - [`HfTokenizersBackend`] (feature `hf-tokenizers`): the Hugging Face
+//!   `tokenizers` crate with Oniguruma regexes
+//!
+//! Backends only encode; decoding and vocabulary queries always use the
+//! tokenizer's own tables, so the rest of the [`Tekkenizer`] API is unchanged.
+
+use crate::errors::Result;
+use crate::tekkenizer::Tekkenizer;
+
+/// An engine that splits text into BPE ranks.
+///
+/// Implementations produce raw ranks (vocabulary indices before the special-token
+/// offset), exactly as listed in [`Tekkenizer::mergeable_ranks`]. A backend must be
+/// built for the vocabulary of the tokenizer it is installed on.
+pub trait BpeBackend: Send + Sync {
+    /// Short name of the backend, for diagnostics.
+    fn name(&self) -> &str;
+
+    /// Encodes text without special-token handling.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to encode
+    /// * `ranks` - Buffer the raw ranks are appended to
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the engine fails to encode the text.
+    fn encode_ordinary(&self, text: &str, ranks: &mut Vec<u32>) -> Result<()>;
+}
+
+impl Tekkenizer {
+    /// Replaces the encoding engine of this tokenizer.
+    ///
+    /// Tokenizers derived from this one (e.g. with [`Tekkenizer::pruned`]) start
+    /// again with the built-in engine. Encoding fails with
+    /// [`TokenizerError::TokenOutOfRange`](crate::TokenizerError::TokenOutOfRange)
+    /// if the backend returns a rank outside this tokenizer's vocabulary.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # #[cfg(feature = "tiktoken")]
+    /// # {
+    /// use tekken::backend::TiktokenBackend;
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let backend = TiktokenBackend::new(&tokenizer)?;
+    /// let tokenizer = tokenizer.with_backend(backend);
+    /// assert_eq!(tokenizer.backend_name(), "tiktoken");
+    /// # }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn with_backend<B: BpeBackend + 'static>(mut self, backend: B) -> Self {
+        self.set_backend(Box::new(backend));
+        self
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+pub use self::tiktoken::TiktokenBackend;
+
+#[cfg(feature = "tiktoken")]
+mod tiktoken {
+    use rustc_hash::FxHashMap;
+    use tiktoken_rs::CoreBPE;
+
+    use super::BpeBackend;
+    use crate::errors::{Result, TokenizerError};
+    use crate::tekkenizer::Tekkenizer;
+
+    /// Encoding backend built on tiktoken-rs.
+    pub struct TiktokenBackend {
+        bpe: CoreBPE,
+    }
+
+    impl TiktokenBackend {
+        /// Builds a tiktoken engine for the vocabulary and pattern of `tokenizer`.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if tiktoken rejects the pattern.
+        pub fn new(tokenizer: &Tekkenizer) -> Result<Self> {
+            let ranks: FxHashMap<Vec<u8>, u32> = tokenizer
+                .mergeable_ranks()
+                .map(|(bytes, rank)| (bytes.to_vec(), rank))
+                .collect();
+            let bpe =
+                CoreBPE::new(ranks, FxHashMap::default(), tokenizer.pattern()).map_err(|e| {
+                    TokenizerError::InvalidConfig(format!("Failed to create CoreBPE: {e}"))
+                })?;
+            Ok(Self { bpe })
+        }
+    }
+
+    impl BpeBackend for TiktokenBackend {
+        fn name(&self) -> &'static str {
+            "tiktoken"
+        }
+
+        fn encode_ordinary(&self, text: &str, ranks: &mut Vec<u32>) -> Result<()> {
+            ranks.extend(self.bpe.encode_ordinary(text));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "hf-tokenizers")]
+pub use self::hf::HfTokenizersBackend;
+
+#[cfg(feature = "hf-tokenizers")]
+mod hf {
+    use std::collections::HashMap;
+
+    use tokenizers::models::bpe::{BPE, Vocab};
+    use tokenizers::pre_tokenizers::byte_level::ByteLevel;
+    use tokenizers::pre_tokenizers::sequence::Sequence;
+    use tokenizers::pre_tokenizers::split::{Split, SplitPattern};
+    use tokenizers::{SplitDelimiterBehavior, Tokenizer};
+
+    use super::BpeBackend;
+    use crate::errors::{Result, TokenizerError};
+    use crate::tekkenizer::Tekkenizer;
+
+    /// Encoding backend built on the Hugging Face `tokenizers` crate.
+    ///
+    /// The vocabulary is converted to a byte-level BPE model whose merges are
+    /// ordered by the rank of the merged token, which reproduces tiktoken-style
+    /// merging.
+    pub struct HfTokenizersBackend {
+        tokenizer: Tokenizer,
+    }
+
+    impl HfTokenizersBackend {
+        /// Builds a `tokenizers` engine for the vocabulary and pattern of `tokenizer`.
+        ///
+        /// # Errors
+        ///
+        /// Returns an error if the pattern or the converted model is rejected.
+        pub fn new(tokenizer: &Tekkenizer) -> Result<Self> {
+            let byte_chars = byte_chars();
+            let to_unicode = |bytes: &[u8]| -> String {
+                bytes.iter().map(|&b| byte_chars[b as usize]).collect()
+            };
+
+            let token_bytes: Vec<&[u8]> = tokenizer
+                .mergeable_ranks()
+                .map(|(bytes, _)| bytes)
+                .collect();
+            let ranks: HashMap<&[u8], u32> = tokenizer.mergeable_ranks().collect();
+            let vocab: Vocab = tokenizer
+                .mergeable_ranks()
+                .map(|(bytes, rank)| (to_unicode(bytes), rank))
+                .collect();
+
+            // Every split of a token into two tokens is a merge, applied in token rank order
+            let mut merges: Vec<(u32, u32, u32)> = Vec::new();
+            for (bytes, rank) in tokenizer.mergeable_ranks() {
+                for split in 1..bytes.len() {
+                    let (left, right) = bytes.split_at(split);
+                    if let (Some(&l), Some(&r)) = (ranks.get(left), ranks.get(right)) {
+                        merges.push((rank, l, r));
+                    }
+                }
+            }
+            merges.sort_unstable();
+            let merges = merges
+                .into_iter()
+                .map(|(_, l, r)| {
+                    (
+                        to_unicode(token_bytes[l as usize]),
+                        to_unicode(token_bytes[r as usize]),
+                    )
+                })
+                .collect();
+
+            let model = BPE::builder()
+                .vocab_and_merges(vocab, merges)
+                .ignore_merges(true)
+                .build()
+                .map_err(|e| TokenizerError::Tokenizers(e.to_string()))?;
+            let split = Split::new(
+                SplitPattern::Regex(tokenizer.pattern().to_string()),
+                SplitDelimiterBehavior::Isolated,
+                false,
+            )
+            .map_err(|e| TokenizerError::Tokenizers(e.to_string()))?;
+
+            let mut hf_tokenizer = Tokenizer::new(model);
+            hf_tokenizer.with_pre_tokenizer(Some(Sequence::new(vec![
+                split.into(),
+                ByteLevel::new(false, false, false).into(),
+            ])));
+            Ok(Self {
+                tokenizer: hf_tokenizer,
+            })
+        }
+    }
+
+    impl BpeBackend for HfTokenizersBackend {
+        fn name(&self) -> &'static str {
+            "hf-tokenizers"
+        }
+
+        fn encode_ordinary(&self, text: &str, ranks: &mut Vec<u32>) -> Result<()> {
+            let encoding = self
+                .tokenizer
+                .encode_fast(text, false)
+                .map_err(|e| TokenizerError::Tokenizers(e.to_string()))?;
+            ranks.extend_from_slice(encoding.get_ids());
+            Ok(())
+        }
+    }
+
+    /// GPT-2 byte-to-character table used by byte-level BPE vocabularies.
+    fn byte_chars() -> [char; 256] {
+        let mut chars = ['\0'; 256];
+        let mut next_unprintable = 256;
+        for byte in 0..=u8::MAX {
+            let printable = matches!(byte, b'!'..=b'~' | 0xA1..=0xAC | 0xAE..=0xFF);
+            let code = if printable {
+                u32::from(byte)
+            } else {
+                next_unprintable += 1;
+                next_unprintable - 1
+            };
+            chars[byte as usize] = char::from_u32(code).unwrap_or('\0');
+        }
+        chars
+    }
+}
@@ -0,0 +1,235 @@
+//! Throughput measurement for comparing tokenizer configurations.
+//!
+//! [`measure_encode_throughput`] and [`measure_decode_throughput`] time batch
+//! encoding and decoding of a user-supplied corpus, so the effect of a
+//! [`ParallelismConfig`], a [`BpeBackend`](crate::backend::BpeBackend) or a
+//! pruned vocabulary can be compared on the hardware that will run it. The
+//! criterion benches in `benches/` use the same workloads to catch regressions
+//! in the crate itself.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tekken::bench::{ThroughputOptions, measure_encode_throughput};
+//! use tekken::parallel::ParallelismConfig;
+//! use tekken::tekkenizer::Tekkenizer;
+//!
+//! let tokenizer = Tekkenizer::from_file("tekken.json")?;
+//! let corpus = vec!["The quick brown fox jumps over the lazy dog.".repeat(100); 256];
+//!
+//! for (name, parallelism) in [
+//!     ("sequential", ParallelismConfig::sequential()),
+//!     ("parallel", ParallelismConfig::default()),
+//! ] {
+//!     let options = ThroughputOptions::default().with_parallelism(parallelism);
+//!     println!("{name}: {}", measure_encode_throughput(&tokenizer, &corpus, &options)?);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::errors::{Result, TokenizerError};
+use crate::parallel::ParallelismConfig;
+use crate::special_tokens::SpecialTokenPolicy;
+use crate::tekkenizer::Tekkenizer;
+
+/// Settings of a throughput measurement.
+///
+/// # Fields
+///
+/// * `warmup_iterations` - Untimed passes over the corpus, to warm caches and thread pools
+/// * `iterations` - Timed passes over the corpus
+/// * `parallelism` - Threads the batch APIs run on
+#[derive(Debug, Clone)]
+pub struct ThroughputOptions {
+    pub warmup_iterations: usize,
+    pub iterations: usize,
+    pub parallelism: ParallelismConfig,
+}
+
+impl Default for ThroughputOptions {
+    /// One warm-up pass and three timed passes with the default parallelism.
+    fn default() -> Self {
+        Self {
+            warmup_iterations: 1,
+            iterations: 3,
+            parallelism: ParallelismConfig::default(),
+        }
+    }
+}
+
+impl ThroughputOptions {
+    /// Sets the number of untimed warm-up passes.
+    #[must_use]
+    pub fn with_warmup_iterations(mut self, warmup_iterations: usize) -> Self {
+        self.warmup_iterations = warmup_iterations;
+        self
+    }
+
+    /// Sets the number of timed passes.
+    #[must_use]
+    pub fn with_iterations(mut self, iterations: usize) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Sets the threads the batch APIs run on.
+    #[must_use]
+    pub fn with_parallelism(mut self, parallelism: ParallelismConfig) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+}
+
+/// Work done during a throughput measurement and the time it took.
+///
+/// Counts cover all timed passes.
+///
+/// # Fields
+///
+/// * `items` - Texts or token sequences processed
+/// * `bytes` - UTF-8 bytes of text encoded or decoded
+/// * `tokens` - Tokens produced or consumed
+/// * `elapsed` - Total time of the timed passes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Throughput {
+    pub items: usize,
+    pub bytes: usize,
+    pub tokens: usize,
+    pub elapsed: Duration,
+}
+
+impl Throughput {
+    /// Returns the tokens processed per second.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn tokens_per_second(&self) -> f64 {
+        self.tokens as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Returns the bytes of text processed per second.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn bytes_per_second(&self) -> f64 {
+        self.bytes as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Returns the mean time per item.
+    #[must_use]
+    pub fn time_per_item(&self) -> Duration {
+        u32::try_from(self.items)
+            .ok()
+            .filter(|&items| items > 0)
+            .map_or(Duration::ZERO, |items| self.elapsed / items)
+    }
+}
+
+impl fmt::Display for Throughput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} tokens ({} bytes) in {:.3?}: {:.0} tokens/s, {:.2} MB/s",
+            self.tokens,
+            self.bytes,
+            self.elapsed,
+            self.tokens_per_second(),
+            self.bytes_per_second() / 1e6
+        )
+    }
+}
+
+/// Measures how fast a tokenizer encodes a corpus.
+///
+/// Each pass encodes all texts with [`Tekkenizer::encode_batch`], without BOS
+/// or EOS.
+///
+/// # Arguments
+///
+/// * `tokenizer` - The tokenizer to measure
+/// * `texts` - The corpus
+/// * `options` - Warm-up, iterations and parallelism
+///
+/// # Returns
+///
+/// The texts, bytes and tokens encoded by the timed passes and their duration.
+///
+/// # Errors
+///
+/// Returns an error if `options.iterations` is zero or any text fails to encode.
+pub fn measure_encode_throughput<S: AsRef<str> + Sync>(
+    tokenizer: &Tekkenizer,
+    texts: &[S],
+    options: &ThroughputOptions,
+) -> Result<Throughput> {
+    let bytes: usize = texts.iter().map(|text| text.as_ref().len()).sum();
+    measure(options, texts.len(), || {
+        let batch = tokenizer
+            .encode_batch(texts, false, false, &options.parallelism)?
+            .into_complete()?;
+        Ok((bytes, batch.iter().map(Vec::len).sum()))
+    })
+}
+
+/// Measures how fast a tokenizer decodes token sequences.
+///
+/// Each pass decodes all sequences with [`Tekkenizer::decode_batch`], skipping
+/// special tokens.
+///
+/// # Arguments
+///
+/// * `tokenizer` - The tokenizer to measure
+/// * `batch` - The token sequences, e.g. from encoding a corpus
+/// * `options` - Warm-up, iterations and parallelism
+///
+/// # Returns
+///
+/// The sequences, text bytes and tokens decoded by the timed passes and their
+/// duration.
+///
+/// # Errors
+///
+/// Returns an error if `options.iterations` is zero or any sequence fails to
+/// decode.
+pub fn measure_decode_throughput<T: AsRef<[u32]> + Sync>(
+    tokenizer: &Tekkenizer,
+    batch: &[T],
+    options: &ThroughputOptions,
+) -> Result<Throughput> {
+    let tokens: usize = batch.iter().map(|tokens| tokens.as_ref().len()).sum();
+    measure(options, batch.len(), || {
+        let texts = tokenizer
+            .decode_batch(batch, SpecialTokenPolicy::Ignore, &options.parallelism)?
+            .into_complete()?;
+        Ok((texts.iter().map(String::len).sum(), tokens))
+    })
+}
+
+/// Runs `pass` for the warm-up and timed iterations, summing the bytes and
+/// tokens it reports for the timed ones.
+fn measure(
+    options: &ThroughputOptions,
+    items: usize,
+    mut pass: impl FnMut() -> Result<(usize, usize)>,
+) -> Result<Throughput> {
+    if options.iterations == 0 {
+        return Err(TokenizerError::InvalidConfig(
+            "A throughput measurement needs at least one iteration".to_string(),
+        ));
+    }
+    for _ in 0..options.warmup_iterations {
+        pass()?;
+    }
+
+    let mut throughput = Throughput::default();
+    let started = Instant::now();
+    for _ in 0..options.iterations {
+        let (bytes, tokens) = pass()?;
+        throughput.items += items;
+        throughput.bytes += bytes;
+        throughput.tokens += tokens;
+    }
+    throughput.elapsed = started.elapsed();
+    Ok(throughput)
+}
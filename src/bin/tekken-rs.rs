@@ -0,0 +1,88 @@
+//! Command-line utilities for Tekken tokenizer files.
+//!
+//! Usage:
+//!
+//! ```text
+//! tekken-rs diff <old.json> <new.json>
+//! tekken-rs explain <tekken.json> <text>
+//! tekken-rs convert <input> <output>
+//! ```
+//!
+//! `diff` exits with status 1 if the files differ, like `diff(1)`. `explain`
+//! prints the pretokenized pieces of the text and the BPE merges of each.
+//! `convert` (feature `zstd`) converts between `tekken.json` and the binary
+//! format of [`tekken::binary`]: outputs ending in `.json` are written as JSON,
+//! all others as binary.
+
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: tekken-rs diff <old.json> <new.json>
+       tekken-rs explain <tekken.json> <text>
+       tekken-rs convert <input> <output>";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["diff", path_a, path_b] => match tekken::diff(path_a, path_b) {
+            Ok(changes) => {
+                print!("{changes}");
+                if changes.is_empty() {
+                    ExitCode::SUCCESS
+                } else {
+                    ExitCode::from(1)
+                }
+            }
+            Err(e) => {
+                eprintln!("error: {e}");
+                ExitCode::from(2)
+            }
+        },
+        ["explain", path, text] => {
+            match tekken::Tekkenizer::from_file(path).and_then(|tokenizer| tokenizer.explain(text))
+            {
+                Ok(explanation) => {
+                    print!("{explanation}");
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    ExitCode::from(2)
+                }
+            }
+        }
+        #[cfg(feature = "zstd")]
+        ["convert", input, output] => match convert(input, output) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {e}");
+                ExitCode::from(2)
+            }
+        },
+        _ => {
+            eprintln!("{USAGE}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Converts a tokenizer file to JSON or binary, depending on the output name.
+#[cfg(feature = "zstd")]
+fn convert(input: &str, output: &str) -> tekken::Result<()> {
+    let bytes = std::fs::read(input)?;
+    let tokenizer = if tekken::binary::is_binary(&bytes) {
+        tekken::Tekkenizer::from_binary(&bytes)?
+    } else {
+        tekken::Tekkenizer::from_bytes(&bytes)?
+    };
+    if output.ends_with(".json") {
+        tokenizer.save(output)
+    } else {
+        tokenizer.save_binary(output)
+    }
+}
@@ -0,0 +1,246 @@
+//! Compact binary distribution format for tokenizers.
+//!
+//! `tekken.json` stores every token as base64 inside JSON objects, which makes
+//! the file large and slow to parse. The binary format holds the same tokenizer
+//! in a fraction of the space and loads without JSON or base64 decoding of the
+//! vocabulary:
+//!
+//! | Field         | Contents                                              |
+//! |---------------|-------------------------------------------------------|
+//! | magic         | `TKNB`                                                |
+//! | version       | Format version, one byte (currently `1`)              |
+//! | fingerprint   | The 32-byte [`Tekkenizer::fingerprint`]               |
+//! | payload       | One zstd frame, described below                       |
+//!
+//! The decompressed payload holds the [`TiktokenSidecar`] as JSON (special
+//! tokens, configuration and media sections), then the number of tokens and
+//! each token as its rank, its length and its raw bytes. Placeholder ranks of
+//! leniently loaded tokenizers have no token; the configuration lists them, so
+//! they load back as placeholders. All integers in the
+//! payload are unsigned LEB128 varints. The fingerprint is checked after
+//! loading, so a corrupted or tampered file is rejected instead of silently
+//! producing different token IDs. Payloads that decompress to more than
+//! [`MAX_PAYLOAD_LEN`] bytes are rejected without decompressing them further.
+//!
+//! Requires the `zstd` feature. Files are converted on the command line with
+//! `tekken-rs convert tekken.json tekken.tknb`.
+
+use std::io::Read;
+use std::path::Path;
+
+use crate::config::TiktokenSidecar;
+use crate::errors::{Result, TokenizerError};
+use crate::integrity::verify_sidecar;
+use crate::tekkenizer::Tekkenizer;
+
+/// Leading bytes of binary tokenizer files.
+pub const MAGIC: [u8; 4] = *b"TKNB";
+
+/// Version of the binary format written by [`Tekkenizer::to_binary`].
+pub const FORMAT_VERSION: u8 = 1;
+
+/// zstd level of written files; they are written once and loaded many times.
+const COMPRESSION_LEVEL: i32 = 19;
+
+/// Largest decompressed payload accepted by [`Tekkenizer::from_binary`].
+///
+/// Real vocabularies take a few megabytes; the limit keeps a small, malicious
+/// file from decompressing into all available memory.
+pub const MAX_PAYLOAD_LEN: u64 = 256 << 20;
+
+/// Length of the uncompressed header: magic, version and fingerprint.
+const HEADER_LEN: usize = MAGIC.len() + 1 + 32;
+
+/// Checks whether `bytes` start like a binary tokenizer file.
+#[must_use]
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+impl Tekkenizer {
+    /// Serializes the tokenizer into the binary distribution format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or compression fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let bytes = tokenizer.to_binary()?;
+    /// let restored = Tekkenizer::from_binary(&bytes)?;
+    /// assert_eq!(restored.fingerprint(), tokenizer.fingerprint());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn to_binary(&self) -> Result<Vec<u8>> {
+        let sidecar = self.tiktoken_sidecar();
+        let placeholders = &sidecar.config.placeholder_ranks;
+        let num_tokens = self.mergeable_ranks().len() - placeholders.len();
+        let sidecar_json = serde_json::to_vec(&sidecar)?;
+        let mut payload = Vec::with_capacity(sidecar_json.len() + 8 * num_tokens);
+        write_varint(&mut payload, sidecar_json.len() as u64);
+        payload.extend_from_slice(&sidecar_json);
+        write_varint(&mut payload, num_tokens as u64);
+        for (bytes, rank) in self.mergeable_ranks() {
+            if placeholders.binary_search(&(rank as usize)).is_ok() {
+                continue;
+            }
+            write_varint(&mut payload, u64::from(rank));
+            write_varint(&mut payload, bytes.len() as u64);
+            payload.extend_from_slice(bytes);
+        }
+
+        let mut output = Vec::with_capacity(HEADER_LEN + payload.len() / 2);
+        output.extend_from_slice(&MAGIC);
+        output.push(FORMAT_VERSION);
+        output.extend_from_slice(&self.fingerprint());
+        zstd::stream::copy_encode(payload.as_slice(), &mut output, COMPRESSION_LEVEL)?;
+        Ok(output)
+    }
+
+    /// Loads a tokenizer from the binary distribution format.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Contents of a file written by [`Tekkenizer::to_binary`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The data is not a binary tokenizer or uses a newer format version
+    /// - The payload is truncated or malformed, or decompresses to more than
+    ///   [`MAX_PAYLOAD_LEN`] bytes
+    /// - The resulting configuration is invalid
+    /// - The loaded tokenizer does not match the embedded fingerprint
+    pub fn from_binary(bytes: &[u8]) -> Result<Self> {
+        if !is_binary(bytes) || bytes.len() < HEADER_LEN {
+            return Err(TokenizerError::UnsupportedFormat(
+                "Not a binary tokenizer file".to_string(),
+            ));
+        }
+        let version = bytes[MAGIC.len()];
+        if version != FORMAT_VERSION {
+            return Err(TokenizerError::UnsupportedFormat(format!(
+                "Binary tokenizer format version {version} is not supported (expected {FORMAT_VERSION})"
+            )));
+        }
+        let fingerprint = &bytes[MAGIC.len() + 1..HEADER_LEN];
+
+        let mut payload = Vec::new();
+        zstd::stream::Decoder::new(&bytes[HEADER_LEN..])?
+            .take(MAX_PAYLOAD_LEN + 1)
+            .read_to_end(&mut payload)?;
+        if payload.len() as u64 > MAX_PAYLOAD_LEN {
+            return Err(malformed(&format!(
+                "payload decompresses to more than {MAX_PAYLOAD_LEN} bytes"
+            )));
+        }
+        let mut reader = Reader(&payload);
+
+        let sidecar_len = reader.len()?;
+        let sidecar: TiktokenSidecar = serde_json::from_slice(reader.bytes(sidecar_len)?)?;
+        let num_tokens = reader.len()?;
+        let mut vocab = Vec::with_capacity(num_tokens.min(payload.len()));
+        for _ in 0..num_tokens {
+            let rank = reader.len()?;
+            let len = reader.len()?;
+            vocab.push((rank, reader.bytes(len)?));
+        }
+        if !reader.0.is_empty() {
+            return Err(malformed("trailing bytes after the vocabulary"));
+        }
+
+        vocab.sort_unstable_by_key(|&(rank, _)| rank);
+        let tokenizer = Self::from_config_parts(
+            vocab.into_iter(),
+            sidecar.special_tokens,
+            sidecar.config,
+            sidecar.audio,
+            sidecar.image,
+        )?;
+        if tokenizer.fingerprint() != fingerprint {
+            return Err(TokenizerError::InvalidConfig(
+                "Binary tokenizer does not match its embedded fingerprint".to_string(),
+            ));
+        }
+        Ok(tokenizer)
+    }
+
+    /// Loads a tokenizer from a binary file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or does not match its
+    /// checksum sidecar (see [`crate::integrity`]), or under the same
+    /// conditions as [`Tekkenizer::from_binary`].
+    pub fn from_binary_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bytes = std::fs::read(path.as_ref())?;
+        verify_sidecar(path.as_ref(), &bytes)?;
+        Self::from_binary(&bytes)
+    }
+
+    /// Writes the tokenizer to a file in the binary distribution format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails or the file cannot be written.
+    pub fn save_binary<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, self.to_binary()?)?;
+        Ok(())
+    }
+}
+
+fn malformed(detail: &str) -> TokenizerError {
+    TokenizerError::UnsupportedFormat(format!("Malformed binary tokenizer: {detail}"))
+}
+
+/// Appends `value` as an unsigned LEB128 varint.
+// Each byte holds the low seven bits of what is left, which always fit
+#[allow(clippy::cast_possible_truncation)]
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push((value & 0x7f) as u8);
+}
+
+/// Cursor over a decompressed payload.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    /// Reads an unsigned LEB128 varint.
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self
+                .0
+                .split_first()
+                .ok_or_else(|| malformed("truncated payload"))?;
+            self.0 = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(malformed("varint longer than 64 bits"))
+    }
+
+    /// Reads a varint used as a length, rank or count.
+    fn len(&mut self) -> Result<usize> {
+        usize::try_from(self.varint()?).map_err(|_| malformed("length does not fit in memory"))
+    }
+
+    /// Reads the next `len` bytes.
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.0.len() {
+            return Err(malformed("truncated payload"));
+        }
+        let (bytes, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,461 @@
+//! Native byte-level BPE engine.
+//!
+//! Text is split into pieces with the tokenizer's pretokenization pattern, and
+//! each piece is merged bottom-up from single bytes, always applying the
+//! lowest-ranked merge first. This is the same algorithm as tiktoken, so outputs
+//! are identical for the same ranks and pattern, but the pattern, the ranks and
+//! the error types are all owned by this crate.
+//!
+//! The engine only needs `alloc`. With `std` the pattern is compiled with
+//! `fancy-regex`; without it, with the lookaround-free matcher in
+//! [`crate::pattern`].
+
+// Without `std` only the plain encode and decode paths are used
+#![cfg_attr(not(feature = "std"), allow(dead_code))]
+
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+#[cfg(feature = "std")]
+use fancy_regex::Regex;
+
+use crate::errors::{Result, TokenizerError};
+#[cfg(not(feature = "std"))]
+use crate::pattern::Regex;
+use crate::token_id::TokenId;
+
+/// Mergeable ranks keyed by token bytes.
+#[cfg(feature = "std")]
+pub(crate) type RankMap = rustc_hash::FxHashMap<Vec<u8>, u32>;
+/// Mergeable ranks keyed by token bytes.
+#[cfg(not(feature = "std"))]
+pub(crate) type RankMap =
+    hashbrown::HashMap<Vec<u8>, u32, core::hash::BuildHasherDefault<rustc_hash::FxHasher>>;
+
+/// Bytes of placeholder ranks, U+FFFD REPLACEMENT CHARACTER.
+const PLACEHOLDER_BYTES: &[u8] = "\u{FFFD}".as_bytes();
+
+/// Pretokenization pattern used when a tokenizer does not specify one.
+const DEFAULT_PATTERN: &str = r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+";
+
+/// Byte-level BPE encoder and decoder over raw (unshifted) ranks.
+#[derive(Debug, Clone)]
+pub(crate) struct BytePairEncoder {
+    ranks: RankMap,
+    // Token bytes indexed by rank; ranks are contiguous, with placeholder ranks
+    // missing from `ranks`
+    token_bytes: Vec<Vec<u8>>,
+    // Length of the longest token, bounding lattice edges when sampling
+    max_token_len: usize,
+    pattern: Regex,
+}
+
+impl BytePairEncoder {
+    /// Creates an encoder from contiguous mergeable ranks and a pretokenization pattern.
+    ///
+    /// An empty pattern selects a cl100k-style default.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regex.
+    pub(crate) fn new(ranks: RankMap, pattern: &str) -> Result<Self> {
+        Self::with_placeholders(ranks, &[], pattern)
+    }
+
+    /// Creates an encoder whose ranks have gaps filled by placeholders.
+    ///
+    /// `ranks` and `placeholders` together must cover `0..n`. Placeholder ranks
+    /// are never produced by encoding and decode as U+FFFD.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is not a valid regex.
+    pub(crate) fn with_placeholders(
+        ranks: RankMap,
+        placeholders: &[u32],
+        pattern: &str,
+    ) -> Result<Self> {
+        let pattern = if pattern.is_empty() {
+            DEFAULT_PATTERN
+        } else {
+            pattern
+        };
+        let pattern = Regex::new(pattern).map_err(|e| {
+            TokenizerError::InvalidConfig(format!("Invalid pretokenization pattern: {e}"))
+        })?;
+
+        let mut token_bytes = vec![Vec::new(); ranks.len() + placeholders.len()];
+        for (bytes, &rank) in &ranks {
+            token_bytes[rank as usize].clone_from(bytes);
+        }
+        for &rank in placeholders {
+            token_bytes[rank as usize] = PLACEHOLDER_BYTES.to_vec();
+        }
+
+        let max_token_len = token_bytes.iter().map(Vec::len).max().unwrap_or(1);
+
+        Ok(Self {
+            ranks,
+            token_bytes,
+            max_token_len,
+            pattern,
+        })
+    }
+
+    /// The effective pretokenization pattern.
+    pub(crate) fn pattern(&self) -> &str {
+        self.pattern.as_str()
+    }
+
+    /// Number of ranks in the vocabulary.
+    pub(crate) fn len(&self) -> usize {
+        self.token_bytes.len()
+    }
+
+    /// Bytes of the token with the given rank.
+    pub(crate) fn token_bytes(&self, rank: usize) -> Option<&[u8]> {
+        self.token_bytes.get(rank).map(Vec::as_slice)
+    }
+
+    /// All token bytes, indexed by rank.
+    pub(crate) fn all_token_bytes(&self) -> &[Vec<u8>] {
+        &self.token_bytes
+    }
+
+    /// Approximate heap memory held by the rank tables, in bytes.
+    ///
+    /// Counts token bytes twice (once per table) plus the table slots; allocator
+    /// and hash map control overhead is not included.
+    pub(crate) fn heap_size(&self) -> usize {
+        let token_bytes: usize = self.token_bytes.iter().map(Vec::capacity).sum();
+        let slot = core::mem::size_of::<Vec<u8>>();
+        2 * token_bytes
+            + self.token_bytes.capacity() * slot
+            + self.ranks.capacity() * (slot + core::mem::size_of::<u32>())
+    }
+
+    /// Checks whether a rank is a placeholder filling a gap in the vocabulary.
+    pub(crate) fn is_placeholder(&self, rank: usize) -> bool {
+        self.token_bytes
+            .get(rank)
+            .is_some_and(|bytes| self.ranks.get(bytes).is_none_or(|&r| r as usize != rank))
+    }
+
+    /// Rank of a byte sequence, if it is a token.
+    pub(crate) fn rank(&self, bytes: &[u8]) -> Option<u32> {
+        self.ranks.get(bytes).copied()
+    }
+
+    /// Byte offsets where the pretokenized pieces of `text` start.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pretokenization regex fails (e.g. backtracking limit).
+    pub(crate) fn piece_starts(&self, text: &str) -> Result<Vec<usize>> {
+        Ok(self
+            .piece_ranges(text)?
+            .into_iter()
+            .map(|range| range.start)
+            .collect())
+    }
+
+    /// Byte ranges of the non-empty pretokenized pieces of `text`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pretokenization regex fails (e.g. backtracking limit).
+    pub(crate) fn piece_ranges(&self, text: &str) -> Result<Vec<Range<usize>>> {
+        let mut ranges = Vec::new();
+        for piece in self.pattern.find_iter(text) {
+            let piece = piece
+                .map_err(|e| TokenizerError::Tokenizers(format!("Pretokenization failed: {e}")))?;
+            if !piece.as_str().is_empty() {
+                ranges.push(piece.start()..piece.start() + piece.as_str().len());
+            }
+        }
+        Ok(ranges)
+    }
+
+    /// Encodes text without special-token handling, appending `offset + rank` for each token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pretokenization regex fails (e.g. backtracking limit).
+    pub(crate) fn encode_ordinary(
+        &self,
+        text: &str,
+        offset: TokenId,
+        out: &mut Vec<u32>,
+    ) -> Result<()> {
+        self.encode_ordinary_until(text, offset, out, usize::MAX)
+    }
+
+    /// Like [`BytePairEncoder::encode_ordinary`], but stops after the piece that
+    /// brings `out` to at least `max_len` tokens.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pretokenization regex fails (e.g. backtracking limit).
+    pub(crate) fn encode_ordinary_until(
+        &self,
+        text: &str,
+        offset: TokenId,
+        out: &mut Vec<u32>,
+        max_len: usize,
+    ) -> Result<()> {
+        for piece in self.pattern.find_iter(text) {
+            if out.len() >= max_len {
+                break;
+            }
+            let piece = piece
+                .map_err(|e| TokenizerError::Tokenizers(format!("Pretokenization failed: {e}")))?
+                .as_str()
+                .as_bytes();
+            self.encode_piece(piece, offset, out);
+        }
+        Ok(())
+    }
+
+    /// Encodes a single pretokenized piece, which need not be valid UTF-8,
+    /// appending `offset + rank` for each token.
+    pub(crate) fn encode_piece(&self, piece: &[u8], offset: TokenId, out: &mut Vec<u32>) {
+        if piece.is_empty() {
+            return;
+        }
+        if let Some(&rank) = self.ranks.get(piece) {
+            out.push((offset + rank).get());
+        } else {
+            self.merge_piece(piece, offset, out, || true);
+        }
+    }
+
+    /// Encodes text with BPE-dropout, skipping each candidate merge with probability `dropout`.
+    ///
+    /// Whole pieces are merged from single bytes even when they are tokens
+    /// themselves, so every segmentation of a piece can be produced.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pretokenization regex fails (e.g. backtracking limit).
+    pub(crate) fn encode_with_dropout(
+        &self,
+        text: &str,
+        offset: TokenId,
+        out: &mut Vec<u32>,
+        dropout: f64,
+        rng: &mut SplitMix64,
+    ) -> Result<()> {
+        for piece in self.pattern.find_iter(text) {
+            let piece = piece
+                .map_err(|e| TokenizerError::Tokenizers(format!("Pretokenization failed: {e}")))?
+                .as_str()
+                .as_bytes();
+            if piece.is_empty() {
+                continue;
+            }
+            self.merge_piece(piece, offset, out, || rng.next_f64() >= dropout);
+        }
+        Ok(())
+    }
+
+    /// Encodes text by sampling a segmentation of each piece into vocabulary tokens.
+    ///
+    /// Needs `std` for floating-point `exp` and `ln`. A segmentation with `k` tokens is drawn with probability proportional to
+    /// `exp(-k / temperature)`, so low temperatures favour the shortest
+    /// segmentations and high temperatures approach a uniform choice.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pretokenization regex fails (e.g. backtracking limit).
+    #[cfg(feature = "std")]
+    pub(crate) fn encode_sampled(
+        &self,
+        text: &str,
+        offset: TokenId,
+        out: &mut Vec<u32>,
+        temperature: f64,
+        rng: &mut SplitMix64,
+    ) -> Result<()> {
+        for piece in self.pattern.find_iter(text) {
+            let piece = piece
+                .map_err(|e| TokenizerError::Tokenizers(format!("Pretokenization failed: {e}")))?
+                .as_str()
+                .as_bytes();
+            if piece.is_empty() {
+                continue;
+            }
+            self.sample_piece(piece, offset, out, temperature, rng);
+        }
+        Ok(())
+    }
+
+    /// Samples one segmentation of `piece` by forward filtering, backward sampling.
+    #[cfg(feature = "std")]
+    fn sample_piece(
+        &self,
+        piece: &[u8],
+        offset: TokenId,
+        out: &mut Vec<u32>,
+        temperature: f64,
+        rng: &mut SplitMix64,
+    ) {
+        let token_cost = -1.0 / temperature;
+        let edges_into = |end: usize| {
+            (end.saturating_sub(self.max_token_len)..end)
+                .filter_map(move |start| self.rank(&piece[start..end]).map(|rank| (start, rank)))
+        };
+
+        // log_weight[j]: log of the total weight of all segmentations of piece[..j]
+        let mut log_weight = vec![f64::NEG_INFINITY; piece.len() + 1];
+        log_weight[0] = 0.0;
+        for end in 1..=piece.len() {
+            // Every single byte is a token, so each prefix has at least one segmentation
+            log_weight[end] =
+                log_sum_exp(edges_into(end).map(|(start, _)| log_weight[start] + token_cost));
+        }
+
+        let mut sampled = Vec::new();
+        let mut end = piece.len();
+        while end > 0 {
+            let mut threshold = rng.next_f64();
+            let mut choice = None;
+            for (start, rank) in edges_into(end) {
+                choice = Some((start, rank));
+                threshold -= (log_weight[start] + token_cost - log_weight[end]).exp();
+                if threshold < 0.0 {
+                    break;
+                }
+            }
+            // Rounding can leave a sliver of probability; fall back to the last edge
+            let Some((start, rank)) = choice else {
+                break;
+            };
+            sampled.push((offset + rank).get());
+            end = start;
+        }
+        out.extend(sampled.into_iter().rev());
+    }
+
+    /// Applies BPE merges to a single piece and appends the resulting ranks.
+    ///
+    /// `keep_merge` is asked whether each candidate merge may be applied.
+    fn merge_piece(
+        &self,
+        piece: &[u8],
+        offset: TokenId,
+        out: &mut Vec<u32>,
+        keep_merge: impl FnMut() -> bool,
+    ) {
+        let boundaries = self.merge_boundaries(piece, keep_merge, |_, _| {});
+        out.extend(boundaries.windows(2).map(|pair| {
+            // Every merged span is a token: merges only join spans whose union is ranked,
+            // and every single byte is ranked
+            (offset + self.ranks[&piece[pair[0].0..pair[1].0]]).get()
+        }));
+    }
+
+    /// Returns the byte ranges of the parts joined by each merge applied to
+    /// `piece`, in order.
+    ///
+    /// Pieces that are tokens themselves are encoded without merges.
+    pub(crate) fn trace_merges(&self, piece: &[u8]) -> Vec<(Range<usize>, Range<usize>)> {
+        let mut merges = Vec::new();
+        if !piece.is_empty() && !self.ranks.contains_key(piece) {
+            self.merge_boundaries(piece, || true, |left, right| merges.push((left, right)));
+        }
+        merges
+    }
+
+    /// Returns the start offsets of the merged parts of `piece`, plus a final end marker.
+    ///
+    /// Each entry holds a start offset and the rank of merging that part with the next one.
+    /// `on_merge` receives the byte ranges of the two parts of every merge applied.
+    fn merge_boundaries(
+        &self,
+        piece: &[u8],
+        mut keep_merge: impl FnMut() -> bool,
+        mut on_merge: impl FnMut(Range<usize>, Range<usize>),
+    ) -> Vec<(usize, u32)> {
+        let rank_of = |parts: &[(usize, u32)], i: usize| {
+            if i + 3 < parts.len() {
+                self.ranks
+                    .get(&piece[parts[i].0..parts[i + 3].0])
+                    .copied()
+                    .unwrap_or(u32::MAX)
+            } else {
+                u32::MAX
+            }
+        };
+
+        let mut parts = Vec::with_capacity(piece.len() + 1);
+        for i in 0..piece.len() - 1 {
+            let rank = self
+                .ranks
+                .get(&piece[i..i + 2])
+                .copied()
+                .unwrap_or(u32::MAX);
+            parts.push((i, rank));
+        }
+        parts.push((piece.len() - 1, u32::MAX));
+        parts.push((piece.len(), u32::MAX));
+
+        loop {
+            // Candidates that cannot beat the current minimum need no dropout sample
+            let mut min_rank = (u32::MAX, usize::MAX);
+            for (i, &(_, rank)) in parts[..parts.len() - 1].iter().enumerate() {
+                if rank < min_rank.0 && keep_merge() {
+                    min_rank = (rank, i);
+                }
+            }
+            if min_rank.0 == u32::MAX {
+                break;
+            }
+
+            let i = min_rank.1;
+            on_merge(parts[i].0..parts[i + 1].0, parts[i + 1].0..parts[i + 2].0);
+            if i > 0 {
+                parts[i - 1].1 = rank_of(&parts, i - 1);
+            }
+            parts[i].1 = rank_of(&parts, i);
+            parts.remove(i + 1);
+        }
+        parts
+    }
+}
+
+/// Numerically stable `log(sum(exp(x)))`.
+#[cfg(feature = "std")]
+fn log_sum_exp(values: impl Iterator<Item = f64> + Clone) -> f64 {
+    let max = values.clone().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + values.map(|value| (value - max).exp()).sum::<f64>().ln()
+}
+
+/// Small seeded PRNG (`SplitMix64`), so sampled encodings are reproducible across
+/// platforms and dependency versions.
+#[derive(Debug, Clone)]
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform sample from `[0, 1)`.
+    #[allow(clippy::cast_precision_loss)]
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
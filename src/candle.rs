@@ -0,0 +1,139 @@
+//! Model inputs as `candle` tensors.
+//!
+//! These helpers go from text or audio straight to [`candle_core::Tensor`]s on the
+//! chosen device, with the layout candle transformer models expect: token IDs and
+//! masks as `u32` tensors of shape `(batch, seq_len)`, and log-mel features of
+//! shape `(batch, num_mel_bins, num_frames)` in the requested float dtype.
+
+use candle_core::{DType, Device, Tensor};
+
+#[cfg(feature = "audio")]
+use crate::audio::{Audio, log_mel_spectrogram};
+use crate::encoding::Encoding;
+use crate::errors::{Result, TokenizerError};
+use crate::tekkenizer::Tekkenizer;
+
+/// Token IDs and masks of a batch of [`Encoding`]s as `u32` tensors.
+#[derive(Debug, Clone)]
+pub struct EncodingTensors {
+    /// Token IDs, shape `(batch, seq_len)`.
+    pub input_ids: Tensor,
+    /// `1` for real tokens and `0` for padding, shape `(batch, seq_len)`.
+    pub attention_mask: Tensor,
+    /// Segment IDs, shape `(batch, seq_len)`.
+    pub type_ids: Tensor,
+    /// Positions from `0` at each row's first real token and `0` for padding,
+    /// shape `(batch, seq_len)`.
+    pub position_ids: Tensor,
+}
+
+impl EncodingTensors {
+    /// Stacks encodings of equal length into tensors on `device`.
+    ///
+    /// Pad the encodings first, e.g. with [`crate::encoding::Padding::Longest`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the batch is empty, the encodings differ in length, or
+    /// the tensors cannot be created on the device.
+    pub fn from_encodings(encodings: &[Encoding], device: &Device) -> Result<Self> {
+        let Some(seq_len) = encodings.first().map(Encoding::len) else {
+            return Err(TokenizerError::InvalidConfig(
+                "Cannot build tensors from an empty batch".to_string(),
+            ));
+        };
+        if let Some(encoding) = encodings.iter().find(|e| e.len() != seq_len) {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Encodings must have equal lengths to be stacked, got {seq_len} and {}; enable padding",
+                encoding.len()
+            )));
+        }
+
+        let shape = (encodings.len(), seq_len);
+        let stack = |column: fn(&Encoding) -> &[u32]| {
+            let values: Vec<u32> = encodings.iter().flat_map(column).copied().collect();
+            Tensor::from_vec(values, shape, device)
+        };
+        Ok(Self {
+            input_ids: stack(|e| &e.ids)?,
+            attention_mask: stack(|e| &e.attention_mask)?,
+            type_ids: stack(|e| &e.type_ids)?,
+            position_ids: Tensor::from_vec(
+                encodings
+                    .iter()
+                    .flat_map(|encoding| encoding.position_ids(0))
+                    .collect(),
+                shape,
+                device,
+            )?,
+        })
+    }
+}
+
+impl Tekkenizer {
+    /// Encodes text into a `u32` tensor of shape `(1, seq_len)` on `device`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails or the tensor cannot be created.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use candle_core::Device;
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let input_ids = tokenizer.encode_tensor("Hello, world!", true, false, &Device::Cpu)?;
+    /// assert_eq!(input_ids.dims(), &[1, 5]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_tensor(
+        &self,
+        text: &str,
+        add_beginning_of_sequence: bool,
+        add_end_of_sequence: bool,
+        device: &Device,
+    ) -> Result<Tensor> {
+        let ids = self.encode(text, add_beginning_of_sequence, add_end_of_sequence)?;
+        let len = ids.len();
+        Ok(Tensor::from_vec(ids, (1, len), device)?)
+    }
+
+    /// Computes the log-mel features of audio as a tensor for the audio encoder.
+    ///
+    /// The audio is resampled and padded with the tokenizer's audio configuration,
+    /// exactly as for [`Tekkenizer::encode_audio`], before features are computed
+    /// with [`log_mel_spectrogram`]. Requires the `audio` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `audio` - The audio to featurize
+    /// * `dtype` - Float dtype of the model, e.g. `DType::F32` or `DType::BF16`
+    /// * `device` - Device to place the tensor on
+    ///
+    /// # Returns
+    ///
+    /// A tensor of shape `(1, num_mel_bins, num_frames)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tokenizer has no audio configuration, audio
+    /// processing fails, or the tensor cannot be created.
+    #[cfg(feature = "audio")]
+    pub fn audio_features_tensor(
+        &self,
+        audio: Audio,
+        dtype: DType,
+        device: &Device,
+    ) -> Result<Tensor> {
+        let encoding = self.encode_audio(audio)?;
+        let config = self
+            .audio_config()
+            .ok_or_else(|| TokenizerError::Audio("Audio encoder not configured".to_string()))?;
+        let features = log_mel_spectrogram(&encoding.audio, &config.audio_encoding_config)?;
+        let (num_mel_bins, num_frames) = features.dim();
+        let values = features.into_raw_vec_and_offset().0;
+        Ok(Tensor::from_vec(values, (1, num_mel_bins, num_frames), device)?.to_dtype(dtype)?)
+    }
+}
@@ -0,0 +1,237 @@
+//! Live microphone capture to audio tokens.
+//!
+//! [`MicrophoneCapture`] records from an input device through
+//! [cpal](https://docs.rs/cpal), downmixes to mono and feeds the samples into a
+//! [`StreamingAudioEncoder`]. Token batches become available as soon as enough
+//! audio for them has been recorded, so a desktop application can stream a
+//! user's speech into a model while they are still talking.
+//!
+//! The device is opened at the tokenizer's sampling rate; devices that cannot
+//! record at that rate are rejected rather than resampled.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tekken::capture::{CaptureOptions, MicrophoneCapture};
+//! use tekken::tekkenizer::Tekkenizer;
+//!
+//! let tokenizer = Tekkenizer::from_file("tekken.json")?;
+//! let mut capture = MicrophoneCapture::start(&tokenizer, CaptureOptions::default())?;
+//! for batch in capture.by_ref().take(25) {
+//!     let batch = batch?;
+//!     println!("{} tokens up to {:?}", batch.tokens.len(), batch.token_time_ranges.last());
+//! }
+//! let last = capture.stop();
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SampleFormat, SizedSample};
+
+use crate::audio::{AudioEncoding, StreamingAudioEncoder};
+use crate::errors::{Result, TokenizerError};
+use crate::tekkenizer::Tekkenizer;
+
+/// Options for [`MicrophoneCapture::start`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureOptions {
+    /// Name of the input device, or `None` for the host's default input.
+    pub device_name: Option<String>,
+    /// Minimum number of `[AUDIO]` tokens per batch. Larger batches mean fewer,
+    /// later wake-ups; the default of `1` yields a batch per token.
+    pub batch_tokens: usize,
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            device_name: None,
+            batch_tokens: 1,
+        }
+    }
+}
+
+/// Messages from the audio callback thread.
+enum Captured {
+    Samples(Vec<f32>),
+    Error(String),
+}
+
+/// A running recording whose audio is encoded into token batches.
+///
+/// Recording stops when the capture is dropped or [`MicrophoneCapture::stop`] is
+/// called. Iterating blocks until the next batch is ready and ends when the
+/// device stops delivering audio.
+pub struct MicrophoneCapture {
+    stream: cpal::Stream,
+    receiver: Receiver<Captured>,
+    encoder: StreamingAudioEncoder,
+    batch_tokens: usize,
+}
+
+impl MicrophoneCapture {
+    /// Opens an input device and starts recording.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - Tokenizer whose audio configuration and tokens to use
+    /// * `options` - Device and batch size
+    ///
+    /// # Returns
+    ///
+    /// The running capture.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tokenizer has no audio support, the device is not
+    /// found, it cannot record at the tokenizer's sampling rate, or the stream
+    /// fails to start.
+    pub fn start(tokenizer: &Tekkenizer, options: CaptureOptions) -> Result<Self> {
+        let encoder = tokenizer.streaming_audio_encoder()?;
+        let device = find_device(options.device_name.as_deref())?;
+        let sampling_rate = u32::try_from(encoder.config().sampling_rate).map_err(|_| {
+            TokenizerError::Audio(format!(
+                "Sampling rate {} is not supported by audio devices",
+                encoder.config().sampling_rate
+            ))
+        })?;
+        let config = device
+            .supported_input_configs()
+            .map_err(device_error)?
+            .filter(|range| {
+                range.min_sample_rate().0 <= sampling_rate
+                    && sampling_rate <= range.max_sample_rate().0
+            })
+            .min_by_key(|range| (range.sample_format() != SampleFormat::F32, range.channels()))
+            .ok_or_else(|| {
+                TokenizerError::Audio(format!("Input device cannot record at {sampling_rate} Hz"))
+            })?
+            .with_sample_rate(cpal::SampleRate(sampling_rate));
+
+        let (sender, receiver) = mpsc::channel();
+        let stream = match config.sample_format() {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, sender),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, sender),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, sender),
+            SampleFormat::I32 => build_stream::<i32>(&device, &config, sender),
+            format => Err(TokenizerError::Audio(format!(
+                "Unsupported input sample format {format}"
+            ))),
+        }?;
+        stream.play().map_err(device_error)?;
+
+        Ok(Self {
+            stream,
+            receiver,
+            encoder,
+            batch_tokens: options.batch_tokens.max(1),
+        })
+    }
+
+    /// Returns the next batch of tokens, waiting for enough audio to be recorded.
+    ///
+    /// # Returns
+    ///
+    /// The batch, or `None` if the device stopped delivering audio.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device reports a stream error.
+    pub fn next_batch(&mut self) -> Result<Option<AudioEncoding>> {
+        loop {
+            if let Some(batch) = self.encoder.pop(self.batch_tokens) {
+                return Ok(Some(batch));
+            }
+            match self.receiver.recv() {
+                Ok(Captured::Samples(samples)) => self.encoder.push(&samples),
+                Ok(Captured::Error(message)) => return Err(TokenizerError::Audio(message)),
+                Err(_) => return Ok(None),
+            }
+        }
+    }
+
+    /// Stops recording and encodes the audio recorded since the last batch.
+    ///
+    /// # Returns
+    ///
+    /// The final tokens, or `None` if no audio is left.
+    pub fn stop(mut self) -> Option<AudioEncoding> {
+        // Pausing can fail on hosts without pause support; dropping stops the stream either way
+        let _ = self.stream.pause();
+        drop(self.stream);
+        while let Ok(captured) = self.receiver.try_recv() {
+            if let Captured::Samples(samples) = captured {
+                self.encoder.push(&samples);
+            }
+        }
+        self.encoder.finish()
+    }
+}
+
+impl Iterator for MicrophoneCapture {
+    type Item = Result<AudioEncoding>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_batch().transpose()
+    }
+}
+
+/// Finds an input device by name, or the default one.
+fn find_device(name: Option<&str>) -> Result<cpal::Device> {
+    let host = cpal::default_host();
+    match name {
+        None => host
+            .default_input_device()
+            .ok_or_else(|| TokenizerError::Audio("No default input device".to_string())),
+        Some(name) => host
+            .input_devices()
+            .map_err(device_error)?
+            .find(|device| device.name().is_ok_and(|device_name| device_name == name))
+            .ok_or_else(|| TokenizerError::Audio(format!("Input device {name:?} not found"))),
+    }
+}
+
+/// Builds an input stream that sends mono `f32` samples to `sender`.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::SupportedStreamConfig,
+    sender: Sender<Captured>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = usize::from(config.channels()).max(1);
+    let error_sender = sender.clone();
+    #[allow(clippy::cast_precision_loss)]
+    let scale = 1.0 / channels as f32;
+    device
+        .build_input_stream(
+            &config.config(),
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let samples = data
+                    .chunks(channels)
+                    .map(|frame| {
+                        frame
+                            .iter()
+                            .map(|&sample| sample.to_sample::<f32>())
+                            .sum::<f32>()
+                            * scale
+                    })
+                    .collect();
+                // The receiver is gone once the capture is stopped
+                let _ = sender.send(Captured::Samples(samples));
+            },
+            move |error| {
+                let _ = error_sender.send(Captured::Error(error.to_string()));
+            },
+            None,
+        )
+        .map_err(device_error)
+}
+
+fn device_error(error: impl std::fmt::Display) -> TokenizerError {
+    TokenizerError::Audio(format!("Audio device error: {error}"))
+}
@@ -0,0 +1,380 @@
+//! Batching of tokenized requests for multimodal inference.
+//!
+//! [`Tekkenizer::collate`] turns several [`MultimodalEncoding`]s, such as
+//! [`TokenizedRequest`]s, into the arrays a model forward pass takes: token IDs
+//! padded to a common length with their attention mask and position IDs, the
+//! log-mel features of all audio and the pixels of all images, each zero-padded
+//! into one array with their real sizes, and the positions of the `[AUDIO]` and
+//! `[IMG]` tokens the embeddings are scattered into.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tekken::encoding::PaddingSide;
+//! use tekken::request::ChatCompletionRequest;
+//! use tekken::tekkenizer::Tekkenizer;
+//!
+//! let tokenizer = Tekkenizer::from_file("tekken.json")?;
+//! let requests: Vec<ChatCompletionRequest> = serde_json::from_str(&std::fs::read_to_string("requests.json")?)?;
+//! let tokenized = requests
+//!     .into_iter()
+//!     .map(|request| tokenizer.encode_request(request))
+//!     .collect::<Result<Vec<_>, _>>()?;
+//! // Left padding, for batched generation with a decoder-only model
+//! let batch = tokenizer.collate(&tokenized, PaddingSide::Left)?;
+//! println!("{} x {} tokens", batch.input_ids.len(), batch.max_length());
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#[cfg(feature = "audio")]
+use ndarray::Array3;
+#[cfg(feature = "image")]
+use ndarray::Array4;
+#[cfg(any(feature = "audio", feature = "image"))]
+use ndarray::s;
+
+#[cfg(feature = "audio")]
+use crate::audio::{AudioEncoding, log_mel_spectrogram};
+use crate::encoding::{PaddingSide, pad, position_ids};
+use crate::errors::{Result, TokenizerError};
+#[cfg(feature = "image")]
+use crate::image::ImageEncoding;
+use crate::prompt::PromptEncoding;
+use crate::request::TokenizedRequest;
+#[cfg(any(feature = "audio", feature = "image"))]
+use crate::special_tokens::SpecialTokens;
+use crate::tekkenizer::Tekkenizer;
+
+/// Token IDs together with the audio and images they embed.
+///
+/// This is the input of [`Tekkenizer::collate`]. It is implemented by
+/// [`TokenizedRequest`], and by [`PromptEncoding`] for text-only prompts.
+pub trait MultimodalEncoding {
+    /// Returns the token IDs, including the `[AUDIO]` and `[IMG]` placeholders.
+    fn tokens(&self) -> &[u32];
+
+    /// Returns the encoded audio, in the order it appears in the tokens.
+    #[cfg(feature = "audio")]
+    fn audios(&self) -> &[AudioEncoding] {
+        &[]
+    }
+
+    /// Returns the encoded images, in the order they appear in the tokens.
+    #[cfg(feature = "image")]
+    fn images(&self) -> &[ImageEncoding] {
+        &[]
+    }
+}
+
+impl MultimodalEncoding for TokenizedRequest {
+    fn tokens(&self) -> &[u32] {
+        &self.tokens
+    }
+
+    #[cfg(feature = "audio")]
+    fn audios(&self) -> &[AudioEncoding] {
+        &self.audios
+    }
+
+    #[cfg(feature = "image")]
+    fn images(&self) -> &[ImageEncoding] {
+        &self.images
+    }
+}
+
+impl MultimodalEncoding for PromptEncoding {
+    fn tokens(&self) -> &[u32] {
+        &self.tokens
+    }
+}
+
+/// Multimodal encodings padded into a batch, from [`Tekkenizer::collate`].
+///
+/// Rows of `input_ids` and `attention_mask` follow the order of the encodings;
+/// audio and images follow the order of the encodings, then the order within
+/// each encoding.
+///
+/// # Fields
+///
+/// * `input_ids` - Token IDs of each encoding, padded to the longest
+/// * `attention_mask` - `1` for real tokens, `0` for padding
+/// * `position_ids` - Position of each real token in its encoding, counted from
+///   `0` at its first token; `0` for padding. Use
+///   [`CollatedBatch::position_ids_from`] for rows continuing cached prefixes
+/// * `lengths` - Number of real tokens of each encoding
+/// * `audio_features` - Log-mel spectrograms of all audio, shaped
+///   `(num_audio, num_mel_bins, num_frames)` and zero-padded to the longest
+/// * `audio_frames` - Number of real frames of each spectrogram
+/// * `audio_requests` - Index of the encoding each audio belongs to
+/// * `audio_positions` - Positions of the `[AUDIO]` tokens in each row of `input_ids`
+/// * `pixel_values` - Normalized pixels of all images, shaped
+///   `(num_images, 3, height, width)` and zero-padded at the bottom and right
+///   to the largest
+/// * `image_sizes` - `(height, width)` in pixels of each image
+/// * `image_requests` - Index of the encoding each image belongs to
+/// * `image_positions` - Positions of the `[IMG]` tokens in each row of `input_ids`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollatedBatch {
+    pub input_ids: Vec<Vec<u32>>,
+    pub attention_mask: Vec<Vec<u32>>,
+    pub position_ids: Vec<Vec<u32>>,
+    pub lengths: Vec<usize>,
+    #[cfg(feature = "audio")]
+    pub audio_features: Array3<f32>,
+    #[cfg(feature = "audio")]
+    pub audio_frames: Vec<usize>,
+    #[cfg(feature = "audio")]
+    pub audio_requests: Vec<usize>,
+    #[cfg(feature = "audio")]
+    pub audio_positions: Vec<Vec<usize>>,
+    #[cfg(feature = "image")]
+    pub pixel_values: Array4<f32>,
+    #[cfg(feature = "image")]
+    pub image_sizes: Vec<(usize, usize)>,
+    #[cfg(feature = "image")]
+    pub image_requests: Vec<usize>,
+    #[cfg(feature = "image")]
+    pub image_positions: Vec<Vec<usize>>,
+}
+
+impl CollatedBatch {
+    /// Returns the padded length of the rows.
+    #[must_use]
+    pub fn max_length(&self) -> usize {
+        self.input_ids.first().map_or(0, Vec::len)
+    }
+
+    /// Returns position IDs for rows that continue cached prefixes.
+    ///
+    /// # Arguments
+    ///
+    /// * `offsets` - Number of tokens already in the cache of each row
+    ///
+    /// # Returns
+    ///
+    /// Position IDs like `position_ids`, with the first real token of each row
+    /// at its offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the number of offsets differs from the number of
+    /// rows.
+    pub fn position_ids_from(&self, offsets: &[u32]) -> Result<Vec<Vec<u32>>> {
+        if offsets.len() != self.attention_mask.len() {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Expected {} position offsets, one per row, got {}",
+                self.attention_mask.len(),
+                offsets.len()
+            )));
+        }
+        Ok(self
+            .attention_mask
+            .iter()
+            .zip(offsets)
+            .map(|(mask, &offset)| position_ids(mask, offset))
+            .collect())
+    }
+}
+
+impl Tekkenizer {
+    /// Pads multimodal encodings into a batch for a forward pass.
+    ///
+    /// # Arguments
+    ///
+    /// * `encodings` - The encodings, e.g. [`TokenizedRequest`]s, in batch order
+    /// * `padding_side` - Which end of the token rows padding is added to;
+    ///   spectrograms and images are always padded at the end
+    ///
+    /// # Returns
+    ///
+    /// The padded token IDs and attention mask, and the audio features and
+    /// pixels of the encodings.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The encodings differ in length and the padding token is not in the
+    ///   vocabulary
+    /// - They contain audio and the tokenizer has no audio configuration or
+    ///   `[AUDIO]` token, or the features cannot be computed
+    /// - They contain images and the `[IMG]` token is not in the vocabulary
+    pub fn collate<E: MultimodalEncoding>(
+        &self,
+        encodings: &[E],
+        padding_side: PaddingSide,
+    ) -> Result<CollatedBatch> {
+        let lengths: Vec<usize> = encodings
+            .iter()
+            .map(|encoding| encoding.tokens().len())
+            .collect();
+        let max_length = lengths.iter().copied().max().unwrap_or(0);
+        let pad_id = if lengths.iter().any(|&length| length < max_length) {
+            self.pad_id()?
+        } else {
+            0
+        };
+
+        let mut input_ids = Vec::with_capacity(encodings.len());
+        let mut attention_mask = Vec::with_capacity(encodings.len());
+        let mut positions = Vec::with_capacity(encodings.len());
+        for tokens in encodings.iter().map(MultimodalEncoding::tokens) {
+            let padding = max_length - tokens.len();
+            let mut ids = tokens.to_vec();
+            pad(&mut ids, padding, pad_id, padding_side);
+            input_ids.push(ids);
+            let mut mask = vec![1; tokens.len()];
+            pad(&mut mask, padding, 0, padding_side);
+            positions.push(position_ids(&mask, 0));
+            attention_mask.push(mask);
+        }
+
+        let batch = CollatedBatch {
+            input_ids,
+            attention_mask,
+            position_ids: positions,
+            lengths,
+            #[cfg(feature = "audio")]
+            audio_features: Array3::zeros((0, 0, 0)),
+            #[cfg(feature = "audio")]
+            audio_frames: Vec::new(),
+            #[cfg(feature = "audio")]
+            audio_requests: Vec::new(),
+            #[cfg(feature = "audio")]
+            audio_positions: vec![Vec::new(); encodings.len()],
+            #[cfg(feature = "image")]
+            pixel_values: Array4::zeros((0, 3, 0, 0)),
+            #[cfg(feature = "image")]
+            image_sizes: Vec::new(),
+            #[cfg(feature = "image")]
+            image_requests: Vec::new(),
+            #[cfg(feature = "image")]
+            image_positions: vec![Vec::new(); encodings.len()],
+        };
+        #[cfg(feature = "audio")]
+        let batch = self.collate_audio(encodings, padding_side, batch)?;
+        #[cfg(feature = "image")]
+        let batch = self.collate_images(encodings, padding_side, batch)?;
+        Ok(batch)
+    }
+
+    /// Stacks the log-mel features of the audio and locates their `[AUDIO]` tokens.
+    #[cfg(feature = "audio")]
+    fn collate_audio<E: MultimodalEncoding>(
+        &self,
+        encodings: &[E],
+        padding_side: PaddingSide,
+        mut batch: CollatedBatch,
+    ) -> Result<CollatedBatch> {
+        if encodings
+            .iter()
+            .all(|encoding| encoding.audios().is_empty())
+        {
+            return Ok(batch);
+        }
+        let config = self.audio_config().ok_or_else(|| {
+            TokenizerError::Audio("Tokenizer has no audio configuration".to_string())
+        })?;
+
+        let mut features = Vec::new();
+        for (index, encoding) in encodings.iter().enumerate() {
+            for audio in encoding.audios() {
+                let spectrogram = log_mel_spectrogram(&audio.audio, &config.audio_encoding_config)?;
+                features.push((index, spectrogram));
+            }
+        }
+        let num_mel_bins = config.audio_encoding_config.num_mel_bins;
+        let longest = features
+            .iter()
+            .map(|(_, spectrogram)| spectrogram.ncols())
+            .max()
+            .unwrap_or(0);
+        batch.audio_features = Array3::zeros((features.len(), num_mel_bins, longest));
+        for (mut slot, (_, spectrogram)) in batch.audio_features.outer_iter_mut().zip(&features) {
+            slot.slice_mut(s![.., ..spectrogram.ncols()])
+                .assign(spectrogram);
+        }
+        batch.audio_frames = features
+            .iter()
+            .map(|(_, spectrogram)| spectrogram.ncols())
+            .collect();
+        batch.audio_requests = features.iter().map(|&(index, _)| index).collect();
+
+        let audio_token_id = self.id_of(SpecialTokens::Audio)?;
+        batch.audio_positions =
+            token_positions(encodings, audio_token_id, padding_side, batch.max_length());
+        Ok(batch)
+    }
+
+    /// Stacks the pixels of the images and locates their `[IMG]` tokens.
+    #[cfg(feature = "image")]
+    fn collate_images<E: MultimodalEncoding>(
+        &self,
+        encodings: &[E],
+        padding_side: PaddingSide,
+        mut batch: CollatedBatch,
+    ) -> Result<CollatedBatch> {
+        let images: Vec<(usize, &ImageEncoding)> = encodings
+            .iter()
+            .enumerate()
+            .flat_map(|(index, encoding)| encoding.images().iter().map(move |image| (index, image)))
+            .collect();
+        if images.is_empty() {
+            return Ok(batch);
+        }
+
+        batch.image_sizes = images
+            .iter()
+            .map(|(_, encoding)| {
+                let (_, height, width) = encoding.image.pixel_values.dim();
+                (height, width)
+            })
+            .collect();
+        let max_height = batch.image_sizes.iter().map(|&(height, _)| height).max();
+        let max_width = batch.image_sizes.iter().map(|&(_, width)| width).max();
+        batch.pixel_values = Array4::zeros((
+            images.len(),
+            3,
+            max_height.unwrap_or(0),
+            max_width.unwrap_or(0),
+        ));
+        for (mut slot, ((_, encoding), &(height, width))) in batch
+            .pixel_values
+            .outer_iter_mut()
+            .zip(images.iter().zip(&batch.image_sizes))
+        {
+            slot.slice_mut(s![.., ..height, ..width])
+                .assign(&encoding.image.pixel_values);
+        }
+        batch.image_requests = images.iter().map(|&(index, _)| index).collect();
+
+        let image_token_id = self.id_of(SpecialTokens::Img)?;
+        batch.image_positions =
+            token_positions(encodings, image_token_id, padding_side, batch.max_length());
+        Ok(batch)
+    }
+}
+
+/// Returns the positions of `token_id` in each padded row.
+#[cfg(any(feature = "audio", feature = "image"))]
+fn token_positions<E: MultimodalEncoding>(
+    encodings: &[E],
+    token_id: u32,
+    padding_side: PaddingSide,
+    max_length: usize,
+) -> Vec<Vec<usize>> {
+    encodings
+        .iter()
+        .map(|encoding| {
+            let tokens = encoding.tokens();
+            let shift = match padding_side {
+                PaddingSide::Right => 0,
+                PaddingSide::Left => max_length - tokens.len(),
+            };
+            tokens
+                .iter()
+                .enumerate()
+                .filter_map(|(position, &token)| (token == token_id).then_some(shift + position))
+                .collect()
+        })
+        .collect()
+}
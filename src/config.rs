@@ -1,6 +1,13 @@
 use crate::audio::AudioConfig;
-use crate::special_tokens::SpecialTokenInfo;
+use crate::errors::{Result, TokenizerError};
+use crate::image::{DATASET_MEAN, DATASET_STD};
+use crate::special_tokens::{SpecialTokenInfo, SpecialTokens};
+use crate::tekkenizer::get_deprecated_special_tokens;
+use base64::Engine;
+use base64::engine::general_purpose;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::borrow::Cow;
 
 /// Information about a vocabulary token.
 ///
@@ -34,6 +41,8 @@ pub struct TokenInfo {
 /// * `default_vocab_size` - Default total vocabulary size including special tokens
 /// * `default_num_special_tokens` - Default number of special tokens
 /// * `version` - Tokenizer version string (e.g., "v7")
+/// * `placeholder_ranks` - Ranks left out of the vocabulary that load as
+///   placeholder tokens (see [`crate::tekkenizer::RankGapPolicy::Placeholder`])
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TekkenConfig {
     /// Regex pattern used for tokenization.
@@ -41,21 +50,250 @@ pub struct TekkenConfig {
     /// Number of regular vocabulary tokens (excluding special tokens).
     pub num_vocab_tokens: usize,
     /// Default total vocabulary size including special tokens.
+    #[serde(alias = "vocab_size")]
     pub default_vocab_size: usize,
     /// Default number of special tokens.
+    #[serde(alias = "num_special_tokens")]
     pub default_num_special_tokens: usize,
     /// Tokenizer version string (e.g., "v7", "v11", "v13").
+    ///
+    /// Missing or unknown versions are inferred with [`detect_version`].
+    #[serde(default)]
     pub version: String,
+    /// Ranks without a vocabulary entry that load as placeholder tokens.
+    ///
+    /// Written when exporting a tokenizer loaded with placeholders, so the
+    /// export loads back with the same token IDs.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub placeholder_ranks: Vec<usize>,
+    /// Keys not known to this crate, kept so they survive a round trip.
+    #[serde(flatten, default, skip_serializing_if = "Map::is_empty")]
+    pub extra: Map<String, Value>,
 }
 
-/// Configuration for image processing (placeholder).
+/// Configuration for image processing.
+///
+/// Mirrors the `image` (or `multimodal`) section of Pixtral-style tokenizer
+/// files. Images are scaled to fit within `max_image_size` and cut into square
+/// patches of `image_patch_size * spatial_merge_size` pixels, one `[IMG]` token
+/// per patch. Unknown keys of the section are kept so they survive a round trip.
 ///
-/// This struct is reserved for future image processing capabilities.
-/// Currently minimal as audio processing is the primary multimodal focus.
+/// The resize filter and pixel normalization default to the Pixtral reference
+/// values and are only written to files when changed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImageConfig {
-    // Image config fields would go here
-    // For now, we'll keep it minimal as audio is the focus
+    /// Side length in pixels of one vision encoder patch.
+    pub image_patch_size: usize,
+    /// Longest side in pixels an image is scaled down to.
+    pub max_image_size: usize,
+    /// Number of patches merged along each axis into one token.
+    #[serde(default = "default_spatial_merge_size")]
+    pub spatial_merge_size: usize,
+    /// Per-channel (RGB) mean subtracted from rescaled pixel values.
+    #[serde(
+        default = "default_image_mean",
+        skip_serializing_if = "is_default_image_mean"
+    )]
+    pub image_mean: [f64; 3],
+    /// Per-channel (RGB) standard deviation rescaled pixel values are divided by.
+    #[serde(
+        default = "default_image_std",
+        skip_serializing_if = "is_default_image_std"
+    )]
+    pub image_std: [f64; 3],
+    /// Factor 8-bit pixel values are multiplied by before normalization.
+    #[serde(
+        default = "default_rescale_factor",
+        skip_serializing_if = "is_default_rescale_factor"
+    )]
+    pub rescale_factor: f64,
+    /// Interpolation used to resize images to the token grid.
+    #[serde(default, skip_serializing_if = "ImageResample::is_default")]
+    pub resample: ImageResample,
+    /// Keys not known to this crate, kept so they survive a round trip.
+    #[serde(flatten, default, skip_serializing_if = "Map::is_empty")]
+    pub extra: Map<String, Value>,
+}
+
+/// Interpolation used to resize images.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageResample {
+    /// Nearest-neighbour sampling.
+    Nearest,
+    /// Linear interpolation.
+    Bilinear,
+    /// Cubic interpolation, as used by Pixtral.
+    #[default]
+    Bicubic,
+    /// Lanczos windowed sinc with three lobes.
+    Lanczos,
+}
+
+impl ImageResample {
+    // `skip_serializing_if` passes a reference
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn default_spatial_merge_size() -> usize {
+    1
+}
+
+fn default_image_mean() -> [f64; 3] {
+    DATASET_MEAN
+}
+
+fn default_image_std() -> [f64; 3] {
+    DATASET_STD
+}
+
+fn default_rescale_factor() -> f64 {
+    1.0 / 255.0
+}
+
+fn is_default_image_mean(mean: &[f64; 3]) -> bool {
+    *mean == DATASET_MEAN
+}
+
+fn is_default_image_std(std: &[f64; 3]) -> bool {
+    *std == DATASET_STD
+}
+
+// `skip_serializing_if` passes a reference, and only the exact default is skipped
+#[allow(clippy::trivially_copy_pass_by_ref, clippy::float_cmp)]
+fn is_default_rescale_factor(factor: &f64) -> bool {
+    *factor == default_rescale_factor()
+}
+
+impl ImageConfig {
+    /// Creates a new image configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `image_patch_size` - Side length in pixels of one patch
+    /// * `max_image_size` - Longest side in pixels images are scaled down to
+    /// * `spatial_merge_size` - Patches merged along each axis into one token
+    ///
+    /// # Returns
+    ///
+    /// The validated configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the sizes is zero.
+    pub fn new(
+        image_patch_size: usize,
+        max_image_size: usize,
+        spatial_merge_size: usize,
+    ) -> Result<Self> {
+        let config = Self {
+            image_patch_size,
+            max_image_size,
+            spatial_merge_size,
+            image_mean: DATASET_MEAN,
+            image_std: DATASET_STD,
+            rescale_factor: default_rescale_factor(),
+            resample: ImageResample::default(),
+            extra: Map::new(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Sets the per-channel (RGB) normalization mean and standard deviation.
+    #[must_use]
+    pub fn with_normalization(mut self, mean: [f64; 3], std: [f64; 3]) -> Self {
+        self.image_mean = mean;
+        self.image_std = std;
+        self
+    }
+
+    /// Sets the factor 8-bit pixel values are multiplied by.
+    #[must_use]
+    pub fn with_rescale_factor(mut self, rescale_factor: f64) -> Self {
+        self.rescale_factor = rescale_factor;
+        self
+    }
+
+    /// Sets the interpolation used to resize images.
+    #[must_use]
+    pub fn with_resample(mut self, resample: ImageResample) -> Self {
+        self.resample = resample;
+        self
+    }
+
+    /// Checks that the configuration can be used for preprocessing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the sizes is zero, a mean or the rescale
+    /// factor is not finite, or a standard deviation is not a positive number.
+    pub fn validate(&self) -> Result<()> {
+        if self.image_patch_size == 0 || self.max_image_size == 0 || self.spatial_merge_size == 0 {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Image sizes must be positive (patch {}, max {}, merge {})",
+                self.image_patch_size, self.max_image_size, self.spatial_merge_size
+            )));
+        }
+        if !self.rescale_factor.is_finite() || self.image_mean.iter().any(|m| !m.is_finite()) {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Image rescale factor ({}) and mean ({:?}) must be finite",
+                self.rescale_factor, self.image_mean
+            )));
+        }
+        if self.image_std.iter().any(|s| !(s.is_finite() && *s > 0.0)) {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Image standard deviation ({:?}) must be positive",
+                self.image_std
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the side length in pixels covered by one image token.
+    #[must_use]
+    pub fn pixels_per_token(&self) -> usize {
+        self.image_patch_size * self.spatial_merge_size
+    }
+
+    /// Computes the token grid of an image.
+    ///
+    /// Images larger than `max_image_size` are first scaled down, keeping their
+    /// aspect ratio and rounding like Python's `round`; each side is then
+    /// covered by as many tokens as needed to span it.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - Image width in pixels
+    /// * `height` - Image height in pixels
+    ///
+    /// # Returns
+    ///
+    /// The number of token columns and rows, `(columns, rows)`.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn token_grid(&self, width: usize, height: usize) -> (usize, usize) {
+        let max_size = self.max_image_size.max(1) as f64;
+        let ratio = (width as f64 / max_size).max(height as f64 / max_size);
+        let (width, height) = if ratio > 1.0 {
+            (
+                (width as f64 / ratio).round_ties_even() as usize,
+                (height as f64 / ratio).round_ties_even() as usize,
+            )
+        } else {
+            (width, height)
+        };
+        let pixels_per_token = self.pixels_per_token().max(1);
+        let tokens = |side: usize| side.saturating_sub(1) / pixels_per_token + 1;
+        (tokens(width), tokens(height))
+    }
 }
 
 /// Complete model data loaded from a tokenizer configuration file.
@@ -69,6 +307,11 @@ pub struct ImageConfig {
 /// * `special_tokens` - Optional special token definitions
 /// * `config` - Core tokenizer configuration
 /// * `audio` - Optional audio processing configuration
+/// * `image` - Optional image section, also read from `multimodal` or `mm`
+/// * `extra` - Top-level keys not known to this crate
+///
+/// Unknown keys are preserved, so files from newer revisions of the format can
+/// be loaded, edited and written back without losing data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelData {
     /// All vocabulary tokens with their metadata.
@@ -79,6 +322,569 @@ pub struct ModelData {
     pub config: TekkenConfig,
     /// Optional audio processing configuration for multimodal support.
     pub audio: Option<AudioConfig>,
+    /// Optional image processing section.
+    #[serde(
+        default,
+        alias = "multimodal",
+        alias = "mm",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub image: Option<ImageConfig>,
+    /// Keys not known to this crate, kept so they survive a round trip.
+    #[serde(flatten, default, skip_serializing_if = "Map::is_empty")]
+    pub extra: Map<String, Value>,
+}
+
+impl TekkenConfig {
+    /// Starts a [`TekkenConfigBuilder`].
+    #[must_use]
+    pub fn builder(pattern: impl Into<String>, version: TokenizerVersion) -> TekkenConfigBuilder {
+        TekkenConfigBuilder::new(pattern, version)
+    }
+}
+
+impl ModelData {
+    /// Starts a [`ModelDataBuilder`].
+    #[must_use]
+    pub fn builder(pattern: impl Into<String>, version: TokenizerVersion) -> ModelDataBuilder {
+        ModelDataBuilder::new(pattern, version)
+    }
+
+    /// Parses the JSON contents of a tokenizer file.
+    ///
+    /// With [`TokenStrPolicy::Drop`], the `token_str` of every vocabulary token
+    /// is skipped by the parser instead of being allocated, which makes parsing
+    /// a full vocabulary faster and the result much smaller. Tokenizers never
+    /// need these strings: [`Tekkenizer`](crate::tekkenizer::Tekkenizer)
+    /// loaders always skip them.
+    ///
+    /// # Arguments
+    ///
+    /// * `json` - The uncompressed file contents
+    /// * `token_strs` - Whether vocabulary `token_str` fields are kept
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `json` is not a valid tokenizer file.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::config::{ModelData, TokenStrPolicy};
+    ///
+    /// let model_data = ModelData::from_slice(&std::fs::read("tekken.json")?, TokenStrPolicy::Drop)?;
+    /// assert!(model_data.vocab.iter().all(|token| token.token_str.is_none()));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_slice(json: &[u8], token_strs: TokenStrPolicy) -> Result<Self> {
+        Ok(match token_strs {
+            TokenStrPolicy::Keep => serde_json::from_slice(json)?,
+            TokenStrPolicy::Drop => serde_json::from_slice::<ModelDataWithoutTokenStrs>(json)?.0,
+        })
+    }
+}
+
+/// Whether the optional `token_str` of vocabulary tokens is kept when parsing
+/// a tokenizer file with [`ModelData::from_slice`].
+///
+/// `token_str` only repeats `token_bytes` as text, for humans reading the file.
+///
+/// # Variants
+///
+/// - `Keep`: Store each `token_str` in [`TokenInfo::token_str`]
+/// - `Drop`: Skip them while parsing; every `token_str` is `None`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TokenStrPolicy {
+    /// Store each `token_str` in [`TokenInfo::token_str`].
+    #[default]
+    Keep,
+    /// Skip them while parsing; every `token_str` is `None`.
+    Drop,
+}
+
+/// [`ModelData`] parsed with its vocabulary `token_str` fields skipped.
+struct ModelDataWithoutTokenStrs(ModelData);
+
+impl<'de> Deserialize<'de> for ModelDataWithoutTokenStrs {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        /// [`TokenInfo`] without `token_str`, which the parser skips as an
+        /// unknown field.
+        #[derive(Deserialize)]
+        struct TokenWithoutStr {
+            rank: usize,
+            token_bytes: String,
+        }
+
+        fn vocab<'de, D: serde::Deserializer<'de>>(
+            deserializer: D,
+        ) -> std::result::Result<Vec<TokenInfo>, D::Error> {
+            struct VocabVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for VocabVisitor {
+                type Value = Vec<TokenInfo>;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    f.write_str("a list of vocabulary tokens")
+                }
+
+                fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                    self,
+                    mut seq: A,
+                ) -> std::result::Result<Self::Value, A::Error> {
+                    let mut vocab = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+                    while let Some(token) = seq.next_element::<TokenWithoutStr>()? {
+                        vocab.push(TokenInfo {
+                            rank: token.rank,
+                            token_bytes: token.token_bytes,
+                            token_str: None,
+                        });
+                    }
+                    Ok(vocab)
+                }
+            }
+
+            deserializer.deserialize_seq(VocabVisitor)
+        }
+
+        /// Mirror of [`ModelData`] whose vocabulary is read by `vocab`.
+        #[derive(Deserialize)]
+        struct Fields {
+            #[serde(deserialize_with = "vocab")]
+            vocab: Vec<TokenInfo>,
+            special_tokens: Option<Vec<SpecialTokenInfo>>,
+            config: TekkenConfig,
+            audio: Option<AudioConfig>,
+            #[serde(default, alias = "multimodal", alias = "mm")]
+            image: Option<ImageConfig>,
+            #[serde(flatten, default)]
+            extra: Map<String, Value>,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        Ok(Self(ModelData {
+            vocab: fields.vocab,
+            special_tokens: fields.special_tokens,
+            config: fields.config,
+            audio: fields.audio,
+            image: fields.image,
+            extra: fields.extra,
+        }))
+    }
+}
+
+/// Builds a validated [`TekkenConfig`].
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::config::{TekkenConfig, TokenizerVersion};
+///
+/// let config = TekkenConfig::builder(r"\p{L}+|\s+|.", TokenizerVersion::V7)
+///     .with_num_vocab_tokens(32_000)
+///     .with_num_special_tokens(1000)
+///     .build()?;
+/// assert_eq!(config.default_vocab_size, 33_000);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct TekkenConfigBuilder {
+    pattern: String,
+    version: TokenizerVersion,
+    num_vocab_tokens: usize,
+    vocab_size: Option<usize>,
+    num_special_tokens: usize,
+}
+
+impl TekkenConfigBuilder {
+    /// Creates a builder with 1000 special tokens and no vocabulary tokens.
+    #[must_use]
+    pub fn new(pattern: impl Into<String>, version: TokenizerVersion) -> Self {
+        Self {
+            pattern: pattern.into(),
+            version,
+            num_vocab_tokens: 0,
+            vocab_size: None,
+            num_special_tokens: 1000,
+        }
+    }
+
+    /// Sets the number of regular vocabulary tokens.
+    #[must_use]
+    pub fn with_num_vocab_tokens(mut self, num_vocab_tokens: usize) -> Self {
+        self.num_vocab_tokens = num_vocab_tokens;
+        self
+    }
+
+    /// Sets the number of special tokens.
+    #[must_use]
+    pub fn with_num_special_tokens(mut self, num_special_tokens: usize) -> Self {
+        self.num_special_tokens = num_special_tokens;
+        self
+    }
+
+    /// Sets the total vocabulary size including special tokens.
+    ///
+    /// Defaults to all vocabulary tokens plus the special tokens; a smaller size
+    /// truncates the vocabulary at load time.
+    #[must_use]
+    pub fn with_vocab_size(mut self, vocab_size: usize) -> Self {
+        self.vocab_size = Some(vocab_size);
+        self
+    }
+
+    /// Validates the settings and builds the configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The pattern is not a valid regex
+    /// - There are fewer than 256 vocabulary tokens (one per byte)
+    /// - The vocabulary size is smaller than the number of special tokens, larger
+    ///   than the number of available tokens, or does not fit a `u32` token ID
+    pub fn build(self) -> Result<TekkenConfig> {
+        if !self.pattern.is_empty() {
+            fancy_regex::Regex::new(&self.pattern).map_err(|e| {
+                TokenizerError::InvalidConfig(format!("Invalid pretokenization pattern: {e}"))
+            })?;
+        }
+        if self.num_vocab_tokens < 256 {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "num_vocab_tokens ({}) must include the 256 byte tokens",
+                self.num_vocab_tokens
+            )));
+        }
+
+        let available = self.num_vocab_tokens + self.num_special_tokens;
+        let vocab_size = self.vocab_size.unwrap_or(available);
+        if vocab_size < self.num_special_tokens || vocab_size > available {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "vocab_size ({vocab_size}) must be between num_special_tokens ({}) and {available}",
+                self.num_special_tokens
+            )));
+        }
+        if u32::try_from(vocab_size).is_err() {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "vocab_size ({vocab_size}) must fit in a u32 token ID"
+            )));
+        }
+
+        Ok(TekkenConfig {
+            pattern: self.pattern,
+            num_vocab_tokens: self.num_vocab_tokens,
+            default_vocab_size: vocab_size,
+            default_num_special_tokens: self.num_special_tokens,
+            version: self.version.as_str().to_string(),
+            placeholder_ranks: Vec::new(),
+            extra: Map::new(),
+        })
+    }
+}
+
+/// Builds a validated [`ModelData`] from raw token bytes.
+///
+/// Tokens receive ranks in the order they are added, so the 256 single-byte
+/// tokens must come first (see [`ModelDataBuilder::with_byte_tokens`]) followed
+/// by merged tokens in merge-priority order. Special tokens default to the
+/// version's [`TokenizerVersion::default_special_tokens`].
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::config::{ModelData, TokenizerVersion};
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let model_data = ModelData::builder(r"\p{L}+|\s+|.", TokenizerVersion::V7)
+///     .with_byte_tokens()
+///     .with_tokens(["he", "ll", "hell", "hello"])
+///     .with_num_special_tokens(100)
+///     .build()?;
+///
+/// let tokenizer = Tekkenizer::from_bytes(&serde_json::to_vec(&model_data)?)?;
+/// assert_eq!(tokenizer.encode("hello", false, false)?, vec![359]);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModelDataBuilder {
+    config: TekkenConfigBuilder,
+    tokens: Vec<Vec<u8>>,
+    special_tokens: Option<Vec<SpecialTokenInfo>>,
+    audio: Option<AudioConfig>,
+    image: Option<ImageConfig>,
+}
+
+impl ModelDataBuilder {
+    /// Creates a builder with an empty vocabulary and 1000 special tokens.
+    #[must_use]
+    pub fn new(pattern: impl Into<String>, version: TokenizerVersion) -> Self {
+        Self {
+            config: TekkenConfigBuilder::new(pattern, version),
+            tokens: Vec::new(),
+            special_tokens: None,
+            audio: None,
+            image: None,
+        }
+    }
+
+    /// Appends the 256 single-byte tokens, ranks `0..256` of every vocabulary.
+    #[must_use]
+    pub fn with_byte_tokens(self) -> Self {
+        self.with_tokens((0..=u8::MAX).map(|byte| vec![byte]))
+    }
+
+    /// Appends vocabulary tokens, ranked in iteration order.
+    #[must_use]
+    pub fn with_tokens<T: AsRef<[u8]>>(mut self, tokens: impl IntoIterator<Item = T>) -> Self {
+        self.tokens
+            .extend(tokens.into_iter().map(|token| token.as_ref().to_vec()));
+        self
+    }
+
+    /// Sets the number of special tokens.
+    #[must_use]
+    pub fn with_num_special_tokens(mut self, num_special_tokens: usize) -> Self {
+        self.config = self.config.with_num_special_tokens(num_special_tokens);
+        self
+    }
+
+    /// Sets the special tokens, replacing the version's defaults.
+    #[must_use]
+    pub fn with_special_tokens(mut self, special_tokens: Vec<SpecialTokenInfo>) -> Self {
+        self.special_tokens = Some(special_tokens);
+        self
+    }
+
+    /// Sets the total vocabulary size including special tokens.
+    #[must_use]
+    pub fn with_vocab_size(mut self, vocab_size: usize) -> Self {
+        self.config = self.config.with_vocab_size(vocab_size);
+        self
+    }
+
+    /// Adds an audio configuration.
+    #[must_use]
+    pub fn with_audio(mut self, audio: AudioConfig) -> Self {
+        self.audio = Some(audio);
+        self
+    }
+
+    /// Adds an image configuration.
+    #[must_use]
+    pub fn with_image(mut self, image: ImageConfig) -> Self {
+        self.image = Some(image);
+        self
+    }
+
+    /// Validates the vocabulary and builds the model data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The configuration is invalid (see [`TekkenConfigBuilder::build`])
+    /// - The first 256 tokens are not the single bytes in order
+    /// - A token is empty or appears more than once
+    /// - Special tokens repeat a string or rank, or have a rank outside
+    ///   `0..num_special_tokens`
+    /// - Audio is configured without the `[AUDIO]` and `[BEGIN_AUDIO]` tokens
+    pub fn build(self) -> Result<ModelData> {
+        let version = self.config.version.clone();
+        let config = self
+            .config
+            .with_num_vocab_tokens(self.tokens.len())
+            .build()?;
+
+        let mut seen = std::collections::HashSet::with_capacity(self.tokens.len());
+        for (rank, token) in self.tokens.iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            if rank < 256 && *token != [rank as u8] {
+                return Err(TokenizerError::InvalidConfig(format!(
+                    "Expected byte token at rank {rank} to be [{rank}], got {token:?}"
+                )));
+            }
+            if token.is_empty() || !seen.insert(token.as_slice()) {
+                return Err(TokenizerError::InvalidConfig(format!(
+                    "Token at rank {rank} is empty or a duplicate: {token:?}"
+                )));
+            }
+        }
+
+        let special_tokens = self
+            .special_tokens
+            .unwrap_or_else(|| version.default_special_tokens());
+        let mut strings = std::collections::HashSet::new();
+        let mut ranks = std::collections::HashSet::new();
+        for token in &special_tokens {
+            if token.rank >= config.default_num_special_tokens
+                || !ranks.insert(token.rank)
+                || !strings.insert(token.token_str.as_str())
+            {
+                return Err(TokenizerError::InvalidConfig(format!(
+                    "Special token {} at rank {} is a duplicate or outside 0..{}",
+                    token.token_str, token.rank, config.default_num_special_tokens
+                )));
+            }
+        }
+        if self.audio.is_some() {
+            for required in [SpecialTokens::Audio, SpecialTokens::BeginAudio] {
+                if !strings.contains(required.as_str()) {
+                    return Err(TokenizerError::TokenNotFound(format!(
+                        "Audio requires the {required} special token"
+                    )));
+                }
+            }
+        }
+
+        let vocab = self
+            .tokens
+            .into_iter()
+            .enumerate()
+            .map(|(rank, bytes)| TokenInfo {
+                rank,
+                token_bytes: general_purpose::STANDARD.encode(&bytes),
+                token_str: String::from_utf8(bytes).ok(),
+            })
+            .collect();
+
+        Ok(ModelData {
+            vocab,
+            special_tokens: Some(special_tokens),
+            config,
+            audio: self.audio,
+            image: self.image,
+            extra: Map::new(),
+        })
+    }
+}
+
+/// Borrowing view of [`ModelData`] used when loading tokenizer files.
+///
+/// Base64 token bytes are borrowed from the input buffer so they can be decoded
+/// without an intermediate `String` per token, and the unused `token_str` fields
+/// are skipped entirely.
+#[derive(Debug, Deserialize)]
+pub(crate) struct RawModelData<'a> {
+    #[serde(borrow)]
+    pub vocab: Vec<RawTokenInfo<'a>>,
+    pub special_tokens: Option<Vec<SpecialTokenInfo>>,
+    pub config: TekkenConfig,
+    pub audio: Option<AudioConfig>,
+    #[serde(default, alias = "multimodal", alias = "mm")]
+    pub image: Option<ImageConfig>,
+}
+
+/// Index file of a tokenizer whose vocabulary is split across shard files.
+///
+/// The index holds everything except the vocabulary, plus the shard file names.
+/// Each shard is a JSON object with a `vocab` array in the same format as
+/// `tekken.json`; shards may hold any subset of ranks and be listed in any order.
+///
+/// # Fields
+///
+/// * `vocab_shards` - Shard file paths, relative to the index file's directory
+/// * `special_tokens` - Optional special token definitions
+/// * `config` - Core tokenizer configuration
+/// * `audio` - Optional audio processing configuration
+/// * `image` - Optional image processing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShardedIndex {
+    /// Shard file paths, relative to the index file's directory.
+    pub vocab_shards: Vec<String>,
+    /// Optional special token definitions (uses defaults if None).
+    pub special_tokens: Option<Vec<SpecialTokenInfo>>,
+    /// Core tokenizer configuration parameters.
+    pub config: TekkenConfig,
+    /// Optional audio processing configuration for multimodal support.
+    pub audio: Option<AudioConfig>,
+    /// Optional image processing configuration.
+    #[serde(
+        default,
+        alias = "multimodal",
+        alias = "mm",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub image: Option<ImageConfig>,
+}
+
+/// Sidecar file of a `.tiktoken` rank file.
+///
+/// A `.tiktoken` file only holds the mergeable ranks, one `<base64 bytes> <rank>`
+/// line per token. The sidecar carries the rest of a `tekken.json` file so the
+/// pair loads back into a complete tokenizer.
+///
+/// # Fields
+///
+/// * `special_tokens` - Optional special token definitions
+/// * `config` - Core tokenizer configuration
+/// * `audio` - Optional audio processing configuration
+/// * `image` - Optional image processing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TiktokenSidecar {
+    /// Optional special token definitions (uses defaults if None).
+    pub special_tokens: Option<Vec<SpecialTokenInfo>>,
+    /// Core tokenizer configuration parameters.
+    pub config: TekkenConfig,
+    /// Optional audio processing configuration for multimodal support.
+    pub audio: Option<AudioConfig>,
+    /// Optional image processing configuration.
+    #[serde(
+        default,
+        alias = "multimodal",
+        alias = "mm",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub image: Option<ImageConfig>,
+}
+
+/// A vocabulary shard of a [`ShardedIndex`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VocabShard {
+    /// The vocabulary tokens stored in this shard.
+    pub vocab: Vec<TokenInfo>,
+}
+
+/// Borrowing view of [`VocabShard`] used when loading shard files.
+#[derive(Debug, Deserialize)]
+pub(crate) struct RawVocabShard<'a> {
+    #[serde(borrow)]
+    pub vocab: Vec<RawTokenInfo<'a>>,
+}
+
+/// Borrowing view of [`TokenInfo`] without the optional `token_str`.
+#[derive(Debug, Deserialize)]
+pub(crate) struct RawTokenInfo<'a> {
+    pub rank: usize,
+    #[serde(borrow)]
+    pub token_bytes: Cow<'a, str>,
+}
+
+/// Determines the version of a tokenizer file.
+///
+/// The `version` field is used when it names a known version. Otherwise the
+/// version is inferred from the special-token layout, newest first:
+///
+/// - `[THINK]` → V13
+/// - `[ARGS]` or `[CALL_ID]` → V11
+/// - `[SYSTEM_PROMPT]`, `[TOOL_CONTENT]` or an audio section → V7
+/// - anything else, including files without a special-token list → V3
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::config::{ModelData, detect_version};
+///
+/// let model_data: ModelData = serde_json::from_slice(&std::fs::read("tekken.json")?)?;
+/// println!("{:?}", detect_version(&model_data));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[must_use]
+pub fn detect_version(model_data: &ModelData) -> TokenizerVersion {
+    TokenizerVersion::from_string(&model_data.config.version).unwrap_or_else(|| {
+        TokenizerVersion::infer(
+            model_data.special_tokens.as_deref(),
+            model_data.audio.is_some(),
+        )
+    })
 }
 
 /// Enumeration of supported tokenizer versions.
@@ -131,6 +937,97 @@ impl TokenizerVersion {
         }
     }
 
+    /// Returns whether system prompts are wrapped in `[SYSTEM_PROMPT]` tokens.
+    ///
+    /// V3 has no system prompt tokens; chat encoders prepend the system prompt to
+    /// the last user message instead, separated by a blank line.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tekken::config::TokenizerVersion;
+    ///
+    /// assert!(!TokenizerVersion::V3.has_system_prompt_tokens());
+    /// assert!(TokenizerVersion::V7.has_system_prompt_tokens());
+    /// ```
+    #[must_use]
+    pub fn has_system_prompt_tokens(&self) -> bool {
+        !matches!(self, Self::V3)
+    }
+
+    /// Infers the version from the special tokens and sections of a tokenizer file.
+    ///
+    /// See [`detect_version`] for the rules.
+    pub(crate) fn infer(special_tokens: Option<&[SpecialTokenInfo]>, has_audio: bool) -> Self {
+        let special_tokens = special_tokens.unwrap_or_default();
+        let has = |token_str: &str| special_tokens.iter().any(|t| t.token_str == token_str);
+
+        if has(SpecialTokens::Think.as_str()) {
+            Self::V13
+        } else if has(SpecialTokens::Args.as_str()) || has(SpecialTokens::CallId.as_str()) {
+            Self::V11
+        } else if has_audio
+            || has(SpecialTokens::BeginSystem.as_str())
+            || has(SpecialTokens::BeginToolContent.as_str())
+        {
+            Self::V7
+        } else {
+            Self::V3
+        }
+    }
+
+    /// Returns the canonical special tokens of this version.
+    ///
+    /// Used for tokenizer files that omit `special_tokens`. Every version starts
+    /// from the original layout of ranks 0-19 (`<unk>` through `[TOOL_CONTENT]`);
+    /// later versions add:
+    ///
+    /// - **V7**: `[AUDIO]` (24), `[BEGIN_AUDIO]` (25) and `[TRANSCRIBE]` (34)
+    /// - **V11**: `[AUDIO]` (24), `[BEGIN_AUDIO]` (25), `[ARGS]` (32) and `[CALL_ID]` (33)
+    /// - **V13**: the V11 tokens plus `[THINK]` (34) and `[/THINK]` (35)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tekken::config::TokenizerVersion;
+    ///
+    /// let tokens = TokenizerVersion::V11.default_special_tokens();
+    /// assert!(tokens.iter().any(|t| t.token_str == "[CALL_ID]" && t.rank == 33));
+    /// ```
+    #[must_use]
+    pub fn default_special_tokens(&self) -> Vec<SpecialTokenInfo> {
+        let added: &[(usize, SpecialTokens)] = match self {
+            Self::V3 => &[],
+            Self::V7 => &[
+                (24, SpecialTokens::Audio),
+                (25, SpecialTokens::BeginAudio),
+                (34, SpecialTokens::Transcribe),
+            ],
+            Self::V11 => &[
+                (24, SpecialTokens::Audio),
+                (25, SpecialTokens::BeginAudio),
+                (32, SpecialTokens::Args),
+                (33, SpecialTokens::CallId),
+            ],
+            Self::V13 => &[
+                (24, SpecialTokens::Audio),
+                (25, SpecialTokens::BeginAudio),
+                (32, SpecialTokens::Args),
+                (33, SpecialTokens::CallId),
+                (34, SpecialTokens::Think),
+                (35, SpecialTokens::EndThink),
+            ],
+        };
+
+        let mut special_tokens = get_deprecated_special_tokens();
+        special_tokens.extend(added.iter().map(|&(rank, token)| SpecialTokenInfo {
+            rank,
+            token_str: token.as_str().to_string(),
+            is_control: true,
+        }));
+        special_tokens
+    }
+
     /// Returns the string representation of the version.
     ///
     /// # Returns
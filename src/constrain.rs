@@ -0,0 +1,264 @@
+//! Regex-constrained token masks for structured-output sampling.
+//!
+//! A [`TokenConstraint`] compiles a regular expression into a byte-level DFA and
+//! walks it over the token byte strings of a Tekken vocabulary. At every decoding
+//! step it reports which token IDs keep the output a valid prefix of the pattern,
+//! so samplers can mask logits without external tooling.
+//!
+//! Token byte strings are visited in sorted order so shared prefixes are walked
+//! once and whole subtrees are skipped as soon as the DFA dies.
+
+use regex_automata::dfa::{Automaton, StartKind, dense};
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+use regex_automata::{Anchored, MatchKind};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::errors::{Result, TokenizerError};
+use crate::tekkenizer::Tekkenizer;
+
+/// Position of a constrained generation within the pattern's DFA.
+///
+/// Obtained from [`TokenConstraint::start`] and advanced with [`TokenConstraint::advance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstraintState(StateID);
+
+/// A regex constraint over the token vocabulary of a [`Tekkenizer`].
+///
+/// The pattern must match the entire generated text; it is implicitly anchored
+/// at the start and EOS is only allowed once the text so far is a full match.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::constrain::TokenConstraint;
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let constraint = TokenConstraint::from_regex(&tokenizer, r"(yes|no)")?;
+///
+/// let mut state = constraint.start();
+/// let allowed = constraint.allowed_tokens(state);
+/// // ... sample `token_id` from `allowed` ...
+/// # let token_id = allowed[0];
+/// state = constraint.advance(state, token_id)?.expect("allowed token");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct TokenConstraint<'a> {
+    tokenizer: &'a Tekkenizer,
+    dfa: dense::DFA<Vec<u32>>,
+    start: StateID,
+    // States from which some continuation still fully matches the pattern
+    live: FxHashSet<StateID>,
+    // Regular token IDs sorted by their byte strings
+    sorted_ids: Vec<u32>,
+}
+
+impl<'a> TokenConstraint<'a> {
+    /// Compiles a regex constraint for the given tokenizer.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer whose vocabulary is constrained
+    /// * `pattern` - The regular expression the generated text must match
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern is invalid or cannot be compiled to a DFA.
+    pub fn from_regex(tokenizer: &'a Tekkenizer, pattern: &str) -> Result<Self> {
+        let dfa = dense::Builder::new()
+            .configure(
+                dense::Config::new()
+                    .start_kind(StartKind::Anchored)
+                    // Report every viable continuation, not just the leftmost-first one
+                    .match_kind(MatchKind::All),
+            )
+            .build(pattern)
+            .map_err(|e| {
+                TokenizerError::InvalidConfig(format!("Invalid constraint pattern: {e}"))
+            })?;
+        let start = dfa
+            .start_state(&start::Config::new().anchored(Anchored::Yes))
+            .map_err(|e| {
+                TokenizerError::InvalidConfig(format!("Invalid constraint pattern: {e}"))
+            })?;
+
+        let live = live_states(&dfa, start);
+
+        let first_regular = tokenizer.rank_offset();
+        let num_regular = tokenizer
+            .vocab_size()
+            .saturating_sub(tokenizer.num_special_tokens());
+        let mut sorted_ids: Vec<u32> = (0..)
+            .take(num_regular)
+            .map(|rank| (first_regular + rank).get())
+            .collect();
+        sorted_ids.sort_by_key(|&id| tokenizer.regular_token_bytes(id).unwrap_or_default());
+
+        Ok(Self {
+            tokenizer,
+            dfa,
+            start,
+            live,
+            sorted_ids,
+        })
+    }
+
+    /// Returns the state before any token has been generated.
+    #[must_use]
+    pub fn start(&self) -> ConstraintState {
+        ConstraintState(self.start)
+    }
+
+    /// Checks whether the text generated so far fully matches the pattern.
+    #[must_use]
+    pub fn is_match(&self, state: ConstraintState) -> bool {
+        self.dfa.is_match_state(self.dfa.next_eoi_state(state.0))
+    }
+
+    /// Advances the state by one generated token.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The current state
+    /// * `token_id` - The generated token ID
+    ///
+    /// # Returns
+    ///
+    /// The next state, or `None` if the token violates the constraint. Special
+    /// tokens (including EOS) never advance the state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token ID is out of vocabulary range.
+    pub fn advance(
+        &self,
+        state: ConstraintState,
+        token_id: u32,
+    ) -> Result<Option<ConstraintState>> {
+        if self.tokenizer.is_special_token(token_id) {
+            self.tokenizer.special_token(token_id)?;
+            return Ok(None);
+        }
+
+        let mut current = state.0;
+        for &byte in self.tokenizer.regular_token_bytes(token_id)? {
+            current = self.dfa.next_state(current, byte);
+            if self.is_rejecting(current) {
+                return Ok(None);
+            }
+        }
+        Ok(Some(ConstraintState(current)))
+    }
+
+    /// Computes the token IDs allowed at the given state.
+    ///
+    /// A regular token is allowed if appending it keeps the text a viable prefix
+    /// of the pattern. The EOS token is allowed when the text is a full match.
+    ///
+    /// # Returns
+    ///
+    /// The allowed token IDs, with regular tokens in byte-string order and EOS last.
+    #[must_use]
+    pub fn allowed_tokens(&self, state: ConstraintState) -> Vec<u32> {
+        let mut allowed = Vec::new();
+        // `states[d]` is the DFA state after the first `d` bytes of `prefix`
+        let mut states = vec![state.0];
+        let mut prefix: &[u8] = &[];
+        let mut dead_prefix: Option<&[u8]> = None;
+
+        for &id in &self.sorted_ids {
+            let Ok(bytes) = self.tokenizer.regular_token_bytes(id) else {
+                continue;
+            };
+            if let Some(dead) = dead_prefix {
+                if bytes.starts_with(dead) {
+                    continue;
+                }
+                dead_prefix = None;
+            }
+
+            let shared = prefix.iter().zip(bytes).take_while(|(a, b)| a == b).count();
+            states.truncate(shared + 1);
+
+            let mut viable = true;
+            for (offset, &byte) in bytes.iter().enumerate().skip(shared) {
+                let next = self.dfa.next_state(states[offset], byte);
+                if self.is_rejecting(next) {
+                    dead_prefix = Some(&bytes[..=offset]);
+                    viable = false;
+                    break;
+                }
+                states.push(next);
+            }
+            prefix = &bytes[..states.len() - 1];
+
+            if viable {
+                allowed.push(id);
+            }
+        }
+
+        if self.is_match(state)
+            && let Ok(eos_id) = self.tokenizer.eos_id()
+        {
+            allowed.push(eos_id);
+        }
+        allowed
+    }
+
+    /// Computes a dense mask over the vocabulary of the tokens allowed at `state`.
+    ///
+    /// # Returns
+    ///
+    /// A vector of length `vocab_size` where `mask[id]` is `true` if `id` is allowed.
+    #[must_use]
+    pub fn allowed_mask(&self, state: ConstraintState) -> Vec<bool> {
+        let mut mask = vec![false; self.tokenizer.vocab_size()];
+        for id in self.allowed_tokens(state) {
+            mask[id as usize] = true;
+        }
+        mask
+    }
+
+    fn is_rejecting(&self, state: StateID) -> bool {
+        !self.live.contains(&state)
+    }
+}
+
+/// Finds the reachable DFA states from which a full match is still possible.
+///
+/// Dense DFAs report matches one byte late, so a non-dead state is not enough:
+/// a state just past a completed match is not dead but may never match again.
+fn live_states(dfa: &dense::DFA<Vec<u32>>, start: StateID) -> FxHashSet<StateID> {
+    let mut predecessors: FxHashMap<StateID, Vec<StateID>> = FxHashMap::default();
+    let mut seen = FxHashSet::default();
+    let mut stack = vec![start];
+    let mut accepting = Vec::new();
+    seen.insert(start);
+
+    while let Some(state) = stack.pop() {
+        if dfa.is_match_state(dfa.next_eoi_state(state)) {
+            accepting.push(state);
+        }
+        for byte in 0..=u8::MAX {
+            let next = dfa.next_state(state, byte);
+            if dfa.is_dead_state(next) || dfa.is_quit_state(next) {
+                continue;
+            }
+            predecessors.entry(next).or_default().push(state);
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+
+    let mut live: FxHashSet<StateID> = accepting.iter().copied().collect();
+    while let Some(state) = accepting.pop() {
+        for &previous in predecessors.get(&state).into_iter().flatten() {
+            if live.insert(previous) {
+                accepting.push(previous);
+            }
+        }
+    }
+    live
+}
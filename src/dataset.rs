@@ -0,0 +1,328 @@
+//! Tokenized datasets as Arrow record batches and Parquet files.
+//!
+//! Training data loaders read Arrow and Parquet directly, so corpora tokenized
+//! here can be consumed without an intermediate JSONL step. Every document becomes
+//! one row of the [`schema`]:
+//!
+//! | Column         | Type          | Contents                                   |
+//! |----------------|---------------|--------------------------------------------|
+//! | `input_ids`    | `List<UInt32>`| Token IDs, including BOS/EOS if requested  |
+//! | `num_tokens`   | `UInt32`      | Length of `input_ids`                      |
+//! | `source_start` | `UInt64`      | Byte offset of the document in its source  |
+//! | `source_end`   | `UInt64`      | `source_start` plus the document's length  |
+//!
+//! The source offsets are supplied by the caller, e.g. the file position of each
+//! line of a text corpus, so rows can be traced back to their input.
+
+use std::io::Write;
+use std::sync::{Arc, OnceLock};
+
+use arrow_array::builder::{ListBuilder, UInt32Builder};
+use arrow_array::{ArrayRef, RecordBatch, UInt32Array, UInt64Array};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use parquet::arrow::ArrowWriter;
+
+use crate::errors::{Result, TokenizerError};
+use crate::parallel::ParallelismConfig;
+use crate::tekkenizer::Tekkenizer;
+
+/// Options for tokenizing datasets.
+///
+/// # Fields
+///
+/// * `add_bos` - Whether to add a Beginning of Sequence token to each document
+/// * `add_eos` - Whether to add an End of Sequence token to each document
+/// * `batch_size` - Rows buffered per record batch (and Parquet row group flush)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatasetOptions {
+    /// Whether to add a Beginning of Sequence token to each document.
+    pub add_bos: bool,
+    /// Whether to add an End of Sequence token to each document.
+    pub add_eos: bool,
+    /// Rows buffered per record batch.
+    pub batch_size: usize,
+}
+
+impl Default for DatasetOptions {
+    fn default() -> Self {
+        Self {
+            add_bos: true,
+            add_eos: true,
+            batch_size: 1024,
+        }
+    }
+}
+
+impl DatasetOptions {
+    /// Creates options with the default batch size.
+    #[must_use]
+    pub fn new(add_beginning_of_sequence: bool, add_end_of_sequence: bool) -> Self {
+        Self {
+            add_bos: add_beginning_of_sequence,
+            add_eos: add_end_of_sequence,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the number of rows buffered per record batch.
+    #[must_use]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+}
+
+/// Returns the Arrow schema of tokenized datasets.
+#[must_use]
+pub fn schema() -> SchemaRef {
+    static SCHEMA: OnceLock<SchemaRef> = OnceLock::new();
+    Arc::clone(SCHEMA.get_or_init(|| {
+        Arc::new(Schema::new(vec![
+            Field::new(
+                "input_ids",
+                DataType::List(Arc::new(Field::new_list_field(DataType::UInt32, false))),
+                false,
+            ),
+            Field::new("num_tokens", DataType::UInt32, false),
+            Field::new("source_start", DataType::UInt64, false),
+            Field::new("source_end", DataType::UInt64, false),
+        ]))
+    }))
+}
+
+/// Rows of a record batch being assembled.
+#[derive(Default)]
+struct Rows {
+    input_ids: Vec<Vec<u32>>,
+    source_ranges: Vec<(u64, u64)>,
+}
+
+impl Rows {
+    fn len(&self) -> usize {
+        self.input_ids.len()
+    }
+
+    fn push(&mut self, ids: Vec<u32>, text: &str, source_offset: u64) {
+        self.input_ids.push(ids);
+        self.source_ranges
+            .push((source_offset, source_offset + text.len() as u64));
+    }
+
+    /// Builds a record batch from the buffered rows and clears them.
+    fn take_batch(&mut self) -> Result<RecordBatch> {
+        let mut input_ids = ListBuilder::new(UInt32Builder::new())
+            .with_field(Arc::new(Field::new_list_field(DataType::UInt32, false)));
+        let mut num_tokens = Vec::with_capacity(self.len());
+        for ids in self.input_ids.drain(..) {
+            num_tokens.push(u32::try_from(ids.len()).map_err(|_| {
+                TokenizerError::InvalidConfig(format!(
+                    "Document of {} tokens exceeds the UInt32 num_tokens column",
+                    ids.len()
+                ))
+            })?);
+            input_ids.values().append_slice(&ids);
+            input_ids.append(true);
+        }
+        let (starts, ends): (Vec<u64>, Vec<u64>) = self.source_ranges.drain(..).unzip();
+
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(input_ids.finish()),
+            Arc::new(UInt32Array::from(num_tokens)),
+            Arc::new(UInt64Array::from(starts)),
+            Arc::new(UInt64Array::from(ends)),
+        ];
+        Ok(RecordBatch::try_new(schema(), columns)?)
+    }
+}
+
+impl Tekkenizer {
+    /// Tokenizes documents into an Arrow record batch with the dataset [`schema`].
+    ///
+    /// # Arguments
+    ///
+    /// * `documents` - Each document's text and its byte offset in the source
+    /// * `options` - Special tokens to add; `batch_size` is ignored
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a document fails to encode or the batch cannot be built.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::dataset::DatasetOptions;
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let batch = tokenizer.encode_record_batch(
+    ///     &[("first document", 0), ("second document", 15)],
+    ///     &DatasetOptions::default(),
+    /// )?;
+    /// assert_eq!(batch.num_rows(), 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_record_batch<S: AsRef<str> + Sync>(
+        &self,
+        documents: &[(S, u64)],
+        options: &DatasetOptions,
+    ) -> Result<RecordBatch> {
+        self.encode_record_batch_with(documents, options, &ParallelismConfig::sequential())
+    }
+
+    /// Tokenizes documents into an Arrow record batch on the given threads.
+    ///
+    /// Behaves like [`Tekkenizer::encode_record_batch`], encoding the
+    /// documents according to `parallelism`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a document fails to encode or the batch cannot be
+    /// built, or [`TokenizerError::Cancelled`] if `parallelism` is cancelled
+    /// before every document is encoded.
+    pub fn encode_record_batch_with<S: AsRef<str> + Sync>(
+        &self,
+        documents: &[(S, u64)],
+        options: &DatasetOptions,
+        parallelism: &ParallelismConfig,
+    ) -> Result<RecordBatch> {
+        let ids = parallelism
+            .try_map(documents, |(text, _)| {
+                self.encode(text.as_ref(), options.add_bos, options.add_eos)
+            })?
+            .into_complete()?;
+        let mut rows = Rows::default();
+        for (ids, (text, source_offset)) in ids.into_iter().zip(documents) {
+            rows.push(ids, text.as_ref(), *source_offset);
+        }
+        rows.take_batch()
+    }
+}
+
+/// Streams tokenized documents into a Parquet file.
+///
+/// Documents are buffered and written as one record batch every
+/// `batch_size` rows. Call [`ParquetDatasetWriter::finish`] to flush the last
+/// batch and write the Parquet footer.
+///
+/// Documents are encoded as they are written, on the calling thread. With
+/// [`ParquetDatasetWriter::with_parallelism`] they are instead encoded a batch
+/// at a time, on the threads of the given [`ParallelismConfig`]. Once its
+/// cancellation token is cancelled, writes fail with
+/// [`TokenizerError::Cancelled`] and [`ParquetDatasetWriter::finish`] completes
+/// the file with the documents written before.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use std::fs::File;
+/// use tekken::dataset::{DatasetOptions, ParquetDatasetWriter};
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let corpus = std::fs::read_to_string("corpus.txt")?;
+/// let file = File::create("corpus.parquet")?;
+/// let mut writer = ParquetDatasetWriter::new(&tokenizer, file, DatasetOptions::default())?;
+///
+/// let mut offset = 0;
+/// for line in corpus.split_inclusive('\n') {
+///     writer.write(line.trim_end(), offset)?;
+///     offset += line.len() as u64;
+/// }
+/// writer.finish()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct ParquetDatasetWriter<'a, W: Write + Send> {
+    tokenizer: &'a Tekkenizer,
+    writer: ArrowWriter<W>,
+    options: DatasetOptions,
+    parallelism: ParallelismConfig,
+    rows: Rows,
+    // Documents waiting to be encoded with `parallelism`
+    pending: Vec<(String, u64)>,
+}
+
+impl<'a, W: Write + Send> ParquetDatasetWriter<'a, W> {
+    /// Creates a writer with the default Parquet writer properties.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Parquet writer cannot be created.
+    pub fn new(tokenizer: &'a Tekkenizer, sink: W, options: DatasetOptions) -> Result<Self> {
+        Ok(Self {
+            tokenizer,
+            writer: ArrowWriter::try_new(sink, schema(), None)?,
+            options,
+            parallelism: ParallelismConfig::sequential(),
+            rows: Rows::default(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Encodes documents a batch at a time on the threads of `parallelism`.
+    ///
+    /// Encoding errors are then reported by the [`ParquetDatasetWriter::write`]
+    /// call that fills the batch, or by [`ParquetDatasetWriter::finish`].
+    #[must_use]
+    pub fn with_parallelism(mut self, parallelism: ParallelismConfig) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// Tokenizes a document and buffers it as a row.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The document
+    /// * `source_offset` - Byte offset of the document in its source
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document fails to encode or a full batch cannot
+    /// be written, or [`TokenizerError::Cancelled`] if the parallelism config
+    /// was cancelled.
+    pub fn write(&mut self, text: &str, source_offset: u64) -> Result<()> {
+        if self.parallelism.is_cancelled() {
+            return Err(TokenizerError::Cancelled);
+        }
+        if self.parallelism.is_parallel() {
+            self.pending.push((text.to_string(), source_offset));
+        } else {
+            let ids = self
+                .tokenizer
+                .encode(text, self.options.add_bos, self.options.add_eos)?;
+            self.rows.push(ids, text, source_offset);
+        }
+        if self.rows.len() + self.pending.len() >= self.options.batch_size.max(1) {
+            self.flush_rows()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the buffered rows and the Parquet footer, returning the sink.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn finish(mut self) -> Result<W> {
+        self.flush_rows()?;
+        Ok(self.writer.into_inner()?)
+    }
+
+    fn flush_rows(&mut self) -> Result<()> {
+        if !self.pending.is_empty() {
+            // Every accepted document is written, even after cancellation
+            let batch = self.tokenizer.encode_record_batch_with(
+                &self.pending,
+                &self.options,
+                &self.parallelism.without_cancellation(),
+            )?;
+            self.pending.clear();
+            self.writer.write(&batch)?;
+        }
+        if self.rows.len() > 0 {
+            let batch = self.rows.take_batch()?;
+            self.writer.write(&batch)?;
+        }
+        Ok(())
+    }
+}
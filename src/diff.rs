@@ -0,0 +1,340 @@
+//! Comparison of two tokenizer files.
+//!
+//! When a new tokenizer revision ships, [`diff`] reports which vocabulary tokens
+//! were added, removed or moved to a different rank, which special tokens changed,
+//! and which configuration values differ.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use base64::{Engine as _, engine::general_purpose};
+
+use crate::config::{ModelData, TokenStrPolicy, detect_version};
+use crate::errors::Result;
+use crate::special_tokens::SpecialTokenInfo;
+
+/// Number of entries per category shown by the `Display` implementation.
+const DISPLAY_LIMIT: usize = 10;
+
+/// A vocabulary token identified by its raw BPE rank.
+///
+/// # Fields
+///
+/// * `rank` - Raw BPE rank (token ID minus the number of special tokens)
+/// * `bytes` - The token's bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenEntry {
+    /// Raw BPE rank of the token.
+    pub rank: usize,
+    /// The token's bytes.
+    pub bytes: Vec<u8>,
+}
+
+/// A vocabulary token present in both files at different ranks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RankChange {
+    /// The token's bytes.
+    pub bytes: Vec<u8>,
+    /// Rank in the first file.
+    pub old_rank: usize,
+    /// Rank in the second file.
+    pub new_rank: usize,
+}
+
+/// A change to the special token defined at a rank.
+#[derive(Debug, Clone)]
+pub enum SpecialTokenChange {
+    /// A special token defined only in the second file.
+    Added(SpecialTokenInfo),
+    /// A special token defined only in the first file.
+    Removed(SpecialTokenInfo),
+    /// The special token at a rank differs between the files.
+    Changed {
+        /// Definition in the first file.
+        old: SpecialTokenInfo,
+        /// Definition in the second file.
+        new: SpecialTokenInfo,
+    },
+}
+
+/// A configuration value that differs between the files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigChange {
+    /// Name of the configuration field.
+    pub field: String,
+    /// Value in the first file.
+    pub old: String,
+    /// Value in the second file.
+    pub new: String,
+}
+
+/// Differences between two tokenizer files.
+///
+/// # Fields
+///
+/// * `added` - Vocabulary tokens only in the second file
+/// * `removed` - Vocabulary tokens only in the first file
+/// * `reordered` - Vocabulary tokens present in both files at different ranks
+/// * `special_tokens` - Special token changes, by rank
+/// * `config` - Configuration and pattern differences
+#[derive(Debug, Clone, Default)]
+pub struct VocabDiff {
+    /// Vocabulary tokens only in the second file, by rank.
+    pub added: Vec<TokenEntry>,
+    /// Vocabulary tokens only in the first file, by rank.
+    pub removed: Vec<TokenEntry>,
+    /// Vocabulary tokens present in both files at different ranks.
+    pub reordered: Vec<RankChange>,
+    /// Special token changes, by rank.
+    pub special_tokens: Vec<SpecialTokenChange>,
+    /// Configuration and pattern differences.
+    pub config: Vec<ConfigChange>,
+}
+
+impl VocabDiff {
+    /// Returns `true` if the two files are equivalent.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.reordered.is_empty()
+            && self.special_tokens.is_empty()
+            && self.config.is_empty()
+    }
+}
+
+/// Compares two tokenizer files.
+///
+/// # Arguments
+///
+/// * `path_a` - Path to the first (old) tokenizer file
+/// * `path_b` - Path to the second (new) tokenizer file
+///
+/// # Returns
+///
+/// The differences going from `path_a` to `path_b`.
+///
+/// # Errors
+///
+/// Returns an error if either file cannot be read or parsed.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// let changes = tekken::diff("tekken-v7.json", "tekken-v11.json")?;
+/// println!("{changes}");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn diff<P: AsRef<Path>, Q: AsRef<Path>>(path_a: P, path_b: Q) -> Result<VocabDiff> {
+    let a = ModelData::from_slice(&std::fs::read(path_a)?, TokenStrPolicy::Drop)?;
+    let b = ModelData::from_slice(&std::fs::read(path_b)?, TokenStrPolicy::Drop)?;
+    diff_model_data(&a, &b)
+}
+
+/// Compares two in-memory tokenizer definitions.
+///
+/// # Errors
+///
+/// Returns an error if a vocabulary entry is not valid base64.
+pub fn diff_model_data(a: &ModelData, b: &ModelData) -> Result<VocabDiff> {
+    let vocab_a = decode_vocab(a)?;
+    let vocab_b = decode_vocab(b)?;
+    let ranks_a: HashMap<&[u8], usize> = vocab_a
+        .iter()
+        .map(|entry| (entry.bytes.as_slice(), entry.rank))
+        .collect();
+    let ranks_b: HashMap<&[u8], usize> = vocab_b
+        .iter()
+        .map(|entry| (entry.bytes.as_slice(), entry.rank))
+        .collect();
+
+    let mut result = VocabDiff {
+        added: vocab_b
+            .iter()
+            .filter(|entry| !ranks_a.contains_key(entry.bytes.as_slice()))
+            .cloned()
+            .collect(),
+        removed: vocab_a
+            .iter()
+            .filter(|entry| !ranks_b.contains_key(entry.bytes.as_slice()))
+            .cloned()
+            .collect(),
+        reordered: vocab_a
+            .iter()
+            .filter_map(|entry| {
+                let &new_rank = ranks_b.get(entry.bytes.as_slice())?;
+                (new_rank != entry.rank).then(|| RankChange {
+                    bytes: entry.bytes.clone(),
+                    old_rank: entry.rank,
+                    new_rank,
+                })
+            })
+            .collect(),
+        special_tokens: diff_special_tokens(a, b),
+        config: Vec::new(),
+    };
+
+    let mut compare = |field: &str, old: String, new: String| {
+        if old != new {
+            result.config.push(ConfigChange {
+                field: field.to_string(),
+                old,
+                new,
+            });
+        }
+    };
+    compare(
+        "pattern",
+        a.config.pattern.clone(),
+        b.config.pattern.clone(),
+    );
+    compare(
+        "num_vocab_tokens",
+        a.config.num_vocab_tokens.to_string(),
+        b.config.num_vocab_tokens.to_string(),
+    );
+    compare(
+        "default_vocab_size",
+        a.config.default_vocab_size.to_string(),
+        b.config.default_vocab_size.to_string(),
+    );
+    compare(
+        "default_num_special_tokens",
+        a.config.default_num_special_tokens.to_string(),
+        b.config.default_num_special_tokens.to_string(),
+    );
+    compare(
+        "version",
+        a.config.version.clone(),
+        b.config.version.clone(),
+    );
+    compare(
+        "audio",
+        serde_json::to_string(&a.audio)?,
+        serde_json::to_string(&b.audio)?,
+    );
+
+    Ok(result)
+}
+
+fn decode_vocab(data: &ModelData) -> Result<Vec<TokenEntry>> {
+    let mut entries = data
+        .vocab
+        .iter()
+        .map(|token| {
+            Ok(TokenEntry {
+                rank: token.rank,
+                bytes: general_purpose::STANDARD.decode(&token.token_bytes)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.rank);
+    Ok(entries)
+}
+
+fn diff_special_tokens(a: &ModelData, b: &ModelData) -> Vec<SpecialTokenChange> {
+    let by_rank = |data: &ModelData| -> HashMap<usize, SpecialTokenInfo> {
+        data.special_tokens
+            .clone()
+            .unwrap_or_else(|| detect_version(data).default_special_tokens())
+            .into_iter()
+            .map(|token| (token.rank, token))
+            .collect()
+    };
+    let mut old = by_rank(a);
+    let new = by_rank(b);
+
+    let mut ranks: Vec<usize> = old.keys().chain(new.keys()).copied().collect();
+    ranks.sort_unstable();
+    ranks.dedup();
+
+    ranks
+        .into_iter()
+        .filter_map(|rank| match (old.remove(&rank), new.get(&rank)) {
+            (None, Some(token)) => Some(SpecialTokenChange::Added(token.clone())),
+            (Some(token), None) => Some(SpecialTokenChange::Removed(token)),
+            (Some(old), Some(new))
+                if old.token_str != new.token_str || old.is_control != new.is_control =>
+            {
+                Some(SpecialTokenChange::Changed {
+                    old,
+                    new: new.clone(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Renders token bytes as a quoted string, escaping bytes that are not valid UTF-8.
+pub(crate) fn render_bytes(bytes: &[u8]) -> String {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => format!("{text:?}"),
+        Err(_) => format!("b\"{}\"", bytes.escape_ascii()),
+    }
+}
+
+fn write_truncated<T>(
+    f: &mut fmt::Formatter<'_>,
+    items: &[T],
+    mut write_item: impl FnMut(&mut fmt::Formatter<'_>, &T) -> fmt::Result,
+) -> fmt::Result {
+    for item in items.iter().take(DISPLAY_LIMIT) {
+        write_item(f, item)?;
+    }
+    if items.len() > DISPLAY_LIMIT {
+        writeln!(f, "  ... and {} more", items.len() - DISPLAY_LIMIT)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for VocabDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Tokenizers are identical");
+        }
+
+        writeln!(f, "Added tokens: {}", self.added.len())?;
+        write_truncated(f, &self.added, |f, entry| {
+            writeln!(f, "  + [{}] {}", entry.rank, render_bytes(&entry.bytes))
+        })?;
+        writeln!(f, "Removed tokens: {}", self.removed.len())?;
+        write_truncated(f, &self.removed, |f, entry| {
+            writeln!(f, "  - [{}] {}", entry.rank, render_bytes(&entry.bytes))
+        })?;
+        writeln!(f, "Reordered tokens: {}", self.reordered.len())?;
+        write_truncated(f, &self.reordered, |f, change| {
+            writeln!(
+                f,
+                "  ~ {} {} -> {}",
+                render_bytes(&change.bytes),
+                change.old_rank,
+                change.new_rank
+            )
+        })?;
+
+        writeln!(f, "Special token changes: {}", self.special_tokens.len())?;
+        for change in &self.special_tokens {
+            match change {
+                SpecialTokenChange::Added(token) => {
+                    writeln!(f, "  + [{}] {}", token.rank, token.token_str)?;
+                }
+                SpecialTokenChange::Removed(token) => {
+                    writeln!(f, "  - [{}] {}", token.rank, token.token_str)?;
+                }
+                SpecialTokenChange::Changed { old, new } => writeln!(
+                    f,
+                    "  ~ [{}] {} (control: {}) -> {} (control: {})",
+                    old.rank, old.token_str, old.is_control, new.token_str, new.is_control
+                )?,
+            }
+        }
+
+        writeln!(f, "Config changes: {}", self.config.len())?;
+        for change in &self.config {
+            writeln!(f, "  {}: {} -> {}", change.field, change.old, change.new)?;
+        }
+        Ok(())
+    }
+}
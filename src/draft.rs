@@ -0,0 +1,119 @@
+//! Verification of draft tokens for speculative decoding.
+//!
+//! A speculative-decoding server proposes draft tokens and keeps those the
+//! target model would have produced. When drafts come from a different
+//! tokenizer, the server turns the draft text into tokens of this vocabulary
+//! and must check them against the text the target accepted: only tokens
+//! matching its canonical encoding can be fed to the model as they are.
+//!
+//! The end of the text is ambiguous, since BPE merges can cross it:
+//! `"hello wor"` ends with `" wor"`, while the text may continue as
+//! `"hello world"` and encode as `" world"`. [`Tekkenizer::verify_draft`]
+//! therefore also accepts draft tokens for the tail of the text that are a
+//! possible start of the encoding of some continuation, using the completions
+//! of [`Tekkenizer::encode_with_unstable`].
+
+use crate::errors::Result;
+use crate::tekkenizer::Tekkenizer;
+
+/// How much of a draft agrees with the canonical encoding of a text, from
+/// [`Tekkenizer::verify_draft`].
+///
+/// # Fields
+///
+/// * `accepted` - Number of leading draft tokens consistent with the text
+/// * `accepted_bytes` - Number of bytes of the text those tokens cover
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DraftVerification {
+    pub accepted: usize,
+    pub accepted_bytes: usize,
+}
+
+impl DraftVerification {
+    /// Whether the accepted tokens cover the whole text.
+    #[must_use]
+    pub fn covers(&self, text: &str) -> bool {
+        self.accepted_bytes == text.len()
+    }
+}
+
+impl Tekkenizer {
+    /// Verifies how many draft tokens agree with the canonical encoding of a
+    /// text.
+    ///
+    /// Draft tokens are accepted while they equal the tokens of `text` that no
+    /// continuation can change. Tokens for the unstable tail of `text` are
+    /// accepted while they are a possible start of the encoding of the tail
+    /// followed by more text, and stay within the text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The target text, without BOS
+    /// * `draft` - The draft tokens, in this tokenizer's vocabulary
+    ///
+    /// # Returns
+    ///
+    /// The number of accepted draft tokens and the bytes of `text` they cover.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if pretokenization fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let draft = tokenizer.encode("The quick brown fox jumps", false, false)?;
+    /// let verification = tokenizer.verify_draft("The quick brown fox jum", &draft)?;
+    /// // " jumps" runs past the text, so it cannot be verified yet
+    /// assert_eq!(verification.accepted, draft.len() - 1);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn verify_draft(&self, text: &str, draft: &[u32]) -> Result<DraftVerification> {
+        let unstable = self.encode_with_unstable(text, false)?;
+        let stable = unstable
+            .tokens
+            .iter()
+            .zip(draft)
+            .take_while(|(token, drafted)| token == drafted)
+            .count();
+        if stable < unstable.tokens.len() {
+            let mut accepted_bytes = 0;
+            for &token in &draft[..stable] {
+                accepted_bytes += self.regular_token_bytes(token)?.len();
+            }
+            return Ok(DraftVerification {
+                accepted: stable,
+                accepted_bytes,
+            });
+        }
+
+        // Extend over the tail while some completion starts with the draft
+        let tail_len = text.len() - unstable.unstable_start;
+        let mut accepted = stable;
+        let mut tail_bytes = 0;
+        for end in stable + 1..=draft.len() {
+            let candidate = &draft[stable..end];
+            if !unstable
+                .completions
+                .iter()
+                .any(|completion| completion.starts_with(candidate))
+            {
+                break;
+            }
+            let len = self.regular_token_bytes(draft[end - 1])?.len();
+            if tail_bytes + len > tail_len {
+                break;
+            }
+            tail_bytes += len;
+            accepted = end;
+        }
+
+        Ok(DraftVerification {
+            accepted,
+            accepted_bytes: unstable.unstable_start + tail_bytes,
+        })
+    }
+}
@@ -0,0 +1,449 @@
+//! Model-ready encodings with attention masks, padding and truncation.
+//!
+//! [`Tekkenizer::encode_plus`] returns an [`Encoding`] laid out like the output of
+//! Hugging Face `tokenizers`: token IDs alongside an attention mask, a special
+//! tokens mask and per-token offsets, all of the same length. Code written
+//! against that layout can consume Tekken encodings unchanged.
+//!
+//! [`Tekkenizer::encode_pair`] joins two segments the way Hugging Face's Llama and
+//! Mistral tokenizers do, `<s> A </s> <s> B </s>` (EOS only with `add_eos`), and
+//! marks the second segment with type ID `1`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{Result, TokenizerError};
+use crate::parallel::{BatchOutput, ParallelismConfig};
+use crate::tekkenizer::Tekkenizer;
+
+/// How an encoding is padded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Padding {
+    /// No padding.
+    #[default]
+    None,
+    /// Pad to the longest encoding of the batch.
+    Longest,
+    /// Pad to a fixed length.
+    MaxLength(usize),
+}
+
+/// Which end of an encoding padding is added to.
+///
+/// Decoder-only models generating for a batch need left padding, so that the
+/// last token of every row is a real token the next one follows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaddingSide {
+    /// Pad after the tokens.
+    #[default]
+    Right,
+    /// Pad before the tokens.
+    Left,
+}
+
+/// Options for [`Tekkenizer::encode_plus`].
+///
+/// # Fields
+///
+/// * `add_bos` - Whether to add a Beginning of Sequence token at the start
+/// * `add_eos` - Whether to add an End of Sequence token at the end
+/// * `max_length` - Truncate to at most this many tokens, BOS/EOS included
+/// * `padding` - How to pad the encoding
+/// * `pad_to_multiple_of` - Round the padded length up to a multiple of this value
+/// * `padding_side` - Which end padding is added to
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::encoding::{EncodingOptions, Padding};
+///
+/// let options = EncodingOptions::new(true, false)
+///     .with_max_length(512)
+///     .with_padding(Padding::MaxLength(512));
+/// assert_eq!(options.max_length, Some(512));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EncodingOptions {
+    /// Whether to add a Beginning of Sequence token at the start.
+    pub add_bos: bool,
+    /// Whether to add an End of Sequence token at the end.
+    pub add_eos: bool,
+    /// Truncate to at most this many tokens, BOS/EOS included.
+    pub max_length: Option<usize>,
+    /// How to pad the encoding.
+    pub padding: Padding,
+    /// Round the padded length up to a multiple of this value.
+    pub pad_to_multiple_of: Option<usize>,
+    /// Which end padding is added to.
+    pub padding_side: PaddingSide,
+}
+
+impl EncodingOptions {
+    /// Creates options without truncation or padding.
+    #[must_use]
+    pub fn new(add_beginning_of_sequence: bool, add_end_of_sequence: bool) -> Self {
+        Self {
+            add_bos: add_beginning_of_sequence,
+            add_eos: add_end_of_sequence,
+            ..Self::default()
+        }
+    }
+
+    /// Truncates encodings to at most `max_length` tokens.
+    #[must_use]
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    /// Sets the padding strategy.
+    #[must_use]
+    pub fn with_padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Rounds padded lengths up to a multiple of `multiple`.
+    #[must_use]
+    pub fn with_pad_to_multiple_of(mut self, multiple: usize) -> Self {
+        self.pad_to_multiple_of = Some(multiple);
+        self
+    }
+
+    /// Sets which end padding is added to.
+    #[must_use]
+    pub fn with_padding_side(mut self, padding_side: PaddingSide) -> Self {
+        self.padding_side = padding_side;
+        self
+    }
+}
+
+/// An encoding with the masks and offsets expected by transformer models.
+///
+/// All vectors have the same length.
+///
+/// # Fields
+///
+/// * `ids` - Token IDs, including BOS/EOS and padding
+/// * `type_ids` - Segment of each token: `0` for the first text, `1` for the second of a pair
+/// * `attention_mask` - `1` for real tokens, `0` for padding
+/// * `special_tokens_mask` - `1` for special tokens (including padding), `0` for text tokens
+/// * `offsets` - Byte range of the token's segment text covered by each token;
+///   `(0, 0)` for special tokens
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Encoding {
+    /// Token IDs, including BOS/EOS and padding.
+    pub ids: Vec<u32>,
+    /// Segment of each token: `0` for the first text, `1` for the second of a pair.
+    pub type_ids: Vec<u32>,
+    /// `1` for real tokens, `0` for padding.
+    pub attention_mask: Vec<u32>,
+    /// `1` for special tokens (including padding), `0` for text tokens.
+    pub special_tokens_mask: Vec<u32>,
+    /// Byte range of the token's segment text covered by each token; `(0, 0)` for special tokens.
+    pub offsets: Vec<(usize, usize)>,
+}
+
+impl Encoding {
+    /// Returns the number of tokens, padding included.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    /// Checks whether the encoding has no tokens.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Returns the position of each token for the model's position embeddings.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Position of the first real token, e.g. the number of tokens
+    ///   of a cached prefix this encoding continues
+    ///
+    /// # Returns
+    ///
+    /// Consecutive positions from `offset` for real tokens and `0` for padding,
+    /// on either side.
+    #[must_use]
+    pub fn position_ids(&self, offset: u32) -> Vec<u32> {
+        position_ids(&self.attention_mask, offset)
+    }
+
+    fn push(&mut self, id: u32, type_id: u32, special: u32, offsets: (usize, usize)) {
+        self.ids.push(id);
+        self.type_ids.push(type_id);
+        self.attention_mask.push(1);
+        self.special_tokens_mask.push(special);
+        self.offsets.push(offsets);
+    }
+
+    fn pad_to(&mut self, length: usize, pad_id: u32, side: PaddingSide) {
+        let padding = length.saturating_sub(self.ids.len());
+        pad(&mut self.ids, padding, pad_id, side);
+        pad(&mut self.type_ids, padding, 0, side);
+        pad(&mut self.attention_mask, padding, 0, side);
+        pad(&mut self.special_tokens_mask, padding, 1, side);
+        pad(&mut self.offsets, padding, (0, 0), side);
+    }
+}
+
+impl Tekkenizer {
+    /// Encodes text into an [`Encoding`] with masks, offsets, truncation and padding.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to encode
+    /// * `options` - Special tokens, truncation and padding settings
+    ///
+    /// # Returns
+    ///
+    /// The encoding. [`Padding::Longest`] leaves a single encoding unpadded
+    /// except for `pad_to_multiple_of`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if BOS/EOS or padding is requested but the token is not
+    /// in the vocabulary, or `max_length` cannot hold the requested BOS/EOS.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::encoding::{EncodingOptions, Padding};
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let options = EncodingOptions::new(true, true).with_padding(Padding::MaxLength(16));
+    /// let encoding = tokenizer.encode_plus("Hello, world!", &options)?;
+    /// assert_eq!(encoding.ids.len(), 16);
+    /// assert_eq!(encoding.attention_mask.iter().sum::<u32>(), 6);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_plus(&self, text: &str, options: &EncodingOptions) -> Result<Encoding> {
+        let mut encoding = self.encode_unpadded(&[text], options)?;
+        self.pad_single(&mut encoding, options)?;
+        Ok(encoding)
+    }
+
+    /// Encodes a pair of texts, e.g. a query and a passage for a cross-encoder.
+    ///
+    /// Each segment gets its own BOS (and EOS with `add_eos`); tokens of `text_b`
+    /// and its special tokens have type ID `1`. With `max_length`, tokens are
+    /// removed from the end of the longer segment first.
+    ///
+    /// # Arguments
+    ///
+    /// * `text_a` - The first segment
+    /// * `text_b` - The second segment
+    /// * `options` - Special tokens, truncation and padding settings
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`Tekkenizer::encode_plus`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::encoding::EncodingOptions;
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let options = EncodingOptions::new(true, true).with_max_length(512);
+    /// let encoding = tokenizer.encode_pair("what is tekken?", "Tekken is a tokenizer.", &options)?;
+    /// assert_eq!(encoding.type_ids.first(), Some(&0));
+    /// assert_eq!(encoding.type_ids.last(), Some(&1));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_pair(
+        &self,
+        text_a: &str,
+        text_b: &str,
+        options: &EncodingOptions,
+    ) -> Result<Encoding> {
+        let mut encoding = self.encode_unpadded(&[text_a, text_b], options)?;
+        self.pad_single(&mut encoding, options)?;
+        Ok(encoding)
+    }
+
+    /// Encodes a batch of texts into [`Encoding`]s.
+    ///
+    /// With [`Padding::Longest`], every encoding is padded to the length of the
+    /// longest one. Texts are encoded with the default [`ParallelismConfig`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`Tekkenizer::encode_plus`].
+    pub fn encode_plus_batch<S: AsRef<str> + Sync>(
+        &self,
+        texts: &[S],
+        options: &EncodingOptions,
+    ) -> Result<Vec<Encoding>> {
+        self.encode_plus_batch_with(texts, options, &ParallelismConfig::default())?
+            .into_complete()
+    }
+
+    /// Encodes a batch of texts into [`Encoding`]s on the given threads.
+    ///
+    /// Behaves like [`Tekkenizer::encode_plus_batch`], encoding the texts
+    /// according to `parallelism`. If `parallelism` is cancelled, the output
+    /// holds the leading texts encoded before it, marked as
+    /// [`BatchOutput::cancelled`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`Tekkenizer::encode_plus`].
+    pub fn encode_plus_batch_with<S: AsRef<str> + Sync>(
+        &self,
+        texts: &[S],
+        options: &EncodingOptions,
+        parallelism: &ParallelismConfig,
+    ) -> Result<BatchOutput<Encoding>> {
+        let mut batch = parallelism.try_map(texts, |text| {
+            self.encode_unpadded(&[text.as_ref()], options)
+        })?;
+
+        let encodings = &mut batch.results;
+        let longest = encodings.iter().map(Encoding::len).max().unwrap_or(0);
+        let Some(length) = padded_length(longest, options) else {
+            return Ok(batch);
+        };
+        if encodings.iter().any(|encoding| encoding.len() < length) {
+            let pad_id = self.pad_id()?;
+            for encoding in encodings {
+                encoding.pad_to(length, pad_id, options.padding_side);
+            }
+        }
+        Ok(batch)
+    }
+
+    fn pad_single(&self, encoding: &mut Encoding, options: &EncodingOptions) -> Result<()> {
+        if let Some(length) = padded_length(encoding.len(), options)
+            && length > encoding.len()
+        {
+            encoding.pad_to(length, self.pad_id()?, options.padding_side);
+        }
+        Ok(())
+    }
+
+    /// Encodes one or two segments, each wrapped in BOS/EOS, without padding.
+    fn encode_unpadded(&self, texts: &[&str], options: &EncodingOptions) -> Result<Encoding> {
+        let bos_id = if options.add_bos {
+            Some(self.bos_id()?)
+        } else {
+            None
+        };
+        let eos_id = if options.add_eos {
+            Some(self.eos_id()?)
+        } else {
+            None
+        };
+
+        let num_special =
+            texts.len() * (usize::from(options.add_bos) + usize::from(options.add_eos));
+        let mut segments = texts
+            .iter()
+            .map(|text| self.encode(text, false, false))
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(max_length) = options.max_length {
+            let max_text = max_length.checked_sub(num_special).ok_or_else(|| {
+                TokenizerError::InvalidConfig(format!(
+                    "max_length ({max_length}) is too small for {num_special} BOS/EOS tokens"
+                ))
+            })?;
+            truncate_longest_first(&mut segments, max_text);
+        }
+
+        let mut encoding = Encoding::default();
+        for (type_id, tokens) in (0..).zip(segments) {
+            if let Some(id) = bos_id {
+                encoding.push(id, type_id, 1, (0, 0));
+            }
+            let mut offset = 0;
+            for id in tokens {
+                let end = offset + self.regular_token_bytes(id)?.len();
+                encoding.push(id, type_id, 0, (offset, end));
+                offset = end;
+            }
+            if let Some(id) = eos_id {
+                encoding.push(id, type_id, 1, (0, 0));
+            }
+        }
+        Ok(encoding)
+    }
+}
+
+/// Drops tokens from the end of the longest segment until all fit in `max_tokens`.
+fn truncate_longest_first(segments: &mut [Vec<u32>], max_tokens: usize) {
+    let mut total: usize = segments.iter().map(Vec::len).sum();
+    while total > max_tokens {
+        // `max_by_key` picks the last of equal maxima, so ties trim the later segment
+        let Some(longest) = segments.iter_mut().max_by_key(|segment| segment.len()) else {
+            return;
+        };
+        longest.pop();
+        total -= 1;
+    }
+}
+
+/// Returns position IDs for a row with the given attention mask.
+///
+/// Real tokens, marked `1` in `attention_mask`, get consecutive positions
+/// starting at `offset`; padding gets `0`. The positions of a left-padded row
+/// therefore start at its first real token rather than at the padding.
+///
+/// # Arguments
+///
+/// * `attention_mask` - `1` for real tokens, `0` for padding
+/// * `offset` - Position of the first real token, e.g. the length of a cached
+///   prefix the row continues
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::encoding::position_ids;
+///
+/// assert_eq!(position_ids(&[0, 0, 1, 1, 1], 0), vec![0, 0, 0, 1, 2]);
+/// assert_eq!(position_ids(&[1, 1, 0], 5), vec![5, 6, 0]);
+/// ```
+#[must_use]
+pub fn position_ids(attention_mask: &[u32], offset: u32) -> Vec<u32> {
+    let mut next = offset;
+    attention_mask
+        .iter()
+        .map(|&bit| {
+            if bit == 0 {
+                return 0;
+            }
+            let position = next;
+            next += 1;
+            position
+        })
+        .collect()
+}
+
+/// Adds `padding` copies of `value` to one end of `values`.
+pub(crate) fn pad<T: Clone>(values: &mut Vec<T>, padding: usize, value: T, side: PaddingSide) {
+    let padding = std::iter::repeat_n(value, padding);
+    match side {
+        PaddingSide::Right => values.extend(padding),
+        PaddingSide::Left => {
+            values.splice(0..0, padding);
+        }
+    }
+}
+
+/// Length to pad encodings of at most `length` tokens to, if padding is enabled.
+fn padded_length(length: usize, options: &EncodingOptions) -> Option<usize> {
+    let target = match options.padding {
+        Padding::None => return None,
+        Padding::Longest => length,
+        Padding::MaxLength(max_length) => length.max(max_length),
+    };
+    let target = match options.pad_to_multiple_of {
+        Some(multiple) if multiple > 0 => target.div_ceil(multiple) * multiple,
+        _ => target,
+    };
+    Some(target)
+}
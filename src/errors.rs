@@ -1,9 +1,17 @@
+use alloc::string::String;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
 use thiserror::Error;
 
+#[cfg(feature = "std")]
+use crate::request::RequestViolation;
+
 /// Type alias for Results with `TokenizerError`.
 ///
 /// This provides a convenient shorthand for Result types throughout the library.
-pub type Result<T> = std::result::Result<T, TokenizerError>;
+pub type Result<T> = core::result::Result<T, TokenizerError>;
 
 /// Comprehensive error type for tokenizer operations.
 ///
@@ -19,9 +27,13 @@ pub type Result<T> = std::result::Result<T, TokenizerError>;
 /// * **Processing Errors**: Tokenization and audio processing failures
 /// * **Configuration Errors**: Invalid parameters or missing tokens
 /// * **Policy Errors**: Special token handling violations
+///
+/// Errors from loading a tokenizer file are wrapped in [`TokenizerError::Load`],
+/// which names the file and the [`LoadStage`] that failed.
 #[derive(Error, Debug)]
 pub enum TokenizerError {
     /// I/O operation failed (file reading, writing, etc.).
+    #[cfg(feature = "std")]
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -29,7 +41,37 @@ pub enum TokenizerError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    /// SIMD-accelerated JSON parsing failed.
+    #[cfg(feature = "simd-json")]
+    #[error("JSON error: {0}")]
+    SimdJson(#[from] simd_json::Error),
+
+    /// Building an Arrow record batch failed.
+    #[cfg(feature = "dataset")]
+    #[error("Arrow error: {0}")]
+    Arrow(#[from] arrow_schema::ArrowError),
+
+    /// Writing a Parquet file failed.
+    #[cfg(feature = "dataset")]
+    #[error("Parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
+
+    /// Creating a `candle` tensor failed.
+    #[cfg(feature = "candle")]
+    #[error("Candle error: {0}")]
+    Candle(#[from] candle_core::Error),
+
+    /// Watching a tokenizer file for changes failed.
+    #[cfg(feature = "notify")]
+    #[error("Watch error: {0}")]
+    Watch(#[from] notify::Error),
+
+    /// Writing formatted output failed.
+    #[error("Formatting error: {0}")]
+    Fmt(#[from] core::fmt::Error),
+
     /// Base64 decoding failed.
+    #[cfg(feature = "std")]
     #[error("Base64 decode error: {0}")]
     Base64(#[from] base64::DecodeError),
 
@@ -41,6 +83,10 @@ pub enum TokenizerError {
     #[error("Audio error: {0}")]
     Audio(String),
 
+    /// Image loading or preprocessing failed.
+    #[error("Image error: {0}")]
+    Image(String),
+
     /// Configuration parameters are invalid or inconsistent.
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
@@ -49,6 +95,19 @@ pub enum TokenizerError {
     #[error("Token not found: {0}")]
     TokenNotFound(String),
 
+    /// Token ID does not exist in the vocabulary.
+    #[error("Token ID {token_id} is out of range for vocabulary of size {vocab_size}")]
+    TokenOutOfRange {
+        /// The offending token ID.
+        token_id: u32,
+        /// Total vocabulary size including special tokens.
+        vocab_size: usize,
+    },
+
+    /// Generated tokens do not form valid tool calls.
+    #[error("Malformed tool call: {0}")]
+    MalformedToolCall(String),
+
     /// Operation violated the specified special token policy.
     #[error("Special token policy violation: {0}")]
     SpecialTokenPolicy(String),
@@ -56,4 +115,103 @@ pub enum TokenizerError {
     /// File format or data format is not supported.
     #[error("Unsupported format: {0}")]
     UnsupportedFormat(String),
+
+    /// A file does not match its expected SHA-256 checksum.
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        /// The expected digest, as lowercase hex.
+        expected: String,
+        /// The digest of the file, as lowercase hex.
+        actual: String,
+    },
+
+    /// The operation was stopped by a cancellation token.
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    /// A chat completion request violates the conversation structure required
+    /// by its validation mode.
+    #[cfg(feature = "std")]
+    #[error("Invalid request: {0}")]
+    InvalidRequest(#[from] RequestViolation),
+
+    /// Loading a tokenizer file failed.
+    #[cfg(feature = "std")]
+    #[error("Failed to load tokenizer from {} ({stage} stage): {source}", path.display())]
+    Load {
+        /// Path of the file being loaded.
+        path: PathBuf,
+        /// Stage of loading that failed.
+        stage: LoadStage,
+        /// The underlying error.
+        source: Box<TokenizerError>,
+    },
+}
+
+impl TokenizerError {
+    /// Returns the underlying error, looking through any [`TokenizerError::Load`]
+    /// context.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::TokenizerError;
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// if let Err(error) = Tekkenizer::from_file("tekken.json") {
+    ///     if matches!(error.root_cause(), TokenizerError::Io(_)) {
+    ///         eprintln!("tokenizer file is missing: {error}");
+    ///     }
+    /// }
+    /// ```
+    #[must_use]
+    pub fn root_cause(&self) -> &Self {
+        match self {
+            #[cfg(feature = "std")]
+            Self::Load { source, .. } => source.root_cause(),
+            _ => self,
+        }
+    }
+
+    /// Wraps an error raised while loading the file at `path`.
+    #[cfg(feature = "std")]
+    pub(crate) fn at_load_stage(self, path: &Path, stage: LoadStage) -> Self {
+        Self::Load {
+            path: path.to_path_buf(),
+            stage,
+            source: Box::new(self),
+        }
+    }
+}
+
+/// Stage of loading a tokenizer file, reported by [`TokenizerError::Load`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadStage {
+    /// Reading and decompressing the file.
+    Read,
+    /// Parsing the JSON contents.
+    Parse,
+    /// Checking the vocabulary, special tokens and configuration.
+    Validate,
+    /// Compiling the pre-tokenization pattern and building the encoder.
+    Build,
+}
+
+impl LoadStage {
+    /// Returns the lowercase name of the stage.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "read",
+            Self::Parse => "parse",
+            Self::Validate => "validate",
+            Self::Build => "build",
+        }
+    }
+}
+
+impl fmt::Display for LoadStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
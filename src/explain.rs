@@ -0,0 +1,165 @@
+//! Step-by-step traces of how text is tokenized.
+//!
+//! [`Tekkenizer::explain`] answers questions like "why did this word become
+//! five tokens": it returns the pieces the pretokenization pattern splits the
+//! text into, and for each piece the BPE merges applied to its bytes, from
+//! single bytes up to the final tokens. The [`Display`](fmt::Display) output is
+//! meant for humans; the fields are meant for visualization tools.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tekken::tekkenizer::Tekkenizer;
+//!
+//! let tokenizer = Tekkenizer::from_file("tekken.json")?;
+//! let explanation = tokenizer.explain("Tokenization isn't magic")?;
+//! print!("{explanation}");
+//! assert_eq!(explanation.tokens(), tokenizer.encode("Tokenization isn't magic", false, false)?);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::diff::render_bytes;
+use crate::errors::Result;
+use crate::tekkenizer::Tekkenizer;
+
+/// How a text is split into pieces and each piece merged into tokens.
+///
+/// # Fields
+///
+/// * `text` - The explained text
+/// * `pieces` - The pretokenized pieces, in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub text: String,
+    pub pieces: Vec<PieceExplanation>,
+}
+
+/// How one pretokenized piece is merged into tokens.
+///
+/// Pieces that are vocabulary tokens themselves are looked up directly and
+/// have no merges.
+///
+/// # Fields
+///
+/// * `range` - Byte range of the piece in the text
+/// * `merges` - The merges applied to the piece's bytes, in order
+/// * `tokens` - The resulting token IDs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PieceExplanation {
+    pub range: Range<usize>,
+    pub merges: Vec<MergeStep>,
+    pub tokens: Vec<u32>,
+}
+
+/// A BPE merge joining two adjacent tokens into one.
+///
+/// # Fields
+///
+/// * `left` - Token ID of the left part
+/// * `right` - Token ID of the right part
+/// * `merged` - Token ID of the joined part; lower IDs are merged first
+/// * `range` - Byte range of the joined part in the text
+/// * `split` - Byte offset in the text where the right part starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeStep {
+    pub left: u32,
+    pub right: u32,
+    pub merged: u32,
+    pub range: Range<usize>,
+    pub split: usize,
+}
+
+impl Explanation {
+    /// Returns the tokens of all pieces, equal to encoding the text without
+    /// BOS/EOS.
+    #[must_use]
+    pub fn tokens(&self) -> Vec<u32> {
+        self.pieces
+            .iter()
+            .flat_map(|piece| piece.tokens.iter().copied())
+            .collect()
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = |range: &Range<usize>| render_bytes(&self.text.as_bytes()[range.clone()]);
+        for piece in &self.pieces {
+            writeln!(f, "{} -> {:?}", bytes(&piece.range), piece.tokens)?;
+            for merge in &piece.merges {
+                writeln!(
+                    f,
+                    "  {} + {} -> {} ({})",
+                    bytes(&(merge.range.start..merge.split)),
+                    bytes(&(merge.split..merge.range.end)),
+                    bytes(&merge.range),
+                    merge.merged
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Tekkenizer {
+    /// Traces how text is split into pieces and each piece merged into tokens.
+    ///
+    /// The trace always follows the built-in BPE engine, which custom backends
+    /// are expected to match.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to explain
+    ///
+    /// # Returns
+    ///
+    /// The pieces of the text with their merges and tokens.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if pretokenization fails.
+    pub fn explain(&self, text: &str) -> Result<Explanation> {
+        let offset = self.rank_offset();
+        // Every span produced by a merge, and every single byte, is a token
+        let token_id = |range: &Range<usize>| {
+            self.rank_of(&text.as_bytes()[range.clone()])
+                .map_or(0, |rank| (offset + rank).get())
+        };
+
+        let mut pieces = Vec::new();
+        for range in self.piece_ranges(text)? {
+            let piece = &text.as_bytes()[range.clone()];
+            let shift = |part: Range<usize>| range.start + part.start..range.start + part.end;
+            let merges = self
+                .trace_merges(piece)
+                .into_iter()
+                .map(|(left, right)| {
+                    let (left, right) = (shift(left), shift(right));
+                    let merged = left.start..right.end;
+                    MergeStep {
+                        left: token_id(&left),
+                        right: token_id(&right),
+                        merged: token_id(&merged),
+                        range: merged,
+                        split: right.start,
+                    }
+                })
+                .collect();
+            let mut tokens = Vec::new();
+            self.encode_piece(piece, &mut tokens);
+            pieces.push(PieceExplanation {
+                range,
+                merges,
+                tokens,
+            });
+        }
+
+        Ok(Explanation {
+            text: text.to_string(),
+            pieces,
+        })
+    }
+}
@@ -0,0 +1,568 @@
+//! C ABI for embedding Tekken in other languages.
+//!
+//! With the `ffi` feature, the `cdylib` exports the functions declared in
+//! `include/tekken.h`, which cbindgen generates from this module. The build
+//! writes the header into `OUT_DIR`; the committed copy is checked against it
+//! by the tests. The ABI is meant to be called from C, C++, Go (cgo), Java
+//! (JNA/Panama) and similar.
+//!
+//! # Conventions
+//!
+//! - Tokenizers and streaming decoders are opaque handles created by a
+//!   `*_new`/`*_from_*` function and released with the matching `*_free`.
+//! - Fallible functions return a [`TekkenErrorCode`]. When the `error` argument is
+//!   not null, it is filled with the code and a message that must be released
+//!   with [`tekken_error_free`].
+//! - Output buffers ([`TekkenEncoding`], strings) are owned by the caller and
+//!   released with [`tekken_encoding_free`] and [`tekken_string_free`].
+//! - Text is passed as UTF-8 pointer/length pairs and returned NUL-terminated.
+//! - Handles may be shared across threads for reading, except decoders, which
+//!   keep per-stream state.
+//!
+//! ```c
+//! TekkenError error = {0};
+//! TekkenTokenizer *tokenizer = tekken_tokenizer_from_file("tekken.json", &error);
+//! if (!tokenizer) {
+//!     fprintf(stderr, "%s\n", error.message);
+//!     tekken_error_free(&error);
+//!     return 1;
+//! }
+//!
+//! TekkenEncoding encoding = {0};
+//! const char *text = "Hello, world!";
+//! if (tekken_encode(tokenizer, (const uint8_t *)text, strlen(text), true, false,
+//!                   &encoding, &error) == TEKKEN_ERROR_CODE_OK) {
+//!     printf("%zu tokens\n", encoding.len);
+//!     tekken_encoding_free(&encoding);
+//! }
+//! tekken_tokenizer_free(tokenizer);
+//! ```
+
+use std::ffi::{CStr, CString, c_char};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+use crate::errors::TokenizerError;
+use crate::incremental::IncrementalDecoder;
+use crate::special_tokens::SpecialTokenPolicy;
+use crate::tekkenizer::Tekkenizer;
+
+/// Status of an FFI call.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TekkenErrorCode {
+    /// The call succeeded.
+    Ok = 0,
+    /// A required pointer argument was null.
+    NullPointer = 1,
+    /// Input text or a path was not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// Reading a file failed.
+    Io = 3,
+    /// The tokenizer file could not be parsed.
+    Parse = 4,
+    /// The tokenizer configuration is invalid.
+    InvalidConfig = 5,
+    /// A required token is missing from the vocabulary.
+    TokenNotFound = 6,
+    /// A token ID is outside the vocabulary.
+    TokenOutOfRange = 7,
+    /// The special token policy was violated.
+    SpecialTokenPolicy = 8,
+    /// Decoded text contains a NUL byte and cannot be returned as a C string.
+    InteriorNul = 9,
+    /// A Rust panic was caught at the ABI boundary.
+    Panic = 10,
+    /// Any other error.
+    Other = 11,
+    /// An argument was outside its allowed values, e.g. an unknown policy.
+    InvalidArgument = 12,
+}
+
+/// Error details filled in by fallible functions.
+///
+/// Zero-initialize before use. `message` is null on success and otherwise a
+/// NUL-terminated string that must be released with [`tekken_error_free`].
+/// The struct may be reused across calls: each call releases the message left
+/// by the previous one before storing its own.
+#[repr(C)]
+#[derive(Debug)]
+pub struct TekkenError {
+    /// The error code.
+    pub code: TekkenErrorCode,
+    /// Human-readable description, or null.
+    pub message: *mut c_char,
+}
+
+/// Token IDs produced by [`tekken_encode`].
+///
+/// Release with [`tekken_encoding_free`].
+#[repr(C)]
+#[derive(Debug)]
+pub struct TekkenEncoding {
+    /// Pointer to `len` token IDs, or null when empty.
+    pub ids: *mut u32,
+    /// Number of token IDs.
+    pub len: usize,
+}
+
+/// How special tokens are handled when decoding.
+///
+/// Functions take the policy as a `uint32_t` holding one of these values, so an
+/// unknown value is reported as [`TekkenErrorCode::InvalidArgument`] rather than
+/// being undefined behavior.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TekkenSpecialTokenPolicy {
+    /// Omit special tokens from the output.
+    Ignore = 0,
+    /// Keep special tokens as their string form.
+    Keep = 1,
+    /// Fail on special tokens.
+    Raise = 2,
+}
+
+impl TryFrom<u32> for TekkenSpecialTokenPolicy {
+    type Error = u32;
+
+    fn try_from(value: u32) -> std::result::Result<Self, u32> {
+        match value {
+            0 => Ok(Self::Ignore),
+            1 => Ok(Self::Keep),
+            2 => Ok(Self::Raise),
+            _ => Err(value),
+        }
+    }
+}
+
+impl From<TekkenSpecialTokenPolicy> for SpecialTokenPolicy {
+    fn from(policy: TekkenSpecialTokenPolicy) -> Self {
+        match policy {
+            TekkenSpecialTokenPolicy::Ignore => Self::Ignore,
+            TekkenSpecialTokenPolicy::Keep => Self::Keep,
+            TekkenSpecialTokenPolicy::Raise => Self::Raise,
+        }
+    }
+}
+
+/// An opaque tokenizer handle.
+pub struct TekkenTokenizer(Tekkenizer);
+
+/// An opaque streaming decoder handle.
+///
+/// The decoder borrows its tokenizer, which must outlive it.
+pub struct TekkenDecoder(IncrementalDecoder<'static>);
+
+/// An error raised at the ABI boundary, before or instead of a [`TokenizerError`].
+enum FfiError {
+    Code(TekkenErrorCode, String),
+    Tokenizer(TokenizerError),
+}
+
+impl From<TokenizerError> for FfiError {
+    fn from(error: TokenizerError) -> Self {
+        Self::Tokenizer(error)
+    }
+}
+
+impl FfiError {
+    fn null(name: &str) -> Self {
+        Self::Code(
+            TekkenErrorCode::NullPointer,
+            format!("`{name}` must not be null"),
+        )
+    }
+
+    /// Converts a policy passed over the ABI, rejecting unknown values.
+    fn policy(value: u32) -> FfiResult<SpecialTokenPolicy> {
+        TekkenSpecialTokenPolicy::try_from(value)
+            .map(SpecialTokenPolicy::from)
+            .map_err(|value| {
+                Self::Code(
+                    TekkenErrorCode::InvalidArgument,
+                    format!("Unknown special token policy: {value}"),
+                )
+            })
+    }
+
+    fn into_parts(self) -> (TekkenErrorCode, String) {
+        match self {
+            Self::Code(code, message) => (code, message),
+            Self::Tokenizer(error) => {
+                let code = match error.root_cause() {
+                    TokenizerError::Io(_) => TekkenErrorCode::Io,
+                    TokenizerError::Json(_) | TokenizerError::Base64(_) => TekkenErrorCode::Parse,
+                    #[cfg(feature = "simd-json")]
+                    TokenizerError::SimdJson(_) => TekkenErrorCode::Parse,
+                    TokenizerError::InvalidConfig(_)
+                    | TokenizerError::InvalidRequest(_)
+                    | TokenizerError::UnsupportedFormat(_) => TekkenErrorCode::InvalidConfig,
+                    TokenizerError::TokenNotFound(_) => TekkenErrorCode::TokenNotFound,
+                    TokenizerError::TokenOutOfRange { .. } => TekkenErrorCode::TokenOutOfRange,
+                    TokenizerError::SpecialTokenPolicy(_) => TekkenErrorCode::SpecialTokenPolicy,
+                    _ => TekkenErrorCode::Other,
+                };
+                (code, error.to_string())
+            }
+        }
+    }
+}
+
+type FfiResult<T> = std::result::Result<T, FfiError>;
+
+/// Runs `f`, catching panics and reporting failures through `error`.
+fn guard<T>(
+    error: *mut TekkenError,
+    f: impl FnOnce() -> FfiResult<T>,
+) -> std::result::Result<T, TekkenErrorCode> {
+    let result = catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        Err(FfiError::Code(
+            TekkenErrorCode::Panic,
+            "panic in tekken".to_string(),
+        ))
+    });
+    match result {
+        Ok(value) => {
+            set_error(error, TekkenErrorCode::Ok, None);
+            Ok(value)
+        }
+        Err(e) => {
+            let (code, message) = e.into_parts();
+            set_error(error, code, Some(message));
+            Err(code)
+        }
+    }
+}
+
+/// Collapses a guarded call into the status code returned over the ABI.
+fn status(result: std::result::Result<(), TekkenErrorCode>) -> TekkenErrorCode {
+    result.err().unwrap_or(TekkenErrorCode::Ok)
+}
+
+fn set_error(error: *mut TekkenError, code: TekkenErrorCode, message: Option<String>) {
+    // SAFETY: callers pass either null or a valid, writable `TekkenError`.
+    let Some(error) = (unsafe { error.as_mut() }) else {
+        return;
+    };
+    // SAFETY: the struct is zero-initialized or holds a message set here earlier.
+    unsafe { tekken_string_free(error.message) };
+    error.code = code;
+    error.message = message.map_or(ptr::null_mut(), |message| {
+        let message = message.replace('\0', " ");
+        CString::new(message).map_or(ptr::null_mut(), CString::into_raw)
+    });
+}
+
+/// Borrows a UTF-8 string from a pointer/length pair.
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` readable bytes.
+unsafe fn str_arg<'a>(data: *const u8, len: usize, name: &str) -> FfiResult<&'a str> {
+    if len == 0 {
+        return Ok("");
+    }
+    if data.is_null() {
+        return Err(FfiError::null(name));
+    }
+    // SAFETY: guaranteed by the caller.
+    let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+    std::str::from_utf8(bytes)
+        .map_err(|e| FfiError::Code(TekkenErrorCode::InvalidUtf8, format!("`{name}`: {e}")))
+}
+
+fn c_string(text: String) -> FfiResult<*mut c_char> {
+    CString::new(text).map(CString::into_raw).map_err(|e| {
+        FfiError::Code(
+            TekkenErrorCode::InteriorNul,
+            format!("Decoded text contains a NUL byte at {}", e.nul_position()),
+        )
+    })
+}
+
+/// Loads a tokenizer from a `tekken.json` file.
+///
+/// Returns null on failure.
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string; `error` must be null or valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_tokenizer_from_file(
+    path: *const c_char,
+    error: *mut TekkenError,
+) -> *mut TekkenTokenizer {
+    guard(error, || {
+        if path.is_null() {
+            return Err(FfiError::null("path"));
+        }
+        // SAFETY: guaranteed by the caller.
+        let path = unsafe { CStr::from_ptr(path) }
+            .to_str()
+            .map_err(|e| FfiError::Code(TekkenErrorCode::InvalidUtf8, format!("`path`: {e}")))?;
+        let tokenizer = Tekkenizer::from_file(path)?;
+        Ok(Box::into_raw(Box::new(TekkenTokenizer(tokenizer))))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Loads a tokenizer from the bytes of a tokenizer file.
+///
+/// Returns null on failure.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes; `error` must be null or valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_tokenizer_from_bytes(
+    data: *const u8,
+    len: usize,
+    error: *mut TekkenError,
+) -> *mut TekkenTokenizer {
+    guard(error, || {
+        if data.is_null() {
+            return Err(FfiError::null("data"));
+        }
+        // SAFETY: guaranteed by the caller.
+        let bytes = unsafe { std::slice::from_raw_parts(data, len) };
+        let tokenizer = Tekkenizer::from_bytes(bytes)?;
+        Ok(Box::into_raw(Box::new(TekkenTokenizer(tokenizer))))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Releases a tokenizer. Null is ignored.
+///
+/// # Safety
+///
+/// `tokenizer` must come from `tekken_tokenizer_from_*`, must not be used
+/// afterwards, and must not be borrowed by a live decoder.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_tokenizer_free(tokenizer: *mut TekkenTokenizer) {
+    if !tokenizer.is_null() {
+        // SAFETY: guaranteed by the caller.
+        drop(unsafe { Box::from_raw(tokenizer) });
+    }
+}
+
+/// Returns the vocabulary size including special tokens, or 0 for null.
+///
+/// # Safety
+///
+/// `tokenizer` must be null or a live tokenizer handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_tokenizer_vocab_size(tokenizer: *const TekkenTokenizer) -> usize {
+    // SAFETY: guaranteed by the caller.
+    unsafe { tokenizer.as_ref() }.map_or(0, |tokenizer| tokenizer.0.vocab_size())
+}
+
+/// Encodes UTF-8 text into token IDs.
+///
+/// On success `out` holds the IDs and must be released with [`tekken_encoding_free`].
+///
+/// # Safety
+///
+/// `tokenizer` must be a live handle, `text` must point to `text_len` readable
+/// bytes, `out` must be writable and `error` must be null or valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_encode(
+    tokenizer: *const TekkenTokenizer,
+    text: *const u8,
+    text_len: usize,
+    add_beginning_of_sequence: bool,
+    add_end_of_sequence: bool,
+    out: *mut TekkenEncoding,
+    error: *mut TekkenError,
+) -> TekkenErrorCode {
+    let result = guard(error, || {
+        // SAFETY: guaranteed by the caller.
+        let tokenizer = unsafe { tokenizer.as_ref() }.ok_or_else(|| FfiError::null("tokenizer"))?;
+        // SAFETY: guaranteed by the caller.
+        let out = unsafe { out.as_mut() }.ok_or_else(|| FfiError::null("out"))?;
+        // SAFETY: guaranteed by the caller.
+        let text = unsafe { str_arg(text, text_len, "text") }?;
+
+        let ids = tokenizer
+            .0
+            .encode(text, add_beginning_of_sequence, add_end_of_sequence)?
+            .into_boxed_slice();
+        out.len = ids.len();
+        out.ids = if ids.is_empty() {
+            ptr::null_mut()
+        } else {
+            Box::into_raw(ids).cast::<u32>()
+        };
+        Ok(())
+    });
+    status(result)
+}
+
+/// Releases the IDs of an encoding and resets it. Null is ignored.
+///
+/// # Safety
+///
+/// `encoding` must be null or filled by [`tekken_encode`] and not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_encoding_free(encoding: *mut TekkenEncoding) {
+    // SAFETY: guaranteed by the caller.
+    let Some(encoding) = (unsafe { encoding.as_mut() }) else {
+        return;
+    };
+    if !encoding.ids.is_null() {
+        let slice = ptr::slice_from_raw_parts_mut(encoding.ids, encoding.len);
+        // SAFETY: `ids` was produced from a boxed slice of exactly `len` IDs.
+        drop(unsafe { Box::from_raw(slice) });
+    }
+    encoding.ids = ptr::null_mut();
+    encoding.len = 0;
+}
+
+/// Decodes token IDs into a NUL-terminated UTF-8 string.
+///
+/// `policy` is a [`TekkenSpecialTokenPolicy`] value. On success `*out_text`
+/// must be released with [`tekken_string_free`].
+///
+/// # Safety
+///
+/// `tokenizer` must be a live handle, `ids` must point to `len` IDs, `out_text`
+/// must be writable and `error` must be null or valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_decode(
+    tokenizer: *const TekkenTokenizer,
+    ids: *const u32,
+    len: usize,
+    policy: u32,
+    out_text: *mut *mut c_char,
+    error: *mut TekkenError,
+) -> TekkenErrorCode {
+    let result = guard(error, || {
+        // SAFETY: guaranteed by the caller.
+        let tokenizer = unsafe { tokenizer.as_ref() }.ok_or_else(|| FfiError::null("tokenizer"))?;
+        // SAFETY: guaranteed by the caller.
+        let out_text = unsafe { out_text.as_mut() }.ok_or_else(|| FfiError::null("out_text"))?;
+        let ids = if len == 0 {
+            &[][..]
+        } else if ids.is_null() {
+            return Err(FfiError::null("ids"));
+        } else {
+            // SAFETY: guaranteed by the caller.
+            unsafe { std::slice::from_raw_parts(ids, len) }
+        };
+
+        *out_text = c_string(tokenizer.0.decode(ids, FfiError::policy(policy)?)?)?;
+        Ok(())
+    });
+    status(result)
+}
+
+/// Releases a string returned by this library. Null is ignored.
+///
+/// # Safety
+///
+/// `text` must be null or a string returned by this library and not yet released.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        // SAFETY: guaranteed by the caller.
+        drop(unsafe { CString::from_raw(text) });
+    }
+}
+
+/// Releases the message of an error and resets its code. Null is ignored.
+///
+/// # Safety
+///
+/// `error` must be null or valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_error_free(error: *mut TekkenError) {
+    // SAFETY: guaranteed by the caller.
+    let Some(error) = (unsafe { error.as_mut() }) else {
+        return;
+    };
+    // SAFETY: non-null messages are always allocated by `set_error`.
+    unsafe { tekken_string_free(error.message) };
+    error.message = ptr::null_mut();
+    error.code = TekkenErrorCode::Ok;
+}
+
+/// Creates a streaming decoder for tokens generated one at a time.
+///
+/// `policy` is a [`TekkenSpecialTokenPolicy`] value. Returns null if
+/// `tokenizer` is null or `policy` is unknown.
+///
+/// # Safety
+///
+/// `tokenizer` must be null or a live handle that outlives the decoder.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_decoder_new(
+    tokenizer: *const TekkenTokenizer,
+    policy: u32,
+) -> *mut TekkenDecoder {
+    // SAFETY: guaranteed by the caller, including that the borrow outlives the decoder.
+    let Some(tokenizer) = (unsafe { tokenizer.as_ref() }) else {
+        return ptr::null_mut();
+    };
+    let Ok(policy) = FfiError::policy(policy) else {
+        return ptr::null_mut();
+    };
+    let decoder = IncrementalDecoder::new(&tokenizer.0, policy);
+    Box::into_raw(Box::new(TekkenDecoder(decoder)))
+}
+
+/// Feeds the next token to a decoder.
+///
+/// On success `*out_text` holds the text completed by this token, possibly the
+/// empty string, and must be released with [`tekken_string_free`].
+///
+/// # Safety
+///
+/// `decoder` must be a live handle not used concurrently, `out_text` must be
+/// writable and `error` must be null or valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_decoder_push(
+    decoder: *mut TekkenDecoder,
+    token_id: u32,
+    out_text: *mut *mut c_char,
+    error: *mut TekkenError,
+) -> TekkenErrorCode {
+    let result = guard(error, || {
+        // SAFETY: guaranteed by the caller.
+        let decoder = unsafe { decoder.as_mut() }.ok_or_else(|| FfiError::null("decoder"))?;
+        // SAFETY: guaranteed by the caller.
+        let out_text = unsafe { out_text.as_mut() }.ok_or_else(|| FfiError::null("out_text"))?;
+        *out_text = c_string(decoder.0.push(token_id)?)?;
+        Ok(())
+    });
+    status(result)
+}
+
+/// Checks that the stream did not end inside a character and resets the decoder.
+///
+/// # Safety
+///
+/// `decoder` must be a live handle and `error` must be null or valid.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_decoder_finish(
+    decoder: *mut TekkenDecoder,
+    error: *mut TekkenError,
+) -> TekkenErrorCode {
+    let result = guard(error, || {
+        // SAFETY: guaranteed by the caller.
+        let decoder = unsafe { decoder.as_mut() }.ok_or_else(|| FfiError::null("decoder"))?;
+        decoder.0.finish().map_err(FfiError::from)
+    });
+    status(result)
+}
+
+/// Releases a decoder. Null is ignored.
+///
+/// # Safety
+///
+/// `decoder` must be null or come from [`tekken_decoder_new`] and not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tekken_decoder_free(decoder: *mut TekkenDecoder) {
+    if !decoder.is_null() {
+        // SAFETY: guaranteed by the caller.
+        drop(unsafe { Box::from_raw(decoder) });
+    }
+}
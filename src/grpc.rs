@@ -0,0 +1,278 @@
+//! gRPC tokenization service built on `tonic`.
+//!
+//! [`TokenizerService`] implements the `tekken.v1.Tokenizer` service defined in
+//! `proto/tekken.proto`, with unary RPCs for text and audio encoding and decoding,
+//! and a bidirectional `DecodeStream` RPC that turns generated tokens into
+//! UTF-8-safe text chunks as they arrive. Clients in other languages can be
+//! generated from the proto file.
+//!
+//! The service only needs `tonic`'s server support; pick a transport (e.g.
+//! `tonic::transport::Server`) in the binary that hosts it.
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! use std::sync::Arc;
+//! use tekken::grpc::TokenizerService;
+//! use tekken::tekkenizer::Tekkenizer;
+//!
+//! let tokenizer = Arc::new(Tekkenizer::from_file("tekken.json")?);
+//! tonic::transport::Server::builder()
+//!     .add_service(TokenizerService::new(tokenizer).into_server())
+//!     .serve("0.0.0.0:50051".parse()?)
+//!     .await?;
+//! ```
+
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tonic::{Request, Response, Status, Streaming};
+
+#[cfg(feature = "audio")]
+use crate::audio::Audio;
+use crate::errors::TokenizerError;
+use crate::incremental::DecodeState;
+use crate::special_tokens::SpecialTokenPolicy;
+use crate::tekkenizer::Tekkenizer;
+
+/// Messages and generated service code for `tekken.v1`.
+///
+/// The messages mirror `proto/tekken.proto` field for field.
+#[allow(missing_docs, clippy::all, clippy::pedantic)]
+pub mod proto {
+    /// How special tokens are handled when decoding.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum SpecialTokenPolicy {
+        Ignore = 0,
+        Keep = 1,
+        Raise = 2,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct EncodeRequest {
+        #[prost(string, tag = "1")]
+        pub text: String,
+        #[prost(bool, tag = "2")]
+        pub add_bos: bool,
+        #[prost(bool, tag = "3")]
+        pub add_eos: bool,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct EncodeResponse {
+        #[prost(uint32, repeated, tag = "1")]
+        pub ids: Vec<u32>,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct DecodeRequest {
+        #[prost(uint32, repeated, tag = "1")]
+        pub ids: Vec<u32>,
+        #[prost(enumeration = "SpecialTokenPolicy", tag = "2")]
+        pub special_token_policy: i32,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct DecodeResponse {
+        #[prost(string, tag = "1")]
+        pub text: String,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct EncodeAudioRequest {
+        #[prost(bytes = "vec", tag = "1")]
+        pub audio: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct DecodeStreamRequest {
+        #[prost(uint32, repeated, tag = "1")]
+        pub ids: Vec<u32>,
+        #[prost(enumeration = "SpecialTokenPolicy", tag = "2")]
+        pub special_token_policy: i32,
+    }
+
+    include!(concat!(env!("OUT_DIR"), "/tekken.v1.Tokenizer.rs"));
+}
+
+pub use proto::tokenizer_client::TokenizerClient;
+pub use proto::tokenizer_server::{Tokenizer, TokenizerServer};
+
+/// Serves a [`Tekkenizer`] over gRPC.
+#[derive(Clone)]
+pub struct TokenizerService {
+    tokenizer: Arc<Tekkenizer>,
+}
+
+impl TokenizerService {
+    /// Creates a service backed by a shared tokenizer.
+    #[must_use]
+    pub fn new(tokenizer: Arc<Tekkenizer>) -> Self {
+        Self { tokenizer }
+    }
+
+    /// Wraps the service for registration with a tonic server.
+    #[must_use]
+    pub fn into_server(self) -> TokenizerServer<Self> {
+        TokenizerServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl Tokenizer for TokenizerService {
+    async fn encode(
+        &self,
+        request: Request<proto::EncodeRequest>,
+    ) -> Result<Response<proto::EncodeResponse>, Status> {
+        let request = request.into_inner();
+        let ids = self
+            .tokenizer
+            .encode(&request.text, request.add_bos, request.add_eos)
+            .map_err(|e| to_status(&e))?;
+        Ok(Response::new(proto::EncodeResponse { ids }))
+    }
+
+    async fn decode(
+        &self,
+        request: Request<proto::DecodeRequest>,
+    ) -> Result<Response<proto::DecodeResponse>, Status> {
+        let request = request.into_inner();
+        let policy = special_token_policy(request.special_token_policy)?;
+        let text = self
+            .tokenizer
+            .decode(&request.ids, policy)
+            .map_err(|e| to_status(&e))?;
+        Ok(Response::new(proto::DecodeResponse { text }))
+    }
+
+    async fn encode_audio(
+        &self,
+        request: Request<proto::EncodeAudioRequest>,
+    ) -> Result<Response<proto::EncodeResponse>, Status> {
+        #[cfg(feature = "audio")]
+        {
+            let audio =
+                Audio::from_bytes(&request.into_inner().audio).map_err(|e| to_status(&e))?;
+            let encoding = self
+                .tokenizer
+                .encode_audio(audio)
+                .map_err(|e| to_status(&e))?;
+            Ok(Response::new(proto::EncodeResponse {
+                ids: encoding.tokens,
+            }))
+        }
+        #[cfg(not(feature = "audio"))]
+        {
+            let _ = request;
+            Err(Status::unimplemented(
+                "Audio encoding requires the `audio` feature",
+            ))
+        }
+    }
+
+    type DecodeStreamStream = DecodeChunks;
+
+    async fn decode_stream(
+        &self,
+        request: Request<Streaming<proto::DecodeStreamRequest>>,
+    ) -> Result<Response<Self::DecodeStreamStream>, Status> {
+        Ok(Response::new(DecodeChunks {
+            tokenizer: Arc::clone(&self.tokenizer),
+            inbound: request.into_inner(),
+            state: None,
+            done: false,
+        }))
+    }
+}
+
+/// Response stream of the `DecodeStream` RPC.
+///
+/// Yields a non-empty text chunk for every request message that completes at
+/// least one character, and ends after the first error.
+pub struct DecodeChunks {
+    tokenizer: Arc<Tekkenizer>,
+    inbound: Streaming<proto::DecodeStreamRequest>,
+    state: Option<DecodeState>,
+    done: bool,
+}
+
+impl DecodeChunks {
+    fn decode(&mut self, request: &proto::DecodeStreamRequest) -> Result<String, Status> {
+        let state = match &mut self.state {
+            Some(state) => state,
+            None => self.state.insert(DecodeState::new(special_token_policy(
+                request.special_token_policy,
+            )?)),
+        };
+        let mut text = String::new();
+        for &id in &request.ids {
+            text.push_str(&state.push(&self.tokenizer, id).map_err(|e| to_status(&e))?);
+        }
+        Ok(text)
+    }
+}
+
+impl Stream for DecodeChunks {
+    type Item = Result<proto::DecodeResponse, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while !this.done {
+            let Poll::Ready(next) = Pin::new(&mut this.inbound).poll_next(cx) else {
+                return Poll::Pending;
+            };
+            let result = match next {
+                Some(Ok(request)) => this.decode(&request),
+                Some(Err(status)) => Err(status),
+                None => {
+                    this.done = true;
+                    match this.state.as_mut().map(DecodeState::finish) {
+                        Some(Err(e)) => Err(to_status(&e)),
+                        _ => return Poll::Ready(None),
+                    }
+                }
+            };
+            match result {
+                Ok(text) if text.is_empty() => {}
+                Ok(text) => return Poll::Ready(Some(Ok(proto::DecodeResponse { text }))),
+                Err(status) => {
+                    this.done = true;
+                    return Poll::Ready(Some(Err(status)));
+                }
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+fn special_token_policy(value: i32) -> Result<SpecialTokenPolicy, Status> {
+    match proto::SpecialTokenPolicy::try_from(value) {
+        Ok(proto::SpecialTokenPolicy::Ignore) => Ok(SpecialTokenPolicy::Ignore),
+        Ok(proto::SpecialTokenPolicy::Keep) => Ok(SpecialTokenPolicy::Keep),
+        Ok(proto::SpecialTokenPolicy::Raise) => Ok(SpecialTokenPolicy::Raise),
+        Err(_) => Err(Status::invalid_argument(format!(
+            "Unknown special token policy: {value}"
+        ))),
+    }
+}
+
+/// Maps tokenizer errors to gRPC status codes.
+fn to_status(error: &TokenizerError) -> Status {
+    match error.root_cause() {
+        TokenizerError::TokenOutOfRange { .. }
+        | TokenizerError::SpecialTokenPolicy(_)
+        | TokenizerError::Audio(_)
+        | TokenizerError::Image(_)
+        | TokenizerError::InvalidRequest(_)
+        | TokenizerError::MalformedToolCall(_)
+        | TokenizerError::UnsupportedFormat(_) => Status::invalid_argument(error.to_string()),
+        TokenizerError::TokenNotFound(_) | TokenizerError::InvalidConfig(_) => {
+            Status::failed_precondition(error.to_string())
+        }
+        TokenizerError::Cancelled => Status::cancelled(error.to_string()),
+        _ => Status::internal(error.to_string()),
+    }
+}
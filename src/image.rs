@@ -0,0 +1,435 @@
+//! Image loading, preprocessing and encoding.
+//!
+//! [`ImageEncoder`] is always available so tokenizer files with an image section
+//! load in every build. Decoding images and computing pixel values require the
+//! `image` feature.
+//!
+//! [`Image`] decodes PNG and JPEG data and [`Image::preprocess`] turns it into the
+//! pixel inputs of a Pixtral-style vision encoder, following the preprocessing of
+//! `mistral-common`:
+//!
+//! 1. Images with transparency are composited onto a white background.
+//! 2. Images larger than [`ImageConfig::max_image_size`] are scaled down,
+//!    keeping their aspect ratio.
+//! 3. Each side is rounded up to whole tokens of
+//!    [`ImageConfig::pixels_per_token`] pixels and the image is resized to that
+//!    size, with bicubic interpolation by default.
+//! 4. Pixel values are multiplied by [`ImageConfig::rescale_factor`] and
+//!    normalized per channel with [`ImageConfig::image_mean`] and
+//!    [`ImageConfig::image_std`], by default [`DATASET_MEAN`] and
+//!    [`DATASET_STD`] over `[0, 1]` values.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "image")]
+//! use tekken::image::Image;
+//! # #[cfg(feature = "image")]
+//! use tekken::tekkenizer::Tekkenizer;
+//!
+//! # #[cfg(feature = "image")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let tokenizer = Tekkenizer::from_file("tekken.json")?;
+//! let encoding = tokenizer.encode_image(&Image::from_file("photo.jpg")?)?;
+//! println!(
+//!     "{}x{} tokens, pixel values {:?}",
+//!     encoding.image.columns,
+//!     encoding.image.rows,
+//!     encoding.image.pixel_values.shape()
+//! );
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "image"))]
+//! # fn main() {}
+//! ```
+
+#[cfg(feature = "image")]
+use std::path::Path;
+
+#[cfg(feature = "image")]
+use ::image::imageops::{self, FilterType};
+#[cfg(feature = "image")]
+use ::image::{DynamicImage, ImageError, RgbImage};
+#[cfg(feature = "image")]
+use base64::Engine;
+#[cfg(feature = "image")]
+use ndarray::Array3;
+
+use crate::config::ImageConfig;
+#[cfg(feature = "image")]
+use crate::config::ImageResample;
+#[cfg(feature = "image")]
+use crate::errors::{Result, TokenizerError};
+
+/// Per-channel (RGB) mean of the vision encoder's training data.
+pub const DATASET_MEAN: [f64; 3] = [0.481_454_66, 0.457_827_5, 0.408_210_73];
+
+/// Per-channel (RGB) standard deviation of the vision encoder's training data.
+pub const DATASET_STD: [f64; 3] = [0.268_629_54, 0.261_302_58, 0.275_777_11];
+
+/// A decoded RGB image.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    rgb: RgbImage,
+}
+
+/// Pixel inputs for the vision encoder together with their token grid.
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreprocessedImage {
+    /// Normalized pixel values, shaped `(3, height, width)`.
+    pub pixel_values: Array3<f32>,
+    /// Number of token columns the image spans.
+    pub columns: usize,
+    /// Number of token rows the image spans.
+    pub rows: usize,
+}
+
+#[cfg(feature = "image")]
+impl Image {
+    /// Loads an image from a PNG or JPEG file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the image file
+    ///
+    /// # Returns
+    ///
+    /// The decoded image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, its format is not supported,
+    /// or it cannot be decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::image::Image;
+    ///
+    /// let image = Image::from_file("photo.png")?;
+    /// println!("{}x{} pixels", image.width(), image.height());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Loads an image from the bytes of a PNG or JPEG file.
+    ///
+    /// The format is detected from the data itself.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - Encoded image data
+    ///
+    /// # Returns
+    ///
+    /// The decoded image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the format is not supported or the data cannot be
+    /// decoded.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let image = ::image::load_from_memory(bytes).map_err(|error| match error {
+            ImageError::Unsupported(error) => {
+                TokenizerError::UnsupportedFormat(format!("{error}; expected a PNG or JPEG image"))
+            }
+            error => TokenizerError::Image(error.to_string()),
+        })?;
+        Ok(Self::from(image))
+    }
+
+    /// Loads an image from a base64-encoded string.
+    ///
+    /// Data URLs such as `data:image/png;base64,...` are accepted as well, see
+    /// [`Image::from_data_url`].
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Base64-encoded image data, or a data URL
+    ///
+    /// # Returns
+    ///
+    /// The decoded image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if base64 decoding or image decoding fails.
+    pub fn from_base64(data: &str) -> Result<Self> {
+        if data.starts_with("data:") {
+            return Self::from_data_url(data);
+        }
+        let image_bytes = base64::engine::general_purpose::STANDARD.decode(data.trim())?;
+        Self::from_bytes(&image_bytes)
+    }
+
+    /// Loads an image from a base64 data URL, as sent by multimodal chat APIs.
+    ///
+    /// PNG (`image/png`) and JPEG (`image/jpeg`, `image/jpg`) are supported;
+    /// MIME parameters are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - A data URL of the form `data:<mime>[;<params>];base64,<data>`
+    ///
+    /// # Returns
+    ///
+    /// The decoded image.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TokenizerError::UnsupportedFormat` for other MIME types, and an
+    /// error if the URL is malformed, not base64-encoded, or the image cannot be
+    /// decoded.
+    pub fn from_data_url(url: &str) -> Result<Self> {
+        let (header, data) = url
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split_once(','))
+            .ok_or_else(|| {
+                TokenizerError::Image(
+                    "Malformed data URL: expected `data:<mime>;base64,<data>`".to_string(),
+                )
+            })?;
+
+        let mut parts = header.split(';').map(str::trim);
+        let mime = parts.next().unwrap_or_default().to_ascii_lowercase();
+        if !parts.any(|part| part.eq_ignore_ascii_case("base64")) {
+            return Err(TokenizerError::Image(
+                "Data URL is not base64-encoded".to_string(),
+            ));
+        }
+
+        match mime.as_str() {
+            "image/png" | "image/jpeg" | "image/jpg" => {
+                let image_bytes = base64::engine::general_purpose::STANDARD.decode(data.trim())?;
+                Self::from_bytes(&image_bytes)
+            }
+            _ => Err(TokenizerError::UnsupportedFormat(format!(
+                "Image MIME type {mime:?} is not supported; expected image/png or image/jpeg"
+            ))),
+        }
+    }
+
+    /// Returns the width in pixels.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.rgb.width()
+    }
+
+    /// Returns the height in pixels.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.rgb.height()
+    }
+
+    /// Returns the RGB pixels.
+    #[must_use]
+    pub fn as_rgb(&self) -> &RgbImage {
+        &self.rgb
+    }
+
+    /// Resizes and normalizes the image for the vision encoder.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Image configuration of the tokenizer
+    ///
+    /// # Returns
+    ///
+    /// The pixel values and token grid.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration is invalid or the image is empty.
+    pub fn preprocess(&self, config: &ImageConfig) -> Result<PreprocessedImage> {
+        config.validate()?;
+        if self.rgb.width() == 0 || self.rgb.height() == 0 {
+            return Err(TokenizerError::Image("Image has no pixels".to_string()));
+        }
+
+        let (columns, rows) =
+            config.token_grid(self.rgb.width() as usize, self.rgb.height() as usize);
+        let pixels_per_token = config.pixels_per_token();
+        let dimension = |tokens: usize| {
+            u32::try_from(tokens * pixels_per_token).map_err(|_| {
+                TokenizerError::Image(format!(
+                    "{tokens} tokens of {pixels_per_token} pixels exceed the supported image size"
+                ))
+            })
+        };
+        let (width, height) = (dimension(columns)?, dimension(rows)?);
+
+        let resized;
+        let rgb = if (width, height) == self.rgb.dimensions() {
+            &self.rgb
+        } else {
+            let filter = match config.resample {
+                ImageResample::Nearest => FilterType::Nearest,
+                ImageResample::Bilinear => FilterType::Triangle,
+                ImageResample::Bicubic => FilterType::CatmullRom,
+                ImageResample::Lanczos => FilterType::Lanczos3,
+            };
+            resized = imageops::resize(&self.rgb, width, height, filter);
+            &resized
+        };
+
+        let pixel_values =
+            Array3::from_shape_fn((3, height as usize, width as usize), |(channel, y, x)| {
+                #[allow(clippy::cast_possible_truncation)]
+                let pixel = rgb.get_pixel(x as u32, y as u32)[channel];
+                let value = f64::from(pixel) * config.rescale_factor;
+                #[allow(clippy::cast_possible_truncation)]
+                let normalized =
+                    ((value - config.image_mean[channel]) / config.image_std[channel]) as f32;
+                normalized
+            });
+
+        Ok(PreprocessedImage {
+            pixel_values,
+            columns,
+            rows,
+        })
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<DynamicImage> for Image {
+    /// Converts to RGB, compositing transparent images onto white.
+    fn from(image: DynamicImage) -> Self {
+        let rgb = match image {
+            DynamicImage::ImageRgb8(rgb) => rgb,
+            image => {
+                let rgba = image.to_rgba8();
+                RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+                    let [red, green, blue, alpha] = rgba.get_pixel(x, y).0;
+                    let blend = |channel: u8| {
+                        let alpha = u32::from(alpha);
+                        let value = u32::from(channel) * alpha + 255 * (255 - alpha);
+                        #[allow(clippy::cast_possible_truncation)]
+                        let blended = ((value + 127) / 255) as u8;
+                        blended
+                    };
+                    ::image::Rgb([blend(red), blend(green), blend(blue)])
+                })
+            }
+        };
+        Self { rgb }
+    }
+}
+
+#[cfg(feature = "image")]
+impl From<RgbImage> for Image {
+    fn from(rgb: RgbImage) -> Self {
+        Self { rgb }
+    }
+}
+
+/// Tokens and pixel values of an encoded image.
+///
+/// # Fields
+///
+/// * `tokens` - One `[IMG]` token per patch, each row followed by `[IMG_BREAK]`;
+///   the last row ends with `[IMG_END]` instead
+/// * `image` - Normalized pixel values and token grid
+#[cfg(feature = "image")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageEncoding {
+    pub tokens: Vec<u32>,
+    pub image: PreprocessedImage,
+}
+
+/// Encoder for converting images into token sequences.
+///
+/// # Fields
+///
+/// * `config` - Image processing configuration
+/// * `image_token_id` - Token ID (u32) for image patches
+/// * `image_break_token_id` - Token ID (u32) ending each row of patches
+/// * `image_end_token_id` - Token ID (u32) ending the image
+#[derive(Debug, Clone)]
+pub struct ImageEncoder {
+    pub config: ImageConfig,
+    pub image_token_id: u32,
+    pub image_break_token_id: u32,
+    pub image_end_token_id: u32,
+}
+
+impl ImageEncoder {
+    /// Creates a new `ImageEncoder`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Image processing configuration
+    /// * `image_token_id` - Token ID (u32) representing an image patch
+    /// * `image_break_token_id` - Token ID (u32) ending each row of patches
+    /// * `image_end_token_id` - Token ID (u32) ending the image
+    ///
+    /// # Returns
+    ///
+    /// A new `ImageEncoder` instance.
+    #[must_use]
+    pub fn new(
+        config: ImageConfig,
+        image_token_id: u32,
+        image_break_token_id: u32,
+        image_end_token_id: u32,
+    ) -> Self {
+        Self {
+            config,
+            image_token_id,
+            image_break_token_id,
+            image_end_token_id,
+        }
+    }
+
+    /// Lays out the tokens of an image spanning a grid of patches.
+    ///
+    /// Each row holds `columns` `[IMG]` tokens followed by `[IMG_BREAK]`, and the
+    /// final `[IMG_BREAK]` is replaced by `[IMG_END]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - Number of token columns
+    /// * `rows` - Number of token rows
+    ///
+    /// # Returns
+    ///
+    /// The token sequence, empty for an empty grid.
+    #[must_use]
+    pub fn grid_tokens(&self, columns: usize, rows: usize) -> Vec<u32> {
+        let mut tokens = Vec::with_capacity((columns + 1) * rows);
+        for _ in 0..rows {
+            tokens.extend(std::iter::repeat_n(self.image_token_id, columns));
+            tokens.push(self.image_break_token_id);
+        }
+        if let Some(last) = tokens.last_mut() {
+            *last = self.image_end_token_id;
+        }
+        tokens
+    }
+
+    /// Encodes an image into a token sequence and pixel values.
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image to encode
+    ///
+    /// # Returns
+    ///
+    /// An `ImageEncoding` with the tokens and the preprocessed image.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if preprocessing fails; see [`Image::preprocess`].
+    #[cfg(feature = "image")]
+    pub fn encode(&self, image: &Image) -> Result<ImageEncoding> {
+        let image = image.preprocess(&self.config)?;
+        Ok(ImageEncoding {
+            tokens: self.grid_tokens(image.columns, image.rows),
+            image,
+        })
+    }
+}
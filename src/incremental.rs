@@ -0,0 +1,385 @@
+//! Incremental decoding of generated token streams, and incremental encoding
+//! of growing text.
+//!
+//! Models emit one token at a time, and a token may end in the middle of a
+//! multi-byte character. [`IncrementalDecoder`] accepts tokens as they arrive and
+//! returns only complete UTF-8 text, holding back partial characters until the
+//! following tokens finish them.
+//!
+//! With the `futures` feature, `DecodeStream` applies the same logic to an
+//! async `Stream` of token IDs, so servers can forward model output to SSE or
+//! WebSocket responses as it is generated.
+//!
+//! In the other direction, [`Tekkenizer::encode_appended`] updates the tokens of
+//! a text that grew at the end, such as a chat transcript, by re-encoding only
+//! the last few pretokenized pieces instead of the whole text.
+
+use crate::errors::{Result, TokenizerError};
+use crate::special_tokens::SpecialTokenPolicy;
+use crate::tekkenizer::{EncodeOptions, InvalidTokenPolicy, Tekkenizer, Utf8Carry};
+
+/// Bytes at the end of the previous text that are re-split to find where an
+/// appended suffix stops affecting pretokenization.
+const TAIL_WINDOW: usize = 256;
+
+/// Decoding state shared by [`IncrementalDecoder`] and `DecodeStream`.
+#[derive(Debug)]
+pub(crate) struct DecodeState {
+    carry: Utf8Carry,
+    special_token_policy: SpecialTokenPolicy,
+    invalid_token_policy: InvalidTokenPolicy,
+}
+
+impl DecodeState {
+    pub(crate) fn new(special_token_policy: SpecialTokenPolicy) -> Self {
+        Self {
+            carry: Utf8Carry::default(),
+            special_token_policy,
+            invalid_token_policy: InvalidTokenPolicy::default(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, tokenizer: &Tekkenizer, token_id: u32) -> Result<String> {
+        if tokenizer.is_special_token(token_id) {
+            let token = tokenizer.special_token(token_id)?;
+            self.carry.finish()?;
+            return match self.special_token_policy {
+                SpecialTokenPolicy::Raise => Err(TokenizerError::SpecialTokenPolicy(format!(
+                    "Decoding tokens that contain special tokens ([{token_id}]) is not allowed",
+                ))),
+                SpecialTokenPolicy::Keep => Ok(token.token_str.clone()),
+                SpecialTokenPolicy::Ignore => Ok(String::new()),
+            };
+        }
+
+        let mut replacement = [0; 4];
+        let (completed, text) = match tokenizer.regular_token_bytes(token_id) {
+            Ok(bytes) => self.carry.feed(bytes)?,
+            Err(e) => match self.invalid_token_policy {
+                InvalidTokenPolicy::Error => return Err(e),
+                InvalidTokenPolicy::Skip => (self.carry.flush(), ""),
+                InvalidTokenPolicy::Replace(ch) => {
+                    (self.carry.flush(), &*ch.encode_utf8(&mut replacement))
+                }
+            },
+        };
+
+        let mut piece = String::with_capacity(text.len() + 4);
+        piece.extend(completed);
+        piece.push_str(text);
+        Ok(piece)
+    }
+
+    pub(crate) fn finish(&mut self) -> Result<()> {
+        self.carry.finish()
+    }
+}
+
+/// Decodes generated tokens one at a time into complete UTF-8 text.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::incremental::IncrementalDecoder;
+/// use tekken::special_tokens::SpecialTokenPolicy;
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let mut decoder = IncrementalDecoder::new(&tokenizer, SpecialTokenPolicy::Ignore);
+///
+/// for token_id in tokenizer.encode("Hello 🚀", false, false)? {
+///     print!("{}", decoder.push(token_id)?);
+/// }
+/// decoder.finish()?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct IncrementalDecoder<'a> {
+    tokenizer: &'a Tekkenizer,
+    state: DecodeState,
+}
+
+impl<'a> IncrementalDecoder<'a> {
+    /// Creates a decoder for tokens produced with the given tokenizer.
+    #[must_use]
+    pub fn new(tokenizer: &'a Tekkenizer, special_token_policy: SpecialTokenPolicy) -> Self {
+        Self {
+            tokenizer,
+            state: DecodeState::new(special_token_policy),
+        }
+    }
+
+    /// Sets how token IDs outside the vocabulary are handled.
+    #[must_use]
+    pub fn with_invalid_token_policy(mut self, policy: InvalidTokenPolicy) -> Self {
+        self.state.invalid_token_policy = policy;
+        self
+    }
+
+    /// Feeds the next token.
+    ///
+    /// # Returns
+    ///
+    /// The text completed by this token. It is empty if the token only holds
+    /// part of a character or is an ignored special token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token is invalid under the configured policies, or
+    /// a special token interrupts an incomplete character.
+    pub fn push(&mut self, token_id: u32) -> Result<String> {
+        self.state.push(self.tokenizer, token_id)
+    }
+
+    /// Checks that the stream did not end inside a character and resets the decoder.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if bytes of an incomplete character are still held back.
+    pub fn finish(&mut self) -> Result<()> {
+        self.state.finish()
+    }
+}
+
+/// Tokens of a text after a suffix was appended, from
+/// [`Tekkenizer::encode_appended`].
+///
+/// # Fields
+///
+/// * `tokens` - Tokens of the previous text followed by the suffix
+/// * `reused` - Number of leading tokens taken unchanged from the previous tokens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppendedEncoding {
+    pub tokens: Vec<u32>,
+    pub reused: usize,
+}
+
+impl Tekkenizer {
+    /// Encodes `previous_text` followed by `suffix`, reusing the tokens of
+    /// `previous_text`.
+    ///
+    /// Appending text can only change the pretokenized pieces near the end of
+    /// the previous text, so this re-encodes from the start of its second-to-last
+    /// piece (or an earlier one if the suffix changes the split further back)
+    /// and keeps all tokens before it. The work is bounded by the length of the
+    /// suffix plus a small window of the previous text, instead of growing with
+    /// the whole text.
+    ///
+    /// The kept tokens are checked against the previous tokens, and the whole
+    /// text is encoded again whenever the tail cannot be matched, e.g. when
+    /// `previous_tokens` ends with EOS, was encoded with BPE-dropout or does not
+    /// belong to `previous_text`. A leading BOS is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `previous_text` - The text encoded so far
+    /// * `previous_tokens` - Tokens of `previous_text`, as returned by
+    ///   [`Tekkenizer::encode`] with or without BOS and without EOS
+    /// * `suffix` - The appended text
+    ///
+    /// # Returns
+    ///
+    /// The tokens of the combined text, equal to encoding it in one go, and how
+    /// many of them were reused.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if pretokenization fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let mut text = String::from("User: Hello there!");
+    /// let mut tokens = tokenizer.encode(&text, true, false)?;
+    /// for suffix in ["\nAssistant:", " Hi", ", how can I help?"] {
+    ///     let appended = tokenizer.encode_appended(&text, &tokens, suffix)?;
+    ///     println!("re-encoded {} tokens", appended.tokens.len() - appended.reused);
+    ///     text.push_str(suffix);
+    ///     tokens = appended.tokens;
+    /// }
+    /// assert_eq!(tokens, tokenizer.encode(&text, true, false)?);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_appended(
+        &self,
+        previous_text: &str,
+        previous_tokens: &[u32],
+        suffix: &str,
+    ) -> Result<AppendedEncoding> {
+        if let Some(appended) = self.encode_appended_tail(previous_text, previous_tokens, suffix)? {
+            return Ok(appended);
+        }
+        let add_bos = previous_tokens
+            .first()
+            .is_some_and(|&token| self.bos_id().ok() == Some(token));
+        let tokens = self.encode(&format!("{previous_text}{suffix}"), add_bos, false)?;
+        let reused = usize::from(add_bos);
+        Ok(AppendedEncoding { tokens, reused })
+    }
+
+    /// Re-encodes the tail of the previous text together with the suffix, or
+    /// returns `None` if no reusable piece boundary is found.
+    fn encode_appended_tail(
+        &self,
+        previous_text: &str,
+        previous_tokens: &[u32],
+        suffix: &str,
+    ) -> Result<Option<AppendedEncoding>> {
+        let mut window_start = previous_text.len().saturating_sub(TAIL_WINDOW);
+        while !previous_text.is_char_boundary(window_start) {
+            window_start -= 1;
+        }
+        if window_start == 0 {
+            // Short texts are simply encoded again
+            return Ok(None);
+        }
+
+        // The window may start inside a piece, so its first piece boundary is
+        // not trusted; the split re-synchronizes with the full split after it.
+        let old_window = &previous_text[window_start..];
+        let new_window = format!("{old_window}{suffix}");
+        let old_starts = self.piece_starts(old_window)?;
+        let new_starts = self.piece_starts(&new_window)?;
+        let Some(restart) = old_starts
+            .iter()
+            .copied()
+            .take(old_starts.len().saturating_sub(1))
+            .skip(1)
+            .rev()
+            .find(|start| new_starts.binary_search(start).is_ok())
+        else {
+            return Ok(None);
+        };
+
+        let mut tail = Vec::new();
+        self.encode_into(&old_window[restart..], &mut tail, EncodeOptions::default())?;
+        let Some(reused) = previous_tokens.len().checked_sub(tail.len()) else {
+            return Ok(None);
+        };
+        if previous_tokens[reused..] != tail[..] {
+            return Ok(None);
+        }
+
+        let mut tokens = previous_tokens[..reused].to_vec();
+        self.encode_into(
+            &new_window[restart..],
+            &mut tokens,
+            EncodeOptions::default(),
+        )?;
+        Ok(Some(AppendedEncoding { tokens, reused }))
+    }
+}
+
+#[cfg(feature = "futures")]
+pub use self::stream::DecodeStream;
+
+#[cfg(feature = "futures")]
+mod stream {
+    use std::ops::Deref;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    use futures_core::Stream;
+
+    use super::DecodeState;
+    use crate::errors::Result;
+    use crate::special_tokens::SpecialTokenPolicy;
+    use crate::tekkenizer::{InvalidTokenPolicy, Tekkenizer};
+
+    /// A `Stream` of decoded text chunks over a `Stream` of token IDs.
+    ///
+    /// Chunks are never empty and always end on a character boundary. The stream
+    /// ends after the first error, including an input stream that stops inside a
+    /// character.
+    ///
+    /// `T` is anything that dereferences to a [`Tekkenizer`], such as
+    /// `&Tekkenizer` or `Arc<Tekkenizer>` for `'static` streams.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(tokens: impl futures_core::Stream<Item = u32> + Unpin) -> tekken::Result<()> {
+    /// use std::sync::Arc;
+    /// use futures::StreamExt;
+    /// use tekken::incremental::DecodeStream;
+    /// use tekken::special_tokens::SpecialTokenPolicy;
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Arc::new(Tekkenizer::from_file("tekken.json")?);
+    /// let mut chunks = DecodeStream::new(tokenizer, tokens, SpecialTokenPolicy::Ignore);
+    /// while let Some(chunk) = chunks.next().await {
+    ///     print!("{}", chunk?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub struct DecodeStream<T, S> {
+        tokenizer: T,
+        tokens: S,
+        state: DecodeState,
+        done: bool,
+    }
+
+    impl<T, S> DecodeStream<T, S>
+    where
+        T: Deref<Target = Tekkenizer>,
+        S: Stream<Item = u32> + Unpin,
+    {
+        /// Wraps a stream of token IDs.
+        ///
+        /// Streams that are not `Unpin` can be wrapped with `Box::pin` first.
+        #[must_use]
+        pub fn new(tokenizer: T, tokens: S, special_token_policy: SpecialTokenPolicy) -> Self {
+            Self {
+                tokenizer,
+                tokens,
+                state: DecodeState::new(special_token_policy),
+                done: false,
+            }
+        }
+
+        /// Sets how token IDs outside the vocabulary are handled.
+        #[must_use]
+        pub fn with_invalid_token_policy(mut self, policy: InvalidTokenPolicy) -> Self {
+            self.state.invalid_token_policy = policy;
+            self
+        }
+    }
+
+    impl<T, S> Stream for DecodeStream<T, S>
+    where
+        T: Deref<Target = Tekkenizer> + Unpin,
+        S: Stream<Item = u32> + Unpin,
+    {
+        type Item = Result<String>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = &mut *self;
+            while !this.done {
+                let Poll::Ready(next) = Pin::new(&mut this.tokens).poll_next(cx) else {
+                    return Poll::Pending;
+                };
+                let result = if let Some(token_id) = next {
+                    this.state.push(&this.tokenizer, token_id)
+                } else {
+                    this.done = true;
+                    match this.state.finish() {
+                        Ok(()) => return Poll::Ready(None),
+                        Err(e) => Err(e),
+                    }
+                };
+                match result {
+                    Ok(chunk) if chunk.is_empty() => {}
+                    Ok(chunk) => return Poll::Ready(Some(Ok(chunk))),
+                    Err(e) => {
+                        this.done = true;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                }
+            }
+            Poll::Ready(None)
+        }
+    }
+}
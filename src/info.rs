@@ -0,0 +1,117 @@
+//! Summaries of loaded tokenizers for logging and diagnostics.
+
+use std::fmt;
+
+use crate::config::TokenizerVersion;
+use crate::integrity::to_hex;
+use crate::special_tokens::SpecialTokens;
+use crate::tekkenizer::Tekkenizer;
+
+/// Summary of a loaded tokenizer.
+///
+/// Its `Display` form is a single line meant for startup logs, e.g.
+/// `tekken v7: 131072 tokens (130072 vocabulary + 1000 special, 23 defined), audio, image, native backend, 31.4 MiB, fingerprint 3f2a…`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenizerInfo {
+    /// Tokenizer version.
+    pub version: TokenizerVersion,
+    /// Total vocabulary size including special tokens.
+    pub vocab_size: usize,
+    /// Number of regular vocabulary tokens in use.
+    pub num_vocab_tokens: usize,
+    /// Number of special-token slots.
+    pub num_special_tokens: usize,
+    /// Number of special tokens with a real name rather than a `<SPECIAL_n>` placeholder.
+    pub num_defined_special_tokens: usize,
+    /// Whether audio can be encoded.
+    pub has_audio_support: bool,
+    /// Whether the image tokens `[IMG]`, `[IMG_BREAK]` and `[IMG_END]` are defined.
+    pub has_image_support: bool,
+    /// Name of the BPE backend.
+    pub backend: String,
+    /// Approximate heap memory held by the tokenizer, in bytes.
+    pub memory_bytes: usize,
+    /// Hex-encoded [`Tekkenizer::fingerprint`].
+    pub fingerprint: String,
+}
+
+impl fmt::Display for TokenizerInfo {
+    #[allow(clippy::cast_precision_loss)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "tekken {}: {} tokens ({} vocabulary + {} special, {} defined)",
+            self.version.as_str(),
+            self.vocab_size,
+            self.num_vocab_tokens,
+            self.num_special_tokens,
+            self.num_defined_special_tokens
+        )?;
+        if self.has_audio_support {
+            f.write_str(", audio")?;
+        }
+        if self.has_image_support {
+            f.write_str(", image")?;
+        }
+        write!(
+            f,
+            ", {} backend, {:.1} MiB, fingerprint {}…",
+            self.backend,
+            self.memory_bytes as f64 / (1024.0 * 1024.0),
+            &self.fingerprint[..self.fingerprint.len().min(16)]
+        )
+    }
+}
+
+impl Tekkenizer {
+    /// Returns a summary of the tokenizer.
+    ///
+    /// Computes the fingerprint if it has not been computed yet.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let info = tokenizer.info();
+    /// println!("Loaded {info}");
+    /// assert_eq!(info.vocab_size, tokenizer.vocab_size());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn info(&self) -> TokenizerInfo {
+        let num_special_tokens = self.num_special_tokens();
+        let num_defined_special_tokens = (0..num_special_tokens)
+            .filter_map(|rank| u32::try_from(rank).ok())
+            .filter_map(|id| self.special_token(id).ok())
+            .filter(|token| token.token_str != format!("<SPECIAL_{}>", token.rank))
+            .count();
+        let has_image_support = [
+            SpecialTokens::Img,
+            SpecialTokens::ImgBreak,
+            SpecialTokens::ImgEnd,
+        ]
+        .into_iter()
+        .all(|token| self.id_of(token).is_ok());
+
+        TokenizerInfo {
+            version: self.version().clone(),
+            vocab_size: self.vocab_size(),
+            num_vocab_tokens: self.vocab_size() - num_special_tokens,
+            num_special_tokens,
+            num_defined_special_tokens,
+            has_audio_support: self.has_audio_support(),
+            has_image_support,
+            backend: self.backend_name().to_string(),
+            memory_bytes: self.heap_size(),
+            fingerprint: to_hex(&self.fingerprint()),
+        }
+    }
+}
+
+impl fmt::Display for Tekkenizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.info().fmt(f)
+    }
+}
@@ -0,0 +1,167 @@
+//! Integrity checks of tokenizer files.
+//!
+//! A corrupted or tampered `tekken.json` can still parse and silently produce
+//! different token IDs. Deployments can guard against this with a SHA-256
+//! checksum of the file:
+//!
+//! - A sidecar file next to the tokenizer, named after it with a `.sha256`
+//!   extension (`tekken.json.sha256`), in the format of `sha256sum`. When it
+//!   exists, [`Tekkenizer::from_file`] and the other file loaders
+//!   ([`Tekkenizer::from_sharded`] for the index and each shard,
+//!   [`Tekkenizer::from_tiktoken`] for both files, and the binary loaders)
+//!   verify the file against it before parsing.
+//! - An expected hash known to the deployment, checked with
+//!   [`Tekkenizer::verify`] before loading.
+//!
+//! Binary tokenizer files (feature `zstd`) also embed the fingerprint of the
+//! tokenizer they hold, which is checked on every load.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tekken::integrity::write_checksum;
+//! use tekken::tekkenizer::Tekkenizer;
+//!
+//! // At release time: writes tekken.json.sha256
+//! write_checksum("tekken.json")?;
+//!
+//! // At startup: fails if tekken.json no longer matches its sidecar
+//! let tokenizer = Tekkenizer::from_file("tekken.json")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::ffi::OsString;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::errors::{Result, TokenizerError};
+use crate::tekkenizer::Tekkenizer;
+
+/// Extension appended to a tokenizer file name to name its checksum sidecar.
+pub const CHECKSUM_EXTENSION: &str = "sha256";
+
+/// Returns the SHA-256 digest of `bytes` as lowercase hex.
+#[must_use]
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    to_hex(&Sha256::digest(bytes))
+}
+
+/// Formats `bytes` as lowercase hex.
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .fold(String::with_capacity(2 * bytes.len()), |mut hex, byte| {
+            // Writing to a `String` cannot fail
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+/// Returns the path of the checksum sidecar of a tokenizer file.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::path::Path;
+/// use tekken::integrity::checksum_path;
+///
+/// assert_eq!(
+///     checksum_path("models/tekken.json"),
+///     Path::new("models/tekken.json.sha256")
+/// );
+/// ```
+#[must_use]
+pub fn checksum_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut name = OsString::from(path.as_ref().as_os_str());
+    name.push(".");
+    name.push(CHECKSUM_EXTENSION);
+    PathBuf::from(name)
+}
+
+/// Writes the checksum sidecar of a tokenizer file.
+///
+/// # Returns
+///
+/// The path of the sidecar.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or the sidecar cannot be written.
+pub fn write_checksum<P: AsRef<Path>>(path: P) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let hash = sha256_hex(&std::fs::read(path)?);
+    let name = path
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+    let sidecar = checksum_path(path);
+    std::fs::write(&sidecar, format!("{hash}  {name}\n"))?;
+    Ok(sidecar)
+}
+
+/// Verifies file contents against the checksum sidecar of `path`, if any.
+///
+/// # Errors
+///
+/// Returns an error if the sidecar exists but cannot be read or parsed, or
+/// does not match `content`.
+pub(crate) fn verify_sidecar(path: &Path, content: &[u8]) -> Result<()> {
+    let sidecar = checksum_path(path);
+    let expected = match std::fs::read_to_string(&sidecar) {
+        Ok(expected) => expected,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    // `sha256sum` lines are `<hash>  <file name>`
+    let expected = expected.split_whitespace().next().unwrap_or_default();
+    verify_hash(content, expected)
+}
+
+/// Checks `content` against an expected hex SHA-256 digest.
+fn verify_hash(content: &[u8], expected: &str) -> Result<()> {
+    if expected.len() != 64 || !expected.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(TokenizerError::InvalidConfig(format!(
+            "Expected a SHA-256 checksum of 64 hex digits, got {expected:?}"
+        )));
+    }
+    let actual = sha256_hex(content);
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(TokenizerError::ChecksumMismatch {
+            expected: expected.to_ascii_lowercase(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+impl Tekkenizer {
+    /// Verifies that a tokenizer file has the expected SHA-256 checksum.
+    ///
+    /// The checksum covers the file exactly as stored, before decompression.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the tokenizer file
+    /// * `expected_hash` - The expected SHA-256 digest, as hex in any case
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read, `expected_hash` is not a
+    /// SHA-256 digest, or [`TokenizerError::ChecksumMismatch`] if the file does
+    /// not match it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let expected = std::env::var("TEKKEN_SHA256")?;
+    /// Tekkenizer::verify("tekken.json", &expected)?;
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn verify<P: AsRef<Path>>(path: P, expected_hash: &str) -> Result<()> {
+        verify_hash(&std::fs::read(path)?, expected_hash)
+    }
+}
@@ -0,0 +1,119 @@
+//! JavaScript bindings built with `wasm-bindgen`.
+//!
+//! Compiled to `wasm32-unknown-unknown` with the `js` feature, this module exports
+//! a `JsTekkenizer` class for client-side tokenization in browsers and Node.js:
+//!
+//! ```js
+//! import init, { JsTekkenizer } from "./tekken.js";
+//!
+//! await init();
+//! const bytes = new Uint8Array(await (await fetch("tekken.json")).arrayBuffer());
+//! const tokenizer = new JsTekkenizer(bytes);
+//! const ids = tokenizer.encode("Hello, world!", true, false); // Uint32Array
+//! console.log(tokenizer.countTokens("Hello, world!"), tokenizer.decode(ids));
+//! ```
+//!
+//! Errors are thrown as JavaScript `Error`s carrying the [`TokenizerError`] message.
+//!
+//! [`TokenizerError`]: crate::errors::TokenizerError
+
+use wasm_bindgen::prelude::*;
+
+use crate::special_tokens::SpecialTokenPolicy;
+use crate::tekkenizer::Tekkenizer;
+
+/// A Tekken tokenizer exposed to JavaScript.
+#[wasm_bindgen]
+pub struct JsTekkenizer {
+    inner: Tekkenizer,
+}
+
+#[wasm_bindgen]
+impl JsTekkenizer {
+    /// Loads a tokenizer from the bytes of a `tekken.json` file.
+    ///
+    /// # Errors
+    ///
+    /// Throws if the bytes are not a valid tokenizer file.
+    #[wasm_bindgen(constructor)]
+    pub fn new(bytes: &[u8]) -> Result<JsTekkenizer, JsError> {
+        Tekkenizer::from_bytes(bytes)
+            .map(Self::from)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Encodes text into a `Uint32Array` of token IDs.
+    ///
+    /// # Errors
+    ///
+    /// Throws if BOS/EOS is requested but missing from the vocabulary.
+    pub fn encode(
+        &self,
+        text: &str,
+        add_beginning_of_sequence: bool,
+        add_end_of_sequence: bool,
+    ) -> Result<Vec<u32>, JsError> {
+        self.inner
+            .encode(text, add_beginning_of_sequence, add_end_of_sequence)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Decodes a `Uint32Array` of token IDs, dropping special tokens unless `keepSpecial` is set.
+    ///
+    /// # Errors
+    ///
+    /// Throws if a token ID is out of range or the tokens are not valid UTF-8.
+    pub fn decode(&self, tokens: &[u32], keep_special: Option<bool>) -> Result<String, JsError> {
+        let policy = if keep_special.unwrap_or(false) {
+            SpecialTokenPolicy::Keep
+        } else {
+            SpecialTokenPolicy::Ignore
+        };
+        self.inner
+            .decode(tokens, policy)
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Counts the tokens of `text`, without BOS/EOS.
+    ///
+    /// # Errors
+    ///
+    /// Throws if the text cannot be encoded.
+    #[wasm_bindgen(js_name = countTokens)]
+    pub fn count_tokens(&self, text: &str) -> Result<usize, JsError> {
+        self.encode(text, false, false).map(|tokens| tokens.len())
+    }
+
+    /// Returns the special token strings, indexed by token ID.
+    #[wasm_bindgen(js_name = specialTokens)]
+    #[must_use]
+    pub fn special_tokens(&self) -> Vec<String> {
+        (0..self.inner.num_special_tokens())
+            .filter_map(|id| u32::try_from(id).ok())
+            .filter_map(|id| self.inner.special_token(id).ok())
+            .map(|token| token.token_str.clone())
+            .collect()
+    }
+
+    /// Returns the IDs of the special tokens as a `Uint32Array`.
+    #[wasm_bindgen(js_name = specialTokenIds)]
+    #[must_use]
+    pub fn special_token_ids(&self) -> Vec<u32> {
+        (0..self.inner.num_special_tokens())
+            .filter_map(|id| u32::try_from(id).ok())
+            .collect()
+    }
+
+    /// Total number of tokens, special tokens included.
+    #[wasm_bindgen(getter, js_name = vocabSize)]
+    #[must_use]
+    pub fn vocab_size(&self) -> usize {
+        self.inner.vocab_size()
+    }
+}
+
+impl From<Tekkenizer> for JsTekkenizer {
+    fn from(inner: Tekkenizer) -> Self {
+        Self { inner }
+    }
+}
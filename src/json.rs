@@ -0,0 +1,177 @@
+//! Canonical JSON matching the reference implementation.
+//!
+//! Tool definitions, tool calls and tool results are embedded in prompts as
+//! JSON text, so a payload only tokenizes like it does in `mistral-common` if it
+//! is serialized byte for byte like Python's `json.dumps(value, ensure_ascii=False)`:
+//!
+//! - `", "` between items and `": "` between keys and values
+//! - keys in insertion order, not sorted
+//! - non-ASCII characters written as is
+//! - floats written like Python's `repr`, e.g. `1e-05`, `1e+16` and `100.0`
+//!
+//! [`to_canonical_string`] serializes any value this way, and [`canonicalize`]
+//! rewrites a JSON document so callers can check their payloads up front.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tekken::json::{canonicalize, is_canonical};
+//!
+//! assert_eq!(canonicalize(r#"{"b":1,"a":[1e-5,2.50]}"#)?, r#"{"b": 1, "a": [1e-05, 2.5]}"#);
+//! assert!(is_canonical(r#"{"city": "Paris"}"#));
+//! # Ok::<(), tekken::TokenizerError>(())
+//! ```
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::ser::Formatter;
+
+use crate::errors::Result;
+
+/// Writes JSON like Python's `json.dumps` with its default separators.
+struct PythonFormatter;
+
+impl Formatter for PythonFormatter {
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if first {
+            Ok(())
+        } else {
+            writer.write_all(b", ")
+        }
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+
+    fn write_f32<W: ?Sized + Write>(&mut self, writer: &mut W, value: f32) -> io::Result<()> {
+        // Python only has doubles
+        self.write_f64(writer, f64::from(value))
+    }
+
+    fn write_f64<W: ?Sized + Write>(&mut self, writer: &mut W, value: f64) -> io::Result<()> {
+        writer.write_all(python_float_repr(value).as_bytes())
+    }
+}
+
+/// Formats a float like Python's `repr`.
+///
+/// Both use the shortest digits that round-trip; Python switches to scientific
+/// notation when the decimal exponent is below -4 or at least 16. `serde_json`
+/// writes non-finite floats as `null` without calling the formatter.
+fn python_float_repr(value: f64) -> String {
+    // `{:e}` gives the shortest round-trip digits, e.g. "-1.25e-7"
+    let scientific = format!("{value:e}");
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or_default();
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+
+    if !(-4..16).contains(&exponent) {
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{rest}")
+        };
+        let exponent_sign = if exponent < 0 { '-' } else { '+' };
+        return format!(
+            "{sign}{first}{fraction}e{exponent_sign}{:02}",
+            exponent.abs()
+        );
+    }
+
+    // Position of the decimal point relative to the first digit
+    let point = exponent + 1;
+    #[allow(clippy::cast_sign_loss)]
+    let fixed = if point <= 0 {
+        format!("0.{}{digits}", "0".repeat((-point) as usize))
+    } else if point as usize >= digits.len() {
+        format!("{digits}{}.0", "0".repeat(point as usize - digits.len()))
+    } else {
+        let (whole, fraction) = digits.split_at(point as usize);
+        format!("{whole}.{fraction}")
+    };
+    format!("{sign}{fixed}")
+}
+
+/// Serializes a value the way the reference implementation does.
+///
+/// # Arguments
+///
+/// * `value` - The value to serialize
+///
+/// # Returns
+///
+/// The JSON text, byte for byte equal to Python's
+/// `json.dumps(value, ensure_ascii=False)`.
+///
+/// # Errors
+///
+/// Returns an error if `value` cannot be serialized as JSON.
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::json::to_canonical_string;
+///
+/// let arguments = serde_json::json!({"city": "Zürich", "days": 3, "threshold": 0.00001});
+/// assert_eq!(
+///     to_canonical_string(&arguments)?,
+///     r#"{"city": "Zürich", "days": 3, "threshold": 1e-05}"#
+/// );
+/// # Ok::<(), tekken::TokenizerError>(())
+/// ```
+pub fn to_canonical_string<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    let mut out = Vec::new();
+    value.serialize(&mut serde_json::Serializer::with_formatter(
+        &mut out,
+        PythonFormatter,
+    ))?;
+    // serde_json only writes valid UTF-8
+    Ok(String::from_utf8(out).unwrap_or_default())
+}
+
+/// Rewrites a JSON document in canonical form, keeping the order of its keys.
+///
+/// # Arguments
+///
+/// * `json` - The JSON text
+///
+/// # Returns
+///
+/// The document as [`to_canonical_string`] writes it.
+///
+/// # Errors
+///
+/// Returns an error if `json` is not valid JSON.
+pub fn canonicalize(json: &str) -> Result<String> {
+    to_canonical_string(&serde_json::from_str::<Value>(json)?)
+}
+
+/// Returns whether `json` is valid JSON already in canonical form.
+#[must_use]
+pub fn is_canonical(json: &str) -> bool {
+    canonicalize(json).is_ok_and(|canonical| canonical == json)
+}
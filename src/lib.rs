@@ -37,9 +37,13 @@
 //!
 //! ### Audio Tokenization
 //!
+//! Audio processing requires the `audio` feature, which is enabled by default.
+//!
 //! ```rust,no_run
+//! # #[cfg(feature = "audio")]
 //! use tekken::{Audio, AudioConfig, AudioSpectrogramConfig, AudioEncoder};
 //!
+//! # #[cfg(feature = "audio")]
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! // Load audio file
 //! let audio = Audio::from_file("audio.wav")?;
@@ -55,13 +59,17 @@
 //! println!("Audio encoded to {} tokens", encoding.tokens.len());
 //! # Ok(())
 //! # }
+//! # #[cfg(not(feature = "audio"))]
+//! # fn main() {}
 //! ```
 //!
 //! ### Multimodal Tokenization
 //!
 //! ```rust,no_run
+//! # #[cfg(feature = "audio")]
 //! use tekken::{Tekkenizer, Audio, SpecialTokenPolicy};
 //!
+//! # #[cfg(feature = "audio")]
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! let tokenizer = Tekkenizer::from_file("tekken.json")?;
 //!
@@ -81,6 +89,8 @@
 //! }
 //! # Ok(())
 //! # }
+//! # #[cfg(not(feature = "audio"))]
+//! # fn main() {}
 //! ```
 //!
 //! ## Architecture
@@ -88,10 +98,33 @@
 //! The library is organized into several modules:
 //!
 //! - [`tekkenizer`]: Main tokenizer implementation and text processing
-//! - [`audio`]: Audio processing, mel-scale spectrograms, and audio tokenization  
+//! - [`token_id`]: Typed token IDs and the rank-to-ID shift
+//! - [`alignment`]: Token-to-character alignment and annotated spans for visualizations
+//! - [`analysis`]: Corpus token statistics under a tokenizer's vocabulary
+//! - [`audio`]: Audio processing, mel-scale spectrograms, and audio tokenization
+//!   (processing requires the default `audio` feature)
+//! - [`backend`]: Pluggable BPE encoding engines
+//! - [`parallel`]: Parallelism settings for batch APIs
+//! - [`pipeline`]: Batch tokenization of text files into token shards
+//! - [`prompt`]: Fluent assembly of instruct prompts
+//! - [`rank_file`]: Import and export of `.tiktoken` rank files
+//! - [`report`]: Non-fatal issues found while loading tokenizer files
+//! - [`sharded`]: Tokenizers whose vocabulary is split across files
 //! - [`special_tokens`]: Special token definitions and handling policies
+//! - [`splitter`]: Token-aware chunking of long documents
+//! - [`stop_sequences`]: Incremental stop-sequence detection over token streams
+//! - [`text`]: Text-only tokenization core, available without `std`
+//! - [`collate`]: Padding of multimodal encodings into batches
 //! - [`config`]: Configuration structures and version management
+//! - [`diff`](mod@diff): Comparison of two tokenizer files
+//! - [`draft`]: Verification of draft tokens for speculative decoding
+//! - [`encoding`]: Model-ready encodings with attention masks, padding and truncation
 //! - [`errors`]: Comprehensive error handling
+//! - [`explain`]: Traces of pretokenization and BPE merges for debugging
+//! - [`incremental`]: Incremental decoding of generated token streams
+//! - [`info`]: Summaries of loaded tokenizers for logging and diagnostics
+//! - [`integrity`]: Checksum verification of tokenizer files
+//! - [`unstable`]: Prompt encodings with the possible completions of their tail
 //!
 //! ## Compatibility
 //!
@@ -111,17 +144,174 @@
 //! - Efficient audio processing with optimized mel-scale computations
 //! - Fast BPE tokenization using proven algorithms
 //! - Minimal allocations and efficient data structures
+//!
+//! ## `no_std` Support
+//!
+//! Everything except the text tokenization core lives behind the default `std`
+//! feature. Without it the crate is `#![no_std]`, needs only `alloc`, and
+//! provides [`TextTokenizer`] for encoding and decoding text on embedded
+//! targets:
+//!
+//! ```toml
+//! [dependencies]
+//! tekken = { version = "0.1.0", default-features = false }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// The `cdylib` crate type needs a panic handler and a global allocator, which
+// `std` provides where it exists; bare-metal targets drop `cdylib` instead
+#[cfg(all(not(feature = "std"), not(target_os = "none")))]
+extern crate std;
 
+#[cfg(feature = "std")]
+pub mod alignment;
+#[cfg(feature = "std")]
+pub mod analysis;
+#[cfg(feature = "std")]
 pub mod audio;
+#[cfg(feature = "std")]
+pub mod backend;
+#[cfg(feature = "std")]
+pub mod bench;
+#[cfg(feature = "zstd")]
+pub mod binary;
+mod bpe;
+#[cfg(feature = "candle")]
+pub mod candle;
+#[cfg(feature = "cpal")]
+pub mod capture;
+#[cfg(feature = "std")]
+pub mod collate;
+#[cfg(feature = "std")]
 pub mod config;
+#[cfg(feature = "constrain")]
+pub mod constrain;
+#[cfg(feature = "dataset")]
+pub mod dataset;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod draft;
+#[cfg(feature = "std")]
+pub mod encoding;
 pub mod errors;
+#[cfg(feature = "std")]
+pub mod explain;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "std")]
+pub mod image;
+#[cfg(feature = "std")]
+pub mod incremental;
+#[cfg(feature = "std")]
+pub mod info;
+#[cfg(feature = "std")]
+pub mod integrity;
+#[cfg(feature = "js")]
+pub mod js;
+#[cfg(feature = "std")]
+pub mod json;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod parallel;
+#[cfg(not(feature = "std"))]
+mod pattern;
+#[cfg(feature = "std")]
+pub mod pipeline;
+#[cfg(feature = "std")]
+pub mod prompt;
+#[cfg(feature = "std")]
+pub mod rank_file;
+#[cfg(feature = "unstable")]
+pub mod raw;
+#[cfg(feature = "std")]
+pub mod report;
+#[cfg(feature = "std")]
+pub mod request;
+#[cfg(feature = "std")]
+pub mod sharded;
 pub mod special_tokens;
+#[cfg(feature = "std")]
+pub mod splitter;
+#[cfg(feature = "std")]
+pub mod stop_sequences;
+#[cfg(feature = "std")]
 pub mod tekkenizer;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+pub mod text;
+pub mod token_id;
+#[cfg(feature = "std")]
+pub mod unstable;
+#[cfg(feature = "notify")]
+pub mod watch;
 
 // Re-export commonly used types for convenience
-pub use audio::{Audio, AudioConfig, AudioEncoder, AudioSpectrogramConfig};
+#[cfg(feature = "std")]
+pub use alignment::{TokenAlignment, TokenSpan};
+#[cfg(feature = "std")]
+pub use analysis::{ByteFallbackReport, TokenStats};
+#[cfg(feature = "audio")]
+pub use audio::{Audio, AudioSample, StreamingAudioEncoder, log_mel_spectrogram};
+#[cfg(feature = "std")]
+pub use audio::{AudioConfig, AudioEncoder, AudioSpectrogramConfig};
+#[cfg(feature = "std")]
+pub use backend::BpeBackend;
+#[cfg(feature = "std")]
+pub use bench::{Throughput, ThroughputOptions};
+#[cfg(feature = "std")]
+pub use collate::{CollatedBatch, MultimodalEncoding};
+#[cfg(feature = "std")]
 pub use config::{TekkenConfig, TokenInfo};
-pub use errors::{Result, TokenizerError};
+#[cfg(feature = "std")]
+pub use diff::{VocabDiff, diff};
+#[cfg(feature = "std")]
+pub use draft::DraftVerification;
+#[cfg(feature = "std")]
+pub use encoding::{Encoding, EncodingOptions, Padding, PaddingSide, position_ids};
+pub use errors::{LoadStage, Result, TokenizerError};
+#[cfg(feature = "std")]
+pub use explain::Explanation;
+#[cfg(feature = "std")]
+pub use image::ImageEncoder;
+#[cfg(feature = "image")]
+pub use image::{Image, ImageEncoding, PreprocessedImage};
+#[cfg(feature = "std")]
+pub use incremental::{AppendedEncoding, IncrementalDecoder};
+#[cfg(feature = "std")]
+pub use info::TokenizerInfo;
+#[cfg(feature = "std")]
+pub use parallel::{BatchOutput, CancellationToken, ParallelismConfig};
+#[cfg(feature = "std")]
+pub use pipeline::{Pipeline, PipelineProgress, PipelineReport};
+#[cfg(feature = "std")]
+pub use prompt::{
+    ChatMessage, EosPolicy, PromptBuilder, PromptEncoding, TruncatedPrompt, TruncationStrategy,
+};
+#[cfg(feature = "unstable")]
+pub use raw::RawBpe;
+#[cfg(feature = "std")]
+pub use report::{LoadReport, LoadWarning};
+#[cfg(feature = "std")]
+pub use request::{ChatCompletionRequest, RequestViolation, TokenizedRequest, ValidationMode};
 pub use special_tokens::SpecialTokenInfo;
 pub use special_tokens::{SpecialTokenPolicy, SpecialTokens};
-pub use tekkenizer::Tekkenizer;
+#[cfg(feature = "std")]
+pub use splitter::{TextChunk, TextSplitter};
+#[cfg(feature = "std")]
+pub use stop_sequences::{StopMatch, StopSequenceMatcher};
+#[cfg(feature = "std")]
+pub use tekkenizer::{
+    BpeDropout, EncodeOptions, InvalidTokenPolicy, MatchMode, RankGapPolicy, Tekkenizer,
+};
+pub use text::TextTokenizer;
+pub use token_id::{TokenId, convert_ids};
+#[cfg(feature = "std")]
+pub use unstable::UnstableEncoding;
+#[cfg(feature = "notify")]
+pub use watch::WatchedTekkenizer;
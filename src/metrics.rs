@@ -0,0 +1,64 @@
+//! Operational metrics through the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! With the `metrics` feature enabled, the tokenizer reports counters and
+//! histograms to whichever recorder the application installs (for example
+//! `metrics-exporter-prometheus`). Without a recorder every call is a no-op.
+//!
+//! | Name | Kind | Labels | Description |
+//! |------|------|--------|-------------|
+//! | [`TOKENS_ENCODED`] | counter | | Token IDs produced by text encoding, including BOS/EOS |
+//! | [`ENCODE_DURATION`] | histogram | | Wall-clock time of one text encoding, in seconds |
+//! | [`DECODE_ERRORS`] | counter | | Decoding calls that failed |
+//! | [`AUDIO_MILLISECONDS_PROCESSED`] | counter | | Milliseconds of audio encoded, measured before resampling |
+//! | [`CACHE_HITS`] | counter | `cache` | Lookups served by a lazily built table (`vocab` or `fingerprint`) |
+//!
+//! Audio is counted in milliseconds because `metrics` counters only hold
+//! integers; divide by 1000 for seconds of audio processed.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! # use tekken::tekkenizer::Tekkenizer;
+//! // Install a recorder first, e.g. `metrics_exporter_prometheus::PrometheusBuilder`
+//! let tokenizer = Tekkenizer::from_file("tekken.json")?;
+//! tokenizer.encode("Hello, world!", true, true)?;
+//! // `tekken_tokens_encoded_total` is now 6
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::time::Duration;
+
+/// Counter of token IDs produced by text encoding.
+pub const TOKENS_ENCODED: &str = "tekken_tokens_encoded_total";
+
+/// Histogram of text encoding latency, in seconds.
+pub const ENCODE_DURATION: &str = "tekken_encode_duration_seconds";
+
+/// Counter of failed decoding calls.
+pub const DECODE_ERRORS: &str = "tekken_decode_errors_total";
+
+/// Counter of milliseconds of audio encoded.
+pub const AUDIO_MILLISECONDS_PROCESSED: &str = "tekken_audio_processed_milliseconds_total";
+
+/// Counter of lookups served by a lazily built table, labelled by `cache`.
+pub const CACHE_HITS: &str = "tekken_cache_hits_total";
+
+pub(crate) fn record_encode(num_tokens: usize, elapsed: Duration) {
+    metrics::counter!(TOKENS_ENCODED).increment(num_tokens as u64);
+    metrics::histogram!(ENCODE_DURATION).record(elapsed.as_secs_f64());
+}
+
+pub(crate) fn record_decode_error() {
+    metrics::counter!(DECODE_ERRORS).increment(1);
+}
+
+#[cfg(feature = "audio")]
+// Durations are non-negative and far below `u64::MAX` milliseconds
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+pub(crate) fn record_audio(seconds: f64) {
+    metrics::counter!(AUDIO_MILLISECONDS_PROCESSED).increment((seconds * 1000.0).round() as u64);
+}
+
+pub(crate) fn record_cache_hit(cache: &'static str) {
+    metrics::counter!(CACHE_HITS, "cache" => cache).increment(1);
+}
@@ -0,0 +1,414 @@
+//! Parallelism settings for batch APIs.
+//!
+//! Batch methods such as [`Tekkenizer::encode_batch`] take a [`ParallelismConfig`]
+//! that decides whether, and on which threads, the items of a batch are processed
+//! concurrently. Applications with their own rayon pools can keep tokenization
+//! off the global pool by passing a dedicated pool or a thread limit. Threads are
+//! only used with the `parallel` feature, which pulls in `rayon`; without it every
+//! configuration runs sequentially on the calling thread, which suits targets
+//! without threads such as WASM.
+//!
+//! A [`CancellationToken`] attached with [`ParallelismConfig::with_cancellation`]
+//! lets a shutting-down service stop long batch jobs early and keep the work
+//! already done: batch APIs return a [`BatchOutput`] that says whether it holds
+//! every result or only those completed before the cancellation.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(feature = "audio")]
+use crate::audio::{Audio, AudioEncoding, AudioSample};
+use crate::errors::{Result, TokenizerError};
+use crate::special_tokens::SpecialTokenPolicy;
+use crate::tekkenizer::Tekkenizer;
+
+/// How batch APIs distribute their work across threads.
+///
+/// The default uses rayon's global thread pool when the `parallel` feature is
+/// enabled, and runs sequentially otherwise.
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::parallel::ParallelismConfig;
+///
+/// // At most two worker threads, e.g. in a memory-constrained serverless function
+/// let config = ParallelismConfig::with_max_threads(2)?;
+/// assert_eq!(config.is_parallel(), cfg!(feature = "parallel"));
+///
+/// assert!(!ParallelismConfig::sequential().is_parallel());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParallelismConfig {
+    mode: Mode,
+    cancellation: Option<CancellationToken>,
+}
+
+/// A flag for stopping batch jobs from another thread.
+///
+/// Clones share the flag, so a service can keep one clone and cancel the jobs
+/// holding the others, e.g. from a shutdown handler.
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::parallel::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let job_token = token.clone();
+/// token.cancel();
+/// assert!(job_token.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a token that is not cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the jobs holding this token or a clone of it.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks whether the token has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Results of a batch job, which may have been cancelled.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::parallel::{CancellationToken, ParallelismConfig};
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let token = CancellationToken::new();
+/// let config = ParallelismConfig::default().with_cancellation(token.clone());
+/// let batch = tokenizer.encode_batch(&["Hello", "world"], true, false, &config)?;
+/// if batch.cancelled {
+///     eprintln!("stopped after {} texts", batch.results.len());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+#[must_use]
+pub struct BatchOutput<R> {
+    /// Results of the leading items processed, in input order: all of them
+    /// unless the batch was cancelled.
+    pub results: Vec<R>,
+    /// Whether the batch was cancelled before processing every item.
+    pub cancelled: bool,
+}
+
+impl<R> BatchOutput<R> {
+    /// Returns the results of a batch that processed every item.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TokenizerError::Cancelled` if the batch was cancelled.
+    pub fn into_complete(self) -> Result<Vec<R>> {
+        if self.cancelled {
+            return Err(TokenizerError::Cancelled);
+        }
+        Ok(self.results)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+enum Mode {
+    Sequential,
+    #[default]
+    Global,
+    #[cfg(feature = "parallel")]
+    Pool(Arc<rayon::ThreadPool>),
+}
+
+impl ParallelismConfig {
+    /// Processes batches on the calling thread only.
+    #[must_use]
+    pub fn sequential() -> Self {
+        Self {
+            mode: Mode::Sequential,
+            cancellation: None,
+        }
+    }
+
+    /// Processes batches on a dedicated pool of at most `max_threads` threads.
+    ///
+    /// A limit of `0` or `1` is the same as [`ParallelismConfig::sequential`]. The
+    /// pool is shared by clones of the returned config. Without the `parallel`
+    /// feature, batches always run sequentially.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the thread pool cannot be created.
+    pub fn with_max_threads(max_threads: usize) -> Result<Self> {
+        if max_threads <= 1 {
+            return Ok(Self::sequential());
+        }
+        #[cfg(feature = "parallel")]
+        {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(max_threads)
+                .build()
+                .map_err(|e| {
+                    TokenizerError::InvalidConfig(format!("Failed to build thread pool: {e}"))
+                })?;
+            Ok(Self::with_thread_pool(Arc::new(pool)))
+        }
+        #[cfg(not(feature = "parallel"))]
+        Ok(Self::default())
+    }
+
+    /// Processes batches on an existing rayon thread pool.
+    #[cfg(feature = "parallel")]
+    #[must_use]
+    pub fn with_thread_pool(pool: Arc<rayon::ThreadPool>) -> Self {
+        Self {
+            mode: Mode::Pool(pool),
+            cancellation: None,
+        }
+    }
+
+    /// Stops batches once `token` is cancelled.
+    ///
+    /// Items not yet started when the token is cancelled are skipped, and batch
+    /// APIs return the results of the leading items that completed, with
+    /// [`BatchOutput::cancelled`] set. Items already running finish first.
+    #[must_use]
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Checks whether the attached [`CancellationToken`] has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// The same threads, without the cancellation token.
+    #[cfg(feature = "dataset")]
+    pub(crate) fn without_cancellation(&self) -> Self {
+        Self {
+            mode: self.mode.clone(),
+            cancellation: None,
+        }
+    }
+
+    /// Checks whether batches may be processed on more than one thread.
+    #[must_use]
+    pub fn is_parallel(&self) -> bool {
+        match self.mode {
+            Mode::Sequential => false,
+            Mode::Global => cfg!(feature = "parallel"),
+            #[cfg(feature = "parallel")]
+            Mode::Pool(_) => true,
+        }
+    }
+
+    /// Applies `f` to every item, keeping the input order in the output.
+    ///
+    /// After cancellation, returns the results of the leading items completed.
+    pub(crate) fn try_map<T, R, F>(&self, items: &[T], f: F) -> Result<BatchOutput<R>>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> Result<R> + Sync + Send,
+    {
+        let len = items.len();
+        let f = |item| (!self.is_cancelled()).then(|| f(item));
+        if len < 2 || !self.is_parallel() {
+            return completed(len, items.iter().map_while(f).collect());
+        }
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+
+            let run = || items.par_iter().map(f).collect::<Vec<_>>();
+            let results = match &self.mode {
+                Mode::Pool(pool) => pool.install(run),
+                _ => run(),
+            };
+            completed(
+                len,
+                results.into_iter().map_while(|result| result).collect(),
+            )
+        }
+        #[cfg(not(feature = "parallel"))]
+        unreachable!("sequential without the `parallel` feature")
+    }
+
+    /// Like [`ParallelismConfig::try_map`], for items consumed by `f`.
+    #[cfg(feature = "audio")]
+    pub(crate) fn try_map_into<T, R, F>(&self, items: Vec<T>, f: F) -> Result<BatchOutput<R>>
+    where
+        T: Send,
+        R: Send,
+        F: Fn(T) -> Result<R> + Sync + Send,
+    {
+        let len = items.len();
+        let f = |item| (!self.is_cancelled()).then(|| f(item));
+        if len < 2 || !self.is_parallel() {
+            return completed(len, items.into_iter().map_while(f).collect());
+        }
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+
+            let run = || items.into_par_iter().map(f).collect::<Vec<_>>();
+            let results = match &self.mode {
+                Mode::Pool(pool) => pool.install(run),
+                _ => run(),
+            };
+            completed(
+                len,
+                results.into_iter().map_while(|result| result).collect(),
+            )
+        }
+        #[cfg(not(feature = "parallel"))]
+        unreachable!("sequential without the `parallel` feature")
+    }
+}
+
+/// Wraps the results of the leading items of a batch of `len` items.
+fn completed<R>(len: usize, results: Result<Vec<R>>) -> Result<BatchOutput<R>> {
+    let results = results?;
+    Ok(BatchOutput {
+        cancelled: results.len() < len,
+        results,
+    })
+}
+
+impl Tekkenizer {
+    /// Encodes a batch of texts.
+    ///
+    /// # Arguments
+    ///
+    /// * `texts` - The texts to encode
+    /// * `add_beginning_of_sequence` - Whether to prepend the BOS token to each text
+    /// * `add_end_of_sequence` - Whether to append the EOS token to each text
+    /// * `parallelism` - Threads to encode on
+    ///
+    /// # Returns
+    ///
+    /// The token IDs of each text, in input order. If `parallelism` is
+    /// cancelled, only those of the leading texts encoded before it, marked as
+    /// [`BatchOutput::cancelled`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any text fails to encode.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::parallel::ParallelismConfig;
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let batch = tokenizer.encode_batch(
+    ///     &["Hello", "world"],
+    ///     true,
+    ///     false,
+    ///     &ParallelismConfig::default(),
+    /// )?;
+    /// assert_eq!(batch.results.len(), 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_batch<S: AsRef<str> + Sync>(
+        &self,
+        texts: &[S],
+        add_beginning_of_sequence: bool,
+        add_end_of_sequence: bool,
+        parallelism: &ParallelismConfig,
+    ) -> Result<BatchOutput<Vec<u32>>> {
+        parallelism.try_map(texts, |text| {
+            self.encode(
+                text.as_ref(),
+                add_beginning_of_sequence,
+                add_end_of_sequence,
+            )
+        })
+    }
+
+    /// Decodes a batch of token sequences.
+    ///
+    /// # Arguments
+    ///
+    /// * `batch` - The token sequences to decode
+    /// * `special_token_policy` - How to handle special tokens during decoding
+    /// * `parallelism` - Threads to decode on
+    ///
+    /// # Returns
+    ///
+    /// The text of each sequence, in input order. If `parallelism` is
+    /// cancelled, only those of the leading sequences decoded before it,
+    /// marked as [`BatchOutput::cancelled`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any sequence fails to decode.
+    pub fn decode_batch<T: AsRef<[u32]> + Sync>(
+        &self,
+        batch: &[T],
+        special_token_policy: SpecialTokenPolicy,
+        parallelism: &ParallelismConfig,
+    ) -> Result<BatchOutput<String>> {
+        parallelism.try_map(batch, |tokens| {
+            self.decode(tokens.as_ref(), special_token_policy)
+        })
+    }
+
+    /// Encodes a batch of audio clips into tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `audios` - The audio clips to encode
+    /// * `parallelism` - Threads to encode on
+    ///
+    /// # Returns
+    ///
+    /// The encoding of each clip, in input order. If `parallelism` is
+    /// cancelled, only those of the leading clips encoded before it, marked as
+    /// [`BatchOutput::cancelled`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the audio encoder is not configured or any clip
+    /// fails to encode.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::audio::Audio;
+    /// use tekken::parallel::ParallelismConfig;
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let audios = vec![Audio::from_file("a.wav")?, Audio::from_file("b.wav")?];
+    /// // Stay off the global rayon pool, e.g. inside a server with its own
+    /// let encodings = tokenizer.encode_audio_batch(audios, &ParallelismConfig::with_max_threads(2)?)?;
+    /// assert_eq!(encodings.results.len(), 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "audio")]
+    pub fn encode_audio_batch<S: AudioSample>(
+        &self,
+        audios: Vec<Audio<S>>,
+        parallelism: &ParallelismConfig,
+    ) -> Result<BatchOutput<AudioEncoding<S>>> {
+        parallelism.try_map_into(audios, |audio| self.encode_audio(audio))
+    }
+}
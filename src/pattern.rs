@@ -0,0 +1,125 @@
+//! Pretokenization pattern matching without `std`.
+//!
+//! `fancy-regex` needs `std`, so `no_std` builds compile the pattern with
+//! `regex-automata`, which has no lookaround. The only lookaround in tokenizer
+//! patterns is the `\s+(?!\S)` alternative: it leaves the last whitespace
+//! character of a run to the word that follows. It is compiled as `\s+` and the
+//! matched run shortened afterwards, which splits text into the same pieces.
+
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::convert::Infallible;
+
+use regex_automata::{Input, meta};
+
+/// Whitespace alternative that must not swallow the space before a word.
+const WHITESPACE_LOOKAHEAD: &str = r"\s+(?!\S)";
+
+/// Pretokenization pattern with the subset of the `fancy_regex::Regex` API the
+/// BPE engine uses.
+#[derive(Debug, Clone)]
+pub(crate) struct Regex {
+    inner: meta::Regex,
+    pattern: String,
+    // Whether whitespace runs followed by a word give up their last character
+    trims_whitespace: bool,
+}
+
+impl Regex {
+    /// Compiles a pattern whose only lookaround is `\s+(?!\S)`.
+    pub(crate) fn new(pattern: &str) -> Result<Self, Box<meta::BuildError>> {
+        let trims_whitespace = pattern.contains(WHITESPACE_LOOKAHEAD);
+        let inner =
+            meta::Regex::new(&pattern.replace(WHITESPACE_LOOKAHEAD, r"\s+")).map_err(Box::new)?;
+        Ok(Self {
+            inner,
+            pattern: String::from(pattern),
+            trims_whitespace,
+        })
+    }
+
+    /// The pattern as written, before lookaround removal.
+    pub(crate) fn as_str(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Iterates over successive non-overlapping matches in `text`.
+    pub(crate) fn find_iter<'r, 't>(&'r self, text: &'t str) -> Matches<'r, 't> {
+        Matches {
+            regex: self,
+            text,
+            pos: 0,
+        }
+    }
+}
+
+/// A single match of a [`Regex`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Match<'t> {
+    text: &'t str,
+    start: usize,
+    end: usize,
+}
+
+impl<'t> Match<'t> {
+    /// Byte offset of the start of the match.
+    pub(crate) fn start(&self) -> usize {
+        self.start
+    }
+
+    /// The matched text.
+    pub(crate) fn as_str(&self) -> &'t str {
+        &self.text[self.start..self.end]
+    }
+}
+
+/// Iterator over the matches of a [`Regex`], see [`Regex::find_iter`].
+///
+/// Items are `Result`s like those of `fancy_regex`, but matching cannot fail.
+#[derive(Debug)]
+pub(crate) struct Matches<'r, 't> {
+    regex: &'r Regex,
+    text: &'t str,
+    pos: usize,
+}
+
+impl<'t> Iterator for Matches<'_, 't> {
+    type Item = Result<Match<'t>, Infallible>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos > self.text.len() {
+            return None;
+        }
+        let found = self
+            .regex
+            .inner
+            .search(&Input::new(self.text).range(self.pos..))?;
+        let (start, mut end) = (found.start(), found.end());
+
+        // A run without line breaks can only come from the `\s+` alternatives;
+        // runs with one are taken by the `\s*[\r\n]+` alternative before them
+        let piece = &self.text[start..end];
+        if self.regex.trims_whitespace
+            && end < self.text.len()
+            && piece.chars().nth(1).is_some()
+            && piece
+                .chars()
+                .all(|c| c.is_whitespace() && c != '\r' && c != '\n')
+            && let Some((last, _)) = piece.char_indices().next_back()
+        {
+            end = start + last;
+        }
+
+        self.pos = if end == start {
+            // Step over the character after an empty match
+            end + self.text[end..].chars().next().map_or(1, char::len_utf8)
+        } else {
+            end
+        };
+        Some(Ok(Match {
+            text: self.text,
+            start,
+            end,
+        }))
+    }
+}
@@ -0,0 +1,346 @@
+//! Batch tokenization of text files into token shards.
+//!
+//! [`Pipeline`] is the usual dataset-preparation loop: collect text files from
+//! directories, tokenize them, and write the tokens to fixed-size shard files for
+//! training. Files are read and encoded a batch at a time, in parallel according
+//! to a [`ParallelismConfig`], so memory stays bounded regardless of corpus size.
+//!
+//! Shards are named `<prefix>_00000.bin`, `<prefix>_00001.bin`, … and hold the
+//! token IDs of whole documents, in input order, as little-endian `u32`s. Input
+//! files are processed in sorted path order, so output is reproducible.
+//!
+//! A pipeline whose [`ParallelismConfig`] carries a
+//! [`CancellationToken`](crate::parallel::CancellationToken) stops once it is
+//! cancelled, keeping the shards of the files tokenized so far.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::errors::{Result, TokenizerError};
+use crate::parallel::ParallelismConfig;
+use crate::tekkenizer::Tekkenizer;
+
+/// Progress of a running [`Pipeline`], reported after every batch of files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PipelineProgress {
+    /// Files tokenized so far.
+    pub files_done: usize,
+    /// Files in the whole run.
+    pub files_total: usize,
+    /// Bytes of text read so far.
+    pub bytes_read: u64,
+    /// Tokens written so far.
+    pub tokens_written: u64,
+    /// Shard files started so far.
+    pub shards: usize,
+}
+
+/// Summary of a completed [`Pipeline`] run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PipelineReport {
+    /// Number of files tokenized.
+    pub files: usize,
+    /// Total number of tokens written.
+    pub tokens: u64,
+    /// Paths of the shard files, in order.
+    pub shards: Vec<PathBuf>,
+    /// Whether the run was cancelled before tokenizing every file.
+    pub cancelled: bool,
+}
+
+type ProgressCallback<'a> = Box<dyn FnMut(&PipelineProgress) + 'a>;
+
+/// Tokenizes directories of text files into shard files.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::parallel::ParallelismConfig;
+/// use tekken::pipeline::Pipeline;
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let report = Pipeline::new(&tokenizer, "shards")
+///     .add_dir("corpus")
+///     .with_pattern("*.txt")
+///     .with_shard_tokens(100_000_000)
+///     .with_parallelism(ParallelismConfig::with_max_threads(8)?)
+///     .on_progress(|p| eprintln!("{}/{} files", p.files_done, p.files_total))
+///     .run()?;
+/// println!("{} tokens in {} shards", report.tokens, report.shards.len());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct Pipeline<'a> {
+    tokenizer: &'a Tekkenizer,
+    output_dir: PathBuf,
+    dirs: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+    pattern: Option<String>,
+    shard_prefix: String,
+    shard_tokens: u64,
+    files_per_batch: usize,
+    add_bos: bool,
+    add_eos: bool,
+    parallelism: ParallelismConfig,
+    progress: Option<ProgressCallback<'a>>,
+}
+
+impl<'a> Pipeline<'a> {
+    /// Creates a pipeline writing shards to `output_dir`.
+    ///
+    /// Defaults: no inputs, every file matches, shards of 100M tokens, 64 files
+    /// per batch, EOS after each document, default parallelism.
+    #[must_use]
+    pub fn new(tokenizer: &'a Tekkenizer, output_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            tokenizer,
+            output_dir: output_dir.into(),
+            dirs: Vec::new(),
+            files: Vec::new(),
+            pattern: None,
+            shard_prefix: "shard".to_string(),
+            shard_tokens: 100_000_000,
+            files_per_batch: 64,
+            add_bos: false,
+            add_eos: true,
+            parallelism: ParallelismConfig::default(),
+            progress: None,
+        }
+    }
+
+    /// Adds every matching file under `dir`, recursively.
+    #[must_use]
+    pub fn add_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dirs.push(dir.into());
+        self
+    }
+
+    /// Adds a single file, regardless of the file name pattern.
+    #[must_use]
+    pub fn add_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.files.push(file.into());
+        self
+    }
+
+    /// Only takes files from directories whose name matches `pattern`.
+    ///
+    /// `*` matches any run of characters and `?` a single character, e.g. `*.txt`.
+    #[must_use]
+    pub fn with_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.pattern = Some(pattern.into());
+        self
+    }
+
+    /// Sets the file name prefix of the shards.
+    #[must_use]
+    pub fn with_shard_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.shard_prefix = prefix.into();
+        self
+    }
+
+    /// Starts a new shard once the current one holds at least `tokens` tokens.
+    ///
+    /// Documents are never split, so a shard can exceed the limit by its last
+    /// document.
+    #[must_use]
+    pub fn with_shard_tokens(mut self, tokens: u64) -> Self {
+        self.shard_tokens = tokens.max(1);
+        self
+    }
+
+    /// Sets how many files are read and tokenized at once.
+    ///
+    /// Larger batches use more threads effectively but keep more text in memory.
+    #[must_use]
+    pub fn with_files_per_batch(mut self, files: usize) -> Self {
+        self.files_per_batch = files.max(1);
+        self
+    }
+
+    /// Sets the special tokens added around each document.
+    #[must_use]
+    pub fn with_special_tokens(
+        mut self,
+        add_beginning_of_sequence: bool,
+        add_end_of_sequence: bool,
+    ) -> Self {
+        self.add_bos = add_beginning_of_sequence;
+        self.add_eos = add_end_of_sequence;
+        self
+    }
+
+    /// Sets the threads used to tokenize each batch.
+    #[must_use]
+    pub fn with_parallelism(mut self, parallelism: ParallelismConfig) -> Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// Calls `callback` with the progress after every batch.
+    #[must_use]
+    pub fn on_progress(mut self, callback: impl FnMut(&PipelineProgress) + 'a) -> Self {
+        self.progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Collects the input files, in the order they are processed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory cannot be read.
+    pub fn input_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = self.files.clone();
+        let mut visited = HashSet::new();
+        for dir in &self.dirs {
+            self.walk(dir, &mut files, &mut visited)?;
+        }
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+
+    /// Collects the files below `dir`, following symlinks.
+    ///
+    /// `visited` holds the canonical paths of the directories walked so far, so
+    /// a directory reached twice, e.g. through a symlink cycle, is walked once.
+    fn walk(
+        &self,
+        dir: &Path,
+        files: &mut Vec<PathBuf>,
+        visited: &mut HashSet<PathBuf>,
+    ) -> Result<()> {
+        let canonical = fs::canonicalize(dir).map_err(|e| with_path(&e, dir))?;
+        if !visited.insert(canonical) {
+            return Ok(());
+        }
+        for entry in fs::read_dir(dir).map_err(|e| with_path(&e, dir))? {
+            let entry = entry.map_err(|e| with_path(&e, dir))?;
+            let path = entry.path();
+            let metadata = fs::metadata(&path).map_err(|e| with_path(&e, &path))?;
+            if metadata.is_dir() {
+                self.walk(&path, files, visited)?;
+            } else if self.pattern.as_deref().is_none_or(|pattern| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| wildcard_match(pattern, name))
+            }) {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Tokenizes all input files into shards.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an input cannot be read or is not UTF-8, tokenization
+    /// fails, or a shard cannot be written. Shards written before the error are
+    /// left in place.
+    ///
+    /// Cancellation is not an error: the run stops, and the report covers the
+    /// leading files tokenized before it, which are fully written.
+    pub fn run(mut self) -> Result<PipelineReport> {
+        let files = self.input_files()?;
+        fs::create_dir_all(&self.output_dir).map_err(|e| with_path(&e, &self.output_dir))?;
+
+        let mut progress = PipelineProgress {
+            files_total: files.len(),
+            ..PipelineProgress::default()
+        };
+        let mut report = PipelineReport::default();
+        let mut shard: Option<(BufWriter<File>, u64)> = None;
+
+        for batch in files.chunks(self.files_per_batch) {
+            let encoded = self.parallelism.try_map(batch, |path| {
+                let text = fs::read_to_string(path).map_err(|e| with_path(&e, path))?;
+                let tokens = self.tokenizer.encode(&text, self.add_bos, self.add_eos)?;
+                Ok((text.len() as u64, tokens))
+            })?;
+            let files_done = encoded.results.len();
+
+            for (bytes, tokens) in encoded.results {
+                if shard.is_none() {
+                    let path = self.output_dir.join(format!(
+                        "{}_{:05}.bin",
+                        self.shard_prefix,
+                        report.shards.len()
+                    ));
+                    let file = File::create(&path).map_err(|e| with_path(&e, &path))?;
+                    shard = Some((BufWriter::new(file), 0));
+                    report.shards.push(path);
+                }
+                if let Some((writer, written)) = &mut shard {
+                    for token in &tokens {
+                        writer.write_all(&token.to_le_bytes())?;
+                    }
+                    *written += tokens.len() as u64;
+                    if *written >= self.shard_tokens {
+                        writer.flush()?;
+                        shard = None;
+                    }
+                }
+
+                progress.bytes_read += bytes;
+                progress.tokens_written += tokens.len() as u64;
+            }
+
+            progress.files_done += files_done;
+            progress.shards = report.shards.len();
+            if let Some(callback) = &mut self.progress {
+                callback(&progress);
+            }
+            if encoded.cancelled {
+                report.cancelled = true;
+                break;
+            }
+        }
+
+        if let Some((mut writer, _)) = shard {
+            writer.flush()?;
+        }
+        report.files = progress.files_done;
+        report.tokens = progress.tokens_written;
+        Ok(report)
+    }
+}
+
+/// Adds the offending path to an I/O error.
+fn with_path(error: &io::Error, path: &Path) -> TokenizerError {
+    TokenizerError::Io(io::Error::new(
+        error.kind(),
+        format!("{}: {error}", path.display()),
+    ))
+}
+
+/// Matches `name` against a pattern of literal characters, `*` and `?`.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position after the last `*` and the name position it currently absorbs up to
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_n)) => {
+                    p = star_p;
+                    n = star_n + 1;
+                    backtrack = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
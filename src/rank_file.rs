@@ -0,0 +1,198 @@
+//! Import and export of `.tiktoken` rank files.
+//!
+//! The `.tiktoken` format used across the tiktoken ecosystem lists the mergeable
+//! ranks of a BPE vocabulary, one `<base64 token bytes> <rank>` line per token.
+//! It carries no pattern or special tokens, so those travel in a JSON sidecar
+//! ([`TiktokenSidecar`]) holding the remaining sections of `tekken.json`.
+//!
+//! Ranks in the file are raw BPE ranks; token IDs are offset by the number of
+//! special tokens as usual.
+
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use base64::Engine;
+use base64::engine::general_purpose;
+
+use crate::config::{TekkenConfig, TiktokenSidecar};
+use crate::errors::{Result, TokenizerError};
+use crate::integrity::verify_sidecar;
+use crate::tekkenizer::Tekkenizer;
+
+impl Tekkenizer {
+    /// Loads a tokenizer from a `.tiktoken` rank file and its sidecar.
+    ///
+    /// Both files are verified against their `.sha256` checksum files, when
+    /// present (see [`crate::integrity`]).
+    ///
+    /// # Arguments
+    ///
+    /// * `ranks_path` - Path to the `.tiktoken` rank file
+    /// * `sidecar_path` - Path to the JSON [`TiktokenSidecar`]
+    ///
+    /// # Returns
+    ///
+    /// A new `Tekkenizer` instance.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A file cannot be read or the sidecar cannot be parsed
+    /// - A file does not match its checksum
+    /// - A line of the rank file is malformed or repeats a rank
+    /// - The resulting configuration is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_tiktoken("tekken.tiktoken", "tekken.sidecar.json")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_tiktoken<P: AsRef<Path>, Q: AsRef<Path>>(
+        ranks_path: P,
+        sidecar_path: Q,
+    ) -> Result<Self> {
+        let ranks = read_verified(ranks_path.as_ref())?;
+        let ranks = String::from_utf8(ranks).map_err(|e| {
+            TokenizerError::InvalidConfig(format!(".tiktoken file is not UTF-8: {e}"))
+        })?;
+        let sidecar: TiktokenSidecar =
+            serde_json::from_slice(&read_verified(sidecar_path.as_ref())?)?;
+        Self::from_tiktoken_str(&ranks, sidecar)
+    }
+
+    /// Builds a tokenizer from the contents of a `.tiktoken` rank file.
+    ///
+    /// Blank lines are skipped and lines may appear in any order.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranks` - Contents of the rank file
+    /// * `sidecar` - Special tokens and configuration of the tokenizer
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line is malformed, a rank is repeated, or the
+    /// resulting configuration is invalid.
+    pub fn from_tiktoken_str(ranks: &str, sidecar: TiktokenSidecar) -> Result<Self> {
+        let mut vocab = Vec::new();
+        for (index, line) in ranks.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let malformed = || {
+                TokenizerError::InvalidConfig(format!(
+                    "Malformed .tiktoken line {}: expected `<base64> <rank>`, got {line:?}",
+                    index + 1
+                ))
+            };
+            let mut fields = line.split_ascii_whitespace();
+            let (Some(token_bytes), Some(rank), None) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(malformed());
+            };
+            let rank: usize = rank.parse().map_err(|_| malformed())?;
+            vocab.push((rank, token_bytes));
+        }
+
+        vocab.sort_unstable_by_key(|&(rank, _)| rank);
+        if let Some(pair) = vocab.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Rank {} appears more than once in the .tiktoken file",
+                pair[0].0
+            )));
+        }
+
+        Self::from_config_parts(
+            vocab.into_iter(),
+            sidecar.special_tokens,
+            sidecar.config,
+            sidecar.audio,
+            sidecar.image,
+        )
+    }
+
+    /// Writes the mergeable ranks in the `.tiktoken` format.
+    ///
+    /// Placeholder ranks are left out; the sidecar lists them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing fails.
+    pub fn write_tiktoken<W: Write>(&self, mut writer: W) -> Result<()> {
+        let placeholders = self.placeholder_ranks();
+        for (bytes, rank) in self.mergeable_ranks() {
+            if placeholders.binary_search(&(rank as usize)).is_ok() {
+                continue;
+            }
+            writeln!(writer, "{} {rank}", general_purpose::STANDARD.encode(bytes))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the sidecar that accompanies this tokenizer's `.tiktoken` file.
+    #[must_use]
+    pub fn tiktoken_sidecar(&self) -> TiktokenSidecar {
+        let special_tokens = (0..self.num_special_tokens())
+            .filter_map(|rank| u32::try_from(rank).ok())
+            .filter_map(|id| self.special_token(id).ok().cloned())
+            .collect();
+        TiktokenSidecar {
+            special_tokens: Some(special_tokens),
+            config: TekkenConfig {
+                pattern: self.pattern().to_string(),
+                num_vocab_tokens: self.mergeable_ranks().len(),
+                default_vocab_size: self.vocab_size(),
+                default_num_special_tokens: self.num_special_tokens(),
+                version: self.version().as_str().to_string(),
+                placeholder_ranks: self.placeholder_ranks(),
+                extra: serde_json::Map::new(),
+            },
+            audio: self.audio_config().cloned(),
+            image: self.image_config().cloned(),
+        }
+    }
+
+    /// Writes the tokenizer as a `.tiktoken` rank file plus JSON sidecar.
+    ///
+    /// # Arguments
+    ///
+    /// * `ranks_path` - Path of the `.tiktoken` file to create
+    /// * `sidecar_path` - Path of the sidecar file to create
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a file cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// tokenizer.save_tiktoken("tekken.tiktoken", "tekken.sidecar.json")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn save_tiktoken<P: AsRef<Path>, Q: AsRef<Path>>(
+        &self,
+        ranks_path: P,
+        sidecar_path: Q,
+    ) -> Result<()> {
+        self.write_tiktoken(BufWriter::new(std::fs::File::create(ranks_path)?))?;
+        let writer = BufWriter::new(std::fs::File::create(sidecar_path)?);
+        serde_json::to_writer_pretty(writer, &self.tiktoken_sidecar())?;
+        Ok(())
+    }
+}
+
+/// Reads a file and verifies it against its checksum sidecar, if any.
+fn read_verified(path: &Path) -> Result<Vec<u8>> {
+    let content = std::fs::read(path)?;
+    verify_sidecar(path, &content)?;
+    Ok(content)
+}
@@ -0,0 +1,134 @@
+//! Direct access to the built-in BPE engine.
+//!
+//! [`Tekkenizer::raw_bpe`] exposes the merge table and the building blocks of
+//! encoding (pretokenization, per-piece merging, rank lookups) so that custom
+//! algorithms such as beam tokenization or constrained merges can be built
+//! without forking the crate.
+//!
+//! This module requires the `unstable` feature. Its API follows the internals
+//! of the engine and may change in any release, including patch releases.
+//!
+//! All ranks here are raw BPE ranks, starting at 0 for the first byte token;
+//! the token ID of a rank is `rank + num_special_tokens()`.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tekken::tekkenizer::Tekkenizer;
+//!
+//! let tokenizer = Tekkenizer::from_file("tekken.json")?;
+//! let bpe = tokenizer.raw_bpe();
+//! for piece in bpe.pieces("Hello world")? {
+//!     let bytes = &"Hello world".as_bytes()[piece];
+//!     println!("{bytes:?} -> {:?}", bpe.encode_piece(bytes));
+//! }
+//! // Would "Hel" and "lo" merge into a single token?
+//! println!("{:?}", bpe.merge_rank(b"Hel", b"lo"));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::ops::Range;
+
+use crate::bpe::BytePairEncoder;
+use crate::errors::Result;
+use crate::tekkenizer::Tekkenizer;
+use crate::token_id::TokenId;
+
+/// Borrowed view of the BPE engine of a tokenizer, from [`Tekkenizer::raw_bpe`].
+#[derive(Debug, Clone, Copy)]
+pub struct RawBpe<'a> {
+    bpe: &'a BytePairEncoder,
+}
+
+impl RawBpe<'_> {
+    /// Returns the number of ranks, including placeholders.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.bpe.len()
+    }
+
+    /// Whether the engine has no ranks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bpe.len() == 0
+    }
+
+    /// Returns the effective pretokenization pattern.
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        self.bpe.pattern()
+    }
+
+    /// Looks up the rank of a byte sequence, `None` if it is not a token.
+    #[must_use]
+    pub fn rank(&self, bytes: &[u8]) -> Option<u32> {
+        self.bpe.rank(bytes)
+    }
+
+    /// Returns the bytes of the token with the given rank.
+    ///
+    /// Placeholder ranks, which fill gaps in leniently loaded vocabularies,
+    /// return U+FFFD.
+    #[must_use]
+    pub fn token_bytes(&self, rank: u32) -> Option<&[u8]> {
+        self.bpe.token_bytes(rank as usize)
+    }
+
+    /// Whether a rank is a placeholder, which encoding never produces.
+    #[must_use]
+    pub fn is_placeholder(&self, rank: u32) -> bool {
+        self.bpe.is_placeholder(rank as usize)
+    }
+
+    /// Returns the rank of the token formed by merging two parts, if any.
+    ///
+    /// BPE merges the adjacent pair with the lowest such rank first.
+    #[must_use]
+    pub fn merge_rank(&self, left: &[u8], right: &[u8]) -> Option<u32> {
+        let mut merged = Vec::with_capacity(left.len() + right.len());
+        merged.extend_from_slice(left);
+        merged.extend_from_slice(right);
+        self.bpe.rank(&merged)
+    }
+
+    /// Splits text into the pieces BPE merges within.
+    ///
+    /// # Returns
+    ///
+    /// The byte ranges of the non-empty pieces of `text`, in order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pretokenization regex fails (e.g. backtracking limit).
+    pub fn pieces(&self, text: &str) -> Result<Vec<Range<usize>>> {
+        self.bpe.piece_ranges(text)
+    }
+
+    /// Encodes a single piece, which need not be valid UTF-8, into ranks.
+    #[must_use]
+    pub fn encode_piece(&self, piece: &[u8]) -> Vec<u32> {
+        let mut ranks = Vec::new();
+        self.bpe.encode_piece(piece, TokenId::new(0), &mut ranks);
+        ranks
+    }
+
+    /// Returns the merges applied when encoding a piece, in order.
+    ///
+    /// Each merge is given as the byte ranges of the two parts it joins.
+    /// Pieces that are tokens themselves are encoded without merges.
+    #[must_use]
+    pub fn merges(&self, piece: &[u8]) -> Vec<(Range<usize>, Range<usize>)> {
+        self.bpe.trace_merges(piece)
+    }
+}
+
+impl Tekkenizer {
+    /// Returns the built-in BPE engine of this tokenizer.
+    ///
+    /// The view always refers to the built-in engine, even when another
+    /// [`BpeBackend`](crate::backend::BpeBackend) is installed.
+    #[must_use]
+    pub fn raw_bpe(&self) -> RawBpe<'_> {
+        RawBpe { bpe: self.bpe() }
+    }
+}
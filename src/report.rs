@@ -0,0 +1,177 @@
+//! Non-fatal issues found while loading a tokenizer file.
+//!
+//! Loading tolerates a number of irregularities: unknown configuration keys are
+//! kept but unused, missing special tokens are filled in with `<SPECIAL_n>`
+//! placeholders, and vocabulary entries beyond `vocab_size` are dropped. A
+//! [`LoadReport`] lists each of them so they can be logged instead of passing
+//! unnoticed.
+
+use std::fmt;
+
+use crate::audio::AudioConfig;
+use crate::config::{TekkenConfig, TokenizerVersion};
+
+/// A non-fatal issue found while loading a tokenizer file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadWarning {
+    /// A configuration key this crate does not know; it is kept but unused.
+    UnknownField {
+        /// Section holding the key, `"config"` or `"audio"`.
+        section: &'static str,
+        /// Name of the key.
+        field: String,
+    },
+    /// The `version` field was missing or unknown, so the version was inferred.
+    VersionInferred {
+        /// The `version` field as written in the file, empty if missing.
+        declared: String,
+        /// The version inferred from the special tokens.
+        inferred: TokenizerVersion,
+    },
+    /// The file has no `special_tokens` list, so the version's defaults are used.
+    DefaultSpecialTokens {
+        /// Version whose defaults are used.
+        version: TokenizerVersion,
+    },
+    /// Special-token ranks without a definition were filled with `<SPECIAL_n>`
+    /// placeholders.
+    PlaceholderSpecialTokens {
+        /// Number of filled-in ranks.
+        count: usize,
+    },
+    /// Vocabulary entries beyond `vocab_size` were dropped.
+    VocabTrimmed {
+        /// Number of vocabulary entries in the file.
+        available: usize,
+        /// Number of vocabulary entries kept.
+        kept: usize,
+    },
+    /// A lenient load repaired the vocabulary, see
+    /// [`crate::tekkenizer::Tekkenizer::from_file_lenient`].
+    Repaired(String),
+}
+
+impl fmt::Display for LoadWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownField { section, field } => {
+                write!(
+                    f,
+                    "Unknown field `{field}` in the {section} section is ignored"
+                )
+            }
+            Self::VersionInferred { declared, inferred } if declared.is_empty() => {
+                write!(f, "No version given, inferred {}", inferred.as_str())
+            }
+            Self::VersionInferred { declared, inferred } => write!(
+                f,
+                "Unknown version {declared:?}, inferred {}",
+                inferred.as_str()
+            ),
+            Self::DefaultSpecialTokens { version } => write!(
+                f,
+                "No special tokens given, using the {} defaults",
+                version.as_str()
+            ),
+            Self::PlaceholderSpecialTokens { count } => write!(
+                f,
+                "Filled {count} undefined special-token ranks with <SPECIAL_n> placeholders"
+            ),
+            Self::VocabTrimmed { available, kept } => write!(
+                f,
+                "Dropped {} of {available} vocabulary entries beyond vocab_size",
+                available - kept
+            ),
+            Self::Repaired(repair) => f.write_str(repair),
+        }
+    }
+}
+
+/// Non-fatal issues found while loading a tokenizer file, in the order they
+/// were found.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LoadReport {
+    /// The issues found.
+    pub warnings: Vec<LoadWarning>,
+}
+
+impl LoadReport {
+    /// Returns `true` if no issues were found.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Records unknown keys and vocabulary entries that `vocab_size` leaves out.
+    pub(crate) fn inspect_sections(
+        &mut self,
+        num_vocab_entries: usize,
+        config: &TekkenConfig,
+        audio: Option<&AudioConfig>,
+    ) {
+        let config_fields = config.extra.keys().map(|field| ("config", field));
+        let audio_fields = audio
+            .into_iter()
+            .flat_map(|audio| audio.extra.keys().map(|field| ("audio", field)));
+        for (section, field) in config_fields.chain(audio_fields) {
+            self.warnings.push(LoadWarning::UnknownField {
+                section,
+                field: field.clone(),
+            });
+        }
+
+        let kept = config
+            .default_vocab_size
+            .saturating_sub(config.default_num_special_tokens);
+        if num_vocab_entries > kept {
+            self.warnings.push(LoadWarning::VocabTrimmed {
+                available: num_vocab_entries,
+                kept,
+            });
+        }
+    }
+
+    /// Records an inferred version, default special tokens and filled-in
+    /// placeholder ranks.
+    ///
+    /// `num_defined` is the number of special tokens given in the file, or
+    /// `None` if the file has no special-token list.
+    pub(crate) fn inspect_special_tokens(
+        &mut self,
+        declared_version: &str,
+        version: &TokenizerVersion,
+        num_defined: Option<usize>,
+        num_special_tokens: usize,
+    ) {
+        if TokenizerVersion::from_string(declared_version).is_none() {
+            self.warnings.push(LoadWarning::VersionInferred {
+                declared: declared_version.to_string(),
+                inferred: version.clone(),
+            });
+        }
+
+        let num_defined = num_defined.unwrap_or_else(|| {
+            self.warnings.push(LoadWarning::DefaultSpecialTokens {
+                version: version.clone(),
+            });
+            version.default_special_tokens().len()
+        });
+        if num_special_tokens > num_defined {
+            self.warnings.push(LoadWarning::PlaceholderSpecialTokens {
+                count: num_special_tokens - num_defined,
+            });
+        }
+    }
+}
+
+impl fmt::Display for LoadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, warning) in self.warnings.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{warning}")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,1098 @@
+//! Tokenization of whole chat completion requests.
+//!
+//! [`ChatCompletionRequest`] deserializes the body of a chat completion request
+//! as sent to the Mistral or `OpenAI` APIs, and [`Tekkenizer::encode_request`]
+//! validates and renders it the way `mistral-common`'s request tokenizer does:
+//! system prompts, available tools, text, image and audio chunks, assistant tool
+//! calls, tool results and `continue_final_message`. The layout per tokenizer
+//! version is described in [`crate::prompt`].
+//!
+//! Images and audio must be embedded as base64 data; remote URLs are rejected
+//! rather than fetched. In the other direction, [`Tekkenizer::parse_tool_calls`]
+//! extracts the tool calls of generated tokens.
+//!
+//! [`ValidationMode`] selects how strictly the conversation structure is
+//! checked: role order, pairing of tool calls with tool results and the role of
+//! the last message. Violations are reported as [`RequestViolation`]s. Valid
+//! requests are then normalized by [`normalize_messages`], which merges
+//! consecutive messages of the same role and drops empty ones.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tekken::request::ChatCompletionRequest;
+//! use tekken::tekkenizer::Tekkenizer;
+//!
+//! let tokenizer = Tekkenizer::from_file("tekken.json")?;
+//! let request: ChatCompletionRequest = serde_json::from_str(
+//!     r#"{"messages": [{"role": "user", "content": "What is the capital of France?"}]}"#,
+//! )?;
+//! let tokenized = tokenizer.encode_request(request)?;
+//! println!("{} tokens: {}", tokenized.tokens.len(), tokenized.text_debug);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+#[cfg(feature = "audio")]
+use crate::audio::{Audio, AudioEncoding};
+use crate::config::TokenizerVersion;
+use crate::errors::{Result, TokenizerError};
+#[cfg(feature = "image")]
+use crate::image::{Image, ImageEncoding};
+use crate::prompt::PromptBuilder;
+use crate::special_tokens::{SpecialTokenPolicy, SpecialTokens};
+use crate::tekkenizer::Tekkenizer;
+
+/// A chat completion request.
+///
+/// Fields of the API request that do not affect tokenization, such as `model`
+/// or `temperature`, are ignored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatCompletionRequest {
+    /// The conversation, in order.
+    pub messages: Vec<RequestMessage>,
+    /// Tools the model may call.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<Tool>>,
+    /// Leave the final assistant message open for the model to continue.
+    #[serde(default)]
+    pub continue_final_message: bool,
+}
+
+/// A message of a [`ChatCompletionRequest`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "lowercase")]
+pub enum RequestMessage {
+    /// A system prompt.
+    System {
+        /// The system prompt text.
+        content: MessageContent,
+    },
+    /// A user message, possibly with images and audio.
+    User {
+        /// The user text and media.
+        content: MessageContent,
+    },
+    /// An assistant reply.
+    Assistant {
+        /// The reply text.
+        #[serde(default)]
+        content: Option<MessageContent>,
+        /// Tools called by the reply.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tool_calls: Option<Vec<ToolCall>>,
+        /// Whether the reply is the beginning of an answer for the model to
+        /// continue; only allowed on the last message.
+        #[serde(default)]
+        prefix: bool,
+    },
+    /// The result of a tool call.
+    Tool {
+        /// ID of the tool call this result answers.
+        #[serde(default, alias = "tool_call_id")]
+        call_id: Option<String>,
+        /// The tool output.
+        content: MessageContent,
+        /// Name of the called tool.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+    },
+}
+
+/// Message content: plain text or a list of typed chunks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    /// Plain text.
+    Text(String),
+    /// Text, image and audio chunks, in order.
+    Chunks(Vec<ContentChunk>),
+}
+
+/// A typed piece of message content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentChunk {
+    /// Text.
+    Text {
+        /// The text.
+        text: String,
+    },
+    /// An image given as a base64 data URL.
+    ImageUrl {
+        /// The image URL.
+        image_url: ImageUrl,
+    },
+    /// An image given as base64 data or a data URL.
+    Image {
+        /// The encoded image.
+        image: String,
+    },
+    /// Audio given as base64 data.
+    InputAudio {
+        /// The encoded audio.
+        input_audio: InputAudio,
+    },
+}
+
+/// The URL of an image chunk, either a bare string or an object with `url`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ImageUrl {
+    /// A bare URL.
+    Url(String),
+    /// A URL object.
+    Object {
+        /// The URL.
+        url: String,
+        /// Requested level of detail, ignored by the tokenizer.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+}
+
+impl ImageUrl {
+    /// Returns the URL.
+    #[must_use]
+    pub fn url(&self) -> &str {
+        match self {
+            Self::Url(url) | Self::Object { url, .. } => url,
+        }
+    }
+}
+
+/// Base64-encoded audio of an audio chunk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputAudio {
+    /// Base64-encoded audio file, or a data URL.
+    pub data: String,
+    /// Container format; only `wav` is supported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+}
+
+/// The kind of a tool; only functions exist.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolType {
+    /// A function tool.
+    #[default]
+    Function,
+}
+
+/// A tool the model may call.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tool {
+    /// The kind of tool.
+    #[serde(rename = "type", default)]
+    pub tool_type: ToolType,
+    /// The function definition.
+    pub function: Function,
+}
+
+/// A function definition.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Function {
+    /// The function name.
+    pub name: String,
+    /// What the function does.
+    #[serde(default)]
+    pub description: String,
+    /// JSON schema of the arguments.
+    #[serde(default = "empty_object")]
+    pub parameters: Value,
+}
+
+/// A tool call made by the assistant.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// ID that tool results refer to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// The kind of tool.
+    #[serde(rename = "type", default)]
+    pub tool_type: ToolType,
+    /// The called function and its arguments.
+    pub function: FunctionCall,
+}
+
+impl ToolCall {
+    /// Returns the ID of the call.
+    ///
+    /// The `"null"` placeholder that Python clients send for calls without an ID
+    /// is treated as no ID.
+    #[must_use]
+    pub fn call_id(&self) -> Option<&str> {
+        self.id.as_deref().filter(|id| *id != "null")
+    }
+}
+
+/// Length of the tool call IDs Mistral models are trained on.
+pub const CALL_ID_LENGTH: usize = 9;
+
+/// Returns whether `id` is a valid tool call ID: [`CALL_ID_LENGTH`] ASCII
+/// letters or digits.
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::request::is_valid_call_id;
+///
+/// assert!(is_valid_call_id("abc123XYZ"));
+/// assert!(!is_valid_call_id("call_0"));
+/// ```
+#[must_use]
+pub fn is_valid_call_id(id: &str) -> bool {
+    id.len() == CALL_ID_LENGTH && id.bytes().all(|byte| byte.is_ascii_alphanumeric())
+}
+
+/// A function called by the assistant.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionCall {
+    /// The function name.
+    pub name: String,
+    /// The arguments, as a JSON object or a string holding one.
+    #[serde(default = "empty_object")]
+    pub arguments: Value,
+}
+
+impl FunctionCall {
+    /// Returns the arguments as JSON, parsing them if they are a JSON string.
+    ///
+    /// Strings that are not valid JSON are returned unchanged.
+    #[must_use]
+    pub fn parsed_arguments(&self) -> Value {
+        match &self.arguments {
+            Value::String(text) => {
+                serde_json::from_str(text).unwrap_or_else(|_| self.arguments.clone())
+            }
+            arguments => arguments.clone(),
+        }
+    }
+}
+
+fn empty_object() -> Value {
+    Value::Object(serde_json::Map::new())
+}
+
+/// Role of a [`RequestMessage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// A system prompt.
+    System,
+    /// A user message.
+    User,
+    /// An assistant reply.
+    Assistant,
+    /// A tool result.
+    Tool,
+}
+
+impl Role {
+    /// Returns the lowercase name of the role.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::User => "user",
+            Self::Assistant => "assistant",
+            Self::Tool => "tool",
+        }
+    }
+
+    /// Roles allowed to follow a message with this role.
+    const fn allowed_next(self) -> &'static [Self] {
+        match self {
+            Self::System => &[Self::User, Self::Assistant, Self::System],
+            Self::User => &[Self::Assistant, Self::System, Self::User],
+            Self::Assistant => &[Self::Assistant, Self::User, Self::Tool],
+            Self::Tool => &[Self::Assistant, Self::Tool],
+        }
+    }
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How strictly [`Tekkenizer::encode_request_with_mode`] checks the structure of
+/// a conversation.
+///
+/// The strict modes follow `mistral-common`'s `ValidationMode`:
+///
+/// * Roles must follow each other in an order the model was trained on, e.g.
+///   tool results only after an assistant message or another tool result
+/// * Assistant messages must have content or tool calls
+/// * Tool call IDs must be nine ASCII letters or digits (see
+///   [`is_valid_call_id`]); the `"null"` placeholder counts as no ID
+/// * Each tool call must be answered by one tool result before the next user
+///   or assistant message
+/// * The last message must be a user message, tool result or assistant prefix
+///   when serving, and an assistant message when finetuning. An assistant
+///   message that is finetuned on may end with unanswered tool calls.
+///
+/// [`ValidationMode::Lenient`] only rejects requests that cannot be rendered at
+/// all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValidationMode {
+    /// Render any conversation the prompt layout can express.
+    #[default]
+    Lenient,
+    /// Check a request for generating the next assistant message.
+    Serving,
+    /// Check a conversation used as a finetuning sample.
+    Finetuning,
+}
+
+impl ValidationMode {
+    /// Returns the lowercase name of the mode.
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lenient => "lenient",
+            Self::Serving => "serving",
+            Self::Finetuning => "finetuning",
+        }
+    }
+
+    /// Describes the roles accepted for the last message.
+    const fn expected_last(self) -> &'static str {
+        match self {
+            Self::Lenient => "any role",
+            Self::Serving => "user, tool or an assistant prefix",
+            Self::Finetuning => "assistant",
+        }
+    }
+}
+
+impl fmt::Display for ValidationMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A structural problem of a request, reported by
+/// [`TokenizerError::InvalidRequest`].
+///
+/// Message indices are zero-based positions in
+/// [`ChatCompletionRequest::messages`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum RequestViolation {
+    /// A message's role may not follow the previous message's role.
+    #[error("Unexpected role '{role}' after role '{previous}' at message {index}")]
+    UnexpectedRole {
+        /// Index of the offending message.
+        index: usize,
+        /// Role of the previous message.
+        previous: Role,
+        /// Role of the offending message.
+        role: Role,
+    },
+    /// An assistant message has neither content nor tool calls.
+    #[error("Assistant message {index} must have content or tool calls")]
+    EmptyAssistantMessage {
+        /// Index of the assistant message.
+        index: usize,
+    },
+    /// A tool result follows no unanswered tool call.
+    #[error("Tool message {index} does not answer any tool call")]
+    UnexpectedToolResult {
+        /// Index of the tool message.
+        index: usize,
+    },
+    /// Tool calls of an assistant message are not all followed by tool results.
+    #[error("{missing} tool call(s) of message {index} have no tool result")]
+    UnansweredToolCalls {
+        /// Index of the assistant message making the calls.
+        index: usize,
+        /// Number of calls without a result.
+        missing: usize,
+    },
+    /// A tool call or tool result has an ID that is not nine ASCII letters or
+    /// digits.
+    #[error(
+        "Tool call ID '{id}' of message {index} must be {CALL_ID_LENGTH} ASCII letters or digits"
+    )]
+    InvalidCallId {
+        /// Index of the assistant or tool message, or of the call when parsing
+        /// generated tool calls.
+        index: usize,
+        /// The invalid ID.
+        id: String,
+    },
+    /// The last message has a role the validation mode does not accept.
+    #[error("Last message has role '{role}', expected {} in {mode} mode", mode.expected_last())]
+    UnexpectedLastMessage {
+        /// The validation mode.
+        mode: ValidationMode,
+        /// Role of the last message.
+        role: Role,
+    },
+    /// The request has no messages left after normalization.
+    #[error("A request needs at least one message")]
+    NoMessages,
+    /// `continue_final_message` is set but the last message is not from the
+    /// assistant.
+    #[error("continue_final_message requires the last message to be from the assistant")]
+    NoFinalAssistantMessage,
+    /// A message other than a user message has image or audio content.
+    #[error("Only user messages may contain images or audio")]
+    MediaOutsideUserMessage,
+}
+
+impl RequestViolation {
+    /// Returns the index of the message the violation points at.
+    #[must_use]
+    pub const fn index(&self) -> Option<usize> {
+        match self {
+            Self::UnexpectedRole { index, .. }
+            | Self::EmptyAssistantMessage { index }
+            | Self::UnexpectedToolResult { index }
+            | Self::UnansweredToolCalls { index, .. }
+            | Self::InvalidCallId { index, .. } => Some(*index),
+            Self::UnexpectedLastMessage { .. }
+            | Self::NoMessages
+            | Self::NoFinalAssistantMessage
+            | Self::MediaOutsideUserMessage => None,
+        }
+    }
+}
+
+impl RequestMessage {
+    /// Returns the role of the message.
+    #[must_use]
+    pub const fn role(&self) -> Role {
+        match self {
+            Self::System { .. } => Role::System,
+            Self::User { .. } => Role::User,
+            Self::Assistant { .. } => Role::Assistant,
+            Self::Tool { .. } => Role::Tool,
+        }
+    }
+}
+
+impl ChatCompletionRequest {
+    /// Checks the structure of the conversation.
+    ///
+    /// [`ValidationMode::Lenient`] accepts every request; the checks of the
+    /// other modes are described on [`ValidationMode`]. Content that cannot be
+    /// rendered, such as images in a system prompt, is only detected when
+    /// encoding.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenizerError::InvalidRequest`] with the first violation found.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tekken::request::{ChatCompletionRequest, RequestViolation, ValidationMode};
+    /// use tekken::TokenizerError;
+    ///
+    /// let request: ChatCompletionRequest = serde_json::from_str(
+    ///     r#"{"messages": [{"role": "user", "content": "Hi"}, {"role": "assistant", "content": "Hello"}]}"#,
+    /// )?;
+    /// assert!(request.validate(ValidationMode::Finetuning).is_ok());
+    /// assert!(matches!(
+    ///     request.validate(ValidationMode::Serving),
+    ///     Err(TokenizerError::InvalidRequest(RequestViolation::UnexpectedLastMessage { .. }))
+    /// ));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn validate(&self, mode: ValidationMode) -> Result<()> {
+        if mode == ValidationMode::Lenient {
+            return Ok(());
+        }
+
+        let mut previous: Option<Role> = None;
+        // Assistant message whose tool calls still await results, and how many.
+        let mut pending: Option<(usize, usize)> = None;
+        for (index, message) in self.messages.iter().enumerate() {
+            let role = message.role();
+            if let Some(previous) = previous
+                && !previous.allowed_next().contains(&role)
+            {
+                return Err(RequestViolation::UnexpectedRole {
+                    index,
+                    previous,
+                    role,
+                }
+                .into());
+            }
+            previous = Some(role);
+
+            match message {
+                RequestMessage::System { .. } => {}
+                RequestMessage::User { .. } => check_answered(pending.take())?,
+                RequestMessage::Assistant {
+                    content,
+                    tool_calls,
+                    ..
+                } => {
+                    for call in tool_calls.iter().flatten() {
+                        check_call_id(index, call.call_id())?;
+                    }
+                    let calls = tool_calls.as_ref().map_or(0, Vec::len);
+                    if calls == 0 && content.as_ref().is_none_or(MessageContent::is_empty) {
+                        return Err(RequestViolation::EmptyAssistantMessage { index }.into());
+                    }
+                    check_answered(pending.take())?;
+                    pending = (calls > 0).then_some((index, calls));
+                }
+                RequestMessage::Tool { call_id, .. } => {
+                    check_call_id(index, call_id.as_deref().filter(|id| *id != "null"))?;
+                    match pending {
+                        Some((_, 1)) => pending = None,
+                        Some((call_index, missing)) => pending = Some((call_index, missing - 1)),
+                        None => return Err(RequestViolation::UnexpectedToolResult { index }.into()),
+                    }
+                }
+            }
+        }
+
+        let Some(last) = self.messages.last() else {
+            return Ok(());
+        };
+        let last_ok = match (mode, last) {
+            (ValidationMode::Finetuning, RequestMessage::Assistant { .. }) => true,
+            (ValidationMode::Finetuning, _) => false,
+            (_, RequestMessage::Assistant { prefix, .. }) => *prefix || self.continue_final_message,
+            _ => true,
+        };
+        if !last_ok {
+            return Err(RequestViolation::UnexpectedLastMessage {
+                mode,
+                role: last.role(),
+            }
+            .into());
+        }
+        // The tool calls of a finetuned last message are the training target.
+        if mode == ValidationMode::Serving {
+            check_answered(pending)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fails if a tool call ID of the message at `index` is malformed.
+fn check_call_id(index: usize, id: Option<&str>) -> Result<()> {
+    match id {
+        Some(id) if !is_valid_call_id(id) => Err(RequestViolation::InvalidCallId {
+            index,
+            id: id.to_string(),
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Fails if an assistant message's tool calls are still awaiting results.
+fn check_answered(pending: Option<(usize, usize)>) -> Result<()> {
+    match pending {
+        Some((index, missing)) => {
+            Err(RequestViolation::UnansweredToolCalls { index, missing }.into())
+        }
+        None => Ok(()),
+    }
+}
+
+/// Tokens of an encoded request together with its media.
+///
+/// # Fields
+///
+/// * `tokens` - The token IDs to feed to the model
+/// * `text_debug` - The prompt with special tokens spelled out, for debugging
+/// * `audios` - Encodings of the audio chunks, in order
+/// * `images` - Encodings of the image chunks, in order
+#[derive(Debug, Clone)]
+pub struct TokenizedRequest {
+    pub tokens: Vec<u32>,
+    pub text_debug: String,
+    #[cfg(feature = "audio")]
+    pub audios: Vec<AudioEncoding>,
+    #[cfg(feature = "image")]
+    pub images: Vec<ImageEncoding>,
+}
+
+impl MessageContent {
+    /// Whether the content has no text and no media.
+    fn is_empty(&self) -> bool {
+        match self {
+            Self::Text(text) => text.is_empty(),
+            Self::Chunks(chunks) => chunks
+                .iter()
+                .all(|chunk| matches!(chunk, ContentChunk::Text { text } if text.is_empty())),
+        }
+    }
+
+    /// Returns the text of the content, joining text chunks with blank lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RequestViolation::MediaOutsideUserMessage`] if the content holds
+    /// image or audio chunks.
+    pub fn to_text(&self) -> Result<String> {
+        match self {
+            Self::Text(text) => Ok(text.clone()),
+            Self::Chunks(chunks) => chunks
+                .iter()
+                .map(|chunk| match chunk {
+                    ContentChunk::Text { text } => Ok(text.as_str()),
+                    _ => Err(RequestViolation::MediaOutsideUserMessage.into()),
+                })
+                .collect::<Result<Vec<_>>>()
+                .map(|texts| texts.join("\n\n")),
+        }
+    }
+
+    /// Appends `other`, separating text with a blank line.
+    fn merge(self, other: Self) -> Self {
+        let into_chunks = |content: Self| match content {
+            Self::Text(text) => vec![ContentChunk::Text { text }],
+            Self::Chunks(chunks) => chunks,
+        };
+        match (self, other) {
+            (Self::Text(text), Self::Text(other)) => Self::Text(format!("{text}\n\n{other}")),
+            (content, other) => {
+                let mut chunks = into_chunks(content);
+                for chunk in into_chunks(other) {
+                    match (chunks.last_mut(), chunk) {
+                        (Some(ContentChunk::Text { text }), ContentChunk::Text { text: other }) => {
+                            text.push_str("\n\n");
+                            text.push_str(&other);
+                        }
+                        (_, chunk) => chunks.push(chunk),
+                    }
+                }
+                Self::Chunks(chunks)
+            }
+        }
+    }
+}
+
+/// Normalizes a conversation the way `mistral-common` does before encoding.
+///
+/// The rules are:
+///
+/// * System and user messages without content, and assistant messages without
+///   content or tool calls that are not a prefix, are dropped.
+/// * Consecutive user messages are merged into one, joining text with blank
+///   lines and keeping media chunks in order.
+/// * Consecutive assistant messages are merged into one, joining their content
+///   with blank lines and concatenating their tool calls. The merged message is
+///   a prefix if any of them was.
+/// * With [`TokenizerVersion::V3`], all system prompts are merged into a single
+///   system message at the start of the conversation, since that version renders
+///   one system prompt only. Later versions keep system prompts in place and
+///   merge consecutive ones.
+///
+/// Tool results are never merged.
+///
+/// # Arguments
+///
+/// * `messages` - The conversation, in order
+/// * `version` - Version of the tokenizer the conversation will be encoded with
+///
+/// # Returns
+///
+/// The normalized conversation.
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::config::TokenizerVersion;
+/// use tekken::request::{MessageContent, RequestMessage, normalize_messages};
+///
+/// let user = |text: &str| RequestMessage::User { content: MessageContent::Text(text.to_string()) };
+/// let normalized = normalize_messages(vec![user("Hi"), user(""), user("Who are you?")], &TokenizerVersion::V7);
+/// assert_eq!(normalized, vec![user("Hi\n\nWho are you?")]);
+/// ```
+#[must_use]
+pub fn normalize_messages(
+    messages: Vec<RequestMessage>,
+    version: &TokenizerVersion,
+) -> Vec<RequestMessage> {
+    let hoist_system = *version == TokenizerVersion::V3;
+    let mut system: Option<MessageContent> = None;
+    let mut normalized: Vec<RequestMessage> = Vec::with_capacity(messages.len());
+    for message in messages {
+        match message {
+            RequestMessage::System { content } | RequestMessage::User { content }
+                if content.is_empty() => {}
+            RequestMessage::Assistant {
+                content,
+                tool_calls,
+                prefix: false,
+            } if content.as_ref().is_none_or(MessageContent::is_empty)
+                && tool_calls.as_ref().is_none_or(Vec::is_empty) => {}
+            RequestMessage::System { content } if hoist_system => {
+                system = Some(match system {
+                    Some(previous) => previous.merge(content),
+                    None => content,
+                });
+            }
+            message => match (normalized.last_mut(), message) {
+                (
+                    Some(RequestMessage::System { content: previous }),
+                    RequestMessage::System { content },
+                )
+                | (
+                    Some(RequestMessage::User { content: previous }),
+                    RequestMessage::User { content },
+                ) => {
+                    *previous = std::mem::replace(previous, MessageContent::Chunks(Vec::new()))
+                        .merge(content);
+                }
+                (
+                    Some(RequestMessage::Assistant {
+                        content: previous_content,
+                        tool_calls: previous_calls,
+                        prefix: previous_prefix,
+                    }),
+                    RequestMessage::Assistant {
+                        content,
+                        tool_calls,
+                        prefix,
+                    },
+                ) => {
+                    *previous_content = match (previous_content.take(), content) {
+                        (Some(previous), Some(content)) => Some(previous.merge(content)),
+                        (previous, content) => previous.or(content),
+                    };
+                    if let Some(tool_calls) = tool_calls {
+                        previous_calls.get_or_insert_default().extend(tool_calls);
+                    }
+                    *previous_prefix |= prefix;
+                }
+                (_, message) => normalized.push(message),
+            },
+        }
+    }
+    if let Some(content) = system {
+        normalized.insert(0, RequestMessage::System { content });
+    }
+    normalized
+}
+
+/// Adds the chunks of a user message to the prompt.
+fn push_user_content(
+    builder: PromptBuilder<'_>,
+    content: MessageContent,
+) -> Result<PromptBuilder<'_>> {
+    let chunks = match content {
+        MessageContent::Text(text) => return Ok(builder.user(text)),
+        MessageContent::Chunks(chunks) => chunks,
+    };
+    chunks
+        .into_iter()
+        .try_fold(builder, |builder, chunk| match chunk {
+            ContentChunk::Text { text } => Ok(builder.user(text)),
+            ContentChunk::ImageUrl { image_url } => push_image(builder, image_url.url(), true),
+            ContentChunk::Image { image } => push_image(builder, &image, false),
+            ContentChunk::InputAudio { input_audio } => push_audio(builder, &input_audio),
+        })
+}
+
+#[cfg(feature = "image")]
+fn push_image<'a>(
+    builder: PromptBuilder<'a>,
+    data: &str,
+    is_url: bool,
+) -> Result<PromptBuilder<'a>> {
+    if is_url && !data.starts_with("data:") {
+        return Err(TokenizerError::UnsupportedFormat(
+            "Image URLs must be base64 data URLs; download remote images first".to_string(),
+        ));
+    }
+    Ok(builder.image(Image::from_base64(data)?))
+}
+
+#[cfg(not(feature = "image"))]
+fn push_image<'a>(_: PromptBuilder<'a>, _: &str, _: bool) -> Result<PromptBuilder<'a>> {
+    Err(TokenizerError::Image(
+        "Image chunks require the `image` feature".to_string(),
+    ))
+}
+
+#[cfg(feature = "audio")]
+fn push_audio<'a>(builder: PromptBuilder<'a>, audio: &InputAudio) -> Result<PromptBuilder<'a>> {
+    if let Some(format) = &audio.format
+        && !format.eq_ignore_ascii_case("wav")
+    {
+        return Err(TokenizerError::UnsupportedFormat(format!(
+            "Audio format {format:?} is not supported; expected wav"
+        )));
+    }
+    Ok(builder.audio(Audio::from_base64(&audio.data)?))
+}
+
+#[cfg(not(feature = "audio"))]
+fn push_audio<'a>(_: PromptBuilder<'a>, _: &InputAudio) -> Result<PromptBuilder<'a>> {
+    Err(TokenizerError::Audio(
+        "Audio chunks require the `audio` feature".to_string(),
+    ))
+}
+
+impl Tekkenizer {
+    /// Validates and encodes a complete chat completion request.
+    ///
+    /// Only checks that the request can be rendered; use
+    /// [`Tekkenizer::encode_request_with_mode`] to enforce the conversation
+    /// structure the model expects. The messages are normalized with
+    /// [`normalize_messages`] before encoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to encode
+    ///
+    /// # Returns
+    ///
+    /// The prompt tokens, starting with BOS, their rendering and the encodings
+    /// of the request's images and audio.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The request has no messages left after [`normalize_messages`]
+    /// - `continue_final_message` is set but the last message is not from the
+    ///   assistant, or an assistant prefix is not the last message
+    /// - System, assistant or tool messages contain images or audio
+    /// - Images or audio cannot be decoded, or the tokenizer does not support them
+    /// - Any error of [`PromptBuilder::build`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::request::{ChatCompletionRequest, RequestMessage, MessageContent};
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let request = ChatCompletionRequest {
+    ///     messages: vec![
+    ///         RequestMessage::User { content: MessageContent::Text("Hi!".to_string()) },
+    ///         RequestMessage::Assistant {
+    ///             content: Some(MessageContent::Text("Hello".to_string())),
+    ///             tool_calls: None,
+    ///             prefix: false,
+    ///         },
+    ///     ],
+    ///     tools: None,
+    ///     continue_final_message: true,
+    /// };
+    /// let tokenized = tokenizer.encode_request(request)?;
+    /// assert!(tokenized.text_debug.ends_with("Hello"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_request(&self, request: ChatCompletionRequest) -> Result<TokenizedRequest> {
+        self.encode_request_with_mode(request, ValidationMode::Lenient)
+    }
+
+    /// Validates a chat completion request with the given strictness and
+    /// encodes it.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to encode
+    /// * `mode` - How strictly to check the conversation structure
+    ///
+    /// # Returns
+    ///
+    /// The same as [`Tekkenizer::encode_request`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TokenizerError::InvalidRequest`] if the request violates the
+    /// rules of `mode` (see [`ChatCompletionRequest::validate`]), and otherwise
+    /// any error of [`Tekkenizer::encode_request`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::request::{ChatCompletionRequest, ValidationMode};
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let sample: ChatCompletionRequest = serde_json::from_str(
+    ///     r#"{"messages": [{"role": "user", "content": "Hi"}, {"role": "assistant", "content": "Hello"}]}"#,
+    /// )?;
+    /// let tokenized = tokenizer.encode_request_with_mode(sample, ValidationMode::Finetuning)?;
+    /// assert!(tokenized.text_debug.ends_with("Hello</s>"));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_request_with_mode(
+        &self,
+        request: ChatCompletionRequest,
+        mode: ValidationMode,
+    ) -> Result<TokenizedRequest> {
+        request.validate(mode)?;
+        let ChatCompletionRequest {
+            mut messages,
+            tools,
+            continue_final_message,
+        } = request;
+        if continue_final_message {
+            match messages.last_mut() {
+                Some(RequestMessage::Assistant { prefix, .. }) => *prefix = true,
+                _ => return Err(RequestViolation::NoFinalAssistantMessage.into()),
+            }
+        }
+        let messages = normalize_messages(messages, self.version());
+        if messages.is_empty() {
+            return Err(RequestViolation::NoMessages.into());
+        }
+
+        let mut builder = self.prompt_builder().tools(tools.unwrap_or_default());
+        for message in messages {
+            builder = match message {
+                RequestMessage::System { content } => builder.system(content.to_text()?),
+                RequestMessage::User { content } => push_user_content(builder, content)?,
+                RequestMessage::Assistant {
+                    content,
+                    tool_calls,
+                    prefix,
+                } => builder.assistant_part(
+                    content
+                        .map(|content| content.to_text())
+                        .transpose()?
+                        .unwrap_or_default(),
+                    tool_calls.unwrap_or_default(),
+                    prefix,
+                ),
+                RequestMessage::Tool {
+                    call_id, content, ..
+                } => builder.tool_results_part(call_id, content.to_text()?),
+            };
+        }
+
+        let rendered = builder.render()?;
+        Ok(TokenizedRequest {
+            tokens: rendered.encoding.tokens,
+            text_debug: rendered.encoding.rendered,
+            #[cfg(feature = "audio")]
+            audios: rendered.audios,
+            #[cfg(feature = "image")]
+            images: rendered.images,
+        })
+    }
+
+    /// Parses the tool calls in generated tokens.
+    ///
+    /// Tokens before the first `[TOOL_CALLS]` are the assistant's text and are
+    /// ignored, as is everything from the first EOS on. V3 and V7 models write a
+    /// JSON list of calls after a single `[TOOL_CALLS]`; V11 and V13 models write
+    /// each call as `[TOOL_CALLS]name[CALL_ID]id[ARGS]arguments`, with the
+    /// `[CALL_ID]` part optional.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - Tokens generated for an assistant message
+    ///
+    /// # Returns
+    ///
+    /// The tool calls in order, or an empty list if the tokens contain none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A call lacks `[ARGS]` ([`TokenizerError::MalformedToolCall`])
+    /// - A call's name, ID or arguments contain special tokens
+    /// - The arguments or the V3/V7 call list are not valid JSON
+    /// - A call ID is not nine ASCII letters or digits
+    ///   ([`RequestViolation::InvalidCallId`], indexed by call)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// # let generated: Vec<u32> = Vec::new();
+    /// for call in tokenizer.parse_tool_calls(&generated)? {
+    ///     println!("{}({})", call.function.name, call.function.arguments);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn parse_tool_calls(&self, tokens: &[u32]) -> Result<Vec<ToolCall>> {
+        /// A call in the JSON list written by V3 and V7 models.
+        #[derive(Deserialize)]
+        struct ListedCall {
+            name: String,
+            #[serde(default = "empty_object")]
+            arguments: Value,
+            #[serde(default)]
+            id: Option<String>,
+        }
+
+        let tool_calls = self.get_control_token(SpecialTokens::ToolCalls.as_str())?;
+        let eos = self.eos_id().ok();
+        let end = tokens
+            .iter()
+            .position(|&token| Some(token) == eos)
+            .unwrap_or(tokens.len());
+        let Some(start) = tokens[..end].iter().position(|&token| token == tool_calls) else {
+            return Ok(Vec::new());
+        };
+        let calls = &tokens[start + 1..end];
+        let text = |tokens: &[u32]| self.decode(tokens, SpecialTokenPolicy::Raise);
+        let tool_call = |index: usize, name: String, id: Option<String>, arguments: Value| {
+            let id = id.filter(|id| id != "null");
+            if let Some(id) = &id
+                && !is_valid_call_id(id)
+            {
+                return Err(RequestViolation::InvalidCallId {
+                    index,
+                    id: id.clone(),
+                }
+                .into());
+            }
+            Ok(ToolCall {
+                id,
+                tool_type: ToolType::Function,
+                function: FunctionCall { name, arguments },
+            })
+        };
+
+        match self.version() {
+            TokenizerVersion::V3 | TokenizerVersion::V7 => {
+                serde_json::from_str::<Vec<ListedCall>>(&text(calls)?)?
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, call)| tool_call(index, call.name, call.id, call.arguments))
+                    .collect()
+            }
+            TokenizerVersion::V11 | TokenizerVersion::V13 => {
+                let args = self.get_control_token(SpecialTokens::Args.as_str())?;
+                let call_id = self.get_control_token(SpecialTokens::CallId.as_str()).ok();
+                calls
+                    .split(|&token| token == tool_calls)
+                    .enumerate()
+                    .map(|(index, call)| {
+                        let Some(args_at) = call.iter().position(|&token| token == args) else {
+                            return Err(TokenizerError::MalformedToolCall(format!(
+                                "Tool call {index} has no [ARGS]"
+                            )));
+                        };
+                        let (head, arguments) = (&call[..args_at], &call[args_at + 1..]);
+                        let (name, id) = match call_id
+                            .and_then(|call_id| head.iter().position(|&token| token == call_id))
+                        {
+                            Some(id_at) => (&head[..id_at], Some(text(&head[id_at + 1..])?)),
+                            None => (head, None),
+                        };
+                        let arguments = serde_json::from_str(&text(arguments)?)?;
+                        tool_call(index, text(name)?, id, arguments)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
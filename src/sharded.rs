@@ -0,0 +1,149 @@
+//! Loading and writing tokenizers whose vocabulary is split across files.
+//!
+//! A sharded layout consists of an index file ([`ShardedIndex`]) holding the
+//! configuration and special tokens, and one or more shard files
+//! ([`VocabShard`]) holding slices of the vocabulary. Shards are merged by rank
+//! at load time, so their order and the ranks they contain are arbitrary as
+//! long as together they form a contiguous vocabulary. Shards may be gzip or
+//! zstd compressed when the matching features are enabled. The index and each
+//! shard are verified against their checksum sidecars, when present (see
+//! [`crate::integrity`]).
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::config::{RawVocabShard, ShardedIndex, VocabShard};
+use crate::errors::{Result, TokenizerError};
+use crate::integrity::verify_sidecar;
+use crate::tekkenizer::{Tekkenizer, decompress};
+
+impl Tekkenizer {
+    /// Loads a tokenizer from a sharded index file.
+    ///
+    /// # Arguments
+    ///
+    /// * `index_path` - Path to the index file; shard paths are resolved relative to it
+    ///
+    /// # Returns
+    ///
+    /// A new `Tekkenizer` instance with the merged vocabulary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The index or a shard cannot be read or parsed
+    /// - The index or a shard does not match its checksum sidecar
+    /// - Two shards define the same rank
+    /// - The merged vocabulary is invalid (e.g. ranks are not contiguous)
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_sharded("tekken.index.json")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_sharded<P: AsRef<Path>>(index_path: P) -> Result<Self> {
+        let index_path = index_path.as_ref();
+        let index: ShardedIndex = serde_json::from_slice(&read_maybe_compressed(index_path)?)?;
+        let base_dir = index_path.parent().unwrap_or_else(|| Path::new(""));
+
+        let buffers = index
+            .vocab_shards
+            .iter()
+            .map(|shard| read_maybe_compressed(&base_dir.join(shard)))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut vocab = Vec::new();
+        for (buffer, shard) in buffers.iter().zip(&index.vocab_shards) {
+            let parsed: RawVocabShard = serde_json::from_slice(buffer).map_err(|e| {
+                TokenizerError::InvalidConfig(format!("Failed to parse vocab shard {shard}: {e}"))
+            })?;
+            vocab.extend(parsed.vocab);
+        }
+
+        vocab.sort_unstable_by_key(|token| token.rank);
+        if let Some(pair) = vocab.windows(2).find(|pair| pair[0].rank == pair[1].rank) {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Vocabulary rank {} is defined by more than one shard",
+                pair[0].rank
+            )));
+        }
+
+        Self::from_config_parts(
+            vocab
+                .iter()
+                .map(|token| (token.rank, token.token_bytes.as_ref())),
+            index.special_tokens,
+            index.config,
+            index.audio,
+            index.image,
+        )
+    }
+
+    /// Writes the tokenizer as a sharded index plus vocabulary shard files.
+    ///
+    /// Shards are written next to the index as
+    /// `<index stem>-vocab-<i>-of-<n>.json`.
+    ///
+    /// # Arguments
+    ///
+    /// * `index_path` - Path of the index file to create
+    /// * `tokens_per_shard` - Maximum number of vocabulary tokens per shard
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `tokens_per_shard` is zero or a file cannot be written.
+    pub fn save_sharded<P: AsRef<Path>>(
+        &self,
+        index_path: P,
+        tokens_per_shard: usize,
+    ) -> Result<()> {
+        if tokens_per_shard == 0 {
+            return Err(TokenizerError::InvalidConfig(
+                "tokens_per_shard must be > 0".to_string(),
+            ));
+        }
+
+        let index_path = index_path.as_ref();
+        let base_dir = index_path.parent().unwrap_or_else(|| Path::new(""));
+        let stem = index_path
+            .file_stem()
+            .map_or(Cow::Borrowed("tekken"), |stem| stem.to_string_lossy());
+        let stem = stem.strip_suffix(".index").unwrap_or(&stem);
+
+        let model_data = self.to_model_data();
+        let num_shards = model_data.vocab.len().div_ceil(tokens_per_shard);
+        let mut vocab_shards = Vec::with_capacity(num_shards);
+
+        for (i, chunk) in model_data.vocab.chunks(tokens_per_shard).enumerate() {
+            let name = format!("{stem}-vocab-{:05}-of-{num_shards:05}.json", i + 1);
+            let writer = std::io::BufWriter::new(std::fs::File::create(base_dir.join(&name))?);
+            serde_json::to_writer(
+                writer,
+                &VocabShard {
+                    vocab: chunk.to_vec(),
+                },
+            )?;
+            vocab_shards.push(name);
+        }
+
+        let index = ShardedIndex {
+            vocab_shards,
+            special_tokens: model_data.special_tokens,
+            config: model_data.config,
+            audio: model_data.audio,
+            image: model_data.image,
+        };
+        let writer = std::io::BufWriter::new(std::fs::File::create(index_path)?);
+        serde_json::to_writer_pretty(writer, &index)?;
+        Ok(())
+    }
+}
+
+fn read_maybe_compressed(path: &Path) -> Result<Vec<u8>> {
+    let content = std::fs::read(path)?;
+    verify_sidecar(path, &content)?;
+    Ok(decompress(&content)?.unwrap_or(content))
+}
@@ -1,5 +1,11 @@
+use alloc::format;
+use alloc::string::String;
+use core::str::FromStr;
+
 use serde::{Deserialize, Serialize};
 
+use crate::errors::{Result, TokenizerError};
+
 /// Enumeration of special tokens used in the Tekken tokenizer.
 ///
 /// Special tokens are used to mark different types of content and control sequences
@@ -15,7 +21,8 @@ use serde::{Deserialize, Serialize};
 /// - **Audio tokens**: Audio, `BeginAudio`, Transcribe for audio content
 /// - **Code tokens**: Prefix, Middle, Suffix for code completion
 /// - **System tokens**: `BeginSystem`, `EndSystem` for system prompts
-#[derive(Debug, Clone, PartialEq)]
+/// - **Reasoning tokens**: `Think`, `EndThink` around model reasoning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SpecialTokens {
     Unk,
     Bos,
@@ -42,9 +49,59 @@ pub enum SpecialTokens {
     Transcribe,
     Args,
     CallId,
+    Think,
+    EndThink,
 }
 
 impl SpecialTokens {
+    /// Number of special tokens known to this crate.
+    pub(crate) const COUNT: usize = Self::all().len();
+
+    /// Returns every special token, in declaration order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tekken::special_tokens::SpecialTokens;
+    ///
+    /// assert!(SpecialTokens::all().contains(&SpecialTokens::BeginInst));
+    /// for token in SpecialTokens::all() {
+    ///     assert_eq!(token.as_str().parse::<SpecialTokens>().ok(), Some(*token));
+    /// }
+    /// ```
+    #[must_use]
+    pub const fn all() -> &'static [Self] {
+        &[
+            Self::Unk,
+            Self::Bos,
+            Self::Eos,
+            Self::BeginInst,
+            Self::EndInst,
+            Self::BeginTools,
+            Self::EndTools,
+            Self::BeginToolResults,
+            Self::EndToolResults,
+            Self::ToolCalls,
+            Self::Img,
+            Self::Pad,
+            Self::ImgBreak,
+            Self::ImgEnd,
+            Self::Prefix,
+            Self::Middle,
+            Self::Suffix,
+            Self::BeginSystem,
+            Self::EndSystem,
+            Self::BeginToolContent,
+            Self::Audio,
+            Self::BeginAudio,
+            Self::Transcribe,
+            Self::Args,
+            Self::CallId,
+            Self::Think,
+            Self::EndThink,
+        ]
+    }
+
     /// Returns the string representation of the special token.
     ///
     /// Each special token has a corresponding string representation that is used
@@ -92,10 +149,130 @@ impl SpecialTokens {
             Self::Transcribe => "[TRANSCRIBE]",
             Self::Args => "[ARGS]",
             Self::CallId => "[CALL_ID]",
+            Self::Think => "[THINK]",
+            Self::EndThink => "[/THINK]",
         }
     }
 }
 
+impl core::fmt::Display for SpecialTokens {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SpecialTokens {
+    type Err = TokenizerError;
+
+    /// Parses the string form of a special token, e.g. `"[INST]"`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TokenNotFound` if the string is not a known special token.
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_token_str(s)
+            .ok_or_else(|| TokenizerError::TokenNotFound(format!("Unknown special token: '{s}'")))
+    }
+}
+
+impl SpecialTokens {
+    /// Parses the string form of a special token with a single `match`, the
+    /// inverse of [`SpecialTokens::as_str`].
+    fn from_token_str(s: &str) -> Option<Self> {
+        Some(match s {
+            "<unk>" => Self::Unk,
+            "<s>" => Self::Bos,
+            "</s>" => Self::Eos,
+            "[INST]" => Self::BeginInst,
+            "[/INST]" => Self::EndInst,
+            "[AVAILABLE_TOOLS]" => Self::BeginTools,
+            "[/AVAILABLE_TOOLS]" => Self::EndTools,
+            "[TOOL_RESULTS]" => Self::BeginToolResults,
+            "[/TOOL_RESULTS]" => Self::EndToolResults,
+            "[TOOL_CALLS]" => Self::ToolCalls,
+            "[IMG]" => Self::Img,
+            "<pad>" => Self::Pad,
+            "[IMG_BREAK]" => Self::ImgBreak,
+            "[IMG_END]" => Self::ImgEnd,
+            "[PREFIX]" => Self::Prefix,
+            "[MIDDLE]" => Self::Middle,
+            "[SUFFIX]" => Self::Suffix,
+            "[SYSTEM_PROMPT]" => Self::BeginSystem,
+            "[/SYSTEM_PROMPT]" => Self::EndSystem,
+            "[TOOL_CONTENT]" => Self::BeginToolContent,
+            "[AUDIO]" => Self::Audio,
+            "[BEGIN_AUDIO]" => Self::BeginAudio,
+            "[TRANSCRIBE]" => Self::Transcribe,
+            "[ARGS]" => Self::Args,
+            "[CALL_ID]" => Self::CallId,
+            "[THINK]" => Self::Think,
+            "[/THINK]" => Self::EndThink,
+            _ => return None,
+        })
+    }
+}
+
+/// Special tokens that are not [`SpecialTokens`], keyed by string.
+#[cfg(feature = "std")]
+type OtherTokens = rustc_hash::FxHashMap<String, usize>;
+/// Special tokens that are not [`SpecialTokens`], keyed by string.
+#[cfg(not(feature = "std"))]
+type OtherTokens =
+    hashbrown::HashMap<String, usize, core::hash::BuildHasherDefault<rustc_hash::FxHasher>>;
+
+/// Lookup from special-token strings to their index in the special tokens.
+///
+/// Looking up control tokens is on the hot path of prompt encoding, so the
+/// tokens known to this crate are resolved without hashing: a `match` on the
+/// string gives the [`SpecialTokens`] variant, which indexes a table. Only the
+/// remaining tokens, such as the `<SPECIAL_N>` placeholders, go to a hash map.
+#[derive(Debug, Clone)]
+pub(crate) struct SpecialTokenIndex {
+    // Index of each known token, by variant
+    known: [Option<usize>; SpecialTokens::COUNT],
+    others: OtherTokens,
+}
+
+impl SpecialTokenIndex {
+    /// Indexes special-token strings; the last index given for a string wins.
+    pub(crate) fn new<'a>(tokens: impl IntoIterator<Item = (&'a str, usize)>) -> Self {
+        let mut index = Self {
+            known: [None; SpecialTokens::COUNT],
+            others: OtherTokens::default(),
+        };
+        for (token_str, rank) in tokens {
+            match SpecialTokens::from_token_str(token_str) {
+                Some(token) => index.known[token as usize] = Some(rank),
+                None => {
+                    index.others.insert(String::from(token_str), rank);
+                }
+            }
+        }
+        index
+    }
+
+    /// Returns the index of a special token by its string form.
+    #[cfg(feature = "std")]
+    pub(crate) fn get(&self, token_str: &str) -> Option<usize> {
+        match SpecialTokens::from_token_str(token_str) {
+            Some(token) => self.rank_of(token),
+            None => self.others.get(token_str).copied(),
+        }
+    }
+
+    /// Returns the index of a known special token.
+    pub(crate) fn rank_of(&self, token: SpecialTokens) -> Option<usize> {
+        self.known[token as usize]
+    }
+
+    /// Approximate heap memory held by the index, in bytes.
+    #[cfg(feature = "std")]
+    pub(crate) fn heap_size(&self) -> usize {
+        self.others.keys().map(String::capacity).sum::<usize>()
+            + self.others.capacity() * (size_of::<String>() + size_of::<usize>())
+    }
+}
+
 /// Policy for handling special tokens during decoding.
 ///
 /// This enum defines how special tokens should be treated when converting
@@ -125,7 +302,8 @@ impl SpecialTokens {
 ///
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SpecialTokenPolicy {
     /// Skip special tokens during decoding, excluding them from the output.
     Ignore,
@@ -0,0 +1,289 @@
+//! Token-aware chunking of long documents.
+//!
+//! [`TextSplitter`] cuts text into chunks of at most a given number of tokens,
+//! preferring paragraph boundaries, then sentence boundaries, then word
+//! boundaries, and only splitting inside a word when a single word is too long.
+//! Token counts are exact, so chunks can be fed to a model or embedding endpoint
+//! without character-based guesswork.
+
+use std::ops::Range;
+
+use crate::errors::{Result, TokenizerError};
+use crate::tekkenizer::Tekkenizer;
+
+/// A chunk of text produced by [`TextSplitter::split`].
+///
+/// # Fields
+///
+/// * `text` - The chunk text, borrowed from the input
+/// * `byte_range` - Position of the chunk in the input
+/// * `token_count` - Number of tokens in the chunk (without BOS/EOS)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextChunk<'t> {
+    /// The chunk text, borrowed from the input.
+    pub text: &'t str,
+    /// Position of the chunk in the input, in bytes.
+    pub byte_range: Range<usize>,
+    /// Number of tokens in the chunk, without BOS/EOS.
+    pub token_count: usize,
+}
+
+/// Boundaries tried in order when a span is too long for one chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Paragraph,
+    Sentence,
+    Word,
+    Token,
+}
+
+/// Splits text into chunks of at most `max_tokens` tokens.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::splitter::TextSplitter;
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let splitter = TextSplitter::new(&tokenizer, 512)?.with_overlap(64)?;
+///
+/// for chunk in splitter.split("A long document...")? {
+///     println!("{} tokens: {}", chunk.token_count, chunk.text);
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Copy)]
+pub struct TextSplitter<'a> {
+    tokenizer: &'a Tekkenizer,
+    max_tokens: usize,
+    overlap: usize,
+}
+
+impl<'a> TextSplitter<'a> {
+    /// Creates a splitter producing chunks of at most `max_tokens` tokens.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_tokens` is zero.
+    pub fn new(tokenizer: &'a Tekkenizer, max_tokens: usize) -> Result<Self> {
+        if max_tokens == 0 {
+            return Err(TokenizerError::InvalidConfig(
+                "max_tokens must be > 0".to_string(),
+            ));
+        }
+        Ok(Self {
+            tokenizer,
+            max_tokens,
+            overlap: 0,
+        })
+    }
+
+    /// Repeats up to `overlap` tokens of trailing context at the start of each chunk.
+    ///
+    /// Overlap is taken in whole sentences (or words, inside over-long sentences),
+    /// so the actual overlap may be smaller than requested.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `overlap` is not smaller than `max_tokens`.
+    pub fn with_overlap(mut self, overlap: usize) -> Result<Self> {
+        if overlap >= self.max_tokens {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "overlap ({overlap}) must be < max_tokens ({})",
+                self.max_tokens
+            )));
+        }
+        self.overlap = overlap;
+        Ok(self)
+    }
+
+    /// Splits text into chunks.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to split
+    ///
+    /// # Returns
+    ///
+    /// The chunks in document order. Without overlap they cover the input exactly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails.
+    pub fn split<'t>(&self, text: &'t str) -> Result<Vec<TextChunk<'t>>> {
+        let mut pieces = Vec::new();
+        self.split_span(text, 0..text.len(), Level::Paragraph, &mut pieces)?;
+
+        let mut chunks: Vec<TextChunk<'t>> = Vec::new();
+        let mut start_piece = 0;
+        while start_piece < pieces.len() {
+            // Grow the chunk one piece at a time while it still fits
+            let mut end_piece = start_piece + 1;
+            let mut token_count = self.count(&text[pieces[start_piece].clone()])?;
+            while end_piece < pieces.len() {
+                let span = pieces[start_piece].start..pieces[end_piece].end;
+                let candidate = self.count(&text[span])?;
+                if candidate > self.max_tokens {
+                    break;
+                }
+                token_count = candidate;
+                end_piece += 1;
+            }
+
+            let byte_range = pieces[start_piece].start..pieces[end_piece - 1].end;
+            chunks.push(TextChunk {
+                text: &text[byte_range.clone()],
+                byte_range,
+                token_count,
+            });
+
+            start_piece = self.overlap_start(text, &pieces, start_piece, end_piece)?;
+        }
+
+        Ok(chunks)
+    }
+
+    /// Picks the first piece of the next chunk, backing up into the previous chunk for overlap.
+    fn overlap_start(
+        &self,
+        text: &str,
+        pieces: &[Range<usize>],
+        start_piece: usize,
+        end_piece: usize,
+    ) -> Result<usize> {
+        if self.overlap == 0 || end_piece == pieces.len() {
+            return Ok(end_piece);
+        }
+
+        let mut next = end_piece;
+        while next > start_piece + 1 {
+            let overlap = self.count(&text[pieces[next - 1].start..pieces[end_piece - 1].end])?;
+            // Keep room for at least the next new piece
+            let with_next = self.count(&text[pieces[next - 1].start..pieces[end_piece].end])?;
+            if overlap > self.overlap || with_next > self.max_tokens {
+                break;
+            }
+            next -= 1;
+        }
+        Ok(next)
+    }
+
+    /// Breaks a span into pieces that each fit in one chunk.
+    fn split_span(
+        &self,
+        text: &str,
+        span: Range<usize>,
+        level: Level,
+        pieces: &mut Vec<Range<usize>>,
+    ) -> Result<()> {
+        if span.is_empty() {
+            return Ok(());
+        }
+        // Overlap is taken in whole pieces, so paragraphs are always broken into sentences
+        let fits_whole = self.overlap == 0 || matches!(level, Level::Word | Level::Token);
+        if fits_whole && self.count(&text[span.clone()])? <= self.max_tokens {
+            pieces.push(span);
+            return Ok(());
+        }
+
+        let next_level = match level {
+            Level::Paragraph => Level::Sentence,
+            Level::Sentence => Level::Word,
+            Level::Word | Level::Token => Level::Token,
+        };
+        if level == Level::Token {
+            return self.split_tokens(text, span, pieces);
+        }
+
+        let parts = boundaries(&text[span.clone()], level);
+        if parts.len() <= 1 {
+            return self.split_span(text, span, next_level, pieces);
+        }
+        for part in parts {
+            let part = span.start + part.start..span.start + part.end;
+            self.split_span(text, part, next_level, pieces)?;
+        }
+        Ok(())
+    }
+
+    /// Splits a single over-long word at token boundaries that fall on characters.
+    fn split_tokens(
+        &self,
+        text: &str,
+        mut span: Range<usize>,
+        pieces: &mut Vec<Range<usize>>,
+    ) -> Result<()> {
+        while !span.is_empty() {
+            let tokens = self.tokenizer.encode(&text[span.clone()], false, false)?;
+            let mut ends = Vec::with_capacity(tokens.len());
+            let mut offset = span.start;
+            for &token in &tokens {
+                offset += self.tokenizer.regular_token_bytes(token)?.len();
+                ends.push(offset);
+            }
+
+            let mut cut = None;
+            for &end in ends.iter().take(self.max_tokens).rev() {
+                if text.is_char_boundary(end)
+                    && self.count(&text[span.start..end])? <= self.max_tokens
+                {
+                    cut = Some(end);
+                    break;
+                }
+            }
+            // Always make progress, even if one character exceeds the budget
+            let cut = cut.unwrap_or_else(|| {
+                span.start + text[span.clone()].chars().next().map_or(0, char::len_utf8)
+            });
+
+            pieces.push(span.start..cut);
+            span.start = cut;
+        }
+        Ok(())
+    }
+
+    fn count(&self, text: &str) -> Result<usize> {
+        Ok(self.tokenizer.encode(text, false, false)?.len())
+    }
+}
+
+/// Splits text after each boundary of the given level, keeping separators with
+/// the preceding piece so the pieces concatenate back to the input.
+fn boundaries(text: &str, level: Level) -> Vec<Range<usize>> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, ch)) = chars.next() {
+        let at_boundary = match level {
+            Level::Paragraph => ch == '\n' && chars.peek().is_some_and(|&(_, next)| next == '\n'),
+            Level::Sentence => {
+                matches!(ch, '.' | '!' | '?' | '。' | '！' | '？')
+                    && chars.peek().is_none_or(|&(_, next)| next.is_whitespace())
+            }
+            Level::Word => ch.is_whitespace(),
+            Level::Token => false,
+        };
+        if !at_boundary {
+            continue;
+        }
+
+        // Absorb the whitespace run that follows the boundary
+        let mut end = i + ch.len_utf8();
+        while let Some(&(j, next)) = chars.peek() {
+            if !next.is_whitespace() {
+                break;
+            }
+            end = j + next.len_utf8();
+            chars.next();
+        }
+        parts.push(start..end);
+        start = end;
+    }
+
+    if start < text.len() {
+        parts.push(start..text.len());
+    }
+    parts
+}
@@ -0,0 +1,199 @@
+//! Incremental stop-sequence detection over generated token streams.
+//!
+//! A stop sequence such as `"\n\nUser:"` can be produced by many different token
+//! splits, including byte-fallback tokens that cut a character in half. The matcher
+//! therefore works on the raw token bytes, so every boundary-crossing form of a stop
+//! sequence is detected without enumerating its tokenizations.
+
+use std::collections::VecDeque;
+
+use crate::errors::{Result, TokenizerError};
+use crate::tekkenizer::Tekkenizer;
+
+/// A stop sequence found in a token stream.
+///
+/// # Fields
+///
+/// * `sequence_index` - Index of the matched stop sequence in the matcher's list
+/// * `trim_tokens` - Number of trailing tokens that contain part of the match
+/// * `trim_bytes` - Number of trailing bytes from the start of the match to the end of the stream
+///
+/// The first trimmed token may start before the match; its leading bytes are the
+/// difference between the trimmed tokens' total length and `trim_bytes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StopMatch {
+    /// Index of the matched stop sequence.
+    pub sequence_index: usize,
+    /// Number of trailing tokens that contain part of the match.
+    pub trim_tokens: usize,
+    /// Number of trailing bytes from the start of the match to the end of the stream.
+    pub trim_bytes: usize,
+}
+
+/// Incrementally checks generated tokens against a set of stop sequences.
+///
+/// Special tokens never take part in a match; a stop sequence cannot span one.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::stop_sequences::StopSequenceMatcher;
+/// use tekken::tekkenizer::Tekkenizer;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let mut matcher = StopSequenceMatcher::new(&tokenizer, &["\n\nUser:"])?;
+///
+/// for token_id in tokenizer.encode("Sure!\n\nUser: hi", false, false)? {
+///     if let Some(stop) = matcher.push(token_id)? {
+///         println!("stop; trim {} tokens", stop.trim_tokens);
+///         break;
+///     }
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct StopSequenceMatcher<'a> {
+    tokenizer: &'a Tekkenizer,
+    stop_sequences: Vec<Vec<u8>>,
+    max_len: usize,
+    // Tail of the generated bytes; `buffer[0]` sits at absolute offset `buffer_start`
+    buffer: Vec<u8>,
+    buffer_start: usize,
+    total_bytes: usize,
+    // Absolute end offsets of the tokens that overlap `buffer`
+    token_ends: VecDeque<usize>,
+}
+
+impl<'a> StopSequenceMatcher<'a> {
+    /// Creates a matcher for the given stop sequences.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer that produces the token stream
+    /// * `stop_sequences` - The stop sequences to detect
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no stop sequences are given or any of them is empty.
+    pub fn new<S: AsRef<str>>(tokenizer: &'a Tekkenizer, stop_sequences: &[S]) -> Result<Self> {
+        if stop_sequences.is_empty() {
+            return Err(TokenizerError::InvalidConfig(
+                "At least one stop sequence is required".to_string(),
+            ));
+        }
+
+        let stop_sequences: Vec<Vec<u8>> = stop_sequences
+            .iter()
+            .map(|sequence| sequence.as_ref().as_bytes().to_vec())
+            .collect();
+        if stop_sequences.iter().any(Vec::is_empty) {
+            return Err(TokenizerError::InvalidConfig(
+                "Stop sequences must not be empty".to_string(),
+            ));
+        }
+        let max_len = stop_sequences.iter().map(Vec::len).max().unwrap_or(0);
+
+        Ok(Self {
+            tokenizer,
+            stop_sequences,
+            max_len,
+            buffer: Vec::with_capacity(max_len * 2),
+            buffer_start: 0,
+            total_bytes: 0,
+            token_ends: VecDeque::new(),
+        })
+    }
+
+    /// Feeds the next generated token and checks for a completed stop sequence.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_id` - The generated token ID
+    ///
+    /// # Returns
+    ///
+    /// The earliest-ending stop sequence completed by this token, if any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token ID is out of vocabulary range.
+    pub fn push(&mut self, token_id: u32) -> Result<Option<StopMatch>> {
+        if self.tokenizer.is_special_token(token_id) {
+            // Validate the ID, then break any partial match
+            self.tokenizer.special_token(token_id)?;
+            self.buffer_start = self.total_bytes;
+            self.buffer.clear();
+            self.token_ends.clear();
+            return Ok(None);
+        }
+
+        let bytes = self.tokenizer.regular_token_bytes(token_id)?;
+        let previous_len = self.buffer.len();
+        self.buffer.extend_from_slice(bytes);
+        self.total_bytes += bytes.len();
+        self.token_ends.push_back(self.total_bytes);
+
+        let stop = self.find_match(previous_len);
+
+        // Keep only the bytes that can still begin a future match
+        let keep = self.buffer.len().min(self.max_len - 1);
+        let drop = self.buffer.len() - keep;
+        self.buffer.drain(..drop);
+        self.buffer_start += drop;
+        while self
+            .token_ends
+            .front()
+            .is_some_and(|&end| end <= self.buffer_start)
+        {
+            self.token_ends.pop_front();
+        }
+
+        Ok(stop)
+    }
+
+    /// Returns how many trailing bytes may be the start of a stop sequence.
+    ///
+    /// Streaming servers should hold these bytes back until more tokens arrive.
+    #[must_use]
+    pub fn held_bytes(&self) -> usize {
+        (1..=self.buffer.len())
+            .rev()
+            .find(|&len| {
+                let suffix = &self.buffer[self.buffer.len() - len..];
+                self.stop_sequences
+                    .iter()
+                    .any(|sequence| sequence.len() > len && sequence.starts_with(suffix))
+            })
+            .unwrap_or(0)
+    }
+
+    /// Clears all buffered state so the matcher can be reused for a new stream.
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.buffer_start = 0;
+        self.total_bytes = 0;
+        self.token_ends.clear();
+    }
+
+    fn find_match(&self, previous_len: usize) -> Option<StopMatch> {
+        for end in previous_len + 1..=self.buffer.len() {
+            for (sequence_index, sequence) in self.stop_sequences.iter().enumerate() {
+                if end < sequence.len() || &self.buffer[end - sequence.len()..end] != sequence {
+                    continue;
+                }
+                let match_start = self.buffer_start + end - sequence.len();
+                let trim_tokens = self
+                    .token_ends
+                    .iter()
+                    .rev()
+                    .take_while(|&&token_end| token_end > match_start)
+                    .count();
+                return Some(StopMatch {
+                    sequence_index,
+                    trim_tokens,
+                    trim_bytes: self.total_bytes - match_start,
+                });
+            }
+        }
+        None
+    }
+}
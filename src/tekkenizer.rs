@@ -1,13 +1,201 @@
 use base64::{Engine as _, engine::general_purpose};
 use rustc_hash::FxHashMap;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ops::Range;
 use std::path::Path;
-use tiktoken_rs::CoreBPE;
+use std::sync::OnceLock;
 
-use crate::audio::{Audio, AudioConfig, AudioEncoder, AudioEncoding};
-use crate::config::{ModelData, TokenInfo, TokenizerVersion};
-use crate::errors::{Result, TokenizerError};
-use crate::special_tokens::{SpecialTokenInfo, SpecialTokenPolicy, SpecialTokens};
+#[cfg(feature = "audio")]
+use crate::audio::{Audio, AudioEncoding, AudioSample, StreamingAudioEncoder};
+use crate::audio::{AudioConfig, AudioEncoder};
+use crate::backend::BpeBackend;
+use crate::bpe::{BytePairEncoder, SplitMix64};
+use crate::config::{
+    ImageConfig, ModelData, RawModelData, TekkenConfig, TokenInfo, TokenizerVersion,
+};
+use crate::errors::{LoadStage, Result, TokenizerError};
+use crate::image::ImageEncoder;
+#[cfg(feature = "image")]
+use crate::image::{Image, ImageEncoding};
+use crate::integrity::verify_sidecar;
+use crate::report::{LoadReport, LoadWarning};
+use crate::special_tokens::{
+    SpecialTokenIndex, SpecialTokenInfo, SpecialTokenPolicy, SpecialTokens,
+};
+use crate::token_id::{TokenId, convert_ids};
+
+/// Upper bound on the number of special tokens, so a malformed file cannot make
+/// the loader allocate billions of placeholder special tokens.
+const MAX_NUM_SPECIAL_TOKENS: usize = 1 << 20;
+
+/// Options controlling how text is encoded into token IDs.
+///
+/// # Fields
+///
+/// * `add_bos` - Whether to add a Beginning of Sequence token at the start
+/// * `add_eos` - Whether to add an End of Sequence token at the end
+/// * `dropout` - Optional BPE-dropout for data augmentation
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::tekkenizer::{BpeDropout, EncodeOptions};
+///
+/// let options = EncodeOptions::new(true, false);
+/// assert!(options.add_bos);
+/// assert!(!options.add_eos);
+///
+/// let augmented = options.with_dropout(BpeDropout::new(0.1, 42));
+/// assert_eq!(augmented.dropout.map(|d| d.seed), Some(42));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EncodeOptions {
+    /// Whether to add a Beginning of Sequence token at the start.
+    pub add_bos: bool,
+    /// Whether to add an End of Sequence token at the end.
+    pub add_eos: bool,
+    /// Optional BPE-dropout for data augmentation.
+    pub dropout: Option<BpeDropout>,
+}
+
+impl EncodeOptions {
+    /// Creates a new `EncodeOptions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `add_beginning_of_sequence` - Whether to prepend the BOS token
+    /// * `add_end_of_sequence` - Whether to append the EOS token
+    #[must_use]
+    pub fn new(add_beginning_of_sequence: bool, add_end_of_sequence: bool) -> Self {
+        Self {
+            add_bos: add_beginning_of_sequence,
+            add_eos: add_end_of_sequence,
+            dropout: None,
+        }
+    }
+
+    /// Enables BPE-dropout.
+    #[must_use]
+    pub fn with_dropout(mut self, dropout: BpeDropout) -> Self {
+        self.dropout = Some(dropout);
+        self
+    }
+}
+
+/// BPE-dropout settings (Provilkov et al., 2020).
+///
+/// At every merge step, each candidate merge is skipped with probability
+/// `probability`, producing alternative segmentations of the same text. The
+/// same seed and text always give the same tokens. A probability of `0.0`
+/// reproduces regular encoding.
+///
+/// Dropout always runs on the built-in BPE engine, even if a different
+/// [`BpeBackend`] is installed.
+///
+/// # Fields
+///
+/// * `probability` - Probability of skipping each candidate merge, in `[0, 1]`
+/// * `seed` - Seed for the random number generator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BpeDropout {
+    /// Probability of skipping each candidate merge, in `[0, 1]`.
+    pub probability: f64,
+    /// Seed for the random number generator.
+    pub seed: u64,
+}
+
+impl BpeDropout {
+    /// Creates BPE-dropout settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `probability` - Probability of skipping each candidate merge, in `[0, 1]`
+    /// * `seed` - Seed for the random number generator
+    #[must_use]
+    pub fn new(probability: f64, seed: u64) -> Self {
+        Self { probability, seed }
+    }
+}
+
+/// Policy for handling token IDs that are not part of the vocabulary during decoding.
+///
+/// Model outputs occasionally contain IDs `>= vocab_size` (sampling bugs, speculative
+/// drafts). This policy lets callers choose between failing and degrading gracefully.
+///
+/// # Variants
+///
+/// - `Error`: Fail with `TokenizerError::TokenOutOfRange`
+/// - `Skip`: Drop invalid tokens from the output
+/// - `Replace`: Emit the given character in place of each invalid token
+///
+/// With `Skip` and `Replace`, the bytes of a character left incomplete by the
+/// token before an invalid one are decoded as U+FFFD, so they never join with
+/// the bytes after it.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::special_tokens::SpecialTokenPolicy;
+/// use tekken::tekkenizer::{InvalidTokenPolicy, Tekkenizer};
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// let text = tokenizer.decode_with_invalid_policy(
+///     &[22177, u32::MAX],
+///     SpecialTokenPolicy::Ignore,
+///     InvalidTokenPolicy::Replace('\u{FFFD}'),
+/// )?;
+/// assert_eq!(text, "Hello\u{FFFD}");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InvalidTokenPolicy {
+    /// Fail with `TokenizerError::TokenOutOfRange`.
+    #[default]
+    Error,
+    /// Drop invalid tokens from the output.
+    Skip,
+    /// Emit the given character in place of each invalid token.
+    Replace(char),
+}
+
+/// How [`Tekkenizer::from_file_lenient_with`] repairs missing vocabulary ranks.
+///
+/// # Variants
+///
+/// - `Shift`: Close gaps by shifting later ranks down, so token IDs after a gap
+///   differ from the ones in the file
+/// - `Placeholder`: Fill gaps with placeholder tokens that encoding never
+///   produces and that decode as U+FFFD, so every token ID of the file is kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RankGapPolicy {
+    /// Shift later ranks down.
+    #[default]
+    Shift,
+    /// Fill gaps with placeholder tokens.
+    Placeholder,
+}
+
+/// How token text is compared with a query string in [`Tekkenizer::token_ids_for`].
+///
+/// # Variants
+///
+/// - `Exact`: Token text equals the query
+/// - `StartsWith`: Token text starts with the query
+/// - `Contains`: Token text contains the query
+/// - `Word`: Token text equals the query, with or without a single leading space
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// Token text equals the query.
+    Exact,
+    /// Token text starts with the query.
+    StartsWith,
+    /// Token text contains the query.
+    Contains,
+    /// Token text equals the query, with or without a single leading space.
+    Word,
+}
 
 /// A Tekken tokenizer that supports both text and audio tokenization.
 ///
@@ -32,15 +220,70 @@ use crate::special_tokens::{SpecialTokenInfo, SpecialTokenPolicy, SpecialTokens}
 /// # Ok::<(), Box<dyn std::error::Error>>(())
 /// ```
 pub struct Tekkenizer {
-    tekkenizer: CoreBPE,
+    bpe: BytePairEncoder,
+    // Replacement encoding engine; `None` uses `bpe`
+    backend: Option<Box<dyn BpeBackend>>,
+    vocab_size: usize,
+    num_special_tokens: usize,
+    // ID of rank 0, one past the last special token
+    rank_offset: TokenId,
+    version: TokenizerVersion,
+    pattern: String,
+    special_tokens: Vec<SpecialTokenInfo>,
+    special_token_index: SpecialTokenIndex,
+    vocab: OnceLock<Vec<String>>,
+    // Ranks sorted by token bytes, for prefix searches
+    ranks_by_bytes: OnceLock<Vec<u32>>,
+    fingerprint: OnceLock<[u8; 32]>,
+    audio_config: Option<AudioConfig>,
+    audio_encoder: Option<AudioEncoder>,
+    image_config: Option<ImageConfig>,
+    image_encoder: Option<ImageEncoder>,
+}
+
+/// Checked tokenizer sections, ready to be built into a [`Tekkenizer`].
+struct ValidatedParts {
+    mergeable_ranks: FxHashMap<Vec<u8>, u32>,
+    placeholder_ranks: Vec<u32>,
     vocab_size: usize,
     num_special_tokens: usize,
     version: TokenizerVersion,
+    pattern: String,
     special_tokens: Vec<SpecialTokenInfo>,
-    special_tokens_map: HashMap<String, usize>,
-    vocab: Vec<String>,
+    special_token_index: SpecialTokenIndex,
     audio_config: Option<AudioConfig>,
     audio_encoder: Option<AudioEncoder>,
+    image_config: Option<ImageConfig>,
+    image_encoder: Option<ImageEncoder>,
+}
+
+impl ValidatedParts {
+    /// Compiles the pre-tokenization pattern and builds the tokenizer.
+    fn build(self) -> Result<Tekkenizer> {
+        let bpe = BytePairEncoder::with_placeholders(
+            self.mergeable_ranks,
+            &self.placeholder_ranks,
+            &self.pattern,
+        )?;
+        Ok(Tekkenizer {
+            bpe,
+            backend: None,
+            vocab_size: self.vocab_size,
+            num_special_tokens: self.num_special_tokens,
+            rank_offset: TokenId::try_from(self.num_special_tokens)?,
+            version: self.version,
+            pattern: self.pattern,
+            special_tokens: self.special_tokens,
+            special_token_index: self.special_token_index,
+            vocab: OnceLock::new(),
+            ranks_by_bytes: OnceLock::new(),
+            fingerprint: OnceLock::new(),
+            audio_config: self.audio_config,
+            audio_encoder: self.audio_encoder,
+            image_config: self.image_config,
+            image_encoder: self.image_encoder,
+        })
+    }
 }
 
 impl Tekkenizer {
@@ -63,20 +306,82 @@ impl Tekkenizer {
     /// # Errors
     ///
     /// Returns an error if:
-    /// - Vocabulary size is inconsistent with provided tokens
-    /// - Special tokens contain duplicates
+    /// - Vocabulary size is inconsistent with provided tokens, leaves no room
+    ///   for the 256 byte tokens, or there are more than 2^20 special tokens
+    /// - Special tokens repeat a string or rank, have a rank outside
+    ///   `0..num_special_tokens`, or use the `<SPECIAL_n>` placeholder name of
+    ///   another rank
     /// - Audio configuration is invalid
     /// - Core BPE creation fails
-    #[allow(clippy::cast_possible_truncation)]
+    // Takes the vocabulary by value to keep the signature of earlier releases
+    #[allow(clippy::cast_possible_truncation, clippy::needless_pass_by_value)]
     pub fn new(
         vocab: Vec<TokenInfo>,
-        special_tokens: &Vec<SpecialTokenInfo>,
-        _pattern: String,
+        special_tokens: &[SpecialTokenInfo],
+        pattern: String,
         vocab_size: usize,
         num_special_tokens: usize,
         version: TokenizerVersion,
         audio_config: Option<AudioConfig>,
     ) -> Result<Self> {
+        Self::from_vocab_entries(
+            vocab
+                .iter()
+                .map(|token| (token.rank, token.token_bytes.as_str())),
+            special_tokens,
+            pattern,
+            vocab_size,
+            num_special_tokens,
+            version,
+            audio_config,
+        )
+    }
+
+    /// Shared constructor over `(rank, token bytes)` vocabulary entries.
+    ///
+    /// Taking borrowed entries lets file loaders decode the base64 strings straight
+    /// out of the input buffer instead of materializing a `TokenInfo` per token.
+    fn from_vocab_entries<B, I>(
+        vocab: I,
+        special_tokens: &[SpecialTokenInfo],
+        pattern: String,
+        vocab_size: usize,
+        num_special_tokens: usize,
+        version: TokenizerVersion,
+        audio_config: Option<AudioConfig>,
+    ) -> Result<Self>
+    where
+        B: EntryBytes,
+        I: ExactSizeIterator<Item = (usize, B)>,
+    {
+        Self::validate_vocab_entries(
+            vocab,
+            special_tokens,
+            pattern,
+            vocab_size,
+            num_special_tokens,
+            version,
+            audio_config,
+        )?
+        .build()
+    }
+
+    /// Checks the vocabulary, special tokens and audio setup of a tokenizer
+    /// without compiling its pattern or building the encoder.
+    #[allow(clippy::cast_possible_truncation)]
+    fn validate_vocab_entries<B, I>(
+        vocab: I,
+        special_tokens: &[SpecialTokenInfo],
+        pattern: String,
+        vocab_size: usize,
+        num_special_tokens: usize,
+        version: TokenizerVersion,
+        audio_config: Option<AudioConfig>,
+    ) -> Result<ValidatedParts>
+    where
+        B: EntryBytes,
+        I: ExactSizeIterator<Item = (usize, B)>,
+    {
         if vocab_size > vocab.len() + num_special_tokens {
             return Err(TokenizerError::InvalidConfig(format!(
                 "vocab_size ({}) must be <= vocab.len() ({}) + num_special_tokens ({})",
@@ -105,95 +410,101 @@ impl Tekkenizer {
             )));
         }
 
-        // Fill missing special tokens
-        let mut all_special_tokens = special_tokens.clone();
-        for i in special_tokens.len()..num_special_tokens {
-            all_special_tokens.push(SpecialTokenInfo {
-                rank: i,
-                token_str: format!("<SPECIAL_{i}>"),
-                is_control: true,
-            });
+        if num_special_tokens > MAX_NUM_SPECIAL_TOKENS {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "num_special_tokens ({num_special_tokens}) must be <= {MAX_NUM_SPECIAL_TOKENS}"
+            )));
         }
 
-        let inner_vocab_size = vocab_size - num_special_tokens;
-        let mergeable_ranks = reload_mergeable_ranks(vocab, inner_vocab_size)?;
-
-        // Create tiktoken CoreBPE from mergeable ranks
-        let special_tokens: FxHashMap<String, u32> = FxHashMap::default();
-        let pattern = r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+(?!\S)|\s+";
+        // BPE merges start from single bytes, so all 256 byte tokens are required
+        if vocab_size < num_special_tokens + 256 {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "vocab_size ({vocab_size}) must be >= num_special_tokens ({num_special_tokens}) + 256"
+            )));
+        }
 
-        let tekkenizer = CoreBPE::new(mergeable_ranks.clone(), special_tokens, pattern)
-            .map_err(|e| TokenizerError::InvalidConfig(format!("Failed to create CoreBPE: {e}")))?;
+        // Token IDs are u32, so every vocabulary index must fit in one
+        if u32::try_from(vocab_size).is_err() {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "vocab_size ({vocab_size}) must fit in a u32 token ID"
+            )));
+        }
 
-        // Create special tokens map
-        let special_tokens_map: HashMap<String, usize> = all_special_tokens
-            .iter()
-            .map(|token| (token.token_str.clone(), token.rank))
+        // Lay out special tokens by rank, filling unassigned ranks with placeholders
+        let mut slots: Vec<Option<SpecialTokenInfo>> = vec![None; num_special_tokens];
+        for token in special_tokens {
+            let Some(slot) = slots.get_mut(token.rank) else {
+                return Err(TokenizerError::InvalidConfig(format!(
+                    "Special token {} has rank {}, must be < num_special_tokens ({num_special_tokens})",
+                    token.token_str, token.rank
+                )));
+            };
+            if let Some(existing) = slot {
+                return Err(TokenizerError::InvalidConfig(format!(
+                    "Special tokens {} and {} share rank {}",
+                    existing.token_str, token.token_str, token.rank
+                )));
+            }
+            // Placeholder names are reserved for the rank they describe
+            if let Some(placeholder_rank) = placeholder_rank(&token.token_str)
+                && placeholder_rank != token.rank
+            {
+                return Err(TokenizerError::InvalidConfig(format!(
+                    "Special token {} at rank {} collides with the placeholder name of rank {placeholder_rank}",
+                    token.token_str, token.rank
+                )));
+            }
+            *slot = Some(token.clone());
+        }
+        let all_special_tokens: Vec<SpecialTokenInfo> = slots
+            .into_iter()
+            .enumerate()
+            .map(|(i, slot)| {
+                slot.unwrap_or_else(|| SpecialTokenInfo {
+                    rank: i,
+                    token_str: format!("<SPECIAL_{i}>"),
+                    is_control: true,
+                })
+            })
             .collect();
 
-        // Create reverse lookup map for efficient vocabulary string creation
-        let rank_to_bytes: FxHashMap<u32, &Vec<u8>> = mergeable_ranks
-            .iter()
-            .map(|(bytes, &rank)| (rank, bytes))
-            .collect();
+        let inner_vocab_size = vocab_size - num_special_tokens;
+        let mergeable_ranks = reload_mergeable_ranks(vocab, inner_vocab_size)?;
 
-        // Create vocabulary
-        let vocab_strings: Vec<String> = (0..vocab_size)
-            .map(|i| {
-                if i < num_special_tokens {
-                    all_special_tokens[i].token_str.clone()
-                } else {
-                    // Get token string from tiktoken using efficient lookup
-                    #[allow(clippy::cast_possible_truncation)]
-                    let token_id = (i - num_special_tokens) as u32;
-                    match rank_to_bytes.get(&token_id) {
-                        Some(bytes) => String::from_utf8_lossy(bytes).to_string(),
-                        None => "<?>".to_string(),
-                    }
-                }
-            })
-            .collect();
+        let special_token_index = SpecialTokenIndex::new(
+            all_special_tokens
+                .iter()
+                .map(|token| (token.token_str.as_str(), token.rank)),
+        );
 
         // Set up audio encoder if audio config is provided
-        let audio_encoder = if let Some(ref config) = audio_config {
-            let audio_token_id = special_tokens_map
-                .get(SpecialTokens::Audio.as_str())
-                .ok_or_else(|| {
-                    TokenizerError::TokenNotFound("Audio token not found".to_string())
-                })?;
-            let begin_audio_token_id = special_tokens_map
-                .get(SpecialTokens::BeginAudio.as_str())
-                .ok_or_else(|| {
-                TokenizerError::TokenNotFound("BeginAudio token not found".to_string())
-            })?;
-
-            #[allow(clippy::cast_possible_truncation)]
-            Some(AudioEncoder::new(
-                config.clone(),
-                *audio_token_id as u32,
-                *begin_audio_token_id as u32,
-            ))
-        } else {
-            None
-        };
+        let audio_encoder = audio_config
+            .as_ref()
+            .map(|config| build_audio_encoder(config, &special_token_index))
+            .transpose()?;
 
-        Ok(Self {
-            tekkenizer,
+        Ok(ValidatedParts {
+            mergeable_ranks,
+            placeholder_ranks: Vec::new(),
             vocab_size,
             num_special_tokens,
             version,
+            pattern,
             special_tokens: all_special_tokens,
-            special_tokens_map,
-            vocab: vocab_strings,
+            special_token_index,
             audio_config,
             audio_encoder,
+            image_config: None,
+            image_encoder: None,
         })
     }
 
     /// Loads a tokenizer from a JSON configuration file.
     ///
     /// The file should contain tokenizer configuration including vocabulary,
-    /// special tokens, patterns, and optional audio configuration.
+    /// special tokens, patterns, and optional audio configuration. Gzip
+    /// (`.json.gz`) and zstd (`.json.zst`) compressed files are detected from
+    /// their contents and decompressed when the `gzip` / `zstd` features are enabled.
     ///
     /// # Arguments
     ///
@@ -207,9 +518,15 @@ impl Tekkenizer {
     ///
     /// Returns an error if:
     /// - File cannot be read
-    /// - JSON parsing fails
+    /// - The file does not match its checksum sidecar (see [`crate::integrity`])
+    /// - The file is compressed and the matching feature is disabled
+    /// - Decompression or JSON parsing fails
     /// - Configuration is invalid
     ///
+    /// Errors are wrapped in [`TokenizerError::Load`], which names the file and
+    /// the [`LoadStage`] that failed; [`TokenizerError::root_cause`] returns the
+    /// underlying error.
+    ///
     /// # Examples
     ///
     /// ```rust,no_run
@@ -220,599 +537,2786 @@ impl Tekkenizer {
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = std::fs::read_to_string(path)?;
-        let model_data: ModelData = serde_json::from_str(&content)?;
-
-        let version =
-            TokenizerVersion::from_string(&model_data.config.version).ok_or_else(|| {
-                TokenizerError::InvalidConfig(format!(
-                    "Unknown version: {}",
-                    model_data.config.version
-                ))
-            })?;
-
-        let special_tokens = model_data.special_tokens.unwrap_or_else(|| {
-            // Use deprecated special tokens for older versions
-            get_deprecated_special_tokens()
-        });
-
-        Self::new(
-            model_data.vocab,
-            &special_tokens,
-            model_data.config.pattern,
-            model_data.config.default_vocab_size,
-            model_data.config.default_num_special_tokens,
-            version,
-            model_data.audio,
-        )
+        Ok(Self::from_file_with_report(path)?.0)
     }
 
-    /// Returns the total vocabulary size including special tokens.
+    /// Loads a tokenizer keeping only the lowest-ranked vocabulary tokens.
+    ///
+    /// Meant for unit tests and local development: only the first
+    /// `max_vocab_tokens` ranks are decoded and indexed, which makes loading a
+    /// full `tekken.json` much faster. At least the 256 byte tokens are always
+    /// kept, so any text can still be encoded, just into more tokens than the
+    /// full vocabulary would produce. Special tokens are unaffected.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the tokenizer configuration file
+    /// * `max_vocab_tokens` - Maximum number of regular vocabulary tokens to keep
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Tekkenizer::from_file`].
     ///
     /// # Examples
     ///
     /// ```rust,no_run
-    /// # use tekken::tekkenizer::Tekkenizer;
-    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
-    /// println!("Vocabulary size: {}", tokenizer.vocab_size());
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file_with_limit("tekken.json", 1000)?;
+    /// assert_eq!(tokenizer.vocab_size(), tokenizer.num_special_tokens() + 1000);
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    #[must_use]
-    pub fn vocab_size(&self) -> usize {
-        self.vocab_size
+    pub fn from_file_with_limit<P: AsRef<Path>>(path: P, max_vocab_tokens: usize) -> Result<Self> {
+        Ok(Self::load_file(path.as_ref(), Some(max_vocab_tokens.max(256)), None)?.0)
     }
 
-    /// Returns the number of special tokens in the vocabulary.
+    /// Loads a tokenizer from the contents of a tokenizer file.
     ///
-    /// Special tokens include control tokens like BOS, EOS, and audio tokens.
-    #[must_use]
-    pub fn num_special_tokens(&self) -> usize {
-        self.num_special_tokens
-    }
-
-    /// Returns the tokenizer version.
+    /// Accepts the same formats as [`Tekkenizer::from_file`], including gzip and
+    /// zstd compressed JSON when the corresponding features are enabled.
     ///
-    /// Different versions may have different vocabulary sizes and special tokens.
-    #[must_use]
-    pub fn version(&self) -> &TokenizerVersion {
-        &self.version
-    }
-
-    /// Returns the token ID (u32) for the Beginning of Sequence (BOS) token.
+    /// # Arguments
     ///
-    /// # Errors
+    /// * `bytes` - The (possibly compressed) file contents
     ///
-    /// Returns an error if the BOS token is not found in the vocabulary.
-    pub fn bos_id(&self) -> Result<u32> {
-        self.get_control_token(SpecialTokens::Bos.as_str())
-    }
-
-    /// Returns the token ID (u32) for the End of Sequence (EOS) token.
+    /// # Returns
+    ///
+    /// A new `Tekkenizer` instance.
     ///
     /// # Errors
     ///
-    /// Returns an error if the EOS token is not found in the vocabulary.
-    pub fn eos_id(&self) -> Result<u32> {
-        self.get_control_token(SpecialTokens::Eos.as_str())
+    /// Returns an error if:
+    /// - The data is compressed and the matching feature is disabled
+    /// - Decompression or JSON parsing fails
+    /// - Configuration is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let bytes = std::fs::read("tekken.json")?;
+    /// let tokenizer = Tekkenizer::from_bytes(&bytes)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(Self::from_bytes_with_report(bytes)?.0)
     }
 
-    /// Returns the token ID (u32) for the padding (PAD) token.
+    /// Loads a tokenizer file and reports the non-fatal issues found on the way.
+    ///
+    /// Loading behaves exactly like [`Tekkenizer::from_file`]; the report lists
+    /// what was tolerated, such as unknown configuration keys, special-token
+    /// ranks filled with placeholders, or vocabulary entries beyond `vocab_size`.
     ///
     /// # Errors
     ///
-    /// Returns an error if the PAD token is not found in the vocabulary.
-    pub fn pad_id(&self) -> Result<u32> {
-        self.get_control_token(SpecialTokens::Pad.as_str())
+    /// Returns the same errors as [`Tekkenizer::from_file`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let (tokenizer, report) = Tekkenizer::from_file_with_report("tekken.json")?;
+    /// for warning in &report.warnings {
+    ///     eprintln!("warning: {warning}");
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_file_with_report<P: AsRef<Path>>(path: P) -> Result<(Self, LoadReport)> {
+        Self::load_file(path.as_ref(), None, None)
     }
 
-    /// Returns the token ID (u32) for the Unknown (UNK) token.
+    /// Loads a tokenizer from the contents of a tokenizer file and reports the
+    /// non-fatal issues found on the way.
     ///
     /// # Errors
     ///
-    /// Returns an error if the UNK token is not found in the vocabulary.
-    pub fn unk_id(&self) -> Result<u32> {
-        self.get_control_token(SpecialTokens::Unk.as_str())
+    /// Returns the same errors as [`Tekkenizer::from_bytes`].
+    pub fn from_bytes_with_report(bytes: &[u8]) -> Result<(Self, LoadReport)> {
+        match decompress(bytes)? {
+            Some(decompressed) => Self::from_json(Cow::Owned(decompressed), None, None, None),
+            None => Self::from_json(Cow::Borrowed(bytes), None, None, None),
+        }
     }
 
-    /// Returns the token ID (u32) for a specific control token by its string representation.
-    ///
-    /// # Arguments
+    /// Loads a tokenizer file whose vocabulary ranks are not contiguous.
     ///
-    /// * `token_str` - The string representation of the control token (e.g., "<s>", "</s>")
+    /// Intended for inspecting slightly malformed community tokenizer files.
+    /// Instead of failing, the vocabulary is repaired:
     ///
-    /// # Returns
+    /// - Entries repeating an earlier rank or earlier token bytes are skipped
+    /// - Gaps are closed by shifting later ranks down, so token IDs after a gap
+    ///   differ from the ones in the file
+    /// - `vocab_size` is reduced if fewer tokens remain than it requires
     ///
-    /// The token ID (u32) if found.
+    /// Every repair is described in the returned warnings, which are empty for
+    /// well-formed files. Use [`Tekkenizer::from_file_lenient_with`] to keep the
+    /// token IDs of the file instead.
     ///
     /// # Errors
     ///
-    /// Returns an error if the control token is not found in the vocabulary.
-    #[allow(clippy::cast_possible_truncation)]
-    pub fn get_control_token(&self, token_str: &str) -> Result<u32> {
-        self.special_tokens_map
-            .get(token_str)
-            .map(|&id| id as u32)
-            .ok_or_else(|| {
-                let available_tokens: Vec<&String> = self.special_tokens_map.keys().collect();
-                TokenizerError::TokenNotFound(format!(
-                    "Unknown control token: '{token_str}'. Available special tokens: {available_tokens:?}",
-                ))
-            })
-    }
-
-    /// Returns a reference to the complete vocabulary as a slice of strings.
+    /// Returns the same errors as [`Tekkenizer::from_file`], except for
+    /// non-contiguous ranks.
     ///
-    /// The vocabulary includes both special tokens and regular tokens.
-    /// Token IDs (u32) correspond to indices in this slice.
-    #[must_use]
-    pub fn vocab(&self) -> &[String] {
-        &self.vocab
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let (tokenizer, warnings) = Tekkenizer::from_file_lenient("community.json")?;
+    /// for warning in &warnings {
+    ///     eprintln!("warning: {warning}");
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn from_file_lenient<P: AsRef<Path>>(path: P) -> Result<(Self, Vec<String>)> {
+        Self::from_file_lenient_with(path, RankGapPolicy::Shift)
     }
 
-    /// Encodes text into a sequence of token IDs.
+    /// Loads a tokenizer file whose vocabulary ranks are not contiguous,
+    /// choosing how gaps are repaired.
+    ///
+    /// With [`RankGapPolicy::Placeholder`], missing ranks and entries repeating
+    /// earlier token bytes become placeholder tokens, so files with deliberately
+    /// removed tokens keep their token IDs and can be decoded and analyzed.
+    /// Placeholders are never produced by encoding, decode as U+FFFD, and are
+    /// reported by [`Tekkenizer::is_placeholder`]. Missing byte tokens, ranks
+    /// `0..256`, cannot be replaced and remain an error.
+    ///
+    /// Exporting such a tokenizer lists its placeholder ranks in the config
+    /// section, so the export loads back with the same token IDs under any
+    /// policy.
     ///
     /// # Arguments
     ///
-    /// * `text` - The input text to tokenize
-    /// * `add_bos` - Whether to add a Beginning of Sequence token at the start
-    /// * `add_eos` - Whether to add an End of Sequence token at the end
+    /// * `path` - Path to the tokenizer file
+    /// * `gaps` - How to repair missing ranks
     ///
     /// # Returns
     ///
-    /// A vector of token IDs (u32) representing the encoded text.
+    /// The tokenizer and a description of every repair.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Tekkenizer::from_file_lenient`], and an
+    /// error for missing byte tokens with [`RankGapPolicy::Placeholder`].
     ///
     /// # Examples
     ///
     /// ```rust,no_run
-    /// # use tekken::tekkenizer::Tekkenizer;
-    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
-    /// let tokens = tokenizer.encode("Hello world!", true, true)?;
-    /// println!("Encoded tokens: {:?}", tokens);
+    /// use tekken::special_tokens::SpecialTokenPolicy;
+    /// use tekken::tekkenizer::{RankGapPolicy, Tekkenizer};
+    ///
+    /// let (tokenizer, warnings) =
+    ///     Tekkenizer::from_file_lenient_with("community.json", RankGapPolicy::Placeholder)?;
+    /// for warning in &warnings {
+    ///     eprintln!("warning: {warning}");
+    /// }
+    /// let text = tokenizer.decode(&[1, 22177, 1044], SpecialTokenPolicy::Ignore)?;
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the tokenizer is not initialized.
-    #[allow(clippy::cast_possible_truncation)]
-    pub fn encode(
+    pub fn from_file_lenient_with<P: AsRef<Path>>(
+        path: P,
+        gaps: RankGapPolicy,
+    ) -> Result<(Self, Vec<String>)> {
+        let (tokenizer, report) = Self::load_file(path.as_ref(), None, Some(gaps))?;
+        let repairs = report
+            .warnings
+            .into_iter()
+            .filter_map(|warning| match warning {
+                LoadWarning::Repaired(repair) => Some(repair),
+                _ => None,
+            })
+            .collect();
+        Ok((tokenizer, repairs))
+    }
+
+    /// Reads, decompresses and loads a tokenizer file, wrapping errors in
+    /// [`TokenizerError::Load`].
+    fn load_file(
+        path: &Path,
+        max_vocab_tokens: Option<usize>,
+        gaps: Option<RankGapPolicy>,
+    ) -> Result<(Self, LoadReport)> {
+        let read_error = |error: TokenizerError| error.at_load_stage(path, LoadStage::Read);
+        let content = std::fs::read(path).map_err(|e| read_error(e.into()))?;
+        verify_sidecar(path, &content).map_err(read_error)?;
+        let content = decompress(&content).map_err(read_error)?.unwrap_or(content);
+        Self::from_json(Cow::Owned(content), Some(path), max_vocab_tokens, gaps)
+    }
+
+    /// Parses uncompressed tokenizer JSON, optionally keeping only the first
+    /// `max_vocab_tokens` ranks. With `gaps`, non-contiguous ranks are repaired
+    /// and each repair is recorded as a warning. With `path`, errors are
+    /// wrapped in [`TokenizerError::Load`] naming the file and failing stage.
+    fn from_json(
+        content: Cow<'_, [u8]>,
+        path: Option<&Path>,
+        max_vocab_tokens: Option<usize>,
+        gaps: Option<RankGapPolicy>,
+    ) -> Result<(Self, LoadReport)> {
+        let at = |stage| {
+            move |error: TokenizerError| match path {
+                Some(path) => error.at_load_stage(path, stage),
+                None => error,
+            }
+        };
+
+        #[cfg(feature = "simd-json")]
+        let mut content = content.into_owned();
+        #[cfg(feature = "simd-json")]
+        let model_data: RawModelData = simd_json::serde::from_slice(&mut content)
+            .map_err(|e| at(LoadStage::Parse)(e.into()))?;
+        #[cfg(not(feature = "simd-json"))]
+        let model_data: RawModelData =
+            serde_json::from_slice(&content).map_err(|e| at(LoadStage::Parse)(e.into()))?;
+
+        let mut report = LoadReport::default();
+        report.inspect_sections(
+            model_data.vocab.len(),
+            &model_data.config,
+            model_data.audio.as_ref(),
+        );
+        let declared_version = model_data.config.version.clone();
+        let num_defined = model_data.special_tokens.as_ref().map(Vec::len);
+
+        let mut config = model_data.config;
+        if let Some(max_vocab_tokens) = max_vocab_tokens {
+            config.default_vocab_size = config
+                .default_vocab_size
+                .min(config.default_num_special_tokens + max_vocab_tokens);
+        }
+
+        let entries = model_data
+            .vocab
+            .iter()
+            .map(|token| (token.rank, token.token_bytes.as_ref()));
+        let mut repairs = Vec::new();
+        let parts = match gaps {
+            None => Self::validate_config_parts(
+                entries,
+                model_data.special_tokens,
+                config,
+                model_data.audio,
+                model_data.image,
+            ),
+            Some(RankGapPolicy::Shift) => {
+                config.placeholder_ranks.clear();
+                let vocab = compact_ranks(entries, &mut repairs);
+                let available = vocab.len() + config.default_num_special_tokens;
+                reduce_vocab_size(&mut config, available, &mut repairs);
+                Self::validate_config_parts(
+                    vocab.into_iter().enumerate(),
+                    model_data.special_tokens,
+                    config,
+                    model_data.audio,
+                    model_data.image,
+                )
+            }
+            Some(RankGapPolicy::Placeholder) => Self::validate_with_placeholders(
+                entries,
+                model_data.special_tokens,
+                config,
+                model_data.audio,
+                model_data.image,
+                &mut repairs,
+            ),
+        }
+        .map_err(at(LoadStage::Validate))?;
+        report
+            .warnings
+            .extend(repairs.into_iter().map(LoadWarning::Repaired));
+
+        report.inspect_special_tokens(
+            &declared_version,
+            &parts.version,
+            num_defined,
+            parts.num_special_tokens,
+        );
+        let tokenizer = parts.build().map_err(at(LoadStage::Build))?;
+        Ok((tokenizer, report))
+    }
+
+    /// Builds a tokenizer from the parsed sections of a tokenizer file.
+    pub(crate) fn from_config_parts<B, I>(
+        vocab: I,
+        special_tokens: Option<Vec<SpecialTokenInfo>>,
+        config: TekkenConfig,
+        audio: Option<AudioConfig>,
+        image: Option<ImageConfig>,
+    ) -> Result<Self>
+    where
+        B: EntryBytes,
+        I: ExactSizeIterator<Item = (usize, B)>,
+    {
+        Self::validate_config_parts(vocab, special_tokens, config, audio, image)?.build()
+    }
+
+    /// Checks the parsed sections of a tokenizer file whose missing ranks are
+    /// filled with placeholders, recording each repair in `repairs`.
+    fn validate_with_placeholders<'a, I>(
+        vocab: I,
+        special_tokens: Option<Vec<SpecialTokenInfo>>,
+        mut config: TekkenConfig,
+        audio: Option<AudioConfig>,
+        image: Option<ImageConfig>,
+        repairs: &mut Vec<String>,
+    ) -> Result<ValidatedParts>
+    where
+        I: Iterator<Item = (usize, &'a str)>,
+    {
+        let mut filled = fill_rank_gaps(vocab, repairs)?;
+        // Placeholders marked by an export may end the vocabulary
+        if let Some(&last) = config.placeholder_ranks.iter().max()
+            && filled.len() <= last
+        {
+            filled.resize(last + 1, None);
+        }
+        config.placeholder_ranks.clear();
+        let available = filled.len() + config.default_num_special_tokens;
+        reduce_vocab_size(&mut config, available, repairs);
+        Self::validate_filled(filled, special_tokens, config, audio, image)
+    }
+
+    /// Checks the parsed sections of a tokenizer file whose vocabulary is
+    /// given by rank, `None` for placeholders.
+    fn validate_filled<B: EntryBytes>(
+        mut filled: Vec<Option<B>>,
+        special_tokens: Option<Vec<SpecialTokenInfo>>,
+        mut config: TekkenConfig,
+        audio: Option<AudioConfig>,
+        image: Option<ImageConfig>,
+    ) -> Result<ValidatedParts> {
+        let vocab_size = config.default_vocab_size;
+        filled.truncate(vocab_size.saturating_sub(config.default_num_special_tokens));
+
+        // Validate the present tokens as contiguous ranks, then move them back
+        let (present, placeholders): (Vec<_>, Vec<_>) =
+            (0..filled.len()).partition(|&rank| filled[rank].is_some());
+        let entries: Vec<B> = filled.into_iter().flatten().collect();
+        config.default_vocab_size -= placeholders.len();
+        let mut parts = Self::validate_config_parts(
+            entries.into_iter().enumerate(),
+            special_tokens,
+            config,
+            audio,
+            image,
+        )?;
+        for rank in parts.mergeable_ranks.values_mut() {
+            *rank = vocab_index(present[*rank as usize]);
+        }
+        parts.placeholder_ranks = placeholders.into_iter().map(vocab_index).collect();
+        parts.vocab_size = vocab_size;
+        Ok(parts)
+    }
+
+    /// Checks the parsed sections of a tokenizer file.
+    fn validate_config_parts<B, I>(
+        vocab: I,
+        special_tokens: Option<Vec<SpecialTokenInfo>>,
+        mut config: TekkenConfig,
+        audio: Option<AudioConfig>,
+        image: Option<ImageConfig>,
+    ) -> Result<ValidatedParts>
+    where
+        B: EntryBytes,
+        I: ExactSizeIterator<Item = (usize, B)>,
+    {
+        if !config.placeholder_ranks.is_empty() {
+            let filled = place_entries(vocab, &std::mem::take(&mut config.placeholder_ranks))?;
+            return Self::validate_filled(filled, special_tokens, config, audio, image);
+        }
+
+        let version = TokenizerVersion::from_string(&config.version)
+            .unwrap_or_else(|| TokenizerVersion::infer(special_tokens.as_deref(), audio.is_some()));
+
+        let special_tokens = special_tokens.unwrap_or_else(|| version.default_special_tokens());
+
+        let mut parts = Self::validate_vocab_entries(
+            vocab,
+            &special_tokens,
+            config.pattern,
+            config.default_vocab_size,
+            config.default_num_special_tokens,
+            version,
+            audio,
+        )?;
+        if let Some(image) = image {
+            parts.image_encoder = Some(build_image_encoder(&image, &parts.special_token_index)?);
+            parts.image_config = Some(image);
+        }
+        Ok(parts)
+    }
+
+    /// Exports the tokenizer as `ModelData`, the in-memory form of a `tekken.json` file.
+    ///
+    /// Special-token ranks without a definition are exported with their
+    /// `<SPECIAL_i>` placeholders, matching how they are decoded. Placeholder
+    /// ranks of the vocabulary are left out and listed in
+    /// `config.placeholder_ranks` instead.
+    ///
+    /// # Returns
+    ///
+    /// Model data that loads back into an equivalent tokenizer.
+    #[must_use]
+    pub fn to_model_data(&self) -> ModelData {
+        let vocab = self
+            .bpe
+            .all_token_bytes()
+            .iter()
+            .enumerate()
+            .filter(|&(rank, _)| !self.bpe.is_placeholder(rank))
+            .map(|(rank, bytes)| TokenInfo {
+                rank,
+                token_bytes: general_purpose::STANDARD.encode(bytes),
+                token_str: String::from_utf8(bytes.clone()).ok(),
+            })
+            .collect();
+
+        ModelData {
+            vocab,
+            special_tokens: Some(self.special_tokens.clone()),
+            config: TekkenConfig {
+                pattern: self.pattern.clone(),
+                num_vocab_tokens: self.bpe.len(),
+                default_vocab_size: self.vocab_size,
+                default_num_special_tokens: self.num_special_tokens,
+                version: self.version.as_str().to_string(),
+                placeholder_ranks: self.placeholder_ranks(),
+                extra: serde_json::Map::new(),
+            },
+            audio: self.audio_config.clone(),
+            image: self.image_config.clone(),
+            extra: serde_json::Map::new(),
+        }
+    }
+
+    /// Writes the tokenizer to a JSON file in the `tekken.json` format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file to create or overwrite
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written or serialization fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// tokenizer.pruned(32_768)?.save("tekken-32k.json")?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        serde_json::to_writer(writer, &self.to_model_data())?;
+        Ok(())
+    }
+
+    /// Creates a smaller tokenizer that keeps only the lowest-ranked tokens.
+    ///
+    /// BPE ranks are ordered by merge priority, so the first ranks form a
+    /// self-contained vocabulary. Special tokens, version and audio configuration
+    /// are preserved, and the reduced vocabulary is re-validated.
+    ///
+    /// # Arguments
+    ///
+    /// * `vocab_size` - Total vocabulary size of the new tokenizer, including special tokens
+    ///
+    /// # Returns
+    ///
+    /// A new `Tekkenizer` with the reduced vocabulary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `vocab_size` is larger than the current vocabulary or too
+    /// small to keep all special tokens and the 256 byte tokens.
+    pub fn pruned(&self, vocab_size: usize) -> Result<Self> {
+        if vocab_size > self.vocab_size {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Pruned vocab_size ({vocab_size}) must be <= current vocab_size ({})",
+                self.vocab_size
+            )));
+        }
+        if vocab_size < self.num_special_tokens + 256 {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Pruned vocab_size ({vocab_size}) must keep {} special tokens and 256 byte tokens",
+                self.num_special_tokens
+            )));
+        }
+
+        let entries: Vec<String> = self.bpe.all_token_bytes()
+            [..vocab_size - self.num_special_tokens]
+            .iter()
+            .map(|bytes| general_purpose::STANDARD.encode(bytes))
+            .collect();
+
+        let mut pruned = Self::from_vocab_entries(
+            entries.iter().map(String::as_str).enumerate(),
+            &self.special_tokens,
+            self.pattern.clone(),
+            vocab_size,
+            self.num_special_tokens,
+            self.version.clone(),
+            self.audio_config.clone(),
+        )?;
+        if let Some(config) = &self.image_config {
+            pruned.set_image_config(config.clone())?;
+        }
+        Ok(pruned)
+    }
+
+    /// Returns the total vocabulary size including special tokens.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// println!("Vocabulary size: {}", tokenizer.vocab_size());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn vocab_size(&self) -> usize {
+        self.vocab_size
+    }
+
+    /// Returns the number of special tokens in the vocabulary.
+    ///
+    /// Special tokens include control tokens like BOS, EOS, and audio tokens.
+    #[must_use]
+    pub fn num_special_tokens(&self) -> usize {
+        self.num_special_tokens
+    }
+
+    /// Returns the tokenizer version.
+    ///
+    /// Different versions may have different vocabulary sizes and special tokens.
+    #[must_use]
+    pub fn version(&self) -> &TokenizerVersion {
+        &self.version
+    }
+
+    /// Returns the token ID (u32) for the Beginning of Sequence (BOS) token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the BOS token is not found in the vocabulary.
+    pub fn bos_id(&self) -> Result<u32> {
+        self.id_of(SpecialTokens::Bos)
+    }
+
+    /// Returns the token ID (u32) for the End of Sequence (EOS) token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the EOS token is not found in the vocabulary.
+    pub fn eos_id(&self) -> Result<u32> {
+        self.id_of(SpecialTokens::Eos)
+    }
+
+    /// Returns the token ID (u32) for the padding (PAD) token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the PAD token is not found in the vocabulary.
+    pub fn pad_id(&self) -> Result<u32> {
+        self.id_of(SpecialTokens::Pad)
+    }
+
+    /// Returns the token ID (u32) for the Unknown (UNK) token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the UNK token is not found in the vocabulary.
+    pub fn unk_id(&self) -> Result<u32> {
+        self.id_of(SpecialTokens::Unk)
+    }
+
+    /// Returns the token ID (u32) of a special token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the token is not defined by this tokenizer.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::special_tokens::SpecialTokens;
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let inst = tokenizer.id_of(SpecialTokens::BeginInst)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn id_of(&self, token: SpecialTokens) -> Result<u32> {
+        self.special_token_index
+            .rank_of(token)
+            .map(vocab_index)
+            .ok_or_else(|| self.unknown_control_token(token.as_str()))
+    }
+
+    /// Returns the token ID (u32) for a specific control token by its string representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_str` - The string representation of the control token (e.g., "<s>", "</s>")
+    ///
+    /// # Returns
+    ///
+    /// The token ID (u32) if found.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the control token is not found in the vocabulary.
+    pub fn get_control_token(&self, token_str: &str) -> Result<u32> {
+        self.special_token_index
+            .get(token_str)
+            .map(vocab_index)
+            .ok_or_else(|| self.unknown_control_token(token_str))
+    }
+
+    fn unknown_control_token(&self, token_str: &str) -> TokenizerError {
+        let available_tokens: Vec<&String> = self
+            .special_tokens
+            .iter()
+            .map(|token| &token.token_str)
+            .collect();
+        TokenizerError::TokenNotFound(format!(
+            "Unknown control token: '{token_str}'. Available special tokens: {available_tokens:?}",
+        ))
+    }
+
+    /// Returns a reference to the complete vocabulary as a slice of strings.
+    ///
+    /// The vocabulary includes both special tokens and regular tokens.
+    /// Token IDs (u32) correspond to indices in this slice.
+    ///
+    /// The string table is built lazily on first access, so tokenizers that only
+    /// encode and decode never pay for it. Regular tokens that are not valid UTF-8
+    /// on their own are rendered lossily.
+    #[must_use]
+    pub fn vocab(&self) -> &[String] {
+        #[cfg(feature = "metrics")]
+        if self.vocab.get().is_some() {
+            crate::metrics::record_cache_hit("vocab");
+        }
+
+        self.vocab.get_or_init(|| {
+            (0..self.vocab_size)
+                .map(|i| {
+                    if i < self.num_special_tokens {
+                        self.special_tokens[i].token_str.clone()
+                    } else {
+                        match self.bpe.token_bytes(i - self.num_special_tokens) {
+                            Some(bytes) => String::from_utf8_lossy(bytes).to_string(),
+                            None => "<?>".to_string(),
+                        }
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// Encodes text into a sequence of token IDs.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The input text to tokenize
+    /// * `add_bos` - Whether to add a Beginning of Sequence token at the start
+    /// * `add_eos` - Whether to add an End of Sequence token at the end
+    ///
+    /// # Returns
+    ///
+    /// A vector of token IDs (u32) representing the encoded text.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let tokens = tokenizer.encode("Hello world!", true, true)?;
+    /// println!("Encoded tokens: {:?}", tokens);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tokenizer is not initialized.
+    pub fn encode(
+        &self,
+        text: &str,
+        add_beginning_of_sequence: bool,
+        add_end_of_sequence: bool,
+    ) -> Result<Vec<u32>> {
+        let mut tokens = Vec::new();
+        self.encode_into(
+            text,
+            &mut tokens,
+            EncodeOptions::new(add_beginning_of_sequence, add_end_of_sequence),
+        )?;
+        Ok(tokens)
+    }
+
+    /// Encodes text into token IDs of the integer type a model runtime takes.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The input text to tokenize
+    /// * `add_bos` - Whether to add a Beginning of Sequence token at the start
+    /// * `add_eos` - Whether to add an End of Sequence token at the end
+    ///
+    /// # Returns
+    ///
+    /// The token IDs as `T`; see [`convert_ids`] for the conversion.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails or a token ID does not fit in `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let input_ids = tokenizer.encode_as::<i64>("Hello world!", true, false)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_as<T: TryFrom<u32>>(
+        &self,
+        text: &str,
+        add_beginning_of_sequence: bool,
+        add_end_of_sequence: bool,
+    ) -> Result<Vec<T>> {
+        convert_ids(self.encode(text, add_beginning_of_sequence, add_end_of_sequence)?)
+    }
+
+    /// Encodes text and appends the token IDs to a caller-provided buffer.
+    ///
+    /// This is the allocation-friendly counterpart of [`Tekkenizer::encode`]: the
+    /// buffer is only extended, never cleared, so it can be reused across calls in
+    /// hot loops. On error the buffer is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The input text to tokenize
+    /// * `tokens` - Buffer the token IDs (u32) are appended to
+    /// * `options` - Whether to add BOS/EOS tokens, and optional BPE-dropout
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::{EncodeOptions, Tekkenizer};
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let mut buffer = Vec::new();
+    /// for line in ["first line", "second line"] {
+    ///     buffer.clear();
+    ///     tokenizer.encode_into(line, &mut buffer, EncodeOptions::default())?;
+    ///     println!("{line}: {} tokens", buffer.len());
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if BOS/EOS is requested but not present in the vocabulary,
+    /// or the BPE-dropout probability is outside `[0, 1]`.
+    pub fn encode_into(
+        &self,
+        text: &str,
+        tokens: &mut Vec<u32>,
+        options: EncodeOptions,
+    ) -> Result<()> {
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        // Resolve control tokens up front so the buffer is untouched on error
+        let bos_id = if options.add_bos {
+            Some(self.bos_id()?)
+        } else {
+            None
+        };
+        let eos_id = if options.add_eos {
+            Some(self.eos_id()?)
+        } else {
+            None
+        };
+
+        if let Some(dropout) = options.dropout
+            && !(0.0..=1.0).contains(&dropout.probability)
+        {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "BPE-dropout probability must be in [0, 1], got {}",
+                dropout.probability
+            )));
+        }
+
+        let start_len = tokens.len();
+        tokens.extend(bos_id);
+
+        // Shift ranks to token IDs to account for special tokens
+        let encoded = match (&self.backend, options.dropout) {
+            (_, Some(dropout)) => {
+                let mut rng = SplitMix64::new(dropout.seed);
+                self.bpe.encode_with_dropout(
+                    text,
+                    self.rank_offset,
+                    tokens,
+                    dropout.probability,
+                    &mut rng,
+                )
+            }
+            (Some(backend), None) => {
+                let ranks_start = tokens.len();
+                backend.encode_ordinary(text, tokens).and_then(|()| {
+                    // Backends are built for a vocabulary, not necessarily this one
+                    for token in &mut tokens[ranks_start..] {
+                        if *token as usize >= self.bpe.len() {
+                            return Err(TokenizerError::TokenOutOfRange {
+                                token_id: TokenId::checked_from_rank(
+                                    *token,
+                                    self.num_special_tokens,
+                                )
+                                .map_or(u32::MAX, TokenId::get),
+                                vocab_size: self.vocab_size,
+                            });
+                        }
+                        *token = (self.rank_offset + *token).get();
+                    }
+                    Ok(())
+                })
+            }
+            (None, None) => self.bpe.encode_ordinary(text, self.rank_offset, tokens),
+        };
+        if let Err(e) = encoded {
+            tokens.truncate(start_len);
+            return Err(e);
+        }
+
+        tokens.extend(eos_id);
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_encode(tokens.len() - start_len, started.elapsed());
+
+        Ok(())
+    }
+
+    /// Encodes text by sampling among near-optimal segmentations.
+    ///
+    /// Each pretokenized piece is split into vocabulary tokens at random, with a
+    /// segmentation of `k` tokens drawn with probability proportional to
+    /// `exp(-k / temperature)`. Low temperatures stay close to the shortest
+    /// segmentations, high temperatures explore more fragmented ones, and a
+    /// temperature of `0.0` returns the regular encoding. Results are
+    /// reproducible for a given seed. Sampling always uses the built-in BPE engine.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The input text to tokenize
+    /// * `temperature` - Sampling temperature, `>= 0.0`
+    /// * `seed` - Seed for the random number generator
+    ///
+    /// # Returns
+    ///
+    /// The sampled token IDs, without BOS/EOS.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `temperature` is negative or NaN.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let tokens = tokenizer.encode_sampled("regularization", 0.5, 42)?;
+    /// assert_eq!(tokens, tokenizer.encode_sampled("regularization", 0.5, 42)?);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_sampled(&self, text: &str, temperature: f64, seed: u64) -> Result<Vec<u32>> {
+        if temperature.is_nan() || temperature < 0.0 {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Sampling temperature must be >= 0, got {temperature}"
+            )));
+        }
+
+        let offset = self.rank_offset;
+        let mut tokens = Vec::new();
+        if temperature == 0.0 {
+            self.bpe.encode_ordinary(text, offset, &mut tokens)?;
+        } else {
+            let mut rng = SplitMix64::new(seed);
+            self.bpe
+                .encode_sampled(text, offset, &mut tokens, temperature, &mut rng)?;
+        }
+        Ok(tokens)
+    }
+
+    /// Encodes the beginning of a text within a token budget.
+    ///
+    /// Encoding stops once `max_tokens` tokens are produced. The result is then
+    /// shortened, if needed, so it ends on a character boundary: the returned
+    /// tokens are a prefix of the full encoding and decode to exactly
+    /// `&text[..bytes_consumed]`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The input text to tokenize
+    /// * `max_tokens` - Maximum number of tokens to return
+    ///
+    /// # Returns
+    ///
+    /// The token IDs (without BOS/EOS) and the number of bytes of `text` they cover.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if encoding fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let prompt = "A very long document...";
+    /// let (tokens, consumed) = tokenizer.encode_bounded(prompt, 4)?;
+    /// println!("kept {} tokens, cut {:?}", tokens.len(), &prompt[consumed..]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_bounded(&self, text: &str, max_tokens: usize) -> Result<(Vec<u32>, usize)> {
+        let mut tokens = Vec::new();
+        match &self.backend {
+            Some(_) => tokens = self.encode(text, false, false)?,
+            None => {
+                self.bpe
+                    .encode_ordinary_until(text, self.rank_offset, &mut tokens, max_tokens)?;
+            }
+        }
+
+        let mut consumed = 0;
+        let mut end = 0;
+        let mut kept = 0;
+        for (index, &token_id) in tokens.iter().take(max_tokens).enumerate() {
+            end += self.regular_token_bytes(token_id)?.len();
+            // A byte-fallback token may end inside a character
+            if text.is_char_boundary(end) {
+                consumed = end;
+                kept = index + 1;
+            }
+        }
+        tokens.truncate(kept);
+        Ok((tokens, consumed))
+    }
+
+    /// Encodes arbitrary bytes, which need not be valid UTF-8.
+    ///
+    /// Valid UTF-8 runs are encoded exactly like [`Tekkenizer::encode`]; every
+    /// byte of an invalid sequence becomes its single-byte token. Together with
+    /// [`Tekkenizer::decode_bytes`] this round-trips any input byte for byte.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The input data to tokenize
+    /// * `add_beginning_of_sequence` - Whether to prepend the BOS token
+    /// * `add_end_of_sequence` - Whether to append the EOS token
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if BOS/EOS is requested but not present in the vocabulary.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # use tekken::special_tokens::SpecialTokenPolicy;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let data = b"caf\xe9 \xff\x00";
+    /// let tokens = tokenizer.encode_bytes(data, false, false)?;
+    /// assert_eq!(tokenizer.decode_bytes(&tokens, SpecialTokenPolicy::Raise)?, data);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_bytes(
+        &self,
+        bytes: &[u8],
+        add_beginning_of_sequence: bool,
+        add_end_of_sequence: bool,
+    ) -> Result<Vec<u32>> {
+        let mut tokens = Vec::with_capacity(bytes.len() / 3 + 2);
+        if add_beginning_of_sequence {
+            tokens.push(self.bos_id()?);
+        }
+        for chunk in bytes.utf8_chunks() {
+            self.encode_into(chunk.valid(), &mut tokens, EncodeOptions::default())?;
+            tokens.extend(
+                chunk
+                    .invalid()
+                    .iter()
+                    .map(|&byte| self.byte_to_token_id(byte)),
+            );
+        }
+        if add_end_of_sequence {
+            tokens.push(self.eos_id()?);
+        }
+        Ok(tokens)
+    }
+
+    /// Decodes token IDs into raw bytes without UTF-8 validation.
+    ///
+    /// This is the inverse of [`Tekkenizer::encode_bytes`]. Kept special tokens
+    /// are written as the UTF-8 bytes of their string form.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs (u32) to decode
+    /// * `special_token_policy` - How to handle special tokens during decoding
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a token ID is out of range, or a special token is
+    /// found with [`SpecialTokenPolicy::Raise`].
+    pub fn decode_bytes(
+        &self,
+        tokens: &[u32],
+        special_token_policy: SpecialTokenPolicy,
+    ) -> Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(tokens.len() * 4);
+        for &token_id in tokens {
+            if self.is_special_token(token_id) {
+                match special_token_policy {
+                    SpecialTokenPolicy::Raise => {
+                        return Err(TokenizerError::SpecialTokenPolicy(format!(
+                            "Decoding tokens that contain special tokens ([{token_id}]) is not allowed",
+                        )));
+                    }
+                    SpecialTokenPolicy::Keep => {
+                        bytes.extend_from_slice(self.special_token(token_id)?.token_str.as_bytes());
+                    }
+                    SpecialTokenPolicy::Ignore => {}
+                }
+            } else {
+                bytes.extend_from_slice(self.regular_token_bytes(token_id)?);
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Decodes a sequence of token IDs back into text.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs (u32) to decode
+    /// * `special_token_policy` - How to handle special tokens during decoding:
+    ///   - `Keep`: Include special tokens in the output
+    ///   - `Ignore`: Skip special tokens
+    ///   - `Raise`: Error if special tokens are encountered
+    ///
+    /// # Returns
+    ///
+    /// The decoded text string.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # use tekken::special_tokens::SpecialTokenPolicy;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// # let tokens = vec![1, 22177, 1044, 4304, 2];
+    /// let text = tokenizer.decode(&tokens, SpecialTokenPolicy::Keep)?;
+    /// println!("Decoded text: {}", text);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns `TokenizerError::TokenOutOfRange` for token IDs outside the vocabulary,
+    /// or an error if the special token policy is violated or the text is not valid UTF-8.
+    pub fn decode(
+        &self,
+        tokens: &[u32],
+        special_token_policy: SpecialTokenPolicy,
+    ) -> Result<String> {
+        self.decode_with_invalid_policy(tokens, special_token_policy, InvalidTokenPolicy::Error)
+    }
+
+    /// Decodes a sequence of token IDs, choosing how IDs outside the vocabulary are handled.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs (u32) to decode
+    /// * `special_token_policy` - How to handle special tokens during decoding
+    /// * `invalid_token_policy` - How to handle token IDs outside the vocabulary
+    ///
+    /// # Returns
+    ///
+    /// The decoded text string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the special token policy is violated, the text is not valid
+    /// UTF-8, or an invalid token is found with `InvalidTokenPolicy::Error`.
+    pub fn decode_with_invalid_policy(
+        &self,
+        tokens: &[u32],
+        special_token_policy: SpecialTokenPolicy,
+        invalid_token_policy: InvalidTokenPolicy,
+    ) -> Result<String> {
+        let mut decoded = String::new();
+        self.decode_pieces(
+            tokens,
+            special_token_policy,
+            invalid_token_policy,
+            |piece| {
+                decoded.push_str(piece);
+                Ok(())
+            },
+        )?;
+        Ok(decoded)
+    }
+
+    /// Decodes a sequence of token IDs, streaming the text into a `fmt::Write` sink.
+    ///
+    /// Unlike [`Tekkenizer::decode`], no intermediate strings are built: token bytes
+    /// are written straight into `writer` as soon as they form complete UTF-8
+    /// characters. If an error occurs, text decoded before the failing token has
+    /// already been written.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs (u32) to decode
+    /// * `special_token_policy` - How to handle special tokens during decoding
+    /// * `writer` - Destination for the decoded text
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # use tekken::special_tokens::SpecialTokenPolicy;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// # let tokens = vec![1, 22177, 1044, 4304, 2];
+    /// let mut text = String::with_capacity(256);
+    /// tokenizer.decode_to_writer(&tokens, SpecialTokenPolicy::Ignore, &mut text)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A token ID is not part of the vocabulary
+    /// - A run of regular tokens does not form valid UTF-8
+    /// - Special tokens are encountered with the `Raise` policy
+    /// - The writer fails
+    pub fn decode_to_writer<W: std::fmt::Write + ?Sized>(
+        &self,
+        tokens: &[u32],
+        special_token_policy: SpecialTokenPolicy,
+        writer: &mut W,
+    ) -> Result<()> {
+        self.decode_pieces(
+            tokens,
+            special_token_policy,
+            InvalidTokenPolicy::Error,
+            |piece| writer.write_str(piece).map_err(TokenizerError::from),
+        )
+    }
+
+    /// Decodes a sequence of token IDs, streaming the UTF-8 bytes into an `io::Write` sink.
+    ///
+    /// This is the `io::Write` counterpart of [`Tekkenizer::decode_to_writer`], suitable
+    /// for writing decoded text directly to files or sockets.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs (u32) to decode
+    /// * `special_token_policy` - How to handle special tokens during decoding
+    /// * `writer` - Destination for the decoded text
+    ///
+    /// # Errors
+    ///
+    /// Returns an error under the same conditions as [`Tekkenizer::decode_to_writer`],
+    /// or if writing to `writer` fails.
+    pub fn decode_to_io_writer<W: std::io::Write + ?Sized>(
+        &self,
+        tokens: &[u32],
+        special_token_policy: SpecialTokenPolicy,
+        writer: &mut W,
+    ) -> Result<()> {
+        self.decode_pieces(
+            tokens,
+            special_token_policy,
+            InvalidTokenPolicy::Error,
+            |piece| {
+                writer
+                    .write_all(piece.as_bytes())
+                    .map_err(TokenizerError::from)
+            },
+        )
+    }
+
+    /// Walks the token sequence and emits decoded text pieces in order.
+    fn decode_pieces<F>(
+        &self,
+        tokens: &[u32],
+        special_token_policy: SpecialTokenPolicy,
+        invalid_token_policy: InvalidTokenPolicy,
+        mut emit: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&str) -> Result<()>,
+    {
+        let mut steps = DecodeIter::new(self, tokens, special_token_policy, invalid_token_policy);
+        let result = (|| -> Result<()> {
+            while let Some(step) = steps.step() {
+                let (completed, text) = step?;
+                if let Some(ch) = completed {
+                    emit(ch.encode_utf8(&mut [0; 4]))?;
+                }
+                if !text.is_empty() {
+                    emit(text)?;
+                }
+            }
+            Ok(())
+        })();
+
+        #[cfg(feature = "metrics")]
+        if result.is_err() {
+            crate::metrics::record_decode_error();
+        }
+
+        result
+    }
+
+    /// Lazily decodes token IDs into text pieces.
+    ///
+    /// Each item is a piece of text that ends on a UTF-8 character boundary: the
+    /// string form of a kept special token, or the text of one or more regular
+    /// tokens. Pieces borrow from the tokenizer's vocabulary whenever possible, so
+    /// consumers can stop early (e.g., at a stop sequence) without decoding the
+    /// rest of the sequence. Concatenating all pieces yields the same text as
+    /// [`Tekkenizer::decode`].
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs (u32) to decode
+    /// * `special_token_policy` - How to handle special tokens during decoding
+    ///
+    /// # Returns
+    ///
+    /// An iterator of decoded text pieces. After the first error no further items
+    /// are produced.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # use tekken::special_tokens::SpecialTokenPolicy;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// # let tokens = vec![1, 22177, 1044, 4304, 2];
+    /// let mut text = String::new();
+    /// for piece in tokenizer.decode_iter(&tokens, SpecialTokenPolicy::Ignore) {
+    ///     text.push_str(&piece?);
+    ///     if text.contains("\n\n") {
+    ///         break;
+    ///     }
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn decode_iter<'a>(
+        &'a self,
+        tokens: &'a [u32],
+        special_token_policy: SpecialTokenPolicy,
+    ) -> impl Iterator<Item = Result<Cow<'a, str>>> + 'a {
+        DecodeIter::new(
+            self,
+            tokens,
+            special_token_policy,
+            InvalidTokenPolicy::Error,
+        )
+    }
+
+    /// Decodes token IDs into separate strings, grouping consecutive special/non-special tokens.
+    ///
+    /// This method preserves the grouping of tokens, returning a vector where each element
+    /// represents a contiguous group of either special tokens or regular tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs (u32) to decode
+    /// * `special_token_policy` - How to handle special tokens during decoding
+    ///
+    /// # Returns
+    ///
+    /// A vector of decoded string segments.
+    ///
+    /// # Errors
+    ///
+    /// If the token IDs are invalid or the special token policy is not recognized.
+    pub fn decode_all(
         &self,
-        text: &str,
-        add_beginning_of_sequence: bool,
-        add_end_of_sequence: bool,
-    ) -> Result<Vec<u32>> {
-        let (tokens, _) = self
-            .tekkenizer
-            .encode(text, &std::collections::HashSet::new());
-        let mut tokens: Vec<u32> = tokens;
+        tokens: &[u32],
+        special_token_policy: SpecialTokenPolicy,
+    ) -> Result<Vec<String>> {
+        self.decode_all_with_invalid_policy(tokens, special_token_policy, InvalidTokenPolicy::Error)
+    }
+
+    /// Decodes token IDs into grouped strings, choosing how IDs outside the vocabulary are handled.
+    ///
+    /// Invalid token IDs are grouped with regular tokens; see [`Tekkenizer::decode_all`].
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs (u32) to decode
+    /// * `special_token_policy` - How to handle special tokens during decoding
+    /// * `invalid_token_policy` - How to handle token IDs outside the vocabulary
+    ///
+    /// # Returns
+    ///
+    /// A vector of decoded string segments.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the special token policy is violated, a group is not valid
+    /// UTF-8, or an invalid token is found with `InvalidTokenPolicy::Error`.
+    pub fn decode_all_with_invalid_policy(
+        &self,
+        tokens: &[u32],
+        special_token_policy: SpecialTokenPolicy,
+        invalid_token_policy: InvalidTokenPolicy,
+    ) -> Result<Vec<String>> {
+        let mut decoded = Vec::new();
+        let mut current_group = Vec::new();
+        let mut current_is_special = None;
+
+        for &token_id in tokens {
+            let is_special = self.is_special_token(token_id);
+
+            if current_is_special.is_none() {
+                current_is_special = Some(is_special);
+            }
+
+            if current_is_special == Some(is_special) {
+                current_group.push(token_id);
+            } else {
+                // Process the current group
+                if let Some(was_special) = current_is_special {
+                    self.decode_group(
+                        &current_group,
+                        was_special,
+                        &mut decoded,
+                        special_token_policy,
+                        invalid_token_policy,
+                    )?;
+                }
 
-        // Shift tokens to account for special tokens
-        for token in &mut tokens {
-            *token += self.num_special_tokens as u32;
+                // Start new group
+                current_group.clear();
+                current_group.push(token_id);
+                current_is_special = Some(is_special);
+            }
         }
 
-        if add_beginning_of_sequence {
-            let bos_id = self.bos_id()?;
-            tokens.insert(0, bos_id);
+        // Process the last group
+        if let Some(was_special) = current_is_special {
+            self.decode_group(
+                &current_group,
+                was_special,
+                &mut decoded,
+                special_token_policy,
+                invalid_token_policy,
+            )?;
         }
 
-        if add_end_of_sequence {
-            let eos_id = self.eos_id()?;
-            tokens.push(eos_id);
+        Ok(decoded)
+    }
+
+    /// Helper method to decode a group of tokens that are all special or all non-special.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The token IDs (u32) in the current group
+    /// * `is_special` - Whether this group contains special tokens
+    /// * `decoded` - Output vector to append decoded strings to
+    /// * `special_token_policy` - How to handle special tokens
+    /// * `invalid_token_policy` - How to handle token IDs outside the vocabulary
+    fn decode_group(
+        &self,
+        group: &[u32],
+        is_special: bool,
+        decoded: &mut Vec<String>,
+        special_token_policy: SpecialTokenPolicy,
+        invalid_token_policy: InvalidTokenPolicy,
+    ) -> Result<()> {
+        if is_special {
+            match special_token_policy {
+                SpecialTokenPolicy::Raise => {
+                    return Err(TokenizerError::SpecialTokenPolicy(format!(
+                        "Decoding tokens that contain special tokens ({group:?}) is not allowed",
+                    )));
+                }
+                SpecialTokenPolicy::Keep => {
+                    for &token_id in group {
+                        decoded.push(self.special_token(token_id)?.token_str.clone());
+                    }
+                }
+                SpecialTokenPolicy::Ignore => {
+                    // Skip special tokens
+                }
+            }
+        } else {
+            // Decode non-special tokens
+            let mut bytes = Vec::new();
+            for &token_id in group {
+                match self.regular_token_bytes(token_id) {
+                    Ok(token_bytes) => bytes.extend_from_slice(token_bytes),
+                    Err(e) => match invalid_token_policy {
+                        InvalidTokenPolicy::Error => return Err(e),
+                        InvalidTokenPolicy::Skip => flush_incomplete(&mut bytes),
+                        InvalidTokenPolicy::Replace(ch) => {
+                            flush_incomplete(&mut bytes);
+                            bytes.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                        }
+                    },
+                }
+            }
+            let decoded_text = String::from_utf8(bytes).map_err(|e| {
+                TokenizerError::Tokenizers(format!(
+                    "Unable to decode into a valid UTF-8 string: {e}"
+                ))
+            })?;
+            decoded.push(decoded_text);
+        }
+
+        Ok(())
+    }
+
+    /// Looks up a special token by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TokenOutOfRange` if the ID is not a special token.
+    pub(crate) fn special_token(&self, token_id: u32) -> Result<&SpecialTokenInfo> {
+        self.special_tokens
+            .get(token_id as usize)
+            .ok_or_else(|| self.token_out_of_range(token_id))
+    }
+
+    /// Looks up the raw bytes of a regular (non-special) token by ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns `TokenOutOfRange` if the ID does not map to a vocabulary token.
+    pub(crate) fn regular_token_bytes(&self, token_id: u32) -> Result<&[u8]> {
+        TokenId::from(token_id)
+            .to_rank(self.num_special_tokens)
+            .and_then(|rank| self.bpe.token_bytes(rank as usize))
+            .ok_or_else(|| self.token_out_of_range(token_id))
+    }
+
+    /// Ranks of the regular tokens whose bytes start with `prefix`, in byte
+    /// order.
+    ///
+    /// The sorted rank table is built on first use.
+    pub(crate) fn ranks_with_prefix(&self, prefix: &[u8]) -> &[u32] {
+        let token_bytes = self.bpe.all_token_bytes();
+        let sorted = self.ranks_by_bytes.get_or_init(|| {
+            #[allow(clippy::cast_possible_truncation)]
+            let mut ranks: Vec<u32> = (0..token_bytes.len() as u32)
+                .filter(|&rank| !self.bpe.is_placeholder(rank as usize))
+                .collect();
+            ranks.sort_unstable_by(|&a, &b| token_bytes[a as usize].cmp(&token_bytes[b as usize]));
+            ranks
+        });
+        let bytes = |rank: u32| token_bytes[rank as usize].as_slice();
+        let start = sorted.partition_point(|&rank| bytes(rank) < prefix);
+        let len = sorted[start..].partition_point(|&rank| bytes(rank).starts_with(prefix));
+        &sorted[start..start + len]
+    }
+
+    /// Encodes bytes as a single pretokenized piece, appending token IDs.
+    pub(crate) fn encode_piece(&self, piece: &[u8], tokens: &mut Vec<u32>) {
+        self.bpe.encode_piece(piece, self.rank_offset, tokens);
+    }
+
+    /// Byte offsets where the pretokenized pieces of `text` start.
+    pub(crate) fn piece_starts(&self, text: &str) -> Result<Vec<usize>> {
+        self.bpe.piece_starts(text)
+    }
+
+    /// Byte ranges of the pretokenized pieces of `text`.
+    pub(crate) fn piece_ranges(&self, text: &str) -> Result<Vec<Range<usize>>> {
+        self.bpe.piece_ranges(text)
+    }
+
+    /// The built-in BPE engine.
+    #[cfg(feature = "unstable")]
+    pub(crate) fn bpe(&self) -> &BytePairEncoder {
+        &self.bpe
+    }
+
+    /// Byte ranges of the parts joined by each BPE merge applied to `piece`.
+    pub(crate) fn trace_merges(&self, piece: &[u8]) -> Vec<(Range<usize>, Range<usize>)> {
+        self.bpe.trace_merges(piece)
+    }
+
+    /// Returns the regex pattern used to split text before BPE merging.
+    ///
+    /// Custom [`BpeBackend`]s should pretokenize with this pattern to match the
+    /// built-in engine.
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        self.bpe.pattern()
+    }
+
+    pub(crate) fn set_backend(&mut self, backend: Box<dyn BpeBackend>) {
+        self.backend = Some(backend);
+    }
+
+    /// Returns the name of the encoding engine, `"native"` for the built-in one.
+    #[must_use]
+    pub fn backend_name(&self) -> &str {
+        self.backend
+            .as_ref()
+            .map_or("native", |backend| backend.name())
+    }
+
+    /// Approximate heap memory held by the tokenizer, in bytes.
+    pub(crate) fn heap_size(&self) -> usize {
+        let strings = |tokens: &[SpecialTokenInfo]| -> usize {
+            tokens.iter().map(|token| token.token_str.capacity()).sum()
+        };
+        let vocab = self.vocab.get().map_or(0, |vocab| {
+            vocab.iter().map(String::capacity).sum::<usize>()
+                + vocab.capacity() * std::mem::size_of::<String>()
+        });
+        self.bpe.heap_size()
+            + strings(&self.special_tokens)
+            + self.special_tokens.capacity() * std::mem::size_of::<SpecialTokenInfo>()
+            + self.special_token_index.heap_size()
+            + self
+                .ranks_by_bytes
+                .get()
+                .map_or(0, |ranks| ranks.capacity() * std::mem::size_of::<u32>())
+            + vocab
+    }
+
+    /// Returns the ID of rank 0, the shift from BPE ranks to token IDs.
+    pub(crate) fn rank_offset(&self) -> TokenId {
+        self.rank_offset
+    }
+
+    fn token_out_of_range(&self, token_id: u32) -> TokenizerError {
+        TokenizerError::TokenOutOfRange {
+            token_id,
+            vocab_size: self.vocab_size,
+        }
+    }
+
+    /// Checks if a token ID represents a special token.
+    ///
+    /// Special tokens include control tokens like BOS, EOS, instruction tokens, etc.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_id` - The token ID (u32) to check
+    ///
+    /// # Returns
+    ///
+    /// `true` if the token is a special token, `false` otherwise.
+    #[must_use]
+    pub fn is_special_token(&self, token_id: u32) -> bool {
+        (token_id as usize) < self.num_special_tokens
+    }
+
+    /// Checks if a token ID is a placeholder filling a gap in the vocabulary.
+    ///
+    /// Placeholders only exist in tokenizers loaded with
+    /// [`RankGapPolicy::Placeholder`] or from an export of such a tokenizer;
+    /// they are never produced by encoding and decode as U+FFFD.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_id` - The token ID (u32) to check
+    #[must_use]
+    pub fn is_placeholder(&self, token_id: u32) -> bool {
+        TokenId::from(token_id)
+            .to_rank(self.num_special_tokens)
+            .is_some_and(|rank| self.bpe.is_placeholder(rank as usize))
+    }
+
+    /// Returns the ranks of the placeholder tokens, in ascending order.
+    pub(crate) fn placeholder_ranks(&self) -> Vec<usize> {
+        (0..self.bpe.len())
+            .filter(|&rank| self.bpe.is_placeholder(rank))
+            .collect()
+    }
+
+    /// Checks if a token ID represents a single byte token.
+    ///
+    /// In BPE tokenization, the first 256 tokens typically represent individual bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_id` - The token ID (u32) to check
+    ///
+    /// # Returns
+    ///
+    /// `true` if the token represents a single byte, `false` otherwise.
+    #[must_use]
+    pub fn is_byte(&self, token_id: u32) -> bool {
+        TokenId::from(token_id)
+            .to_rank(self.num_special_tokens)
+            .is_some_and(|rank| rank < 256)
+    }
+
+    /// Returns the byte value represented by a byte token.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_id` - The token ID (u32) to inspect
+    ///
+    /// # Returns
+    ///
+    /// `Some(byte)` if the token is a byte token, `None` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let token_id = tokenizer.byte_to_token_id(b'A');
+    /// assert_eq!(tokenizer.byte_token_value(token_id), Some(b'A'));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn byte_token_value(&self, token_id: u32) -> Option<u8> {
+        if token_id as usize >= self.vocab_size {
+            return None;
+        }
+        // Ranks below 256 are validated to hold exactly their own byte at load time.
+        TokenId::from(token_id)
+            .to_rank(self.num_special_tokens)
+            .and_then(|rank| u8::try_from(rank).ok())
+    }
+
+    /// Returns the token ID of the byte token for a given byte value.
+    ///
+    /// This is the inverse of [`Tekkenizer::byte_token_value`].
+    ///
+    /// # Arguments
+    ///
+    /// * `byte` - The byte value to look up
+    ///
+    /// # Returns
+    ///
+    /// The token ID (u32) representing that single byte.
+    #[must_use]
+    pub fn byte_to_token_id(&self, byte: u8) -> u32 {
+        (self.rank_offset + u32::from(byte)).get()
+    }
+
+    /// Returns a stable SHA-256 fingerprint of the tokenizer.
+    ///
+    /// The hash covers everything that affects encoding and decoding: the version,
+    /// the pattern, vocabulary and special-token sizes, every special token, every
+    /// vocabulary token's bytes, and the audio configuration. Two tokenizers with
+    /// the same fingerprint produce identical token IDs, so it can be used to verify
+    /// that distributed nodes loaded the same file or to key caches.
+    ///
+    /// The fingerprint is computed on first access and cached.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let key: String = tokenizer
+    ///     .fingerprint()
+    ///     .iter()
+    ///     .map(|byte| format!("{byte:02x}"))
+    ///     .collect();
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn fingerprint(&self) -> [u8; 32] {
+        #[cfg(feature = "metrics")]
+        if self.fingerprint.get().is_some() {
+            crate::metrics::record_cache_hit("fingerprint");
         }
 
-        Ok(tokens)
+        *self.fingerprint.get_or_init(|| {
+            let mut hasher = Sha256::new();
+            // Length-prefix every variable-sized field so the encoding is unambiguous
+            let mut update = |bytes: &[u8]| {
+                hasher.update((bytes.len() as u64).to_le_bytes());
+                hasher.update(bytes);
+            };
+
+            update(b"tekken-fingerprint-v1");
+            update(self.version.as_str().as_bytes());
+            update(self.pattern.as_bytes());
+            update(&(self.vocab_size as u64).to_le_bytes());
+            update(&(self.num_special_tokens as u64).to_le_bytes());
+            for token in &self.special_tokens {
+                update(token.token_str.as_bytes());
+                update(&[u8::from(token.is_control)]);
+            }
+            for bytes in self.bpe.all_token_bytes() {
+                update(bytes);
+            }
+            let audio = serde_json::to_vec(&self.audio_config).unwrap_or_default();
+            update(&audio);
+            // Only hashed when present, so text and audio fingerprints are unchanged
+            if let Some(image) = &self.image_config {
+                update(&serde_json::to_vec(image).unwrap_or_default());
+            }
+
+            hasher.finalize().into()
+        })
+    }
+
+    /// Returns the BPE mergeable ranks as `(token bytes, rank)` pairs.
+    ///
+    /// Ranks are the raw BPE ranks, starting at 0 for the first byte token; the
+    /// corresponding token ID is `rank + num_special_tokens()`. Pairs are yielded
+    /// in ascending rank order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// for (bytes, rank) in tokenizer.mergeable_ranks().take(5) {
+    ///     println!("{rank}: {bytes:?}");
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn mergeable_ranks(&self) -> impl ExactSizeIterator<Item = (&[u8], u32)> + '_ {
+        self.bpe
+            .all_token_bytes()
+            .iter()
+            .enumerate()
+            .map(|(rank, bytes)| (bytes.as_slice(), rank as u32))
+    }
+
+    /// Looks up the BPE rank of a byte sequence.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte sequence to look up
+    ///
+    /// # Returns
+    ///
+    /// The raw BPE rank if the bytes are a vocabulary token, `None` otherwise.
+    /// Add `num_special_tokens()` to obtain the token ID.
+    #[must_use]
+    pub fn rank_of(&self, bytes: &[u8]) -> Option<u32> {
+        self.bpe.rank(bytes)
     }
 
-    /// Decodes a sequence of token IDs back into text.
+    /// Finds the regular tokens whose text matches a string.
+    ///
+    /// Matching is done on the raw token bytes, so it also works for tokens that are
+    /// not valid UTF-8 on their own. Special tokens and placeholder ranks (see
+    /// [`Tekkenizer::is_placeholder`]) are never returned.
     ///
     /// # Arguments
     ///
-    /// * `tokens` - The token IDs (u32) to decode
-    /// * `special_token_policy` - How to handle special tokens during decoding:
-    ///   - `Keep`: Include special tokens in the output
-    ///   - `Ignore`: Skip special tokens
-    ///   - `Raise`: Error if special tokens are encountered
+    /// * `text` - The string to match against token text
+    /// * `mode` - How token text is compared with `text`
     ///
     /// # Returns
     ///
-    /// The decoded text string.
+    /// The matching token IDs, sorted ascending.
     ///
     /// # Examples
     ///
     /// ```rust,no_run
-    /// # use tekken::tekkenizer::Tekkenizer;
-    /// # use tekken::special_tokens::SpecialTokenPolicy;
-    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
-    /// # let tokens = vec![1, 22177, 1044, 4304, 2];
-    /// let text = tokenizer.decode(&tokens, SpecialTokenPolicy::Keep)?;
-    /// println!("Decoded text: {}", text);
+    /// use tekken::tekkenizer::{MatchMode, Tekkenizer};
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// // Both "Hello" and " Hello", e.g. for an OpenAI-style logit_bias
+    /// let banned = tokenizer.token_ids_for("Hello", MatchMode::Word);
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    ///
-    /// # Errors
-    ///
-    /// If the token IDs are invalid or the special token policy is not recognized.
-    pub fn decode(
-        &self,
-        tokens: &[u32],
-        special_token_policy: SpecialTokenPolicy,
-    ) -> Result<String> {
-        let decoded_parts = self.decode_all(tokens, special_token_policy)?;
-        Ok(decoded_parts.join(""))
+    #[must_use]
+    pub fn token_ids_for(&self, text: &str, mode: MatchMode) -> Vec<u32> {
+        let needle = text.as_bytes();
+        let matches = |bytes: &[u8]| match mode {
+            MatchMode::Exact => bytes == needle,
+            MatchMode::StartsWith => bytes.starts_with(needle),
+            MatchMode::Contains => {
+                needle.is_empty() || bytes.windows(needle.len()).any(|window| window == needle)
+            }
+            MatchMode::Word => {
+                bytes == needle || (bytes.first() == Some(&b' ') && &bytes[1..] == needle)
+            }
+        };
+
+        let num_regular = self.vocab_size.saturating_sub(self.num_special_tokens);
+        self.bpe
+            .all_token_bytes()
+            .iter()
+            .take(num_regular)
+            .zip(0u32..)
+            .filter(|&(bytes, rank)| !self.bpe.is_placeholder(rank as usize) && matches(bytes))
+            .map(|(_, rank)| (self.rank_offset + rank).get())
+            .collect()
     }
 
-    /// Decodes token IDs into separate strings, grouping consecutive special/non-special tokens.
+    /// Converts a single token ID to its string representation.
     ///
-    /// This method preserves the grouping of tokens, returning a vector where each element
-    /// represents a contiguous group of either special tokens or regular tokens.
+    /// This method includes special tokens in the output.
     ///
     /// # Arguments
     ///
-    /// * `tokens` - The token IDs (u32) to decode
-    /// * `special_token_policy` - How to handle special tokens during decoding
+    /// * `token_id` - The token ID (u32) to convert
     ///
     /// # Returns
     ///
-    /// A vector of decoded string segments.
+    /// The string representation of the token.
     ///
     /// # Errors
     ///
-    /// If the token IDs are invalid or the special token policy is not recognized.
-    #[allow(clippy::cast_possible_truncation)]
-    pub fn decode_all(
-        &self,
-        tokens: &[u32],
-        special_token_policy: SpecialTokenPolicy,
-    ) -> Result<Vec<String>> {
-        let mut decoded = Vec::new();
-        let mut current_group = Vec::new();
-        let mut current_is_special = None;
-
-        for &token_id in tokens {
-            #[allow(clippy::cast_possible_truncation)]
-            let is_special = token_id < self.num_special_tokens as u32;
-
-            if current_is_special.is_none() {
-                current_is_special = Some(is_special);
-            }
-
-            if current_is_special == Some(is_special) {
-                current_group.push(token_id);
-            } else {
-                // Process the current group
-                if let Some(was_special) = current_is_special {
-                    self.decode_group(
-                        &current_group,
-                        was_special,
-                        &mut decoded,
-                        special_token_policy,
-                    )?;
-                }
-
-                // Start new group
-                current_group.clear();
-                current_group.push(token_id);
-                current_is_special = Some(is_special);
-            }
-        }
-
-        // Process the last group
-        if let Some(was_special) = current_is_special {
-            self.decode_group(
-                &current_group,
-                was_special,
-                &mut decoded,
-                special_token_policy,
-            )?;
+    /// Returns an error if the token ID is out of vocabulary range.
+    pub fn id_to_piece(&self, token_id: u32) -> Result<String> {
+        // Validate token ID is within vocabulary range
+        if token_id as usize >= self.vocab_size {
+            return Err(self.token_out_of_range(token_id));
         }
 
-        Ok(decoded)
+        self.decode(&[token_id], SpecialTokenPolicy::Keep)
     }
 
-    /// Helper method to decode a group of tokens that are all special or all non-special.
+    /// Converts a single token ID to its byte representation.
     ///
     /// # Arguments
     ///
-    /// * `group` - The token IDs (u32) in the current group
-    /// * `is_special` - Whether this group contains special tokens
-    /// * `decoded` - Output vector to append decoded strings to
+    /// * `token_id` - The token ID (u32) to convert
     /// * `special_token_policy` - How to handle special tokens
-    #[allow(clippy::cast_possible_truncation)]
-    fn decode_group(
+    ///
+    /// # Returns
+    ///
+    /// The byte representation of the token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Token ID is invalid (out of vocabulary range)
+    /// - Special token policy is Raise and token is special
+    pub fn id_to_byte_piece(
         &self,
-        group: &[u32],
-        is_special: bool,
-        decoded: &mut Vec<String>,
+        token_id: u32,
         special_token_policy: SpecialTokenPolicy,
-    ) -> Result<()> {
-        if is_special {
+    ) -> Result<Vec<u8>> {
+        // Validate token ID is within vocabulary range
+        if token_id as usize >= self.vocab_size {
+            return Err(self.token_out_of_range(token_id));
+        }
+
+        if self.is_special_token(token_id) {
+            let token = self.special_token(token_id)?;
             match special_token_policy {
-                SpecialTokenPolicy::Raise => {
-                    return Err(TokenizerError::SpecialTokenPolicy(format!(
-                        "Decoding tokens that contain special tokens ({group:?}) is not allowed",
-                    )));
-                }
-                SpecialTokenPolicy::Keep => {
-                    for &token_id in group {
-                        decoded.push(self.special_tokens[token_id as usize].token_str.clone());
-                    }
-                }
-                SpecialTokenPolicy::Ignore => {
-                    // Skip special tokens
-                }
+                SpecialTokenPolicy::Keep => Ok(token.token_str.as_bytes().to_vec()),
+                SpecialTokenPolicy::Raise => Err(TokenizerError::SpecialTokenPolicy(format!(
+                    "Token ID {} is a special token ({}), cannot convert to byte piece with Raise policy",
+                    token_id, token.token_str
+                ))),
+                SpecialTokenPolicy::Ignore => Ok(vec![]),
             }
         } else {
-            // Decode non-special tokens
-            #[allow(clippy::cast_possible_truncation)]
-            let shifted_tokens: Vec<u32> = group
-                .iter()
-                .map(|&t| t - self.num_special_tokens as u32)
-                .collect();
-            let decoded_text = self
-                .tekkenizer
-                .decode(shifted_tokens)
-                .map_err(|e| TokenizerError::Tokenizers(format!("{e:?}")))?;
-            decoded.push(decoded_text);
+            // Raw merge-rank bytes, exact even when the token is not valid UTF-8 on its own
+            self.regular_token_bytes(token_id).map(<[u8]>::to_vec)
         }
-
-        Ok(())
     }
 
-    /// Checks if a token ID represents a special token.
+    /// Encodes audio data into tokens that can be mixed with text tokens.
     ///
-    /// Special tokens include control tokens like BOS, EOS, instruction tokens, etc.
+    /// This method converts audio waveforms into token sequences using mel-scale
+    /// spectrogram processing and audio-specific special tokens. It requires the
+    /// `audio` feature.
     ///
     /// # Arguments
     ///
-    /// * `token_id` - The token ID (u32) to check
+    /// * `audio` - The audio data to encode
     ///
     /// # Returns
     ///
-    /// `true` if the token is a special token, `false` otherwise.
-    #[must_use]
-    pub fn is_special_token(&self, token_id: u32) -> bool {
-        (token_id as usize) < self.num_special_tokens
+    /// An `AudioEncoding` containing the token sequence and processed audio data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Audio encoder is not configured
+    /// - Audio processing fails
+    /// - Resampling fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # use tekken::audio::Audio;
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let audio = Audio::from_file("audio.wav")?;
+    /// let encoding = tokenizer.encode_audio(audio)?;
+    /// println!("Audio encoded to {} tokens", encoding.tokens.len());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "audio")]
+    pub fn encode_audio<S: AudioSample>(&self, audio: Audio<S>) -> Result<AudioEncoding<S>> {
+        match &self.audio_encoder {
+            Some(encoder) => {
+                #[cfg(feature = "metrics")]
+                let seconds = audio.duration();
+                let encoding = encoder.encode(audio)?;
+                #[cfg(feature = "metrics")]
+                crate::metrics::record_audio(seconds);
+                Ok(encoding)
+            }
+            None => Err(TokenizerError::Audio(
+                "Audio encoder not configured".to_string(),
+            )),
+        }
     }
 
-    /// Checks if a token ID represents a single byte token.
+    /// Encodes audio into tokens after changing its speed by `factor`.
     ///
-    /// In BPE tokenization, the first 256 tokens typically represent individual bytes.
+    /// Use this to generate speed-perturbed training data, typically with the
+    /// factors in [`crate::audio::SPEED_FACTORS`]. See
+    /// [`AudioEncoder::encode_with_speed`].
     ///
-    /// # Arguments
+    /// # Errors
     ///
-    /// * `token_id` - The token ID (u32) to check
+    /// Returns an error if the audio encoder is not configured, `factor` is not a
+    /// positive number, or audio processing fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// # use tekken::audio::{Audio, SPEED_FACTORS};
+    /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let audio = Audio::from_file("audio.wav")?;
+    /// for factor in SPEED_FACTORS {
+    ///     let encoding = tokenizer.encode_audio_with_speed(audio.clone(), factor)?;
+    ///     println!("x{factor}: {} tokens", encoding.tokens.len());
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[cfg(feature = "audio")]
+    pub fn encode_audio_with_speed<S: AudioSample>(
+        &self,
+        audio: Audio<S>,
+        factor: f64,
+    ) -> Result<AudioEncoding<S>> {
+        match &self.audio_encoder {
+            Some(encoder) => {
+                #[cfg(feature = "metrics")]
+                let seconds = audio.duration();
+                let encoding = encoder.encode_with_speed(audio, factor)?;
+                #[cfg(feature = "metrics")]
+                crate::metrics::record_audio(seconds);
+                Ok(encoding)
+            }
+            None => Err(TokenizerError::Audio(
+                "Audio encoder not configured".to_string(),
+            )),
+        }
+    }
+
+    /// Creates a [`StreamingAudioEncoder`] for audio that arrives in pieces.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the audio encoder is not configured or its
+    /// configuration is invalid.
+    #[cfg(feature = "audio")]
+    pub fn streaming_audio_encoder(&self) -> Result<StreamingAudioEncoder> {
+        match &self.audio_encoder {
+            Some(encoder) => StreamingAudioEncoder::new(encoder.clone()),
+            None => Err(TokenizerError::Audio(
+                "Audio encoder not configured".to_string(),
+            )),
+        }
+    }
+
+    /// Checks if this tokenizer instance supports audio processing.
+    ///
+    /// Audio support depends on the tokenizer configuration containing audio settings
+    /// and the presence of required audio special tokens. Without the `audio`
+    /// feature this is always `false`, although [`Tekkenizer::audio_config`] still
+    /// reports the configuration loaded from the file; `encode_audio` and the rest
+    /// of the audio API are only compiled with that feature.
     ///
     /// # Returns
     ///
-    /// `true` if the token represents a single byte, `false` otherwise.
+    /// `true` if audio encoding is available, `false` otherwise.
     #[must_use]
-    #[allow(clippy::cast_possible_truncation)]
-    pub fn is_byte(&self, token_id: u32) -> bool {
-        #[allow(clippy::cast_possible_truncation)]
-        if token_id < self.num_special_tokens as u32 {
-            false
-        } else {
-            #[allow(clippy::cast_possible_truncation)]
-            let shifted_id = token_id - self.num_special_tokens as u32;
-            shifted_id < 256
-        }
+    pub fn has_audio_support(&self) -> bool {
+        cfg!(feature = "audio") && self.audio_encoder.is_some()
     }
 
-    /// Converts a single token ID to its string representation.
+    /// Returns a reference to the audio configuration, if available.
     ///
-    /// This method includes special tokens in the output.
+    /// # Returns
+    ///
+    /// An optional reference to the `AudioConfig` if audio support is configured.
+    /// Returns `None` if the tokenizer was not initialized with audio support.
+    #[must_use]
+    pub fn audio_config(&self) -> Option<&AudioConfig> {
+        self.audio_config.as_ref()
+    }
+
+    /// Replaces the audio configuration, rebuilding the audio encoder.
+    ///
+    /// The encoder uses this tokenizer's `[AUDIO]` and `[BEGIN_AUDIO]` special
+    /// tokens, so audio can be enabled for tokenizer files without an audio
+    /// section, or encoded with a different frame rate or spectrogram setup.
+    /// The configuration is part of [`Tekkenizer::fingerprint`].
     ///
     /// # Arguments
     ///
-    /// * `token_id` - The token ID (u32) to convert
+    /// * `config` - The new audio configuration
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// The string representation of the token.
+    /// Returns `TokenizerError::TokenNotFound` if the vocabulary lacks the audio
+    /// special tokens, or `TokenizerError::InvalidConfig` if the configuration
+    /// is invalid (see [`AudioConfig::validate`]); the tokenizer is then left
+    /// unchanged.
     ///
-    /// # Errors
+    /// # Examples
     ///
-    /// Returns an error if the token ID is out of vocabulary range.
-    pub fn id_to_piece(&self, token_id: u32) -> Result<String> {
-        // Validate token ID is within vocabulary range
-        if token_id as usize >= self.vocab_size {
-            return Err(TokenizerError::InvalidConfig(format!(
-                "Token ID {} is out of vocabulary range (0-{})",
-                token_id,
-                self.vocab_size - 1
-            )));
-        }
-
-        self.decode(&[token_id], SpecialTokenPolicy::Keep)
+    /// ```rust,no_run
+    /// # use tekken::audio::{AudioConfig, AudioSpectrogramConfig};
+    /// # use tekken::tekkenizer::Tekkenizer;
+    /// let mut tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let spectrogram_config = AudioSpectrogramConfig::new(128, 160, 400)?;
+    /// tokenizer.set_audio_config(AudioConfig::new(16000, 25.0, spectrogram_config, None)?)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn set_audio_config(&mut self, config: AudioConfig) -> Result<()> {
+        self.audio_encoder = Some(build_audio_encoder(&config, &self.special_token_index)?);
+        self.audio_config = Some(config);
+        self.fingerprint = OnceLock::new();
+        Ok(())
     }
 
-    /// Converts a single token ID to its byte representation.
-    ///
-    /// # Arguments
-    ///
-    /// * `token_id` - The token ID (u32) to convert
-    /// * `special_token_policy` - How to handle special tokens
-    ///
-    /// # Returns
+    /// Returns this tokenizer with a replaced audio configuration.
     ///
-    /// The byte representation of the token.
+    /// See [`Tekkenizer::set_audio_config`].
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - Token ID is invalid (out of vocabulary range)
-    /// - Special token policy is Raise and token is special
-    /// - Token cannot be converted to bytes (should be rare)
-    #[allow(clippy::cast_possible_truncation)]
-    pub fn id_to_byte_piece(
-        &self,
-        token_id: u32,
-        special_token_policy: SpecialTokenPolicy,
-    ) -> Result<Vec<u8>> {
-        // Validate token ID is within vocabulary range
-        if token_id as usize >= self.vocab_size {
-            return Err(TokenizerError::InvalidConfig(format!(
-                "Token ID {} is out of vocabulary range (0-{})",
-                token_id,
-                self.vocab_size - 1
-            )));
-        }
-
-        #[allow(clippy::cast_possible_truncation)]
-        if token_id < self.num_special_tokens as u32 {
-            match special_token_policy {
-                SpecialTokenPolicy::Keep => Ok(self.special_tokens[token_id as usize]
-                    .token_str
-                    .as_bytes()
-                    .to_vec()),
-                SpecialTokenPolicy::Raise => Err(TokenizerError::SpecialTokenPolicy(format!(
-                    "Token ID {} is a special token ({}), cannot convert to byte piece with Raise policy",
-                    token_id, self.special_tokens[token_id as usize].token_str
-                ))),
-                SpecialTokenPolicy::Ignore => Ok(vec![]),
-            }
-        } else {
-            #[allow(clippy::cast_possible_truncation)]
-            let shifted_id = token_id - self.num_special_tokens as u32;
-
-            // Try to decode the token - this might fail for some byte tokens
-            match self.tekkenizer.decode(vec![shifted_id]) {
-                Ok(decoded) => Ok(decoded.as_bytes().to_vec()),
-                Err(e) => {
-                    // If decoding fails, try to get the raw bytes from the vocabulary
-                    // This can happen with incomplete UTF-8 sequences in individual byte tokens
-                    if let Some(vocab_entry) = self.vocab.get(token_id as usize) {
-                        Ok(vocab_entry.as_bytes().to_vec())
-                    } else {
-                        Err(TokenizerError::Tokenizers(format!(
-                            "Failed to decode token ID {token_id} to bytes: {e:?}. Token may represent invalid UTF-8 sequence.",
-                        )))
-                    }
-                }
-            }
-        }
+    /// Returns `TokenizerError::TokenNotFound` if the vocabulary lacks the audio
+    /// special tokens, or `TokenizerError::InvalidConfig` if the configuration
+    /// is invalid.
+    pub fn with_audio_config(mut self, config: AudioConfig) -> Result<Self> {
+        self.set_audio_config(config)?;
+        Ok(self)
     }
 
-    /// Encodes audio data into tokens that can be mixed with text tokens.
+    /// Encodes an image into tokens and normalized pixel values.
     ///
-    /// This method converts audio waveforms into token sequences using mel-scale
-    /// spectrogram processing and audio-specific special tokens.
+    /// The image is preprocessed as described in [`crate::image`] and covered by
+    /// one `[IMG]` token per patch, with `[IMG_BREAK]` after each row and
+    /// `[IMG_END]` after the last.
     ///
     /// # Arguments
     ///
-    /// * `audio` - The audio data to encode
+    /// * `image` - The image to encode
     ///
     /// # Returns
     ///
-    /// An `AudioEncoding` containing the token sequence and processed audio data.
+    /// An `ImageEncoding` containing the token sequence and pixel values.
     ///
     /// # Errors
     ///
-    /// Returns an error if:
-    /// - Audio encoder is not configured
-    /// - Audio processing fails
-    /// - Resampling fails
+    /// Returns an error if the image encoder is not configured or the image is
+    /// empty.
     ///
     /// # Examples
     ///
     /// ```rust,no_run
     /// # use tekken::tekkenizer::Tekkenizer;
-    /// # use tekken::audio::Audio;
+    /// # use tekken::image::Image;
     /// # let tokenizer = Tekkenizer::from_file("tekken.json")?;
-    /// let audio = Audio::from_file("audio.wav")?;
-    /// let encoding = tokenizer.encode_audio(audio)?;
-    /// println!("Audio encoded to {} tokens", encoding.tokens.len());
+    /// let image = Image::from_file("photo.png")?;
+    /// let encoding = tokenizer.encode_image(&image)?;
+    /// println!("Image encoded to {} tokens", encoding.tokens.len());
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    pub fn encode_audio(&self, audio: Audio) -> Result<AudioEncoding> {
-        match &self.audio_encoder {
-            Some(encoder) => encoder.encode(audio),
-            None => Err(TokenizerError::Audio(
-                "Audio encoder not configured".to_string(),
+    #[cfg(feature = "image")]
+    pub fn encode_image(&self, image: &Image) -> Result<ImageEncoding> {
+        match &self.image_encoder {
+            Some(encoder) => encoder.encode(image),
+            None => Err(TokenizerError::Image(
+                "Image encoder not configured".to_string(),
             )),
         }
     }
 
-    /// Checks if this tokenizer instance supports audio processing.
+    /// Checks if this tokenizer instance supports image processing.
     ///
-    /// Audio support depends on the tokenizer configuration containing audio settings
-    /// and the presence of required audio special tokens.
+    /// Without the `image` feature this is always `false`, although
+    /// [`Tekkenizer::image_config`] still reports the configuration loaded from
+    /// the file.
     ///
     /// # Returns
     ///
-    /// `true` if audio encoding is available, `false` otherwise.
+    /// `true` if image encoding is available, `false` otherwise.
     #[must_use]
-    pub fn has_audio_support(&self) -> bool {
-        self.audio_encoder.is_some()
+    pub fn has_image_support(&self) -> bool {
+        cfg!(feature = "image") && self.image_encoder.is_some()
     }
 
-    /// Returns a reference to the audio configuration, if available.
+    /// Returns a reference to the image configuration, if available.
+    #[must_use]
+    pub fn image_config(&self) -> Option<&ImageConfig> {
+        self.image_config.as_ref()
+    }
+
+    /// Replaces the image configuration, rebuilding the image encoder.
     ///
-    /// # Returns
+    /// The encoder uses this tokenizer's `[IMG]`, `[IMG_BREAK]` and `[IMG_END]`
+    /// special tokens. The configuration is part of [`Tekkenizer::fingerprint`].
     ///
-    /// An optional reference to the `AudioConfig` if audio support is configured.
-    /// Returns `None` if the tokenizer was not initialized with audio support.
-    #[must_use]
-    pub fn audio_config(&self) -> Option<&AudioConfig> {
-        self.audio_config.as_ref()
+    /// # Arguments
+    ///
+    /// * `config` - The new image configuration
+    ///
+    /// # Errors
+    ///
+    /// Returns `TokenizerError::InvalidConfig` if the configuration is invalid, or
+    /// `TokenizerError::TokenNotFound` if the vocabulary lacks the image special
+    /// tokens; the tokenizer is then left unchanged.
+    pub fn set_image_config(&mut self, config: ImageConfig) -> Result<()> {
+        self.image_encoder = Some(build_image_encoder(&config, &self.special_token_index)?);
+        self.image_config = Some(config);
+        self.fingerprint = OnceLock::new();
+        Ok(())
+    }
+
+    /// Returns this tokenizer with a replaced image configuration.
+    ///
+    /// See [`Tekkenizer::set_image_config`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the configuration is invalid or the vocabulary lacks
+    /// the image special tokens.
+    pub fn with_image_config(mut self, config: ImageConfig) -> Result<Self> {
+        self.set_image_config(config)?;
+        Ok(self)
+    }
+}
+
+/// Builds the audio encoder for `config` from the `[AUDIO]` and `[BEGIN_AUDIO]`
+/// special tokens.
+fn build_audio_encoder(
+    config: &AudioConfig,
+    special_token_index: &SpecialTokenIndex,
+) -> Result<AudioEncoder> {
+    let audio_token_id = special_token_index
+        .rank_of(SpecialTokens::Audio)
+        .ok_or_else(|| TokenizerError::TokenNotFound("Audio token not found".to_string()))?;
+    let begin_audio_token_id = special_token_index
+        .rank_of(SpecialTokens::BeginAudio)
+        .ok_or_else(|| TokenizerError::TokenNotFound("BeginAudio token not found".to_string()))?;
+    config.validate()?;
+
+    Ok(AudioEncoder::new(
+        config.clone(),
+        vocab_index(audio_token_id),
+        vocab_index(begin_audio_token_id),
+    ))
+}
+
+/// Builds the image encoder for `config` from the `[IMG]`, `[IMG_BREAK]` and
+/// `[IMG_END]` special tokens.
+fn build_image_encoder(
+    config: &ImageConfig,
+    special_token_index: &SpecialTokenIndex,
+) -> Result<ImageEncoder> {
+    config.validate()?;
+    let token_id = |token: SpecialTokens| {
+        special_token_index
+            .rank_of(token)
+            .map(vocab_index)
+            .ok_or_else(|| {
+                TokenizerError::TokenNotFound(format!("{} token not found", token.as_str()))
+            })
+    };
+
+    Ok(ImageEncoder::new(
+        config.clone(),
+        token_id(SpecialTokens::Img)?,
+        token_id(SpecialTokens::ImgBreak)?,
+        token_id(SpecialTokens::ImgEnd)?,
+    ))
+}
+
+/// Step-wise decoder behind [`Tekkenizer::decode_iter`] and the streaming decoders.
+struct DecodeIter<'a> {
+    tokenizer: &'a Tekkenizer,
+    tokens: &'a [u32],
+    policy: SpecialTokenPolicy,
+    invalid_policy: InvalidTokenPolicy,
+    pos: usize,
+    carry: Utf8Carry,
+    // Replacement for an invalid token, emitted after the U+FFFD flushed before it
+    queued: Option<char>,
+    done: bool,
+}
+
+impl<'a> DecodeIter<'a> {
+    fn new(
+        tokenizer: &'a Tekkenizer,
+        tokens: &'a [u32],
+        policy: SpecialTokenPolicy,
+        invalid_policy: InvalidTokenPolicy,
+    ) -> Self {
+        Self {
+            tokenizer,
+            tokens,
+            policy,
+            invalid_policy,
+            pos: 0,
+            carry: Utf8Carry::default(),
+            queued: None,
+            done: false,
+        }
+    }
+
+    /// Decodes the next token.
+    ///
+    /// Yields the character completed by previously held-back bytes (if any) and
+    /// the text that follows it; both may be empty for ignored special tokens or
+    /// tokens that only hold part of a character.
+    fn step(&mut self) -> Option<Result<(Option<char>, &'a str)>> {
+        if self.done {
+            return None;
+        }
+        if let Some(ch) = self.queued.take() {
+            return Some(Ok((Some(ch), "")));
+        }
+
+        let Some(&token_id) = self.tokens.get(self.pos) else {
+            self.done = true;
+            return match self.carry.finish() {
+                Ok(()) => None,
+                Err(e) => Some(Err(e)),
+            };
+        };
+
+        let result = if self.tokenizer.is_special_token(token_id) {
+            self.carry.finish().and_then(|()| match self.policy {
+                SpecialTokenPolicy::Raise => {
+                    let group: Vec<u32> = self.tokens[self.pos..]
+                        .iter()
+                        .copied()
+                        .take_while(|&t| self.tokenizer.is_special_token(t))
+                        .collect();
+                    Err(TokenizerError::SpecialTokenPolicy(format!(
+                        "Decoding tokens that contain special tokens ({group:?}) is not allowed",
+                    )))
+                }
+                SpecialTokenPolicy::Keep => self
+                    .tokenizer
+                    .special_token(token_id)
+                    .map(|token| (None, token.token_str.as_str())),
+                SpecialTokenPolicy::Ignore => Ok((None, "")),
+            })
+        } else {
+            match self.tokenizer.regular_token_bytes(token_id) {
+                Ok(bytes) => self.carry.feed(bytes),
+                Err(e) => match self.invalid_policy {
+                    InvalidTokenPolicy::Error => Err(e),
+                    InvalidTokenPolicy::Skip => Ok((self.carry.flush(), "")),
+                    InvalidTokenPolicy::Replace(ch) => match self.carry.flush() {
+                        Some(flushed) => {
+                            self.queued = Some(ch);
+                            Ok((Some(flushed), ""))
+                        }
+                        None => Ok((Some(ch), "")),
+                    },
+                },
+            }
+        };
+
+        self.pos += 1;
+        if result.is_err() {
+            self.done = true;
+        }
+        Some(result)
+    }
+}
+
+impl<'a> Iterator for DecodeIter<'a> {
+    type Item = Result<Cow<'a, str>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.step()? {
+                Err(e) => return Some(Err(e)),
+                Ok((None, "")) => {}
+                Ok((None, text)) => return Some(Ok(Cow::Borrowed(text))),
+                Ok((Some(ch), text)) => {
+                    let mut piece = String::with_capacity(ch.len_utf8() + text.len());
+                    piece.push(ch);
+                    piece.push_str(text);
+                    return Some(Ok(Cow::Owned(piece)));
+                }
+            }
+        }
+    }
+}
+
+/// Reassembles UTF-8 characters whose bytes are split across token boundaries.
+///
+/// Byte-level BPE tokens do not necessarily end on character boundaries, so an
+/// incomplete trailing sequence (at most 3 bytes) is held back until the bytes of
+/// the following token complete it.
+#[derive(Debug, Default)]
+pub(crate) struct Utf8Carry {
+    buf: [u8; 4],
+    len: usize,
+}
+
+impl Utf8Carry {
+    /// Feeds the bytes of the next token.
+    ///
+    /// Returns the character completed by the held-back bytes (if any) and the
+    /// longest valid UTF-8 prefix of `bytes` that follows it.
+    pub(crate) fn feed<'a>(&mut self, mut bytes: &'a [u8]) -> Result<(Option<char>, &'a str)> {
+        let mut completed = None;
+
+        if self.len > 0 {
+            let width = utf8_char_width(self.buf[0]);
+            let take = (width - self.len).min(bytes.len());
+            self.buf[self.len..self.len + take].copy_from_slice(&bytes[..take]);
+            self.len += take;
+            bytes = &bytes[take..];
+
+            if self.len < width {
+                return Ok((None, ""));
+            }
+
+            let ch = std::str::from_utf8(&self.buf[..width]).map_err(invalid_utf8)?;
+            completed = ch.chars().next();
+            self.len = 0;
+        }
+
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Ok((completed, text)),
+            Err(e) if e.error_len().is_none() => {
+                let valid = e.valid_up_to();
+                let rest = &bytes[valid..];
+                self.buf[..rest.len()].copy_from_slice(rest);
+                self.len = rest.len();
+                let text = std::str::from_utf8(&bytes[..valid]).map_err(invalid_utf8)?;
+                Ok((completed, text))
+            }
+            Err(e) => Err(invalid_utf8(e)),
+        }
+    }
+
+    /// Drops the bytes of an incomplete character, returning U+FFFD in their
+    /// place if there were any.
+    pub(crate) fn flush(&mut self) -> Option<char> {
+        (self.len > 0).then(|| {
+            self.len = 0;
+            char::REPLACEMENT_CHARACTER
+        })
+    }
+
+    /// Checks that no incomplete character is pending and resets the state.
+    pub(crate) fn finish(&mut self) -> Result<()> {
+        if self.len > 0 {
+            let pending = self.buf[..self.len].to_vec();
+            self.len = 0;
+            return Err(TokenizerError::Tokenizers(format!(
+                "Unable to decode into a valid UTF-8 string: incomplete sequence {pending:?}"
+            )));
+        }
+        Ok(())
     }
 }
 
-/// Processes vocabulary tokens into a format suitable for tiktoken encoding.
+/// Returns the encoded width of a UTF-8 character from its leading byte.
+fn utf8_char_width(lead: u8) -> usize {
+    match lead {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => 1,
+    }
+}
+
+/// Replaces the bytes of an incomplete character at the end of `bytes` with U+FFFD.
+fn flush_incomplete(bytes: &mut Vec<u8>) {
+    let tail = bytes.len().saturating_sub(3);
+    if let Some(lead) = (tail..bytes.len()).rev().find(|&i| bytes[i] & 0xC0 != 0x80)
+        && utf8_char_width(bytes[lead]) > bytes.len() - lead
+    {
+        bytes.truncate(lead);
+        bytes.extend_from_slice("\u{FFFD}".as_bytes());
+    }
+}
+
+fn invalid_utf8(e: std::str::Utf8Error) -> TokenizerError {
+    TokenizerError::Tokenizers(format!("Unable to decode into a valid UTF-8 string: {e}"))
+}
+
+/// Decompresses gzip or zstd data, detected by its magic bytes.
+///
+/// Returns `None` for uncompressed data.
+pub(crate) fn decompress(bytes: &[u8]) -> Result<Option<Vec<u8>>> {
+    const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+    const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+    if bytes.starts_with(&GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        {
+            use std::io::Read;
+            let mut decompressed = Vec::new();
+            flate2::read::MultiGzDecoder::new(bytes).read_to_end(&mut decompressed)?;
+            return Ok(Some(decompressed));
+        }
+        #[cfg(not(feature = "gzip"))]
+        return Err(TokenizerError::UnsupportedFormat(
+            "Tokenizer data is gzip-compressed; enable the `gzip` feature".to_string(),
+        ));
+    }
+
+    if bytes.starts_with(&ZSTD_MAGIC) {
+        #[cfg(feature = "zstd")]
+        return Ok(Some(zstd::stream::decode_all(bytes)?));
+        #[cfg(not(feature = "zstd"))]
+        return Err(TokenizerError::UnsupportedFormat(
+            "Tokenizer data is zstd-compressed; enable the `zstd` feature".to_string(),
+        ));
+    }
+
+    Ok(None)
+}
+
+/// Token bytes of a vocabulary entry, as stored in a tokenizer file.
+pub(crate) trait EntryBytes {
+    /// Returns the raw token bytes.
+    fn token_bytes(&self) -> Result<Vec<u8>>;
+}
+
+/// Base64 token bytes, as in `tekken.json` and `.tiktoken` files.
+impl EntryBytes for &str {
+    fn token_bytes(&self) -> Result<Vec<u8>> {
+        Ok(general_purpose::STANDARD.decode(self)?)
+    }
+}
+
+/// Raw token bytes, as in binary vocabularies.
+impl EntryBytes for &[u8] {
+    fn token_bytes(&self) -> Result<Vec<u8>> {
+        Ok(self.to_vec())
+    }
+}
+
+/// Processes vocabulary tokens into a format suitable for BPE encoding.
 ///
 /// This function converts token information into the mergeable ranks format
-/// required by tiktoken, validating byte tokens and ensuring rank contiguity.
+/// required by the BPE engine, validating byte tokens and ensuring rank contiguity.
+///
+/// Like `_reload_mergeable_ranks` in mistral-common, only the first `max_vocab`
+/// entries are kept, so a `vocab_size` below the length of the vocabulary keeps
+/// ranks `0..max_vocab` and never looks at the entries past the cutoff. The kept
+/// entries must be listed in rank order, since keeping a prefix of an unordered
+/// vocabulary would keep different tokens than the ranks suggest.
 ///
 /// # Arguments
 ///
-/// * `vocab` - The vocabulary tokens with their byte representations
+/// * `vocab` - The vocabulary entries as `(rank, token bytes)` pairs, in rank
+///   order
 /// * `max_vocab` - Maximum number of vocabulary tokens to process
 ///
 /// # Returns
 ///
-/// A hash map from byte sequences to token ranks (u32 for tiktoken).
+/// A hash map from byte sequences to token ranks.
 #[allow(clippy::cast_possible_truncation)]
-fn reload_mergeable_ranks(
-    vocab: Vec<TokenInfo>,
-    max_vocab: usize,
-) -> Result<FxHashMap<Vec<u8>, u32>> {
-    let vocab = if vocab.len() > max_vocab {
-        vocab.into_iter().take(max_vocab).collect()
-    } else {
-        vocab
-    };
-
+fn reload_mergeable_ranks<B, I>(vocab: I, max_vocab: usize) -> Result<FxHashMap<Vec<u8>, u32>>
+where
+    B: EntryBytes,
+    I: Iterator<Item = (usize, B)>,
+{
     let mut ranks = FxHashMap::default();
+    // Ranks that map to bytes already seen, with the rank that claimed them first
+    let mut duplicate_bytes = Vec::new();
+    // First entry whose rank differs from its position in the vocabulary
+    let mut misplaced = None;
 
-    for token in vocab {
-        let token_bytes = general_purpose::STANDARD.decode(&token.token_bytes)?;
+    for (position, (rank, entry)) in vocab.take(max_vocab).enumerate() {
+        if rank != position && misplaced.is_none() {
+            misplaced = Some((position, rank));
+        }
+        let token_bytes = entry.token_bytes()?;
 
         // Verify byte tokens for first 256 tokens
         #[allow(clippy::cast_possible_truncation)]
-        if token.rank < 256 && token_bytes != vec![token.rank as u8] {
+        if rank < 256 && token_bytes != vec![rank as u8] {
             return Err(TokenizerError::InvalidConfig(format!(
-                "Expected byte token at rank {} to be [{}], got {:?}",
-                token.rank, token.rank, token_bytes
+                "Expected byte token at rank {rank} to be [{rank}], got {token_bytes:?}"
             )));
         }
 
         #[allow(clippy::cast_possible_truncation)]
-        ranks.insert(token_bytes, token.rank as u32);
+        if let Some(previous) = ranks.insert(token_bytes, rank as u32) {
+            duplicate_bytes.push((previous as usize, rank));
+        }
     }
 
     // Verify ranks are contiguous
-    #[allow(clippy::cast_possible_truncation)]
-    let expected_ranks: std::collections::HashSet<_> = (0..ranks.len() as u32).collect();
-    let actual_ranks: std::collections::HashSet<_> = ranks.values().copied().collect();
+    let mut seen = vec![false; ranks.len()];
+    let mut duplicate_ranks = Vec::new();
+    let mut out_of_range = Vec::new();
+    for &rank in ranks.values() {
+        match seen.get_mut(rank as usize) {
+            Some(true) => duplicate_ranks.push(rank as usize),
+            Some(slot) => *slot = true,
+            None => out_of_range.push(rank as usize),
+        }
+    }
+    if !duplicate_bytes.is_empty() || !out_of_range.is_empty() || !duplicate_ranks.is_empty() {
+        let missing: Vec<usize> = (0..seen.len()).filter(|&rank| !seen[rank]).collect();
+        duplicate_ranks.sort_unstable();
+        duplicate_ranks.dedup();
+        out_of_range.sort_unstable();
+        return Err(TokenizerError::InvalidConfig(rank_diagnostics(
+            &missing,
+            &duplicate_ranks,
+            &out_of_range,
+            &duplicate_bytes,
+        )));
+    }
+    if let Some((position, rank)) = misplaced {
+        return Err(TokenizerError::InvalidConfig(format!(
+            "Vocabulary entry {position} has rank {rank}; entries must be listed in rank order"
+        )));
+    }
+
+    Ok(ranks)
+}
+
+/// Orders vocabulary entries by rank and drops repeated ranks and token bytes,
+/// returning the token bytes of the compacted ranks `0..n`.
+///
+/// Every dropped entry and closed gap is described in `repairs`.
+fn compact_ranks<'a, I>(vocab: I, repairs: &mut Vec<String>) -> Vec<&'a str>
+where
+    I: Iterator<Item = (usize, &'a str)>,
+{
+    let mut entries: Vec<(usize, &str)> = vocab.collect();
+    entries.sort_by_key(|&(rank, _)| rank);
+
+    let mut compacted = Vec::with_capacity(entries.len());
+    let mut first_rank_of: HashMap<&str, usize> = HashMap::with_capacity(entries.len());
+    let mut previous_rank = None;
+    for (rank, token_bytes) in entries {
+        if previous_rank == Some(rank) {
+            repairs.push(format!("Skipped a repeated entry for rank {rank}"));
+            continue;
+        }
+        if let Some(first_rank) = first_rank_of.get(token_bytes) {
+            repairs.push(format!(
+                "Skipped rank {rank}, whose token bytes repeat rank {first_rank}"
+            ));
+            previous_rank = Some(rank);
+            continue;
+        }
+
+        let expected = previous_rank.map_or(0, |previous| previous + 1);
+        if rank > expected {
+            repairs.push(format!(
+                "Ranks {expected}..{rank} are missing; later ranks are shifted down by {}",
+                rank - compacted.len()
+            ));
+        }
+        first_rank_of.insert(token_bytes, rank);
+        compacted.push(token_bytes);
+        previous_rank = Some(rank);
+    }
+    compacted
+}
+
+/// Orders vocabulary entries by rank, dropping repeated ranks and turning
+/// entries that repeat earlier token bytes into placeholders.
+///
+/// Returns the token bytes of every rank up to the last one, `None` for
+/// placeholders. Every dropped entry and placeholder is described in `repairs`.
+///
+/// # Errors
+///
+/// Returns an error if a byte token, rank `0..256`, would become a placeholder.
+fn fill_rank_gaps<'a, I>(vocab: I, repairs: &mut Vec<String>) -> Result<Vec<Option<&'a str>>>
+where
+    I: Iterator<Item = (usize, &'a str)>,
+{
+    let mut entries: Vec<(usize, &str)> = vocab.collect();
+    entries.sort_by_key(|&(rank, _)| rank);
+
+    let mut filled = Vec::with_capacity(entries.len());
+    let mut first_rank_of: HashMap<&str, usize> = HashMap::with_capacity(entries.len());
+    let mut previous_rank = None;
+    for (rank, token_bytes) in entries {
+        if previous_rank == Some(rank) {
+            repairs.push(format!("Skipped a repeated entry for rank {rank}"));
+            continue;
+        }
+        let expected = previous_rank.map_or(0, |previous| previous + 1);
+        previous_rank = Some(rank);
+        let first_rank = first_rank_of.get(token_bytes).copied();
+        // BPE merges start from single bytes, so byte tokens cannot be replaced
+        let first_placeholder = if rank > expected {
+            Some(expected)
+        } else {
+            first_rank.map(|_| rank)
+        };
+        if let Some(first_placeholder) = first_placeholder
+            && first_placeholder < 256
+        {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Byte token rank {first_placeholder} is missing or repeated and cannot be replaced by a placeholder"
+            )));
+        }
+        if rank > expected {
+            repairs.push(format!(
+                "Ranks {expected}..{rank} are missing; filled with placeholder tokens"
+            ));
+            filled.resize(rank, None);
+        }
+        if let Some(first_rank) = first_rank {
+            repairs.push(format!(
+                "Rank {rank} repeats the token bytes of rank {first_rank}; replaced with a placeholder token"
+            ));
+            filled.push(None);
+            continue;
+        }
+        first_rank_of.insert(token_bytes, rank);
+        filled.push(Some(token_bytes));
+    }
+    Ok(filled)
+}
 
-    if expected_ranks != actual_ranks {
-        return Err(TokenizerError::InvalidConfig(
-            "Vocabulary ranks are not contiguous".to_string(),
+/// Lowers `vocab_size` to the number of tokens available, recording the repair.
+fn reduce_vocab_size(config: &mut TekkenConfig, available: usize, repairs: &mut Vec<String>) {
+    if config.default_vocab_size > available {
+        repairs.push(format!(
+            "Reduced vocab_size from {} to {available} to match the remaining tokens",
+            config.default_vocab_size
         ));
+        config.default_vocab_size = available;
     }
+}
 
-    Ok(ranks)
+/// Orders vocabulary entries by rank, leaving `None` at the ranks marked as
+/// placeholders.
+///
+/// # Errors
+///
+/// Returns an error unless the entries and placeholders together hold every
+/// rank up to the last exactly once, or if a byte token is marked.
+fn place_entries<B, I>(vocab: I, placeholder_ranks: &[usize]) -> Result<Vec<Option<B>>>
+where
+    I: ExactSizeIterator<Item = (usize, B)>,
+{
+    let len = vocab.len() + placeholder_ranks.len();
+    let mut filled: Vec<Option<B>> = std::iter::repeat_with(|| None).take(len).collect();
+    let mut marked = vec![false; len];
+    for &rank in placeholder_ranks {
+        if rank < 256 || rank >= len || std::mem::replace(&mut marked[rank], true) {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Placeholder rank {rank} is a byte token, repeated or beyond the token count {len}"
+            )));
+        }
+    }
+    // With as many slots as entries and placeholders, every rank is then filled
+    for (rank, bytes) in vocab {
+        if rank >= len || marked[rank] || filled[rank].is_some() {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "Vocabulary rank {rank} is repeated, marked as a placeholder or beyond the token count {len}"
+            )));
+        }
+        filled[rank] = Some(bytes);
+    }
+    Ok(filled)
+}
+
+/// Describes why vocabulary ranks are not contiguous.
+fn rank_diagnostics(
+    missing: &[usize],
+    duplicate_ranks: &[usize],
+    out_of_range: &[usize],
+    duplicate_bytes: &[(usize, usize)],
+) -> String {
+    const SHOWN: usize = 10;
+    fn list<T: std::fmt::Debug>(items: &[T]) -> String {
+        let shown: Vec<String> = items
+            .iter()
+            .take(SHOWN)
+            .map(|item| format!("{item:?}"))
+            .collect();
+        if items.len() > SHOWN {
+            format!("{}, … ({} total)", shown.join(", "), items.len())
+        } else {
+            shown.join(", ")
+        }
+    }
+
+    let mut problems = Vec::new();
+    if !missing.is_empty() {
+        problems.push(format!("missing ranks {}", list(missing)));
+    }
+    if !duplicate_ranks.is_empty() {
+        problems.push(format!("duplicate ranks {}", list(duplicate_ranks)));
+    }
+    if !out_of_range.is_empty() {
+        problems.push(format!(
+            "ranks beyond the token count {}",
+            list(out_of_range)
+        ));
+    }
+    if !duplicate_bytes.is_empty() {
+        problems.push(format!(
+            "token bytes repeated at (first rank, repeat rank) {}",
+            list(duplicate_bytes)
+        ));
+    }
+    format!(
+        "Vocabulary ranks are not contiguous: {}",
+        problems.join("; ")
+    )
+}
+
+/// Converts an index into a loaded vocabulary, a rank or token ID, to `u32`.
+///
+/// Tokenizers with more than `u32::MAX` entries are rejected at load, so such
+/// indices always fit.
+#[allow(clippy::cast_possible_truncation)]
+const fn vocab_index(index: usize) -> u32 {
+    index as u32
+}
+
+/// Returns `n` if `token_str` is the `<SPECIAL_n>` placeholder name.
+fn placeholder_rank(token_str: &str) -> Option<usize> {
+    token_str
+        .strip_prefix("<SPECIAL_")?
+        .strip_suffix('>')?
+        .parse()
+        .ok()
 }
 
 /// Returns the default special tokens for older tokenizer versions.
@@ -824,7 +3328,7 @@ fn reload_mergeable_ranks(
 ///
 /// A vector of special token information for legacy tokenizers.
 #[allow(clippy::too_many_lines)]
-fn get_deprecated_special_tokens() -> Vec<SpecialTokenInfo> {
+pub(crate) fn get_deprecated_special_tokens() -> Vec<SpecialTokenInfo> {
     vec![
         SpecialTokenInfo {
             rank: 0,
@@ -0,0 +1,423 @@
+//! Golden test-vector harness for verifying tokenizer parity.
+//!
+//! This module is available with the `test-utils` feature. It loads JSON fixture
+//! files exported from the Python implementation and checks that a [`Tekkenizer`]
+//! reproduces them exactly, so downstream crates can verify compatibility of their
+//! tokenizer files in their own CI.
+//!
+//! It also provides [`Tekkenizer::tiny_for_tests`], a small built-in tokenizer for
+//! unit tests that should not depend on a `tekken.json` fixture, and
+//! [`synthetic_tokenizer`], which generates vocabularies of realistic size for
+//! performance and correctness tests.
+//!
+//! # Fixture Format
+//!
+//! A fixture file is a JSON array mixing encode and decode vectors:
+//!
+//! ```json
+//! [
+//!   { "text": "Hello, world!", "expected_tokens": [22177, 1044, 4304, 1033] },
+//!   { "text": "Hi", "expected_tokens": [1, 30680, 2], "add_bos": true, "add_eos": true },
+//!   { "tokens": [1, 22177, 2], "expected_text": "<s>Hello</s>" },
+//!   { "tokens": [1, 22177, 2], "expected_text": "Hello", "special_token_policy": "ignore" }
+//! ]
+//! ```
+//!
+//! `add_bos`/`add_eos` default to `false` and `special_token_policy` defaults to
+//! `"keep"`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+use crate::audio::{AudioConfig, AudioSpectrogramConfig};
+use crate::bpe::SplitMix64;
+use crate::config::{ModelData, TokenizerVersion};
+use crate::errors::Result;
+use crate::special_tokens::SpecialTokenPolicy;
+use crate::tekkenizer::Tekkenizer;
+
+/// Pre-tokenization pattern of [`Tekkenizer::tiny_for_tests`].
+const TINY_PATTERN: &str = r"[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}|\s+|.";
+
+/// Merged tokens of [`Tekkenizer::tiny_for_tests`], ranked after the 256 byte tokens.
+const TINY_TOKENS: [&str; 9] = [
+    "he", "ll", "llo", "hello", " w", "or", " wor", "ld", " world",
+];
+
+impl Tekkenizer {
+    /// Builds a small deterministic tokenizer for unit tests.
+    ///
+    /// The tokenizer is a V7 tokenizer with the 256 byte tokens, a handful of
+    /// merges (so `"hello world"` encodes to two tokens), 100 special tokens with
+    /// the V7 defaults, and audio support at 16 kHz and 12.5 frames per second.
+    /// It is built in memory, so tests need no `tekken.json` fixture.
+    ///
+    /// # Panics
+    ///
+    /// Never in practice; the built-in configuration is always valid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::tiny_for_tests();
+    /// let tokens = tokenizer.encode("hello world", true, false)?;
+    /// assert_eq!(tokens.len(), 3);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    #[must_use]
+    pub fn tiny_for_tests() -> Self {
+        let audio = AudioSpectrogramConfig::new(128, 160, 400)
+            .and_then(|spectrogram| AudioConfig::new(16_000, 12.5, spectrogram, None))
+            .expect("built-in audio configuration is valid");
+        let model_data = ModelData::builder(TINY_PATTERN, TokenizerVersion::V7)
+            .with_byte_tokens()
+            .with_tokens(TINY_TOKENS)
+            .with_num_special_tokens(100)
+            .with_audio(audio)
+            .build()
+            .expect("built-in model data is valid");
+        let bytes = serde_json::to_vec(&model_data).expect("model data serializes");
+        Self::from_bytes(&bytes).expect("built-in tokenizer is valid")
+    }
+}
+
+/// Pre-tokenization pattern of [`synthetic_tokenizer`], the one of the released
+/// V7 `tekken.json` files.
+const SYNTHETIC_PATTERN: &str = r"[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]*[\p{Ll}\p{Lm}\p{Lo}\p{M}]+|[^\r\n\p{L}\p{N}]?[\p{Lu}\p{Lt}\p{Lm}\p{Lo}\p{M}]+[\p{Ll}\p{Lm}\p{Lo}\p{M}]*|\p{N}| ?[^\s\p{L}\p{N}]+[\r\n/]*|\s*[\r\n]+|\s+(?!\S)|\s+";
+
+/// Number of special tokens of [`synthetic_tokenizer`], as in the released files.
+const SYNTHETIC_NUM_SPECIAL_TOKENS: usize = 1000;
+
+/// Longest merged token of [`synthetic_tokenizer`], in bytes.
+const SYNTHETIC_MAX_TOKEN_LEN: usize = 24;
+
+/// Lowercase letters seeding the synthetic vocabulary, most frequent first.
+const SYNTHETIC_LETTERS: [&str; 32] = [
+    "e", "t", "a", "o", "i", "n", "s", "h", "r", "d", "l", "c", "u", "m", "w", "f", "g", "y", "p",
+    "b", "v", "k", "j", "x", "q", "z", "é", "è", "à", "ü", "ö", "ñ",
+];
+
+/// Punctuation the synthetic vocabulary combines into code- and prose-like tokens.
+const SYNTHETIC_PUNCTUATION: [&str; 16] = [
+    ".", ",", "(", ")", ":", ";", "\"", "'", "{", "}", "[", "]", "-", "=", "/", "_",
+];
+
+/// Generates a deterministic tokenizer with a vocabulary of realistic size.
+///
+/// The real `tekken.json` files are hundreds of megabytes, too large to ship as
+/// test fixtures. This tokenizer has the V7 pattern and special tokens, the 256
+/// byte tokens and merged tokens grown the way BPE training grows them: every
+/// merged token concatenates two earlier tokens, preferring frequent ones, into
+/// lowercase, capitalized and space-prefixed words, punctuation runs and
+/// indentation. Frequent words therefore encode to few tokens while any text
+/// still round-trips.
+///
+/// # Arguments
+///
+/// * `seed` - Seed of the generator; the same seed always gives the same vocabulary
+/// * `vocab_size` - Total vocabulary size, including the 1000 special tokens
+///
+/// # Panics
+///
+/// Panics if `vocab_size` leaves no room for the 256 byte tokens after the
+/// special tokens, i.e. is below 1256.
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::test_utils::synthetic_tokenizer;
+///
+/// let tokenizer = synthetic_tokenizer(42, 32_768);
+/// assert_eq!(tokenizer.vocab_size(), 32_768);
+///
+/// let text = "The tokenizer handles prose, code() and café menus.";
+/// let tokens = tokenizer.encode(text, false, false)?;
+/// assert!(tokens.len() < text.len());
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[must_use]
+pub fn synthetic_tokenizer(seed: u64, vocab_size: usize) -> Tekkenizer {
+    let num_merges = vocab_size
+        .checked_sub(SYNTHETIC_NUM_SPECIAL_TOKENS + 256)
+        .unwrap_or_else(|| {
+            panic!(
+                "A synthetic vocabulary needs at least {} tokens, got {vocab_size}",
+                SYNTHETIC_NUM_SPECIAL_TOKENS + 256
+            )
+        });
+
+    let model_data = ModelData::builder(SYNTHETIC_PATTERN, TokenizerVersion::V7)
+        .with_byte_tokens()
+        .with_tokens(synthetic_merges(seed, num_merges))
+        .with_num_special_tokens(SYNTHETIC_NUM_SPECIAL_TOKENS)
+        .build()
+        .expect("synthetic model data is valid");
+    let bytes = serde_json::to_vec(&model_data).expect("model data serializes");
+    Tekkenizer::from_bytes(&bytes).expect("synthetic tokenizer is valid")
+}
+
+/// Generates `count` distinct merged tokens in rank order.
+fn synthetic_merges(seed: u64, count: usize) -> Vec<String> {
+    let mut rng = SplitMix64::new(seed);
+    let mut known: HashSet<String> = (0..=0x7Fu8)
+        .map(|byte| char::from(byte).to_string())
+        .collect();
+    let mut merges = Vec::with_capacity(count);
+    // Lowercase tokens that words are assembled from; the accented letters are
+    // two bytes, so they are merges themselves
+    let mut words: Vec<String> = SYNTHETIC_LETTERS
+        .iter()
+        .map(|&letter| letter.to_string())
+        .collect();
+    for letter in SYNTHETIC_LETTERS.iter().filter(|letter| letter.len() > 1) {
+        if merges.len() < count {
+            known.insert((*letter).to_string());
+            merges.push((*letter).to_string());
+        }
+    }
+
+    while merges.len() < count {
+        let roll = rng.next_f64();
+        let token = if roll < 0.5 {
+            format!("{}{}", pick(&mut rng, &words), pick(&mut rng, &words))
+        } else if roll < 0.8 {
+            format!(" {}", pick(&mut rng, &words))
+        } else if roll < 0.9 {
+            let word = pick(&mut rng, &words);
+            let mut chars = word.chars();
+            let capitalized: String = chars
+                .next()
+                .into_iter()
+                .flat_map(char::to_uppercase)
+                .chain(chars)
+                .collect();
+            if rng.next_f64() < 0.5 {
+                format!(" {capitalized}")
+            } else {
+                capitalized
+            }
+        } else if roll < 0.95 {
+            let prefix = if rng.next_f64() < 0.3 { " " } else { "" };
+            let suffix = if rng.next_f64() < 0.2 { "\n" } else { "" };
+            format!(
+                "{prefix}{}{}{suffix}",
+                pick(&mut rng, &SYNTHETIC_PUNCTUATION),
+                pick(&mut rng, &SYNTHETIC_PUNCTUATION)
+            )
+        } else {
+            #[allow(clippy::cast_possible_truncation)]
+            let width = 2 + (rng.next_u64() % 15) as usize;
+            if rng.next_f64() < 0.8 {
+                " ".repeat(width)
+            } else {
+                "\n".repeat(width.min(4))
+            }
+        };
+
+        if token.len() > SYNTHETIC_MAX_TOKEN_LEN || !known.insert(token.clone()) {
+            continue;
+        }
+        if token.chars().all(char::is_lowercase) {
+            words.push(token.clone());
+        }
+        merges.push(token);
+    }
+    merges
+}
+
+/// Picks an element, favouring the front of the list like BPE favours frequent
+/// pairs.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn pick<'a, S: AsRef<str>>(rng: &mut SplitMix64, items: &'a [S]) -> &'a str {
+    let index = (items.len() as f64 * rng.next_f64().powi(3)) as usize;
+    items[index.min(items.len() - 1)].as_ref()
+}
+
+/// An expected text-to-tokens encoding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EncodeVector {
+    /// Input text.
+    pub text: String,
+    /// Token IDs the reference implementation produced.
+    pub expected_tokens: Vec<u32>,
+    /// Whether a BOS token is added.
+    #[serde(default)]
+    pub add_bos: bool,
+    /// Whether an EOS token is added.
+    #[serde(default)]
+    pub add_eos: bool,
+}
+
+/// An expected tokens-to-text decoding.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodeVector {
+    /// Input token IDs.
+    pub tokens: Vec<u32>,
+    /// Text the reference implementation produced.
+    pub expected_text: String,
+    /// How special tokens are handled while decoding.
+    #[serde(default = "default_policy")]
+    pub special_token_policy: SpecialTokenPolicy,
+}
+
+fn default_policy() -> SpecialTokenPolicy {
+    SpecialTokenPolicy::Keep
+}
+
+/// A single golden test vector.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TestVector {
+    /// Text that must encode to the expected tokens.
+    Encode(EncodeVector),
+    /// Tokens that must decode to the expected text.
+    Decode(DecodeVector),
+}
+
+/// A test vector the tokenizer did not reproduce.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParityFailure {
+    /// Position of the vector in the fixture.
+    pub index: usize,
+    /// The vector that failed.
+    pub vector: TestVector,
+    /// What the tokenizer produced instead (or the error it returned).
+    pub actual: String,
+}
+
+impl fmt::Display for ParityFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.vector {
+            TestVector::Encode(v) => write!(
+                f,
+                "vector {}: encode({:?}) expected {:?}, got {}",
+                self.index, v.text, v.expected_tokens, self.actual
+            ),
+            TestVector::Decode(v) => write!(
+                f,
+                "vector {}: decode({:?}) expected {:?}, got {}",
+                self.index, v.tokens, v.expected_text, self.actual
+            ),
+        }
+    }
+}
+
+/// Outcome of checking a set of test vectors.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParityReport {
+    /// Number of vectors reproduced exactly.
+    pub passed: usize,
+    /// Vectors that were not reproduced.
+    pub failures: Vec<ParityFailure>,
+}
+
+impl ParityReport {
+    /// Returns `true` if every vector was reproduced.
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Loads golden test vectors from a JSON fixture file.
+///
+/// # Arguments
+///
+/// * `path` - Path to the fixture file
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or is not a valid fixture.
+pub fn load_test_vectors<P: AsRef<Path>>(path: P) -> Result<Vec<TestVector>> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Checks every test vector against the tokenizer.
+///
+/// # Arguments
+///
+/// * `tokenizer` - The tokenizer under test
+/// * `vectors` - Golden test vectors
+///
+/// # Returns
+///
+/// A report of which vectors were reproduced.
+#[must_use]
+pub fn check_test_vectors(tokenizer: &Tekkenizer, vectors: &[TestVector]) -> ParityReport {
+    let mut report = ParityReport::default();
+
+    for (index, vector) in vectors.iter().enumerate() {
+        let actual = match vector {
+            TestVector::Encode(v) => match tokenizer.encode(&v.text, v.add_bos, v.add_eos) {
+                Ok(tokens) if tokens == v.expected_tokens => None,
+                Ok(tokens) => Some(format!("{tokens:?}")),
+                Err(e) => Some(format!("error: {e}")),
+            },
+            TestVector::Decode(v) => match tokenizer.decode(&v.tokens, v.special_token_policy) {
+                Ok(text) if text == v.expected_text => None,
+                Ok(text) => Some(format!("{text:?}")),
+                Err(e) => Some(format!("error: {e}")),
+            },
+        };
+
+        match actual {
+            None => report.passed += 1,
+            Some(actual) => report.failures.push(ParityFailure {
+                index,
+                vector: vector.clone(),
+                actual,
+            }),
+        }
+    }
+
+    report
+}
+
+/// Asserts that the tokenizer reproduces every vector in a fixture file.
+///
+/// # Arguments
+///
+/// * `tokenizer` - The tokenizer under test
+/// * `path` - Path to the fixture file
+///
+/// # Panics
+///
+/// Panics if the fixture cannot be loaded or any vector is not reproduced,
+/// listing every failing vector.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use tekken::Tekkenizer;
+/// use tekken::test_utils::assert_parity;
+///
+/// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+/// assert_parity(&tokenizer, "tests/fixtures/python_vectors.json");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn assert_parity<P: AsRef<Path>>(tokenizer: &Tekkenizer, path: P) {
+    let path = path.as_ref();
+    let vectors = load_test_vectors(path)
+        .unwrap_or_else(|e| panic!("Failed to load test vectors from {}: {e}", path.display()));
+    let report = check_test_vectors(tokenizer, &vectors);
+
+    if !report.is_ok() {
+        let failures: Vec<String> = report.failures.iter().map(ToString::to_string).collect();
+        panic!(
+            "{} of {} test vectors from {} failed:\n{}",
+            report.failures.len(),
+            vectors.len(),
+            path.display(),
+            failures.join("\n")
+        );
+    }
+}
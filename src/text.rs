@@ -0,0 +1,272 @@
+//! Text-only tokenization core.
+//!
+//! [`TextTokenizer`] encodes and decodes plain text with a BPE vocabulary and
+//! special tokens. It is the part of the crate that builds without the `std`
+//! feature, for inference on embedded devices: there is no file I/O, JSON
+//! parsing, audio or image support, so the vocabulary is passed in as token
+//! bytes, e.g. decoded from a blob embedded with `include_bytes!`.
+//!
+//! With `std`, text is split into pieces exactly like `Tekkenizer` splits it.
+//! Without it, patterns may not use lookaround other than the `\s+(?!\S)`
+//! alternative of the released tokenizer patterns.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use tekken::special_tokens::SpecialTokenPolicy;
+//! use tekken::text::TextTokenizer;
+//!
+//! let mut vocab: Vec<Vec<u8>> = (0..=u8::MAX).map(|byte| vec![byte]).collect();
+//! vocab.extend(["he", "ll", "llo", "hello"].map(|token| token.as_bytes().to_vec()));
+//! let special_tokens = ["<unk>", "<s>", "</s>"].map(String::from).to_vec();
+//!
+//! let tokenizer = TextTokenizer::new(vocab, special_tokens, r"\p{L}+|\s+|.")?;
+//! let tokens = tokenizer.encode("hello", true, false)?;
+//! assert_eq!(tokens, vec![1, 3 + 259]);
+//! assert_eq!(tokenizer.decode(&tokens, SpecialTokenPolicy::Keep)?, "<s>hello");
+//! # Ok::<(), tekken::TokenizerError>(())
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::bpe::{BytePairEncoder, RankMap};
+use crate::errors::{Result, TokenizerError};
+use crate::special_tokens::{SpecialTokenIndex, SpecialTokenPolicy, SpecialTokens};
+use crate::token_id::TokenId;
+
+/// Text tokenizer over a BPE vocabulary, available without `std`.
+///
+/// Token IDs follow the Tekken layout: special tokens take IDs
+/// `0..num_special_tokens` and vocabulary token `rank` has ID
+/// `num_special_tokens + rank`.
+#[derive(Debug, Clone)]
+pub struct TextTokenizer {
+    bpe: BytePairEncoder,
+    // Special token strings, indexed by ID
+    special_tokens: Vec<String>,
+    special_token_index: SpecialTokenIndex,
+}
+
+impl TextTokenizer {
+    /// Creates a tokenizer from vocabulary token bytes and special tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `vocab` - Token bytes in rank order; the first 256 must be the single
+    ///   bytes `0..=255` in order
+    /// * `special_tokens` - Special token strings in ID order, e.g. `<unk>`,
+    ///   `<s>`, `</s>`
+    /// * `pattern` - Pretokenization regex pattern; empty selects a cl100k-style
+    ///   default
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The first 256 tokens are not the single bytes in order
+    /// - A token appears more than once
+    /// - A special token appears more than once
+    /// - The vocabulary does not fit in `u32` token IDs
+    /// - The pattern is not a valid regex
+    pub fn new(vocab: Vec<Vec<u8>>, special_tokens: Vec<String>, pattern: &str) -> Result<Self> {
+        let vocab_size = special_tokens.len() + vocab.len();
+        if u32::try_from(vocab_size).is_err() {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "vocab_size ({vocab_size}) must fit in a u32 token ID"
+            )));
+        }
+        for (index, token) in special_tokens.iter().enumerate() {
+            if special_tokens[..index].contains(token) {
+                return Err(TokenizerError::InvalidConfig(format!(
+                    "Duplicate special token: {token}"
+                )));
+            }
+        }
+
+        // BPE merges start from single bytes, so all 256 byte tokens are required
+        let mut ranks = RankMap::default();
+        ranks.reserve(vocab.len());
+        for (rank, bytes) in vocab.into_iter().enumerate() {
+            #[allow(clippy::cast_possible_truncation)]
+            if rank < 256 && bytes != [rank as u8] {
+                return Err(TokenizerError::InvalidConfig(format!(
+                    "Expected byte token at rank {rank} to be [{rank}], got {bytes:?}"
+                )));
+            }
+            if let Some(previous) = ranks.get(&bytes) {
+                return Err(TokenizerError::InvalidConfig(format!(
+                    "Token {bytes:?} has ranks {previous} and {rank}"
+                )));
+            }
+            #[allow(clippy::cast_possible_truncation)]
+            ranks.insert(bytes, rank as u32);
+        }
+        if ranks.len() < 256 {
+            return Err(TokenizerError::InvalidConfig(format!(
+                "The vocabulary must start with the 256 byte tokens, got {} tokens",
+                ranks.len()
+            )));
+        }
+
+        let special_token_index = SpecialTokenIndex::new(
+            special_tokens
+                .iter()
+                .enumerate()
+                .map(|(index, token)| (token.as_str(), index)),
+        );
+        Ok(Self {
+            bpe: BytePairEncoder::new(ranks, pattern)?,
+            special_tokens,
+            special_token_index,
+        })
+    }
+
+    /// Returns the total vocabulary size including special tokens.
+    #[must_use]
+    pub fn vocab_size(&self) -> usize {
+        self.special_tokens.len() + self.bpe.len()
+    }
+
+    /// Returns the number of special tokens.
+    #[must_use]
+    pub fn num_special_tokens(&self) -> usize {
+        self.special_tokens.len()
+    }
+
+    /// Returns the effective pretokenization pattern.
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        self.bpe.pattern()
+    }
+
+    /// Returns whether a token ID is a special token.
+    #[must_use]
+    pub fn is_special_token(&self, token_id: u32) -> bool {
+        (token_id as usize) < self.special_tokens.len()
+    }
+
+    /// Returns the ID of a special token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tokenizer has no such special token.
+    pub fn id_of(&self, token: SpecialTokens) -> Result<u32> {
+        self.special_token_index
+            .rank_of(token)
+            .map(vocab_index)
+            .ok_or_else(|| {
+                TokenizerError::TokenNotFound(format!("Unknown control token: '{token}'"))
+            })
+    }
+
+    /// Encodes text into token IDs.
+    ///
+    /// Special token strings in the text are encoded as ordinary text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The input text to tokenize
+    /// * `add_beginning_of_sequence` - Whether to prepend the BOS token
+    /// * `add_end_of_sequence` - Whether to append the EOS token
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if BOS/EOS is requested but not a special token, or
+    /// pretokenization fails.
+    pub fn encode(
+        &self,
+        text: &str,
+        add_beginning_of_sequence: bool,
+        add_end_of_sequence: bool,
+    ) -> Result<Vec<u32>> {
+        let mut tokens = Vec::new();
+        if add_beginning_of_sequence {
+            tokens.push(self.id_of(SpecialTokens::Bos)?);
+        }
+        let eos_id = if add_end_of_sequence {
+            Some(self.id_of(SpecialTokens::Eos)?)
+        } else {
+            None
+        };
+
+        let offset = TokenId::try_from(self.special_tokens.len())?;
+        self.bpe.encode_ordinary(text, offset, &mut tokens)?;
+        tokens.extend(eos_id);
+        Ok(tokens)
+    }
+
+    /// Decodes token IDs into text.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokens` - The token IDs to decode
+    /// * `special_token_policy` - How to handle special tokens
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A token ID is not part of the vocabulary
+    /// - A run of regular tokens does not form valid UTF-8
+    /// - Special tokens are encountered with the `Raise` policy
+    pub fn decode(
+        &self,
+        tokens: &[u32],
+        special_token_policy: SpecialTokenPolicy,
+    ) -> Result<String> {
+        let num_special_tokens = self.special_tokens.len();
+        let mut text = String::new();
+        let mut run = Vec::new();
+
+        for (index, &token_id) in tokens.iter().enumerate() {
+            if let Some(rank) = TokenId::from(token_id).to_rank(num_special_tokens) {
+                let bytes =
+                    self.bpe
+                        .token_bytes(rank as usize)
+                        .ok_or(TokenizerError::TokenOutOfRange {
+                            token_id,
+                            vocab_size: self.vocab_size(),
+                        })?;
+                run.extend_from_slice(bytes);
+                continue;
+            }
+
+            flush_run(&mut run, &mut text)?;
+            match special_token_policy {
+                SpecialTokenPolicy::Keep => text.push_str(&self.special_tokens[token_id as usize]),
+                SpecialTokenPolicy::Ignore => {}
+                SpecialTokenPolicy::Raise => {
+                    let group: Vec<u32> = tokens[index..]
+                        .iter()
+                        .copied()
+                        .take_while(|&t| self.is_special_token(t))
+                        .collect();
+                    return Err(TokenizerError::SpecialTokenPolicy(format!(
+                        "Decoding tokens that contain special tokens ({group:?}) is not allowed",
+                    )));
+                }
+            }
+        }
+        flush_run(&mut run, &mut text)?;
+        Ok(text)
+    }
+}
+
+/// Converts an index into the vocabulary, a rank or token ID, to `u32`.
+///
+/// [`TextTokenizer::new`] rejects vocabularies with more than `u32::MAX`
+/// entries, so such indices always fit.
+#[allow(clippy::cast_possible_truncation)]
+const fn vocab_index(index: usize) -> u32 {
+    index as u32
+}
+
+/// Appends a run of regular token bytes to `text` and clears it.
+fn flush_run(run: &mut Vec<u8>, text: &mut String) -> Result<()> {
+    let decoded = core::str::from_utf8(run).map_err(|e| {
+        TokenizerError::Tokenizers(format!("Unable to decode into a valid UTF-8 string: {e}"))
+    })?;
+    text.push_str(decoded);
+    run.clear();
+    Ok(())
+}
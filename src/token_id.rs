@@ -0,0 +1,190 @@
+//! Typed token IDs.
+//!
+//! Tekken vocabularies place their special tokens first, so the ID of a regular
+//! token is its BPE rank shifted by the number of special tokens. [`TokenId`]
+//! keeps shifted IDs apart from unshifted ranks; the tokenizer performs every
+//! shift between the two through it.
+//!
+//! The tokenizer APIs take and return plain `u32` token IDs; wrap them with
+//! `TokenId::from` where the distinction matters. `TokenId` converts to and
+//! from `u32` losslessly and is `#[repr(transparent)]`. [`convert_ids`] turns
+//! IDs into the integer type a model runtime takes, e.g. `i64`.
+
+use alloc::format;
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::fmt;
+use core::ops::Add;
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{Result, TokenizerError};
+
+/// A token ID in a Tekken vocabulary, counting special tokens first.
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::TokenId;
+///
+/// // With 1000 special tokens, rank 72 is token 1072
+/// let id = TokenId::checked_from_rank(72, 1000).unwrap();
+/// assert_eq!(id, TokenId::new(1072));
+/// assert_eq!(TokenId::checked_from_rank(u32::MAX, 1000), None);
+/// assert_eq!(id.to_rank(1000), Some(72));
+///
+/// // Special tokens have no rank
+/// assert_eq!(TokenId::new(1).to_rank(1000), None);
+/// assert_eq!(u32::from(id), 1072);
+/// ```
+#[repr(transparent)]
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct TokenId(u32);
+
+impl TokenId {
+    /// Wraps a raw token ID.
+    #[must_use]
+    pub const fn new(id: u32) -> Self {
+        Self(id)
+    }
+
+    /// Returns the raw token ID.
+    #[must_use]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the token ID as an index into the vocabulary.
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Creates the ID of the regular token with the given BPE rank.
+    ///
+    /// # Arguments
+    ///
+    /// * `rank` - The unshifted BPE rank
+    /// * `num_special_tokens` - Number of special tokens in the vocabulary
+    ///
+    /// # Returns
+    ///
+    /// The shifted ID, or `None` if it does not fit in a `u32`.
+    #[must_use]
+    pub const fn checked_from_rank(rank: u32, num_special_tokens: usize) -> Option<Self> {
+        if num_special_tokens > u32::MAX as usize {
+            return None;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        match rank.checked_add(num_special_tokens as u32) {
+            Some(id) => Some(Self(id)),
+            None => None,
+        }
+    }
+
+    /// Returns the BPE rank of a regular token.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_special_tokens` - Number of special tokens in the vocabulary
+    ///
+    /// # Returns
+    ///
+    /// The unshifted rank, or `None` if the ID belongs to a special token.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn to_rank(self, num_special_tokens: usize) -> Option<u32> {
+        if self.is_special(num_special_tokens) {
+            return None;
+        }
+        // Smaller than this ID, so it fits in a `u32`
+        Some(self.0 - num_special_tokens as u32)
+    }
+
+    /// Checks whether the ID belongs to one of the first `num_special_tokens` tokens.
+    #[must_use]
+    pub const fn is_special(self, num_special_tokens: usize) -> bool {
+        self.index() < num_special_tokens
+    }
+}
+
+impl Add<u32> for TokenId {
+    type Output = Self;
+
+    /// Returns the ID `rank` tokens after this one, e.g. the ID of a rank
+    /// when added to the ID of rank 0.
+    fn add(self, rank: u32) -> Self {
+        Self(self.0 + rank)
+    }
+}
+
+impl From<u32> for TokenId {
+    fn from(id: u32) -> Self {
+        Self(id)
+    }
+}
+
+impl TryFrom<usize> for TokenId {
+    type Error = TokenizerError;
+
+    fn try_from(index: usize) -> Result<Self> {
+        u32::try_from(index).map(Self).map_err(|_| {
+            TokenizerError::InvalidConfig(format!("Token index {index} does not fit in a u32"))
+        })
+    }
+}
+
+impl From<TokenId> for u32 {
+    fn from(id: TokenId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for TokenId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Converts token IDs to another integer type.
+///
+/// Converting to a type of the same width, such as `i32`, reuses the
+/// allocation of `ids`.
+///
+/// # Arguments
+///
+/// * `ids` - The token IDs
+///
+/// # Returns
+///
+/// The IDs as `T`, in order.
+///
+/// # Errors
+///
+/// Returns an error if an ID does not fit in `T`.
+///
+/// # Examples
+///
+/// ```rust
+/// use tekken::token_id::convert_ids;
+///
+/// let ids: Vec<i64> = convert_ids(vec![1, 1072, 150_999])?;
+/// assert_eq!(ids, vec![1_i64, 1072, 150_999]);
+/// assert!(convert_ids::<u16>(vec![150_999]).is_err());
+/// # Ok::<(), tekken::TokenizerError>(())
+/// ```
+pub fn convert_ids<T: TryFrom<u32>>(ids: Vec<u32>) -> Result<Vec<T>> {
+    ids.into_iter()
+        .map(|id| {
+            T::try_from(id).map_err(|_| {
+                TokenizerError::InvalidConfig(format!(
+                    "Token ID {id} does not fit in {}",
+                    type_name::<T>()
+                ))
+            })
+        })
+        .collect()
+}
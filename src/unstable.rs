@@ -0,0 +1,176 @@
+//! Encoding of prompts that a model will continue.
+//!
+//! BPE merges can cross the point where a prompt ends: `"hello wor"` encodes its
+//! end as `" wor"`, while `"hello world"` uses a single `" world"` token. A
+//! completion server that feeds the prompt tokens to the model therefore asks it
+//! to continue from a segmentation it would never have produced, and constrained
+//! decoding that starts from those tokens rejects the natural continuations.
+//!
+//! [`Tekkenizer::encode_with_unstable`] follows tiktoken's
+//! `encode_with_unstable`: it encodes the prompt up to its last pretokenized
+//! piece, and lists the token sequences that can stand for that unstable tail
+//! once more text follows it. The server can then let the model start with any
+//! of these completions, e.g. by constraining the first tokens it samples.
+
+use std::collections::BTreeSet;
+
+use crate::errors::Result;
+use crate::tekkenizer::{EncodeOptions, Tekkenizer};
+
+/// A prompt split into tokens that extensions of it keep, and the possible
+/// encodings of its tail, from [`Tekkenizer::encode_with_unstable`].
+///
+/// # Fields
+///
+/// * `tokens` - Tokens of the text before `unstable_start`
+/// * `unstable_start` - Byte offset in the text where the unstable tail begins
+/// * `completions` - Token sequences that start with the bytes of the tail;
+///   each is a possible start of the encoding of the tail followed by more text
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UnstableEncoding {
+    pub tokens: Vec<u32>,
+    pub unstable_start: usize,
+    pub completions: BTreeSet<Vec<u32>>,
+}
+
+impl Tekkenizer {
+    /// Encodes a prompt whose end may still merge with the text that follows.
+    ///
+    /// The last pretokenized piece of `text` is left unencoded, together with
+    /// any whitespace-only pieces before a whitespace-only last piece, since
+    /// appended text can change how they are split and merged. For this tail
+    /// the possible completions are collected like tiktoken does:
+    ///
+    /// - every token whose bytes start with the tail
+    /// - for every token that starts inside the tail and runs past its end, the
+    ///   tokens that cover the tail when the tail's head is encoded with it
+    /// - the tail with a trailing whitespace character encoded on its own
+    ///
+    /// Like tiktoken's, the set is a heuristic: it covers the ways common
+    /// continuations encode, but not necessarily every one.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The prompt
+    /// * `add_beginning_of_sequence` - Whether to prepend the BOS token
+    ///
+    /// # Returns
+    ///
+    /// The stable tokens, where the tail starts and its possible completions.
+    /// Text without pieces has an empty tail and no completions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if BOS is requested but not present in the vocabulary,
+    /// or pretokenization fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tekken::tekkenizer::Tekkenizer;
+    ///
+    /// let tokenizer = Tekkenizer::from_file("tekken.json")?;
+    /// let prompt = tokenizer.encode_with_unstable("fn main() { println!(\"hello wor", true)?;
+    /// assert_eq!(
+    ///     prompt.tokens,
+    ///     tokenizer.encode("fn main() { println!(\"hello", true, false)?
+    /// );
+    /// // Among the completions is the single token of " world"
+    /// let world = tokenizer.encode(" world", false, false)?;
+    /// assert!(prompt.completions.contains(&world));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn encode_with_unstable(
+        &self,
+        text: &str,
+        add_beginning_of_sequence: bool,
+    ) -> Result<UnstableEncoding> {
+        let unstable_start = self.unstable_start(text)?;
+        let tokens = self.encode(&text[..unstable_start], add_beginning_of_sequence, false)?;
+        let tail = &text.as_bytes()[unstable_start..];
+        let mut completions = BTreeSet::new();
+        if tail.is_empty() {
+            return Ok(UnstableEncoding {
+                tokens,
+                unstable_start,
+                completions,
+            });
+        }
+
+        // Tokens that contain the whole tail
+        let offset = self.rank_offset();
+        completions.extend(
+            self.ranks_with_prefix(tail)
+                .iter()
+                .map(|&rank| vec![(offset + rank).get()]),
+        );
+
+        // Tokens that start inside the tail and continue past its end
+        for split in 1..tail.len() {
+            let (head, rest) = tail.split_at(split);
+            for &rank in self.ranks_with_prefix(rest) {
+                let mut possibility = head.to_vec();
+                possibility.extend_from_slice(self.regular_token_bytes((offset + rank).get())?);
+                let mut encoded = Vec::new();
+                match std::str::from_utf8(&possibility) {
+                    Ok(possibility) => {
+                        self.encode_into(possibility, &mut encoded, EncodeOptions::default())?;
+                    }
+                    Err(_) => self.encode_piece(&possibility, &mut encoded),
+                }
+                encoded.truncate(self.tokens_covering(&encoded, tail.len())?);
+                completions.insert(encoded);
+            }
+        }
+
+        // Trailing whitespace may be split off the tail, e.g. before a word
+        if let Some(last) = text[unstable_start..].chars().next_back()
+            && last.is_whitespace()
+            && last.len_utf8() < tail.len()
+        {
+            let cut = tail.len() - last.len_utf8();
+            let mut encoded = Vec::new();
+            self.encode_piece(&tail[..cut], &mut encoded);
+            self.encode_piece(&tail[cut..], &mut encoded);
+            completions.insert(encoded);
+        }
+
+        Ok(UnstableEncoding {
+            tokens,
+            unstable_start,
+            completions,
+        })
+    }
+
+    /// Byte offset where the last piece of `text` starts, moved back over the
+    /// whitespace-only pieces before a whitespace-only last piece.
+    fn unstable_start(&self, text: &str) -> Result<usize> {
+        let starts = self.piece_starts(text)?;
+        let Some(mut index) = starts.len().checked_sub(1) else {
+            return Ok(text.len());
+        };
+        let is_whitespace = |index: usize| {
+            let end = starts.get(index + 1).copied().unwrap_or(text.len());
+            text[starts[index]..end].chars().all(char::is_whitespace)
+        };
+        // A run such as "\n" + " \n" may be split differently once it grows
+        if is_whitespace(index) {
+            while index > 0 && is_whitespace(index - 1) {
+                index -= 1;
+            }
+        }
+        Ok(starts[index])
+    }
+
+    /// Number of leading `tokens` needed to cover `len` bytes.
+    fn tokens_covering(&self, tokens: &[u32], len: usize) -> Result<usize> {
+        let mut covered = 0;
+        for (count, &token) in tokens.iter().enumerate() {
+            if covered >= len {
+                return Ok(count);
+            }
+            covered += self.regular_token_bytes(token)?.len();
+        }
+        Ok(tokens.len())
+    }
+}
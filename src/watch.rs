@@ -0,0 +1,257 @@
+//! Hot reloading of tokenizer files.
+//!
+//! Long-running services can roll out a new `tekken.json` without a restart:
+//! [`WatchedTekkenizer`] watches the file and, when it changes, loads it again
+//! and atomically swaps the new tokenizer in. Requests that already hold the
+//! previous tokenizer keep using it until they drop it, so a single request is
+//! never encoded with two vocabularies.
+//!
+//! Reloads go through the same loaders as startup, so checksum sidecars (see
+//! [`crate::integrity`]) are verified and compressed or binary files are
+//! accepted. A reload that fails keeps the current tokenizer. Writing the new
+//! file next to the old one and renaming it into place avoids reloading a
+//! partially written file; changes to the checksum sidecar also trigger a
+//! reload, so the file and its sidecar can be replaced in either order.
+//!
+//! Requires the `notify` feature.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use tekken::watch::WatchedTekkenizer;
+//!
+//! let tokenizer = WatchedTekkenizer::new("tekken.json")?.with_on_reload(|result| match result {
+//!     Ok(tokenizer) => eprintln!("reloaded, {} tokens", tokenizer.vocab_size()),
+//!     Err(error) => eprintln!("keeping the current tokenizer: {error}"),
+//! });
+//!
+//! // In each request handler
+//! let tokens = tokenizer.load().encode("Hello world", true, false)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use arc_swap::{ArcSwap, Guard};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use crate::errors::Result;
+use crate::integrity::checksum_path;
+use crate::tekkenizer::Tekkenizer;
+
+/// Callback told about the outcome of reloads triggered by file changes.
+type ReloadCallback = Box<dyn Fn(&Result<Arc<Tekkenizer>>) + Send + Sync>;
+
+/// A tokenizer that is reloaded whenever its file changes.
+///
+/// Cloning the [`WatchedTekkenizer::handle`] shares the tokenizer with other
+/// threads; the file is watched for as long as the `WatchedTekkenizer` lives.
+pub struct WatchedTekkenizer {
+    shared: Arc<Shared>,
+    _watcher: RecommendedWatcher,
+}
+
+/// State shared with the watcher thread.
+struct Shared {
+    path: PathBuf,
+    current: Arc<ArcSwap<Tekkenizer>>,
+    reloads: AtomicU64,
+    /// Held while reloading, so concurrent reloads swap a change in once.
+    reloading: Mutex<()>,
+    on_reload: Mutex<Option<ReloadCallback>>,
+}
+
+impl WatchedTekkenizer {
+    /// Loads a tokenizer file and starts watching it for changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to the tokenizer file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be loaded (see
+    /// [`Tekkenizer::from_file`]) or its directory cannot be watched.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let shared = Arc::new(Shared {
+            current: Arc::new(ArcSwap::from_pointee(load(&path)?)),
+            path,
+            reloads: AtomicU64::new(0),
+            reloading: Mutex::new(()),
+            on_reload: Mutex::new(None),
+        });
+
+        // Watching the directory rather than the file follows replacements by
+        // rename, which give the path a new inode
+        let directory = match shared.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let names = [
+            shared.path.file_name().map(OsString::from),
+            checksum_path(&shared.path).file_name().map(OsString::from),
+        ];
+        let state = Arc::clone(&shared);
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) if is_relevant(&event, &names) => state.reload_on_change(),
+                Ok(_) => {}
+                Err(error) => log::warn!("Watching {} failed: {error}", state.path.display()),
+            })?;
+        watcher.watch(&directory, RecursiveMode::NonRecursive)?;
+        // Pick up changes made between the first load and the start of watching
+        shared.reload_on_change();
+
+        Ok(Self {
+            shared,
+            _watcher: watcher,
+        })
+    }
+
+    /// Sets a callback told about every reload triggered by a file change.
+    ///
+    /// The callback runs on the watcher thread with the newly loaded tokenizer,
+    /// or the error that kept the current one in place. Changes that leave the
+    /// tokenizer identical (same [`Tekkenizer::fingerprint`]) are not reported.
+    /// Without a callback, failed reloads are logged as warnings.
+    #[must_use]
+    pub fn with_on_reload<F>(self, callback: F) -> Self
+    where
+        F: Fn(&Result<Arc<Tekkenizer>>) + Send + Sync + 'static,
+    {
+        *self
+            .shared
+            .on_reload
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the current tokenizer.
+    ///
+    /// The guard is cheap to take and meant to be held for the duration of a
+    /// single request; use [`WatchedTekkenizer::load_full`] to keep it longer.
+    #[must_use]
+    pub fn load(&self) -> Guard<Arc<Tekkenizer>> {
+        self.shared.current.load()
+    }
+
+    /// Returns an owned reference to the current tokenizer.
+    #[must_use]
+    pub fn load_full(&self) -> Arc<Tekkenizer> {
+        self.shared.current.load_full()
+    }
+
+    /// Returns the swappable tokenizer, to share it with other threads.
+    ///
+    /// The handle sees every reload for as long as this `WatchedTekkenizer`
+    /// lives, and keeps the last loaded tokenizer afterwards.
+    #[must_use]
+    pub fn handle(&self) -> Arc<ArcSwap<Tekkenizer>> {
+        Arc::clone(&self.shared.current)
+    }
+
+    /// Returns the path of the watched file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.shared.path
+    }
+
+    /// Returns how many times a changed tokenizer was swapped in.
+    #[must_use]
+    pub fn reloads(&self) -> u64 {
+        self.shared.reloads.load(Ordering::Acquire)
+    }
+
+    /// Loads the file again now, without waiting for a change to be noticed.
+    ///
+    /// # Returns
+    ///
+    /// Whether the tokenizer changed and was swapped in.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be loaded; the current tokenizer is
+    /// kept.
+    pub fn reload(&self) -> Result<bool> {
+        Ok(self.shared.reload()?.is_some())
+    }
+}
+
+impl std::fmt::Debug for WatchedTekkenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WatchedTekkenizer")
+            .field("path", &self.shared.path)
+            .field("reloads", &self.reloads())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Shared {
+    /// Loads the file and swaps it in if it differs from the current tokenizer.
+    fn reload(&self) -> Result<Option<Arc<Tekkenizer>>> {
+        let _reloading = self
+            .reloading
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let tokenizer = load(&self.path)?;
+        if tokenizer.fingerprint() == self.current.load().fingerprint() {
+            return Ok(None);
+        }
+        let tokenizer = Arc::new(tokenizer);
+        self.current.store(Arc::clone(&tokenizer));
+        self.reloads.fetch_add(1, Ordering::AcqRel);
+        Ok(Some(tokenizer))
+    }
+
+    /// Reloads after a file change and reports the outcome.
+    fn reload_on_change(&self) {
+        let result = match self.reload() {
+            Ok(Some(tokenizer)) => Ok(tokenizer),
+            Ok(None) => return,
+            Err(error) => Err(error),
+        };
+        let on_reload = self
+            .on_reload
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        match (&*on_reload, &result) {
+            (Some(callback), _) => callback(&result),
+            (None, Err(error)) => log::warn!(
+                "Reloading {} failed, keeping the current tokenizer: {error}",
+                self.path.display()
+            ),
+            (None, Ok(_)) => {}
+        }
+    }
+}
+
+/// Whether an event may have changed the tokenizer file or its sidecar.
+fn is_relevant(event: &Event, names: &[Option<OsString>]) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+        && event.paths.iter().any(|path| {
+            names
+                .iter()
+                .flatten()
+                .any(|name| path.file_name() == Some(name))
+        })
+}
+
+/// Loads a tokenizer file in any format [`Tekkenizer`] can load from disk.
+fn load(path: &Path) -> Result<Tekkenizer> {
+    #[cfg(feature = "zstd")]
+    {
+        use std::io::Read;
+
+        let mut magic = [0; crate::binary::MAGIC.len()];
+        let read = std::fs::File::open(path).and_then(|mut file| file.read_exact(&mut magic));
+        if read.is_ok() && crate::binary::is_binary(&magic) {
+            return Tekkenizer::from_binary_file(path);
+        }
+    }
+    Tekkenizer::from_file(path)
+}
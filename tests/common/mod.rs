@@ -0,0 +1,14 @@
+//! Fixtures shared by the integration tests.
+
+use tekken::config::{ModelData, TokenizerVersion};
+
+/// A small V7 tokenizer with 100 special tokens: the 256 byte tokens, then
+/// `tokens` from rank 256.
+pub fn model_data<T: AsRef<[u8]>>(tokens: impl IntoIterator<Item = T>) -> ModelData {
+    ModelData::builder(r"\p{L}+|\s+|.", TokenizerVersion::V7)
+        .with_byte_tokens()
+        .with_tokens(tokens)
+        .with_num_special_tokens(100)
+        .build()
+        .unwrap()
+}
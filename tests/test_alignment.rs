@@ -0,0 +1,114 @@
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_alignment_ascii() {
+    let tokenizer = get_tokenizer();
+    let text = "Hello, world!";
+    let tokens = tokenizer.encode(text, true, true).unwrap();
+    let alignments = tokenizer.token_alignments(text, &tokens).unwrap();
+
+    assert_eq!(alignments.len(), tokens.len());
+    assert_eq!(alignments[0].piece, "<s>");
+    assert_eq!(alignments[0].char_range, 0..0);
+    assert_eq!(alignments[1].piece, "Hello");
+    assert_eq!(alignments[1].char_range, 0..5);
+    assert_eq!(alignments.last().unwrap().piece, "</s>");
+    assert_eq!(alignments.last().unwrap().char_range, 13..13);
+
+    let regular: String = alignments[1..alignments.len() - 1]
+        .iter()
+        .map(|a| a.piece.as_str())
+        .collect();
+    assert_eq!(regular, text);
+}
+
+#[test]
+fn test_alignment_multibyte_grouping() {
+    let tokenizer = get_tokenizer();
+    let text = "a🚀b日本";
+    let tokens = tokenizer.encode(text, false, false).unwrap();
+    let alignments = tokenizer.token_alignments(text, &tokens).unwrap();
+
+    let mut covered = 0;
+    for alignment in &alignments {
+        // Char ranges are contiguous or shared with the previous token of a split character
+        assert!(alignment.char_range.start <= covered);
+        assert!(alignment.char_range.end >= covered);
+        covered = alignment.char_range.end;
+
+        let expected: String = text
+            .chars()
+            .skip(alignment.char_range.start)
+            .take(alignment.char_range.len())
+            .collect();
+        assert_eq!(alignment.piece, expected);
+    }
+    assert_eq!(covered, text.chars().count());
+    assert_eq!(alignments.last().unwrap().byte_range.end, text.len());
+}
+
+#[test]
+fn test_alignment_mismatch() {
+    let tokenizer = get_tokenizer();
+    let tokens = tokenizer.encode("Hello", false, false).unwrap();
+
+    assert!(tokenizer.token_alignments("Goodbye", &tokens).is_err());
+    assert!(tokenizer.token_alignments("Hello world", &tokens).is_err());
+    assert!(tokenizer.token_alignments("Hello", &[u32::MAX]).is_err());
+}
+
+#[test]
+fn test_annotate_text() {
+    let tokenizer = get_tokenizer();
+    let text = "Hello 𓀀!";
+    let spans = tokenizer.annotate_text(text, true, true).unwrap();
+    let tokens = tokenizer.encode(text, true, true).unwrap();
+
+    assert_eq!(spans.len(), tokens.len());
+    for (span, &token_id) in spans.iter().zip(&tokens) {
+        assert_eq!(span.token_id, token_id);
+        assert_eq!(span.is_special, tokenizer.is_special_token(token_id));
+        assert_eq!(span.is_byte, tokenizer.is_byte(token_id));
+    }
+    assert!(spans[0].is_special);
+    assert_eq!(spans[0].piece, "<s>");
+    assert_eq!(spans[0].byte_range, 0..0);
+    assert_eq!(spans[1].piece, "Hello");
+    assert!(spans.iter().any(|span| span.is_byte && span.piece == "𓀀"));
+    assert_eq!(spans[spans.len() - 2].byte_range.end, text.len());
+
+    let json = serde_json::to_value(&spans[1]).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "token_id": tokens[1],
+            "piece": "Hello",
+            "byte_range": {"start": 0, "end": 5},
+            "is_special": false,
+            "is_byte": false
+        })
+    );
+}
+
+#[test]
+fn test_annotate_tokens() {
+    let tokenizer = get_tokenizer();
+    let text = "Generated text, 東京!";
+    let tokens = tokenizer.encode(text, true, true).unwrap();
+
+    assert_eq!(
+        tokenizer.annotate_tokens(&tokens).unwrap(),
+        tokenizer.annotate_text(text, true, true).unwrap()
+    );
+    assert!(tokenizer.annotate_tokens(&[u32::MAX]).is_err());
+}
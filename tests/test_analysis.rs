@@ -0,0 +1,106 @@
+use std::sync::OnceLock;
+use tekken::analysis::TokenStats;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json").expect("Failed to load tokenizer")
+    })
+}
+
+#[test]
+fn test_counts_match_encoding() {
+    let tokenizer = get_tokenizer();
+    let texts = ["Hello, world!", "Hello again, world."];
+    let stats = TokenStats::from_texts(tokenizer, texts).unwrap();
+
+    let encoded: Vec<Vec<u32>> = texts
+        .iter()
+        .map(|text| tokenizer.encode(text, false, false).unwrap())
+        .collect();
+    assert_eq!(stats.num_texts, 2);
+    assert_eq!(
+        stats.num_tokens,
+        encoded.iter().map(Vec::len).sum::<usize>()
+    );
+    assert_eq!(stats.num_words, 5);
+    assert_eq!(stats.num_chars, 32);
+    assert_eq!(stats.num_bytes, 32);
+    assert_eq!(stats.token_counts.len(), tokenizer.vocab_size());
+    assert_eq!(stats.token_counts.iter().sum::<usize>(), stats.num_tokens);
+    assert_eq!(stats.token_counts[22177], 2);
+}
+
+#[test]
+fn test_ratios() {
+    let tokenizer = get_tokenizer();
+    let stats = TokenStats::from_texts(tokenizer, ["Hello, world!"]).unwrap();
+    assert_eq!(stats.num_tokens, 4);
+    assert!((stats.tokens_per_char() - 4.0 / 13.0).abs() < 1e-12);
+    assert!((stats.tokens_per_word() - 2.0).abs() < 1e-12);
+    assert!((stats.compression_ratio() - 13.0 / 4.0).abs() < 1e-12);
+
+    let empty = TokenStats::new(tokenizer);
+    assert_eq!(empty.tokens_per_word(), 0.0);
+    assert_eq!(empty.compression_ratio(), 0.0);
+}
+
+#[test]
+fn test_frequency_views() {
+    let tokenizer = get_tokenizer();
+    let stats = TokenStats::from_texts(tokenizer, ["Hello Hello Hello, world!"]).unwrap();
+    let most_frequent = stats.most_frequent(1);
+    let hello = tokenizer.encode(" Hello", false, false).unwrap()[0];
+    assert_eq!(most_frequent, vec![(hello, 2)]);
+
+    let histogram = stats.frequency_histogram();
+    let distinct = stats.num_distinct_tokens();
+    assert_eq!(histogram[0], (0, tokenizer.vocab_size() - distinct));
+    assert_eq!(
+        histogram.iter().map(|&(_, tokens)| tokens).sum::<usize>(),
+        tokenizer.vocab_size()
+    );
+    assert!(stats.vocab_coverage() > 0.0 && stats.vocab_coverage() < 0.001);
+}
+
+#[test]
+fn test_byte_fallback_report() {
+    use tekken::analysis::{ByteFallbackReport, Script};
+
+    let tokenizer = get_tokenizer();
+    let report = tokenizer.byte_fallback_report("Hello, world!").unwrap();
+    assert_eq!(report.total.num_tokens, 4);
+    assert_eq!(report.total.num_fallback_tokens, 0);
+    assert_eq!(report.total.fallback_rate(), 0.0);
+    // "Hello" and " world" are Latin, "," and "!" are common punctuation
+    assert_eq!(report.by_script[&Script::Latin].num_tokens, 2);
+    assert_eq!(report.by_script[&Script::Common].num_tokens, 2);
+
+    // A rare character outside the vocabulary is spelled out byte by byte
+    let rare = "\u{10900}";
+    let tokens = tokenizer.encode(rare, false, false).unwrap();
+    assert!(tokens.iter().all(|&token| tokenizer.is_byte(token)));
+    let report = ByteFallbackReport::from_texts(tokenizer, ["Hello", rare]).unwrap();
+    assert_eq!(report.total.num_tokens, 1 + tokens.len());
+    assert_eq!(report.total.num_fallback_tokens, tokens.len());
+    let other = report.by_script[&Script::Other];
+    assert_eq!(other.num_fallback_tokens, tokens.len());
+    assert_eq!(other.fallback_rate(), 1.0);
+}
+
+#[test]
+fn test_script_of() {
+    use tekken::analysis::Script;
+
+    assert_eq!(Script::of('a'), Script::Latin);
+    assert_eq!(Script::of('é'), Script::Latin);
+    assert_eq!(Script::of('7'), Script::Common);
+    assert_eq!(Script::of('Ж'), Script::Cyrillic);
+    assert_eq!(Script::of('م'), Script::Arabic);
+    assert_eq!(Script::of('字'), Script::Han);
+    assert_eq!(Script::of('か'), Script::Kana);
+    assert_eq!(Script::of('한'), Script::Hangul);
+    assert_eq!(Script::of('🚀'), Script::Emoji);
+}
@@ -1,6 +1,9 @@
 use serde_json::json;
 
-use tekken::audio::{Audio, AudioConfig, AudioEncoder, AudioSpectrogramConfig, mel_filter_bank};
+use tekken::audio::{
+    Audio, AudioConfig, AudioEncoder, AudioSpectrogramConfig, SPEED_FACTORS, StreamingAudioEncoder,
+    log_mel_spectrogram, mel_filter_bank,
+};
 
 #[test]
 fn test_rust_audio() {
@@ -50,3 +53,268 @@ fn test_rust_audio() {
 
     println!("Rust results: {results}");
 }
+
+#[test]
+fn test_log_mel_spectrogram_of_tone() {
+    // One second of a 1 kHz tone
+    let sampling_rate = 16000;
+    #[allow(clippy::cast_precision_loss)]
+    let samples: Vec<f32> = (0..sampling_rate)
+        .map(|i| (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / sampling_rate as f32).sin())
+        .collect();
+    let audio = Audio::new(
+        ndarray::Array1::from(samples),
+        sampling_rate,
+        "wav".to_string(),
+    );
+    let config = AudioSpectrogramConfig::new(80, 160, 400).unwrap();
+    let features = log_mel_spectrogram(&audio, &config).unwrap();
+
+    assert_eq!(features.dim(), (80, 100));
+    let max = features.fold(f32::NEG_INFINITY, |max, &value| max.max(value));
+    let min = features.fold(f32::INFINITY, |min, &value| min.min(value));
+    assert!(max - min <= 2.0 + 1e-5);
+
+    // The loudest mel bin of a middle frame is the one centered nearest 1 kHz
+    let frame = features.column(50);
+    let loudest = (0..80)
+        .max_by(|&a, &b| frame[a].total_cmp(&frame[b]))
+        .unwrap();
+    let filters = mel_filter_bank(201, 80, 0.0, 8000.0, 16000).unwrap();
+    let tone_bin = 1000 * 400 / 16000;
+    let expected = (0..80)
+        .max_by(|&a, &b| filters[[tone_bin, a]].total_cmp(&filters[[tone_bin, b]]))
+        .unwrap();
+    assert_eq!(loudest, expected);
+
+    let short = Audio::new(
+        ndarray::Array1::zeros(100),
+        sampling_rate,
+        "wav".to_string(),
+    );
+    assert!(log_mel_spectrogram(&short, &config).is_err());
+}
+
+#[test]
+fn test_token_time_ranges() {
+    // 1.5 seconds of silence at 16 kHz, 12.5 tokens per second
+    let audio = Audio::new(ndarray::Array1::zeros(24000), 16000, "wav".to_string());
+    let spectrogram_config = AudioSpectrogramConfig::new(80, 160, 400).unwrap();
+    let audio_config = AudioConfig::new(16000, 12.5, spectrogram_config, None).unwrap();
+    let encoding = AudioEncoder::new(audio_config, 1000, 1001)
+        .encode(audio)
+        .unwrap();
+
+    assert_eq!(encoding.token_time_ranges.len(), encoding.tokens.len() - 1);
+    assert_eq!(encoding.token_time_ranges[0], (0.0, 0.08));
+    for (i, &(start, end)) in encoding.token_time_ranges.iter().enumerate() {
+        approx::assert_abs_diff_eq!(start, i as f64 * 0.08, epsilon = 1e-9);
+        assert!(end > start);
+        assert!(end <= encoding.audio.duration() + 1e-9);
+    }
+    let last = encoding.token_time_ranges.last().unwrap();
+    approx::assert_abs_diff_eq!(last.1, 1.5, epsilon = 1e-9);
+}
+
+#[test]
+fn test_streaming_encoder_matches_batch_times() {
+    let spectrogram_config = AudioSpectrogramConfig::new(80, 160, 400).unwrap();
+    let audio_config = AudioConfig::new(16000, 12.5, spectrogram_config, None).unwrap();
+    let mut stream =
+        StreamingAudioEncoder::new(AudioEncoder::new(audio_config, 1000, 1001)).unwrap();
+
+    // 1280 samples per token; 2000 samples hold one complete token
+    stream.push(&[0.0; 2000]);
+    assert!(stream.pop(2).is_none());
+    let first = stream.pop(1).unwrap();
+    assert_eq!(first.tokens, vec![1001, 1000]);
+    assert_eq!(first.audio.audio_array.len(), 1280);
+    assert_eq!(first.token_time_ranges, vec![(0.0, 0.08)]);
+
+    stream.push(&[0.0; 3000]);
+    let second = stream.pop(1).unwrap();
+    assert_eq!(second.tokens, vec![1000, 1000]);
+    approx::assert_abs_diff_eq!(second.token_time_ranges[0].0, 0.08, epsilon = 1e-9);
+    approx::assert_abs_diff_eq!(second.token_time_ranges[1].1, 0.24, epsilon = 1e-9);
+    assert_eq!(stream.num_tokens(), 3);
+
+    // 1160 buffered samples plus 200 more: one full token and a padded partial one
+    stream.push(&[0.0; 200]);
+    let last = stream.finish().unwrap();
+    assert_eq!(last.tokens, vec![1000, 1000]);
+    assert_eq!(last.audio.audio_array.len(), 2560);
+    approx::assert_abs_diff_eq!(
+        last.token_time_ranges[1].1,
+        5200.0 / 16000.0,
+        epsilon = 1e-9
+    );
+}
+
+#[test]
+fn test_streaming_encoder_without_audio() {
+    let spectrogram_config = AudioSpectrogramConfig::new(80, 160, 400).unwrap();
+    let audio_config = AudioConfig::new(16000, 12.5, spectrogram_config, None).unwrap();
+    let stream = StreamingAudioEncoder::new(AudioEncoder::new(audio_config, 1000, 1001)).unwrap();
+    assert!(stream.finish().is_none());
+}
+
+#[test]
+fn test_rejects_tokens_shorter_than_a_hop() {
+    // 16 kHz at 200 tokens per second is 80 samples per token, less than a hop
+    let spectrogram_config = AudioSpectrogramConfig::new(80, 160, 400).unwrap();
+    assert!(AudioConfig::new(16000, 200.0, spectrogram_config.clone(), None).is_err());
+
+    let mut audio_config = AudioConfig::new(16000, 12.5, spectrogram_config, None).unwrap();
+    audio_config.frame_rate = 200.0;
+    assert!(audio_config.validate().is_err());
+    let encoder = AudioEncoder::new(audio_config, 1000, 1001);
+    assert!(StreamingAudioEncoder::new(encoder).is_err());
+}
+
+/// Counts the rising zero crossings of a waveform.
+fn rising_zero_crossings(samples: &ndarray::Array1<f32>) -> usize {
+    samples
+        .windows(2)
+        .into_iter()
+        .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+        .count()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn tone(frequency: f32, sampling_rate: usize, num_samples: usize) -> Audio {
+    let samples: Vec<f32> = (0..num_samples)
+        .map(|i| (2.0 * std::f32::consts::PI * frequency * i as f32 / sampling_rate as f32).sin())
+        .collect();
+    Audio::new(
+        ndarray::Array1::from(samples),
+        sampling_rate,
+        "wav".to_string(),
+    )
+}
+
+#[test]
+fn test_resample_keeps_pitch() {
+    let mut audio = tone(440.0, 48000, 48000);
+    audio.resample(16000).unwrap();
+
+    assert_eq!(audio.sampling_rate, 16000);
+    assert_eq!(audio.audio_array.len(), 16000);
+    assert!((438..=441).contains(&rising_zero_crossings(&audio.audio_array)));
+}
+
+#[test]
+fn test_perturb_speed() {
+    let original = tone(440.0, 16000, 16000);
+    for factor in SPEED_FACTORS {
+        let mut audio = original.clone();
+        audio.perturb_speed(factor).unwrap();
+
+        assert_eq!(audio.sampling_rate, 16000);
+        approx::assert_abs_diff_eq!(audio.duration(), 1.0 / factor, epsilon = 1e-3);
+        // Same number of cycles in less (or more) time
+        assert!((438..=441).contains(&rising_zero_crossings(&audio.audio_array)));
+    }
+
+    let mut audio = original;
+    assert!(audio.perturb_speed(0.0).is_err());
+    assert!(audio.perturb_speed(f64::NAN).is_err());
+}
+
+#[test]
+fn test_encode_with_speed_adjusts_token_count() {
+    let spectrogram_config = AudioSpectrogramConfig::new(80, 160, 400).unwrap();
+    let audio_config = AudioConfig::new(16000, 12.5, spectrogram_config, None).unwrap();
+    let encoder = AudioEncoder::new(audio_config, 1000, 1001);
+    let audio = tone(440.0, 16000, 32000);
+
+    let counts: Vec<usize> = SPEED_FACTORS
+        .iter()
+        .map(|&factor| {
+            let encoding = encoder.encode_with_speed(audio.clone(), factor).unwrap();
+            approx::assert_abs_diff_eq!(encoding.audio.duration(), 2.0 / factor, epsilon = 1e-3);
+            encoding.tokens.len()
+        })
+        .collect();
+
+    assert_eq!(counts[1], encoder.encode(audio).unwrap().tokens.len());
+    assert!(counts[0] > counts[1] && counts[1] > counts[2]);
+}
+
+#[test]
+fn test_f64_spectrogram_matches_f32() {
+    let audio = tone(1000.0, 16000, 16000);
+    let precise = audio.cast::<f64>();
+    let config = AudioSpectrogramConfig::new(80, 160, 400).unwrap();
+
+    let features = log_mel_spectrogram(&audio, &config).unwrap();
+    let precise_features = log_mel_spectrogram(&precise, &config).unwrap();
+
+    assert_eq!(precise_features.dim(), features.dim());
+    for (&value, &precise_value) in features.iter().zip(&precise_features) {
+        approx::assert_abs_diff_eq!(f64::from(value), precise_value, epsilon = 1e-3);
+    }
+}
+
+#[test]
+fn test_f64_audio_encoding() {
+    let audio = Audio::<f64>::from_i16(&[0, 16384, -16384, -32768], 48000);
+    assert_eq!(audio.audio_array.to_vec(), vec![0.0, 0.5, -0.5, -1.0]);
+
+    let mut long = tone(440.0, 48000, 48000).cast::<f64>();
+    long.resample(16000).unwrap();
+    assert_eq!(long.audio_array.len(), 16000);
+
+    let spectrogram_config = AudioSpectrogramConfig::new(80, 160, 400).unwrap();
+    let audio_config = AudioConfig::new(16000, 12.5, spectrogram_config, None).unwrap();
+    let encoder = AudioEncoder::new(audio_config, 1000, 1001);
+    let precise = encoder.encode(long.clone()).unwrap();
+    let single = encoder.encode(long.cast::<f32>()).unwrap();
+    assert_eq!(precise.tokens, single.tokens);
+
+    let json = serde_json::to_string(&precise).unwrap();
+    let restored: tekken::audio::AudioEncoding<f64> = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.audio.audio_array, precise.audio.audio_array);
+}
+
+#[test]
+fn test_from_data_url() {
+    use base64::Engine as _;
+    use base64::engine::general_purpose::STANDARD;
+
+    let bytes = std::fs::read("tests/assets/jfk.wav").unwrap();
+    let expected = Audio::from_bytes(&bytes).unwrap();
+    let encoded = STANDARD.encode(&bytes);
+
+    for url in [
+        format!("data:audio/wav;base64,{encoded}"),
+        format!("data:audio/x-wav;codecs=1;base64,{encoded}"),
+        format!("data:Audio/WAVE;BASE64,{encoded}"),
+    ] {
+        let audio = Audio::from_data_url(&url).unwrap();
+        assert_eq!(audio.audio_array, expected.audio_array);
+        assert_eq!(audio.sampling_rate, expected.sampling_rate);
+    }
+
+    let audio = Audio::from_base64(&format!("data:audio/wav;base64,{encoded}")).unwrap();
+    assert_eq!(audio.audio_array, expected.audio_array);
+}
+
+#[test]
+fn test_from_data_url_errors() {
+    assert!(matches!(
+        Audio::from_data_url("data:audio/mpeg;base64,AAAA"),
+        Err(tekken::TokenizerError::UnsupportedFormat(message)) if message.contains("audio/mpeg")
+    ));
+    assert!(matches!(
+        Audio::from_data_url("data:;base64,AAAA"),
+        Err(tekken::TokenizerError::UnsupportedFormat(_))
+    ));
+    assert!(matches!(
+        Audio::from_data_url("data:audio/wav,RIFF"),
+        Err(tekken::TokenizerError::Audio(_))
+    ));
+    assert!(matches!(
+        Audio::from_data_url("audio/wav;base64,AAAA"),
+        Err(tekken::TokenizerError::Audio(_))
+    ));
+}
@@ -0,0 +1,157 @@
+use tekken::audio::{AudioConfig, AudioSpectrogramConfig};
+
+fn config(sampling_rate: usize, frame_rate: f64, chunk_length_s: Option<f64>) -> AudioConfig {
+    let spectrogram_config = AudioSpectrogramConfig::new(128, 160, 400).unwrap();
+    AudioConfig::new(
+        sampling_rate,
+        frame_rate,
+        spectrogram_config,
+        chunk_length_s,
+    )
+    .unwrap()
+}
+
+/// `AudioConfig.audio_length_per_tok` from mistral-common.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn python_audio_length_per_tok(config: &AudioConfig) -> usize {
+    let downsample_factor = (config.sampling_rate as f64 / config.frame_rate).floor();
+    (downsample_factor / config.audio_encoding_config.hop_length as f64) as usize
+}
+
+/// Token count of mistral-common's `AudioEncoder`, after padding.
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    clippy::cast_precision_loss
+)]
+fn python_num_audio_tokens(config: &AudioConfig, num_samples: usize) -> usize {
+    let window_size = config.audio_encoding_config.window_size;
+    let hop_length = config.audio_encoding_config.hop_length;
+    let num_samples = match config.chunk_length_s {
+        Some(chunk_length_s) => {
+            let chunk_frames = (chunk_length_s * config.sampling_rate as f64) as usize;
+            num_samples.div_ceil(chunk_frames) * chunk_frames
+        }
+        None => num_samples.max(window_size),
+    };
+    let signal_length = if num_samples % hop_length == 0 {
+        num_samples / hop_length
+    } else {
+        (num_samples as f64 / hop_length as f64 - 1.0).ceil() as usize
+    };
+    (signal_length as f64 / python_audio_length_per_tok(config) as f64).ceil() as usize
+}
+
+#[test]
+fn test_voxtral_config() {
+    let config = config(16000, 12.5, None);
+    assert_eq!(config.audio_length_per_tok(), 8);
+    assert_eq!(config.samples_per_token(), 1280.0);
+    assert_eq!(config.tokens_per_second(), 12.5);
+}
+
+#[test]
+fn test_truncated_hops_per_token() {
+    // 16000 / 12 = 1333.3 samples per token, 8.33 hops: tokens cover 8 hops
+    let config = config(16000, 12.0, None);
+    assert_eq!(config.audio_length_per_tok(), 8);
+    approx::assert_abs_diff_eq!(config.samples_per_token(), 16000.0 / 12.0);
+    assert_eq!(config.tokens_per_second(), 12.5);
+}
+
+#[test]
+fn test_audio_length_per_tok_matches_python() {
+    for (sampling_rate, frame_rate) in [
+        (16000, 12.5),
+        (16000, 12.0),
+        (16000, 25.0),
+        (24000, 12.5),
+        (16000, 100.0 / 3.0),
+        (22050, 10.0),
+        (8000, 50.0),
+    ] {
+        let config = config(sampling_rate, frame_rate, None);
+        assert_eq!(
+            config.audio_length_per_tok(),
+            python_audio_length_per_tok(&config),
+            "{sampling_rate} Hz at {frame_rate} frames/s"
+        );
+    }
+}
+
+#[test]
+fn test_expected_tokens_match_python() {
+    for chunk_length_s in [None, Some(30.0), Some(0.5)] {
+        let config = config(16000, 12.5, chunk_length_s);
+        for num_samples in [
+            0, 1, 159, 160, 161, 399, 400, 1279, 1280, 1281, 16000, 16001, 480_000, 480_001,
+        ] {
+            assert_eq!(
+                config.expected_tokens_for_samples(num_samples),
+                python_num_audio_tokens(&config, num_samples),
+                "{num_samples} samples with chunk length {chunk_length_s:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_padded_length() {
+    assert_eq!(config(16000, 12.5, None).padded_length(100), 400);
+    assert_eq!(config(16000, 12.5, None).padded_length(16000), 16000);
+    assert_eq!(
+        config(16000, 12.5, Some(30.0)).padded_length(16000),
+        480_000
+    );
+    assert_eq!(
+        config(16000, 12.5, Some(30.0)).padded_length(480_001),
+        960_000
+    );
+}
+
+#[test]
+fn test_rejects_empty_chunks() {
+    let spectrogram_config = AudioSpectrogramConfig::new(128, 160, 400).unwrap();
+    assert!(AudioConfig::new(16000, 12.5, spectrogram_config, Some(1e-6)).is_err());
+}
+
+#[test]
+#[cfg(feature = "audio")]
+fn test_pad_rejects_empty_chunks() {
+    use tekken::audio::Audio;
+
+    let mut config = config(16000, 12.5, Some(30.0));
+    config.chunk_length_s = Some(1e-6);
+    let mut audio = Audio::new(ndarray::Array1::zeros(100), 16000, "wav".to_string());
+    assert!(audio.pad(&config).is_err());
+    assert_eq!(audio.audio_array.len(), 100);
+
+    config.chunk_length_s = None;
+    audio.pad(&config).unwrap();
+    assert_eq!(audio.audio_array.len(), 400);
+}
+
+#[test]
+#[cfg(feature = "audio")]
+fn test_expected_tokens_match_encoder() {
+    use tekken::audio::{Audio, AudioEncoder};
+
+    let config = config(16000, 12.5, None);
+    let encoder = AudioEncoder::new(config.clone(), 1000, 1001);
+    for num_samples in [100, 16000, 24321] {
+        let audio = Audio::new(
+            ndarray::Array1::zeros(num_samples),
+            16000,
+            "wav".to_string(),
+        );
+        let encoding = encoder.encode(audio).unwrap();
+        assert_eq!(
+            encoding.tokens.len() - 1,
+            config.expected_tokens_for_samples(num_samples)
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::OnceLock;
+use tekken::backend::BpeBackend;
+use tekken::tekkenizer::{EncodeOptions, Tekkenizer};
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[cfg(any(feature = "tiktoken", feature = "hf-tokenizers"))]
+const SAMPLE_TEXTS: &[&str] = &[
+    "",
+    "Hello, world!",
+    "The quick brown fox jumps over the lazy dog.",
+    "  leading and trailing spaces  ",
+    "line one\nline two\r\n\r\nline four",
+    "Numbers: 1234567890 and 3.14159",
+    "I'm sure they'll say it's fine, we've done it.",
+    "Mixed scripts: Привет мир, こんにちは世界, مرحبا بالعالم",
+    "Emoji 🎉🚀 and combining e\u{301}",
+    "fn main() { println!(\"{}\", 42); }",
+    "path/to/some/file.rs\n\n\n\tindented",
+];
+
+/// Encodes every byte as its byte token, to check backend dispatch.
+struct ByteBackend;
+
+impl BpeBackend for ByteBackend {
+    fn name(&self) -> &str {
+        "bytes"
+    }
+
+    fn encode_ordinary(&self, text: &str, ranks: &mut Vec<u32>) -> tekken::Result<()> {
+        ranks.extend(text.bytes().map(u32::from));
+        Ok(())
+    }
+}
+
+#[test]
+fn test_native_backend_is_default() {
+    assert_eq!(get_tokenizer().backend_name(), "native");
+}
+
+#[test]
+fn test_custom_backend_ranks_are_shifted() {
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json")
+        .unwrap()
+        .with_backend(ByteBackend);
+    assert_eq!(tokenizer.backend_name(), "bytes");
+
+    let tokens = tokenizer.encode("Hi", true, false).unwrap();
+    let num_special = tokenizer.num_special_tokens() as u32;
+    assert_eq!(
+        tokens,
+        vec![
+            tokenizer.bos_id().unwrap(),
+            u32::from(b'H') + num_special,
+            u32::from(b'i') + num_special
+        ]
+    );
+
+    // Decoding is independent of the backend
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, tekken::SpecialTokenPolicy::Ignore)
+            .unwrap(),
+        "Hi"
+    );
+}
+
+/// Returns a rank past the end of any vocabulary.
+struct OutOfRangeBackend;
+
+impl BpeBackend for OutOfRangeBackend {
+    fn name(&self) -> &str {
+        "out-of-range"
+    }
+
+    fn encode_ordinary(&self, _text: &str, ranks: &mut Vec<u32>) -> tekken::Result<()> {
+        ranks.extend([u32::from(b'H'), u32::MAX]);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_custom_backend_ranks_are_checked() {
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json")
+        .unwrap()
+        .with_backend(OutOfRangeBackend);
+
+    let mut tokens = vec![7];
+    let result = tokenizer.encode_into("Hi", &mut tokens, EncodeOptions::default());
+    assert!(matches!(
+        result,
+        Err(tekken::TokenizerError::TokenOutOfRange {
+            token_id: u32::MAX,
+            ..
+        })
+    ));
+    assert_eq!(tokens, vec![7]);
+}
+
+#[cfg(feature = "tiktoken")]
+#[test]
+fn test_tiktoken_backend_matches_native() {
+    use tekken::backend::TiktokenBackend;
+
+    let native = get_tokenizer();
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    let backend = TiktokenBackend::new(&tokenizer).unwrap();
+    let tokenizer = tokenizer.with_backend(backend);
+    assert_eq!(tokenizer.backend_name(), "tiktoken");
+
+    for text in SAMPLE_TEXTS {
+        assert_eq!(
+            tokenizer.encode(text, true, true).unwrap(),
+            native.encode(text, true, true).unwrap(),
+            "mismatch for {text:?}"
+        );
+    }
+}
+
+#[cfg(feature = "hf-tokenizers")]
+#[test]
+fn test_hf_tokenizers_backend_matches_native() {
+    use tekken::backend::HfTokenizersBackend;
+
+    let native = get_tokenizer();
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    let backend = HfTokenizersBackend::new(&tokenizer).unwrap();
+    let tokenizer = tokenizer.with_backend(backend);
+    assert_eq!(tokenizer.backend_name(), "hf-tokenizers");
+
+    for text in SAMPLE_TEXTS {
+        assert_eq!(
+            tokenizer.encode(text, false, false).unwrap(),
+            native.encode(text, false, false).unwrap(),
+            "mismatch for {text:?}"
+        );
+    }
+}
@@ -0,0 +1,54 @@
+use std::sync::OnceLock;
+use tekken::TokenizerError;
+use tekken::bench::{ThroughputOptions, measure_decode_throughput, measure_encode_throughput};
+use tekken::parallel::ParallelismConfig;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_measured_work_matches_corpus() {
+    let tokenizer = get_tokenizer();
+    let corpus = ["Hello world", "The quick brown fox 🚀", ""];
+    let tokens: Vec<Vec<u32>> = corpus
+        .iter()
+        .map(|text| tokenizer.encode(text, false, false).unwrap())
+        .collect();
+    let token_count: usize = tokens.iter().map(Vec::len).sum();
+    let byte_count: usize = corpus.iter().map(|text| text.len()).sum();
+
+    let options = ThroughputOptions::default()
+        .with_warmup_iterations(0)
+        .with_iterations(2)
+        .with_parallelism(ParallelismConfig::sequential());
+
+    let encode = measure_encode_throughput(tokenizer, &corpus, &options).unwrap();
+    assert_eq!(encode.items, 6);
+    assert_eq!(encode.bytes, 2 * byte_count);
+    assert_eq!(encode.tokens, 2 * token_count);
+    assert!(encode.tokens_per_second() > 0.0);
+    assert!(encode.to_string().contains("tokens/s"));
+
+    let decode = measure_decode_throughput(tokenizer, &tokens, &options).unwrap();
+    assert_eq!(
+        (decode.items, decode.bytes, decode.tokens),
+        (encode.items, encode.bytes, encode.tokens)
+    );
+    assert_eq!(decode.time_per_item(), decode.elapsed / 6);
+}
+
+#[test]
+fn test_zero_iterations_are_rejected() {
+    let options = ThroughputOptions::default().with_iterations(0);
+    assert!(matches!(
+        measure_encode_throughput(get_tokenizer(), &["Hi"], &options),
+        Err(TokenizerError::InvalidConfig(_))
+    ));
+}
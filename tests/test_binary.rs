@@ -0,0 +1,121 @@
+use std::sync::OnceLock;
+use tekken::binary::{FORMAT_VERSION, MAGIC, MAX_PAYLOAD_LEN, is_binary};
+use tekken::errors::TokenizerError;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+static BINARY: OnceLock<Vec<u8>> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+fn get_binary() -> &'static [u8] {
+    BINARY.get_or_init(|| get_tokenizer().to_binary().unwrap())
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let tokenizer = get_tokenizer();
+    let bytes = get_binary();
+    assert!(is_binary(bytes));
+    assert_eq!(bytes[..4], MAGIC);
+    assert_eq!(bytes[4], FORMAT_VERSION);
+    assert_eq!(bytes[5..37], tokenizer.fingerprint());
+
+    let restored = Tekkenizer::from_binary(bytes).unwrap();
+    assert_eq!(restored.fingerprint(), tokenizer.fingerprint());
+    assert_eq!(restored.vocab_size(), tokenizer.vocab_size());
+    assert_eq!(restored.version(), tokenizer.version());
+    assert_eq!(restored.has_audio_support(), tokenizer.has_audio_support());
+    let text = "Hello, world! Binary vocabularies 🚀";
+    assert_eq!(
+        restored.encode(text, true, false).unwrap(),
+        tokenizer.encode(text, true, false).unwrap()
+    );
+}
+
+#[test]
+fn test_binary_is_smaller_than_json() {
+    let json_len = std::fs::metadata("tests/assets/tekken.json").unwrap().len() as usize;
+    assert!(get_binary().len() * 5 < json_len, "{}", get_binary().len());
+}
+
+#[test]
+fn test_binary_file_round_trip() {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    get_tokenizer().save_binary(file.path()).unwrap();
+    let restored = Tekkenizer::from_binary_file(file.path()).unwrap();
+    assert_eq!(restored.fingerprint(), get_tokenizer().fingerprint());
+}
+
+#[test]
+fn test_binary_rejects_other_data() {
+    assert!(matches!(
+        Tekkenizer::from_binary(b"{\"config\": {}}"),
+        Err(TokenizerError::UnsupportedFormat(_))
+    ));
+
+    let mut newer = get_binary().to_vec();
+    newer[4] = FORMAT_VERSION + 1;
+    let error = Tekkenizer::from_binary(&newer).err().unwrap();
+    assert!(error.to_string().contains("version"), "{error}");
+
+    let truncated = &get_binary()[..get_binary().len() / 2];
+    assert!(Tekkenizer::from_binary(truncated).is_err());
+}
+
+#[test]
+fn test_binary_checks_fingerprint() {
+    let mut tampered = get_binary().to_vec();
+    tampered[5] ^= 0xff;
+    let error = Tekkenizer::from_binary(&tampered).err().unwrap();
+    assert!(error.to_string().contains("fingerprint"), "{error}");
+}
+
+#[test]
+fn test_binary_limits_decompressed_size() {
+    let mut bomb = get_binary()[..MAGIC.len() + 1 + 32].to_vec();
+    let zeros = std::io::Read::take(std::io::repeat(0), MAX_PAYLOAD_LEN + 1);
+    zstd::stream::copy_encode(zeros, &mut bomb, 1).unwrap();
+    assert!(bomb.len() < 1 << 20);
+
+    let error = Tekkenizer::from_binary(&bomb).err().unwrap();
+    assert!(error.to_string().contains("more than"), "{error}");
+}
+
+#[test]
+fn test_binary_round_trip_with_placeholders() {
+    use tekken::config::{ModelData, TokenizerVersion};
+    use tekken::tekkenizer::RankGapPolicy;
+
+    let mut json = serde_json::to_value(
+        ModelData::builder(r"\p{L}+|\s+|.", TokenizerVersion::V7)
+            .with_byte_tokens()
+            .with_tokens(["he", "ll", "hell", "hello", " w", "or"])
+            .with_num_special_tokens(100)
+            .build()
+            .unwrap(),
+    )
+    .unwrap();
+    let vocab = json["vocab"].as_array_mut().unwrap();
+    vocab.remove(260);
+    vocab.remove(258);
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), serde_json::to_vec(&json).unwrap()).unwrap();
+    let (tokenizer, _) =
+        Tekkenizer::from_file_lenient_with(file.path(), RankGapPolicy::Placeholder).unwrap();
+    assert!(tokenizer.is_placeholder(358) && tokenizer.is_placeholder(360));
+
+    let restored = Tekkenizer::from_binary(&tokenizer.to_binary().unwrap()).unwrap();
+    assert_eq!(restored.fingerprint(), tokenizer.fingerprint());
+    let placeholders: Vec<u32> = (0..362).filter(|&id| restored.is_placeholder(id)).collect();
+    assert_eq!(placeholders, vec![358, 360]);
+    assert_eq!(
+        restored.encode("hello world", false, false).unwrap(),
+        tokenizer.encode("hello world", false, false).unwrap()
+    );
+}
@@ -0,0 +1,73 @@
+use std::sync::OnceLock;
+use tekken::SpecialTokenPolicy;
+use tekken::tekkenizer::{BpeDropout, EncodeOptions, Tekkenizer};
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+const TEXT: &str = "Tokenization robustness improves with subword regularization.";
+
+fn encode_with(probability: f64, seed: u64) -> Vec<u32> {
+    let mut tokens = Vec::new();
+    let options = EncodeOptions::new(true, true).with_dropout(BpeDropout::new(probability, seed));
+    get_tokenizer()
+        .encode_into(TEXT, &mut tokens, options)
+        .unwrap();
+    tokens
+}
+
+#[test]
+fn test_zero_dropout_matches_regular_encoding() {
+    let regular = get_tokenizer().encode(TEXT, true, true).unwrap();
+    assert_eq!(encode_with(0.0, 7), regular);
+}
+
+#[test]
+fn test_full_dropout_yields_byte_tokens() {
+    let tokenizer = get_tokenizer();
+    let tokens = encode_with(1.0, 7);
+    assert_eq!(tokens.len(), TEXT.len() + 2);
+    assert!(
+        tokens[1..tokens.len() - 1]
+            .iter()
+            .all(|&t| tokenizer.is_byte(t))
+    );
+}
+
+#[test]
+fn test_dropout_is_seeded_and_lossless() {
+    let tokenizer = get_tokenizer();
+    let regular = tokenizer.encode(TEXT, true, true).unwrap();
+
+    let first = encode_with(0.3, 42);
+    assert_eq!(first, encode_with(0.3, 42));
+    assert!(first.len() > regular.len());
+    assert_eq!(
+        tokenizer
+            .decode(&first, SpecialTokenPolicy::Ignore)
+            .unwrap(),
+        TEXT
+    );
+
+    let variants: std::collections::HashSet<Vec<u32>> =
+        (0..8).map(|seed| encode_with(0.3, seed)).collect();
+    assert!(variants.len() > 1);
+}
+
+#[test]
+fn test_invalid_probability_is_rejected() {
+    let mut tokens = vec![7];
+    let options = EncodeOptions::new(true, false).with_dropout(BpeDropout::new(1.5, 0));
+    assert!(
+        get_tokenizer()
+            .encode_into(TEXT, &mut tokens, options)
+            .is_err()
+    );
+    assert_eq!(tokens, vec![7]);
+}
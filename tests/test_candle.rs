@@ -0,0 +1,74 @@
+use std::sync::OnceLock;
+
+use candle_core::{DType, Device};
+#[cfg(feature = "audio")]
+use tekken::audio::Audio;
+use tekken::candle::EncodingTensors;
+use tekken::encoding::{EncodingOptions, Padding};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_encode_tensor() {
+    let tensor = get_tokenizer()
+        .encode_tensor("Hello, world!", true, false, &Device::Cpu)
+        .unwrap();
+    assert_eq!(tensor.dims(), &[1, 5]);
+    assert_eq!(tensor.dtype(), DType::U32);
+    assert_eq!(
+        tensor.to_vec2::<u32>().unwrap(),
+        vec![vec![1, 22177, 1044, 4304, 1033]]
+    );
+}
+
+#[test]
+fn test_padded_batch_tensors() {
+    let tokenizer = get_tokenizer();
+    let options = EncodingOptions::new(true, false).with_padding(Padding::Longest);
+    let encodings = tokenizer
+        .encode_plus_batch(&["Hello", "Hello, world!"], &options)
+        .unwrap();
+
+    let tensors = EncodingTensors::from_encodings(&encodings, &Device::Cpu).unwrap();
+    assert_eq!(tensors.input_ids.dims(), &[2, 5]);
+    assert_eq!(
+        tensors.attention_mask.to_vec2::<u32>().unwrap(),
+        vec![vec![1, 1, 0, 0, 0], vec![1, 1, 1, 1, 1]]
+    );
+    assert_eq!(
+        tensors.position_ids.to_vec2::<u32>().unwrap(),
+        vec![vec![0, 1, 0, 0, 0], vec![0, 1, 2, 3, 4]]
+    );
+
+    let unpadded = tokenizer
+        .encode_plus_batch(
+            &["Hello", "Hello, world!"],
+            &EncodingOptions::new(true, false),
+        )
+        .unwrap();
+    assert!(EncodingTensors::from_encodings(&unpadded, &Device::Cpu).is_err());
+}
+
+#[cfg(feature = "audio")]
+#[test]
+fn test_audio_features_tensor() {
+    let tokenizer = get_tokenizer();
+    let config = tokenizer.audio_config().unwrap().clone();
+    let audio = Audio::from_file("tests/assets/jfk.wav").unwrap();
+
+    let features = tokenizer
+        .audio_features_tensor(audio, DType::F16, &Device::Cpu)
+        .unwrap();
+    let dims = features.dims();
+    assert_eq!(features.dtype(), DType::F16);
+    assert_eq!(dims[..2], [1, config.audio_encoding_config.num_mel_bins]);
+    assert!(dims[2] > 0);
+}
@@ -0,0 +1,57 @@
+use tekken::json::{canonicalize, is_canonical, to_canonical_string};
+
+#[test]
+fn test_floats_match_python_repr() {
+    // Expected values are Python's `json.dumps(x)`
+    let cases = [
+        (0.1, "0.1"),
+        (100.0, "100.0"),
+        (-0.0, "-0.0"),
+        (123.456, "123.456"),
+        (0.0001, "0.0001"),
+        (0.00001, "1e-05"),
+        (-2.5e-7, "-2.5e-07"),
+        (1e15, "1000000000000000.0"),
+        (1e16, "1e+16"),
+        (12_345_678_901_234_567.0, "1.2345678901234568e+16"),
+        (1.5e300, "1.5e+300"),
+        (f64::MIN_POSITIVE, "2.2250738585072014e-308"),
+    ];
+    for (value, expected) in cases {
+        assert_eq!(to_canonical_string(&value).unwrap(), expected, "{value:e}");
+    }
+    assert_eq!(to_canonical_string(&0.1f32).unwrap(), "0.10000000149011612");
+}
+
+#[test]
+fn test_separators_order_and_strings() {
+    let value = serde_json::json!({
+        "zeta": [1, 2.0, true, null],
+        "alpha": {"nested": "Zürich 🚀"},
+        "escapes": "quote \" backslash \\ newline \n tab \t bell \u{7}",
+        "empty": {},
+        "list": [],
+    });
+    assert_eq!(
+        to_canonical_string(&value).unwrap(),
+        concat!(
+            r#"{"zeta": [1, 2.0, true, null], "alpha": {"nested": "Zürich 🚀"}, "#,
+            r#""escapes": "quote \" backslash \\ newline \n tab \t bell \u0007", "#,
+            r#""empty": {}, "list": []}"#,
+        )
+    );
+}
+
+#[test]
+fn test_canonicalize() {
+    assert_eq!(
+        canonicalize("{\n  \"b\" : 1e2,\n  \"a\" : [ \"x\" ,\"y\" ]\n}").unwrap(),
+        r#"{"b": 100.0, "a": ["x", "y"]}"#
+    );
+    assert!(canonicalize("{not json").is_err());
+
+    assert!(is_canonical(r#"{"a": 1, "b": [1, 2]}"#));
+    assert!(!is_canonical(r#"{"a":1}"#));
+    assert!(!is_canonical(r#"{"a": 1e-5}"#));
+    assert!(!is_canonical("{not json"));
+}
@@ -0,0 +1,283 @@
+use std::sync::OnceLock;
+use tekken::encoding::PaddingSide;
+use tekken::request::{ChatCompletionRequest, TokenizedRequest};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+fn tokenize(tokenizer: &Tekkenizer, content: serde_json::Value) -> TokenizedRequest {
+    let request: ChatCompletionRequest = serde_json::from_value(serde_json::json!({
+        "messages": [{"role": "user", "content": content}],
+    }))
+    .unwrap();
+    tokenizer.encode_request(request).unwrap()
+}
+
+#[test]
+fn test_collate_pads_text() {
+    let tokenizer = get_tokenizer();
+    let short = tokenize(tokenizer, "Hi".into());
+    let long = tokenize(tokenizer, "Tell me a story about a dragon.".into());
+    let batch = tokenizer
+        .collate(&[short.clone(), long.clone()], PaddingSide::Right)
+        .unwrap();
+
+    assert_eq!(batch.lengths, vec![short.tokens.len(), long.tokens.len()]);
+    assert_eq!(batch.max_length(), long.tokens.len());
+    assert_eq!(batch.input_ids[1], long.tokens);
+    assert_eq!(&batch.input_ids[0][..short.tokens.len()], &short.tokens[..]);
+    let pad_id = tokenizer.pad_id().unwrap();
+    assert!(
+        batch.input_ids[0][short.tokens.len()..]
+            .iter()
+            .all(|&id| id == pad_id)
+    );
+    for (mask, &length) in batch.attention_mask.iter().zip(&batch.lengths) {
+        assert_eq!(mask.len(), batch.max_length());
+        assert_eq!(mask.iter().sum::<u32>() as usize, length);
+        assert!(mask[..length].iter().all(|&bit| bit == 1));
+    }
+
+    assert_eq!(
+        batch.position_ids[0],
+        (0..short.tokens.len() as u32)
+            .chain(std::iter::repeat_n(
+                0,
+                long.tokens.len() - short.tokens.len()
+            ))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        batch.position_ids[1],
+        (0..long.tokens.len() as u32).collect::<Vec<_>>()
+    );
+
+    let empty = tokenizer
+        .collate::<TokenizedRequest>(&[], PaddingSide::Right)
+        .unwrap();
+    assert!(empty.input_ids.is_empty());
+    assert_eq!(empty.max_length(), 0);
+}
+
+#[test]
+fn test_collate_left_padding() {
+    let tokenizer = get_tokenizer();
+    let short = tokenize(tokenizer, "Hi".into());
+    let long = tokenize(tokenizer, "Tell me a story about a dragon.".into());
+    let batch = tokenizer
+        .collate(&[short.clone(), long.clone()], PaddingSide::Left)
+        .unwrap();
+
+    let padding = long.tokens.len() - short.tokens.len();
+    let pad_id = tokenizer.pad_id().unwrap();
+    assert!(batch.input_ids[0][..padding].iter().all(|&id| id == pad_id));
+    assert_eq!(&batch.input_ids[0][padding..], &short.tokens[..]);
+    assert_eq!(batch.input_ids[1], long.tokens);
+    assert!(
+        batch.attention_mask[0][..padding]
+            .iter()
+            .all(|&bit| bit == 0)
+    );
+    assert!(
+        batch.attention_mask[0][padding..]
+            .iter()
+            .all(|&bit| bit == 1)
+    );
+
+    // Positions count from the first real token
+    assert!(batch.position_ids[0][..padding].iter().all(|&id| id == 0));
+    assert_eq!(
+        batch.position_ids[0][padding..],
+        (0..short.tokens.len() as u32).collect::<Vec<_>>()
+    );
+    assert_eq!(batch.lengths, vec![short.tokens.len(), long.tokens.len()]);
+}
+
+#[test]
+fn test_position_ids_from_offsets() {
+    let tokenizer = get_tokenizer();
+    let short = tokenize(tokenizer, "Hi".into());
+    let long = tokenize(tokenizer, "Tell me a story about a dragon.".into());
+    let batch = tokenizer
+        .collate(&[short.clone(), long.clone()], PaddingSide::Left)
+        .unwrap();
+
+    assert_eq!(
+        batch.position_ids_from(&[0, 0]).unwrap(),
+        batch.position_ids
+    );
+    let positions = batch.position_ids_from(&[7, 3]).unwrap();
+    let padding = long.tokens.len() - short.tokens.len();
+    assert!(positions[0][..padding].iter().all(|&id| id == 0));
+    assert_eq!(
+        positions[0][padding..],
+        (7..7 + short.tokens.len() as u32).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        positions[1],
+        (3..3 + long.tokens.len() as u32).collect::<Vec<_>>()
+    );
+
+    assert!(batch.position_ids_from(&[0]).is_err());
+}
+
+#[test]
+#[cfg(feature = "audio")]
+fn test_collate_audio() {
+    use base64::Engine;
+    use ndarray::Axis;
+    use tekken::audio::log_mel_spectrogram;
+    use tekken::special_tokens::SpecialTokens;
+
+    let tokenizer = get_tokenizer();
+    let wav = std::fs::read("tests/assets/jfk.wav").unwrap();
+    let data = base64::engine::general_purpose::STANDARD.encode(wav);
+    let with_audio = tokenize(
+        tokenizer,
+        serde_json::json!([
+            {"type": "input_audio", "input_audio": {"data": data, "format": "wav"}},
+            {"type": "text", "text": "Transcribe this."},
+        ]),
+    );
+    let text_only = tokenize(tokenizer, "Hello".into());
+    let batch = tokenizer
+        .collate(&[text_only.clone(), with_audio.clone()], PaddingSide::Right)
+        .unwrap();
+
+    let config = &tokenizer.audio_config().unwrap().audio_encoding_config;
+    let features = log_mel_spectrogram(&with_audio.audios[0].audio, config).unwrap();
+    assert_eq!(
+        batch.audio_features.dim(),
+        (1, config.num_mel_bins, features.ncols())
+    );
+    assert_eq!(batch.audio_features.index_axis(Axis(0), 0), features.view());
+    assert_eq!(batch.audio_frames, vec![features.ncols()]);
+    assert_eq!(batch.audio_requests, vec![1]);
+
+    let audio_id = tokenizer.id_of(SpecialTokens::Audio).unwrap();
+    assert!(batch.audio_positions[0].is_empty());
+    let positions = &batch.audio_positions[1];
+    assert_eq!(positions.len(), with_audio.audios[0].tokens.len() - 1);
+    assert!(
+        positions
+            .iter()
+            .all(|&position| batch.input_ids[1][position] == audio_id)
+    );
+
+    // Left padding shifts the positions of the shorter row
+    let long_text = tokenize(tokenizer, "word ".repeat(1000).into());
+    let shift = long_text.tokens.len() - with_audio.tokens.len();
+    let left = tokenizer
+        .collate(&[with_audio.clone(), long_text], PaddingSide::Left)
+        .unwrap();
+    assert_eq!(
+        left.audio_positions[0],
+        positions
+            .iter()
+            .map(|position| position + shift)
+            .collect::<Vec<_>>()
+    );
+    assert!(
+        left.audio_positions[0]
+            .iter()
+            .all(|&position| left.input_ids[0][position] == audio_id)
+    );
+    let text_batch = tokenizer.collate(&[text_only], PaddingSide::Right).unwrap();
+    assert_eq!(text_batch.audio_features.dim(), (0, 0, 0));
+    assert_eq!(text_batch.audio_positions, vec![Vec::<usize>::new()]);
+}
+
+#[test]
+#[cfg(feature = "image")]
+fn test_collate_images() {
+    use std::io::Cursor;
+
+    use base64::Engine;
+    use image::{DynamicImage, ImageFormat, Rgb, RgbImage};
+    use ndarray::s;
+    use tekken::special_tokens::SpecialTokens;
+
+    // The test tokenizer with a Pixtral-style `multimodal` section
+    let mut json: serde_json::Value =
+        serde_json::from_slice(&std::fs::read("tests/assets/tekken.json").unwrap()).unwrap();
+    json["multimodal"] = serde_json::json!({"image_patch_size": 16, "max_image_size": 1024});
+    let tokenizer = Tekkenizer::from_bytes(&serde_json::to_vec(&json).unwrap()).unwrap();
+    let png = |width, height| {
+        let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb([255, 0, 0])));
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, ImageFormat::Png).unwrap();
+        base64::engine::general_purpose::STANDARD.encode(bytes.into_inner())
+    };
+
+    let wide = tokenize(
+        &tokenizer,
+        serde_json::json!([
+            {"type": "image", "image": png(48, 16)},
+            {"type": "text", "text": "Describe this."},
+        ]),
+    );
+    let tall = tokenize(
+        &tokenizer,
+        serde_json::json!([
+            {"type": "text", "text": "Compare"},
+            {"type": "image", "image": png(16, 32)},
+            {"type": "image", "image": png(32, 32)},
+        ]),
+    );
+    let batch = tokenizer
+        .collate(&[wide.clone(), tall.clone()], PaddingSide::Left)
+        .unwrap();
+
+    assert_eq!(batch.image_sizes, vec![(16, 48), (32, 16), (32, 32)]);
+    assert_eq!(batch.image_requests, vec![0, 1, 1]);
+    assert_eq!(batch.pixel_values.dim(), (3, 3, 32, 48));
+    let first = &wide.images[0].image.pixel_values;
+    assert_eq!(batch.pixel_values.slice(s![0, .., ..16, ..]), first.view());
+    assert!(
+        batch
+            .pixel_values
+            .slice(s![0, .., 16.., ..])
+            .iter()
+            .all(|&value| value == 0.0)
+    );
+
+    let img_id = tokenizer.id_of(SpecialTokens::Img).unwrap();
+    for (row, (positions, encoding)) in batch.image_positions.iter().zip([&wide, &tall]).enumerate()
+    {
+        let expected = encoding.tokens.iter().filter(|&&id| id == img_id).count();
+        assert_eq!(positions.len(), expected);
+        assert!(
+            positions
+                .iter()
+                .all(|&position| batch.input_ids[row][position] == img_id)
+        );
+    }
+
+    let text_batch = tokenizer
+        .collate(&[tokenize(&tokenizer, "Hello".into())], PaddingSide::Right)
+        .unwrap();
+    assert_eq!(text_batch.pixel_values.dim(), (0, 3, 0, 0));
+    assert_eq!(text_batch.image_positions, vec![Vec::<usize>::new()]);
+}
+
+#[test]
+fn test_collate_prompt_encodings() {
+    use tekken::prompt::PromptEncoding;
+
+    let tokenizer = get_tokenizer();
+    let request = tokenize(tokenizer, "Hi".into());
+    let prompt = PromptEncoding {
+        tokens: request.tokens.clone(),
+        rendered: request.text_debug.clone(),
+    };
+    let from_prompts = tokenizer.collate(&[prompt], PaddingSide::Right).unwrap();
+    let from_requests = tokenizer.collate(&[request], PaddingSide::Right).unwrap();
+    assert_eq!(from_prompts, from_requests);
+}
@@ -0,0 +1,50 @@
+use std::io::Write;
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+
+const TOKENIZER_PATH: &str = "tests/assets/tekken.json";
+
+static JSON: OnceLock<Vec<u8>> = OnceLock::new();
+
+fn get_json() -> &'static [u8] {
+    JSON.get_or_init(|| std::fs::read(TOKENIZER_PATH).expect("Failed to read tokenizer file"))
+}
+
+fn assert_same_tokenizer(tokenizer: &Tekkenizer) {
+    assert_eq!(
+        tokenizer.encode("Hello, world!", false, false).unwrap(),
+        vec![22177, 1044, 4304, 1033]
+    );
+}
+
+#[test]
+fn test_from_gzip_file() {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(get_json()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tekken.json.gz");
+    std::fs::write(&path, &compressed).unwrap();
+
+    assert_same_tokenizer(&Tekkenizer::from_file(&path).unwrap());
+    assert_same_tokenizer(&Tekkenizer::from_bytes(&compressed).unwrap());
+}
+
+#[test]
+fn test_from_zstd_file() {
+    let compressed = zstd::stream::encode_all(get_json(), 1).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tekken.json.zst");
+    std::fs::write(&path, &compressed).unwrap();
+
+    assert_same_tokenizer(&Tekkenizer::from_file(&path).unwrap());
+    assert_same_tokenizer(&Tekkenizer::from_bytes(&compressed).unwrap());
+}
+
+#[test]
+fn test_corrupt_compressed_data() {
+    assert!(Tekkenizer::from_bytes(&[0x1f, 0x8b, 0x00, 0x01]).is_err());
+    assert!(Tekkenizer::from_bytes(&[0x28, 0xb5, 0x2f, 0xfd, 0x00]).is_err());
+}
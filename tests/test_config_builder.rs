@@ -0,0 +1,99 @@
+use tekken::audio::{AudioConfig, AudioSpectrogramConfig};
+use tekken::config::{ModelData, TekkenConfig, TokenizerVersion};
+use tekken::special_tokens::{SpecialTokenInfo, SpecialTokenPolicy};
+use tekken::tekkenizer::Tekkenizer;
+
+const PATTERN: &str = r"\p{L}+|\s+|.";
+
+fn builder() -> tekken::config::ModelDataBuilder {
+    ModelData::builder(PATTERN, TokenizerVersion::V7)
+        .with_byte_tokens()
+        .with_tokens(["he", "ll", "hell", "hello", " w", "or", "ld"])
+        .with_num_special_tokens(100)
+}
+
+#[test]
+fn test_built_model_data_loads() {
+    let model_data = builder().build().unwrap();
+    assert_eq!(model_data.vocab.len(), 263);
+    assert_eq!(model_data.config.default_vocab_size, 363);
+    assert_eq!(model_data.vocab[259].token_str.as_deref(), Some("hello"));
+
+    let tokenizer = Tekkenizer::from_bytes(&serde_json::to_vec(&model_data).unwrap()).unwrap();
+    assert_eq!(*tokenizer.version(), TokenizerVersion::V7);
+    let tokens = tokenizer.encode("hello world", true, false).unwrap();
+    assert_eq!(tokens[..2], [1, 359]);
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, SpecialTokenPolicy::Ignore)
+            .unwrap(),
+        "hello world"
+    );
+}
+
+#[test]
+fn test_config_builder_validation() {
+    let config = TekkenConfig::builder(PATTERN, TokenizerVersion::V11)
+        .with_num_vocab_tokens(1000)
+        .with_vocab_size(1500)
+        .build()
+        .unwrap();
+    assert_eq!(config.version, "v11");
+    assert_eq!(config.default_num_special_tokens, 1000);
+
+    let too_large = TekkenConfig::builder(PATTERN, TokenizerVersion::V7)
+        .with_num_vocab_tokens(1000)
+        .with_vocab_size(2001);
+    assert!(too_large.build().is_err());
+
+    let bad_pattern = TekkenConfig::builder("(?<", TokenizerVersion::V7).with_num_vocab_tokens(256);
+    assert!(bad_pattern.build().is_err());
+
+    let no_bytes = TekkenConfig::builder(PATTERN, TokenizerVersion::V7).with_num_vocab_tokens(10);
+    assert!(no_bytes.build().is_err());
+}
+
+#[test]
+fn test_model_data_builder_validation() {
+    let missing_bytes = ModelData::builder(PATTERN, TokenizerVersion::V7).with_tokens(["a"; 300]);
+    assert!(missing_bytes.build().is_err());
+
+    assert!(builder().with_tokens(["he"]).build().is_err());
+    assert!(builder().with_tokens([""]).build().is_err());
+
+    let special_tokens = |rank| {
+        vec![SpecialTokenInfo {
+            rank,
+            token_str: "<s>".to_string(),
+            is_control: true,
+        }]
+    };
+    assert!(
+        builder()
+            .with_special_tokens(special_tokens(1))
+            .build()
+            .is_ok()
+    );
+    assert!(
+        builder()
+            .with_special_tokens(special_tokens(100))
+            .build()
+            .is_err()
+    );
+
+    let audio = AudioConfig::new(
+        16000,
+        12.5,
+        AudioSpectrogramConfig::new(128, 160, 400).unwrap(),
+        None,
+    )
+    .unwrap();
+    assert!(builder().with_audio(audio.clone()).build().is_ok());
+    assert!(
+        ModelData::builder(PATTERN, TokenizerVersion::V3)
+            .with_byte_tokens()
+            .with_audio(audio)
+            .build()
+            .is_err()
+    );
+}
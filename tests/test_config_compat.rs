@@ -0,0 +1,74 @@
+use std::sync::OnceLock;
+use tekken::config::ModelData;
+use tekken::tekkenizer::Tekkenizer;
+
+static MODEL_JSON: OnceLock<serde_json::Value> = OnceLock::new();
+
+/// A small copy of the test tokenizer as JSON, with keys from a newer revision.
+fn get_model_json() -> &'static serde_json::Value {
+    MODEL_JSON.get_or_init(|| {
+        let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file");
+        let model_data = tokenizer
+            .pruned(tokenizer.num_special_tokens() + 2048)
+            .expect("Failed to prune tokenizer")
+            .to_model_data();
+
+        let mut json = serde_json::to_value(model_data).unwrap();
+        json["type"] = "Tekken".into();
+        json["config"]["future_option"] = true.into();
+        json["multimodal"] = serde_json::json!({"image_patch_size": 16, "max_image_size": 1024});
+        let audio = json["audio"].as_object_mut().unwrap();
+        let encoding_config = audio.remove("audio_encoding_config").unwrap();
+        audio.insert("encoding_config".to_string(), encoding_config);
+        audio.insert("transcription_delay_ms".to_string(), 480.into());
+        json
+    })
+}
+
+#[test]
+fn test_newer_files_load() {
+    let bytes = serde_json::to_vec(get_model_json()).unwrap();
+    let tokenizer = Tekkenizer::from_bytes(&bytes).unwrap();
+    assert!(tokenizer.audio_config().is_some());
+    assert_eq!(
+        tokenizer.audio_config().unwrap().extra["transcription_delay_ms"],
+        480
+    );
+}
+
+#[test]
+fn test_unknown_fields_round_trip() {
+    let model_data: ModelData = serde_json::from_value(get_model_json().clone()).unwrap();
+    assert_eq!(model_data.extra["type"], "Tekken");
+    assert_eq!(model_data.config.extra["future_option"], true);
+    assert_eq!(model_data.image.as_ref().unwrap().image_patch_size, 16);
+    assert_eq!(
+        model_data
+            .audio
+            .as_ref()
+            .unwrap()
+            .audio_encoding_config
+            .num_mel_bins,
+        128
+    );
+
+    let written = serde_json::to_value(&model_data).unwrap();
+    assert_eq!(written["type"], "Tekken");
+    assert_eq!(written["config"]["future_option"], true);
+    assert_eq!(written["image"]["max_image_size"], 1024);
+    assert_eq!(written["audio"]["transcription_delay_ms"], 480);
+}
+
+#[test]
+fn test_exported_files_have_no_extra_keys() {
+    let tokenizer = Tekkenizer::from_bytes(&serde_json::to_vec(get_model_json()).unwrap()).unwrap();
+    let written = serde_json::to_value(tokenizer.to_model_data()).unwrap();
+    let mut keys: Vec<&String> = written.as_object().unwrap().keys().collect();
+    keys.sort();
+    // The `multimodal` section is kept by the tokenizer and written as `image`
+    assert_eq!(
+        keys,
+        ["audio", "config", "image", "special_tokens", "vocab"]
+    );
+}
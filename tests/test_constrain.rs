@@ -0,0 +1,89 @@
+use std::sync::OnceLock;
+use tekken::constrain::TokenConstraint;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+fn piece(tokenizer: &Tekkenizer, id: u32) -> Vec<u8> {
+    tokenizer
+        .id_to_byte_piece(id, SpecialTokenPolicy::Keep)
+        .unwrap()
+}
+
+#[test]
+fn test_allowed_tokens_are_viable_prefixes() {
+    let tokenizer = get_tokenizer();
+    let constraint = TokenConstraint::from_regex(tokenizer, "(yes|no)").unwrap();
+    let start = constraint.start();
+    assert!(!constraint.is_match(start));
+
+    let allowed = constraint.allowed_tokens(start);
+    assert!(!allowed.is_empty());
+    assert!(!allowed.contains(&tokenizer.eos_id().unwrap()));
+    for &id in &allowed {
+        let bytes = piece(tokenizer, id);
+        assert!(b"yes".starts_with(&bytes) || b"no".starts_with(&bytes));
+    }
+    assert!(allowed.contains(&tokenizer.byte_to_token_id(b'y')));
+    assert!(!allowed.contains(&tokenizer.byte_to_token_id(b'x')));
+
+    let mask = constraint.allowed_mask(start);
+    assert_eq!(mask.len(), tokenizer.vocab_size());
+    assert_eq!(
+        mask.iter().filter(|&&allowed| allowed).count(),
+        allowed.len()
+    );
+}
+
+#[test]
+fn test_advance_to_full_match() {
+    let tokenizer = get_tokenizer();
+    let constraint = TokenConstraint::from_regex(tokenizer, "[0-9]{2}-[a-z]+").unwrap();
+
+    let mut state = constraint.start();
+    for &byte in b"42-" {
+        state = constraint
+            .advance(state, tokenizer.byte_to_token_id(byte))
+            .unwrap()
+            .unwrap();
+    }
+    assert!(!constraint.is_match(state));
+    assert!(
+        constraint
+            .advance(state, tokenizer.byte_to_token_id(b'7'))
+            .unwrap()
+            .is_none()
+    );
+
+    for token in tokenizer.encode("abc", false, false).unwrap() {
+        state = constraint.advance(state, token).unwrap().unwrap();
+    }
+    assert!(constraint.is_match(state));
+    assert!(
+        constraint
+            .allowed_tokens(state)
+            .contains(&tokenizer.eos_id().unwrap())
+    );
+
+    assert!(
+        constraint
+            .advance(state, tokenizer.eos_id().unwrap())
+            .unwrap()
+            .is_none()
+    );
+    assert!(constraint.advance(state, u32::MAX).is_err());
+}
+
+#[test]
+fn test_invalid_pattern() {
+    let tokenizer = get_tokenizer();
+    assert!(TokenConstraint::from_regex(tokenizer, "(unclosed").is_err());
+}
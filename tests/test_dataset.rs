@@ -0,0 +1,144 @@
+use std::sync::OnceLock;
+
+use arrow_array::cast::AsArray;
+use arrow_array::types::{UInt32Type, UInt64Type};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use tekken::dataset::{DatasetOptions, ParquetDatasetWriter, schema};
+use tekken::errors::TokenizerError;
+use tekken::parallel::{CancellationToken, ParallelismConfig};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_record_batch_columns() {
+    let tokenizer = get_tokenizer();
+    let batch = tokenizer
+        .encode_record_batch(
+            &[("Hello, world!", 0), ("Hello", 14)],
+            &DatasetOptions::new(true, false),
+        )
+        .unwrap();
+
+    assert_eq!(batch.schema(), schema());
+    assert_eq!(batch.num_rows(), 2);
+
+    let input_ids = batch.column(0).as_list::<i32>();
+    let first = input_ids.value(0);
+    assert_eq!(
+        first.as_primitive::<UInt32Type>().values(),
+        &[1, 22177, 1044, 4304, 1033]
+    );
+    assert_eq!(
+        batch.column(1).as_primitive::<UInt32Type>().values(),
+        &[5, 2]
+    );
+    assert_eq!(
+        batch.column(2).as_primitive::<UInt64Type>().values(),
+        &[0, 14]
+    );
+    assert_eq!(
+        batch.column(3).as_primitive::<UInt64Type>().values(),
+        &[13, 19]
+    );
+}
+
+#[test]
+fn test_parquet_round_trip() {
+    for parallelism in [
+        ParallelismConfig::sequential(),
+        ParallelismConfig::with_max_threads(2).unwrap(),
+    ] {
+        assert_parquet_round_trip(parallelism);
+    }
+}
+
+fn assert_parquet_round_trip(parallelism: ParallelismConfig) {
+    let tokenizer = get_tokenizer();
+    let corpus = ["first line", "second line 🚀", "", "fourth"];
+    let options = DatasetOptions::new(false, true).with_batch_size(3);
+
+    let mut writer = ParquetDatasetWriter::new(tokenizer, tempfile::tempfile().unwrap(), options)
+        .unwrap()
+        .with_parallelism(parallelism);
+    let mut offset = 0;
+    for line in corpus {
+        writer.write(line, offset).unwrap();
+        offset += line.len() as u64 + 1;
+    }
+    let file = writer.finish().unwrap();
+
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap();
+    let batches: Vec<_> = reader.map(Result::unwrap).collect();
+    let num_tokens: Vec<u32> = batches
+        .iter()
+        .flat_map(|batch| {
+            batch
+                .column(1)
+                .as_primitive::<UInt32Type>()
+                .values()
+                .to_vec()
+        })
+        .collect();
+    let expected: Vec<u32> = corpus
+        .iter()
+        .map(|line| tokenizer.encode(line, false, true).unwrap().len() as u32)
+        .collect();
+    assert_eq!(num_tokens, expected);
+}
+
+#[test]
+fn test_record_batch_on_dedicated_pool() {
+    let tokenizer = get_tokenizer();
+    let documents = [("Hello, world!", 0), ("Hello", 14), ("数字 12345", 20)];
+    let options = DatasetOptions::default();
+    let expected = tokenizer.encode_record_batch(&documents, &options).unwrap();
+    let batch = tokenizer
+        .encode_record_batch_with(
+            &documents,
+            &options,
+            &ParallelismConfig::with_max_threads(2).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(batch, expected);
+}
+
+#[test]
+fn test_cancelled_writer_keeps_written_rows() {
+    let tokenizer = get_tokenizer();
+    let token = CancellationToken::new();
+    let mut writer = ParquetDatasetWriter::new(
+        tokenizer,
+        tempfile::tempfile().unwrap(),
+        DatasetOptions::default(),
+    )
+    .unwrap()
+    .with_parallelism(ParallelismConfig::default().with_cancellation(token.clone()));
+    writer.write("first", 0).unwrap();
+    writer.write("second", 6).unwrap();
+
+    token.cancel();
+    assert!(matches!(
+        writer.write("third", 13),
+        Err(TokenizerError::Cancelled)
+    ));
+    let file = writer.finish().unwrap();
+
+    let rows: usize = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(|batch| batch.unwrap().num_rows())
+        .sum();
+    assert_eq!(rows, 2);
+}
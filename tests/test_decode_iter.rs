@@ -0,0 +1,71 @@
+use std::sync::OnceLock;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_decode_iter_concatenates_to_decode() {
+    let tokenizer = get_tokenizer();
+    let test_cases = ["Hello, world!", "🚀🌟⭐ emojis", "北京 and مرحبا", ""];
+
+    for text in test_cases {
+        let tokens = tokenizer.encode(text, true, true).unwrap();
+        for policy in [SpecialTokenPolicy::Keep, SpecialTokenPolicy::Ignore] {
+            let expected = tokenizer.decode(&tokens, policy).unwrap();
+            let pieces: Vec<_> = tokenizer
+                .decode_iter(&tokens, policy)
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+            assert!(pieces.iter().all(|piece| !piece.is_empty()));
+            assert_eq!(pieces.concat(), expected, "Mismatch for input: {text:?}");
+        }
+    }
+}
+
+#[test]
+fn test_decode_iter_stops_early() {
+    let tokenizer = get_tokenizer();
+    let tokens = tokenizer
+        .encode(
+            "First sentence. STOP and the rest is never decoded",
+            false,
+            false,
+        )
+        .unwrap();
+
+    let mut text = String::new();
+    let mut consumed = 0;
+    for piece in tokenizer.decode_iter(&tokens, SpecialTokenPolicy::Ignore) {
+        text.push_str(&piece.unwrap());
+        consumed += 1;
+        if text.contains("STOP") {
+            break;
+        }
+    }
+
+    assert!(text.ends_with("STOP"));
+    assert!(consumed < tokens.len());
+}
+
+#[test]
+fn test_decode_iter_fuses_after_error() {
+    let tokenizer = get_tokenizer();
+    let mut tokens = tokenizer.encode("Hello", false, false).unwrap();
+    tokens.push(tokenizer.bos_id().unwrap());
+    tokens.extend(tokenizer.encode(" world", false, false).unwrap());
+
+    let results: Vec<_> = tokenizer
+        .decode_iter(&tokens, SpecialTokenPolicy::Raise)
+        .collect();
+    assert!(results.last().unwrap().is_err());
+    assert_eq!(results.iter().filter(|r| r.is_err()).count(), 1);
+}
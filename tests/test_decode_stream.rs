@@ -0,0 +1,62 @@
+use std::sync::{Arc, OnceLock};
+
+use futures::executor::block_on;
+use futures::stream::{self, StreamExt};
+use tekken::SpecialTokenPolicy;
+use tekken::incremental::DecodeStream;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_stream_yields_utf8_safe_chunks() {
+    let tokenizer = get_tokenizer();
+    let text = "Streaming 🚀 output: naïve café";
+    let tokens = tokenizer.encode(text, true, true).unwrap();
+
+    let chunks: Vec<String> = block_on(
+        DecodeStream::new(tokenizer, stream::iter(tokens), SpecialTokenPolicy::Ignore)
+            .map(Result::unwrap)
+            .collect(),
+    );
+    assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+    assert_eq!(chunks.concat(), text);
+}
+
+#[test]
+fn test_stream_with_shared_tokenizer() {
+    let tokenizer = Arc::new(
+        Tekkenizer::from_file("tests/assets/tekken.json").expect("Failed to load tokenizer"),
+    );
+    let tokens = tokenizer.encode("Hello, world!", false, false).unwrap();
+
+    let chunks: Vec<String> = block_on(
+        DecodeStream::new(tokenizer, stream::iter(tokens), SpecialTokenPolicy::Raise)
+            .map(Result::unwrap)
+            .collect(),
+    );
+    assert_eq!(chunks, ["Hello", ",", " world", "!"]);
+}
+
+#[test]
+fn test_stream_errors_on_truncated_character() {
+    let tokenizer = get_tokenizer();
+    let tokens = vec![
+        tokenizer.byte_to_token_id(b'a'),
+        tokenizer.byte_to_token_id(0xF0),
+    ];
+
+    let results: Vec<_> = block_on(
+        DecodeStream::new(tokenizer, stream::iter(tokens), SpecialTokenPolicy::Ignore).collect(),
+    );
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0].as_ref().unwrap(), "a");
+    assert!(results[1].is_err());
+}
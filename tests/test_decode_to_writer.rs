@@ -0,0 +1,71 @@
+use std::sync::OnceLock;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_decode_to_writer_matches_decode_all() {
+    let tokenizer = get_tokenizer();
+    let test_cases = [
+        "Hello, world!",
+        "🚀 Unicode emojis and 日本語 text",
+        "café naïve Здравствуй",
+        "",
+    ];
+
+    for text in test_cases {
+        let tokens = tokenizer.encode(text, true, true).unwrap();
+        for policy in [SpecialTokenPolicy::Keep, SpecialTokenPolicy::Ignore] {
+            let expected = tokenizer.decode_all(&tokens, policy).unwrap().join("");
+
+            let mut written = String::new();
+            tokenizer
+                .decode_to_writer(&tokens, policy, &mut written)
+                .unwrap();
+            assert_eq!(written, expected, "Mismatch for input: {text:?}");
+        }
+    }
+}
+
+#[test]
+fn test_decode_to_io_writer() {
+    let tokenizer = get_tokenizer();
+    let text = "Streaming 🌟 text to a socket";
+    let tokens = tokenizer.encode(text, true, false).unwrap();
+
+    let mut buffer: Vec<u8> = Vec::new();
+    tokenizer
+        .decode_to_io_writer(&tokens, SpecialTokenPolicy::Ignore, &mut buffer)
+        .unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), text);
+}
+
+#[test]
+fn test_decode_to_writer_errors() {
+    let tokenizer = get_tokenizer();
+    let tokens = tokenizer.encode("Hello", true, true).unwrap();
+
+    let mut written = String::new();
+    assert!(
+        tokenizer
+            .decode_to_writer(&tokens, SpecialTokenPolicy::Raise, &mut written)
+            .is_err()
+    );
+
+    // A lone UTF-8 lead byte cannot be decoded on its own
+    let lead_byte = 0xF0 + tokenizer.num_special_tokens() as u32;
+    let mut written = String::new();
+    assert!(
+        tokenizer
+            .decode_to_writer(&[lead_byte], SpecialTokenPolicy::Ignore, &mut written)
+            .is_err()
+    );
+}
@@ -0,0 +1,153 @@
+use base64::{Engine as _, engine::general_purpose};
+use tekken::config::{TokenInfo, TokenizerVersion};
+use tekken::errors::TokenizerError;
+use tekken::special_tokens::{SpecialTokenInfo, SpecialTokenPolicy};
+use tekken::tekkenizer::Tekkenizer;
+
+fn create_tokenizer() -> Tekkenizer {
+    let vocab: Vec<TokenInfo> = (0..256)
+        .map(|i| TokenInfo {
+            rank: i,
+            token_bytes: general_purpose::STANDARD.encode([i as u8]),
+            token_str: None,
+        })
+        .collect();
+
+    // Special tokens with a gap between rank 2 and rank 6
+    let special_tokens = vec![
+        SpecialTokenInfo {
+            rank: 0,
+            token_str: "<unk>".to_string(),
+            is_control: true,
+        },
+        SpecialTokenInfo {
+            rank: 1,
+            token_str: "<s>".to_string(),
+            is_control: true,
+        },
+        SpecialTokenInfo {
+            rank: 2,
+            token_str: "</s>".to_string(),
+            is_control: true,
+        },
+        SpecialTokenInfo {
+            rank: 6,
+            token_str: "[AUDIO]".to_string(),
+            is_control: true,
+        },
+    ];
+
+    Tekkenizer::new(
+        vocab,
+        &special_tokens,
+        String::new(),
+        266,
+        10,
+        TokenizerVersion::V7,
+        None,
+    )
+    .expect("Failed to create tokenizer")
+}
+
+#[test]
+fn test_special_tokens_are_laid_out_by_rank() {
+    let tokenizer = create_tokenizer();
+
+    assert_eq!(tokenizer.get_control_token("[AUDIO]").unwrap(), 6);
+    assert_eq!(tokenizer.id_to_piece(6).unwrap(), "[AUDIO]");
+    assert_eq!(tokenizer.id_to_piece(3).unwrap(), "<SPECIAL_3>");
+    assert_eq!(tokenizer.id_to_piece(9).unwrap(), "<SPECIAL_9>");
+    assert_eq!(
+        tokenizer
+            .decode(&[1, 6, 2], SpecialTokenPolicy::Keep)
+            .unwrap(),
+        "<s>[AUDIO]</s>"
+    );
+}
+
+#[test]
+fn test_decode_is_total_over_token_ids() {
+    let tokenizer = create_tokenizer();
+    let policies = [
+        SpecialTokenPolicy::Keep,
+        SpecialTokenPolicy::Ignore,
+        SpecialTokenPolicy::Raise,
+    ];
+    let ids = (0..300).chain([u32::MAX - 1, u32::MAX, 1 << 31]);
+
+    for token_id in ids {
+        for policy in policies {
+            let result = tokenizer.decode(&[token_id], policy);
+            let all = tokenizer.decode_all(&[token_id], policy);
+            let mut written = String::new();
+            let streamed = tokenizer.decode_to_writer(&[token_id], policy, &mut written);
+
+            if token_id as usize >= tokenizer.vocab_size() {
+                for err in [result.unwrap_err(), all.unwrap_err(), streamed.unwrap_err()] {
+                    assert!(
+                        matches!(
+                            err,
+                            TokenizerError::TokenOutOfRange { token_id: id, vocab_size: 266 }
+                                if id == token_id
+                        ),
+                        "Unexpected error for {token_id}: {err}"
+                    );
+                }
+            }
+        }
+
+        let _ = tokenizer.id_to_piece(token_id);
+        let _ = tokenizer.id_to_byte_piece(token_id, SpecialTokenPolicy::Keep);
+    }
+}
+
+#[test]
+fn test_vocab_size_smaller_than_special_tokens_is_rejected() {
+    let result = Tekkenizer::new(
+        Vec::new(),
+        &[],
+        String::new(),
+        5,
+        10,
+        TokenizerVersion::V7,
+        None,
+    );
+    assert!(matches!(result, Err(TokenizerError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_vocab_without_byte_tokens_is_rejected() {
+    let vocab: Vec<TokenInfo> = (0..256)
+        .map(|i| TokenInfo {
+            rank: i,
+            token_bytes: general_purpose::STANDARD.encode([i as u8]),
+            token_str: None,
+        })
+        .collect();
+
+    // Only 100 of the 256 byte tokens fit next to the special tokens
+    let result = Tekkenizer::new(
+        vocab,
+        &[],
+        String::new(),
+        110,
+        10,
+        TokenizerVersion::V7,
+        None,
+    );
+    assert!(matches!(result, Err(TokenizerError::InvalidConfig(_))));
+}
+
+#[test]
+fn test_huge_special_token_count_is_rejected() {
+    let result = Tekkenizer::new(
+        Vec::new(),
+        &[],
+        String::new(),
+        1 << 31,
+        1 << 31,
+        TokenizerVersion::V7,
+        None,
+    );
+    assert!(matches!(result, Err(TokenizerError::InvalidConfig(_))));
+}
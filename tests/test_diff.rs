@@ -0,0 +1,100 @@
+use std::process::Command;
+use std::sync::OnceLock;
+use tekken::config::ModelData;
+use tekken::diff::{SpecialTokenChange, diff_model_data};
+use tekken::tekkenizer::Tekkenizer;
+
+const TOKENIZER_PATH: &str = "tests/assets/tekken.json";
+
+static MODEL_DATA: OnceLock<ModelData> = OnceLock::new();
+
+fn get_model_data() -> &'static ModelData {
+    MODEL_DATA.get_or_init(|| {
+        Tekkenizer::from_file(TOKENIZER_PATH)
+            .expect("Failed to load tokenizer from file")
+            .to_model_data()
+    })
+}
+
+#[test]
+fn test_identical_files() {
+    let changes = tekken::diff(TOKENIZER_PATH, TOKENIZER_PATH).unwrap();
+    assert!(changes.is_empty());
+    assert_eq!(changes.to_string(), "Tokenizers are identical\n");
+}
+
+#[test]
+fn test_vocab_changes() {
+    let old = get_model_data();
+    let mut new = old.clone();
+
+    // Swap two ranks, drop the last token and add a new one
+    let (a, b) = (1000, 1001);
+    let bytes_a = new.vocab[a].token_bytes.clone();
+    new.vocab[a].token_bytes = new.vocab[b].token_bytes.clone();
+    new.vocab[b].token_bytes = bytes_a;
+    let removed = new.vocab.pop().unwrap();
+    new.vocab.push(tekken::TokenInfo {
+        rank: removed.rank,
+        token_bytes: "AAEC/w==".to_string(),
+        token_str: None,
+    });
+
+    let changes = diff_model_data(old, &new).unwrap();
+    assert_eq!(changes.reordered.len(), 2);
+    assert_eq!(changes.removed.len(), 1);
+    assert_eq!(changes.removed[0].rank, removed.rank);
+    assert_eq!(changes.added.len(), 1);
+    assert_eq!(changes.added[0].bytes, vec![0, 1, 2, 255]);
+    assert!(changes.special_tokens.is_empty());
+    assert!(changes.config.is_empty());
+
+    let report = changes.to_string();
+    assert!(report.contains("Reordered tokens: 2"));
+    assert!(report.contains("b\"\\x00\\x01\\x02\\xff\""));
+}
+
+#[test]
+fn test_special_token_and_config_changes() {
+    let old = get_model_data();
+    let mut new = old.clone();
+    let special_tokens = new.special_tokens.as_mut().unwrap();
+    special_tokens[0].token_str = "<unknown>".to_string();
+    special_tokens.pop();
+    new.config.version = "v11".to_string();
+    new.audio = None;
+
+    let changes = diff_model_data(old, &new).unwrap();
+    assert_eq!(changes.special_tokens.len(), 2);
+    assert!(matches!(
+        &changes.special_tokens[0],
+        SpecialTokenChange::Changed { new, .. } if new.token_str == "<unknown>"
+    ));
+    assert!(matches!(
+        changes.special_tokens[1],
+        SpecialTokenChange::Removed(_)
+    ));
+
+    let fields: Vec<&str> = changes.config.iter().map(|c| c.field.as_str()).collect();
+    assert_eq!(fields, ["version", "audio"]);
+}
+
+#[test]
+fn test_cli_diff() {
+    let output = Command::new(env!("CARGO_BIN_EXE_tekken-rs"))
+        .args(["diff", TOKENIZER_PATH, TOKENIZER_PATH])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Tokenizers are identical\n"
+    );
+
+    let status = Command::new(env!("CARGO_BIN_EXE_tekken-rs"))
+        .arg("diff")
+        .output()
+        .unwrap()
+        .status;
+    assert_eq!(status.code(), Some(2));
+}
@@ -0,0 +1,74 @@
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+const SUFFIXES: &[&str] = &[
+    " ", " ", "hello", "world", "  ", "\n", "\n", "123", "4", "56", "'s", " it", "'ll", "!!", "?",
+    "\r\n", "", "Zürich", "é", "🚀", "🚀", "    ", "x", "\t", "END.",
+];
+
+#[test]
+fn test_appending_matches_full_encoding() {
+    let tokenizer = get_tokenizer();
+    let mut text = "The quick brown fox jumps over the lazy dog. ".repeat(12);
+    for add_bos in [false, true] {
+        let mut tokens = tokenizer.encode(&text, add_bos, false).unwrap();
+        for suffix in SUFFIXES {
+            let appended = tokenizer.encode_appended(&text, &tokens, suffix).unwrap();
+            text.push_str(suffix);
+            let expected = tokenizer.encode(&text, add_bos, false).unwrap();
+            assert_eq!(appended.tokens, expected, "after appending {suffix:?}");
+            assert_eq!(
+                appended.tokens[..appended.reused],
+                tokens[..appended.reused]
+            );
+            assert!(
+                appended.reused + 16 > tokens.len(),
+                "{suffix:?} re-encoded too much"
+            );
+            tokens = appended.tokens;
+        }
+    }
+}
+
+#[test]
+fn test_short_and_mismatched_inputs_are_encoded_again() {
+    let tokenizer = get_tokenizer();
+
+    let tokens = tokenizer.encode("Hi", true, false).unwrap();
+    let appended = tokenizer.encode_appended("Hi", &tokens, " there").unwrap();
+    assert_eq!(
+        appended.tokens,
+        tokenizer.encode("Hi there", true, false).unwrap()
+    );
+    assert_eq!(appended.reused, 1);
+
+    let appended = tokenizer.encode_appended("", &[], "Hi").unwrap();
+    assert_eq!(
+        appended.tokens,
+        tokenizer.encode("Hi", false, false).unwrap()
+    );
+    assert_eq!(appended.reused, 0);
+
+    // Tokens ending with EOS do not match the previous text's tail
+    let text = "Some long text that keeps going. ".repeat(20);
+    let with_eos = tokenizer.encode(&text, true, true).unwrap();
+    let appended = tokenizer
+        .encode_appended(&text, &with_eos, "More.")
+        .unwrap();
+    assert_eq!(
+        appended.tokens,
+        tokenizer
+            .encode(&format!("{text}More."), true, false)
+            .unwrap()
+    );
+    assert_eq!(appended.reused, 1);
+}
@@ -0,0 +1,52 @@
+use std::sync::OnceLock;
+use tekken::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_bounded_prefix_of_full_encoding() {
+    let tokenizer = get_tokenizer();
+    let text = "Hello, world!";
+
+    let (tokens, consumed) = tokenizer.encode_bounded(text, 2).unwrap();
+    assert_eq!(tokens, vec![22177, 1044]);
+    assert_eq!(consumed, 6);
+    assert_eq!(&text[..consumed], "Hello,");
+
+    let (all, consumed) = tokenizer.encode_bounded(text, 100).unwrap();
+    assert_eq!(all, tokenizer.encode(text, false, false).unwrap());
+    assert_eq!(consumed, text.len());
+
+    let (none, consumed) = tokenizer.encode_bounded(text, 0).unwrap();
+    assert!(none.is_empty());
+    assert_eq!(consumed, 0);
+}
+
+#[test]
+fn test_bounded_stops_on_char_boundary() {
+    let tokenizer = get_tokenizer();
+    let text = "ok 𓀀𓀁𓀂 done";
+    let full = tokenizer.encode(text, false, false).unwrap();
+    assert!(full.iter().any(|&t| tokenizer.is_byte(t)));
+
+    for max_tokens in 0..=full.len() {
+        let (tokens, consumed) = tokenizer.encode_bounded(text, max_tokens).unwrap();
+        assert!(tokens.len() <= max_tokens);
+        assert!(text.is_char_boundary(consumed));
+        assert_eq!(tokens, full[..tokens.len()]);
+        assert_eq!(
+            tokenizer
+                .decode(&tokens, SpecialTokenPolicy::Raise)
+                .unwrap(),
+            &text[..consumed]
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::OnceLock;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_valid_utf8_matches_encode() {
+    let tokenizer = get_tokenizer();
+    let text = "Hello, world! café 🚀";
+    assert_eq!(
+        tokenizer.encode_bytes(text.as_bytes(), true, true).unwrap(),
+        tokenizer.encode(text, true, true).unwrap()
+    );
+}
+
+#[test]
+fn test_invalid_utf8_round_trips() {
+    let tokenizer = get_tokenizer();
+    let data = b"Hello\xff\xfe world \xe2\x82 end\x00";
+    let tokens = tokenizer.encode_bytes(data, false, false).unwrap();
+    assert!(tokens.contains(&tokenizer.byte_to_token_id(0xff)));
+    assert_eq!(
+        tokenizer
+            .decode_bytes(&tokens, SpecialTokenPolicy::Raise)
+            .unwrap(),
+        data
+    );
+}
+
+#[test]
+fn test_random_bytes_round_trip() {
+    let tokenizer = get_tokenizer();
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let data: Vec<u8> = (0..4096)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state.to_le_bytes()[0]
+        })
+        .collect();
+
+    let tokens = tokenizer.encode_bytes(&data, true, true).unwrap();
+    assert_eq!(
+        tokenizer
+            .decode_bytes(&tokens, SpecialTokenPolicy::Ignore)
+            .unwrap(),
+        data
+    );
+}
+
+#[test]
+fn test_decode_bytes_special_token_policies() {
+    let tokenizer = get_tokenizer();
+    let tokens = tokenizer.encode_bytes(b"hi", true, true).unwrap();
+    assert_eq!(
+        tokenizer
+            .decode_bytes(&tokens, SpecialTokenPolicy::Keep)
+            .unwrap(),
+        b"<s>hi</s>"
+    );
+    assert!(
+        tokenizer
+            .decode_bytes(&tokens, SpecialTokenPolicy::Raise)
+            .is_err()
+    );
+    assert!(
+        tokenizer
+            .decode_bytes(&[u32::MAX], SpecialTokenPolicy::Ignore)
+            .is_err()
+    );
+}
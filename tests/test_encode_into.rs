@@ -0,0 +1,47 @@
+use std::sync::OnceLock;
+use tekken::tekkenizer::{EncodeOptions, Tekkenizer};
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_encode_into_matches_encode() {
+    let tokenizer = get_tokenizer();
+    let test_cases = ["Hello, world!", "", "The quick brown fox", "🚀 café"];
+
+    for text in test_cases {
+        for (add_bos, add_eos) in [(false, false), (true, false), (false, true), (true, true)] {
+            let expected = tokenizer.encode(text, add_bos, add_eos).unwrap();
+            let mut buffer = Vec::new();
+            tokenizer
+                .encode_into(text, &mut buffer, EncodeOptions::new(add_bos, add_eos))
+                .unwrap();
+            assert_eq!(buffer, expected, "Mismatch for input: {text:?}");
+        }
+    }
+}
+
+#[test]
+fn test_encode_into_appends_to_buffer() {
+    let tokenizer = get_tokenizer();
+
+    let first = tokenizer.encode("Hello", true, false).unwrap();
+    let second = tokenizer.encode(" world", false, true).unwrap();
+
+    let mut buffer = Vec::with_capacity(16);
+    tokenizer
+        .encode_into("Hello", &mut buffer, EncodeOptions::new(true, false))
+        .unwrap();
+    tokenizer
+        .encode_into(" world", &mut buffer, EncodeOptions::new(false, true))
+        .unwrap();
+
+    let expected: Vec<u32> = first.into_iter().chain(second).collect();
+    assert_eq!(buffer, expected);
+}
@@ -0,0 +1,191 @@
+use std::sync::OnceLock;
+use tekken::encoding::{EncodingOptions, Padding, PaddingSide};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_masks_and_offsets() {
+    let tokenizer = get_tokenizer();
+    let text = "Hello, world!";
+    let encoding = tokenizer
+        .encode_plus(text, &EncodingOptions::new(true, true))
+        .unwrap();
+
+    assert_eq!(encoding.ids, vec![1, 22177, 1044, 4304, 1033, 2]);
+    assert_eq!(encoding.attention_mask, vec![1; 6]);
+    assert_eq!(encoding.special_tokens_mask, vec![1, 0, 0, 0, 0, 1]);
+    assert_eq!(
+        encoding.offsets,
+        vec![(0, 0), (0, 5), (5, 6), (6, 12), (12, 13), (0, 0)]
+    );
+    for &(start, end) in &encoding.offsets[1..5] {
+        assert!(!text[start..end].is_empty());
+    }
+}
+
+#[test]
+fn test_padding_to_max_length() {
+    let tokenizer = get_tokenizer();
+    let pad_id = tokenizer.pad_id().unwrap();
+    let options = EncodingOptions::new(true, false).with_padding(Padding::MaxLength(8));
+    let encoding = tokenizer.encode_plus("Hello", &options).unwrap();
+
+    assert_eq!(encoding.len(), 8);
+    assert_eq!(encoding.ids[..2], [1, 22177]);
+    assert!(encoding.ids[2..].iter().all(|&id| id == pad_id));
+    assert_eq!(encoding.attention_mask, vec![1, 1, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(encoding.special_tokens_mask, vec![1, 0, 1, 1, 1, 1, 1, 1]);
+    assert_eq!(encoding.offsets[7], (0, 0));
+}
+
+#[test]
+fn test_truncation_keeps_bos_and_eos() {
+    let tokenizer = get_tokenizer();
+    let options = EncodingOptions::new(true, true).with_max_length(4);
+    let encoding = tokenizer.encode_plus("Hello, world!", &options).unwrap();
+
+    assert_eq!(encoding.ids, vec![1, 22177, 1044, 2]);
+    assert_eq!(encoding.special_tokens_mask, vec![1, 0, 0, 1]);
+
+    let too_small = EncodingOptions::new(true, true).with_max_length(1);
+    assert!(tokenizer.encode_plus("Hello", &too_small).is_err());
+}
+
+#[test]
+fn test_batch_pads_to_longest() {
+    let tokenizer = get_tokenizer();
+    let options = EncodingOptions::new(true, false)
+        .with_padding(Padding::Longest)
+        .with_pad_to_multiple_of(4);
+    let encodings = tokenizer
+        .encode_plus_batch(&["Hello", "Hello, world!"], &options)
+        .unwrap();
+
+    // The longest encoding has 5 tokens, rounded up to 8
+    assert!(encodings.iter().all(|encoding| encoding.len() == 8));
+    assert_eq!(encodings[0].attention_mask.iter().sum::<u32>(), 2);
+    assert_eq!(encodings[1].attention_mask.iter().sum::<u32>(), 5);
+
+    let unpadded = tokenizer
+        .encode_plus_batch(
+            &["Hello", "Hello, world!"],
+            &EncodingOptions::new(false, false),
+        )
+        .unwrap();
+    assert_eq!(unpadded[0].len(), 1);
+    assert_eq!(unpadded[1].len(), 4);
+}
+
+#[test]
+fn test_pair_layout_and_type_ids() {
+    let tokenizer = get_tokenizer();
+    let encoding = tokenizer
+        .encode_pair("Hello", "Hello, world!", &EncodingOptions::new(true, true))
+        .unwrap();
+
+    assert_eq!(
+        encoding.ids,
+        vec![1, 22177, 2, 1, 22177, 1044, 4304, 1033, 2]
+    );
+    assert_eq!(encoding.type_ids, vec![0, 0, 0, 1, 1, 1, 1, 1, 1]);
+    assert_eq!(
+        encoding.special_tokens_mask,
+        vec![1, 0, 1, 1, 0, 0, 0, 0, 1]
+    );
+    // Offsets are relative to each segment's own text
+    assert_eq!(encoding.offsets[1], (0, 5));
+    assert_eq!(encoding.offsets[4], (0, 5));
+    assert_eq!(encoding.offsets[7], (12, 13));
+
+    let single = tokenizer
+        .encode_plus("Hello", &EncodingOptions::new(true, true))
+        .unwrap();
+    assert_eq!(single.type_ids, vec![0, 0, 0]);
+}
+
+#[test]
+fn test_pair_truncates_longest_first() {
+    let tokenizer = get_tokenizer();
+    let options = EncodingOptions::new(true, false)
+        .with_max_length(5)
+        .with_padding(Padding::MaxLength(6));
+    let encoding = tokenizer
+        .encode_pair("Hello", "Hello, world!", &options)
+        .unwrap();
+
+    // 3 text tokens fit; the 4-token second segment is trimmed to 2
+    assert_eq!(encoding.ids[..5], [1, 22177, 1, 22177, 1044]);
+    assert_eq!(encoding.type_ids, vec![0, 0, 1, 1, 1, 0]);
+    assert_eq!(encoding.attention_mask, vec![1, 1, 1, 1, 1, 0]);
+}
+
+#[test]
+fn test_left_padding() {
+    let tokenizer = get_tokenizer();
+    let pad_id = tokenizer.pad_id().unwrap();
+    let options = EncodingOptions::new(true, false)
+        .with_padding(Padding::MaxLength(8))
+        .with_padding_side(PaddingSide::Left);
+    let right = tokenizer
+        .encode_plus("Hello", &options.with_padding_side(PaddingSide::Right))
+        .unwrap();
+    let left = tokenizer.encode_plus("Hello", &options).unwrap();
+    assert_eq!(left.len(), 8);
+
+    let real = right.attention_mask.iter().filter(|&&bit| bit == 1).count();
+    let padding = 8 - real;
+    assert!(left.ids[..padding].iter().all(|&id| id == pad_id));
+    assert_eq!(left.ids[padding..], right.ids[..real]);
+    assert_eq!(left.attention_mask[..padding], vec![0; padding]);
+    assert_eq!(left.offsets[padding..], right.offsets[..real]);
+    assert_eq!(
+        left.special_tokens_mask[padding..],
+        right.special_tokens_mask[..real]
+    );
+
+    let batch = tokenizer
+        .encode_plus_batch(
+            &["Hi", "A somewhat longer text"],
+            &EncodingOptions::new(true, false)
+                .with_padding(Padding::Longest)
+                .with_padding_side(PaddingSide::Left),
+        )
+        .unwrap();
+    assert_eq!(batch[0].len(), batch[1].len());
+    assert_eq!(
+        batch[0].ids.last(),
+        tokenizer.encode("Hi", false, false).unwrap().last()
+    );
+    assert_eq!(batch[0].attention_mask.last(), Some(&1));
+}
+
+#[test]
+fn test_position_ids() {
+    let tokenizer = get_tokenizer();
+    let options = EncodingOptions::new(true, false).with_padding(Padding::MaxLength(6));
+    let right = tokenizer.encode_plus("Hello", &options).unwrap();
+    let left = tokenizer
+        .encode_plus("Hello", &options.with_padding_side(PaddingSide::Left))
+        .unwrap();
+    let real = right.attention_mask.iter().filter(|&&bit| bit == 1).count();
+    let padding = 6 - real;
+
+    let expected: Vec<u32> = (0..real as u32).collect();
+    assert_eq!(right.position_ids(0)[..real], expected[..]);
+    assert!(right.position_ids(0)[real..].iter().all(|&id| id == 0));
+    assert_eq!(left.position_ids(0)[padding..], expected[..]);
+    assert!(left.position_ids(0)[..padding].iter().all(|&id| id == 0));
+
+    // Continuing after a cached prefix of 10 tokens
+    let continued: Vec<u32> = (10..10 + real as u32).collect();
+    assert_eq!(left.position_ids(10)[padding..], continued[..]);
+    assert!(left.position_ids(10)[..padding].iter().all(|&id| id == 0));
+}
@@ -0,0 +1,701 @@
+use std::sync::OnceLock;
+use tekken::TokenizerError;
+use tekken::request::{ChatCompletionRequest, RequestViolation, TokenizedRequest};
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+/// Small copy of the test tokenizer with another version and its default special tokens.
+///
+/// V3 has no audio tokens, so its copy drops the audio configuration.
+fn tokenizer_with_version(version: &str) -> Tekkenizer {
+    let tokenizer = get_tokenizer();
+    let mut model_data = tokenizer
+        .pruned(tokenizer.num_special_tokens() + 2048)
+        .unwrap()
+        .to_model_data();
+    model_data.config.version = version.to_string();
+    model_data.special_tokens = None;
+    if version == "v3" {
+        model_data.audio = None;
+    }
+    Tekkenizer::from_bytes(&serde_json::to_vec(&model_data).unwrap()).unwrap()
+}
+
+fn request(json: serde_json::Value) -> ChatCompletionRequest {
+    serde_json::from_value(json).unwrap()
+}
+
+fn assert_consistent(tokenizer: &Tekkenizer, tokenized: &TokenizedRequest) {
+    assert_eq!(
+        tokenizer
+            .decode(&tokenized.tokens, SpecialTokenPolicy::Keep)
+            .unwrap(),
+        tokenized.text_debug
+    );
+}
+
+#[test]
+fn test_text_request_matches_prompt_builder() {
+    let tokenizer = get_tokenizer();
+    let tokenized = tokenizer
+        .encode_request(request(serde_json::json!({
+            "model": "ignored",
+            "temperature": 0.2,
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "Hi!"},
+            ],
+        })))
+        .unwrap();
+
+    let prompt = tokenizer
+        .prompt_builder()
+        .system("Be brief.")
+        .user("Hi!")
+        .build()
+        .unwrap();
+    assert_eq!(tokenized.tokens, prompt.tokens);
+    assert_eq!(
+        tokenized.text_debug,
+        "<s>[SYSTEM_PROMPT]Be brief.[/SYSTEM_PROMPT][INST]Hi![/INST]"
+    );
+}
+
+#[test]
+fn test_tools_and_tool_calls_v7() {
+    let tokenizer = get_tokenizer();
+    let tokenized = tokenizer
+        .encode_request(request(serde_json::json!({
+            "tools": [{
+                "type": "function",
+                "function": {
+                    "name": "get_weather",
+                    "description": "Current weather",
+                    "parameters": {"type": "object", "properties": {"city": {"type": "string"}}},
+                },
+            }],
+            "messages": [
+                {"role": "user", "content": "Weather in Paris?"},
+                {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "abc123def",
+                        "type": "function",
+                        "function": {"name": "get_weather", "arguments": "{\"city\": \"Paris\"}"},
+                    }],
+                },
+                {"role": "tool", "tool_call_id": "abc123def", "name": "get_weather", "content": "Sunny"},
+                {"role": "user", "content": [{"type": "text", "text": "Thanks!"}]},
+            ],
+        })))
+        .unwrap();
+
+    assert_eq!(
+        tokenized.text_debug,
+        concat!(
+            "<s>[INST]Weather in Paris?[/INST]",
+            r#"[TOOL_CALLS][{"name": "get_weather", "arguments": {"city": "Paris"}, "id": "abc123def"}]</s>"#,
+            "[TOOL_RESULTS]abc123def[TOOL_CONTENT]Sunny[/TOOL_RESULTS]",
+            r#"[AVAILABLE_TOOLS][{"type": "function", "function": {"name": "get_weather", "description": "Current weather", "parameters": {"type": "object", "properties": {"city": {"type": "string"}}}}}][/AVAILABLE_TOOLS]"#,
+            "[INST]Thanks![/INST]",
+        )
+    );
+    assert_consistent(tokenizer, &tokenized);
+}
+
+#[test]
+fn test_tool_calls_v11() {
+    let tokenizer = tokenizer_with_version("v11");
+    let tokenized = tokenizer
+        .encode_request(request(serde_json::json!({
+            "messages": [
+                {"role": "user", "content": "Add 1 and 2"},
+                {
+                    "role": "assistant",
+                    "tool_calls": [{"function": {"name": "add", "arguments": {"a": 1, "b": 2}}}],
+                },
+                {"role": "tool", "tool_call_id": "abc123def", "content": "3"},
+            ],
+        })))
+        .unwrap();
+
+    assert_eq!(
+        tokenized.text_debug,
+        r#"<s>[INST]Add 1 and 2[/INST][TOOL_CALLS]add[ARGS]{"a": 1, "b": 2}</s>[TOOL_RESULTS]3[/TOOL_RESULTS]"#
+    );
+    assert_consistent(&tokenizer, &tokenized);
+}
+
+#[test]
+fn test_continue_final_message() {
+    let tokenizer = get_tokenizer();
+    let messages = serde_json::json!([
+        {"role": "user", "content": "Count to three"},
+        {"role": "assistant", "content": "One, two"},
+    ]);
+
+    let closed = tokenizer
+        .encode_request(request(serde_json::json!({"messages": messages})))
+        .unwrap();
+    assert!(closed.text_debug.ends_with("One, two</s>"));
+
+    let open = tokenizer
+        .encode_request(request(
+            serde_json::json!({"messages": messages, "continue_final_message": true}),
+        ))
+        .unwrap();
+    assert!(open.text_debug.ends_with("One, two"));
+    assert_eq!(open.tokens[..], closed.tokens[..closed.tokens.len() - 1]);
+
+    let prefix = tokenizer
+        .encode_request(request(serde_json::json!({"messages": [
+            {"role": "user", "content": "Count to three"},
+            {"role": "assistant", "content": "One, two", "prefix": true},
+        ]})))
+        .unwrap();
+    assert_eq!(prefix.tokens, open.tokens);
+}
+
+#[test]
+fn test_invalid_requests_are_rejected() {
+    let tokenizer = get_tokenizer();
+    let invalid = [
+        (
+            serde_json::json!({"messages": []}),
+            RequestViolation::NoMessages,
+        ),
+        (
+            serde_json::json!({
+                "messages": [{"role": "user", "content": "Hi"}],
+                "continue_final_message": true,
+            }),
+            RequestViolation::NoFinalAssistantMessage,
+        ),
+        (
+            serde_json::json!({"messages": [
+                {"role": "system", "content": [{"type": "image", "image": "AAAA"}]},
+                {"role": "user", "content": "Hi"},
+            ]}),
+            RequestViolation::MediaOutsideUserMessage,
+        ),
+    ];
+    for (json, violation) in invalid {
+        assert!(
+            matches!(
+                tokenizer.encode_request(request(json.clone())),
+                Err(TokenizerError::InvalidRequest(v)) if v == violation
+            ),
+            "{json}"
+        );
+    }
+
+    // Prefixes are checked by the prompt builder
+    let json = serde_json::json!({"messages": [
+        {"role": "user", "content": "Hi"},
+        {"role": "assistant", "content": "Hello", "prefix": true},
+        {"role": "user", "content": "Hi again"},
+    ]});
+    assert!(matches!(
+        tokenizer.encode_request(request(json)),
+        Err(TokenizerError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn test_text_chunks_of_system_messages_are_joined() {
+    let tokenizer = get_tokenizer();
+    let tokenized = tokenizer
+        .encode_request(request(serde_json::json!({"messages": [
+            {"role": "system", "content": [
+                {"type": "text", "text": "Be brief."},
+                {"type": "text", "text": "Be kind."},
+            ]},
+            {"role": "user", "content": "Hi!"},
+        ]})))
+        .unwrap();
+    assert!(
+        tokenized
+            .text_debug
+            .starts_with("<s>[SYSTEM_PROMPT]Be brief.\n\nBe kind.[/SYSTEM_PROMPT]")
+    );
+}
+
+#[test]
+#[cfg(feature = "audio")]
+fn test_audio_chunks() {
+    use base64::Engine;
+
+    let tokenizer = get_tokenizer();
+    let wav = std::fs::read("tests/assets/jfk.wav").unwrap();
+    let data = base64::engine::general_purpose::STANDARD.encode(wav);
+    let tokenized = tokenizer
+        .encode_request(request(serde_json::json!({"messages": [{
+            "role": "user",
+            "content": [
+                {"type": "input_audio", "input_audio": {"data": data, "format": "wav"}},
+                {"type": "text", "text": "Transcribe this."},
+            ],
+        }]})))
+        .unwrap();
+
+    assert_eq!(tokenized.audios.len(), 1);
+    let audio_tokens = &tokenized.audios[0].tokens;
+    assert_eq!(
+        tokenized.tokens[2..2 + audio_tokens.len()],
+        audio_tokens[..]
+    );
+    assert!(
+        tokenized
+            .text_debug
+            .starts_with("<s>[INST][BEGIN_AUDIO][AUDIO]x")
+    );
+
+    assert!(matches!(
+        tokenizer.encode_request(request(serde_json::json!({"messages": [{
+            "role": "user",
+            "content": [{"type": "input_audio", "input_audio": {"data": "", "format": "mp3"}}],
+        }]}))),
+        Err(TokenizerError::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+#[cfg(feature = "image")]
+fn test_image_chunks() {
+    use base64::Engine;
+    use image::{DynamicImage, ImageFormat, RgbImage};
+    use tekken::config::ImageConfig;
+
+    let mut png = std::io::Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(RgbImage::new(32, 16))
+        .write_to(&mut png, ImageFormat::Png)
+        .unwrap();
+    let data_url = format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png.into_inner())
+    );
+    let body = serde_json::json!({"messages": [{
+        "role": "user",
+        "content": [
+            {"type": "text", "text": "Describe"},
+            {"type": "image_url", "image_url": {"url": data_url}},
+        ],
+    }]});
+
+    assert!(matches!(
+        get_tokenizer().encode_request(request(body.clone())),
+        Err(TokenizerError::Image(_))
+    ));
+
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json")
+        .unwrap()
+        .with_image_config(ImageConfig::new(16, 1024, 1).unwrap())
+        .unwrap();
+    let tokenized = tokenizer.encode_request(request(body)).unwrap();
+    assert_eq!(tokenized.images.len(), 1);
+    assert_eq!(
+        (
+            tokenized.images[0].image.columns,
+            tokenized.images[0].image.rows
+        ),
+        (2, 1)
+    );
+    assert_eq!(
+        tokenized.text_debug,
+        "<s>[INST]Describe[IMG]x2[IMG_END][/INST]"
+    );
+    assert_eq!(
+        tokenizer
+            .decode(&tokenized.tokens, SpecialTokenPolicy::Keep)
+            .unwrap(),
+        "<s>[INST]Describe[IMG][IMG][IMG_END][/INST]"
+    );
+
+    let remote = serde_json::json!({"messages": [{
+        "role": "user",
+        "content": [{"type": "image_url", "image_url": "https://example.com/cat.png"}],
+    }]});
+    assert!(matches!(
+        tokenizer.encode_request(request(remote)),
+        Err(TokenizerError::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+fn test_validation_modes() {
+    use tekken::request::{RequestViolation, Role, ValidationMode};
+
+    let tokenizer = get_tokenizer();
+    let violation = |mode, json| match tokenizer.encode_request_with_mode(request(json), mode) {
+        Err(TokenizerError::InvalidRequest(violation)) => Some(violation),
+        Ok(_) => None,
+        Err(error) => panic!("unexpected error: {error}"),
+    };
+
+    let sample = serde_json::json!({"messages": [
+        {"role": "system", "content": "Be brief."},
+        {"role": "user", "content": "Hi"},
+        {"role": "assistant", "content": "Hello"},
+    ]});
+    assert_eq!(violation(ValidationMode::Lenient, sample.clone()), None);
+    assert_eq!(violation(ValidationMode::Finetuning, sample.clone()), None);
+    assert_eq!(
+        violation(ValidationMode::Serving, sample),
+        Some(RequestViolation::UnexpectedLastMessage {
+            mode: ValidationMode::Serving,
+            role: Role::Assistant,
+        })
+    );
+
+    let question = serde_json::json!({"messages": [{"role": "user", "content": "Hi"}]});
+    assert_eq!(violation(ValidationMode::Serving, question.clone()), None);
+    assert!(matches!(
+        violation(ValidationMode::Finetuning, question),
+        Some(RequestViolation::UnexpectedLastMessage {
+            role: Role::User,
+            ..
+        })
+    ));
+
+    let continued = serde_json::json!({
+        "messages": [
+            {"role": "user", "content": "Count to three"},
+            {"role": "assistant", "content": "One, two"},
+        ],
+        "continue_final_message": true,
+    });
+    assert_eq!(violation(ValidationMode::Serving, continued), None);
+
+    let out_of_order = serde_json::json!({"messages": [
+        {"role": "user", "content": "Hi"},
+        {"role": "tool", "tool_call_id": "abc123def", "content": "3"},
+    ]});
+    assert_eq!(
+        violation(ValidationMode::Serving, out_of_order.clone()),
+        Some(RequestViolation::UnexpectedRole {
+            index: 1,
+            previous: Role::User,
+            role: Role::Tool,
+        })
+    );
+    assert_eq!(violation(ValidationMode::Lenient, out_of_order), None);
+
+    let empty_assistant = serde_json::json!({"messages": [
+        {"role": "user", "content": "Hi"},
+        {"role": "assistant", "content": ""},
+    ]});
+    assert_eq!(
+        violation(ValidationMode::Finetuning, empty_assistant),
+        Some(RequestViolation::EmptyAssistantMessage { index: 1 })
+    );
+}
+
+#[test]
+fn test_validation_pairs_tool_calls_with_results() {
+    use tekken::request::{RequestViolation, ValidationMode};
+
+    let tokenizer = get_tokenizer();
+    let call = |id: &str| serde_json::json!({"id": id, "function": {"name": "add", "arguments": {"a": 1, "b": 2}}});
+    let calls = serde_json::json!({
+        "role": "assistant",
+        "tool_calls": [call("aaaaaaaaa"), call("bbbbbbbbb")],
+    });
+    let result = |id: &str| serde_json::json!({"role": "tool", "tool_call_id": id, "content": "3"});
+    let user = serde_json::json!({"role": "user", "content": "Add 1 and 2 twice"});
+    let validate = |mode, messages: serde_json::Value| {
+        request(serde_json::json!({"messages": messages})).validate(mode)
+    };
+
+    let answered = serde_json::json!([user, calls, result("aaaaaaaaa"), result("bbbbbbbbb")]);
+    assert!(validate(ValidationMode::Serving, answered.clone()).is_ok());
+    assert!(
+        tokenizer
+            .encode_request_with_mode(
+                request(serde_json::json!({"messages": answered})),
+                ValidationMode::Serving
+            )
+            .is_ok()
+    );
+
+    let missing = serde_json::json!([user, calls, result("aaaaaaaaa")]);
+    assert!(matches!(
+        validate(ValidationMode::Serving, missing),
+        Err(TokenizerError::InvalidRequest(
+            RequestViolation::UnansweredToolCalls {
+                index: 1,
+                missing: 1
+            }
+        ))
+    ));
+
+    let reply = serde_json::json!({"role": "assistant", "content": "3"});
+    let interrupted = serde_json::json!([user, calls, result("aaaaaaaaa"), reply]);
+    assert!(matches!(
+        validate(ValidationMode::Finetuning, interrupted),
+        Err(TokenizerError::InvalidRequest(
+            RequestViolation::UnansweredToolCalls {
+                index: 1,
+                missing: 1
+            }
+        ))
+    ));
+
+    let extra = serde_json::json!([
+        user,
+        calls,
+        result("aaaaaaaaa"),
+        result("bbbbbbbbb"),
+        result("ccccccccc"),
+    ]);
+    assert!(matches!(
+        validate(ValidationMode::Serving, extra),
+        Err(TokenizerError::InvalidRequest(
+            RequestViolation::UnexpectedToolResult { index: 4 }
+        ))
+    ));
+
+    // A finetuning sample may end with the tool calls the model should learn.
+    let target = serde_json::json!([user, calls]);
+    assert!(validate(ValidationMode::Finetuning, target.clone()).is_ok());
+    assert!(validate(ValidationMode::Serving, target).is_err());
+}
+
+#[test]
+fn test_normalize_messages() {
+    use tekken::config::TokenizerVersion;
+    use tekken::request::{RequestMessage, normalize_messages};
+
+    let messages = |json| serde_json::from_value::<Vec<RequestMessage>>(json).unwrap();
+    let conversation = messages(serde_json::json!([
+        {"role": "system", "content": "Be brief."},
+        {"role": "user", "content": "Hi"},
+        {"role": "user", "content": ""},
+        {"role": "user", "content": [{"type": "text", "text": "Who are you?"}, {"type": "image", "image": "AAAA"}]},
+        {"role": "assistant", "content": "I am"},
+        {"role": "assistant", "content": null},
+        {"role": "assistant", "tool_calls": [{"id": "abc123def", "function": {"name": "whoami", "arguments": {}}}]},
+        {"role": "tool", "tool_call_id": "abc123def", "content": "a model"},
+        {"role": "tool", "tool_call_id": "abc123def", "content": "a model"},
+        {"role": "system", "content": "Be kind."},
+        {"role": "system", "content": "Be honest."},
+        {"role": "user", "content": "Thanks"},
+    ]));
+
+    let system = |text| serde_json::json!({"role": "system", "content": text});
+    let rest = [
+        serde_json::json!({"role": "user", "content": [
+            {"type": "text", "text": "Hi\n\nWho are you?"},
+            {"type": "image", "image": "AAAA"},
+        ]}),
+        serde_json::json!({
+            "role": "assistant",
+            "content": "I am",
+            "tool_calls": [{"id": "abc123def", "type": "function", "function": {"name": "whoami", "arguments": {}}}],
+            "prefix": false,
+        }),
+        serde_json::json!({"role": "tool", "call_id": "abc123def", "content": "a model"}),
+        serde_json::json!({"role": "tool", "call_id": "abc123def", "content": "a model"}),
+    ];
+    let thanks = serde_json::json!({"role": "user", "content": "Thanks"});
+
+    let mut expected_v7 = vec![system("Be brief.")];
+    expected_v7.extend(rest.iter().cloned());
+    expected_v7.extend([system("Be kind.\n\nBe honest."), thanks.clone()]);
+    assert_eq!(
+        normalize_messages(conversation.clone(), &TokenizerVersion::V7),
+        messages(serde_json::Value::Array(expected_v7))
+    );
+
+    let mut expected_v3 = vec![system("Be brief.\n\nBe kind.\n\nBe honest.")];
+    expected_v3.extend(rest.iter().cloned());
+    expected_v3.push(thanks);
+    assert_eq!(
+        normalize_messages(conversation, &TokenizerVersion::V3),
+        messages(serde_json::Value::Array(expected_v3))
+    );
+}
+
+#[test]
+fn test_requests_are_normalized_before_encoding() {
+    let tokenizer = get_tokenizer();
+    let tokenized = tokenizer
+        .encode_request(request(serde_json::json!({"messages": [
+            {"role": "system", "content": "Be brief."},
+            {"role": "system", "content": "Be kind."},
+            {"role": "user", "content": "Hi"},
+            {"role": "user", "content": "there"},
+            {"role": "assistant", "content": ""},
+            {"role": "assistant", "content": "Hello"},
+            {"role": "assistant", "content": "friend"},
+        ]})))
+        .unwrap();
+    assert_eq!(
+        tokenized.text_debug,
+        "<s>[SYSTEM_PROMPT]Be brief.\n\nBe kind.[/SYSTEM_PROMPT][INST]Hi\n\nthere[/INST]Hello\n\nfriend</s>"
+    );
+
+    // An empty final message stays open for continuation.
+    let open = tokenizer
+        .encode_request(request(serde_json::json!({
+            "messages": [
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": ""},
+            ],
+            "continue_final_message": true,
+        })))
+        .unwrap();
+    assert_eq!(open.text_debug, "<s>[INST]Hi[/INST]");
+
+    assert!(matches!(
+        tokenizer.encode_request(request(serde_json::json!({"messages": [
+            {"role": "user", "content": ""},
+        ]}))),
+        Err(TokenizerError::InvalidRequest(RequestViolation::NoMessages))
+    ));
+}
+
+#[test]
+fn test_system_prompts_follow_version() {
+    let body = serde_json::json!({"messages": [
+        {"role": "system", "content": "Be brief."},
+        {"role": "user", "content": "Hello"},
+        {"role": "assistant", "content": "Hi!"},
+        {"role": "system", "content": "Be kind."},
+        {"role": "user", "content": "Weather?"},
+    ]});
+
+    let v3 = tokenizer_with_version("v3");
+    let tokenized = v3.encode_request(request(body.clone())).unwrap();
+    assert_eq!(
+        tokenized.text_debug,
+        "<s>[INST]Hello[/INST]Hi!</s>[INST]Be brief.\n\nBe kind.\n\nWeather?[/INST]"
+    );
+    assert_consistent(&v3, &tokenized);
+
+    let tokenized = get_tokenizer().encode_request(request(body)).unwrap();
+    assert_eq!(
+        tokenized.text_debug,
+        "<s>[SYSTEM_PROMPT]Be brief.[/SYSTEM_PROMPT][INST]Hello[/INST]Hi!</s>\
+         [SYSTEM_PROMPT]Be kind.[/SYSTEM_PROMPT][INST]Weather?[/INST]"
+    );
+}
+
+#[test]
+fn test_call_ids_round_trip() {
+    use tekken::request::{RequestViolation, ValidationMode};
+
+    let body = |id: &str| {
+        serde_json::json!({"messages": [
+            {"role": "user", "content": "Add 1 and 2, then 3 and 4"},
+            {"role": "assistant", "tool_calls": [
+                {"id": id, "function": {"name": "add", "arguments": {"a": 1, "b": 2}}},
+                {"id": "null", "function": {"name": "add", "arguments": "{\"a\": 3, \"b\": 4}"}},
+            ]},
+        ]})
+    };
+
+    let v11 = tokenizer_with_version("v11");
+    let tokenized = v11
+        .encode_request_with_mode(request(body("abc123def")), ValidationMode::Finetuning)
+        .unwrap();
+    assert_eq!(
+        tokenized.text_debug,
+        concat!(
+            "<s>[INST]Add 1 and 2, then 3 and 4[/INST]",
+            r#"[TOOL_CALLS]add[CALL_ID]abc123def[ARGS]{"a": 1, "b": 2}"#,
+            r#"[TOOL_CALLS]add[ARGS]{"a": 3, "b": 4}</s>"#,
+        )
+    );
+    assert_consistent(&v11, &tokenized);
+
+    for tokenizer in [&v11, get_tokenizer()] {
+        let tokenized = tokenizer
+            .encode_request(request(body("abc123def")))
+            .unwrap();
+        let calls = tokenizer.parse_tool_calls(&tokenized.tokens).unwrap();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].id.as_deref(), Some("abc123def"));
+        assert_eq!(calls[0].function.name, "add");
+        assert_eq!(
+            calls[0].function.arguments,
+            serde_json::json!({"a": 1, "b": 2})
+        );
+        assert_eq!(calls[1].id, None);
+        assert_eq!(
+            calls[1].function.arguments,
+            serde_json::json!({"a": 3, "b": 4})
+        );
+
+        let text = tokenizer.encode("No tools needed.", true, true).unwrap();
+        assert!(tokenizer.parse_tool_calls(&text).unwrap().is_empty());
+    }
+
+    assert!(matches!(
+        v11.encode_request_with_mode(request(body("call_0")), ValidationMode::Finetuning),
+        Err(TokenizerError::InvalidRequest(
+            RequestViolation::InvalidCallId { index: 1, .. }
+        ))
+    ));
+    let lenient = v11.encode_request(request(body("call_0"))).unwrap();
+    assert!(matches!(
+        v11.parse_tool_calls(&lenient.tokens),
+        Err(TokenizerError::InvalidRequest(
+            RequestViolation::InvalidCallId { index: 0, .. }
+        ))
+    ));
+    let tool_calls = v11.get_control_token("[TOOL_CALLS]").unwrap();
+    let mut no_args = vec![tool_calls];
+    no_args.extend(v11.encode("add{}", false, false).unwrap());
+    assert!(matches!(
+        v11.parse_tool_calls(&no_args),
+        Err(TokenizerError::MalformedToolCall(_))
+    ));
+
+    let bad_result = request(serde_json::json!({"messages": [
+        {"role": "user", "content": "Add 1 and 2"},
+        {"role": "assistant", "tool_calls": [{"function": {"name": "add", "arguments": {}}}]},
+        {"role": "tool", "tool_call_id": "too-long-id", "content": "3"},
+    ]}));
+    assert!(matches!(
+        bad_result.validate(ValidationMode::Serving),
+        Err(TokenizerError::InvalidRequest(
+            RequestViolation::InvalidCallId { index: 2, .. }
+        ))
+    ));
+}
+
+#[test]
+fn test_tool_arguments_are_canonical() {
+    let body = serde_json::json!({"messages": [
+        {"role": "user", "content": "Convert"},
+        {"role": "assistant", "tool_calls": [{
+            "id": "abc123def",
+            "function": {"name": "convert", "arguments": "{\"to\":\"°C\",\"value\":1e-5}"},
+        }]},
+    ]});
+
+    let tokenized = get_tokenizer()
+        .encode_request(request(body.clone()))
+        .unwrap();
+    assert!(tokenized.text_debug.contains(
+        r#"[TOOL_CALLS][{"name": "convert", "arguments": {"to": "°C", "value": 1e-05}, "id": "abc123def"}]"#
+    ));
+
+    let v11 = tokenizer_with_version("v11");
+    let tokenized = v11.encode_request(request(body)).unwrap();
+    assert!(
+        tokenized
+            .text_debug
+            .ends_with(r#"[ARGS]{"to": "°C", "value": 1e-05}</s>"#)
+    );
+}
@@ -0,0 +1,70 @@
+use std::collections::HashSet;
+use std::sync::OnceLock;
+use tekken::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+const TEXT: &str = "Stochastic segmentation helps with subword regularization! 🚀";
+
+#[test]
+fn test_zero_temperature_is_regular_encoding() {
+    let tokenizer = get_tokenizer();
+    assert_eq!(
+        tokenizer.encode_sampled(TEXT, 0.0, 1).unwrap(),
+        tokenizer.encode(TEXT, false, false).unwrap()
+    );
+}
+
+#[test]
+fn test_samples_are_reproducible_and_lossless() {
+    let tokenizer = get_tokenizer();
+    let first = tokenizer.encode_sampled(TEXT, 1.0, 42).unwrap();
+    assert_eq!(first, tokenizer.encode_sampled(TEXT, 1.0, 42).unwrap());
+
+    for seed in 0..16 {
+        let tokens = tokenizer.encode_sampled(TEXT, 1.0, seed).unwrap();
+        assert_eq!(
+            tokenizer
+                .decode(&tokens, SpecialTokenPolicy::Raise)
+                .unwrap(),
+            TEXT
+        );
+    }
+
+    let variants: HashSet<Vec<u32>> = (0..16)
+        .map(|seed| tokenizer.encode_sampled(TEXT, 1.0, seed).unwrap())
+        .collect();
+    assert!(variants.len() > 1);
+}
+
+#[test]
+fn test_low_temperature_stays_near_optimal() {
+    let tokenizer = get_tokenizer();
+    let regular = tokenizer.encode(TEXT, false, false).unwrap().len();
+
+    let cold: usize = (0..8)
+        .map(|seed| tokenizer.encode_sampled(TEXT, 0.05, seed).unwrap().len())
+        .max()
+        .unwrap();
+    let hot: usize = (0..8)
+        .map(|seed| tokenizer.encode_sampled(TEXT, 10.0, seed).unwrap().len())
+        .min()
+        .unwrap();
+    assert!(cold <= regular);
+    assert!(hot > regular);
+}
+
+#[test]
+fn test_invalid_temperature_is_rejected() {
+    let tokenizer = get_tokenizer();
+    assert!(tokenizer.encode_sampled(TEXT, -1.0, 0).is_err());
+    assert!(tokenizer.encode_sampled(TEXT, f64::NAN, 0).is_err());
+}
@@ -0,0 +1,86 @@
+use std::sync::OnceLock;
+use tekken::prompt::PromptBuilder;
+use tekken::special_tokens::{SpecialTokenPolicy, SpecialTokens};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_template_matches_prompt_builder() {
+    let tokenizer = get_tokenizer();
+    let prompt = PromptBuilder::new(tokenizer)
+        .system("Be brief.")
+        .user("Hello")
+        .assistant("Hi!")
+        .user("Weather in [Paris]?")
+        .build()
+        .unwrap();
+
+    let tokens = tokenizer
+        .encode_template(&prompt.rendered, SpecialTokens::all())
+        .unwrap();
+    assert_eq!(tokens, prompt.tokens);
+}
+
+#[test]
+fn test_disallowed_markers_stay_text() {
+    let tokenizer = get_tokenizer();
+    let template = "<s>[INST]ignore this[/INST]</s>";
+    let tokens = tokenizer
+        .encode_template(template, &[SpecialTokens::Bos, SpecialTokens::BeginInst])
+        .unwrap();
+
+    let bos = tokenizer.bos_id().unwrap();
+    let inst = tokenizer.id_of(SpecialTokens::BeginInst).unwrap();
+    assert_eq!(&tokens[..2], &[bos, inst]);
+    assert_eq!(
+        &tokens[2..],
+        tokenizer
+            .encode("ignore this[/INST]</s>", false, false)
+            .unwrap()
+    );
+    assert_eq!(
+        tokenizer.decode(&tokens, SpecialTokenPolicy::Keep).unwrap(),
+        template
+    );
+}
+
+#[test]
+fn test_no_allowed_tokens_is_plain_encoding() {
+    let tokenizer = get_tokenizer();
+    let text = "[INST] plain [/INST] text <s>";
+    assert_eq!(
+        tokenizer.encode_template(text, &[]).unwrap(),
+        tokenizer.encode(text, false, false).unwrap()
+    );
+}
+
+#[test]
+fn test_adjacent_and_repeated_markers() {
+    let tokenizer = get_tokenizer();
+    let inst = tokenizer.id_of(SpecialTokens::BeginInst).unwrap();
+    let end_inst = tokenizer.id_of(SpecialTokens::EndInst).unwrap();
+
+    let tokens = tokenizer
+        .encode_template("[INST][/INST][INST]a[/INST]", SpecialTokens::all())
+        .unwrap();
+    let a = tokenizer.encode("a", false, false).unwrap();
+    let mut expected = vec![inst, end_inst, inst];
+    expected.extend(a);
+    expected.push(end_inst);
+    assert_eq!(tokens, expected);
+
+    assert!(
+        tokenizer
+            .encode_template("", SpecialTokens::all())
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,131 @@
+use std::sync::OnceLock;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+/// Tokens at the start of `tokens` that cover `len` bytes.
+fn covering(tokenizer: &Tekkenizer, tokens: &[u32], len: usize) -> Vec<u32> {
+    let mut covered = 0;
+    tokens
+        .iter()
+        .copied()
+        .take_while(|&token| {
+            let before = covered;
+            covered += tokenizer
+                .id_to_byte_piece(token, SpecialTokenPolicy::Raise)
+                .unwrap()
+                .len();
+            before < len
+        })
+        .collect()
+}
+
+#[test]
+fn test_stable_tokens_and_tail() {
+    let tokenizer = get_tokenizer();
+    let text = "The quick brown fox jum";
+    let encoding = tokenizer.encode_with_unstable(text, true).unwrap();
+
+    assert_eq!(&text[encoding.unstable_start..], " jum");
+    assert_eq!(
+        encoding.tokens,
+        tokenizer
+            .encode("The quick brown fox", true, false)
+            .unwrap()
+    );
+    for completion in &encoding.completions {
+        let bytes: Vec<u8> = completion
+            .iter()
+            .flat_map(|&token| {
+                tokenizer
+                    .id_to_byte_piece(token, SpecialTokenPolicy::Raise)
+                    .unwrap()
+            })
+            .collect();
+        assert!(bytes.starts_with(b" jum"), "{completion:?}");
+    }
+}
+
+#[test]
+fn test_completions_cover_continuations() {
+    let tokenizer = get_tokenizer();
+    let cases = [
+        ("The quick brown fox jum", "ps over the lazy dog"),
+        ("hello wor", "ld"),
+        ("hello wor", "k"),
+        ("hello ", "world"),
+        ("The price is 1", "2.50"),
+        ("let x = foo", "bar();"),
+        ("Bonjour à tou", "s !"),
+        ("東京は日本の首", "都です"),
+        ("first line\n", "\nsecond line"),
+        ("indent:\n  ", "  code"),
+    ];
+
+    for (prompt, continuation) in cases {
+        let encoding = tokenizer.encode_with_unstable(prompt, true).unwrap();
+        let full = tokenizer
+            .encode(&format!("{prompt}{continuation}"), true, false)
+            .unwrap();
+        assert!(
+            full.starts_with(&encoding.tokens),
+            "{prompt:?} + {continuation:?}: stable tokens changed"
+        );
+
+        let tail_len = prompt.len() - encoding.unstable_start;
+        let next = covering(tokenizer, &full[encoding.tokens.len()..], tail_len);
+        assert!(
+            encoding.completions.contains(&next),
+            "{prompt:?} + {continuation:?}: {next:?} is not a completion"
+        );
+    }
+}
+
+#[test]
+fn test_complete_prompt_is_a_completion() {
+    let tokenizer = get_tokenizer();
+    let prompt = "Hello, world";
+    let encoding = tokenizer.encode_with_unstable(prompt, false).unwrap();
+    let tail = tokenizer
+        .encode(&prompt[encoding.unstable_start..], false, false)
+        .unwrap();
+    assert!(encoding.completions.contains(&tail));
+
+    let mut tokens = encoding.tokens.clone();
+    tokens.extend(&tail);
+    assert_eq!(tokens, tokenizer.encode(prompt, false, false).unwrap());
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, SpecialTokenPolicy::Raise)
+            .unwrap(),
+        prompt
+    );
+}
+
+#[test]
+fn test_trailing_whitespace_runs_are_unstable() {
+    let tokenizer = get_tokenizer();
+    let encoding = tokenizer.encode_with_unstable("end\n \n", false).unwrap();
+    assert_eq!(encoding.unstable_start, 3);
+    assert_eq!(
+        encoding.tokens,
+        tokenizer.encode("end", false, false).unwrap()
+    );
+}
+
+#[test]
+fn test_empty_prompt() {
+    let tokenizer = get_tokenizer();
+    let encoding = tokenizer.encode_with_unstable("", true).unwrap();
+    assert_eq!(encoding.tokens, vec![tokenizer.bos_id().unwrap()]);
+    assert_eq!(encoding.unstable_start, 0);
+    assert!(encoding.completions.is_empty());
+}
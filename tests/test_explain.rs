@@ -0,0 +1,96 @@
+mod common;
+
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_explain_matches_encode() {
+    let tokenizer = get_tokenizer();
+    for text in [
+        "Hello, world!",
+        "Tokenization isn't magic: antidisestablishmentarianism",
+        "fn main() {\n    println!(\"{}\", 42);\n}\n",
+        "Привет, мир! 東京は日本の首都です。 🚀",
+        "",
+    ] {
+        let explanation = tokenizer.explain(text).unwrap();
+        assert_eq!(explanation.text, text);
+        assert_eq!(
+            explanation.tokens(),
+            tokenizer.encode(text, false, false).unwrap()
+        );
+
+        // Pieces are ordered, non-empty and cover the text
+        let mut end = 0;
+        for piece in &explanation.pieces {
+            assert_eq!(piece.range.start, end);
+            assert!(!piece.range.is_empty());
+            end = piece.range.end;
+        }
+        assert_eq!(end, text.len());
+    }
+}
+
+#[test]
+fn test_merges_build_the_tokens() {
+    let tokenizer = get_tokenizer();
+    let text = "antidisestablishmentarianism";
+    let explanation = tokenizer.explain(text).unwrap();
+    assert_eq!(explanation.pieces.len(), 1);
+    let piece = &explanation.pieces[0];
+    assert!(piece.tokens.len() > 1);
+
+    // Replaying the merges over single bytes gives the final tokens
+    let mut parts: Vec<(usize, u32)> = text
+        .bytes()
+        .enumerate()
+        .map(|(offset, byte)| (offset, tokenizer.byte_to_token_id(byte)))
+        .collect();
+    for merge in &piece.merges {
+        assert!(merge.range.start < merge.split && merge.split < merge.range.end);
+        let index = parts
+            .iter()
+            .position(|&(offset, _)| offset == merge.range.start)
+            .unwrap();
+        assert_eq!(parts[index].1, merge.left);
+        assert_eq!(parts[index + 1], (merge.split, merge.right));
+        assert_eq!(
+            tokenizer.id_to_piece(merge.merged).unwrap(),
+            &text[merge.range.clone()]
+        );
+        parts[index].1 = merge.merged;
+        parts.remove(index + 1);
+    }
+    let replayed: Vec<u32> = parts.into_iter().map(|(_, token)| token).collect();
+    assert_eq!(replayed, piece.tokens);
+}
+
+#[test]
+fn test_whole_piece_tokens_have_no_merges() {
+    let tokenizer = get_tokenizer();
+    let explanation = tokenizer.explain(" world").unwrap();
+    assert_eq!(explanation.pieces.len(), 1);
+    assert_eq!(explanation.pieces[0].tokens.len(), 1);
+    assert!(explanation.pieces[0].merges.is_empty());
+}
+
+#[test]
+fn test_display() {
+    let model_data = common::model_data(["he", "ll", "hell"]);
+    let tokenizer = Tekkenizer::from_bytes(&serde_json::to_vec(&model_data).unwrap()).unwrap();
+
+    let explanation = tokenizer.explain("hello!").unwrap();
+    assert_eq!(
+        explanation.to_string(),
+        "\"hello\" -> [358, 211]\n  \"h\" + \"e\" -> \"he\" (356)\n  \"l\" + \"l\" -> \"ll\" (357)\n  \"he\" + \"ll\" -> \"hell\" (358)\n\"!\" -> [133]\n"
+    );
+}
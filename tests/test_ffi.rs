@@ -0,0 +1,195 @@
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+
+use tekken::ffi::*;
+
+fn load() -> *mut TekkenTokenizer {
+    let path = CString::new("tests/assets/tekken.json").unwrap();
+    let mut error = TekkenError {
+        code: TekkenErrorCode::Ok,
+        message: ptr::null_mut(),
+    };
+    let tokenizer = unsafe { tekken_tokenizer_from_file(path.as_ptr(), &mut error) };
+    assert!(!tokenizer.is_null());
+    assert_eq!(error.code, TekkenErrorCode::Ok);
+    tokenizer
+}
+
+fn take_string(text: *mut c_char) -> String {
+    let owned = unsafe { CStr::from_ptr(text) }
+        .to_str()
+        .unwrap()
+        .to_string();
+    unsafe { tekken_string_free(text) };
+    owned
+}
+
+#[test]
+fn test_encode_decode_round_trip() {
+    let tokenizer = load();
+    let text = "Hello, world!";
+    let mut encoding = TekkenEncoding {
+        ids: ptr::null_mut(),
+        len: 0,
+    };
+    let code = unsafe {
+        tekken_encode(
+            tokenizer,
+            text.as_ptr(),
+            text.len(),
+            true,
+            false,
+            &mut encoding,
+            ptr::null_mut(),
+        )
+    };
+    assert_eq!(code, TekkenErrorCode::Ok);
+    let ids = unsafe { std::slice::from_raw_parts(encoding.ids, encoding.len) };
+    assert_eq!(ids, [1, 22177, 1044, 4304, 1033]);
+
+    let mut decoded = ptr::null_mut();
+    let code = unsafe {
+        tekken_decode(
+            tokenizer,
+            encoding.ids,
+            encoding.len,
+            TekkenSpecialTokenPolicy::Keep as u32,
+            &mut decoded,
+            ptr::null_mut(),
+        )
+    };
+    assert_eq!(code, TekkenErrorCode::Ok);
+    assert_eq!(take_string(decoded), "<s>Hello, world!");
+
+    unsafe {
+        tekken_encoding_free(&mut encoding);
+        tekken_tokenizer_free(tokenizer);
+    }
+    assert!(encoding.ids.is_null());
+}
+
+#[test]
+fn test_errors_carry_code_and_message() {
+    let tokenizer = load();
+    let mut error = TekkenError {
+        code: TekkenErrorCode::Ok,
+        message: ptr::null_mut(),
+    };
+    let mut decoded = ptr::null_mut();
+    let ids = [u32::MAX];
+    let code = unsafe {
+        tekken_decode(
+            tokenizer,
+            ids.as_ptr(),
+            ids.len(),
+            TekkenSpecialTokenPolicy::Ignore as u32,
+            &mut decoded,
+            &mut error,
+        )
+    };
+    assert_eq!(code, TekkenErrorCode::TokenOutOfRange);
+    assert_eq!(error.code, code);
+    let message = unsafe { CStr::from_ptr(error.message) }.to_str().unwrap();
+    assert!(message.contains("out of range"));
+    unsafe { tekken_error_free(&mut error) };
+    assert!(error.message.is_null());
+
+    // The status code is returned even without an error struct
+    let code = unsafe {
+        tekken_encode(
+            ptr::null(),
+            ptr::null(),
+            0,
+            false,
+            false,
+            ptr::null_mut(),
+            ptr::null_mut(),
+        )
+    };
+    assert_eq!(code, TekkenErrorCode::NullPointer);
+    unsafe { tekken_tokenizer_free(tokenizer) };
+}
+
+#[test]
+fn test_unknown_policy_is_rejected() {
+    let tokenizer = load();
+    let mut error = TekkenError {
+        code: TekkenErrorCode::Ok,
+        message: ptr::null_mut(),
+    };
+    let mut decoded = ptr::null_mut();
+    let ids = [1];
+    // The same error struct is reused, releasing the previous message each time
+    for _ in 0..2 {
+        let code = unsafe {
+            tekken_decode(
+                tokenizer,
+                ids.as_ptr(),
+                ids.len(),
+                7,
+                &mut decoded,
+                &mut error,
+            )
+        };
+        assert_eq!(code, TekkenErrorCode::InvalidArgument);
+        assert!(!error.message.is_null());
+    }
+    assert!(decoded.is_null());
+    unsafe { tekken_error_free(&mut error) };
+
+    assert!(unsafe { tekken_decoder_new(tokenizer, 3) }.is_null());
+    unsafe { tekken_tokenizer_free(tokenizer) };
+}
+
+#[test]
+fn test_streaming_decoder() {
+    let tokenizer = load();
+    let text = "Grüße 🚀";
+    let mut encoding = TekkenEncoding {
+        ids: ptr::null_mut(),
+        len: 0,
+    };
+    unsafe {
+        tekken_encode(
+            tokenizer,
+            text.as_ptr(),
+            text.len(),
+            false,
+            false,
+            &mut encoding,
+            ptr::null_mut(),
+        );
+    }
+    let ids = unsafe { std::slice::from_raw_parts(encoding.ids, encoding.len) };
+
+    let decoder = unsafe { tekken_decoder_new(tokenizer, TekkenSpecialTokenPolicy::Ignore as u32) };
+    let mut decoded = String::new();
+    for &id in ids {
+        let mut chunk = ptr::null_mut();
+        let code = unsafe { tekken_decoder_push(decoder, id, &mut chunk, ptr::null_mut()) };
+        assert_eq!(code, TekkenErrorCode::Ok);
+        decoded.push_str(&take_string(chunk));
+    }
+    assert_eq!(
+        unsafe { tekken_decoder_finish(decoder, ptr::null_mut()) },
+        TekkenErrorCode::Ok
+    );
+    assert_eq!(decoded, text);
+
+    unsafe {
+        tekken_decoder_free(decoder);
+        tekken_encoding_free(&mut encoding);
+        tekken_tokenizer_free(tokenizer);
+    }
+}
+
+#[test]
+fn test_committed_header_is_up_to_date() {
+    let path = env!("TEKKEN_GENERATED_HEADER");
+    let generated = std::fs::read_to_string(path).unwrap();
+    let committed = std::fs::read_to_string("include/tekken.h").unwrap();
+    assert!(
+        generated == committed,
+        "include/tekken.h is out of date, copy it from {path}"
+    );
+}
@@ -0,0 +1,48 @@
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_fingerprint_is_stable_across_loads() {
+    let tokenizer = get_tokenizer();
+    let reloaded = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+
+    assert_eq!(tokenizer.fingerprint(), reloaded.fingerprint());
+    assert_eq!(tokenizer.fingerprint(), tokenizer.fingerprint());
+    assert_ne!(tokenizer.fingerprint(), [0; 32]);
+}
+
+#[test]
+fn test_fingerprint_survives_export() {
+    let tokenizer = get_tokenizer();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tekken.json");
+    tokenizer.save(&path).unwrap();
+
+    let reloaded = Tekkenizer::from_file(&path).unwrap();
+    assert_eq!(tokenizer.fingerprint(), reloaded.fingerprint());
+}
+
+#[test]
+fn test_fingerprint_detects_changes() {
+    let tokenizer = get_tokenizer();
+    let pruned = tokenizer.pruned(tokenizer.vocab_size() - 1).unwrap();
+    assert_ne!(tokenizer.fingerprint(), pruned.fingerprint());
+
+    let mut model_data = tokenizer.to_model_data();
+    model_data.special_tokens.as_mut().unwrap()[0].token_str = "<unknown>".to_string();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tekken.json");
+    std::fs::write(&path, serde_json::to_vec(&model_data).unwrap()).unwrap();
+
+    let modified = Tekkenizer::from_file(&path).unwrap();
+    assert_ne!(tokenizer.fingerprint(), modified.fingerprint());
+}
@@ -93,3 +93,92 @@ fn test_from_file() {
 
     println!("\n✓ All tests passed!");
 }
+
+#[test]
+fn test_from_file_matches_model_data() {
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json")
+        .expect("Failed to load tokenizer from file");
+
+    let content =
+        std::fs::read_to_string("tests/assets/tekken.json").expect("Failed to read tekken.json");
+    let model_data: tekken::config::ModelData =
+        serde_json::from_str(&content).expect("Failed to parse JSON");
+    let config = model_data.config;
+    let reference = Tekkenizer::new(
+        model_data.vocab,
+        &model_data.special_tokens.unwrap_or_default(),
+        config.pattern,
+        config.default_vocab_size,
+        config.default_num_special_tokens,
+        tekken::config::TokenizerVersion::from_string(&config.version)
+            .expect("Failed to parse version"),
+        model_data.audio,
+    )
+    .expect("Failed to create tokenizer");
+
+    assert_eq!(tokenizer.vocab_size(), reference.vocab_size());
+    assert_eq!(tokenizer.vocab(), reference.vocab());
+
+    let text = "Borrowed parsing must not change tokenization: café 🚀";
+    assert_eq!(
+        tokenizer.encode(text, true, true).unwrap(),
+        reference.encode(text, true, true).unwrap()
+    );
+}
+
+#[test]
+fn test_from_bytes_matches_from_file() {
+    let bytes = std::fs::read("tests/assets/tekken.json").expect("Failed to read tekken.json");
+    let tokenizer = Tekkenizer::from_bytes(&bytes).expect("Failed to load tokenizer from bytes");
+
+    assert_eq!(
+        tokenizer.encode("Hello, world!", false, false).unwrap(),
+        vec![22177, 1044, 4304, 1033]
+    );
+}
+
+#[test]
+#[cfg(not(feature = "gzip"))]
+fn test_compressed_data_requires_feature() {
+    let result = Tekkenizer::from_bytes(&[0x1f, 0x8b, 0x08, 0x00]);
+    assert!(matches!(
+        result,
+        Err(tekken::TokenizerError::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+fn test_from_file_with_limit() {
+    let tokenizer = Tekkenizer::from_file_with_limit("tests/assets/tekken.json", 1000)
+        .expect("Failed to load limited tokenizer");
+    assert_eq!(
+        tokenizer.vocab_size(),
+        tokenizer.num_special_tokens() + 1000
+    );
+
+    let full = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    let pruned = full.pruned(full.num_special_tokens() + 1000).unwrap();
+    assert_eq!(tokenizer.fingerprint(), pruned.fingerprint());
+
+    let text = "Hello world! café 🚀";
+    let tokens = tokenizer.encode(text, false, false).unwrap();
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, tekken::SpecialTokenPolicy::Raise)
+            .unwrap(),
+        text
+    );
+
+    let bytes_only = Tekkenizer::from_file_with_limit("tests/assets/tekken.json", 10).unwrap();
+    assert_eq!(
+        bytes_only.vocab_size(),
+        bytes_only.num_special_tokens() + 256
+    );
+}
+
+#[test]
+fn test_audio_support_follows_feature() {
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    assert!(tokenizer.audio_config().is_some());
+    assert_eq!(tokenizer.has_audio_support(), cfg!(feature = "audio"));
+}
@@ -0,0 +1,39 @@
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+use tekken::test_utils::{
+    EncodeVector, TestVector, assert_parity, check_test_vectors, load_test_vectors,
+};
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_golden_vectors_parity() {
+    assert_parity(get_tokenizer(), "tests/assets/golden_vectors.json");
+}
+
+#[test]
+fn test_golden_vectors_report_failures() {
+    let tokenizer = get_tokenizer();
+    let mut vectors = load_test_vectors("tests/assets/golden_vectors.json").unwrap();
+    let total = vectors.len();
+    vectors.push(TestVector::Encode(EncodeVector {
+        text: "Hello".to_string(),
+        expected_tokens: vec![0],
+        add_bos: false,
+        add_eos: false,
+    }));
+
+    let report = check_test_vectors(tokenizer, &vectors);
+    assert!(!report.is_ok());
+    assert_eq!(report.passed, total);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].index, total);
+    assert_eq!(report.failures[0].actual, "[22177]");
+}
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use tekken::grpc::proto::{
+    DecodeRequest, DecodeStreamRequest, EncodeAudioRequest, EncodeRequest, SpecialTokenPolicy,
+};
+use tekken::grpc::{TokenizerClient, TokenizerService};
+use tekken::tekkenizer::Tekkenizer;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::Code;
+use tonic::transport::{Channel, Server};
+
+async fn start_server() -> TokenizerClient<Channel> {
+    let tokenizer = Arc::new(
+        Tekkenizer::from_file("tests/assets/tekken.json").expect("Failed to load tokenizer"),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(
+        Server::builder()
+            .add_service(TokenizerService::new(tokenizer).into_server())
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+
+    let channel = Channel::from_shared(format!("http://{address}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    TokenizerClient::new(channel)
+}
+
+#[tokio::test]
+async fn test_unary_encode_and_decode() {
+    let mut client = start_server().await;
+
+    let ids = client
+        .encode(EncodeRequest {
+            text: "Hello, world!".to_string(),
+            add_bos: true,
+            add_eos: false,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .ids;
+    assert_eq!(ids, vec![1, 22177, 1044, 4304, 1033]);
+
+    let text = client
+        .decode(DecodeRequest {
+            ids: ids.clone(),
+            special_token_policy: SpecialTokenPolicy::Keep as i32,
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .text;
+    assert_eq!(text, "<s>Hello, world!");
+
+    let status = client
+        .decode(DecodeRequest {
+            ids,
+            special_token_policy: SpecialTokenPolicy::Raise as i32,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    if cfg!(feature = "audio") {
+        let audio_ids = client
+            .encode_audio(EncodeAudioRequest {
+                audio: std::fs::read("tests/assets/jfk.wav").unwrap(),
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .ids;
+        assert!(audio_ids.len() > 1);
+
+        let status = client
+            .encode_audio(EncodeAudioRequest {
+                audio: b"not audio".to_vec(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    } else {
+        let status = client
+            .encode_audio(EncodeAudioRequest {
+                audio: std::fs::read("tests/assets/jfk.wav").unwrap(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unimplemented);
+    }
+}
+
+#[tokio::test]
+async fn test_streaming_decode() {
+    let mut client = start_server().await;
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    let text = "Streaming 🚀 café";
+    let ids = tokenizer.encode(text, true, false).unwrap();
+
+    let requests = ids.into_iter().map(|id| DecodeStreamRequest {
+        ids: vec![id],
+        special_token_policy: SpecialTokenPolicy::Ignore as i32,
+    });
+    let mut responses = client
+        .decode_stream(tokio_stream::iter(requests))
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut decoded = String::new();
+    while let Some(response) = responses.message().await.unwrap() {
+        assert!(!response.text.is_empty());
+        decoded.push_str(&response.text);
+    }
+    assert_eq!(decoded, text);
+}
@@ -0,0 +1,283 @@
+use std::io::Cursor;
+use std::sync::OnceLock;
+
+use image::{DynamicImage, ImageFormat, Rgb, RgbImage, Rgba, RgbaImage};
+use tekken::config::{ImageConfig, ImageResample};
+use tekken::image::{DATASET_MEAN, DATASET_STD, Image};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+/// The test tokenizer with a Pixtral-style `multimodal` section.
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        let mut json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read("tests/assets/tekken.json").unwrap()).unwrap();
+        json["multimodal"] = serde_json::json!({"image_patch_size": 16, "max_image_size": 1024});
+        Tekkenizer::from_bytes(&serde_json::to_vec(&json).unwrap())
+            .expect("Failed to load tokenizer")
+    })
+}
+
+fn encode(image: &DynamicImage, format: ImageFormat) -> Vec<u8> {
+    let mut bytes = Cursor::new(Vec::new());
+    image.write_to(&mut bytes, format).unwrap();
+    bytes.into_inner()
+}
+
+fn solid(width: u32, height: u32, color: [u8; 3]) -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_pixel(width, height, Rgb(color)))
+}
+
+#[test]
+fn test_token_grid_matches_mistral_common() {
+    let config = ImageConfig::new(16, 1024, 1).unwrap();
+    // Small images are only rounded up to whole patches
+    assert_eq!(config.token_grid(1, 1), (1, 1));
+    assert_eq!(config.token_grid(16, 16), (1, 1));
+    assert_eq!(config.token_grid(17, 33), (2, 3));
+    // Large images are scaled to fit 1024 pixels: 2048x1000 -> 1024x500
+    assert_eq!(config.token_grid(2048, 1000), (64, 32));
+    // Python's round ties to even: 3000x1500 -> 1024x512, 1500x625 -> 1024x426.67
+    assert_eq!(config.token_grid(3000, 1500), (64, 32));
+    assert_eq!(config.token_grid(1500, 625), (64, 27));
+
+    let merged = ImageConfig::new(14, 1540, 2).unwrap();
+    assert_eq!(merged.pixels_per_token(), 28);
+    assert_eq!(merged.token_grid(640, 480), (23, 18));
+}
+
+#[test]
+fn test_invalid_config_is_rejected() {
+    assert!(ImageConfig::new(0, 1024, 1).is_err());
+    assert!(ImageConfig::new(16, 1024, 0).is_err());
+}
+
+#[test]
+fn test_png_and_jpeg_load() {
+    let image = solid(40, 20, [255, 0, 0]);
+
+    let png = Image::from_bytes(&encode(&image, ImageFormat::Png)).unwrap();
+    assert_eq!((png.width(), png.height()), (40, 20));
+    assert_eq!(png.as_rgb().get_pixel(0, 0), &Rgb([255, 0, 0]));
+
+    let jpeg = Image::from_bytes(&encode(&image, ImageFormat::Jpeg)).unwrap();
+    assert_eq!((jpeg.width(), jpeg.height()), (40, 20));
+    assert!(jpeg.as_rgb().get_pixel(20, 10)[0] > 240);
+}
+
+#[test]
+fn test_from_file() {
+    let path = std::env::temp_dir().join("tekken_test_image.png");
+    std::fs::write(&path, encode(&solid(8, 8, [1, 2, 3]), ImageFormat::Png)).unwrap();
+    let image = Image::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(image.as_rgb().get_pixel(7, 7), &Rgb([1, 2, 3]));
+
+    assert!(matches!(
+        Image::from_file("tests/assets/does_not_exist.png"),
+        Err(tekken::TokenizerError::Io(_))
+    ));
+}
+
+#[test]
+fn test_invalid_data_is_rejected() {
+    assert!(Image::from_bytes(b"not an image").is_err());
+    let gif = b"GIF89a\x01\x00\x01\x00\x00\x00\x00;";
+    assert!(matches!(
+        Image::from_bytes(gif),
+        Err(tekken::TokenizerError::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+fn test_transparency_is_composited_on_white() {
+    let mut rgba = RgbaImage::from_pixel(2, 1, Rgba([0, 0, 0, 0]));
+    rgba.put_pixel(1, 0, Rgba([0, 0, 0, 128]));
+    let image =
+        Image::from_bytes(&encode(&DynamicImage::ImageRgba8(rgba), ImageFormat::Png)).unwrap();
+
+    assert_eq!(image.as_rgb().get_pixel(0, 0), &Rgb([255, 255, 255]));
+    assert_eq!(image.as_rgb().get_pixel(1, 0), &Rgb([127, 127, 127]));
+}
+
+#[test]
+fn test_preprocess_resizes_to_token_grid() {
+    let config = ImageConfig::new(16, 64, 1).unwrap();
+    let image = Image::from(solid(100, 30, [255, 128, 0]));
+    let preprocessed = image.preprocess(&config).unwrap();
+
+    // 100x30 is scaled to 64x19, then padded up to whole 16 pixel patches
+    assert_eq!((preprocessed.columns, preprocessed.rows), (4, 2));
+    assert_eq!(preprocessed.pixel_values.shape(), &[3, 32, 64]);
+}
+
+#[test]
+fn test_preprocess_normalizes_pixels() {
+    let config = ImageConfig::new(4, 1024, 1).unwrap();
+    let image = Image::from(solid(8, 4, [255, 128, 0]));
+    let preprocessed = image.preprocess(&config).unwrap();
+    assert_eq!(preprocessed.pixel_values.shape(), &[3, 4, 8]);
+
+    for (channel, value) in [255.0, 128.0, 0.0].into_iter().enumerate() {
+        let expected = (value / 255.0 - DATASET_MEAN[channel]) / DATASET_STD[channel];
+        for pixel in preprocessed
+            .pixel_values
+            .index_axis(ndarray::Axis(0), channel)
+        {
+            approx::assert_relative_eq!(f64::from(*pixel), expected, epsilon = 1e-6);
+        }
+    }
+}
+
+#[test]
+fn test_image_section_is_loaded() {
+    let tokenizer = get_tokenizer();
+    assert!(tokenizer.has_image_support());
+    let config = tokenizer.image_config().unwrap();
+    assert_eq!(config.image_patch_size, 16);
+    assert_eq!(config.spatial_merge_size, 1);
+
+    let plain = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    assert!(!plain.has_image_support());
+    assert_ne!(plain.fingerprint(), tokenizer.fingerprint());
+}
+
+#[test]
+fn test_encode_image_token_layout() {
+    let tokenizer = get_tokenizer();
+    let img = tokenizer.get_control_token("[IMG]").unwrap();
+    let img_break = tokenizer.get_control_token("[IMG_BREAK]").unwrap();
+    let img_end = tokenizer.get_control_token("[IMG_END]").unwrap();
+
+    let encoding = tokenizer
+        .encode_image(&Image::from(solid(40, 20, [0, 0, 0])))
+        .unwrap();
+    assert_eq!(
+        encoding.tokens,
+        [img, img, img, img_break, img, img, img, img_end]
+    );
+    assert_eq!((encoding.image.columns, encoding.image.rows), (3, 2));
+    assert_eq!(encoding.image.pixel_values.shape(), &[3, 32, 48]);
+
+    let single = tokenizer
+        .encode_image(&Image::from(solid(1, 1, [0, 0, 0])))
+        .unwrap();
+    assert_eq!(single.tokens, [img, img_end]);
+}
+
+#[test]
+fn test_encode_image_without_config() {
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    assert!(tokenizer.image_config().is_none());
+    assert!(matches!(
+        tokenizer.encode_image(&Image::from(solid(8, 8, [0, 0, 0]))),
+        Err(tekken::TokenizerError::Image(_))
+    ));
+
+    let tokenizer = tokenizer
+        .with_image_config(ImageConfig::new(8, 64, 1).unwrap())
+        .unwrap();
+    let encoding = tokenizer
+        .encode_image(&Image::from(solid(16, 8, [0, 0, 0])))
+        .unwrap();
+    assert_eq!(encoding.tokens.len(), 3);
+}
+
+#[test]
+fn test_image_config_survives_export_and_pruning() {
+    let tokenizer = get_tokenizer();
+    let model_data = tokenizer.to_model_data();
+    assert_eq!(model_data.image.as_ref().unwrap().max_image_size, 1024);
+
+    let pruned = tokenizer
+        .pruned(tokenizer.num_special_tokens() + 1024)
+        .unwrap();
+    assert_eq!(pruned.image_config().unwrap().max_image_size, 1024);
+    assert!(pruned.has_image_support());
+}
+
+#[test]
+fn test_normalization_defaults_to_pixtral() {
+    let config: ImageConfig =
+        serde_json::from_value(serde_json::json!({"image_patch_size": 16, "max_image_size": 1024}))
+            .unwrap();
+    assert_eq!(config.image_mean, [0.48145466, 0.4578275, 0.40821073]);
+    assert_eq!(config.image_std, [0.26862954, 0.26130258, 0.27577711]);
+    assert_eq!(config.rescale_factor, 1.0 / 255.0);
+    assert_eq!(config.resample, ImageResample::Bicubic);
+
+    // Defaults are not written back, so files stay compatible with mistral-common
+    let written = serde_json::to_value(&config).unwrap();
+    assert_eq!(
+        written,
+        serde_json::json!({"image_patch_size": 16, "max_image_size": 1024, "spatial_merge_size": 1})
+    );
+}
+
+#[test]
+fn test_normalization_options_round_trip() {
+    let config = ImageConfig::new(16, 1024, 1)
+        .unwrap()
+        .with_normalization([0.5; 3], [0.25; 3])
+        .with_rescale_factor(1.0)
+        .with_resample(ImageResample::Lanczos);
+    let written = serde_json::to_value(&config).unwrap();
+    assert_eq!(written["resample"], "lanczos");
+    assert_eq!(written["rescale_factor"], 1.0);
+
+    let read: ImageConfig = serde_json::from_value(written).unwrap();
+    assert_eq!(read.image_mean, [0.5; 3]);
+    assert_eq!(read.image_std, [0.25; 3]);
+    assert_eq!(read.resample, ImageResample::Lanczos);
+}
+
+#[test]
+fn test_invalid_normalization_is_rejected() {
+    let config = ImageConfig::new(16, 1024, 1).unwrap();
+    assert!(
+        config
+            .clone()
+            .with_normalization([0.5; 3], [0.5, 0.0, 0.5])
+            .validate()
+            .is_err()
+    );
+    assert!(
+        config
+            .clone()
+            .with_rescale_factor(f64::NAN)
+            .validate()
+            .is_err()
+    );
+    assert!(
+        Image::from(solid(2, 2, [0, 0, 0]))
+            .preprocess(&config.with_normalization([f64::INFINITY; 3], [1.0; 3]))
+            .is_err()
+    );
+}
+
+#[test]
+fn test_custom_normalization_and_nearest_resize() {
+    let config = ImageConfig::new(2, 1024, 1)
+        .unwrap()
+        .with_normalization([0.5; 3], [0.5; 3])
+        .with_resample(ImageResample::Nearest);
+    let mut rgb = RgbImage::new(2, 1);
+    rgb.put_pixel(1, 0, Rgb([255, 255, 255]));
+    let preprocessed = Image::from(rgb).preprocess(&config).unwrap();
+
+    // The single row is doubled to fill one 2x2 patch; black maps to -1, white to 1
+    let expected =
+        ndarray::Array3::from_shape_fn((3, 2, 2), |(_, _, x)| if x == 0 { -1.0f32 } else { 1.0 });
+    assert_eq!(preprocessed.pixel_values, expected);
+
+    let raw = ImageConfig::new(2, 1024, 1)
+        .unwrap()
+        .with_normalization([0.0; 3], [1.0; 3])
+        .with_rescale_factor(1.0);
+    let preprocessed = Image::from(solid(2, 2, [10, 20, 30]))
+        .preprocess(&raw)
+        .unwrap();
+    assert_eq!(preprocessed.pixel_values[[0, 0, 0]], 10.0);
+    assert_eq!(preprocessed.pixel_values[[2, 1, 1]], 30.0);
+}
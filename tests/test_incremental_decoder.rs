@@ -0,0 +1,79 @@
+use std::sync::OnceLock;
+use tekken::SpecialTokenPolicy;
+use tekken::incremental::IncrementalDecoder;
+use tekken::tekkenizer::{InvalidTokenPolicy, Tekkenizer};
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_split_characters_are_held_back() {
+    let tokenizer = get_tokenizer();
+    let mut decoder = IncrementalDecoder::new(tokenizer, SpecialTokenPolicy::Ignore);
+
+    // "é" as two byte tokens: nothing is emitted until the character is complete
+    assert_eq!(decoder.push(tokenizer.byte_to_token_id(0xC3)).unwrap(), "");
+    assert_eq!(decoder.push(tokenizer.byte_to_token_id(0xA9)).unwrap(), "é");
+    decoder.finish().unwrap();
+
+    decoder.push(tokenizer.byte_to_token_id(0xC3)).unwrap();
+    assert!(decoder.finish().is_err());
+}
+
+#[test]
+fn test_pushes_concatenate_to_full_decode() {
+    let tokenizer = get_tokenizer();
+    let text = "Hello 🚀 wörld, 你好!";
+    let tokens = tokenizer.encode(text, true, true).unwrap();
+
+    let mut decoder = IncrementalDecoder::new(tokenizer, SpecialTokenPolicy::Ignore);
+    let mut decoded = String::new();
+    for &token in &tokens {
+        decoded.push_str(&decoder.push(token).unwrap());
+    }
+    decoder.finish().unwrap();
+    assert_eq!(decoded, text);
+
+    let mut keep = IncrementalDecoder::new(tokenizer, SpecialTokenPolicy::Keep);
+    assert_eq!(keep.push(tokens[0]).unwrap(), "<s>");
+    let mut raise = IncrementalDecoder::new(tokenizer, SpecialTokenPolicy::Raise);
+    assert!(raise.push(tokens[0]).is_err());
+}
+
+#[test]
+fn test_invalid_token_policy() {
+    let tokenizer = get_tokenizer();
+    let out_of_range = u32::try_from(tokenizer.vocab_size()).unwrap();
+
+    let mut strict = IncrementalDecoder::new(tokenizer, SpecialTokenPolicy::Ignore);
+    assert!(strict.push(out_of_range).is_err());
+
+    let mut lenient = IncrementalDecoder::new(tokenizer, SpecialTokenPolicy::Ignore)
+        .with_invalid_token_policy(InvalidTokenPolicy::Replace('\u{FFFD}'));
+    assert_eq!(lenient.push(out_of_range).unwrap(), "\u{FFFD}");
+}
+
+#[test]
+fn test_invalid_token_inside_a_character() {
+    let tokenizer = get_tokenizer();
+    let out_of_range = u32::try_from(tokenizer.vocab_size()).unwrap();
+    let lead = tokenizer.byte_to_token_id(0xC3);
+
+    for (policy, replaced) in [
+        (InvalidTokenPolicy::Skip, "\u{FFFD}"),
+        (InvalidTokenPolicy::Replace('?'), "\u{FFFD}?"),
+    ] {
+        let mut decoder = IncrementalDecoder::new(tokenizer, SpecialTokenPolicy::Ignore)
+            .with_invalid_token_policy(policy);
+        assert_eq!(decoder.push(lead).unwrap(), "");
+        assert_eq!(decoder.push(out_of_range).unwrap(), replaced);
+        // The continuation byte does not complete the flushed character
+        assert!(decoder.push(tokenizer.byte_to_token_id(0xA9)).is_err());
+    }
+}
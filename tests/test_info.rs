@@ -0,0 +1,54 @@
+use std::sync::OnceLock;
+use tekken::config::TokenizerVersion;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json").expect("Failed to load tokenizer")
+    })
+}
+
+#[test]
+fn test_info_matches_tokenizer() {
+    let tokenizer = get_tokenizer();
+    let info = tokenizer.info();
+
+    assert_eq!(info.version, TokenizerVersion::V7);
+    assert_eq!(info.vocab_size, tokenizer.vocab_size());
+    assert_eq!(info.num_special_tokens, 1000);
+    assert_eq!(
+        info.num_vocab_tokens + info.num_special_tokens,
+        info.vocab_size
+    );
+    assert!(info.num_defined_special_tokens > 0);
+    assert!(info.num_defined_special_tokens < info.num_special_tokens);
+    assert_eq!(info.has_audio_support, cfg!(feature = "audio"));
+    assert_eq!(info.backend, tokenizer.backend_name());
+    assert!(info.memory_bytes > 0);
+    assert_eq!(info.fingerprint.len(), 64);
+}
+
+#[test]
+fn test_display_is_single_line_summary() {
+    let tokenizer = get_tokenizer();
+    let line = tokenizer.to_string();
+
+    assert_eq!(line, tokenizer.info().to_string());
+    assert!(!line.contains('\n'));
+    assert!(line.starts_with("tekken v7: "));
+    assert!(line.contains("1000 special"));
+    assert_eq!(line.contains(", audio"), cfg!(feature = "audio"));
+    assert!(line.contains(&tokenizer.info().fingerprint[..16]));
+}
+
+#[test]
+fn test_pruned_tokenizer_reports_less_memory() {
+    let tokenizer = get_tokenizer();
+    let pruned = tokenizer.pruned(1256).unwrap();
+
+    assert_eq!(pruned.info().num_vocab_tokens, 256);
+    assert!(pruned.info().memory_bytes < tokenizer.info().memory_bytes);
+    assert_ne!(pruned.info().fingerprint, tokenizer.info().fingerprint);
+}
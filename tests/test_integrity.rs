@@ -0,0 +1,141 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use tekken::errors::{LoadStage, TokenizerError};
+use tekken::integrity::{checksum_path, sha256_hex, write_checksum};
+use tekken::tekkenizer::Tekkenizer;
+
+fn write_model(dir: &Path) -> PathBuf {
+    let model_data = common::model_data(["he", "ll", "hell", "hello"]);
+    let path = dir.join("tekken.json");
+    std::fs::write(&path, serde_json::to_vec(&model_data).unwrap()).unwrap();
+    path
+}
+
+#[test]
+fn test_sha256_hex() {
+    assert_eq!(
+        sha256_hex(b"abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn test_verify_expected_hash() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_model(dir.path());
+    let hash = sha256_hex(&std::fs::read(&path).unwrap());
+
+    Tekkenizer::verify(&path, &hash).unwrap();
+    Tekkenizer::verify(&path, &hash.to_ascii_uppercase()).unwrap();
+
+    let wrong = "0".repeat(64);
+    let error = Tekkenizer::verify(&path, &wrong).err().unwrap();
+    let TokenizerError::ChecksumMismatch { expected, actual } = error else {
+        panic!("expected a checksum mismatch, got {error:?}");
+    };
+    assert_eq!(expected, wrong);
+    assert_eq!(actual, hash);
+
+    assert!(matches!(
+        Tekkenizer::verify(&path, "not a hash"),
+        Err(TokenizerError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn test_from_file_checks_sidecar() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_model(dir.path());
+
+    // Without a sidecar nothing is checked
+    Tekkenizer::from_file(&path).unwrap();
+
+    let sidecar = write_checksum(&path).unwrap();
+    assert_eq!(sidecar, checksum_path(&path));
+    assert!(
+        std::fs::read_to_string(&sidecar)
+            .unwrap()
+            .ends_with("  tekken.json\n")
+    );
+    Tekkenizer::from_file(&path).unwrap();
+
+    // A changed vocabulary that still parses is rejected
+    let mut json: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+    json["vocab"].as_array_mut().unwrap().swap(256, 257);
+    json["vocab"][256]["rank"] = 256.into();
+    json["vocab"][257]["rank"] = 257.into();
+    std::fs::write(&path, serde_json::to_vec(&json).unwrap()).unwrap();
+
+    let error = Tekkenizer::from_file(&path).err().unwrap();
+    let TokenizerError::Load { stage, .. } = &error else {
+        panic!("expected a load error, got {error:?}");
+    };
+    assert_eq!(*stage, LoadStage::Read);
+    assert!(matches!(
+        error.root_cause(),
+        TokenizerError::ChecksumMismatch { .. }
+    ));
+    assert!(Tekkenizer::from_file_lenient(&path).is_err());
+
+    write_checksum(&path).unwrap();
+    Tekkenizer::from_file(&path).unwrap();
+}
+
+/// Changes a file without changing what it parses to.
+fn touch_up(path: &Path) {
+    let mut content = std::fs::read(path).unwrap();
+    content.push(b'\n');
+    std::fs::write(path, content).unwrap();
+}
+
+#[test]
+fn test_from_sharded_checks_sidecars() {
+    let dir = tempfile::tempdir().unwrap();
+    let tokenizer = Tekkenizer::from_file(write_model(dir.path())).unwrap();
+    let index = dir.path().join("tekken.index.json");
+    tokenizer.save_sharded(&index, 200).unwrap();
+    let shard = dir.path().join("tekken-vocab-00002-of-00002.json");
+    write_checksum(&index).unwrap();
+    write_checksum(&shard).unwrap();
+    Tekkenizer::from_sharded(&index).unwrap();
+
+    touch_up(&shard);
+    assert!(matches!(
+        Tekkenizer::from_sharded(&index),
+        Err(TokenizerError::ChecksumMismatch { .. })
+    ));
+    write_checksum(&shard).unwrap();
+
+    touch_up(&index);
+    assert!(matches!(
+        Tekkenizer::from_sharded(&index),
+        Err(TokenizerError::ChecksumMismatch { .. })
+    ));
+    write_checksum(&index).unwrap();
+    Tekkenizer::from_sharded(&index).unwrap();
+}
+
+#[test]
+fn test_from_tiktoken_checks_sidecars() {
+    let dir = tempfile::tempdir().unwrap();
+    let tokenizer = Tekkenizer::from_file(write_model(dir.path())).unwrap();
+    let ranks = dir.path().join("tekken.tiktoken");
+    let sidecar = dir.path().join("tekken.sidecar.json");
+    tokenizer.save_tiktoken(&ranks, &sidecar).unwrap();
+    write_checksum(&ranks).unwrap();
+    write_checksum(&sidecar).unwrap();
+    Tekkenizer::from_tiktoken(&ranks, &sidecar).unwrap();
+
+    for path in [&ranks, &sidecar] {
+        touch_up(path);
+        assert!(matches!(
+            Tekkenizer::from_tiktoken(&ranks, &sidecar),
+            Err(TokenizerError::ChecksumMismatch { .. })
+        ));
+        write_checksum(path).unwrap();
+    }
+    Tekkenizer::from_tiktoken(&ranks, &sidecar).unwrap();
+}
@@ -0,0 +1,126 @@
+use std::sync::OnceLock;
+use tekken::errors::TokenizerError;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::{InvalidTokenPolicy, Tekkenizer};
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_invalid_token_policies() {
+    let tokenizer = get_tokenizer();
+    let invalid = tokenizer.vocab_size() as u32;
+    let mut tokens = tokenizer.encode("Hello", true, false).unwrap();
+    tokens.push(invalid);
+    tokens.extend(tokenizer.encode(", world!", false, false).unwrap());
+    tokens.push(u32::MAX);
+
+    let error = tokenizer
+        .decode_with_invalid_policy(
+            &tokens,
+            SpecialTokenPolicy::Ignore,
+            InvalidTokenPolicy::Error,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(error, TokenizerError::TokenOutOfRange { token_id, .. } if token_id == invalid)
+    );
+
+    let skipped = tokenizer
+        .decode_with_invalid_policy(
+            &tokens,
+            SpecialTokenPolicy::Ignore,
+            InvalidTokenPolicy::Skip,
+        )
+        .unwrap();
+    assert_eq!(skipped, "Hello, world!");
+
+    let replaced = tokenizer
+        .decode_with_invalid_policy(
+            &tokens,
+            SpecialTokenPolicy::Keep,
+            InvalidTokenPolicy::Replace('\u{FFFD}'),
+        )
+        .unwrap();
+    assert_eq!(replaced, "<s>Hello\u{FFFD}, world!\u{FFFD}");
+}
+
+#[test]
+fn test_invalid_token_policies_decode_all() {
+    let tokenizer = get_tokenizer();
+    let mut tokens = tokenizer.encode("Hi", true, false).unwrap();
+    tokens.push(u32::MAX);
+
+    assert!(
+        tokenizer
+            .decode_all(&tokens, SpecialTokenPolicy::Keep)
+            .is_err()
+    );
+
+    let skipped = tokenizer
+        .decode_all_with_invalid_policy(&tokens, SpecialTokenPolicy::Keep, InvalidTokenPolicy::Skip)
+        .unwrap();
+    assert_eq!(skipped, vec!["<s>".to_string(), "Hi".to_string()]);
+
+    let replaced = tokenizer
+        .decode_all_with_invalid_policy(
+            &tokens,
+            SpecialTokenPolicy::Keep,
+            InvalidTokenPolicy::Replace('?'),
+        )
+        .unwrap();
+    assert_eq!(replaced, vec!["<s>".to_string(), "Hi?".to_string()]);
+}
+
+#[test]
+fn test_invalid_token_inside_a_character() {
+    let tokenizer = get_tokenizer();
+    let invalid = tokenizer.vocab_size() as u32;
+    // The first byte of "é", an invalid token, then "Hi"
+    let mut tokens = vec![tokenizer.byte_to_token_id(0xC3), invalid];
+    tokens.extend(tokenizer.encode("Hi", false, false).unwrap());
+
+    let decode =
+        |policy| tokenizer.decode_with_invalid_policy(&tokens, SpecialTokenPolicy::Ignore, policy);
+    assert_eq!(decode(InvalidTokenPolicy::Skip).unwrap(), "\u{FFFD}Hi");
+    assert_eq!(
+        decode(InvalidTokenPolicy::Replace('?')).unwrap(),
+        "\u{FFFD}?Hi"
+    );
+    let decode_all = |policy| {
+        tokenizer.decode_all_with_invalid_policy(&tokens, SpecialTokenPolicy::Ignore, policy)
+    };
+    assert_eq!(
+        decode_all(InvalidTokenPolicy::Skip).unwrap(),
+        vec!["\u{FFFD}Hi".to_string()]
+    );
+    assert_eq!(
+        decode_all(InvalidTokenPolicy::Replace('?')).unwrap(),
+        vec!["\u{FFFD}?Hi".to_string()]
+    );
+
+    // The bytes on either side of the invalid token do not join into "é"
+    let split = [
+        tokenizer.byte_to_token_id(0xC3),
+        invalid,
+        tokenizer.byte_to_token_id(0xA9),
+    ];
+    for policy in [InvalidTokenPolicy::Skip, InvalidTokenPolicy::Replace('?')] {
+        assert!(
+            tokenizer
+                .decode_with_invalid_policy(&split, SpecialTokenPolicy::Ignore, policy)
+                .is_err()
+        );
+        assert!(
+            tokenizer
+                .decode_all_with_invalid_policy(&split, SpecialTokenPolicy::Ignore, policy)
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use tekken::js::JsTekkenizer;
+
+fn get_tokenizer() -> JsTekkenizer {
+    let bytes = std::fs::read("tests/assets/tekken.json").expect("Failed to read tokenizer file");
+    JsTekkenizer::new(&bytes).unwrap_or_else(|_| panic!("Failed to load tokenizer"))
+}
+
+// Error paths build JavaScript `Error` objects and can only run on wasm32
+#[test]
+fn test_encode_decode_and_count() {
+    let tokenizer = get_tokenizer();
+    let Ok(ids) = tokenizer.encode("Hello, world!", true, false) else {
+        panic!("encode failed");
+    };
+    assert_eq!(ids, vec![1, 22177, 1044, 4304, 1033]);
+    assert_eq!(
+        tokenizer.decode(&ids, None).ok().as_deref(),
+        Some("Hello, world!")
+    );
+    assert_eq!(
+        tokenizer.decode(&ids, Some(true)).ok().as_deref(),
+        Some("<s>Hello, world!")
+    );
+    assert_eq!(tokenizer.count_tokens("Hello, world!").ok(), Some(4));
+}
+
+#[test]
+fn test_special_tokens() {
+    let tokenizer = get_tokenizer();
+    let special = tokenizer.special_tokens();
+    assert_eq!(special.len(), 1000);
+    assert_eq!(special[1], "<s>");
+    assert_eq!(special[2], "</s>");
+    assert_eq!(tokenizer.special_token_ids().len(), 1000);
+    assert!(tokenizer.vocab_size() > special.len());
+}
@@ -0,0 +1,67 @@
+use base64::{Engine as _, engine::general_purpose};
+use tekken::config::{TokenInfo, TokenizerVersion};
+use tekken::special_tokens::SpecialTokenInfo;
+use tekken::tekkenizer::Tekkenizer;
+
+fn create_tokenizer() -> Tekkenizer {
+    let mut vocab: Vec<TokenInfo> = (0..256)
+        .map(|i| TokenInfo {
+            rank: i,
+            token_bytes: general_purpose::STANDARD.encode([i as u8]),
+            token_str: None,
+        })
+        .collect();
+    vocab.push(TokenInfo {
+        rank: 256,
+        token_bytes: general_purpose::STANDARD.encode(b"hello"),
+        token_str: Some("hello".to_string()),
+    });
+
+    let special_tokens = vec![
+        SpecialTokenInfo {
+            rank: 0,
+            token_str: "<unk>".to_string(),
+            is_control: true,
+        },
+        SpecialTokenInfo {
+            rank: 1,
+            token_str: "<s>".to_string(),
+            is_control: true,
+        },
+        SpecialTokenInfo {
+            rank: 2,
+            token_str: "</s>".to_string(),
+            is_control: true,
+        },
+    ];
+
+    Tekkenizer::new(
+        vocab,
+        &special_tokens,
+        String::new(),
+        262,
+        5,
+        TokenizerVersion::V7,
+        None,
+    )
+    .expect("Failed to create tokenizer")
+}
+
+#[test]
+fn test_vocab_is_built_on_demand() {
+    let tokenizer = create_tokenizer();
+
+    // Encoding and decoding work without ever touching the string table
+    let tokens = tokenizer.encode("hello", false, false).unwrap();
+    assert_eq!(tokens, vec![261]);
+
+    let vocab = tokenizer.vocab();
+    assert_eq!(vocab.len(), 262);
+    assert_eq!(vocab[1], "<s>");
+    assert_eq!(vocab[4], "<SPECIAL_4>");
+    assert_eq!(vocab[5 + u32::from(b'a') as usize], "a");
+    assert_eq!(vocab[261], "hello");
+
+    // Subsequent calls return the same table
+    assert!(std::ptr::eq(vocab, tokenizer.vocab()));
+}
@@ -0,0 +1,227 @@
+mod common;
+
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::{MatchMode, RankGapPolicy, Tekkenizer};
+
+/// A small tokenizer file as JSON: 256 byte tokens plus merges up to rank 262.
+fn model_json() -> serde_json::Value {
+    let model_data = common::model_data(["he", "ll", "hell", "hello", " w", "or", "ld"]);
+    serde_json::to_value(model_data).unwrap()
+}
+
+fn vocab(json: &mut serde_json::Value) -> &mut Vec<serde_json::Value> {
+    json["vocab"].as_array_mut().unwrap()
+}
+
+fn write(json: &serde_json::Value) -> tempfile::NamedTempFile {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), serde_json::to_vec(json).unwrap()).unwrap();
+    file
+}
+
+#[test]
+fn test_strict_loading_reports_gaps() {
+    let mut json = model_json();
+    vocab(&mut json).remove(260);
+    json["config"]["default_vocab_size"] = 362.into();
+
+    let error = Tekkenizer::from_bytes(&serde_json::to_vec(&json).unwrap())
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("missing ranks 260"), "{error}");
+    assert!(error.contains("beyond the token count 262"), "{error}");
+}
+
+#[test]
+fn test_strict_loading_reports_duplicates() {
+    let mut json = model_json();
+    vocab(&mut json)[261]["token_bytes"] = vocab(&mut json)[256]["token_bytes"].clone();
+
+    let error = Tekkenizer::from_bytes(&serde_json::to_vec(&json).unwrap())
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("token bytes repeated"), "{error}");
+    assert!(error.contains("(256, 261)"), "{error}");
+
+    let mut json = model_json();
+    vocab(&mut json)[261]["rank"] = 260.into();
+    let error = Tekkenizer::from_bytes(&serde_json::to_vec(&json).unwrap())
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("duplicate ranks 260"), "{error}");
+}
+
+#[test]
+fn test_lenient_loading_compacts_ranks() {
+    let mut json = model_json();
+    vocab(&mut json).remove(260);
+    let duplicate = vocab(&mut json)[256].clone();
+    vocab(&mut json).push(serde_json::json!({
+        "rank": 300,
+        "token_bytes": duplicate["token_bytes"],
+        "token_str": null,
+    }));
+    let file = write(&json);
+
+    assert!(Tekkenizer::from_file(file.path()).is_err());
+    let (tokenizer, warnings) = Tekkenizer::from_file_lenient(file.path()).unwrap();
+    assert_eq!(warnings.len(), 3, "{warnings:?}");
+    assert!(warnings[0].contains("Ranks 260..261 are missing"));
+    assert!(warnings[1].contains("Skipped rank 300"));
+    assert!(warnings[2].contains("Reduced vocab_size from 363 to 362"));
+
+    assert_eq!(tokenizer.vocab_size(), 362);
+    let tokens = tokenizer.encode("hello world", false, false).unwrap();
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, SpecialTokenPolicy::Raise)
+            .unwrap(),
+        "hello world"
+    );
+}
+
+#[test]
+fn test_lenient_loading_of_valid_file() {
+    let (tokenizer, warnings) = Tekkenizer::from_file_lenient("tests/assets/tekken.json").unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(
+        tokenizer.encode("Hello, world!", false, false).unwrap(),
+        vec![22177, 1044, 4304, 1033]
+    );
+}
+
+#[test]
+fn test_placeholders_keep_token_ids() {
+    let strict = Tekkenizer::from_bytes(&serde_json::to_vec(&model_json()).unwrap()).unwrap();
+    let mut json = model_json();
+    vocab(&mut json).remove(260);
+    let duplicate = vocab(&mut json)[256].clone();
+    vocab(&mut json).push(serde_json::json!({
+        "rank": 264,
+        "token_bytes": duplicate["token_bytes"],
+        "token_str": null,
+    }));
+    json["config"]["default_vocab_size"] = 365.into();
+    let file = write(&json);
+
+    let (tokenizer, warnings) =
+        Tekkenizer::from_file_lenient_with(file.path(), RankGapPolicy::Placeholder).unwrap();
+    assert_eq!(warnings.len(), 3, "{warnings:?}");
+    assert!(warnings[0].contains("Ranks 260..261 are missing"));
+    assert!(warnings[1].contains("Ranks 263..264 are missing"));
+    assert!(warnings[2].contains("Rank 264 repeats the token bytes of rank 256"));
+    assert_eq!(tokenizer.vocab_size(), 365);
+
+    // Token IDs after the gap are the ones of the file
+    let placeholders: Vec<u32> = (0..365)
+        .filter(|&id| tokenizer.is_placeholder(id))
+        .collect();
+    assert_eq!(placeholders, vec![360, 363, 364]);
+    assert_eq!(
+        tokenizer.id_to_piece(361).unwrap(),
+        strict.id_to_piece(361).unwrap()
+    );
+    assert_eq!(
+        tokenizer.id_to_piece(362).unwrap(),
+        strict.id_to_piece(362).unwrap()
+    );
+
+    // Placeholders are never encoded and decode as U+FFFD
+    for text in ["hello world", " w", "\u{FFFD}"] {
+        let tokens = tokenizer.encode(text, false, false).unwrap();
+        assert!(
+            tokens.iter().all(|&id| !tokenizer.is_placeholder(id)),
+            "{text:?}"
+        );
+        assert_eq!(
+            tokenizer
+                .decode(&tokens, SpecialTokenPolicy::Raise)
+                .unwrap(),
+            text
+        );
+    }
+    assert_eq!(
+        tokenizer
+            .decode(&[358, 360, 361], SpecialTokenPolicy::Raise)
+            .unwrap(),
+        "hell\u{FFFD}or"
+    );
+    assert!(!strict.is_placeholder(360));
+
+    // Nor matched by token_ids_for
+    for (text, mode) in [
+        ("", MatchMode::Contains),
+        ("", MatchMode::StartsWith),
+        ("\u{FFFD}", MatchMode::Exact),
+    ] {
+        let ids = tokenizer.token_ids_for(text, mode);
+        assert!(
+            ids.iter().all(|&id| !tokenizer.is_placeholder(id)),
+            "{text:?}"
+        );
+    }
+    assert_eq!(
+        tokenizer.token_ids_for("", MatchMode::Contains).len(),
+        365 - 100 - 3
+    );
+}
+
+#[test]
+fn test_placeholder_export_loads_back() {
+    let mut json = model_json();
+    vocab(&mut json).remove(260);
+    let duplicate = vocab(&mut json)[256].clone();
+    vocab(&mut json).push(serde_json::json!({
+        "rank": 264,
+        "token_bytes": duplicate["token_bytes"],
+        "token_str": null,
+    }));
+    json["config"]["default_vocab_size"] = 365.into();
+    let file = write(&json);
+    let (tokenizer, _) =
+        Tekkenizer::from_file_lenient_with(file.path(), RankGapPolicy::Placeholder).unwrap();
+
+    let model_data = tokenizer.to_model_data();
+    assert_eq!(model_data.config.placeholder_ranks, vec![260, 263, 264]);
+    assert_eq!(model_data.vocab.len(), 262);
+    let exported = serde_json::to_vec(&model_data).unwrap();
+    let loaded = Tekkenizer::from_bytes(&exported).unwrap();
+    assert_eq!(loaded.fingerprint(), tokenizer.fingerprint());
+    assert_eq!(loaded.vocab_size(), 365);
+    let placeholders: Vec<u32> = (0..365).filter(|&id| loaded.is_placeholder(id)).collect();
+    assert_eq!(placeholders, vec![360, 363, 364]);
+    assert_eq!(
+        loaded.encode("hello world", false, false).unwrap(),
+        tokenizer.encode("hello world", false, false).unwrap()
+    );
+
+    // Lenient loading keeps the marked placeholders, including trailing ones
+    let file = write(&serde_json::from_slice(&exported).unwrap());
+    let (lenient, _) =
+        Tekkenizer::from_file_lenient_with(file.path(), RankGapPolicy::Placeholder).unwrap();
+    assert_eq!(lenient.fingerprint(), tokenizer.fingerprint());
+
+    // Marks must not overlap entries or byte tokens
+    for marks in [vec![259, 263, 264], vec![65, 263, 264]] {
+        let mut json: serde_json::Value = serde_json::from_slice(&exported).unwrap();
+        json["config"]["placeholder_ranks"] = marks.into();
+        assert!(Tekkenizer::from_bytes(&serde_json::to_vec(&json).unwrap()).is_err());
+    }
+}
+
+#[test]
+fn test_placeholders_cannot_replace_byte_tokens() {
+    let mut json = model_json();
+    vocab(&mut json).remove(65);
+    let file = write(&json);
+
+    assert!(Tekkenizer::from_file_lenient(file.path()).is_err());
+    let error = Tekkenizer::from_file_lenient_with(file.path(), RankGapPolicy::Placeholder)
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("Byte token rank 65"), "{error}");
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use std::path::Path;
+use tekken::errors::{LoadStage, TokenizerError};
+use tekken::tekkenizer::Tekkenizer;
+
+fn model_json() -> serde_json::Value {
+    let model_data = common::model_data(["he", "ll", "hell", "hello"]);
+    serde_json::to_value(model_data).unwrap()
+}
+
+fn write(contents: &[u8]) -> tempfile::NamedTempFile {
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), contents).unwrap();
+    file
+}
+
+fn load_error(path: &Path) -> TokenizerError {
+    Tekkenizer::from_file(path)
+        .err()
+        .expect("loading should fail")
+}
+
+fn assert_stage(error: &TokenizerError, path: &Path, expected: LoadStage) {
+    let TokenizerError::Load {
+        path: error_path,
+        stage,
+        ..
+    } = error
+    else {
+        panic!("expected a load error, got {error:?}");
+    };
+    assert_eq!(error_path, path);
+    assert_eq!(*stage, expected);
+
+    let message = error.to_string();
+    assert!(message.contains(&path.display().to_string()), "{message}");
+    assert!(
+        message.contains(&format!("({expected} stage)")),
+        "{message}"
+    );
+}
+
+#[test]
+fn test_missing_file_fails_in_read_stage() {
+    let path = Path::new("tests/assets/does_not_exist.json");
+    let error = load_error(path);
+    assert_stage(&error, path, LoadStage::Read);
+    assert!(matches!(error.root_cause(), TokenizerError::Io(_)));
+}
+
+#[test]
+fn test_malformed_json_fails_in_parse_stage() {
+    let file = write(b"{\"config\": ");
+    let error = load_error(file.path());
+    assert_stage(&error, file.path(), LoadStage::Parse);
+    #[cfg(not(feature = "simd-json"))]
+    assert!(error.to_string().contains("line 1"), "{error}");
+}
+
+#[test]
+fn test_inconsistent_config_fails_in_validate_stage() {
+    let mut json = model_json();
+    json["config"]["default_vocab_size"] = 1000.into();
+    let file = write(&serde_json::to_vec(&json).unwrap());
+
+    let error = load_error(file.path());
+    assert_stage(&error, file.path(), LoadStage::Validate);
+    assert!(matches!(
+        error.root_cause(),
+        TokenizerError::InvalidConfig(_)
+    ));
+}
+
+#[test]
+fn test_invalid_pattern_fails_in_build_stage() {
+    let mut json = model_json();
+    json["config"]["pattern"] = "(?<".into();
+    let file = write(&serde_json::to_vec(&json).unwrap());
+
+    let error = load_error(file.path());
+    assert_stage(&error, file.path(), LoadStage::Build);
+    assert!(error.to_string().contains("pattern"), "{error}");
+}
+
+#[test]
+fn test_lenient_and_limited_loading_add_context() {
+    let file = write(b"not json");
+    let error = Tekkenizer::from_file_lenient(file.path()).err().unwrap();
+    assert_stage(&error, file.path(), LoadStage::Parse);
+
+    let error = Tekkenizer::from_file_with_limit(file.path(), 1000)
+        .err()
+        .unwrap();
+    assert_stage(&error, file.path(), LoadStage::Parse);
+}
+
+#[test]
+fn test_from_bytes_errors_are_not_wrapped() {
+    let error = Tekkenizer::from_bytes(b"not json").err().unwrap();
+    assert!(!matches!(error, TokenizerError::Load { .. }));
+    assert!(std::ptr::eq(error.root_cause(), &error));
+}
@@ -0,0 +1,96 @@
+mod common;
+
+use tekken::config::TokenizerVersion;
+use tekken::report::LoadWarning;
+use tekken::tekkenizer::Tekkenizer;
+
+fn model_json() -> serde_json::Value {
+    let model_data = common::model_data(["he", "ll", "hell", "hello"]);
+    serde_json::to_value(model_data).unwrap()
+}
+
+fn load(json: &serde_json::Value) -> Vec<LoadWarning> {
+    let (_, report) =
+        Tekkenizer::from_bytes_with_report(&serde_json::to_vec(json).unwrap()).unwrap();
+    report.warnings
+}
+
+#[test]
+fn test_fully_specified_file_has_clean_report() {
+    let mut json = model_json();
+    let tokens = (0..100)
+        .map(|rank| {
+            let name = TokenizerVersion::V7
+                .default_special_tokens()
+                .into_iter()
+                .find(|token| token.rank == rank)
+                .map_or_else(|| format!("<SPECIAL_{rank}>"), |token| token.token_str);
+            serde_json::json!({ "rank": rank, "token_str": name, "is_control": true })
+        })
+        .collect::<Vec<_>>();
+    json["special_tokens"] = tokens.into();
+
+    assert_eq!(load(&json), Vec::new());
+}
+
+#[test]
+fn test_unknown_fields_are_reported() {
+    let mut json = model_json();
+    json["config"]["rope_theta"] = 1e6.into();
+    let warnings = load(&json);
+    assert!(warnings.contains(&LoadWarning::UnknownField {
+        section: "config",
+        field: "rope_theta".to_string(),
+    }));
+    assert!(warnings[0].to_string().contains("rope_theta"));
+}
+
+#[test]
+fn test_placeholder_slots_are_reported() {
+    let warnings = load(&model_json());
+    let defined = TokenizerVersion::V7.default_special_tokens().len();
+    assert!(warnings.contains(&LoadWarning::PlaceholderSpecialTokens {
+        count: 100 - defined
+    }));
+}
+
+#[test]
+fn test_default_special_tokens_and_inferred_version_are_reported() {
+    let mut json = model_json();
+    json.as_object_mut().unwrap().remove("special_tokens");
+    json["config"]["version"] = "v99".into();
+
+    let warnings = load(&json);
+    assert!(warnings.contains(&LoadWarning::VersionInferred {
+        declared: "v99".to_string(),
+        inferred: TokenizerVersion::V3,
+    }));
+    assert!(warnings.contains(&LoadWarning::DefaultSpecialTokens {
+        version: TokenizerVersion::V3
+    }));
+}
+
+#[test]
+fn test_trimmed_vocab_is_reported() {
+    let mut json = model_json();
+    json["config"]["default_vocab_size"] = 358.into();
+    let warnings = load(&json);
+    assert!(warnings.contains(&LoadWarning::VocabTrimmed {
+        available: 260,
+        kept: 258
+    }));
+}
+
+#[test]
+fn test_report_from_file_matches_bytes() {
+    let (_, report) = Tekkenizer::from_file_with_report("tests/assets/tekken.json").unwrap();
+    let bytes = std::fs::read("tests/assets/tekken.json").unwrap();
+    let (_, from_bytes) = Tekkenizer::from_bytes_with_report(&bytes).unwrap();
+    assert_eq!(report, from_bytes);
+    assert!(
+        report
+            .warnings
+            .iter()
+            .all(|warning| !matches!(warning, LoadWarning::Repaired(_)))
+    );
+}
@@ -0,0 +1,44 @@
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_mergeable_ranks_cover_vocabulary() {
+    let tokenizer = get_tokenizer();
+    let ranks = tokenizer.mergeable_ranks();
+    assert_eq!(
+        ranks.len(),
+        tokenizer.vocab_size() - tokenizer.num_special_tokens()
+    );
+
+    for (expected_rank, (bytes, rank)) in tokenizer.mergeable_ranks().enumerate() {
+        assert_eq!(rank as usize, expected_rank);
+        assert_eq!(tokenizer.rank_of(bytes), Some(rank));
+        if rank < 256 {
+            assert_eq!(bytes, [rank as u8]);
+        }
+    }
+}
+
+#[test]
+fn test_rank_of_matches_encoding() {
+    let tokenizer = get_tokenizer();
+    let num_special = tokenizer.num_special_tokens() as u32;
+
+    let rank = tokenizer.rank_of(b"Hello").unwrap();
+    assert_eq!(rank + num_special, 22177);
+    assert_eq!(tokenizer.rank_of(b"\xff"), Some(255));
+    assert_eq!(tokenizer.rank_of(b""), None);
+    assert_eq!(
+        tokenizer.rank_of("this is surely not a single token".as_bytes()),
+        None
+    );
+}
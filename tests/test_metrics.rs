@@ -0,0 +1,123 @@
+use std::sync::OnceLock;
+
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+use tekken::SpecialTokenPolicy;
+use tekken::metrics::{CACHE_HITS, DECODE_ERRORS, ENCODE_DURATION, TOKENS_ENCODED};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json").expect("Failed to load tokenizer")
+    })
+}
+
+/// A recorded metric: name, labels and value.
+type Recorded = (String, Vec<(String, String)>, DebugValue);
+
+/// Runs `f` with a thread-local recorder and returns its recorded values.
+fn record(f: impl FnOnce()) -> Vec<Recorded> {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter: Snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, f);
+    snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let key = key.key();
+            let labels = key
+                .labels()
+                .map(|label| (label.key().to_string(), label.value().to_string()))
+                .collect();
+            (key.name().to_string(), labels, value)
+        })
+        .collect()
+}
+
+fn counter(values: &[Recorded], name: &str) -> Option<u64> {
+    values.iter().find_map(|(key, _, value)| match value {
+        DebugValue::Counter(count) if key == name => Some(*count),
+        _ => None,
+    })
+}
+
+#[test]
+fn test_encode_records_tokens_and_latency() {
+    let tokenizer = get_tokenizer();
+    let values = record(|| {
+        tokenizer.encode("Hello, world!", true, true).unwrap();
+        tokenizer.encode("Hello, world!", false, false).unwrap();
+    });
+
+    assert_eq!(counter(&values, TOKENS_ENCODED), Some(10));
+    let latencies = values
+        .iter()
+        .find_map(|(key, _, value)| match value {
+            DebugValue::Histogram(samples) if key == ENCODE_DURATION => Some(samples.len()),
+            _ => None,
+        })
+        .expect("encode latency should be recorded");
+    assert_eq!(latencies, 2);
+}
+
+#[test]
+fn test_decode_errors_are_counted() {
+    let tokenizer = get_tokenizer();
+    let values = record(|| {
+        tokenizer
+            .decode(&[22177, 1044], SpecialTokenPolicy::Keep)
+            .unwrap();
+        assert!(
+            tokenizer
+                .decode(&[u32::MAX], SpecialTokenPolicy::Keep)
+                .is_err()
+        );
+        assert!(tokenizer.decode(&[1], SpecialTokenPolicy::Raise).is_err());
+    });
+
+    assert_eq!(counter(&values, DECODE_ERRORS), Some(2));
+}
+
+#[test]
+fn test_cache_hits_are_labelled() {
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    let values = record(|| {
+        let _ = tokenizer.fingerprint();
+        let _ = tokenizer.fingerprint();
+        let _ = tokenizer.fingerprint();
+        let _ = tokenizer.vocab();
+    });
+
+    let hits: Vec<_> = values
+        .iter()
+        .filter(|(key, _, _)| key == CACHE_HITS)
+        .collect();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(
+        hits[0].1,
+        vec![("cache".to_string(), "fingerprint".to_string())]
+    );
+    assert_eq!(hits[0].2, DebugValue::Counter(2));
+}
+
+#[test]
+#[cfg(feature = "audio")]
+fn test_audio_duration_is_counted() {
+    use tekken::audio::Audio;
+    use tekken::metrics::AUDIO_MILLISECONDS_PROCESSED;
+
+    let tokenizer = get_tokenizer();
+    let sampling_rate = tokenizer.audio_config().unwrap().sampling_rate;
+    let audio = Audio::new(
+        ndarray::Array1::zeros(sampling_rate * 3 / 2),
+        sampling_rate,
+        "wav".to_string(),
+    );
+    let values = record(|| {
+        tokenizer.encode_audio(audio).unwrap();
+    });
+
+    assert_eq!(counter(&values, AUDIO_MILLISECONDS_PROCESSED), Some(1500));
+}
@@ -0,0 +1,100 @@
+use std::sync::OnceLock;
+use tekken::config::{ModelData, TokenStrPolicy};
+use tekken::tekkenizer::Tekkenizer;
+
+static MODEL_JSON: OnceLock<Vec<u8>> = OnceLock::new();
+
+fn get_model_json() -> &'static [u8] {
+    MODEL_JSON.get_or_init(|| {
+        std::fs::read("tests/assets/tekken.json").expect("Failed to read tokenizer file")
+    })
+}
+
+#[test]
+fn test_keep_stores_token_strs() {
+    let model_data = ModelData::from_slice(get_model_json(), TokenStrPolicy::Keep).unwrap();
+    assert_eq!(model_data.vocab.len(), 150_000);
+    assert!(
+        model_data
+            .vocab
+            .iter()
+            .any(|token| token.token_str.is_some())
+    );
+
+    let parsed: ModelData = serde_json::from_slice(get_model_json()).unwrap();
+    assert_eq!(
+        serde_json::to_value(&model_data.vocab).unwrap(),
+        serde_json::to_value(&parsed.vocab).unwrap()
+    );
+}
+
+#[test]
+fn test_drop_skips_token_strs() {
+    let kept = ModelData::from_slice(get_model_json(), TokenStrPolicy::Keep).unwrap();
+    let dropped = ModelData::from_slice(get_model_json(), TokenStrPolicy::Drop).unwrap();
+
+    assert!(dropped.vocab.iter().all(|token| token.token_str.is_none()));
+    assert_eq!(dropped.vocab.len(), kept.vocab.len());
+    for (dropped, kept) in dropped.vocab.iter().zip(&kept.vocab) {
+        assert_eq!(dropped.rank, kept.rank);
+        assert_eq!(dropped.token_bytes, kept.token_bytes);
+    }
+
+    // Everything outside the vocabulary is parsed as usual
+    assert_eq!(
+        serde_json::to_value(&dropped.special_tokens).unwrap(),
+        serde_json::to_value(&kept.special_tokens).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_value(&dropped.config).unwrap(),
+        serde_json::to_value(&kept.config).unwrap()
+    );
+    assert_eq!(
+        serde_json::to_value(&dropped.audio).unwrap(),
+        serde_json::to_value(&kept.audio).unwrap()
+    );
+    assert_eq!(dropped.extra, kept.extra);
+}
+
+#[test]
+fn test_dropped_model_data_loads_same_tokenizer() {
+    let dropped = ModelData::from_slice(get_model_json(), TokenStrPolicy::Drop).unwrap();
+    let tokenizer = Tekkenizer::from_bytes(&serde_json::to_vec(&dropped).unwrap()).unwrap();
+    let reference = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+
+    let text = "Hello, world! Dropping token strings changes nothing.";
+    assert_eq!(
+        tokenizer.encode(text, true, true).unwrap(),
+        reference.encode(text, true, true).unwrap()
+    );
+}
+
+#[test]
+fn test_drop_keeps_unknown_keys_and_aliases() {
+    let json = serde_json::json!({
+        "vocab": [{"rank": 0, "token_bytes": "AA==", "token_str": "\u{0}", "note": 1}],
+        "config": {
+            "pattern": ".",
+            "num_vocab_tokens": 1,
+            "default_vocab_size": 1001,
+            "default_num_special_tokens": 1000,
+            "version": "v7"
+        },
+        "multimodal": {"image_patch_size": 16, "max_image_size": 1024},
+        "type": "Tekken"
+    });
+    let bytes = serde_json::to_vec(&json).unwrap();
+
+    let model_data = ModelData::from_slice(&bytes, TokenStrPolicy::Drop).unwrap();
+    assert_eq!(model_data.vocab[0].token_str, None);
+    assert!(model_data.special_tokens.is_none());
+    assert!(model_data.image.is_some());
+    assert_eq!(model_data.extra["type"], "Tekken");
+}
+
+#[test]
+fn test_drop_rejects_invalid_vocab() {
+    let json = br#"{"vocab": [{"rank": 0}], "config": {}}"#;
+    assert!(ModelData::from_slice(json, TokenStrPolicy::Drop).is_err());
+    assert!(ModelData::from_slice(b"not json", TokenStrPolicy::Drop).is_err());
+}
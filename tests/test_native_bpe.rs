@@ -0,0 +1,70 @@
+use rustc_hash::FxHashMap;
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+use tiktoken_rs::CoreBPE;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+fn reference_bpe(tokenizer: &Tekkenizer) -> CoreBPE {
+    let ranks: FxHashMap<Vec<u8>, u32> = tokenizer
+        .mergeable_ranks()
+        .map(|(bytes, rank)| (bytes.to_vec(), rank))
+        .collect();
+    let pattern = tokenizer.to_model_data().config.pattern;
+    CoreBPE::new(ranks, FxHashMap::default(), &pattern).expect("Failed to build reference BPE")
+}
+
+#[test]
+fn test_matches_tiktoken_with_same_pattern() {
+    let tokenizer = get_tokenizer();
+    let reference = reference_bpe(tokenizer);
+    let num_special = tokenizer.num_special_tokens() as u32;
+
+    let texts = [
+        "",
+        "Hello, world!",
+        "The quick brown fox jumps over the lazy dog.",
+        "  leading and trailing spaces  ",
+        "line one\nline two\r\n\r\nline four",
+        "Numbers: 1234567890 and 3.14159",
+        "CamelCaseIdentifiers and snake_case_names",
+        "I'm sure they'll say it's fine, we've done it.",
+        "Mixed scripts: Привет мир, こんにちは世界, مرحبا بالعالم",
+        "Emoji 🎉🚀 and combining e\u{301}",
+        "fn main() { println!(\"{}\", 42); }",
+        "path/to/some/file.rs\n\n\n\tindented",
+        "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+    ];
+
+    for text in texts {
+        let expected: Vec<u32> = reference
+            .encode_ordinary(text)
+            .into_iter()
+            .map(|rank| rank + num_special)
+            .collect();
+        let actual = tokenizer.encode(text, false, false).unwrap();
+        assert_eq!(actual, expected, "mismatch for {text:?}");
+    }
+}
+
+#[test]
+fn test_uses_configured_pattern_for_digits() {
+    let tokenizer = get_tokenizer();
+
+    // The Tekken pattern splits numbers into single digits, unlike cl100k's `\p{N}{1,3}`
+    let tokens = tokenizer.encode("12345", false, false).unwrap();
+    assert_eq!(tokens.len(), 5);
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, tekken::SpecialTokenPolicy::Keep)
+            .unwrap(),
+        "12345"
+    );
+}
@@ -0,0 +1,164 @@
+use std::sync::OnceLock;
+use tekken::SpecialTokenPolicy;
+#[cfg(feature = "audio")]
+use tekken::audio::Audio;
+use tekken::encoding::{EncodingOptions, Padding};
+use tekken::parallel::{CancellationToken, ParallelismConfig};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+const TEXTS: [&str; 4] = ["Hello, world!", "Bonjour 🚀", "", "数字 12345"];
+
+#[test]
+fn test_batches_match_single_calls_for_every_config() {
+    let tokenizer = get_tokenizer();
+    let expected: Vec<Vec<u32>> = TEXTS
+        .iter()
+        .map(|text| tokenizer.encode(text, true, false).unwrap())
+        .collect();
+
+    for config in [
+        ParallelismConfig::default(),
+        ParallelismConfig::sequential(),
+        ParallelismConfig::with_max_threads(2).unwrap(),
+    ] {
+        let batch = tokenizer
+            .encode_batch(&TEXTS, true, false, &config)
+            .unwrap();
+        assert!(!batch.cancelled);
+        assert_eq!(batch.results, expected);
+
+        let decoded = tokenizer
+            .decode_batch(&batch.results, SpecialTokenPolicy::Ignore, &config)
+            .unwrap()
+            .into_complete()
+            .unwrap();
+        assert_eq!(decoded, TEXTS);
+    }
+}
+
+#[test]
+fn test_thread_limits() {
+    assert!(!ParallelismConfig::sequential().is_parallel());
+    assert!(
+        !ParallelismConfig::with_max_threads(1)
+            .unwrap()
+            .is_parallel()
+    );
+    assert_eq!(
+        ParallelismConfig::with_max_threads(4)
+            .unwrap()
+            .is_parallel(),
+        cfg!(feature = "parallel")
+    );
+}
+
+#[test]
+fn test_batch_errors_are_reported() {
+    let tokenizer = get_tokenizer();
+    let batch = vec![vec![22177], vec![u32::MAX]];
+    assert!(
+        tokenizer
+            .decode_batch(
+                &batch,
+                SpecialTokenPolicy::Ignore,
+                &ParallelismConfig::with_max_threads(2).unwrap()
+            )
+            .is_err()
+    );
+}
+
+#[test]
+fn test_encode_plus_batch_on_dedicated_pool() {
+    let tokenizer = get_tokenizer();
+    let options = EncodingOptions::new(true, false).with_padding(Padding::Longest);
+    let expected = tokenizer.encode_plus_batch(&TEXTS, &options).unwrap();
+    for config in [
+        ParallelismConfig::sequential(),
+        ParallelismConfig::with_max_threads(2).unwrap(),
+    ] {
+        assert_eq!(
+            tokenizer
+                .encode_plus_batch_with(&TEXTS, &options, &config)
+                .unwrap()
+                .results,
+            expected
+        );
+    }
+}
+
+#[cfg(feature = "audio")]
+#[test]
+fn test_encode_audio_batch_matches_single_calls() {
+    let tokenizer = get_tokenizer();
+    let audio = Audio::from_file("tests/assets/jfk.wav").unwrap();
+    let expected = tokenizer.encode_audio(audio.clone()).unwrap();
+
+    for config in [
+        ParallelismConfig::sequential(),
+        ParallelismConfig::with_max_threads(2).unwrap(),
+    ] {
+        let encodings = tokenizer
+            .encode_audio_batch(vec![audio.clone(), audio.clone()], &config)
+            .unwrap()
+            .into_complete()
+            .unwrap();
+        assert_eq!(encodings.len(), 2);
+        for encoding in encodings {
+            assert_eq!(encoding.tokens, expected.tokens);
+        }
+    }
+}
+
+#[test]
+fn test_cancelled_batches_return_completed_prefix() {
+    let tokenizer = get_tokenizer();
+    for config in [
+        ParallelismConfig::sequential(),
+        ParallelismConfig::with_max_threads(2).unwrap(),
+    ] {
+        let token = CancellationToken::new();
+        let config = config.with_cancellation(token.clone());
+        assert!(!config.is_cancelled());
+        let batch = tokenizer
+            .encode_batch(&TEXTS, true, false, &config)
+            .unwrap();
+        assert_eq!(batch.results.len(), TEXTS.len());
+        assert!(!batch.cancelled);
+
+        token.cancel();
+        assert!(config.is_cancelled());
+        let batch = tokenizer
+            .encode_batch(&TEXTS, true, false, &config)
+            .unwrap();
+        assert!(batch.results.is_empty());
+        assert!(batch.cancelled);
+        assert!(matches!(
+            batch.into_complete(),
+            Err(tekken::TokenizerError::Cancelled)
+        ));
+
+        let decoded = tokenizer
+            .decode_batch(&[vec![22177]], SpecialTokenPolicy::Ignore, &config)
+            .unwrap();
+        assert!(decoded.results.is_empty());
+        assert!(decoded.cancelled);
+
+        // Empty batches have nothing left to do
+        let empty: [&str; 0] = [];
+        assert!(
+            !tokenizer
+                .encode_batch(&empty, true, false, &config)
+                .unwrap()
+                .cancelled
+        );
+    }
+}
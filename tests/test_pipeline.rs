@@ -0,0 +1,134 @@
+use std::fs;
+use std::sync::OnceLock;
+
+use tekken::parallel::{CancellationToken, ParallelismConfig};
+use tekken::pipeline::Pipeline;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+fn read_shard(path: &std::path::Path) -> Vec<u32> {
+    fs::read(path)
+        .unwrap()
+        .chunks_exact(4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect()
+}
+
+#[test]
+fn test_directory_to_shards() {
+    let tokenizer = get_tokenizer();
+    let input = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    fs::create_dir(input.path().join("nested")).unwrap();
+    let documents = [
+        ("a.txt", "Hello, world!"),
+        ("b.txt", "Second document with more words in it."),
+        ("nested/c.txt", "Third 🚀"),
+        ("skip.md", "not matched"),
+    ];
+    for (name, text) in documents {
+        fs::write(input.path().join(name), text).unwrap();
+    }
+
+    let mut batches = Vec::new();
+    let report = Pipeline::new(tokenizer, output.path())
+        .add_dir(input.path())
+        .with_pattern("*.txt")
+        .with_shard_tokens(8)
+        .with_files_per_batch(2)
+        .with_parallelism(ParallelismConfig::with_max_threads(2).unwrap())
+        .on_progress(|progress| batches.push(*progress))
+        .run()
+        .unwrap();
+
+    let expected: Vec<u32> = documents[..3]
+        .iter()
+        .flat_map(|(_, text)| tokenizer.encode(text, false, true).unwrap())
+        .collect();
+    let written: Vec<u32> = report
+        .shards
+        .iter()
+        .flat_map(|path| read_shard(path))
+        .collect();
+    assert_eq!(written, expected);
+    assert_eq!(report.files, 3);
+    assert_eq!(report.tokens, expected.len() as u64);
+    // "Hello, world!" + EOS stays below 8 tokens, so the first shard holds two documents
+    assert_eq!(report.shards.len(), 2);
+    assert!(report.shards[0].ends_with("shard_00000.bin"));
+
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].files_done, 2);
+    assert_eq!(batches[1].files_done, 3);
+    assert_eq!(batches[1].files_total, 3);
+    assert_eq!(batches[1].tokens_written, report.tokens);
+}
+
+#[test]
+#[cfg(unix)]
+fn test_symlinked_directories_are_walked_once() {
+    let input = tempfile::tempdir().unwrap();
+    let shared = tempfile::tempdir().unwrap();
+    fs::write(shared.path().join("a.txt"), "Hello").unwrap();
+    std::os::unix::fs::symlink(shared.path(), input.path().join("linked")).unwrap();
+    // A cycle back to the input directory
+    std::os::unix::fs::symlink(input.path(), input.path().join("linked_back")).unwrap();
+
+    let files = Pipeline::new(get_tokenizer(), "unused")
+        .add_dir(input.path())
+        .input_files()
+        .unwrap();
+    assert_eq!(files, vec![input.path().join("linked").join("a.txt")]);
+}
+
+#[test]
+fn test_errors_name_the_file() {
+    let output = tempfile::tempdir().unwrap();
+    let error = Pipeline::new(get_tokenizer(), output.path())
+        .add_file("does/not/exist.txt")
+        .run()
+        .unwrap_err();
+    assert!(error.to_string().contains("does/not/exist.txt"));
+}
+
+#[test]
+fn test_cancellation_keeps_completed_batches() {
+    let tokenizer = get_tokenizer();
+    let input = tempfile::tempdir().unwrap();
+    let output = tempfile::tempdir().unwrap();
+    let documents = ["first", "second", "third"];
+    for (i, text) in documents.iter().enumerate() {
+        fs::write(input.path().join(format!("{i}.txt")), text).unwrap();
+    }
+
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    let report = Pipeline::new(tokenizer, output.path())
+        .add_dir(input.path())
+        .with_files_per_batch(2)
+        .with_parallelism(ParallelismConfig::default().with_cancellation(token))
+        .on_progress(move |_| cancel.cancel())
+        .run()
+        .unwrap();
+
+    assert!(report.cancelled);
+    assert_eq!(report.files, 2);
+    let expected: Vec<u32> = documents[..2]
+        .iter()
+        .flat_map(|text| tokenizer.encode(text, false, true).unwrap())
+        .collect();
+    let written: Vec<u32> = report
+        .shards
+        .iter()
+        .flat_map(|path| read_shard(path))
+        .collect();
+    assert_eq!(written, expected);
+}
@@ -0,0 +1,409 @@
+use std::sync::OnceLock;
+#[cfg(feature = "audio")]
+use tekken::Audio;
+use tekken::PromptEncoding;
+use tekken::config::TokenizerVersion;
+use tekken::prompt::{ChatMessage, EosPolicy, PromptBuilder, TruncationStrategy};
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+/// Small copy of the test tokenizer relabeled with another version.
+fn tokenizer_with_version(version: &str) -> Tekkenizer {
+    let tokenizer = get_tokenizer();
+    let mut model_data = tokenizer
+        .pruned(tokenizer.num_special_tokens() + 2048)
+        .unwrap()
+        .to_model_data();
+    model_data.config.version = version.to_string();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tekken.json");
+    std::fs::write(&path, serde_json::to_vec(&model_data).unwrap()).unwrap();
+    Tekkenizer::from_file(&path).unwrap()
+}
+
+fn assert_consistent(tokenizer: &Tekkenizer, prompt: &PromptEncoding) {
+    assert_eq!(
+        tokenizer
+            .decode(&prompt.tokens, SpecialTokenPolicy::Keep)
+            .unwrap(),
+        prompt.rendered
+    );
+}
+
+#[test]
+fn test_v7_conversation() {
+    let tokenizer = get_tokenizer();
+    assert_eq!(*tokenizer.version(), TokenizerVersion::V7);
+
+    let prompt = PromptBuilder::new(tokenizer)
+        .system("Be brief.")
+        .user("Hello")
+        .assistant("Hi!")
+        .user("Weather?")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        prompt.rendered,
+        "<s>[SYSTEM_PROMPT]Be brief.[/SYSTEM_PROMPT][INST]Hello[/INST]Hi!</s>[INST]Weather?[/INST]"
+    );
+    assert_eq!(prompt.tokens[0], tokenizer.bos_id().unwrap());
+    assert_consistent(tokenizer, &prompt);
+}
+
+#[test]
+fn test_v7_tool_results() {
+    let tokenizer = get_tokenizer();
+    let prompt = tokenizer
+        .prompt_builder()
+        .user("Weather in Paris?")
+        .tool_results("abc123XYZ", "22C")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        prompt.rendered,
+        "<s>[INST]Weather in Paris?[/INST][TOOL_RESULTS]abc123XYZ[TOOL_CONTENT]22C[/TOOL_RESULTS]"
+    );
+    assert_consistent(tokenizer, &prompt);
+}
+
+#[test]
+fn test_v3_system_prompt_and_tool_results() {
+    let tokenizer = tokenizer_with_version("v3");
+    let prompt = tokenizer
+        .prompt_builder()
+        .system("Be brief.")
+        .user("Hello")
+        .assistant("Hi!")
+        .user("Weather?")
+        .tool_results("abc123XYZ", "22C")
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        prompt.rendered,
+        "<s>[INST]Hello[/INST]Hi!</s>[INST]Be brief.\n\nWeather?[/INST]\
+         [TOOL_RESULTS]{\"content\": \"22C\", \"call_id\": \"abc123XYZ\"}[/TOOL_RESULTS]"
+    );
+    assert_consistent(&tokenizer, &prompt);
+
+    assert!(tokenizer.prompt_builder().system("Alone").build().is_err());
+}
+
+#[test]
+fn test_v11_tool_results() {
+    let tokenizer = tokenizer_with_version("v11");
+    let prompt = tokenizer
+        .prompt_builder()
+        .user("Hi")
+        .tool_results("abc123XYZ", "ok")
+        .build()
+        .unwrap();
+    assert!(prompt.rendered.ends_with("[TOOL_RESULTS]ok[/TOOL_RESULTS]"));
+}
+
+#[cfg(feature = "audio")]
+#[test]
+fn test_audio_is_merged_into_user_turn() {
+    let tokenizer = get_tokenizer();
+    let sampling_rate = tokenizer.audio_config().unwrap().sampling_rate;
+    let audio = Audio::new(
+        ndarray::Array1::zeros(sampling_rate),
+        sampling_rate,
+        "wav".to_string(),
+    );
+
+    let prompt = tokenizer
+        .prompt_builder()
+        .user("Transcribe:")
+        .audio(audio)
+        .build()
+        .unwrap();
+
+    assert!(
+        prompt
+            .rendered
+            .starts_with("<s>[INST]Transcribe:[BEGIN_AUDIO][AUDIO]x")
+    );
+    assert!(prompt.rendered.ends_with("[/INST]"));
+    let begin_inst = tokenizer.get_control_token("[INST]").unwrap();
+    assert_eq!(
+        prompt.tokens.iter().filter(|&&t| t == begin_inst).count(),
+        1
+    );
+}
+
+#[test]
+fn test_count_chat_tokens_includes_framing() {
+    let messages: Vec<ChatMessage> = serde_json::from_str(
+        r#"[
+            {"role": "system", "content": "Be brief."},
+            {"role": "user", "content": "Weather in Paris?"},
+            {"role": "tool", "tool_call_id": "abc123XYZ", "content": "22C"}
+        ]"#,
+    )
+    .unwrap();
+
+    for tokenizer in [tokenizer_with_version("v3"), tokenizer_with_version("v7")] {
+        let prompt = PromptBuilder::new(&tokenizer)
+            .system("Be brief.")
+            .user("Weather in Paris?")
+            .tool_results("abc123XYZ", "22C")
+            .build()
+            .unwrap();
+        let count = tokenizer.count_chat_tokens(&messages).unwrap();
+        assert_eq!(count, prompt.tokens.len());
+
+        let text_only: usize = ["Be brief.", "Weather in Paris?", "abc123XYZ", "22C"]
+            .iter()
+            .map(|text| tokenizer.encode(text, false, false).unwrap().len())
+            .sum();
+        assert!(count > text_only);
+    }
+}
+
+#[test]
+fn test_count_chat_tokens_errors() {
+    let tokenizer = tokenizer_with_version("v3");
+    let messages = [ChatMessage::System {
+        content: "Alone".to_string(),
+    }];
+    assert!(tokenizer.count_chat_tokens(&messages).is_err());
+    assert_eq!(tokenizer.count_chat_tokens(&[]).unwrap(), 1);
+}
+
+#[test]
+fn test_system_prompt_placement_per_version() {
+    let build = |tokenizer: &Tekkenizer| {
+        tokenizer
+            .prompt_builder()
+            .system("Be brief.")
+            .user("Hello")
+            .assistant("Hi!")
+            .system("Be kind.")
+            .user("Weather?")
+            .build()
+            .unwrap()
+    };
+
+    let v3 = tokenizer_with_version("v3");
+    let prompt = build(&v3);
+    assert_eq!(
+        prompt.rendered,
+        "<s>[INST]Hello[/INST]Hi!</s>[INST]Be brief.\n\nBe kind.\n\nWeather?[/INST]"
+    );
+    assert_consistent(&v3, &prompt);
+
+    for version in ["v7", "v11", "v13"] {
+        let tokenizer = tokenizer_with_version(version);
+        let prompt = build(&tokenizer);
+        assert_eq!(
+            prompt.rendered,
+            "<s>[SYSTEM_PROMPT]Be brief.[/SYSTEM_PROMPT][INST]Hello[/INST]Hi!</s>\
+             [SYSTEM_PROMPT]Be kind.[/SYSTEM_PROMPT][INST]Weather?[/INST]",
+            "{version}"
+        );
+        assert_consistent(&tokenizer, &prompt);
+    }
+}
+
+#[cfg(feature = "audio")]
+#[test]
+fn test_v3_system_prompt_before_leading_audio() {
+    let tokenizer = tokenizer_with_version("v3");
+    let sampling_rate = tokenizer.audio_config().unwrap().sampling_rate;
+    let audio = Audio::new(
+        ndarray::Array1::zeros(sampling_rate),
+        sampling_rate,
+        "wav".to_string(),
+    );
+
+    let prompt = tokenizer
+        .prompt_builder()
+        .system("Be brief.")
+        .audio(audio)
+        .user("Transcribe.")
+        .build()
+        .unwrap();
+    assert!(
+        prompt
+            .rendered
+            .starts_with("<s>[INST]Be brief.\n\n[BEGIN_AUDIO][AUDIO]x")
+    );
+    assert!(prompt.rendered.ends_with("Transcribe.[/INST]"));
+}
+
+#[test]
+fn test_eos_policy() {
+    let tokenizer = get_tokenizer();
+    for version in [
+        TokenizerVersion::V3,
+        TokenizerVersion::V7,
+        TokenizerVersion::V11,
+        TokenizerVersion::V13,
+    ] {
+        assert_eq!(EosPolicy::for_version(&version), EosPolicy::AfterAssistant);
+    }
+
+    let build = |policy: Option<EosPolicy>| {
+        let mut builder = PromptBuilder::new(tokenizer);
+        if let Some(policy) = policy {
+            builder = builder.eos_policy(policy);
+        }
+        let prompt = builder
+            .user("Hi")
+            .assistant("Hello!")
+            .user("Bye")
+            .assistant("Goodbye!")
+            .user("Wait")
+            .assistant_prefix("Yes")
+            .build()
+            .unwrap();
+        assert_consistent(tokenizer, &prompt);
+        prompt.rendered
+    };
+
+    assert_eq!(
+        build(None),
+        "<s>[INST]Hi[/INST]Hello!</s>[INST]Bye[/INST]Goodbye!</s>[INST]Wait[/INST]Yes"
+    );
+    assert_eq!(build(Some(EosPolicy::AfterAssistant)), build(None));
+    assert_eq!(
+        build(Some(EosPolicy::AfterFinalAssistant)),
+        "<s>[INST]Hi[/INST]Hello![INST]Bye[/INST]Goodbye!</s>[INST]Wait[/INST]Yes"
+    );
+    assert_eq!(
+        build(Some(EosPolicy::Never)),
+        "<s>[INST]Hi[/INST]Hello![INST]Bye[/INST]Goodbye![INST]Wait[/INST]Yes"
+    );
+}
+
+fn long_conversation(tokenizer: &Tekkenizer) -> PromptBuilder<'_> {
+    PromptBuilder::new(tokenizer)
+        .system("Be brief.")
+        .user("First question about the weather in Paris today?")
+        .assistant("It is sunny and warm in Paris today.")
+        .user("Second question about the weather in Madrid today?")
+        .assistant("It is raining in Madrid today.")
+        .user("Third question about the weather in Rome today?")
+        .assistant("It is cloudy in Rome today.")
+        .user("And in Berlin?")
+}
+
+#[test]
+fn test_build_within_fitting_prompt_is_unchanged() {
+    let tokenizer = get_tokenizer();
+    let builder = long_conversation(tokenizer);
+    let full = builder.build().unwrap();
+    let prompt = builder
+        .build_within(full.tokens.len(), &TruncationStrategy::KeepLastTurns)
+        .unwrap();
+    assert_eq!(prompt.encoding, full);
+    assert!(!prompt.is_truncated());
+}
+
+#[test]
+fn test_keep_last_turns() {
+    let tokenizer = get_tokenizer();
+    let builder = long_conversation(tokenizer);
+    let full = builder.build().unwrap();
+
+    let prompt = builder
+        .build_within(full.tokens.len() - 1, &TruncationStrategy::KeepLastTurns)
+        .unwrap();
+    assert_eq!(prompt.dropped_messages, vec![1, 2]);
+    assert!(prompt.trimmed_messages.is_empty());
+    assert!(prompt.encoding.tokens.len() < full.tokens.len());
+    assert!(
+        prompt
+            .encoding
+            .rendered
+            .starts_with("<s>[SYSTEM_PROMPT]Be brief.[/SYSTEM_PROMPT][INST]Second question")
+    );
+    assert_consistent(tokenizer, &prompt.encoding);
+
+    // Only the system prompt and the last turn are left
+    let last = PromptBuilder::new(tokenizer)
+        .system("Be brief.")
+        .user("And in Berlin?")
+        .build()
+        .unwrap();
+    let prompt = builder
+        .build_within(last.tokens.len(), &TruncationStrategy::KeepLastTurns)
+        .unwrap();
+    assert_eq!(prompt.dropped_messages, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(prompt.encoding, last);
+
+    assert!(
+        builder
+            .build_within(last.tokens.len() - 1, &TruncationStrategy::KeepLastTurns)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_drop_middle() {
+    let tokenizer = get_tokenizer();
+    let builder = long_conversation(tokenizer);
+    let full = builder.build().unwrap();
+    let strategy = TruncationStrategy::DropMiddle {
+        ellipsis: "[...] ".to_string(),
+    };
+
+    let prompt = builder
+        .build_within(full.tokens.len() - 1, &strategy)
+        .unwrap();
+    assert_eq!(prompt.dropped_messages, vec![3, 4]);
+    assert!(prompt.encoding.tokens.len() < full.tokens.len());
+    assert_eq!(
+        prompt.encoding.rendered,
+        "<s>[SYSTEM_PROMPT]Be brief.[/SYSTEM_PROMPT]\
+         [INST]First question about the weather in Paris today?[/INST]\
+         It is sunny and warm in Paris today.</s>\
+         [INST][...] Third question about the weather in Rome today?[/INST]\
+         It is cloudy in Rome today.</s>[INST]And in Berlin?[/INST]"
+    );
+    assert_consistent(tokenizer, &prompt.encoding);
+}
+
+#[test]
+fn test_trim_messages() {
+    let tokenizer = get_tokenizer();
+    let builder = long_conversation(tokenizer);
+    let full = builder.build().unwrap();
+
+    let max_tokens = full.tokens.len() - 20;
+    let prompt = builder
+        .build_within(max_tokens, &TruncationStrategy::TrimMessages)
+        .unwrap();
+    assert!(prompt.encoding.tokens.len() <= max_tokens);
+    assert!(prompt.dropped_messages.is_empty());
+    assert_eq!(prompt.trimmed_messages, vec![1, 2, 3, 4, 5, 6, 7]);
+    assert!(
+        prompt
+            .encoding
+            .rendered
+            .starts_with("<s>[SYSTEM_PROMPT]Be brief.[/SYSTEM_PROMPT][INST]First question")
+    );
+    assert_consistent(tokenizer, &prompt.encoding);
+
+    // Multi-byte characters are never split
+    let prompt = PromptBuilder::new(tokenizer)
+        .user("東京は日本の首都です。大阪は日本の第二の都市です。")
+        .build_within(8, &TruncationStrategy::TrimMessages)
+        .unwrap();
+    assert!(prompt.encoding.tokens.len() <= 8);
+    assert_eq!(prompt.trimmed_messages, vec![0]);
+    assert_consistent(tokenizer, &prompt.encoding);
+}
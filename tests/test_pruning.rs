@@ -0,0 +1,80 @@
+use std::sync::OnceLock;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_pruned_tokenizer_round_trips() {
+    let tokenizer = get_tokenizer();
+    let vocab_size = tokenizer.num_special_tokens() + 4096;
+    let pruned = tokenizer.pruned(vocab_size).unwrap();
+
+    assert_eq!(pruned.vocab_size(), vocab_size);
+    assert_eq!(pruned.num_special_tokens(), tokenizer.num_special_tokens());
+    assert_eq!(pruned.bos_id().unwrap(), tokenizer.bos_id().unwrap());
+    assert_eq!(pruned.has_audio_support(), tokenizer.has_audio_support());
+
+    let text = "Pruned vocabularies still encode everything: café 🚀 日本語";
+    let tokens = pruned.encode(text, true, true).unwrap();
+    assert!(tokens.iter().all(|&id| (id as usize) < vocab_size));
+    assert!(tokens.len() >= tokenizer.encode(text, true, true).unwrap().len());
+    assert_eq!(
+        pruned.decode(&tokens, SpecialTokenPolicy::Ignore).unwrap(),
+        text
+    );
+
+    // Tokens kept by the pruned vocabulary keep their IDs
+    assert_eq!(
+        pruned.encode(" the", false, false).unwrap(),
+        tokenizer.encode(" the", false, false).unwrap()
+    );
+}
+
+#[test]
+fn test_save_and_reload() {
+    let tokenizer = get_tokenizer();
+    let pruned = tokenizer
+        .pruned(tokenizer.num_special_tokens() + 1024)
+        .unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tekken-small.json");
+    pruned.save(&path).unwrap();
+    let reloaded = Tekkenizer::from_file(&path).unwrap();
+
+    assert_eq!(reloaded.vocab_size(), pruned.vocab_size());
+    assert_eq!(reloaded.version(), pruned.version());
+    let text = "Saving and reloading keeps the same encoding.";
+    assert_eq!(
+        reloaded.encode(text, true, false).unwrap(),
+        pruned.encode(text, true, false).unwrap()
+    );
+    assert_eq!(
+        reloaded.mergeable_ranks().collect::<Vec<_>>(),
+        pruned.mergeable_ranks().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_invalid_pruning_sizes() {
+    let tokenizer = get_tokenizer();
+    assert!(tokenizer.pruned(tokenizer.vocab_size() + 1).is_err());
+    assert!(
+        tokenizer
+            .pruned(tokenizer.num_special_tokens() + 255)
+            .is_err()
+    );
+    assert!(
+        tokenizer
+            .pruned(tokenizer.num_special_tokens() + 256)
+            .is_ok()
+    );
+}
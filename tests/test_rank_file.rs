@@ -0,0 +1,75 @@
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file");
+        tokenizer
+            .pruned(tokenizer.num_special_tokens() + 5000)
+            .expect("Failed to prune tokenizer")
+    })
+}
+
+#[test]
+fn test_tiktoken_round_trip() {
+    let tokenizer = get_tokenizer();
+    let dir = tempfile::tempdir().unwrap();
+    let ranks_path = dir.path().join("tekken.tiktoken");
+    let sidecar_path = dir.path().join("tekken.sidecar.json");
+    tokenizer.save_tiktoken(&ranks_path, &sidecar_path).unwrap();
+
+    let ranks = std::fs::read_to_string(&ranks_path).unwrap();
+    assert_eq!(ranks.lines().count(), 5000);
+    assert_eq!(ranks.lines().next(), Some("AA== 0"));
+    assert_eq!(ranks.lines().nth(65), Some("QQ== 65"));
+
+    let reloaded = Tekkenizer::from_tiktoken(&ranks_path, &sidecar_path).unwrap();
+    assert_eq!(reloaded.fingerprint(), tokenizer.fingerprint());
+    assert_eq!(
+        reloaded.encode("Hello, world!", true, true).unwrap(),
+        tokenizer.encode("Hello, world!", true, true).unwrap()
+    );
+}
+
+#[test]
+fn test_tiktoken_lines_in_any_order() {
+    let tokenizer = get_tokenizer();
+    let mut buffer = Vec::new();
+    tokenizer.write_tiktoken(&mut buffer).unwrap();
+    let ranks = String::from_utf8(buffer).unwrap();
+    let shuffled: String = ranks
+        .lines()
+        .rev()
+        .map(|line| format!("{line}\n\n"))
+        .collect();
+
+    let reloaded = Tekkenizer::from_tiktoken_str(&shuffled, tokenizer.tiktoken_sidecar()).unwrap();
+    assert_eq!(reloaded.fingerprint(), tokenizer.fingerprint());
+}
+
+#[test]
+fn test_invalid_tiktoken_files() {
+    let tokenizer = get_tokenizer();
+    let mut buffer = Vec::new();
+    tokenizer.write_tiktoken(&mut buffer).unwrap();
+    let ranks = String::from_utf8(buffer).unwrap();
+
+    let malformed = format!("{ranks}QUJD\n");
+    let error = Tekkenizer::from_tiktoken_str(&malformed, tokenizer.tiktoken_sidecar())
+        .err()
+        .unwrap();
+    assert!(error.to_string().contains("line 5001"));
+
+    let repeated = format!("{ranks}QUJD 4999\n");
+    assert!(Tekkenizer::from_tiktoken_str(&repeated, tokenizer.tiktoken_sidecar()).is_err());
+
+    let gap: String = ranks
+        .lines()
+        .skip(1)
+        .map(|line| format!("{line}\n"))
+        .collect();
+    assert!(Tekkenizer::from_tiktoken_str(&gap, tokenizer.tiktoken_sidecar()).is_err());
+}
@@ -0,0 +1,77 @@
+use std::sync::OnceLock;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_raw_bpe_encodes_like_the_tokenizer() {
+    let tokenizer = get_tokenizer();
+    let bpe = tokenizer.raw_bpe();
+    assert_eq!(
+        bpe.len(),
+        tokenizer.vocab_size() - tokenizer.num_special_tokens()
+    );
+    assert_eq!(bpe.pattern(), tokenizer.pattern());
+
+    let text = "Hello, world! Tokenization with raw ranks.";
+    let offset = tokenizer.num_special_tokens() as u32;
+    let mut ids = Vec::new();
+    for piece in bpe.pieces(text).unwrap() {
+        ids.extend(
+            bpe.encode_piece(&text.as_bytes()[piece])
+                .into_iter()
+                .map(|rank| rank + offset),
+        );
+    }
+    assert_eq!(ids, tokenizer.encode(text, false, false).unwrap());
+}
+
+#[test]
+fn test_raw_bpe_lookups() {
+    let tokenizer = get_tokenizer();
+    let bpe = tokenizer.raw_bpe();
+
+    let rank = bpe.rank(b"Hello").unwrap();
+    assert_eq!(Some(rank), tokenizer.rank_of(b"Hello"));
+    assert_eq!(bpe.token_bytes(rank), Some(&b"Hello"[..]));
+    assert_eq!(bpe.token_bytes(bpe.len() as u32), None);
+    assert!(!bpe.is_placeholder(rank));
+
+    assert_eq!(bpe.merge_rank(b"Hel", b"lo"), Some(rank));
+    assert_eq!(bpe.merge_rank(b"\xff", b"\xfe\xfd"), None);
+}
+
+#[test]
+fn test_raw_bpe_merges_build_the_encoding() {
+    let tokenizer = get_tokenizer();
+    let bpe = tokenizer.raw_bpe();
+    let piece = "Tokenization".as_bytes();
+
+    // Replay the merges over single bytes and compare with the encoding
+    let mut parts: Vec<std::ops::Range<usize>> = (0..piece.len()).map(|i| i..i + 1).collect();
+    for (left, right) in bpe.merges(piece) {
+        assert!(
+            bpe.merge_rank(&piece[left.clone()], &piece[right.clone()])
+                .is_some()
+        );
+        let index = parts.iter().position(|part| *part == left).unwrap();
+        assert_eq!(parts[index + 1], right);
+        parts[index] = left.start..right.end;
+        parts.remove(index + 1);
+    }
+    let ranks: Vec<u32> = parts
+        .into_iter()
+        .map(|part| bpe.rank(&piece[part]).unwrap())
+        .collect();
+    assert_eq!(ranks, bpe.encode_piece(piece));
+
+    // Whole tokens need no merges
+    assert!(bpe.merges(b"Hello").is_empty());
+}
@@ -0,0 +1,65 @@
+use std::sync::OnceLock;
+#[cfg(feature = "audio")]
+use tekken::audio::{Audio, AudioEncoding};
+use tekken::encoding::{Encoding, EncodingOptions};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_encoding_round_trip() {
+    let tokenizer = get_tokenizer();
+    let encoding = tokenizer
+        .encode_plus("Hello, world!", &EncodingOptions::new(true, true))
+        .unwrap();
+
+    let json = serde_json::to_string(&encoding).unwrap();
+    let restored: Encoding = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, encoding);
+}
+
+#[cfg(feature = "audio")]
+#[test]
+fn test_audio_samples_serialize_as_base64() {
+    let audio = Audio::new(vec![0.0, 1.0, -0.5].into(), 16000, "wav".to_string());
+
+    let value = serde_json::to_value(&audio).unwrap();
+    assert_eq!(value["audio_array"], "AAAAAAAAgD8AAAC/");
+    assert_eq!(value["sampling_rate"], 16000);
+
+    let restored: Audio = serde_json::from_value(value).unwrap();
+    assert_eq!(restored.audio_array, audio.audio_array);
+    assert_eq!(restored.format, "wav");
+}
+
+#[cfg(feature = "audio")]
+#[test]
+fn test_audio_samples_reject_truncated_bytes() {
+    let json = r#"{"audio_array":"AAAAAA==","sampling_rate":16000,"format":"wav"}"#;
+    assert!(serde_json::from_str::<Audio>(json).is_ok());
+
+    let json = r#"{"audio_array":"AAA=","sampling_rate":16000,"format":"wav"}"#;
+    assert!(serde_json::from_str::<Audio>(json).is_err());
+}
+
+#[cfg(feature = "audio")]
+#[test]
+fn test_audio_encoding_round_trip() {
+    let tokenizer = get_tokenizer();
+    let audio = Audio::from_file("tests/assets/jfk.wav").unwrap();
+    let encoding = tokenizer.encode_audio(audio).unwrap();
+
+    let json = serde_json::to_string(&encoding).unwrap();
+    let restored: AudioEncoding = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.tokens, encoding.tokens);
+    assert_eq!(restored.audio.audio_array, encoding.audio.audio_array);
+    assert_eq!(restored.audio.sampling_rate, encoding.audio.sampling_rate);
+    assert_eq!(restored.token_time_ranges, encoding.token_time_ranges);
+}
@@ -0,0 +1,67 @@
+use tekken::audio::{AudioConfig, AudioSpectrogramConfig};
+use tekken::config::{ModelData, TokenizerVersion};
+use tekken::tekkenizer::Tekkenizer;
+
+fn small_tokenizer(version: TokenizerVersion) -> Tekkenizer {
+    let model_data = ModelData::builder(r"\p{L}+|\s+|.", version)
+        .with_byte_tokens()
+        .with_tokens(["he", "ll", "hello"])
+        .with_num_special_tokens(100)
+        .build()
+        .unwrap();
+    Tekkenizer::from_bytes(&serde_json::to_vec(&model_data).unwrap()).unwrap()
+}
+
+fn audio_config(frame_rate: f64) -> AudioConfig {
+    let spectrogram_config = AudioSpectrogramConfig::new(128, 160, 400).unwrap();
+    AudioConfig::new(16000, frame_rate, spectrogram_config, None).unwrap()
+}
+
+#[test]
+fn test_enable_audio_on_text_only_tokenizer() {
+    let mut tokenizer = small_tokenizer(TokenizerVersion::V7);
+    assert!(tokenizer.audio_config().is_none());
+    assert!(!tokenizer.has_audio_support());
+    let fingerprint = tokenizer.fingerprint();
+
+    tokenizer.set_audio_config(audio_config(12.5)).unwrap();
+
+    assert_eq!(tokenizer.audio_config().unwrap().frame_rate, 12.5);
+    assert_eq!(tokenizer.has_audio_support(), cfg!(feature = "audio"));
+    assert_ne!(tokenizer.fingerprint(), fingerprint);
+}
+
+#[test]
+fn test_missing_audio_tokens_leave_tokenizer_unchanged() {
+    let tokenizer = small_tokenizer(TokenizerVersion::V3);
+    let fingerprint = tokenizer.fingerprint();
+
+    let mut changed = small_tokenizer(TokenizerVersion::V3);
+    assert!(matches!(
+        changed.set_audio_config(audio_config(12.5)),
+        Err(tekken::TokenizerError::TokenNotFound(_))
+    ));
+    assert!(changed.audio_config().is_none());
+    assert_eq!(changed.fingerprint(), fingerprint);
+
+    assert!(tokenizer.with_audio_config(audio_config(12.5)).is_err());
+}
+
+#[test]
+#[cfg(feature = "audio")]
+fn test_override_frame_rate() {
+    use tekken::audio::Audio;
+
+    let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    let audio = Audio::new(ndarray::Array1::zeros(32000), 16000, "wav".to_string());
+    let default_tokens = tokenizer.encode_audio(audio.clone()).unwrap().tokens;
+
+    let mut config = tokenizer.audio_config().unwrap().clone();
+    config.frame_rate *= 2.0;
+    let tokenizer = tokenizer.with_audio_config(config).unwrap();
+    let tokens = tokenizer.encode_audio(audio).unwrap().tokens;
+
+    // Twice the frame rate, twice the [AUDIO] tokens after [BEGIN_AUDIO]
+    assert_eq!(tokens[0], default_tokens[0]);
+    assert_eq!(tokens.len() - 1, 2 * (default_tokens.len() - 1));
+}
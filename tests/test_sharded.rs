@@ -0,0 +1,85 @@
+use std::path::Path;
+use std::sync::OnceLock;
+use tekken::config::ShardedIndex;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file");
+        tokenizer
+            .pruned(tokenizer.num_special_tokens() + 5000)
+            .expect("Failed to prune tokenizer")
+    })
+}
+
+fn read_index(path: &Path) -> ShardedIndex {
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+fn write_index(path: &Path, index: &ShardedIndex) {
+    std::fs::write(path, serde_json::to_vec(index).unwrap()).unwrap();
+}
+
+#[test]
+fn test_sharded_round_trip() {
+    let tokenizer = get_tokenizer();
+    let dir = tempfile::tempdir().unwrap();
+    let index_path = dir.path().join("tekken.index.json");
+    tokenizer.save_sharded(&index_path, 1000).unwrap();
+
+    let index = read_index(&index_path);
+    assert_eq!(index.vocab_shards.len(), 5);
+    assert_eq!(index.vocab_shards[0], "tekken-vocab-00001-of-00005.json");
+
+    let reloaded = Tekkenizer::from_sharded(&index_path).unwrap();
+    assert_eq!(reloaded.fingerprint(), tokenizer.fingerprint());
+}
+
+#[test]
+fn test_shard_order_does_not_matter() {
+    let tokenizer = get_tokenizer();
+    let dir = tempfile::tempdir().unwrap();
+    let index_path = dir.path().join("tekken.index.json");
+    tokenizer.save_sharded(&index_path, 1500).unwrap();
+
+    let mut index = read_index(&index_path);
+    index.vocab_shards.reverse();
+    write_index(&index_path, &index);
+
+    let reloaded = Tekkenizer::from_sharded(&index_path).unwrap();
+    assert_eq!(reloaded.fingerprint(), tokenizer.fingerprint());
+}
+
+#[test]
+fn test_invalid_shard_layouts() {
+    let tokenizer = get_tokenizer();
+    let dir = tempfile::tempdir().unwrap();
+    let index_path = dir.path().join("tekken.index.json");
+    tokenizer.save_sharded(&index_path, 2000).unwrap();
+    let index = read_index(&index_path);
+
+    // A shard listed twice defines its ranks twice
+    let mut duplicated = index.clone();
+    duplicated
+        .vocab_shards
+        .push(duplicated.vocab_shards[0].clone());
+    write_index(&index_path, &duplicated);
+    let error = Tekkenizer::from_sharded(&index_path).err().unwrap();
+    assert!(error.to_string().contains("more than one shard"));
+
+    // A missing middle shard leaves a gap in the ranks
+    let mut gap = index.clone();
+    gap.vocab_shards.remove(1);
+    write_index(&index_path, &gap);
+    assert!(Tekkenizer::from_sharded(&index_path).is_err());
+
+    let mut missing = index;
+    missing.vocab_shards.push("does-not-exist.json".to_string());
+    write_index(&index_path, &missing);
+    assert!(Tekkenizer::from_sharded(&index_path).is_err());
+
+    assert!(tokenizer.save_sharded(&index_path, 0).is_err());
+}
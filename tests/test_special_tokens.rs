@@ -0,0 +1,104 @@
+use std::sync::OnceLock;
+use tekken::config::{ModelData, TokenizerVersion};
+use tekken::special_tokens::{SpecialTokenInfo, SpecialTokens};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_from_str_round_trips_all() {
+    assert_eq!(SpecialTokens::all().len(), 27);
+    for &token in SpecialTokens::all() {
+        assert_eq!(token.as_str().parse::<SpecialTokens>().unwrap(), token);
+        assert_eq!(token.to_string(), token.as_str());
+    }
+    assert_eq!(
+        "[INST]".parse::<SpecialTokens>().unwrap(),
+        SpecialTokens::BeginInst
+    );
+    assert!("[NOT_A_TOKEN]".parse::<SpecialTokens>().is_err());
+}
+
+#[test]
+fn test_id_of() {
+    let tokenizer = get_tokenizer();
+    assert_eq!(tokenizer.id_of(SpecialTokens::Bos).unwrap(), 1);
+    assert_eq!(tokenizer.id_of(SpecialTokens::Eos).unwrap(), 2);
+    for &token in SpecialTokens::all() {
+        if let Ok(id) = tokenizer.id_of(token) {
+            assert_eq!(id, tokenizer.get_control_token(token.as_str()).unwrap());
+            assert!(tokenizer.is_special_token(id));
+        }
+    }
+}
+
+#[test]
+fn test_get_control_token_resolves_every_special_token() {
+    let tokenizer = get_tokenizer();
+    let vocab = tokenizer.vocab();
+    for (id, token_str) in vocab[..tokenizer.num_special_tokens()].iter().enumerate() {
+        assert_eq!(
+            tokenizer.get_control_token(token_str).unwrap() as usize,
+            id,
+            "{token_str}"
+        );
+    }
+
+    let error = tokenizer.get_control_token("[NOT_A_TOKEN]").unwrap_err();
+    assert!(error.to_string().contains("Available special tokens"));
+    assert!(tokenizer.get_control_token("[inst]").is_err());
+    assert!(tokenizer.get_control_token("<SPECIAL_1000>").is_err());
+}
+
+fn load_with_special_tokens(special_tokens: Vec<SpecialTokenInfo>) -> tekken::Result<Tekkenizer> {
+    let model_data = ModelData::builder(r"\p{L}+|\s+|.", TokenizerVersion::V7)
+        .with_byte_tokens()
+        .with_num_special_tokens(10)
+        .with_special_tokens(Vec::new())
+        .build()
+        .unwrap();
+    let mut json = serde_json::to_value(model_data).unwrap();
+    json["special_tokens"] = serde_json::to_value(special_tokens).unwrap();
+    Tekkenizer::from_bytes(&serde_json::to_vec(&json).unwrap())
+}
+
+fn special(rank: usize, token_str: &str) -> SpecialTokenInfo {
+    SpecialTokenInfo {
+        rank,
+        token_str: token_str.to_string(),
+        is_control: true,
+    }
+}
+
+#[test]
+fn test_special_token_rank_validation() {
+    let tokenizer = load_with_special_tokens(vec![special(0, "<unk>"), special(1, "<s>")]).unwrap();
+    assert_eq!(tokenizer.bos_id().unwrap(), 1);
+    assert_eq!(tokenizer.get_control_token("<SPECIAL_5>").unwrap(), 5);
+    assert!(load_with_special_tokens(vec![special(3, "<SPECIAL_3>")]).is_ok());
+
+    let duplicate_rank = load_with_special_tokens(vec![special(1, "<s>"), special(1, "</s>")]);
+    assert!(
+        duplicate_rank
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("share rank 1")
+    );
+
+    let duplicate_string = load_with_special_tokens(vec![special(1, "<s>"), special(2, "<s>")]);
+    assert!(duplicate_string.is_err());
+
+    let out_of_range = load_with_special_tokens(vec![special(10, "<s>")]);
+    assert!(out_of_range.is_err());
+
+    let collision = load_with_special_tokens(vec![special(1, "<SPECIAL_5>")]);
+    assert!(collision.err().unwrap().to_string().contains("placeholder"));
+}
@@ -0,0 +1,98 @@
+use std::sync::OnceLock;
+use tekken::stop_sequences::{StopMatch, StopSequenceMatcher};
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+fn first_match(matcher: &mut StopSequenceMatcher, tokens: &[u32]) -> Option<(usize, StopMatch)> {
+    for (i, &token) in tokens.iter().enumerate() {
+        if let Some(stop) = matcher.push(token).unwrap() {
+            return Some((i, stop));
+        }
+    }
+    None
+}
+
+#[test]
+fn test_stop_sequence_across_tokens() {
+    let tokenizer = get_tokenizer();
+    let text = "Sure, here it is.\n\nUser: thanks";
+    let tokens = tokenizer.encode(text, false, false).unwrap();
+    let mut matcher = StopSequenceMatcher::new(tokenizer, &["\n\nUser:"]).unwrap();
+
+    let (index, stop) = first_match(&mut matcher, &tokens).expect("stop sequence not found");
+    assert_eq!(stop.sequence_index, 0);
+
+    let generated = &tokens[..=index];
+    let generated_text = tokenizer
+        .decode(generated, tekken::SpecialTokenPolicy::Keep)
+        .unwrap();
+    let kept = &generated_text[..generated_text.len() - stop.trim_bytes];
+    assert_eq!(kept, "Sure, here it is.");
+
+    let kept_tokens = &generated[..generated.len() - stop.trim_tokens];
+    let kept_token_text = tokenizer
+        .decode(kept_tokens, tekken::SpecialTokenPolicy::Keep)
+        .unwrap();
+    assert!(kept.starts_with(&kept_token_text));
+}
+
+#[test]
+fn test_stop_sequence_with_byte_tokens() {
+    let tokenizer = get_tokenizer();
+    let mut matcher = StopSequenceMatcher::new(tokenizer, &["END", "🚀"]).unwrap();
+
+    // Feed the rocket one byte token at a time
+    let tokens: Vec<u32> = "go 🚀"
+        .bytes()
+        .map(|byte| tokenizer.byte_to_token_id(byte))
+        .collect();
+    let (index, stop) = first_match(&mut matcher, &tokens).unwrap();
+    assert_eq!(index, tokens.len() - 1);
+    assert_eq!(stop.sequence_index, 1);
+    assert_eq!(stop.trim_tokens, 4);
+    assert_eq!(stop.trim_bytes, 4);
+}
+
+#[test]
+fn test_held_bytes_and_special_tokens() {
+    let tokenizer = get_tokenizer();
+    let mut matcher = StopSequenceMatcher::new(tokenizer, &["ab"]).unwrap();
+
+    assert_eq!(
+        matcher.push(tokenizer.byte_to_token_id(b'x')).unwrap(),
+        None
+    );
+    assert_eq!(matcher.held_bytes(), 0);
+    assert_eq!(
+        matcher.push(tokenizer.byte_to_token_id(b'a')).unwrap(),
+        None
+    );
+    assert_eq!(matcher.held_bytes(), 1);
+
+    // A special token breaks a partial match
+    matcher.push(tokenizer.eos_id().unwrap()).unwrap();
+    assert_eq!(matcher.held_bytes(), 0);
+    assert_eq!(
+        matcher.push(tokenizer.byte_to_token_id(b'b')).unwrap(),
+        None
+    );
+
+    matcher.reset();
+    assert!(matcher.push(u32::MAX).is_err());
+}
+
+#[test]
+fn test_invalid_stop_sequences() {
+    let tokenizer = get_tokenizer();
+    let none: [&str; 0] = [];
+    assert!(StopSequenceMatcher::new(tokenizer, &none).is_err());
+    assert!(StopSequenceMatcher::new(tokenizer, &["ok", ""]).is_err());
+}
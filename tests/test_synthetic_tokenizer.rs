@@ -0,0 +1,67 @@
+use tekken::config::TokenizerVersion;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::test_utils::synthetic_tokenizer;
+
+const SAMPLE: &str = "The quick brown fox jumps over the lazy dog.\n\nfn main() {\n    println!(\"héllo wörld\");\n}\n東京 🚀 12345";
+
+#[test]
+fn test_synthetic_tokenizer_shape() {
+    let tokenizer = synthetic_tokenizer(7, 20_000);
+    assert_eq!(*tokenizer.version(), TokenizerVersion::V7);
+    assert_eq!(tokenizer.vocab_size(), 20_000);
+    assert_eq!(tokenizer.num_special_tokens(), 1000);
+    assert_eq!(tokenizer.bos_id().unwrap(), 1);
+}
+
+#[test]
+fn test_synthetic_tokenizer_is_deterministic() {
+    let first = synthetic_tokenizer(7, 5_000);
+    assert_eq!(
+        first.fingerprint(),
+        synthetic_tokenizer(7, 5_000).fingerprint()
+    );
+    assert_ne!(
+        first.fingerprint(),
+        synthetic_tokenizer(8, 5_000).fingerprint()
+    );
+    assert_eq!(
+        first.encode(SAMPLE, false, false).unwrap(),
+        synthetic_tokenizer(7, 5_000)
+            .encode(SAMPLE, false, false)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_synthetic_tokenizer_round_trip() {
+    let tokenizer = synthetic_tokenizer(1, 50_000);
+    let tokens = tokenizer.encode(SAMPLE, true, true).unwrap();
+    assert!(tokens.len() < SAMPLE.len());
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, SpecialTokenPolicy::Ignore)
+            .unwrap(),
+        SAMPLE
+    );
+}
+
+#[test]
+fn test_synthetic_merges_are_reachable() {
+    // Most merged tokens encode to themselves, as in a trained vocabulary
+    let tokenizer = synthetic_tokenizer(3, 10_000);
+    let merged = 1000 + 256..10_000u32;
+    let reachable = merged
+        .clone()
+        .filter(|&id| {
+            let text = tokenizer.id_to_piece(id).unwrap();
+            tokenizer.encode(&text, false, false).unwrap() == [id]
+        })
+        .count();
+    assert!(reachable * 10 >= merged.len() * 9, "{reachable}");
+}
+
+#[test]
+#[should_panic(expected = "at least 1256")]
+fn test_synthetic_tokenizer_needs_byte_tokens() {
+    let _ = synthetic_tokenizer(0, 1000);
+}
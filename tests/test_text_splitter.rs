@@ -0,0 +1,117 @@
+use std::sync::OnceLock;
+use tekken::splitter::TextSplitter;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+fn document() -> String {
+    let paragraph = "Tokenizers turn text into integers. Models only see those integers! \
+                     Chunking by characters is a rough guess at best.";
+    (0..8)
+        .map(|i| format!("Paragraph {i}. {paragraph}"))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[test]
+fn test_chunks_fit_and_cover_input() {
+    let tokenizer = get_tokenizer();
+    let text = document();
+    let splitter = TextSplitter::new(tokenizer, 40).unwrap();
+    let chunks = splitter.split(&text).unwrap();
+
+    assert!(chunks.len() > 1);
+    let mut offset = 0;
+    for chunk in &chunks {
+        assert_eq!(chunk.byte_range.start, offset);
+        offset = chunk.byte_range.end;
+        assert!(chunk.token_count <= 40);
+        assert_eq!(
+            chunk.token_count,
+            tokenizer.encode(chunk.text, false, false).unwrap().len()
+        );
+    }
+    assert_eq!(offset, text.len());
+    let joined: String = chunks.iter().map(|c| c.text).collect();
+    assert_eq!(joined, text);
+}
+
+#[test]
+fn test_prefers_sentence_and_paragraph_boundaries() {
+    let tokenizer = get_tokenizer();
+    let text = document();
+    let chunks = TextSplitter::new(tokenizer, 40)
+        .unwrap()
+        .split(&text)
+        .unwrap();
+
+    for chunk in &chunks[..chunks.len() - 1] {
+        let trimmed = chunk.text.trim_end();
+        assert!(
+            trimmed.ends_with('.') || trimmed.ends_with('!'),
+            "chunk does not end at a sentence: {:?}",
+            chunk.text
+        );
+    }
+
+    // A whole paragraph fits, so every chunk is exactly one paragraph
+    let paragraphs = TextSplitter::new(tokenizer, 1000)
+        .unwrap()
+        .split("First paragraph.\n\nSecond paragraph.")
+        .unwrap();
+    assert_eq!(paragraphs.len(), 1);
+}
+
+#[test]
+fn test_overlap() {
+    let tokenizer = get_tokenizer();
+    let text = document();
+    let chunks = TextSplitter::new(tokenizer, 40)
+        .unwrap()
+        .with_overlap(20)
+        .unwrap()
+        .split(&text)
+        .unwrap();
+
+    for pair in chunks.windows(2) {
+        assert!(pair[1].byte_range.start < pair[0].byte_range.end);
+        assert!(pair[1].byte_range.start > pair[0].byte_range.start);
+        assert!(pair[1].token_count <= 40);
+    }
+    assert_eq!(chunks.last().unwrap().byte_range.end, text.len());
+}
+
+#[test]
+fn test_long_words_and_invalid_config() {
+    let tokenizer = get_tokenizer();
+    let word = "🚀".repeat(50);
+    let chunks = TextSplitter::new(tokenizer, 7)
+        .unwrap()
+        .split(&word)
+        .unwrap();
+    assert!(chunks.len() > 1);
+    assert!(chunks.iter().all(|chunk| chunk.token_count <= 7));
+    assert_eq!(chunks.iter().map(|c| c.text).collect::<String>(), word);
+
+    assert!(TextSplitter::new(tokenizer, 0).is_err());
+    assert!(
+        TextSplitter::new(tokenizer, 10)
+            .unwrap()
+            .with_overlap(10)
+            .is_err()
+    );
+    assert!(
+        TextSplitter::new(tokenizer, 10)
+            .unwrap()
+            .split("")
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,167 @@
+//! Runs with and without `std`: `cargo test --no-default-features --test test_text_tokenizer`
+//! checks the `no_std` pretokenizer against token IDs from `Tekkenizer`.
+
+use std::sync::OnceLock;
+
+use base64::Engine;
+use tekken::TokenizerError;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::text::TextTokenizer;
+
+static TOKENIZER: OnceLock<TextTokenizer> = OnceLock::new();
+
+/// Builds the text tokenizer of `tests/assets/tekken.json` the way an embedded
+/// application would, from token bytes and special token strings.
+fn get_tokenizer() -> &'static TextTokenizer {
+    TOKENIZER.get_or_init(|| {
+        let data: serde_json::Value =
+            serde_json::from_slice(&std::fs::read("tests/assets/tekken.json").unwrap()).unwrap();
+        let config = &data["config"];
+        let num_special_tokens = config["default_num_special_tokens"].as_u64().unwrap() as usize;
+        let vocab_size = config["default_vocab_size"].as_u64().unwrap() as usize;
+
+        let vocab = data["vocab"].as_array().unwrap()[..vocab_size - num_special_tokens]
+            .iter()
+            .map(|token| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(token["token_bytes"].as_str().unwrap())
+                    .unwrap()
+            })
+            .collect();
+        let mut special_tokens: Vec<String> = (0..num_special_tokens)
+            .map(|rank| format!("<SPECIAL_{rank}>"))
+            .collect();
+        for token in data["special_tokens"].as_array().unwrap() {
+            special_tokens[token["rank"].as_u64().unwrap() as usize] =
+                token["token_str"].as_str().unwrap().to_string();
+        }
+
+        TextTokenizer::new(vocab, special_tokens, config["pattern"].as_str().unwrap()).unwrap()
+    })
+}
+
+const SAMPLES: [(&str, &[u32]); 4] = [
+    ("Hello, world!", &[22177, 1044, 4304, 1033]),
+    (
+        "fn main() {\n    let x = 1;\n}\n",
+        &[
+            28117, 2830, 1690, 1512, 1293, 2878, 2460, 1376, 1032, 1049, 1365, 2002,
+        ],
+    ),
+    (
+        "Café   au lait\t\tи 東京 🚀",
+        &[
+            1067, 4032, 1337, 1256, 1817, 74724, 1009, 1009, 1347, 48798, 119685, 1154, 1128,
+        ],
+    ),
+    (
+        "   leading and trailing   ",
+        &[1256, 8924, 1321, 49875, 1293],
+    ),
+];
+
+#[test]
+fn test_encode_matches_known_tokens() {
+    let tokenizer = get_tokenizer();
+    assert_eq!(tokenizer.vocab_size(), 131_072);
+    assert_eq!(tokenizer.num_special_tokens(), 1000);
+
+    for (text, expected) in SAMPLES {
+        assert_eq!(
+            tokenizer.encode(text, false, false).unwrap(),
+            expected,
+            "{text:?}"
+        );
+        assert_eq!(
+            tokenizer
+                .decode(expected, SpecialTokenPolicy::Raise)
+                .unwrap(),
+            text
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_matches_tekkenizer() {
+    let tekkenizer = tekken::Tekkenizer::from_file("tests/assets/tekken.json").unwrap();
+    let tokenizer = get_tokenizer();
+    assert_eq!(tokenizer.pattern(), tekkenizer.pattern());
+
+    let readme = std::fs::read_to_string("README.md").unwrap();
+    for text in readme.split("\n\n").chain(SAMPLES.map(|(text, _)| text)) {
+        assert_eq!(
+            tokenizer.encode(text, true, true).unwrap(),
+            tekkenizer.encode(text, true, true).unwrap(),
+            "{text:?}"
+        );
+    }
+}
+
+#[test]
+fn test_special_tokens() {
+    let tokenizer = get_tokenizer();
+    let tokens = tokenizer.encode("Hello", true, true).unwrap();
+    assert_eq!(tokens, [1, 22177, 2]);
+    assert!(tokenizer.is_special_token(1));
+    assert!(!tokenizer.is_special_token(22177));
+
+    assert_eq!(
+        tokenizer.decode(&tokens, SpecialTokenPolicy::Keep).unwrap(),
+        "<s>Hello</s>"
+    );
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, SpecialTokenPolicy::Ignore)
+            .unwrap(),
+        "Hello"
+    );
+    assert!(matches!(
+        tokenizer.decode(&tokens, SpecialTokenPolicy::Raise),
+        Err(TokenizerError::SpecialTokenPolicy(_))
+    ));
+}
+
+#[test]
+fn test_decode_errors() {
+    let tokenizer = get_tokenizer();
+    assert!(matches!(
+        tokenizer.decode(&[131_072], SpecialTokenPolicy::Keep),
+        Err(TokenizerError::TokenOutOfRange {
+            token_id: 131_072,
+            vocab_size: 131_072
+        })
+    ));
+    // A lone UTF-8 lead byte
+    assert!(
+        tokenizer
+            .decode(&[1000 + 0xC3], SpecialTokenPolicy::Keep)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_invalid_vocabularies() {
+    let bytes = || (0..=u8::MAX).map(|byte| vec![byte]);
+    let special_tokens = || vec!["<unk>".to_string(), "<s>".to_string()];
+
+    assert!(TextTokenizer::new(bytes().collect(), special_tokens(), "").is_ok());
+    assert!(TextTokenizer::new(bytes().take(200).collect(), special_tokens(), "").is_err());
+    assert!(TextTokenizer::new(bytes().rev().collect(), special_tokens(), "").is_err());
+    assert!(
+        TextTokenizer::new(
+            bytes().chain([b"ab".to_vec(), b"ab".to_vec()]).collect(),
+            special_tokens(),
+            ""
+        )
+        .is_err()
+    );
+    assert!(TextTokenizer::new(bytes().collect(), vec!["<s>".to_string(); 2], "").is_err());
+    assert!(TextTokenizer::new(bytes().collect(), special_tokens(), "(").is_err());
+
+    let tokenizer = TextTokenizer::new(bytes().collect(), special_tokens(), "").unwrap();
+    assert!(matches!(
+        tokenizer.encode("Hi", false, true),
+        Err(TokenizerError::TokenNotFound(_))
+    ));
+}
@@ -0,0 +1,51 @@
+#[cfg(feature = "audio")]
+use tekken::audio::Audio;
+use tekken::config::TokenizerVersion;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+#[test]
+fn test_tiny_tokenizer_shape() {
+    let tokenizer = Tekkenizer::tiny_for_tests();
+    assert_eq!(*tokenizer.version(), TokenizerVersion::V7);
+    assert_eq!(tokenizer.num_special_tokens(), 100);
+    assert_eq!(tokenizer.vocab_size(), 100 + 256 + 9);
+    assert_eq!(tokenizer.bos_id().unwrap(), 1);
+    assert_eq!(tokenizer.eos_id().unwrap(), 2);
+    assert!(tokenizer.audio_config().is_some());
+    assert_eq!(tokenizer.has_audio_support(), cfg!(feature = "audio"));
+}
+
+#[test]
+fn test_tiny_tokenizer_is_deterministic() {
+    assert_eq!(
+        Tekkenizer::tiny_for_tests().fingerprint(),
+        Tekkenizer::tiny_for_tests().fingerprint()
+    );
+}
+
+#[test]
+fn test_tiny_tokenizer_round_trip() {
+    let tokenizer = Tekkenizer::tiny_for_tests();
+    let tokens = tokenizer.encode("hello world", true, true).unwrap();
+    assert_eq!(tokens, vec![1, 359, 364, 2]);
+
+    let text = "héllo, wörld! 123";
+    let tokens = tokenizer.encode(text, false, false).unwrap();
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, SpecialTokenPolicy::Raise)
+            .unwrap(),
+        text
+    );
+}
+
+#[cfg(feature = "audio")]
+#[test]
+fn test_tiny_tokenizer_encodes_audio() {
+    let tokenizer = Tekkenizer::tiny_for_tests();
+    let audio = Audio::new(vec![0.0; 16_000].into(), 16_000, "wav".to_string());
+    let encoding = tokenizer.encode_audio(audio).unwrap();
+    assert_eq!(encoding.tokens.first(), Some(&25));
+    assert!(encoding.tokens[1..].iter().all(|&token| token == 24));
+}
@@ -0,0 +1,98 @@
+use std::sync::OnceLock;
+use tekken::TokenId;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+#[test]
+fn test_rank_shift_round_trips() {
+    let id = TokenId::checked_from_rank(5, 1000).unwrap();
+    assert_eq!(id.get(), 1005);
+    assert_eq!(id.index(), 1005);
+    assert_eq!(id.to_rank(1000), Some(5));
+    assert!(!id.is_special(1000));
+
+    let special = TokenId::new(999);
+    assert_eq!(special.to_rank(1000), None);
+    assert!(special.is_special(1000));
+}
+
+#[test]
+fn test_checked_constructors() {
+    assert_eq!(TokenId::checked_from_rank(u32::MAX, 1), None);
+    assert_eq!(
+        TokenId::checked_from_rank(u32::MAX - 1, 1),
+        Some(TokenId::new(u32::MAX))
+    );
+    assert_eq!(TokenId::try_from(1005_usize).unwrap(), TokenId::new(1005));
+    assert_eq!(TokenId::new(5).to_rank(1000), None);
+
+    #[cfg(target_pointer_width = "64")]
+    {
+        assert_eq!(TokenId::checked_from_rank(0, 1 << 32), None);
+        assert!(TokenId::try_from(1_usize << 32).is_err());
+        assert_eq!(TokenId::new(u32::MAX).to_rank(1 << 32), None);
+    }
+}
+
+#[test]
+fn test_conversions_and_serde_are_transparent() {
+    let id = TokenId::from(22177);
+    assert_eq!(u32::from(id), 22177);
+    assert_eq!(id.to_string(), "22177");
+    assert_eq!(serde_json::to_string(&id).unwrap(), "22177");
+    assert_eq!(serde_json::from_str::<TokenId>("22177").unwrap(), id);
+}
+
+#[test]
+fn test_matches_tokenizer_shift() {
+    let tokenizer = get_tokenizer();
+    let num_special = tokenizer.num_special_tokens();
+
+    let hello = tokenizer.encode("Hello", false, false).unwrap()[0];
+    let rank = tokenizer.rank_of(b"Hello").unwrap();
+    assert_eq!(
+        TokenId::checked_from_rank(rank, num_special).unwrap().get(),
+        hello
+    );
+    assert_eq!(TokenId::new(hello).to_rank(num_special), Some(rank));
+
+    assert_eq!(
+        TokenId::checked_from_rank(u32::from(b'A'), num_special)
+            .unwrap()
+            .get(),
+        tokenizer.byte_to_token_id(b'A')
+    );
+    assert!(TokenId::new(tokenizer.bos_id().unwrap()).is_special(num_special));
+}
+
+#[test]
+fn test_encode_as_integer_types() {
+    let tokenizer = get_tokenizer();
+    let text = "Hello, world!";
+    let ids = tokenizer.encode(text, true, true).unwrap();
+
+    let wide: Vec<i64> = tokenizer.encode_as(text, true, true).unwrap();
+    assert_eq!(
+        wide,
+        ids.iter().map(|&id| i64::from(id)).collect::<Vec<_>>()
+    );
+    let signed = tokenizer.encode_as::<i32>(text, true, true).unwrap();
+    assert_eq!(signed, ids.iter().map(|&id| id as i32).collect::<Vec<_>>());
+    assert_eq!(tokenizer.encode_as::<u32>(text, true, true).unwrap(), ids);
+
+    // Regular token IDs exceed u16::MAX in a 151k vocabulary
+    let large = tokenizer.vocab_size() as u32 - 1;
+    assert!(tekken::convert_ids::<u16>(vec![1, large]).is_err());
+    assert_eq!(
+        tekken::convert_ids::<usize>(vec![large]).unwrap(),
+        vec![large as usize]
+    );
+}
@@ -0,0 +1,65 @@
+use std::sync::OnceLock;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::{MatchMode, Tekkenizer};
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+fn piece(tokenizer: &Tekkenizer, id: u32) -> Vec<u8> {
+    tokenizer
+        .id_to_byte_piece(id, SpecialTokenPolicy::Keep)
+        .unwrap()
+}
+
+#[test]
+fn test_exact_and_word_matches() {
+    let tokenizer = get_tokenizer();
+
+    let exact = tokenizer.token_ids_for("Hello", MatchMode::Exact);
+    assert_eq!(exact, vec![22177]);
+
+    let word = tokenizer.token_ids_for("Hello", MatchMode::Word);
+    assert!(word.contains(&22177));
+    let leading_space = tokenizer.encode(" Hello", false, false).unwrap();
+    assert_eq!(leading_space.len(), 1);
+    assert!(word.contains(&leading_space[0]));
+    assert!(word.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn test_prefix_and_contains_matches() {
+    let tokenizer = get_tokenizer();
+
+    let prefix = tokenizer.token_ids_for("Hel", MatchMode::StartsWith);
+    assert!(prefix.contains(&22177));
+    assert!(
+        prefix
+            .iter()
+            .all(|&id| piece(tokenizer, id).starts_with(b"Hel"))
+    );
+
+    let contains = tokenizer.token_ids_for("ello", MatchMode::Contains);
+    assert!(contains.contains(&22177));
+    assert!(
+        contains.len()
+            >= prefix
+                .iter()
+                .filter(|&&id| piece(tokenizer, id).starts_with(b"Hello"))
+                .count()
+    );
+
+    // Special tokens are never matched
+    assert!(tokenizer.token_ids_for("<s>", MatchMode::Exact).is_empty());
+    assert!(
+        tokenizer
+            .token_ids_for("", MatchMode::Contains)
+            .iter()
+            .all(|&id| !tokenizer.is_special_token(id))
+    );
+}
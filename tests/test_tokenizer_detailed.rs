@@ -54,6 +54,53 @@ fn test_byte_piece_functionality() {
     }
 }
 
+#[test]
+fn test_byte_token_values() {
+    let tokenizer = get_tokenizer();
+
+    for byte in 0..=u8::MAX {
+        let token_id = tokenizer.byte_to_token_id(byte);
+        assert!(tokenizer.is_byte(token_id));
+        assert_eq!(tokenizer.byte_token_value(token_id), Some(byte));
+    }
+
+    // "Hello" is a single merged token, not a byte token
+    assert_eq!(tokenizer.byte_token_value(22177), None);
+    assert_eq!(
+        tokenizer.byte_token_value(tokenizer.bos_id().unwrap()),
+        None
+    );
+    assert_eq!(tokenizer.byte_token_value(u32::MAX), None);
+}
+
+#[test]
+fn test_byte_piece_is_exact_for_non_utf8_tokens() {
+    let tokenizer = get_tokenizer();
+
+    // Lone continuation and lead bytes are not valid UTF-8 on their own
+    for byte in [0x80, 0xE2, 0xF0, 0xFF] {
+        let token_id = tokenizer.byte_to_token_id(byte);
+        let piece = tokenizer
+            .id_to_byte_piece(token_id, SpecialTokenPolicy::Keep)
+            .unwrap();
+        assert_eq!(piece, vec![byte]);
+    }
+
+    // Concatenated byte pieces reproduce the exact UTF-8 input
+    for text in ["🚀", "café", "日本語のテキスト"] {
+        let tokens = tokenizer.encode(text, false, false).unwrap();
+        let bytes: Vec<u8> = tokens
+            .iter()
+            .flat_map(|&id| {
+                tokenizer
+                    .id_to_byte_piece(id, SpecialTokenPolicy::Keep)
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(bytes, text.as_bytes());
+    }
+}
+
 #[test]
 fn test_special_token_handling() {
     let tokenizer = get_tokenizer();
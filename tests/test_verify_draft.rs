@@ -0,0 +1,103 @@
+use std::sync::OnceLock;
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+static TOKENIZER: OnceLock<Tekkenizer> = OnceLock::new();
+
+fn get_tokenizer() -> &'static Tekkenizer {
+    TOKENIZER.get_or_init(|| {
+        Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file")
+    })
+}
+
+fn byte_len(tokenizer: &Tekkenizer, tokens: &[u32]) -> usize {
+    tokens
+        .iter()
+        .map(|&token| {
+            tokenizer
+                .id_to_byte_piece(token, SpecialTokenPolicy::Raise)
+                .unwrap()
+                .len()
+        })
+        .sum()
+}
+
+#[test]
+fn test_canonical_draft_is_fully_accepted() {
+    let tokenizer = get_tokenizer();
+    for text in [
+        "Hello, world!",
+        "fn main() {\n    println!(\"{}\", 42);\n}\n",
+        "東京は日本の首都です。",
+    ] {
+        let draft = tokenizer.encode(text, false, false).unwrap();
+        let verification = tokenizer.verify_draft(text, &draft).unwrap();
+        assert_eq!(verification.accepted, draft.len(), "{text:?}");
+        assert!(verification.covers(text));
+    }
+}
+
+#[test]
+fn test_divergence_stops_acceptance() {
+    let tokenizer = get_tokenizer();
+    let text = "The quick brown fox jumps over the lazy dog";
+    let canonical = tokenizer.encode(text, false, false).unwrap();
+    let other = tokenizer.encode(" cat", false, false).unwrap();
+
+    let mut draft = canonical[..3].to_vec();
+    draft.extend(&other);
+    let verification = tokenizer.verify_draft(text, &draft).unwrap();
+    assert_eq!(verification.accepted, 3);
+    assert_eq!(
+        verification.accepted_bytes,
+        byte_len(tokenizer, &canonical[..3])
+    );
+
+    // A non-canonical segmentation of the same bytes is rejected
+    let mut draft = canonical[..2].to_vec();
+    draft.extend(
+        text.as_bytes()[byte_len(tokenizer, &canonical[..2])..]
+            .iter()
+            .map(|&byte| tokenizer.byte_to_token_id(byte)),
+    );
+    assert_eq!(tokenizer.verify_draft(text, &draft).unwrap().accepted, 2);
+
+    assert_eq!(tokenizer.verify_draft(text, &[]).unwrap().accepted_bytes, 0);
+}
+
+#[test]
+fn test_tail_accepts_tokens_of_continuations() {
+    let tokenizer = get_tokenizer();
+    let text = "hello wor";
+    let canonical = tokenizer.encode(text, false, false).unwrap();
+    let stable = tokenizer.encode("hello", false, false).unwrap();
+    assert_eq!(
+        tokenizer.verify_draft(text, &canonical).unwrap().accepted,
+        canonical.len()
+    );
+
+    // " world" is how the tail encodes once the word is complete, but it runs
+    // past the text
+    let draft = tokenizer.encode("hello world", false, false).unwrap();
+    assert_eq!(draft.len(), stable.len() + 1);
+    let verification = tokenizer.verify_draft(text, &draft).unwrap();
+    assert_eq!(verification.accepted, stable.len());
+    assert_eq!(verification.accepted_bytes, "hello".len());
+    assert!(!verification.covers(text));
+}
+
+#[test]
+fn test_draft_past_the_text() {
+    let tokenizer = get_tokenizer();
+    let text = "The weather is nice";
+    let draft = tokenizer
+        .encode("The weather is nice today.", false, false)
+        .unwrap();
+    let verification = tokenizer.verify_draft(text, &draft).unwrap();
+    assert_eq!(
+        verification.accepted,
+        tokenizer.encode(text, false, false).unwrap().len()
+    );
+    assert!(verification.covers(text));
+}
@@ -0,0 +1,94 @@
+use std::sync::OnceLock;
+use tekken::config::{ModelData, TokenizerVersion, detect_version};
+use tekken::special_tokens::SpecialTokenInfo;
+use tekken::tekkenizer::Tekkenizer;
+
+static MODEL_DATA: OnceLock<ModelData> = OnceLock::new();
+
+/// Model data of a small copy of the test tokenizer.
+fn get_model_data() -> &'static ModelData {
+    MODEL_DATA.get_or_init(|| {
+        let tokenizer = Tekkenizer::from_file("tests/assets/tekken.json")
+            .expect("Failed to load tokenizer from file");
+        tokenizer
+            .pruned(tokenizer.num_special_tokens() + 2048)
+            .expect("Failed to prune tokenizer")
+            .to_model_data()
+    })
+}
+
+fn load(model_data: &ModelData) -> Tekkenizer {
+    Tekkenizer::from_bytes(&serde_json::to_vec(model_data).unwrap()).unwrap()
+}
+
+fn rename_special_token(model_data: &mut ModelData, from: &str, to: &str) {
+    let special_tokens = model_data.special_tokens.as_mut().unwrap();
+    let token: &mut SpecialTokenInfo = special_tokens
+        .iter_mut()
+        .find(|token| token.token_str == from)
+        .unwrap();
+    token.token_str = to.to_string();
+}
+
+#[test]
+fn test_explicit_version_wins() {
+    let mut model_data = get_model_data().clone();
+    model_data.config.version = "v11".to_string();
+    assert_eq!(detect_version(&model_data), TokenizerVersion::V11);
+    assert_eq!(*load(&model_data).version(), TokenizerVersion::V11);
+}
+
+#[test]
+fn test_missing_or_unknown_version_is_inferred() {
+    let mut model_data = get_model_data().clone();
+    model_data.config.version = "v99".to_string();
+    assert_eq!(detect_version(&model_data), TokenizerVersion::V7);
+    assert_eq!(*load(&model_data).version(), TokenizerVersion::V7);
+
+    let mut json: serde_json::Value = serde_json::to_value(get_model_data()).unwrap();
+    json["config"].as_object_mut().unwrap().remove("version");
+    let tokenizer = Tekkenizer::from_bytes(&serde_json::to_vec(&json).unwrap()).unwrap();
+    assert_eq!(*tokenizer.version(), TokenizerVersion::V7);
+}
+
+#[test]
+fn test_inference_from_special_tokens() {
+    let mut model_data = get_model_data().clone();
+    model_data.config.version = String::new();
+
+    let mut v11 = model_data.clone();
+    rename_special_token(&mut v11, "<SPECIAL_32>", "[ARGS]");
+    assert_eq!(detect_version(&v11), TokenizerVersion::V11);
+
+    let mut v13 = v11.clone();
+    rename_special_token(&mut v13, "<SPECIAL_35>", "[THINK]");
+    assert_eq!(detect_version(&v13), TokenizerVersion::V13);
+
+    let mut v3 = model_data.clone();
+    v3.special_tokens = None;
+    v3.audio = None;
+    assert_eq!(detect_version(&v3), TokenizerVersion::V3);
+}
+
+#[test]
+fn test_default_special_tokens_per_version() {
+    let cases = [
+        (TokenizerVersion::V3, "[SYSTEM_PROMPT]", 17, "<SPECIAL_24>"),
+        (TokenizerVersion::V7, "[TRANSCRIBE]", 34, "[AUDIO]"),
+        (TokenizerVersion::V11, "[CALL_ID]", 33, "[AUDIO]"),
+        (TokenizerVersion::V13, "[/THINK]", 35, "[AUDIO]"),
+    ];
+    for (version, token_str, id, token_24) in cases {
+        let mut model_data = get_model_data().clone();
+        model_data.config.version = version.as_str().to_string();
+        model_data.special_tokens = None;
+        if version == TokenizerVersion::V3 {
+            model_data.audio = None;
+        }
+
+        let tokenizer = load(&model_data);
+        assert_eq!(*tokenizer.version(), version);
+        assert_eq!(tokenizer.get_control_token(token_str).unwrap(), id);
+        assert_eq!(tokenizer.id_to_piece(24).unwrap(), token_24);
+    }
+}
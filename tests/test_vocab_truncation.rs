@@ -0,0 +1,95 @@
+//! Loading with a `vocab_size` below the length of the vocabulary.
+//!
+//! The expected outcomes follow `Tekkenizer.__init__` and
+//! `_reload_mergeable_ranks` of mistral-common: the first
+//! `vocab_size - num_special_tokens` entries are kept, each must have the rank
+//! of its position, and the entries past the cutoff are never read.
+
+mod common;
+
+use tekken::special_tokens::SpecialTokenPolicy;
+use tekken::tekkenizer::Tekkenizer;
+
+/// Number of special tokens of [`common::model_data`].
+const NUM_SPECIAL_TOKENS: usize = 100;
+
+/// Byte tokens at ranks 0..256, then "he", "ll", "hell", "hello" at 256..260.
+fn model_json() -> serde_json::Value {
+    let model_data = common::model_data(["he", "ll", "hell", "hello"]);
+    serde_json::to_value(model_data).unwrap()
+}
+
+fn load(json: &serde_json::Value, vocab_size: usize) -> tekken::errors::Result<Tekkenizer> {
+    let mut json = json.clone();
+    json["config"]["default_vocab_size"] = vocab_size.into();
+    Tekkenizer::from_bytes(&serde_json::to_vec(&json).unwrap())
+}
+
+#[test]
+fn test_truncation_keeps_leading_ranks() {
+    let json = model_json();
+    // Keep the byte tokens, "he" and "ll", drop "hell" and "hello"
+    let tokenizer = load(&json, NUM_SPECIAL_TOKENS + 258).unwrap();
+    assert_eq!(tokenizer.vocab_size(), NUM_SPECIAL_TOKENS + 258);
+    assert_eq!(tokenizer.num_special_tokens(), NUM_SPECIAL_TOKENS);
+
+    let he = (NUM_SPECIAL_TOKENS + 256) as u32;
+    let ll = he + 1;
+    assert_eq!(tokenizer.id_to_piece(he).unwrap(), "he");
+    assert_eq!(tokenizer.id_to_piece(ll).unwrap(), "ll");
+    assert!(tokenizer.id_to_piece(ll + 1).is_err());
+
+    // "hello" falls back to the kept merges
+    let o = NUM_SPECIAL_TOKENS as u32 + u32::from(b'o');
+    let tokens = tokenizer.encode("hello", false, false).unwrap();
+    assert_eq!(tokens, [he, ll, o]);
+    assert_eq!(
+        tokenizer
+            .decode(&tokens, SpecialTokenPolicy::Raise)
+            .unwrap(),
+        "hello"
+    );
+}
+
+#[test]
+fn test_truncation_ignores_entries_past_the_cutoff() {
+    let mut json = model_json();
+    // Corrupt "hello", past a cutoff after "he"
+    json["vocab"][259]["token_bytes"] = "not base64!".into();
+    json["vocab"][259]["rank"] = 7.into();
+
+    assert!(load(&json, NUM_SPECIAL_TOKENS + 260).is_err());
+    let tokenizer = load(&json, NUM_SPECIAL_TOKENS + 257).unwrap();
+    assert_eq!(tokenizer.vocab_size(), NUM_SPECIAL_TOKENS + 257);
+}
+
+#[test]
+fn test_truncation_requires_rank_order() {
+    let mut json = model_json();
+    // Swap "hell" and "hello" in the file, keeping their ranks
+    let vocab = json["vocab"].as_array_mut().unwrap();
+    vocab.swap(258, 259);
+
+    // The cutoff before the swap keeps a valid prefix
+    assert!(load(&json, NUM_SPECIAL_TOKENS + 258).is_ok());
+
+    // Keeping one of the two would keep rank 259 in place of rank 258
+    let error = load(&json, NUM_SPECIAL_TOKENS + 259).err().unwrap();
+    assert!(error.to_string().contains("missing ranks 258"), "{error}");
+
+    // Both ranks are kept, but still out of order
+    let error = load(&json, NUM_SPECIAL_TOKENS + 260).err().unwrap();
+    assert!(error.to_string().contains("rank order"), "{error}");
+}
+
+#[test]
+fn test_truncation_bounds() {
+    let json = model_json();
+    // The special tokens plus every byte token is the smallest vocabulary
+    let tokenizer = load(&json, NUM_SPECIAL_TOKENS + 256).unwrap();
+    assert_eq!(tokenizer.vocab_size(), NUM_SPECIAL_TOKENS + 256);
+
+    // vocab_size may not exceed the vocabulary plus the special tokens
+    let error = load(&json, NUM_SPECIAL_TOKENS + 261).err().unwrap();
+    assert!(error.to_string().contains("vocab_size"), "{error}");
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tekken::integrity::write_checksum;
+use tekken::tekkenizer::Tekkenizer;
+use tekken::watch::WatchedTekkenizer;
+
+fn model_json(tokens: &[&str]) -> Vec<u8> {
+    let model_data = common::model_data(tokens);
+    serde_json::to_vec(&model_data).unwrap()
+}
+
+/// Replaces a file the way deployments should: write, then rename into place.
+fn replace(path: &Path, contents: &[u8]) {
+    let staging = path.with_extension("staging");
+    std::fs::write(&staging, contents).unwrap();
+    std::fs::rename(&staging, path).unwrap();
+}
+
+fn write_model(dir: &Path, tokens: &[&str]) -> PathBuf {
+    let path = dir.join("tekken.json");
+    std::fs::write(&path, model_json(tokens)).unwrap();
+    path
+}
+
+fn wait_for(condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    condition()
+}
+
+#[test]
+fn test_reloads_replaced_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_model(dir.path(), &["he", "ll"]);
+    let watched = WatchedTekkenizer::new(&path).unwrap();
+    assert_eq!(watched.path(), path);
+    assert_eq!(watched.reloads(), 0);
+
+    let before = watched.load_full();
+    assert_eq!(before.encode("hello", false, false).unwrap().len(), 3);
+
+    replace(&path, &model_json(&["he", "ll", "hell", "hello"]));
+    assert!(wait_for(|| watched.reloads() == 1), "{watched:?}");
+
+    let after = watched.load();
+    assert_eq!(after.encode("hello", false, false).unwrap().len(), 1);
+    assert_eq!(
+        after.fingerprint(),
+        Tekkenizer::from_file(&path).unwrap().fingerprint()
+    );
+    // Holders of the previous tokenizer keep it
+    assert_eq!(before.encode("hello", false, false).unwrap().len(), 3);
+}
+
+#[test]
+fn test_failed_reload_keeps_current_tokenizer() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_model(dir.path(), &["he", "ll"]);
+    let outcomes = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&outcomes);
+    let watched = WatchedTekkenizer::new(&path)
+        .unwrap()
+        .with_on_reload(move |result| {
+            recorded.lock().unwrap().push(result.is_ok());
+        });
+    let fingerprint = watched.load().fingerprint();
+
+    replace(&path, b"{ not a tokenizer");
+    assert!(wait_for(|| outcomes.lock().unwrap().contains(&false)));
+    assert_eq!(watched.reloads(), 0);
+    assert_eq!(watched.load().fingerprint(), fingerprint);
+    assert!(watched.reload().is_err());
+
+    replace(&path, &model_json(&["he", "ll", "hell"]));
+    assert!(wait_for(|| outcomes.lock().unwrap().contains(&true)));
+    assert_eq!(watched.reloads(), 1);
+    assert_ne!(watched.load().fingerprint(), fingerprint);
+}
+
+#[test]
+fn test_reload_checks_sidecar() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_model(dir.path(), &["he", "ll"]);
+    write_checksum(&path).unwrap();
+    let watched = WatchedTekkenizer::new(&path).unwrap();
+
+    // The new file does not match the old sidecar until it is updated
+    let contents = model_json(&["he", "ll", "hell"]);
+    std::fs::write(dir.path().join("next.json"), &contents).unwrap();
+    write_checksum(dir.path().join("next.json")).unwrap();
+    replace(&path, &contents);
+    assert!(watched.reload().is_err());
+    assert_eq!(watched.reloads(), 0);
+
+    std::fs::rename(
+        dir.path().join("next.json.sha256"),
+        dir.path().join("tekken.json.sha256"),
+    )
+    .unwrap();
+    assert!(wait_for(|| watched.reloads() == 1), "{watched:?}");
+}
+
+#[test]
+fn test_manual_reload_and_shared_handle() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_model(dir.path(), &["he", "ll"]);
+    let watched = WatchedTekkenizer::new(&path).unwrap();
+    let handle = watched.handle();
+
+    // Reloading an unchanged file leaves the tokenizer in place
+    assert!(!watched.reload().unwrap());
+    assert_eq!(watched.reloads(), 0);
+
+    std::fs::write(&path, model_json(&["he", "ll", "hell"])).unwrap();
+    // Either the watcher or this call swaps the new tokenizer in, not both
+    watched.reload().unwrap();
+    assert_eq!(watched.reloads(), 1);
+    assert_eq!(handle.load().fingerprint(), watched.load().fingerprint());
+    assert_eq!(handle.load().encode("hell", false, false).unwrap().len(), 1);
+}
+
+#[test]
+fn test_missing_file() {
+    let dir = tempfile::tempdir().unwrap();
+    assert!(WatchedTekkenizer::new(dir.path().join("missing.json")).is_err());
+}